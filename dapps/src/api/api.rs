@@ -19,6 +19,7 @@ use unicase::UniCase;
 use hyper::{server, net, Decoder, Encoder, Next, Control};
 use hyper::header;
 use hyper::method::Method;
+use hyper::status::StatusCode;
 
 use api::types::{App, ApiError};
 use api::response;
@@ -26,6 +27,8 @@ use apps::fetcher::Fetcher;
 
 use handlers::extract_url;
 use endpoint::{Endpoint, Endpoints, Handler, EndpointPath};
+use ethcore_rpc::node_health::NodeHealth;
+use ethcore_rpc::v1::types::HealthStatus;
 use jsonrpc_http_server;
 use jsonrpc_server_utils::cors;
 
@@ -34,14 +37,21 @@ pub struct RestApi {
 	cors_domains: Option<Vec<cors::AccessControlAllowOrigin>>,
 	endpoints: Arc<Endpoints>,
 	fetcher: Arc<Fetcher>,
+	health: Option<Arc<NodeHealth>>,
 }
 
 impl RestApi {
-	pub fn new(cors_domains: Vec<cors::AccessControlAllowOrigin>, endpoints: Arc<Endpoints>, fetcher: Arc<Fetcher>) -> Box<Endpoint> {
+	pub fn new(
+		cors_domains: Vec<cors::AccessControlAllowOrigin>,
+		endpoints: Arc<Endpoints>,
+		fetcher: Arc<Fetcher>,
+		health: Option<Arc<NodeHealth>>,
+	) -> Box<Endpoint> {
 		Box::new(RestApi {
 			cors_domains: Some(cors_domains),
 			endpoints: endpoints,
 			fetcher: fetcher,
+			health: health,
 		})
 	}
 
@@ -50,6 +60,18 @@ impl RestApi {
 			e.info().map(|ref info| App::from_info(k, info))
 		}).collect()
 	}
+
+	/// Health report; responds with `503` when the node should not be used.
+	fn health(&self) -> Option<Box<Handler>> {
+		self.health.as_ref().map(|health| {
+			let health = health.health();
+			let code = match health.status {
+				HealthStatus::Bad => StatusCode::ServiceUnavailable,
+				_ => StatusCode::Ok,
+			};
+			response::as_json_with_status(code, &health)
+		})
+	}
 }
 
 impl Endpoint for RestApi {
@@ -144,6 +166,7 @@ impl server::Handler<net::HttpStream> for RestApiRouter {
 		let handler = endpoint.and_then(|v| match v {
 			"apps" => Some(response::as_json(&self.api.list_apps())),
 			"ping" => Some(response::ping()),
+			"health" => self.api.health(),
 			"content" => self.resolve_content(hash, path, control),
 			_ => None
 		});
@@ -16,6 +16,7 @@
 
 use serde::Serialize;
 use serde_json;
+use hyper::status::StatusCode;
 use endpoint::Handler;
 use handlers::{ContentHandler, EchoHandler};
 
@@ -29,6 +30,12 @@ pub fn as_json<T: Serialize>(val: &T) -> Box<Handler> {
 	Box::new(ContentHandler::ok(json, mime!(Application/Json)))
 }
 
+pub fn as_json_with_status<T: Serialize>(code: StatusCode, val: &T) -> Box<Handler> {
+	let json = serde_json::to_string(val)
+		.expect("serialization to string is infallible; qed");
+	Box::new(ContentHandler::new(code, json, mime!(Application/Json)))
+}
+
 pub fn as_json_error<T: Serialize>(val: &T) -> Box<Handler> {
 	let json = serde_json::to_string(val)
 		.expect("serialization to string is infallible; qed");
@@ -80,6 +80,7 @@ pub use jsonrpc_http_server::{DomainsValidation, Host, AccessControlAllowOrigin}
 pub use jsonrpc_http_server::hyper;
 
 use ethcore_rpc::Metadata;
+use ethcore_rpc::node_health::NodeHealth;
 use fetch::{Fetch, Client as FetchClient};
 use hash_fetch::urlhint::ContractClient;
 use parity_reactor::Remote;
@@ -113,6 +114,7 @@ pub struct ServerBuilder<T: Fetch = FetchClient> {
 	extra_dapps: Vec<PathBuf>,
 	registrar: Arc<ContractClient>,
 	sync_status: Arc<SyncStatus>,
+	node_health: Option<Arc<NodeHealth>>,
 	web_proxy_tokens: Arc<WebProxyTokens>,
 	signer_address: Option<(String, u16)>,
 	allowed_hosts: Option<Vec<Host>>,
@@ -129,6 +131,7 @@ impl ServerBuilder {
 			extra_dapps: vec![],
 			registrar: registrar,
 			sync_status: Arc::new(|| false),
+			node_health: None,
 			web_proxy_tokens: Arc::new(|_| false),
 			signer_address: None,
 			allowed_hosts: Some(vec![]),
@@ -147,6 +150,7 @@ impl<T: Fetch> ServerBuilder<T> {
 			extra_dapps: vec![],
 			registrar: self.registrar,
 			sync_status: self.sync_status,
+			node_health: self.node_health,
 			web_proxy_tokens: self.web_proxy_tokens,
 			signer_address: self.signer_address,
 			allowed_hosts: self.allowed_hosts,
@@ -162,6 +166,12 @@ impl<T: Fetch> ServerBuilder<T> {
 		self
 	}
 
+	/// Serve node health reports under `/api/health`.
+	pub fn node_health(mut self, health: Arc<NodeHealth>) -> Self {
+		self.node_health = Some(health);
+		self
+	}
+
 	/// Change default web proxy tokens validator.
 	pub fn web_proxy_tokens(mut self, tokens: Arc<WebProxyTokens>) -> Self {
 		self.web_proxy_tokens = tokens;
@@ -210,6 +220,7 @@ impl<T: Fetch> ServerBuilder<T> {
 			self.signer_address,
 			self.registrar,
 			self.sync_status,
+			self.node_health,
 			self.web_proxy_tokens,
 			self.remote,
 			tokio_remote,
@@ -232,6 +243,7 @@ impl<T: Fetch> ServerBuilder<T> {
 			self.signer_address,
 			self.registrar,
 			self.sync_status,
+			self.node_health,
 			self.web_proxy_tokens,
 			self.remote,
 			tokio_remote,
@@ -302,6 +314,7 @@ impl Server {
 		signer_address: Option<(String, u16)>,
 		registrar: Arc<ContractClient>,
 		sync_status: Arc<SyncStatus>,
+		node_health: Option<Arc<NodeHealth>>,
 		web_proxy_tokens: Arc<WebProxyTokens>,
 		remote: Remote,
 		tokio_remote: TokioRemote,
@@ -331,7 +344,7 @@ impl Server {
 			special.insert(router::SpecialEndpoint::Utils, apps::utils());
 			special.insert(
 				router::SpecialEndpoint::Api,
-				api::RestApi::new(cors_domains, endpoints.clone(), content_fetcher.clone())
+				api::RestApi::new(cors_domains, endpoints.clone(), content_fetcher.clone(), node_health)
 			);
 			special
 		});
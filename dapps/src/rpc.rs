@@ -92,6 +92,9 @@ impl HttpMetaExtractor<Metadata> for MetadataExtractor {
 			});
 		Metadata {
 			origin: Origin::Dapps(dapp_id.map(Into::into).unwrap_or_default()),
+			session: None,
+			api_key: None,
+			ip: request.transport().0.peer_addr().ok().map(|addr| addr.ip()),
 		}
 	}
 }
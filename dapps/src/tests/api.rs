@@ -85,6 +85,26 @@ fn should_handle_ping() {
 	assert_security_headers(&response.headers);
 }
 
+#[test]
+fn should_not_serve_health_when_not_configured() {
+	// given
+	let server = serve();
+
+	// when
+	let response = request(server,
+		"\
+			GET /api/health HTTP/1.1\r\n\
+			Host: home.parity\r\n\
+			Connection: close\r\n\
+			\r\n\
+		"
+	);
+
+	// then
+	response.assert_status("HTTP/1.1 404 Not Found");
+	response.assert_header("Content-Type", "application/json");
+}
+
 
 #[test]
 fn should_try_to_resolve_dapp() {
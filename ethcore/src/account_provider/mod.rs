@@ -17,13 +17,14 @@
 //! Account management.
 
 mod stores;
+mod signing_log;
 
-use self::stores::{AddressBook, DappsSettingsStore, NewDappsPolicy};
+use self::stores::{AddressBook, DappsSettingsStore, NewDappsPolicy, VaultPolicyStore};
 
 use std::fmt;
 use std::collections::{HashMap, HashSet};
 use std::time::{Instant, Duration};
-use util::{RwLock};
+use util::{H256, RwLock};
 use ethstore::{
 	SimpleSecretStore, SecretStore, Error as SSError, EthStore, EthMultiStore,
 	random_string, SecretVaultRef, StoreAccountRef,
@@ -34,6 +35,8 @@ use ethjson::misc::AccountMeta;
 use hardware_wallet::{Error as HardwareError, HardwareWalletManager, KeyPath};
 pub use ethstore::ethkey::Signature;
 pub use ethstore::{Derivation, IndexDerivation, KeyFile};
+pub use self::stores::VaultPolicy;
+pub use self::signing_log::{SigningLog, SigningLogEntry, SigningDecision};
 
 /// Type of unlock.
 #[derive(Clone)]
@@ -54,6 +57,14 @@ struct AccountData {
 	password: String,
 }
 
+/// Account unlocked with a session token.
+struct Session {
+	account: StoreAccountRef,
+	password: String,
+	expires: Instant,
+	remaining_uses: Option<u32>,
+}
+
 /// Signing error
 #[derive(Debug)]
 pub enum SignError {
@@ -117,14 +128,19 @@ type AccountToken = String;
 /// Responsible for unlocking accounts.
 pub struct AccountProvider {
 	unlocked: RwLock<HashMap<StoreAccountRef, AccountData>>,
+	/// Accounts unlocked for sessions, by session token
+	sessions: RwLock<HashMap<AccountToken, Session>>,
 	address_book: RwLock<AddressBook>,
 	dapps_settings: RwLock<DappsSettingsStore>,
+	vault_policies: RwLock<VaultPolicyStore>,
 	/// Accounts on disk
 	sstore: Box<SecretStore>,
 	/// Accounts unlocked with rolling tokens
 	transient_sstore: EthMultiStore,
 	/// Accounts in hardware wallets.
 	hardware_store: Option<HardwareWalletManager>,
+	/// Log of signing operations
+	signing_log: RwLock<Option<SigningLog>>,
 }
 
 /// Account management settings.
@@ -159,11 +175,14 @@ impl AccountProvider {
 		}
 		AccountProvider {
 			unlocked: RwLock::new(HashMap::new()),
+			sessions: RwLock::new(HashMap::new()),
 			address_book: RwLock::new(AddressBook::new(&sstore.local_path())),
 			dapps_settings: RwLock::new(DappsSettingsStore::new(&sstore.local_path())),
+			vault_policies: RwLock::new(VaultPolicyStore::new(&sstore.local_path())),
 			sstore: sstore,
 			transient_sstore: transient_sstore(),
 			hardware_store: hardware_store,
+			signing_log: RwLock::new(None),
 		}
 	}
 
@@ -171,11 +190,14 @@ impl AccountProvider {
 	pub fn transient_provider() -> Self {
 		AccountProvider {
 			unlocked: RwLock::new(HashMap::new()),
+			sessions: RwLock::new(HashMap::new()),
 			address_book: RwLock::new(AddressBook::transient()),
 			dapps_settings: RwLock::new(DappsSettingsStore::transient()),
+			vault_policies: RwLock::new(VaultPolicyStore::transient()),
 			sstore: Box::new(EthStore::open(Box::new(MemoryDirectory::default())).expect("MemoryDirectory load always succeeds; qed")),
 			transient_sstore: transient_sstore(),
 			hardware_store: None,
+			signing_log: RwLock::new(None),
 		}
 	}
 
@@ -561,6 +583,56 @@ impl AccountProvider {
 		self.unlock_account(account, password, Unlock::Timed(Instant::now() + Duration::from_millis(duration_ms as u64)))
 	}
 
+	/// Unlocks account for a session limited by `ttl` and optionally by a number of uses.
+	/// Returns a token which has to be provided instead of the password to sign with the account.
+	pub fn unlock_account_with_token(&self, address: Address, password: String, ttl: Duration, max_uses: Option<u32>) -> Result<AccountToken, Error> {
+		// verify password by signing dump message
+		let account = self.sstore.account_ref(&address)?;
+		let _ = self.sstore.sign(&account, &password, &Default::default())?;
+
+		let token = random_string(32);
+		self.sessions.write().insert(token.clone(), Session {
+			account: account,
+			password: password,
+			expires: Instant::now() + ttl,
+			remaining_uses: max_uses,
+		});
+		Ok(token)
+	}
+
+	/// Returns password of the account unlocked with given session token. Consumes one use of the session.
+	pub fn session_password(&self, address: &Address, token: &str) -> Result<String, SignError> {
+		let account = self.sstore.account_ref(address)?;
+		let mut sessions = self.sessions.write();
+
+		let now = Instant::now();
+		let expired: Vec<_> = sessions.iter()
+			.filter(|&(_, session)| session.expires <= now)
+			.map(|(token, _)| token.clone())
+			.collect();
+		for token in expired {
+			sessions.remove(&token);
+		}
+
+		let (password, exhausted) = match sessions.get_mut(token) {
+			Some(session) => {
+				if session.account != account {
+					return Err(SignError::NotUnlocked);
+				}
+				if let Some(ref mut uses) = session.remaining_uses {
+					*uses = uses.saturating_sub(1);
+				}
+				(session.password.clone(), session.remaining_uses == Some(0))
+			},
+			None => return Err(SignError::NotUnlocked),
+		};
+
+		if exhausted {
+			sessions.remove(token);
+		}
+		Ok(password)
+	}
+
 	/// Checks if given account is unlocked
 	pub fn is_unlocked(&self, address: Address) -> bool {
 		let unlocked = self.unlocked.read();
@@ -685,9 +757,15 @@ impl AccountProvider {
 	}
 
 	/// Change vault of the given address.
+	/// Accounts can't be moved out of a vault with a signing policy, that would lift the policy.
 	pub fn change_vault(&self, address: Address, new_vault: &str) -> Result<(), Error> {
 		let new_vault_ref = if new_vault.is_empty() { SecretVaultRef::Root } else { SecretVaultRef::Vault(new_vault.to_owned()) };
 		let old_account_ref = self.sstore.account_ref(&address)?;
+		if let SecretVaultRef::Vault(ref name) = old_account_ref.vault {
+			if old_account_ref.vault != new_vault_ref && self.vault_policy(name).is_some() {
+				return Err(SSError::Custom(format!("Vault {} has a signing policy, remove it before moving accounts out.", name)));
+			}
+		}
 		self.sstore.change_account_vault(new_vault_ref, old_account_ref)
 			.map_err(Into::into)
 			.map(|_| ())
@@ -705,6 +783,65 @@ impl AccountProvider {
 			.map_err(Into::into)
 	}
 
+	/// Get signing policy of the vault.
+	pub fn vault_policy(&self, name: &str) -> Option<VaultPolicy> {
+		self.vault_policies.read().get(name)
+	}
+
+	/// Set (or remove with `None`) signing policy of the vault. Requires the vault password.
+	pub fn set_vault_policy(&self, name: &str, password: &str, policy: Option<VaultPolicy>) -> Result<(), Error> {
+		if !self.sstore.list_vaults()?.iter().any(|vault| vault == name) {
+			return Err(SSError::VaultNotFound);
+		}
+		if !self.sstore.test_vault_password(name, password)? {
+			return Err(SSError::InvalidPassword);
+		}
+		self.vault_policies.write().set(name.to_owned(), policy);
+		Ok(())
+	}
+
+	/// Get signing policy applying to given account, if it is stored in a vault with a policy.
+	pub fn account_policy(&self, address: &Address) -> Option<VaultPolicy> {
+		match self.sstore.account_ref(address) {
+			Ok(StoreAccountRef { vault: SecretVaultRef::Vault(ref name), .. }) => self.vault_policy(name),
+			_ => None,
+		}
+	}
+
+	/// Set the log signing operations are recorded into.
+	pub fn set_signing_log(&self, log: SigningLog) {
+		*self.signing_log.write() = Some(log);
+	}
+
+	/// Record the outcome of a signing request, if there is a signing log.
+	pub fn record_signing(&self, account: Address, origin: String, method: String, hash: Option<H256>, decision: SigningDecision) {
+		let guard = self.signing_log.read();
+		let log = match *guard {
+			Some(ref log) => log,
+			None => return,
+		};
+
+		let entry = SigningLogEntry {
+			account: account,
+			origin: origin,
+			method: method,
+			hash: hash,
+			decision: decision,
+			timestamp: ::time::get_time().sec as u64,
+		};
+		if let Err(e) = log.append(&entry) {
+			warn!(target: "signing_log", "Failed to record signing request of {:?}: {}", account, e);
+		}
+	}
+
+	/// Most recent signing operations, newest first.
+	pub fn signing_history(&self, count: usize) -> Vec<SigningLogEntry> {
+		match *self.signing_log.read() {
+			Some(ref log) => log.recent(count),
+			None => Vec::new(),
+		}
+	}
+
 	/// Sign transaction with hardware wallet.
 	pub fn sign_with_hardware(&self, address: Address, transaction: &[u8]) -> Result<Signature, SignError> {
 		match self.hardware_store.as_ref().map(|s| s.sign_transaction(&address, transaction)) {
@@ -718,7 +855,7 @@ impl AccountProvider {
 #[cfg(test)]
 mod tests {
 	use super::{AccountProvider, Unlock, DappId};
-	use std::time::Instant;
+	use std::time::{Instant, Duration};
 	use ethstore::ethkey::{Generator, Random};
 	use ethstore::{StoreAccountRef, Derivation};
 	use util::H256;
@@ -934,4 +1071,49 @@ mod tests {
 		assert_eq!(ap.new_dapps_default_address().unwrap(), address);
 		assert_eq!(ap.dapp_default_address("app1".into()).unwrap(), address);
 	}
+
+	#[test]
+	fn should_not_set_policy_for_unknown_vault() {
+		// given
+		let ap = AccountProvider::transient_provider();
+		let address = ap.new_account("test").unwrap();
+
+		// when
+		let res = ap.set_vault_policy("hot", "password", Some(Default::default()));
+
+		// then
+		assert!(res.is_err());
+		assert_eq!(ap.vault_policy("hot"), None);
+		assert_eq!(ap.account_policy(&address), None);
+	}
+
+	#[test]
+	fn session_token_should_expire_after_uses() {
+		let kp = Random.generate().unwrap();
+		let ap = AccountProvider::transient_provider();
+		assert!(ap.insert_account(kp.secret().clone(), "test").is_ok());
+		assert!(ap.unlock_account_with_token(kp.address(), "test1".into(), Duration::from_secs(60), Some(2)).is_err());
+
+		let token = ap.unlock_account_with_token(kp.address(), "test".into(), Duration::from_secs(60), Some(2)).unwrap();
+		assert!(ap.session_password(&kp.address(), "invalid").is_err());
+		assert_eq!(ap.session_password(&kp.address(), &token).unwrap(), "test".to_owned());
+		assert_eq!(ap.session_password(&kp.address(), &token).unwrap(), "test".to_owned());
+		assert!(ap.session_password(&kp.address(), &token).is_err());
+		// the account itself is not unlocked
+		assert!(ap.sign(kp.address(), None, Default::default()).is_err());
+	}
+
+	#[test]
+	fn session_token_should_expire_after_ttl() {
+		let kp = Random.generate().unwrap();
+		let ap = AccountProvider::transient_provider();
+		assert!(ap.insert_account(kp.secret().clone(), "test").is_ok());
+		let other = ap.new_account("other").unwrap();
+
+		let token = ap.unlock_account_with_token(kp.address(), "test".into(), Duration::from_millis(100), None).unwrap();
+		assert!(ap.session_password(&other, &token).is_err());
+		assert!(ap.session_password(&kp.address(), &token).is_ok());
+		::std::thread::sleep(Duration::from_millis(150));
+		assert!(ap.session_password(&kp.address(), &token).is_err());
+	}
 }
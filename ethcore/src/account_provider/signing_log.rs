@@ -0,0 +1,199 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Append-only log of signing operations.
+
+use std::sync::Arc;
+use util::{Address, H256, Mutex, DBTransaction, KeyValueDB};
+use rlp::{self, Encodable, Decodable, RlpStream, UntrustedRlp, DecoderError};
+
+/// Outcome of a signing request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SigningDecision {
+	/// Request was signed.
+	Signed,
+	/// Request was rejected by the user or by a signing policy.
+	Rejected,
+	/// Request was confirmed, but signing failed.
+	Failed,
+}
+
+impl Encodable for SigningDecision {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		let value: u8 = match *self {
+			SigningDecision::Signed => 0,
+			SigningDecision::Rejected => 1,
+			SigningDecision::Failed => 2,
+		};
+		s.append(&value);
+	}
+}
+
+impl Decodable for SigningDecision {
+	fn decode(rlp: &UntrustedRlp) -> Result<Self, DecoderError> {
+		match rlp.as_val::<u8>()? {
+			0 => Ok(SigningDecision::Signed),
+			1 => Ok(SigningDecision::Rejected),
+			2 => Ok(SigningDecision::Failed),
+			_ => Err(DecoderError::Custom("Unknown signing decision.")),
+		}
+	}
+}
+
+/// Single signing operation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SigningLogEntry {
+	/// Account which was requested to sign.
+	pub account: Address,
+	/// Origin of the request.
+	pub origin: String,
+	/// Name of the RPC method.
+	pub method: String,
+	/// Hash of the signed transaction or message, if known.
+	pub hash: Option<H256>,
+	/// Outcome of the request.
+	pub decision: SigningDecision,
+	/// UNIX timestamp of the decision.
+	pub timestamp: u64,
+}
+
+impl Encodable for SigningLogEntry {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(6)
+			.append(&self.account)
+			.append(&self.origin)
+			.append(&self.method)
+			.append(&self.hash)
+			.append(&self.decision)
+			.append(&self.timestamp);
+	}
+}
+
+impl Decodable for SigningLogEntry {
+	fn decode(rlp: &UntrustedRlp) -> Result<Self, DecoderError> {
+		Ok(SigningLogEntry {
+			account: rlp.val_at(0)?,
+			origin: rlp.val_at(1)?,
+			method: rlp.val_at(2)?,
+			hash: rlp.val_at(3)?,
+			decision: rlp.val_at(4)?,
+			timestamp: rlp.val_at(5)?,
+		})
+	}
+}
+
+// entries are keyed by their big-endian index, so they are stored in insertion order.
+fn entry_key(index: u64) -> [u8; 8] {
+	let mut key = [0u8; 8];
+	for i in 0..8 {
+		key[i] = (index >> (56 - i * 8)) as u8;
+	}
+	key
+}
+
+fn entry_index(key: &[u8]) -> u64 {
+	key.iter().fold(0, |acc, b| (acc << 8) | *b as u64)
+}
+
+/// Signing log stored in a dedicated database or column.
+pub struct SigningLog {
+	db: Arc<KeyValueDB>,
+	col: Option<u32>,
+	next_index: Mutex<u64>,
+}
+
+impl SigningLog {
+	/// Open the signing log stored in the given column.
+	pub fn new(db: Arc<KeyValueDB>, col: Option<u32>) -> Self {
+		let next_index = db.iter_range_rev(col, &entry_key(0), &entry_key(u64::max_value()))
+			.next()
+			.map_or(0, |(key, _)| entry_index(&key) + 1);
+
+		SigningLog {
+			db: db,
+			col: col,
+			next_index: Mutex::new(next_index),
+		}
+	}
+
+	/// Append an entry to the log.
+	pub fn append(&self, entry: &SigningLogEntry) -> Result<(), String> {
+		let mut next_index = self.next_index.lock();
+		let mut batch = DBTransaction::new();
+		batch.put_vec(self.col, &entry_key(*next_index), rlp::encode(entry).to_vec());
+		self.db.write(batch)?;
+		*next_index += 1;
+		Ok(())
+	}
+
+	/// Most recent entries of the log, newest first.
+	pub fn recent(&self, count: usize) -> Vec<SigningLogEntry> {
+		let end = entry_key(*self.next_index.lock());
+		self.db.iter_range_rev(self.col, &entry_key(0), &end)
+			.filter_map(|(key, value)| match UntrustedRlp::new(&value).as_val() {
+				Ok(entry) => Some(entry),
+				Err(e) => {
+					warn!(target: "signing_log", "Corrupted signing log entry #{}: {:?}", entry_index(&key), e);
+					None
+				}
+			})
+			.take(count)
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use util::kvdb::in_memory;
+	use super::{SigningLog, SigningLogEntry, SigningDecision};
+
+	fn entry(decision: SigningDecision, timestamp: u64) -> SigningLogEntry {
+		SigningLogEntry {
+			account: 5.into(),
+			origin: "RPC (service: http://parity.io)".into(),
+			method: "eth_sendTransaction".into(),
+			hash: Some(10.into()),
+			decision: decision,
+			timestamp: timestamp,
+		}
+	}
+
+	#[test]
+	fn should_return_recent_entries_newest_first() {
+		let log = SigningLog::new(Arc::new(in_memory(1)), Some(0));
+		log.append(&entry(SigningDecision::Signed, 1)).unwrap();
+		log.append(&entry(SigningDecision::Rejected, 2)).unwrap();
+		log.append(&entry(SigningDecision::Failed, 3)).unwrap();
+
+		assert_eq!(log.recent(2), vec![entry(SigningDecision::Failed, 3), entry(SigningDecision::Rejected, 2)]);
+		assert_eq!(log.recent(10).len(), 3);
+	}
+
+	#[test]
+	fn should_continue_log_after_reopening() {
+		let db = Arc::new(in_memory(1));
+		{
+			let log = SigningLog::new(db.clone(), Some(0));
+			log.append(&entry(SigningDecision::Signed, 1)).unwrap();
+		}
+
+		let log = SigningLog::new(db, Some(0));
+		log.append(&entry(SigningDecision::Rejected, 2)).unwrap();
+
+		assert_eq!(log.recent(10), vec![entry(SigningDecision::Rejected, 2), entry(SigningDecision::Signed, 1)]);
+	}
+}
@@ -14,20 +14,23 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-//! Address Book and Dapps Settings Store
+//! Address Book, Dapps Settings and Vault Policy Store
 
 use std::{fs, fmt, hash, ops};
 use std::sync::atomic::{self, AtomicUsize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
+use util::U256;
 use ethstore::ethkey::Address;
 use ethjson::misc::{
 	AccountMeta,
 	DappsSettings as JsonSettings,
 	DappsHistory as JsonDappsHistory,
 	NewDappsPolicy as JsonNewDappsPolicy,
+	VaultPolicy as JsonVaultPolicy,
 };
+use ethjson::uint::Uint;
 use account_provider::DappId;
 
 /// Disk-backed map from Address to String. Uses JSON.
@@ -296,6 +299,93 @@ impl DappsSettingsStore {
 	}
 }
 
+/// Restrictions on signing with accounts stored in a vault.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct VaultPolicy {
+	/// Maximal value of a single transaction.
+	pub max_value: Option<U256>,
+	/// If set, transactions may only be sent to these addresses.
+	pub allowed_recipients: Option<Vec<Address>>,
+	/// If set, only these signing operations are allowed
+	/// (`sendTransaction`, `signTransaction`, `sign`, `signTypedData`, `decrypt`).
+	pub allowed_methods: Option<Vec<String>>,
+}
+
+impl VaultPolicy {
+	/// Checks if given signing operation is allowed.
+	pub fn allows_method(&self, method: &str) -> bool {
+		self.allowed_methods.as_ref().map_or(true, |methods| methods.iter().any(|m| m == method))
+	}
+
+	/// Checks if a transaction with given recipient (`None` for contract creation) and value is allowed.
+	pub fn allows_transaction(&self, to: Option<&Address>, value: &U256) -> bool {
+		let recipient_allowed = match (self.allowed_recipients.as_ref(), to) {
+			(None, _) => true,
+			(Some(recipients), Some(to)) => recipients.contains(to),
+			(Some(_), None) => false,
+		};
+		let value_allowed = self.max_value.as_ref().map_or(true, |max| value <= max);
+		recipient_allowed && value_allowed
+	}
+}
+
+impl From<JsonVaultPolicy> for VaultPolicy {
+	fn from(p: JsonVaultPolicy) -> Self {
+		VaultPolicy {
+			max_value: p.max_value.map(Into::into),
+			allowed_recipients: p.allowed_recipients.map(|recipients| recipients.into_iter().map(Into::into).collect()),
+			allowed_methods: p.allowed_methods,
+		}
+	}
+}
+
+impl From<VaultPolicy> for JsonVaultPolicy {
+	fn from(p: VaultPolicy) -> Self {
+		JsonVaultPolicy {
+			max_value: p.max_value.map(Uint),
+			allowed_recipients: p.allowed_recipients.map(|recipients| recipients.into_iter().map(Into::into).collect()),
+			allowed_methods: p.allowed_methods,
+		}
+	}
+}
+
+/// Disk-backed map from vault name to its signing policy. Uses JSON.
+pub struct VaultPolicyStore {
+	policies: DiskMap<String, VaultPolicy>,
+}
+
+impl VaultPolicyStore {
+	/// Creates new store at given directory path.
+	pub fn new(path: &Path) -> Self {
+		let mut r = VaultPolicyStore {
+			policies: DiskMap::new(path, "vault_policies.json"),
+		};
+		r.policies.revert(JsonVaultPolicy::read);
+		r
+	}
+
+	/// Creates transient store (no changes are saved to disk).
+	pub fn transient() -> Self {
+		VaultPolicyStore {
+			policies: DiskMap::transient(),
+		}
+	}
+
+	/// Returns policy of given vault.
+	pub fn get(&self, vault: &str) -> Option<VaultPolicy> {
+		self.policies.get(vault).cloned()
+	}
+
+	/// Sets or removes (with `None`) policy of given vault.
+	pub fn set(&mut self, vault: String, policy: Option<VaultPolicy>) {
+		match policy {
+			Some(policy) => { self.policies.insert(vault, policy); },
+			None => { self.policies.remove(&vault); },
+		}
+		self.policies.save(JsonVaultPolicy::write);
+	}
+}
+
 /// Disk-serializable HashMap
 #[derive(Debug)]
 struct DiskMap<K: hash::Hash + Eq, V> {
@@ -366,7 +456,7 @@ impl<K: hash::Hash + Eq, V> DiskMap<K, V> {
 
 #[cfg(test)]
 mod tests {
-	use super::{AddressBook, DappsSettingsStore, DappsSettings, NewDappsPolicy};
+	use super::{AddressBook, DappsSettingsStore, DappsSettings, NewDappsPolicy, VaultPolicyStore, VaultPolicy};
 	use account_provider::DappId;
 	use std::collections::HashMap;
 	use ethjson::misc::AccountMeta;
@@ -457,4 +547,43 @@ mod tests {
 			"default".into() => NewDappsPolicy::Whitelist(vec![1.into(), 2.into()])
 		]);
 	}
+
+	#[test]
+	fn should_save_and_reload_vault_policies() {
+		// given
+		let path = RandomTempPath::create_dir();
+		let mut store = VaultPolicyStore::new(&path);
+		let policy = VaultPolicy {
+			max_value: Some(1000.into()),
+			allowed_recipients: Some(vec![1.into()]),
+			allowed_methods: None,
+		};
+
+		// when
+		store.set("hot".into(), Some(policy.clone()));
+		store.set("cold".into(), Some(VaultPolicy::default()));
+		store.set("cold".into(), None);
+
+		// then
+		let store = VaultPolicyStore::new(&path);
+		assert_eq!(store.get("hot"), Some(policy));
+		assert_eq!(store.get("cold"), None);
+	}
+
+	#[test]
+	fn vault_policy_should_restrict_transactions() {
+		let policy = VaultPolicy {
+			max_value: Some(1000.into()),
+			allowed_recipients: Some(vec![1.into()]),
+			allowed_methods: Some(vec!["sendTransaction".into()]),
+		};
+
+		assert!(policy.allows_method("sendTransaction"));
+		assert!(!policy.allows_method("sign"));
+		assert!(policy.allows_transaction(Some(&1.into()), &1000.into()));
+		assert!(!policy.allows_transaction(Some(&1.into()), &1001.into()));
+		assert!(!policy.allows_transaction(Some(&2.into()), &1.into()));
+		assert!(!policy.allows_transaction(None, &1.into()));
+		assert!(VaultPolicy::default().allows_transaction(None, &1001.into()));
+	}
 }
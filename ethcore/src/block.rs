@@ -31,13 +31,17 @@ use error::{Error, BlockError, TransactionError};
 use factory::Factories;
 use header::Header;
 use receipt::Receipt;
-use state::State;
+use state::{State, ApplyOutcome, Merge};
 use state_db::StateDB;
 use trace::FlatTrace;
 use transaction::{UnverifiedTransaction, SignedTransaction};
 use verification::PreverifiedBlock;
 use views::BlockView;
 
+/// Number of transactions per thread executed speculatively at once by
+/// `OpenBlock::push_transactions_parallel`.
+const PARALLEL_BATCH_PER_THREAD: usize = 4;
+
 /// A block, encoded as it is on the block chain.
 #[derive(Default, Debug, Clone, PartialEq)]
 pub struct Block {
@@ -261,6 +265,8 @@ impl<'x> OpenBlock<'x> {
 		gas_range_target: (U256, U256),
 		extra_data: Bytes,
 	) -> Result<Self, Error> {
+		// interpreters running the block's transactions reuse each other's memory.
+		let factories = Factories { vm: factories.vm.with_memory_pool(), ..factories };
 		let state = State::from_existing(db, parent.state_root().clone(), engine.account_start_nonce(), factories)?;
 		let mut r = OpenBlock {
 			block: ExecutedBlock::new(state, tracing),
@@ -330,6 +336,11 @@ impl<'x> OpenBlock<'x> {
 		Ok(())
 	}
 
+	/// Remove all uncles from the block, returning them.
+	pub fn take_uncles(&mut self) -> Vec<Header> {
+		::std::mem::replace(&mut self.block.uncles, Vec::new())
+	}
+
 	/// Get the environment info concerning this block.
 	pub fn env_info(&self) -> EnvInfo {
 		// TODO: memoise.
@@ -356,17 +367,56 @@ impl<'x> OpenBlock<'x> {
 //		info!("env_info says gas_used={}", env_info.gas_used);
 		match self.block.state.apply(&env_info, self.engine, &t, self.block.traces.is_some()) {
 			Ok(outcome) => {
-				self.block.transactions_set.insert(h.unwrap_or_else(||t.hash()));
-				self.block.transactions.push(t.into());
-				let t = outcome.trace;
-				self.block.traces.as_mut().map(|traces| traces.push(t));
-				self.block.receipts.push(outcome.receipt);
+				self.note_transaction(t, h, outcome);
 				Ok(self.block.receipts.last().expect("receipt just pushed; qed"))
 			}
 			Err(x) => Err(From::from(x))
 		}
 	}
 
+	/// Push transactions onto the block, executing independent ones in parallel on up to
+	/// `threads` threads. The outcome is the same as pushing them one by one.
+	pub fn push_transactions_parallel(&mut self, transactions: &[SignedTransaction], threads: usize) -> Result<(), Error> {
+		let batch_size = cmp::max(1, threads) * PARALLEL_BATCH_PER_THREAD;
+		let tracing = self.block.traces.is_some();
+		let mut next = 0;
+		while next < transactions.len() {
+			let end = cmp::min(next + batch_size, transactions.len());
+			let env_info = self.env_info();
+			let speculations = self.block.state.speculate(&env_info, self.engine, &transactions[next..end], tracing, threads);
+
+			// merge in order until a transaction has to be executed serially, then start a new batch.
+			let mut merge = Merge::default();
+			for speculation in speculations {
+				let t = transactions[next].clone();
+				next += 1;
+
+				let env_info = self.env_info();
+				let merged = match speculation {
+					Ok(speculation) if !self.block.transactions_set.contains(&t.hash()) =>
+						self.block.state.merge(&mut merge, &env_info, self.engine, speculation)?,
+					_ => None,
+				};
+				match merged {
+					Some(outcome) => self.note_transaction(t, None, outcome),
+					None => {
+						self.push_transaction(t, None)?;
+						break;
+					}
+				}
+			}
+		}
+		Ok(())
+	}
+
+	fn note_transaction(&mut self, t: SignedTransaction, h: Option<H256>, outcome: ApplyOutcome) {
+		self.block.transactions_set.insert(h.unwrap_or_else(||t.hash()));
+		self.block.transactions.push(t.into());
+		let t = outcome.trace;
+		self.block.traces.as_mut().map(|traces| traces.push(t));
+		self.block.receipts.push(outcome.receipt);
+	}
+
 	/// Turn this into a `ClosedBlock`.
 	pub fn close(self) -> ClosedBlock {
 		let mut s = self;
@@ -523,7 +573,8 @@ impl IsBlock for SealedBlock {
 	fn block(&self) -> &ExecutedBlock { &self.block }
 }
 
-/// Enact the block given by block header, transactions and uncles
+/// Enact the block given by block header, transactions and uncles.
+/// Transactions are executed speculatively in parallel if `parallel_threads` is non-zero.
 #[cfg_attr(feature="dev", allow(too_many_arguments))]
 pub fn enact(
 	header: &Header,
@@ -535,6 +586,7 @@ pub fn enact(
 	parent: &Header,
 	last_hashes: Arc<LastHashes>,
 	factories: Factories,
+	parallel_threads: usize,
 ) -> Result<LockedBlock, Error> {
 	{
 		if ::log::max_log_level() >= ::log::LogLevel::Trace {
@@ -553,7 +605,10 @@ pub fn enact(
 	b.set_uncles_hash(header.uncles_hash().clone());
 	b.set_transactions_root(header.transactions_root().clone());
 
-	push_transactions(&mut b, transactions)?;
+	match parallel_threads {
+		0 => push_transactions(&mut b, transactions)?,
+		threads => b.push_transactions_parallel(transactions, threads)?,
+	}
 	for u in uncles {
 		b.push_uncle(u.clone())?;
 	}
@@ -599,9 +654,10 @@ pub fn enact_verified(
 	parent: &Header,
 	last_hashes: Arc<LastHashes>,
 	factories: Factories,
+	parallel_threads: usize,
 ) -> Result<LockedBlock, Error> {
 	let view = BlockView::new(&block.bytes);
-	enact(&block.header, &block.transactions, &view.uncles(), engine, tracing, db, parent, last_hashes, factories)
+	enact(&block.header, &block.transactions, &view.uncles(), engine, tracing, db, parent, last_hashes, factories, parallel_threads)
 }
 
 #[cfg(test)]
@@ -634,7 +690,7 @@ mod tests {
 		let header = block.header();
 		let transactions: Result<Vec<_>, Error> = block.transactions().into_iter().map(SignedTransaction::new).collect();
 		let transactions = transactions?;
-		enact(&header, &transactions, &block.uncles(), engine, tracing, db, parent, last_hashes, factories)
+		enact(&header, &transactions, &block.uncles(), engine, tracing, db, parent, last_hashes, factories, 0)
 	}
 
 	/// Enact the block given by `block_bytes` using `engine` on the database `db` with given `parent` block header. Seal the block aferwards
@@ -726,4 +782,59 @@ mod tests {
 		assert_eq!(orig_db.journal_db().keys(), db.journal_db().keys());
 		assert!(orig_db.journal_db().keys().iter().filter(|k| orig_db.journal_db().get(k.0) != db.journal_db().get(k.0)).next() == None);
 	}
+
+	#[test]
+	fn parallel_push_matches_serial() {
+		use spec::*;
+		use ethkey::{Generator, Random};
+		use state::CleanupMode;
+		use transaction::{Transaction, Action};
+		use util::U256;
+
+		let spec = Spec::new_test();
+		let engine = &*spec.engine;
+		let genesis_header = spec.genesis_header();
+		let last_hashes = Arc::new(vec![genesis_header.hash()]);
+		let author = Address::from(0xaa);
+
+		let keys: Vec<_> = (0..3).map(|_| Random.generate().unwrap()).collect();
+		let tx = |sender: usize, nonce: u64, to: Address| Transaction {
+			action: Action::Call(to),
+			nonce: U256::from(nonce),
+			gas_price: U256::from(1),
+			gas: U256::from(21_000),
+			value: U256::from(1_000),
+			data: Vec::new(),
+		}.sign(keys[sender].secret(), None);
+		let transactions = vec![
+			tx(0, 0, Address::from(1)),
+			tx(1, 0, Address::from(2)),
+			// spends funds received by the first one.
+			tx(2, 0, keys[0].address()),
+			tx(0, 1, Address::from(3)),
+			tx(1, 1, author),
+		];
+
+		let open = || {
+			let mut db_result = get_temp_state_db();
+			let db = spec.ensure_db_good(db_result.take(), &Default::default()).unwrap();
+			let mut b = OpenBlock::new(engine, Default::default(), false, db, &genesis_header, last_hashes.clone(), author, (3141562.into(), 31415620.into()), vec![]).unwrap();
+			for key in &keys {
+				b.block.state.add_balance(&key.address(), &U256::from(1_000_000), CleanupMode::NoEmpty).unwrap();
+			}
+			b
+		};
+
+		let mut serial = open();
+		for t in &transactions {
+			serial.push_transaction(t.clone(), None).unwrap();
+		}
+		let mut parallel = open();
+		parallel.push_transactions_parallel(&transactions, 2).unwrap();
+
+		let serial = serial.close_and_lock();
+		let parallel = parallel.close_and_lock();
+		assert_eq!(parallel.receipts(), serial.receipts());
+		assert_eq!(parallel.header().state_root(), serial.header().state_root());
+	}
 }
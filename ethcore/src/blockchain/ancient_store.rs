@@ -0,0 +1,270 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Append-only flat-file store for bodies and receipts of old canonical blocks.
+//!
+//! Blocks buried deep enough below the best block are never reverted, so their
+//! bodies and receipts are moved out of the database into two data files,
+//! addressed by an index holding one fixed-size entry per block number.
+//!
+//! Not to be confused with the "ancient" blocks imported behind a warp-synced
+//! chain, which are kept in the database like any other block.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use byteorder::{ByteOrder, LittleEndian};
+use header::BlockNumber;
+use util::{Bytes, H256, Mutex};
+
+const INDEX_FILE: &'static str = "index.dat";
+const BODIES_FILE: &'static str = "bodies.dat";
+const RECEIPTS_FILE: &'static str = "receipts.dat";
+
+// block hash followed by offset and length of the body and of the receipts.
+const ENTRY_SIZE: u64 = 32 + 12 + 12;
+
+struct Entry {
+	hash: H256,
+	body: (u64, u32),
+	receipts: (u64, u32),
+}
+
+impl Entry {
+	fn encode(&self) -> [u8; ENTRY_SIZE as usize] {
+		let mut buf = [0u8; ENTRY_SIZE as usize];
+		buf[..32].copy_from_slice(&self.hash);
+		LittleEndian::write_u64(&mut buf[32..40], self.body.0);
+		LittleEndian::write_u32(&mut buf[40..44], self.body.1);
+		LittleEndian::write_u64(&mut buf[44..52], self.receipts.0);
+		LittleEndian::write_u32(&mut buf[52..56], self.receipts.1);
+		buf
+	}
+
+	fn decode(buf: &[u8]) -> Self {
+		Entry {
+			hash: H256::from_slice(&buf[..32]),
+			body: (LittleEndian::read_u64(&buf[32..40]), LittleEndian::read_u32(&buf[40..44])),
+			receipts: (LittleEndian::read_u64(&buf[44..52]), LittleEndian::read_u32(&buf[52..56])),
+		}
+	}
+
+	fn bodies_end(&self) -> u64 {
+		self.body.0 + self.body.1 as u64
+	}
+
+	fn receipts_end(&self) -> u64 {
+		self.receipts.0 + self.receipts.1 as u64
+	}
+}
+
+struct Files {
+	index: File,
+	bodies: File,
+	receipts: File,
+	len: u64,
+	bodies_end: u64,
+	receipts_end: u64,
+}
+
+impl Files {
+	fn entry(&mut self, number: BlockNumber) -> io::Result<Entry> {
+		let mut buf = [0u8; ENTRY_SIZE as usize];
+		self.index.seek(SeekFrom::Start(number * ENTRY_SIZE))?;
+		self.index.read_exact(&mut buf)?;
+		Ok(Entry::decode(&buf))
+	}
+}
+
+/// Flat-file store of block bodies and receipts, indexed by block number.
+///
+/// Blocks are appended strictly in order starting from the genesis. Every entry
+/// records the block hash, so lookups for blocks which are not part of the stored
+/// chain simply miss.
+pub struct AncientStore {
+	files: Mutex<Files>,
+}
+
+impl AncientStore {
+	/// Open the store in the given directory, creating it if necessary.
+	///
+	/// Entries which point beyond the end of the data files, and any data written
+	/// after the last complete entry, are left over from an interrupted append and
+	/// get truncated away.
+	pub fn open(path: &Path) -> io::Result<Self> {
+		fs::create_dir_all(path)?;
+		let open = |name: &str| OpenOptions::new().read(true).write(true).create(true).open(path.join(name));
+
+		let mut files = Files {
+			index: open(INDEX_FILE)?,
+			bodies: open(BODIES_FILE)?,
+			receipts: open(RECEIPTS_FILE)?,
+			len: 0,
+			bodies_end: 0,
+			receipts_end: 0,
+		};
+
+		let bodies_size = files.bodies.metadata()?.len();
+		let receipts_size = files.receipts.metadata()?.len();
+		let mut len = files.index.metadata()?.len() / ENTRY_SIZE;
+		while len > 0 {
+			let entry = files.entry(len - 1)?;
+			if entry.bodies_end() <= bodies_size && entry.receipts_end() <= receipts_size {
+				files.bodies_end = entry.bodies_end();
+				files.receipts_end = entry.receipts_end();
+				break;
+			}
+			len -= 1;
+		}
+
+		files.len = len;
+		files.index.set_len(len * ENTRY_SIZE)?;
+		files.bodies.set_len(files.bodies_end)?;
+		files.receipts.set_len(files.receipts_end)?;
+
+		trace!(target: "blockchain", "Opened ancient store at {} with {} blocks", path.display(), len);
+		Ok(AncientStore {
+			files: Mutex::new(files),
+		})
+	}
+
+	/// Number of blocks in the store, which is also the number of the next block to append.
+	pub fn len(&self) -> BlockNumber {
+		self.files.lock().len
+	}
+
+	/// Append the raw body and receipts of the next block.
+	/// Nothing is guaranteed to be on disk until `flush` is called.
+	pub fn append(&self, hash: &H256, body: &[u8], receipts: &[u8]) -> io::Result<()> {
+		let mut files = self.files.lock();
+		let files = &mut *files;
+		let entry = Entry {
+			hash: hash.clone(),
+			body: (files.bodies_end, body.len() as u32),
+			receipts: (files.receipts_end, receipts.len() as u32),
+		};
+
+		files.bodies.seek(SeekFrom::Start(files.bodies_end))?;
+		files.bodies.write_all(body)?;
+		files.receipts.seek(SeekFrom::Start(files.receipts_end))?;
+		files.receipts.write_all(receipts)?;
+		files.index.seek(SeekFrom::Start(files.len * ENTRY_SIZE))?;
+		files.index.write_all(&entry.encode())?;
+
+		files.bodies_end = entry.bodies_end();
+		files.receipts_end = entry.receipts_end();
+		files.len += 1;
+		Ok(())
+	}
+
+	/// Make everything appended so far durable. Data files are synced before the index.
+	pub fn flush(&self) -> io::Result<()> {
+		let files = self.files.lock();
+		files.bodies.sync_data()?;
+		files.receipts.sync_data()?;
+		files.index.sync_data()
+	}
+
+	/// Get the raw body of the block with given number and hash.
+	pub fn body(&self, number: BlockNumber, hash: &H256) -> io::Result<Option<Bytes>> {
+		self.read(number, hash, false)
+	}
+
+	/// Get the raw receipts of the block with given number and hash.
+	pub fn receipts(&self, number: BlockNumber, hash: &H256) -> io::Result<Option<Bytes>> {
+		self.read(number, hash, true)
+	}
+
+	fn read(&self, number: BlockNumber, hash: &H256, receipts: bool) -> io::Result<Option<Bytes>> {
+		let mut files = self.files.lock();
+		let files = &mut *files;
+		if number >= files.len {
+			return Ok(None);
+		}
+
+		let entry = files.entry(number)?;
+		if &entry.hash != hash {
+			return Ok(None);
+		}
+
+		let ((offset, len), file) = match receipts {
+			true => (entry.receipts, &mut files.receipts),
+			false => (entry.body, &mut files.bodies),
+		};
+
+		// blocks frozen without receipts have an empty receipts entry.
+		if len == 0 {
+			return Ok(None);
+		}
+
+		let mut buf = vec![0u8; len as usize];
+		file.seek(SeekFrom::Start(offset))?;
+		file.read_exact(&mut buf)?;
+		Ok(Some(buf))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs::OpenOptions;
+	use std::io::Write;
+	use devtools::RandomTempPath;
+	use util::H256;
+	use super::{AncientStore, BODIES_FILE};
+
+	#[test]
+	fn append_and_read() {
+		let path = RandomTempPath::create_dir();
+		let store = AncientStore::open(path.as_path()).unwrap();
+		let (first, second) = (H256::from(1), H256::from(2));
+
+		store.append(&first, b"body one", b"receipts one").unwrap();
+		store.append(&second, b"body two", b"").unwrap();
+		store.flush().unwrap();
+
+		assert_eq!(store.len(), 2);
+		assert_eq!(store.body(0, &first).unwrap(), Some(b"body one".to_vec()));
+		assert_eq!(store.receipts(0, &first).unwrap(), Some(b"receipts one".to_vec()));
+		assert_eq!(store.body(1, &second).unwrap(), Some(b"body two".to_vec()));
+		assert_eq!(store.receipts(1, &second).unwrap(), None);
+
+		// wrong hash or unknown number.
+		assert_eq!(store.body(1, &first).unwrap(), None);
+		assert_eq!(store.body(2, &second).unwrap(), None);
+	}
+
+	#[test]
+	fn truncates_interrupted_append() {
+		let path = RandomTempPath::create_dir();
+		let hash = H256::from(1);
+		{
+			let store = AncientStore::open(path.as_path()).unwrap();
+			store.append(&hash, b"body", b"receipts").unwrap();
+			store.flush().unwrap();
+		}
+
+		// a body written without its index entry.
+		OpenOptions::new().append(true).open(path.as_path().join(BODIES_FILE)).unwrap()
+			.write_all(b"partial").unwrap();
+
+		let store = AncientStore::open(path.as_path()).unwrap();
+		assert_eq!(store.len(), 1);
+		store.append(&H256::from(2), b"next", b"").unwrap();
+		assert_eq!(store.body(0, &hash).unwrap(), Some(b"body".to_vec()));
+		assert_eq!(store.body(1, &H256::from(2)).unwrap(), Some(b"next".to_vec()));
+	}
+}
@@ -17,6 +17,7 @@
 //! Blockchain database.
 
 use bloomchain as bc;
+use byteorder::{BigEndian, ByteOrder};
 use util::*;
 use rlp::*;
 use header::*;
@@ -31,13 +32,32 @@ use blockchain::best_block::{BestBlock, BestAncientBlock};
 use types::blockchain_info::BlockChainInfo;
 use types::tree_route::TreeRoute;
 use blockchain::update::ExtrasUpdate;
-use blockchain::{CacheSize, ImportRoute, Config};
-use db::{self, Writable, Readable, CacheUpdatePolicy};
+use blockchain::{AncientStore, CacheSize, ImportRoute, Config};
+use db::{self, Writable, Readable, Key, CacheUpdatePolicy};
 use cache_manager::CacheManager;
 use encoded;
+use filter::Filter;
 
 const LOG_BLOOMS_LEVELS: usize = 3;
 const LOG_BLOOMS_ELEMENTS_PER_INDEX: usize = 16;
+/// Key of the number of the first block in the log index.
+const LOG_INDEX_START_KEY: &'static [u8] = b"log-index-start";
+/// Log index key prefix of the addresses; topics are prefixed with their position, starting at 1.
+const LOG_INDEX_ADDRESS_PREFIX: u8 = 0;
+
+/// Key of the log index entry noting that a log of block `number` has been emitted by an address
+/// or has a topic at the position given by `prefix`.
+///
+/// Entries sort by block number within the same item, so those in a range of blocks can be iterated over.
+fn log_index_key(prefix: u8, item: &[u8], number: BlockNumber) -> Vec<u8> {
+	let mut key = Vec::with_capacity(1 + item.len() + 8);
+	key.push(prefix);
+	key.extend_from_slice(item);
+	let mut encoded_number = [0u8; 8];
+	BigEndian::write_u64(&mut encoded_number, number);
+	key.extend_from_slice(&encoded_number);
+	key
+}
 
 /// Interface for querying blocks by hash and by number.
 pub trait BlockProvider {
@@ -193,8 +213,17 @@ pub struct BlockChain {
 
 	db: Arc<KeyValueDB>,
 
+	// bodies and receipts of blocks more than `ancient_depth` below the best block.
+	ancient_store: Option<AncientStore>,
+	ancient_depth: u64,
+
 	cache_man: Mutex<CacheManager<CacheId>>,
 
+	// whether the addresses and topics of the logs are indexed on import.
+	log_index: bool,
+	// first block in the log index, blocks before it being found through the blooms only.
+	log_index_start: RwLock<Option<BlockNumber>>,
+
 	pending_best_block: RwLock<Option<BestBlock>>,
 	pending_block_hashes: RwLock<HashMap<BlockNumber, H256>>,
 	pending_block_details: RwLock<HashMap<H256, BlockDetails>>,
@@ -291,20 +320,22 @@ impl BlockProvider for BlockChain {
 			}
 		}
 
-		// Read from DB and populate cache
+		// Read from DB, falling back to the ancient store, and populate cache
 		let opt = self.db.get(db::COL_BODIES, hash)
 			.expect("Low level database error. Some issue with disk?");
 
-		let result = match opt {
-			Some(b) => {
-				let bytes: Bytes = UntrustedRlp::new(&b).decompress(RlpType::Blocks).to_vec();
-				let mut write = self.block_bodies.write();
-				write.insert(hash.clone(), bytes.clone());
-				Some(encoded::Body::new(bytes))
-			},
-			None => None
+		let decompressed = match opt {
+			Some(b) => Some(UntrustedRlp::new(&b).decompress(RlpType::Blocks).to_vec()),
+			None => self.ancient_data(hash, AncientStore::body)
+				.map(|b| UntrustedRlp::new(&b).decompress(RlpType::Blocks).to_vec()),
 		};
 
+		let result = decompressed.map(|bytes: Bytes| {
+			let mut write = self.block_bodies.write();
+			write.insert(hash.clone(), bytes.clone());
+			encoded::Body::new(bytes)
+		});
+
 		self.cache_man.lock().note_used(CacheId::BlockBody(hash.clone()));
 
 		result
@@ -333,7 +364,8 @@ impl BlockProvider for BlockChain {
 
 	/// Get receipts of block with given hash.
 	fn block_receipts(&self, hash: &H256) -> Option<BlockReceipts> {
-		let result = self.db.read_with_cache(db::COL_EXTRA, &self.block_receipts, hash);
+		let result = self.db.read_with_cache(db::COL_EXTRA, &self.block_receipts, hash)
+			.or_else(|| self.ancient_data(hash, AncientStore::receipts).map(|b| ::rlp::decode(&b)));
 		self.cache_man.lock().note_used(CacheId::BlockReceipts(hash.clone()));
 		result
 	}
@@ -425,6 +457,14 @@ impl BlockChain {
 		// 400 is the avarage size of the key
 		let cache_man = CacheManager::new(config.pref_cache_size, config.max_cache_size, 400);
 
+		let ancient_store = config.ancient_path.as_ref().and_then(|path| match AncientStore::open(path) {
+			Ok(store) => Some(store),
+			Err(e) => {
+				warn!("Cannot open ancient block store at {}: {}. Keeping all blocks in the database.", path.display(), e);
+				None
+			}
+		});
+
 		let mut bc = BlockChain {
 			blooms_config: bc::Config {
 				levels: LOG_BLOOMS_LEVELS,
@@ -441,13 +481,27 @@ impl BlockChain {
 			blocks_blooms: RwLock::new(HashMap::new()),
 			block_receipts: RwLock::new(HashMap::new()),
 			db: db.clone(),
+			ancient_store: ancient_store,
+			ancient_depth: config.ancient_depth,
 			cache_man: Mutex::new(cache_man),
+			log_index: config.log_index,
+			log_index_start: RwLock::new(None),
 			pending_best_block: RwLock::new(None),
 			pending_block_hashes: RwLock::new(HashMap::new()),
 			pending_block_details: RwLock::new(HashMap::new()),
 			pending_transaction_addresses: RwLock::new(HashMap::new()),
 		};
 
+		// blocks imported while the index was turned off are missing from it, so it has to start over.
+		let log_index_start: Option<BlockNumber> = bc.db.get(db::COL_EXTRA, LOG_INDEX_START_KEY).unwrap().map(|start| ::rlp::decode(&start));
+		if log_index_start.is_some() && !bc.log_index {
+			let mut batch = DBTransaction::new();
+			batch.delete(db::COL_EXTRA, LOG_INDEX_START_KEY);
+			bc.db.write(batch).expect("Low level database error. Some issue with disk?");
+		} else {
+			*bc.log_index_start.write() = log_index_start;
+		}
+
 		// load best block
 		let best_block_hash = match bc.db.get(db::COL_EXTRA, b"best").unwrap() {
 			Some(best) => {
@@ -848,6 +902,10 @@ impl BlockChain {
 			);
 		}
 
+		if self.log_index {
+			self.prepare_log_index_update(batch, &receipts, &info);
+		}
+
 		self.prepare_update(batch, ExtrasUpdate {
 			block_hashes: self.prepare_block_hashes_update(bytes, &info),
 			block_details: self.prepare_block_details_update(bytes, &info),
@@ -1203,6 +1261,110 @@ impl BlockChain {
 			.collect()
 	}
 
+	/// This function writes the log index entries of the blocks becoming canon with the inserted one.
+	/// Entries of retracted blocks are left behind; they only cost a lookup of the canon block's
+	/// receipts when filtering.
+	fn prepare_log_index_update(&self, batch: &mut DBTransaction, receipts: &[Receipt], info: &BlockInfo) {
+		let start = match info.location {
+			BlockLocation::Branch => return,
+			BlockLocation::CanonChain => info.number,
+			BlockLocation::BranchBecomingCanonChain(ref data) => {
+				let ancestor_number = self.block_number(&data.ancestor).expect("`ancestor` is in the route; qed");
+				for (offset, hash) in data.enacted.iter().enumerate() {
+					let receipts = self.block_receipts(hash).map_or_else(Vec::new, |r| r.receipts);
+					Self::write_log_index(batch, &receipts, ancestor_number + 1 + offset as BlockNumber);
+				}
+				ancestor_number + 1
+			},
+		};
+
+		Self::write_log_index(batch, receipts, info.number);
+
+		let mut index_start = self.log_index_start.write();
+		if index_start.is_none() {
+			batch.put(db::COL_EXTRA, LOG_INDEX_START_KEY, &::rlp::encode(&start));
+			*index_start = Some(start);
+		}
+	}
+
+	fn write_log_index(batch: &mut DBTransaction, receipts: &[Receipt], number: BlockNumber) {
+		for log in receipts.iter().flat_map(|r| r.logs.iter()) {
+			batch.put(db::COL_LOG_INDEX, &log_index_key(LOG_INDEX_ADDRESS_PREFIX, &log.address, number), &[]);
+			for (position, topic) in log.topics.iter().enumerate().take(4) {
+				batch.put(db::COL_LOG_INDEX, &log_index_key(position as u8 + 1, topic, number), &[]);
+			}
+		}
+	}
+
+	/// Returns numbers of the blocks within the range which may contain logs matching the filter.
+	///
+	/// The log index is consulted for the indexed part of the range when the filter restricts the
+	/// addresses or topics, the blooms otherwise.
+	pub fn blocks_with_logs(&self, filter: &Filter, from_block: BlockNumber, to_block: BlockNumber) -> Vec<BlockNumber> {
+		let index_start = match *self.log_index_start.read() {
+			Some(start) if to_block >= start && Self::is_restricted(filter) => start,
+			_ => return self.blocks_with_blooms(filter, from_block, to_block),
+		};
+
+		// blocks imported before the index existed are only found through the blooms.
+		let mut numbers = if from_block < index_start {
+			self.blocks_with_blooms(filter, from_block, index_start - 1)
+		} else {
+			Vec::new()
+		};
+
+		let start = cmp::max(from_block, index_start);
+		let end = to_block + 1;
+		let addresses = match filter.address {
+			Some(ref addresses) if !addresses.is_empty() =>
+				Some(self.indexed_blocks(LOG_INDEX_ADDRESS_PREFIX, addresses.iter().map(|a| &**a), start, end)),
+			_ => None,
+		};
+		let topics = filter.topics.iter().take(4).enumerate().filter_map(|(position, topics)| match *topics {
+			Some(ref topics) if !topics.is_empty() =>
+				Some(self.indexed_blocks(position as u8 + 1, topics.iter().map(|t| &**t), start, end)),
+			_ => None,
+		});
+
+		let indexed = addresses.into_iter().chain(topics)
+			.fold(None, |acc: Option<BTreeSet<BlockNumber>>, blocks| match acc {
+				Some(acc) => Some(acc.intersection(&blocks).cloned().collect()),
+				None => Some(blocks),
+			})
+			.expect("the filter restricts the addresses or some of the topics; qed");
+
+		numbers.extend(indexed);
+		numbers
+	}
+
+	fn is_restricted(filter: &Filter) -> bool {
+		filter.address.as_ref().map_or(false, |a| !a.is_empty()) ||
+			filter.topics.iter().take(4).any(|t| t.as_ref().map_or(false, |t| !t.is_empty()))
+	}
+
+	fn blocks_with_blooms(&self, filter: &Filter, from_block: BlockNumber, to_block: BlockNumber) -> Vec<BlockNumber> {
+		filter.bloom_possibilities().iter()
+			.flat_map(|bloom| self.blocks_with_bloom(bloom, from_block, to_block))
+			// remove duplicate elements
+			.collect::<HashSet<BlockNumber>>()
+			.into_iter()
+			.collect()
+	}
+
+	/// Numbers of the blocks within `[start, end)` with a log having any of the items at the position
+	/// given by `prefix`.
+	fn indexed_blocks<'a, I>(&self, prefix: u8, items: I, start: BlockNumber, end: BlockNumber) -> BTreeSet<BlockNumber>
+		where I: Iterator<Item=&'a [u8]>
+	{
+		items
+			.flat_map(|item| {
+				self.db.iter_range(db::COL_LOG_INDEX, &log_index_key(prefix, item, start), &log_index_key(prefix, item, end))
+					.map(|(key, _)| BigEndian::read_u64(&key[key.len() - 8..]))
+					.collect::<Vec<_>>()
+			})
+			.collect()
+	}
+
 	/// Get best block hash.
 	pub fn best_block_hash(&self) -> H256 {
 		self.best_block.read().hash.clone()
@@ -1285,6 +1447,131 @@ impl BlockChain {
 		});
 	}
 
+	// Read data of a block from the ancient store. Only canonical blocks are ever stored,
+	// and the store checks the hash, so a lookup by the block's number is enough.
+	fn ancient_data<F>(&self, hash: &H256, read: F) -> Option<Bytes>
+		where F: Fn(&AncientStore, BlockNumber, &H256) -> ::std::io::Result<Option<Bytes>>
+	{
+		let store = match self.ancient_store {
+			Some(ref store) => store,
+			None => return None,
+		};
+
+		self.block_number(hash).and_then(|number| read(store, number, hash)
+			.expect("Ancient store read failed. Some issue with disk?"))
+	}
+
+	/// Move bodies and receipts of canonical blocks buried deeper than the configured depth
+	/// from the database to the ancient store, at most `limit` blocks at a time.
+	///
+	/// Blocks are moved in order, stopping at the first one whose body isn't in the database
+	/// (e.g. the gap left behind a warp-synced chain). Returns the number of blocks moved.
+	pub fn freeze_ancient(&self, limit: usize) -> ::std::io::Result<usize> {
+		let store = match self.ancient_store {
+			Some(ref store) => store,
+			None => return Ok(0),
+		};
+
+		let best = self.best_block_number();
+		if best < self.ancient_depth {
+			return Ok(0);
+		}
+
+		let target = best - self.ancient_depth;
+		let mut batch = DBTransaction::new();
+		let mut moved = 0;
+		while moved < limit && store.len() <= target {
+			let number = store.len();
+			let hash = match self.block_hash(number) {
+				Some(hash) => hash,
+				None => break,
+			};
+
+			let body = match self.db.get(db::COL_BODIES, &hash).expect("Low level database error. Some issue with disk?") {
+				Some(body) => body,
+				None => break,
+			};
+
+			let receipts_key = <H256 as Key<BlockReceipts>>::key(&hash);
+			let receipts = self.db.get(db::COL_EXTRA, &receipts_key).expect("Low level database error. Some issue with disk?");
+
+			store.append(&hash, &body, receipts.as_ref().map_or(&[][..], |r| &r[..]))?;
+			batch.delete(db::COL_BODIES, &hash);
+			batch.delete(db::COL_EXTRA, &receipts_key);
+			moved += 1;
+		}
+
+		if moved > 0 {
+			// data must be durable in the store before it goes away from the database.
+			store.flush()?;
+			self.db.write(batch).expect("Low level database error. Some issue with disk?");
+			trace!(target: "blockchain", "Moved {} blocks to the ancient store, up to #{}", moved, store.len() - 1);
+		}
+
+		Ok(moved)
+	}
+
+	/// Check the stored data of the canonical block with given number for consistency.
+	///
+	/// The header must hash to the canonical hash and agree with the block details, the body
+	/// must match the transactions root and uncles hash, and the receipts must match the
+	/// receipts root and log bloom. Data is decoded defensively, so corruption is reported
+	/// rather than causing a panic. Returns the header, or a description of the first problem.
+	pub fn verify_block(&self, number: BlockNumber) -> Result<Header, String> {
+		let hash = self.block_hash(number).ok_or_else(|| "missing canonical hash".to_owned())?;
+
+		let header_bytes = self.block_header_data(&hash).ok_or_else(|| format!("missing header {}", hash))?.into_inner();
+		let header: Header = UntrustedRlp::new(&header_bytes).as_val()
+			.map_err(|e| format!("undecodable header {}: {}", hash, e))?;
+		if header.hash() != hash || header.number() != number {
+			return Err(format!("header {} is stored under the wrong hash or number", hash));
+		}
+
+		let details_key = <H256 as Key<BlockDetails>>::key(&hash);
+		let details: BlockDetails = match self.db.get(db::COL_EXTRA, &details_key).expect("Low level database error. Some issue with disk?") {
+			Some(raw) => UntrustedRlp::new(&raw).as_val().map_err(|e| format!("undecodable details {}: {}", hash, e))?,
+			None => return Err(format!("missing details {}", hash)),
+		};
+		if details.number != number || (number > 0 && &details.parent != header.parent_hash()) {
+			return Err(format!("details of {} disagree with the header", hash));
+		}
+
+		let body = match self.db.get(db::COL_BODIES, &hash).expect("Low level database error. Some issue with disk?") {
+			Some(raw) => UntrustedRlp::new(&raw).decompress(RlpType::Blocks).to_vec(),
+			None => self.ancient_data(&hash, AncientStore::body)
+				.map(|raw| UntrustedRlp::new(&raw).decompress(RlpType::Blocks).to_vec())
+				.ok_or_else(|| format!("missing body {}", hash))?,
+		};
+		let body = UntrustedRlp::new(&body);
+		let (transactions, uncles) = match (body.at(0), body.at(1)) {
+			(Ok(transactions), Ok(uncles)) => (transactions, uncles),
+			_ => return Err(format!("undecodable body {}", hash)),
+		};
+		let transactions_root = ordered_trie_root(transactions.iter().map(|tx| tx.as_raw().to_vec()));
+		if &transactions_root != header.transactions_root() || &uncles.as_raw().sha3() != header.uncles_hash() {
+			return Err(format!("body {} does not match the header", hash));
+		}
+
+		let receipts_key = <H256 as Key<BlockReceipts>>::key(&hash);
+		let receipts = match self.db.get(db::COL_EXTRA, &receipts_key).expect("Low level database error. Some issue with disk?") {
+			Some(raw) => Some(raw.to_vec()),
+			None => self.ancient_data(&hash, AncientStore::receipts),
+		};
+		let receipts: BlockReceipts = match receipts {
+			Some(raw) => UntrustedRlp::new(&raw).as_val().map_err(|e| format!("undecodable receipts {}: {}", hash, e))?,
+			// the genesis block has no receipts stored.
+			None if number == 0 => return Ok(header),
+			None => return Err(format!("missing receipts {}", hash)),
+		};
+		let receipts_root = ordered_trie_root(receipts.receipts.iter().map(|r| encode(r).to_vec()));
+		let log_bloom = receipts.receipts.iter().fold(H2048::zero(), |b, r| &b | &r.log_bloom);
+		if Ok(receipts.receipts.len()) != transactions.item_count() || &receipts_root != header.receipts_root() || &log_bloom != header.log_bloom() {
+			return Err(format!("receipts {} do not match the block", hash));
+		}
+
+		Ok(header)
+	}
+
 	/// Create a block body from a block.
 	pub fn block_to_body(block: &[u8]) -> Bytes {
 		let mut body = RlpStream::new_list(2);
@@ -1337,6 +1624,8 @@ mod tests {
 	use log_entry::{LogEntry, LocalizedLogEntry};
 	use ethkey::Secret;
 	use header::BlockNumber;
+	use filter::Filter;
+	use client::BlockId;
 
 	fn new_db() -> Arc<KeyValueDB> {
 		Arc::new(::util::kvdb::in_memory(::db::NUM_COLUMNS.unwrap_or(0)))
@@ -1427,6 +1716,82 @@ mod tests {
 		assert_eq!(bc.ancestry_iter(block_hashes[0].clone()).unwrap().collect::<Vec<_>>(), block_hashes)
 	}
 
+	#[test]
+	fn moves_old_blocks_to_ancient_store() {
+		let mut canon_chain = ChainGenerator::default();
+		let mut finalizer = BlockFinalizer::default();
+		let genesis = canon_chain.generate(&mut finalizer).unwrap();
+
+		let path = ::devtools::RandomTempPath::create_dir();
+		let config = Config {
+			ancient_path: Some(path.as_path().clone()),
+			ancient_depth: 3,
+			..Config::default()
+		};
+
+		let db = new_db();
+		let bc = BlockChain::new(config, &genesis, db.clone());
+		let mut hashes = vec![bc.genesis_hash()];
+		for _ in 0..5 {
+			let block = canon_chain.generate(&mut finalizer).unwrap();
+			hashes.push(BlockView::new(&block).header_view().sha3());
+			let mut batch = db.transaction();
+			bc.insert_block(&mut batch, &block, vec![Receipt::new(None, 0.into(), vec![])]);
+			db.write(batch).unwrap();
+			bc.commit();
+		}
+
+		let expected: Vec<_> = hashes.iter()
+			.map(|h| (bc.block_body(h).unwrap(), bc.block_receipts(h).map(|r| r.receipts)))
+			.collect();
+		assert_eq!(bc.freeze_ancient(1).unwrap(), 1);
+		assert_eq!(bc.freeze_ancient(10).unwrap(), 2);
+		assert_eq!(bc.freeze_ancient(10).unwrap(), 0);
+		assert!(db.get(::db::COL_BODIES, &hashes[2]).unwrap().is_none());
+		assert!(db.get(::db::COL_BODIES, &hashes[3]).unwrap().is_some());
+
+		// reads are served from the store after a restart.
+		let config = Config {
+			ancient_path: Some(path.as_path().clone()),
+			ancient_depth: 3,
+			..Config::default()
+		};
+		let bc = BlockChain::new(config, &genesis, db.clone());
+		for (hash, &(ref body, ref receipts)) in hashes.iter().zip(expected.iter()) {
+			assert_eq!(&bc.block_body(hash).unwrap(), body);
+			assert_eq!(&bc.block_receipts(hash).map(|r| r.receipts), receipts);
+		}
+	}
+
+	#[test]
+	fn verify_block_reports_corruption() {
+		use db::Writable;
+
+		let mut canon_chain = ChainGenerator::default();
+		let mut finalizer = BlockFinalizer::default();
+		let genesis = canon_chain.generate(&mut finalizer).unwrap();
+		let first = canon_chain.generate(&mut finalizer).unwrap();
+		let first_hash = BlockView::new(&first).header_view().sha3();
+
+		let db = new_db();
+		let bc = new_chain(&genesis, db.clone());
+		let mut batch = db.transaction();
+		bc.insert_block(&mut batch, &first, vec![]);
+		db.write(batch).unwrap();
+		bc.commit();
+
+		assert!(bc.verify_block(0).is_ok());
+		assert_eq!(bc.verify_block(1).unwrap().hash(), first_hash);
+		assert!(bc.verify_block(2).is_err());
+
+		// receipts for a block without transactions are an empty list; store a bogus one.
+		let mut batch = db.transaction();
+		batch.write(::db::COL_EXTRA, &first_hash, &::blockchain::extras::BlockReceipts::new(vec![Receipt::new(None, 0.into(), vec![])]));
+		db.write(batch).unwrap();
+		let bc = new_chain(&genesis, db.clone());
+		assert!(bc.verify_block(1).unwrap_err().contains("receipts"));
+	}
+
 	#[test]
 	#[cfg_attr(feature="dev", allow(cyclomatic_complexity))]
 	fn test_find_uncles() {
@@ -1983,6 +2348,55 @@ mod tests {
 		]);
 	}
 
+	#[test]
+	fn finds_blocks_through_log_index() {
+		// given
+		let mut canon_chain = ChainGenerator::default();
+		let mut finalizer = BlockFinalizer::default();
+		let genesis = canon_chain.generate(&mut finalizer).unwrap();
+		let b1 = canon_chain.generate(&mut finalizer).unwrap();
+		let b2 = canon_chain.generate(&mut finalizer).unwrap();
+		let b3 = canon_chain.generate(&mut finalizer).unwrap();
+
+		let receipt = |address: u64, topics: Vec<H256>| Receipt {
+			state_root: Some(H256::default()),
+			gas_used: 10_000.into(),
+			log_bloom: Default::default(),
+			logs: vec![LogEntry { address: Address::from(address), topics: topics, data: vec![] }],
+		};
+		let filter = |address: Option<Vec<Address>>, topics: Vec<Option<Vec<H256>>>| Filter {
+			from_block: BlockId::Earliest,
+			to_block: BlockId::Latest,
+			address: address,
+			topics: topics,
+			limit: None,
+		};
+
+		let db = new_db();
+		let config = Config {
+			log_index: true,
+			..Config::default()
+		};
+		let bc = BlockChain::new(config, &genesis, db.clone());
+		insert_block(&db, &bc, &b1, vec![receipt(1, vec![H256::from(10)])]);
+		insert_block(&db, &bc, &b2, vec![receipt(2, vec![H256::from(10), H256::from(20)])]);
+		insert_block(&db, &bc, &b3, vec![receipt(1, vec![H256::from(20)])]);
+
+		// when
+		let mut by_address = bc.blocks_with_logs(&filter(Some(vec![Address::from(1)]), vec![None, None, None, None]), 0, 3);
+		let mut by_topic = bc.blocks_with_logs(&filter(None, vec![Some(vec![H256::from(10)]), None, None, None]), 0, 3);
+		let by_both = bc.blocks_with_logs(&filter(Some(vec![Address::from(1)]), vec![None, Some(vec![H256::from(20)]), None, None]), 0, 3);
+		let in_range = bc.blocks_with_logs(&filter(Some(vec![Address::from(1)]), vec![None, None, None, None]), 2, 3);
+		by_address.sort();
+		by_topic.sort();
+
+		// then
+		assert_eq!(by_address, vec![1, 3]);
+		assert_eq!(by_topic, vec![1, 2]);
+		assert_eq!(by_both, Vec::<BlockNumber>::new());
+		assert_eq!(in_range, vec![3]);
+	}
+
 	#[test]
 	fn test_bloom_filter_simple() {
 		// TODO: From here
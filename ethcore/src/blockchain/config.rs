@@ -16,6 +16,8 @@
 
 //! Blockchain configuration.
 
+use std::path::PathBuf;
+
 /// Blockchain configuration.
 #[derive(Debug, PartialEq, Clone)]
 pub struct Config {
@@ -25,6 +27,13 @@ pub struct Config {
 	pub max_cache_size: usize,
 	/// Backing db cache_size
 	pub db_cache_size: Option<usize>,
+	/// Directory of the ancient block store. `None` keeps all blocks in the database.
+	pub ancient_path: Option<PathBuf>,
+	/// Number of blocks below the best block kept in the database before their bodies
+	/// and receipts are moved to the ancient store.
+	pub ancient_depth: u64,
+	/// Index the addresses and topics of the logs of imported blocks.
+	pub log_index: bool,
 }
 
 impl Default for Config {
@@ -33,6 +42,9 @@ impl Default for Config {
 			pref_cache_size: 1 << 14,
 			max_cache_size: 1 << 20,
 			db_cache_size: None,
+			ancient_path: None,
+			ancient_depth: 90_000,
+			log_index: false,
 		}
 	}
 }
@@ -15,6 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::cmp::{max, min};
+use std::collections::HashMap;
 use std::io::{self, Read};
 
 use byteorder::{ByteOrder, BigEndian};
@@ -23,7 +24,7 @@ use crypto::ripemd160::Ripemd160 as Ripemd160Digest;
 use crypto::digest::Digest;
 use num::{BigUint, Zero, One};
 
-use util::{U256, H256, Uint, Hashable, BytesRef};
+use util::{U256, H256, Uint, Hashable, BytesRef, RwLock};
 use ethkey::{Signature, recover as ec_recover};
 use ethjson;
 
@@ -110,6 +111,18 @@ impl Builtin {
 
 impl From<ethjson::spec::Builtin> for Builtin {
 	fn from(b: ethjson::spec::Builtin) -> Self {
+		let mut natives: Vec<Box<Impl>> = {
+			let registry = REGISTRY.read();
+			b.natives().into_iter().map(|name| match registry.get(name) {
+				Some(factory) => factory(),
+				None => panic!("invalid builtin name: {}", name),
+			}).collect()
+		};
+		let native = match natives.len() {
+			1 => natives.pop().expect("one native; qed"),
+			_ => Box::new(Chain(natives)) as Box<Impl>,
+		};
+
 		let pricer: Box<Pricer> = match b.pricing {
 			ethjson::spec::Pricing::Linear(linear) => {
 				Box::new(Linear {
@@ -131,20 +144,76 @@ impl From<ethjson::spec::Builtin> for Builtin {
 
 		Builtin {
 			pricer: pricer,
-			native: ethereum_builtin(&b.name),
+			native: native,
 			activate_at: b.activate_at.map(Into::into).unwrap_or(0),
 		}
 	}
 }
 
+// Natives declared for one builtin in the chain spec, run in turn.
+struct Chain(Vec<Box<Impl>>);
+
+impl Impl for Chain {
+	fn execute(&self, input: &[u8], output: &mut BytesRef) {
+		let mut data = input.to_vec();
+		for native in &self.0 {
+			let mut out = Vec::new();
+			native.execute(&data, &mut BytesRef::Flexible(&mut out));
+			data = out;
+		}
+		output.write(0, &data);
+	}
+}
+
+/// Creates a native builtin implementation.
+pub type ImplFactory = fn() -> Box<Impl>;
+
+lazy_static! {
+	// native implementations chain specs may declare builtins with, by name.
+	static ref REGISTRY: RwLock<HashMap<String, ImplFactory>> = {
+		let mut natives = HashMap::new();
+		for name in &["identity", "ecrecover", "sha256", "ripemd160", "modexp"] {
+			natives.insert(name.to_string(), ethereum_factory(name));
+		}
+		RwLock::new(natives)
+	};
+}
+
+/// Registers a native implementation under the given name, so that chain specs can declare
+/// builtins running it with their own pricing and activation block.
+/// Must be done before the spec is loaded. Returns false if the name is already taken.
+pub fn register(name: &str, factory: ImplFactory) -> bool {
+	let mut registry = REGISTRY.write();
+	if registry.contains_key(name) {
+		return false;
+	}
+	registry.insert(name.to_owned(), factory);
+	true
+}
+
+/// Whether a native implementation is registered under the given name.
+pub fn is_registered(name: &str) -> bool {
+	REGISTRY.read().contains_key(name)
+}
+
 // Ethereum builtin creator.
 fn ethereum_builtin(name: &str) -> Box<Impl> {
+	ethereum_factory(name)()
+}
+
+fn ethereum_factory(name: &str) -> ImplFactory {
+	fn identity() -> Box<Impl> { Box::new(Identity) }
+	fn ecrecover() -> Box<Impl> { Box::new(EcRecover) }
+	fn sha256() -> Box<Impl> { Box::new(Sha256) }
+	fn ripemd160() -> Box<Impl> { Box::new(Ripemd160) }
+	fn modexp() -> Box<Impl> { Box::new(ModexpImpl) }
+
 	match name {
-		"identity" => Box::new(Identity) as Box<Impl>,
-		"ecrecover" => Box::new(EcRecover) as Box<Impl>,
-		"sha256" => Box::new(Sha256) as Box<Impl>,
-		"ripemd160" => Box::new(Ripemd160) as Box<Impl>,
-		"modexp" => Box::new(ModexpImpl) as Box<Impl>,
+		"identity" => identity as ImplFactory,
+		"ecrecover" => ecrecover as ImplFactory,
+		"sha256" => sha256 as ImplFactory,
+		"ripemd160" => ripemd160 as ImplFactory,
+		"modexp" => modexp as ImplFactory,
 		_ => panic!("invalid builtin name: {}", name),
 	}
 }
@@ -299,7 +368,7 @@ impl Impl for ModexpImpl {
 
 #[cfg(test)]
 mod tests {
-	use super::{Builtin, Linear, ethereum_builtin, Pricer, Modexp};
+	use super::{Builtin, Linear, ethereum_builtin, Pricer, Modexp, Impl, register, is_registered};
 	use ethjson;
 	use util::{U256, BytesRef};
 
@@ -557,6 +626,7 @@ mod tests {
 	fn from_json() {
 		let b = Builtin::from(ethjson::spec::Builtin {
 			name: "identity".to_owned(),
+			natives: None,
 			pricing: ethjson::spec::Pricing::Linear(ethjson::spec::Linear {
 				base: 10,
 				word: 20,
@@ -574,4 +644,68 @@ mod tests {
 		b.execute(&i[..], &mut BytesRef::Fixed(&mut o[..]));
 		assert_eq!(i, o);
 	}
+
+	#[test]
+	fn registered_natives() {
+		struct Reverse;
+
+		impl Impl for Reverse {
+			fn execute(&self, input: &[u8], output: &mut BytesRef) {
+				let reversed: Vec<u8> = input.iter().rev().cloned().collect();
+				output.write(0, &reversed);
+			}
+		}
+
+		fn reverse() -> Box<Impl> { Box::new(Reverse) }
+
+		assert!(!is_registered("test_reverse"));
+		assert!(register("test_reverse", reverse));
+		assert!(!register("test_reverse", reverse));
+		assert!(!register("identity", reverse));
+		assert!(is_registered("test_reverse"));
+
+		let b = Builtin::from(ethjson::spec::Builtin {
+			name: "test_reverse".to_owned(),
+			natives: None,
+			pricing: ethjson::spec::Pricing::Linear(ethjson::spec::Linear {
+				base: 100,
+				word: 0,
+			}),
+			activate_at: Some(ethjson::uint::Uint(10.into())),
+		});
+
+		assert_eq!(b.cost(&[0; 64]), U256::from(100));
+		assert!(!b.is_active(9));
+		assert!(b.is_active(10));
+
+		let i = [0u8, 1, 2, 3];
+		let mut o = [255u8; 4];
+		b.execute(&i[..], &mut BytesRef::Fixed(&mut o[..]));
+		assert_eq!(o, [3u8, 2, 1, 0]);
+	}
+
+	#[test]
+	fn chained_natives() {
+		let b = Builtin::from(ethjson::spec::Builtin {
+			name: "double_sha256".to_owned(),
+			natives: Some(vec!["sha256".to_owned(), "sha256".to_owned()]),
+			pricing: ethjson::spec::Pricing::Linear(ethjson::spec::Linear {
+				base: 120,
+				word: 24,
+			}),
+			activate_at: None,
+		});
+		let sha256 = ethereum_builtin("sha256");
+
+		let i = [0u8, 1, 2, 3];
+		let mut once = [0u8; 32];
+		sha256.execute(&i[..], &mut BytesRef::Fixed(&mut once[..]));
+		let mut twice = [0u8; 32];
+		sha256.execute(&once[..], &mut BytesRef::Fixed(&mut twice[..]));
+
+		let mut o = [255u8; 32];
+		b.execute(&i[..], &mut BytesRef::Fixed(&mut o[..]));
+		assert_eq!(o, twice);
+		assert_eq!(b.cost(&i[..]), U256::from(144));
+	}
 }
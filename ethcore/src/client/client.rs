@@ -18,15 +18,17 @@ use std::collections::{HashSet, HashMap, BTreeMap, VecDeque};
 use std::str::FromStr;
 use std::sync::{Arc, Weak};
 use std::fmt;
+use std::io::{Read, Write};
 use std::sync::atomic::{AtomicUsize, AtomicBool, Ordering as AtomicOrdering};
 use std::time::{Instant};
 use time::precise_time_ns;
 
 // util
 use util::{Bytes, PerfTimer, Itertools, Mutex, RwLock, MutexGuard, Hashable};
-use util::{journaldb, DBValue, TrieFactory, Trie};
+use util::{journaldb, DBValue, TrieFactory, Trie, TrieDB, HashDB, SHA3_EMPTY, SHA3_NULL_RLP, ToPretty};
 use util::{U256, H256, Address, H2048, Uint};
-use util::trie::TrieSpec;
+use util::trie::{TrieSpec, TrieStats};
+use util::journaldb::OverlayStats;
 use util::kvdb::*;
 
 // other
@@ -53,7 +55,7 @@ use blockchain::{BlockChain, BlockProvider, TreeRoute, ImportRoute};
 use client::{
 	BlockId, TransactionId, UncleId, TraceId, ClientConfig, BlockChainClient,
 	MiningBlockChainClient, EngineClient, TraceFilter, CallAnalytics, BlockImportError, Mode,
-	ChainNotify, PruningInfo,
+	ChainNotify, PruningInfo, DbStats, JournalCompaction, EvmStats, GasSearch,
 };
 use client::Error as ClientError;
 use env_info::EnvInfo;
@@ -62,14 +64,18 @@ use receipt::{Receipt, LocalizedReceipt};
 use trace::{TraceDB, ImportRequest as TraceImportRequest, LocalizedTrace, Database as TraceDatabase};
 use trace;
 use trace::FlatTransactionTraces;
-use evm::{Factory as EvmFactory, Schedule};
-use miner::{Miner, MinerService, TransactionImportResult};
+use evm::{self, Factory as EvmFactory, Schedule};
+use miner::{Miner, MinerService, TransactionImportResult, LocalTransactionStatus};
 use snapshot::{self, io as snapshot_io};
 use factory::Factories;
 use rlp::UntrustedRlp;
+use account_db::AccountDB;
+use types::basic_account::BasicAccount;
 use state_db::StateDB;
 use rand::OsRng;
+use crossbeam;
 use client::registry::Registry;
+use client::prefetch;
 use encoded;
 
 // re-export
@@ -81,6 +87,14 @@ pub use verification::queue::QueueInfo as BlockQueueInfo;
 const MAX_TX_QUEUE_SIZE: usize = 4096;
 const MAX_QUEUE_SIZE_TO_SLEEP_ON: usize = 2;
 const MIN_HISTORY_SIZE: u64 = 8;
+// Maximum number of eras examined by a single journal compaction step.
+const JOURNAL_COMPACTION_ERAS: usize = 256;
+// Maximum number of blocks moved to the ancient store by a single tick.
+const ANCIENT_FREEZE_BLOCKS: usize = 1024;
+// Maximum number of storage items of killed accounts removed by a single tick.
+const STORAGE_EXPIRY_ITEMS: usize = 16384;
+// Number of blocks scanned at once when looking up a page of logs.
+const LOGS_CHUNK_SIZE: u64 = 1000;
 
 impl fmt::Display for BlockChainInfo {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -99,6 +113,8 @@ pub struct ClientReport {
 	pub gas_processed: U256,
 	/// Memory used by state DB
 	pub state_db_mem: usize,
+	/// Size of the state DB's overlay of recent history.
+	pub state_db_overlay: OverlayStats,
 }
 
 impl ClientReport {
@@ -134,6 +150,7 @@ pub struct Client {
 	engine: Arc<Engine>,
 	config: ClientConfig,
 	pruning: journaldb::Algorithm,
+	journal_compaction: Mutex<Option<JournalCompaction>>,
 	db: RwLock<Arc<KeyValueDB>>,
 	state_db: Mutex<StateDB>,
 	block_queue: BlockQueue,
@@ -150,6 +167,8 @@ pub struct Client {
 	last_hashes: RwLock<VecDeque<H256>>,
 	factories: Factories,
 	history: u64,
+	prefetch_threads: usize,
+	parallel_exec_threads: usize,
 	rng: Mutex<OsRng>,
 	on_user_defaults_change: Mutex<Option<Box<FnMut(Option<Mode>) + 'static + Send>>>,
 	registrar: Mutex<Option<Registry>>,
@@ -172,15 +191,16 @@ impl Client {
 			false => TrieSpec::Secure,
 		};
 
-		let trie_factory = TrieFactory::new(trie_spec);
+		let trie_factory = TrieFactory::with_node_encoding(trie_spec, config.trie_encoding);
 		let factories = Factories {
 			vm: EvmFactory::new(config.vm_type.clone(), config.jump_table_size),
 			trie: trie_factory,
 			accountdb: Default::default(),
 		};
 
-		let journal_db = journaldb::new(db.clone(), config.pruning, ::db::COL_STATE);
+		let journal_db = journaldb::new_with_overlay_budget(db.clone(), config.pruning, ::db::COL_STATE, config.overlay_memory_budget);
 		let mut state_db = StateDB::new(journal_db, config.state_cache_size);
+		state_db.set_record_preimages(config.key_preimages);
 		if state_db.journal_db().is_empty() {
 			// Sets the correct state root.
 			state_db = spec.ensure_db_good(state_db, &factories)?;
@@ -225,6 +245,12 @@ impl Client {
 			tracedb: tracedb,
 			engine: engine,
 			pruning: config.pruning.clone(),
+			prefetch_threads: config.prefetch_threads,
+			parallel_exec_threads: config.parallel_exec_threads,
+			journal_compaction: Mutex::new(match config.pruning {
+				journaldb::Algorithm::RefCounted => Some(JournalCompaction::default()),
+				_ => None,
+			}),
 			verifier: verification::new(config.verifier_type.clone()),
 			config: config,
 			db: RwLock::new(db),
@@ -387,7 +413,7 @@ impl Client {
 			let last_hashes = self.build_last_hashes(header.parent_hash().clone());
 			let db = self.state_db.lock().boxed_clone_canon(header.parent_hash());
 
-			let enact_result = enact_verified(block, engine, self.tracedb.read().tracing_enabled(), db, &parent, last_hashes, self.factories.clone());
+			let enact_result = enact_verified(block, engine, self.tracedb.read().tracing_enabled(), db, &parent, last_hashes, self.factories.clone(), self.parallel_exec_threads);
 			let locked_block = enact_result.map_err(|e| {
 				warn!(target: "client", "Block import failed for #{} ({})\nError: {:?}", header.number(), header.hash(), e);
 			})?;
@@ -454,29 +480,54 @@ impl Client {
 			let _timer = PerfTimer::new("import_verified_blocks");
 			let start = precise_time_ns();
 
-			for block in blocks {
-				let header = &block.header;
-				let is_invalid = invalid_blocks.contains(header.parent_hash());
-				if is_invalid {
-					invalid_blocks.insert(header.hash());
-					continue;
+			let prefetch_accounts = match self.prefetch_threads {
+				0 => Vec::new(),
+				_ => prefetch::touched_accounts(&blocks),
+			};
+			let prefetch_stop = AtomicBool::new(false);
+
+			crossbeam::scope(|scope| {
+				let first_parent = blocks[0].header.parent_hash().clone();
+				if let Some(parent) = self.chain.read().block_header(&first_parent) {
+					prefetch::spawn(
+						scope,
+						&self.state_db.lock(),
+						&first_parent,
+						parent.state_root().clone(),
+						self.engine.account_start_nonce(),
+						&self.factories,
+						&prefetch_accounts,
+						self.prefetch_threads,
+						&prefetch_stop,
+					);
 				}
-				if let Ok(closed_block) = self.check_and_close_block(&block) {
-					if self.engine.is_proposal(&block.header) {
-						self.block_queue.mark_as_good(&[header.hash()]);
-						proposed_blocks.push(block.bytes);
-					} else {
-						imported_blocks.push(header.hash());
 
-						let route = self.commit_block(closed_block, &header.hash(), &block.bytes);
-						import_results.push(route);
+				for block in blocks {
+					let header = &block.header;
+					let is_invalid = invalid_blocks.contains(header.parent_hash());
+					if is_invalid {
+						invalid_blocks.insert(header.hash());
+						continue;
+					}
+					if let Ok(closed_block) = self.check_and_close_block(&block) {
+						if self.engine.is_proposal(&block.header) {
+							self.block_queue.mark_as_good(&[header.hash()]);
+							proposed_blocks.push(block.bytes);
+						} else {
+							imported_blocks.push(header.hash());
+
+							let route = self.commit_block(closed_block, &header.hash(), &block.bytes);
+							import_results.push(route);
 
-						self.report.write().accrue_block(&block);
+							self.report.write().accrue_block(&block);
+						}
+					} else {
+						invalid_blocks.insert(header.hash());
 					}
-				} else {
-					invalid_blocks.insert(header.hash());
 				}
-			}
+
+				prefetch_stop.store(true, AtomicOrdering::Relaxed);
+			});
 
 			let imported = imported_blocks.len();
 			let invalid_blocks = invalid_blocks.into_iter().collect::<Vec<H256>>();
@@ -684,8 +735,13 @@ impl Client {
 		self.block_header(id).and_then(|header| {
 			let db = self.state_db.lock().boxed_clone();
 
-			// early exit for pruned blocks
-			if db.is_pruned() && self.chain.read().best_block_number() >= block_number + self.history {
+			// early exit for pruned blocks. the state of every era still in the journal is
+			// complete, which may be many more than the minimum history.
+			let journalled = match db.journal_db().earliest_era() {
+				Some(earliest) => block_number >= earliest,
+				None => self.chain.read().best_block_number() < block_number + self.history,
+			};
+			if db.is_pruned() && !journalled {
 				return None;
 			}
 
@@ -694,6 +750,27 @@ impl Client {
 		})
 	}
 
+	/// Write the state at the given block to `out` as a stream of snapshot state chunks.
+	/// See `snapshot::dump_state` for the format.
+	pub fn dump_state<W: Write>(&self, id: BlockId, out: W) -> Result<(), EthcoreError> {
+		let state = self.state_at(id.clone()).ok_or(snapshot::Error::InvalidStartingBlock(id))?;
+		let root = state.root().clone();
+		let (_, db) = state.drop();
+
+		snapshot::dump_state(db.as_hashdb(), &root, out)?;
+		Ok(())
+	}
+
+	/// Import a state stream written by `dump_state` into the state database.
+	/// Returns the root of the imported state.
+	pub fn restore_state<R: Read>(&self, input: R) -> Result<H256, EthcoreError> {
+		let root = snapshot::restore_state(input, self.db.read().clone(), self.pruning)?;
+
+		// accounts were written to the bloom on disk; the in-memory copy must be refreshed.
+		self.state_db.lock().reload_bloom();
+		Ok(root)
+	}
+
 	/// Attempt to get a copy of a specific block's beginning state.
 	///
 	/// This will not fail if given BlockId::Latest.
@@ -728,7 +805,9 @@ impl Client {
 	/// Get the report.
 	pub fn report(&self) -> ClientReport {
 		let mut report = self.report.read().clone();
-		report.state_db_mem = self.state_db.lock().mem_used();
+		let state_db = self.state_db.lock();
+		report.state_db_mem = state_db.mem_used();
+		report.state_db_overlay = state_db.journal_db().overlay_stats();
 		report
 	}
 
@@ -737,6 +816,82 @@ impl Client {
 	pub fn tick(&self) {
 		self.check_garbage();
 		self.check_snooze();
+		self.check_journal_compaction();
+		self.check_storage_expiry();
+		self.check_ancient_freeze();
+	}
+
+	// remove storage tries of accounts killed in canonical blocks, a bounded number of items at a time.
+	fn check_storage_expiry(&self) {
+		let _import_lock = self.import_lock.lock();
+		let chain = self.chain.read();
+		let best_root = chain.best_block_header().state_root();
+
+		let mut state_db = self.state_db.lock();
+		let earliest_era = state_db.journal_db().earliest_era().unwrap_or_else(|| chain.best_block_number());
+		// states of all blocks still journalled, on any branch descending from the canonical
+		// block of the earliest era.
+		let journalled_roots = || {
+			let mut roots = Vec::new();
+			let mut pending: Vec<H256> = chain.block_hash(earliest_era).into_iter().collect();
+			while let Some(hash) = pending.pop() {
+				if let Some(header) = chain.block_header(&hash) {
+					roots.push(header.state_root().clone());
+				}
+				if let Some(details) = chain.block_details(&hash) {
+					pending.extend(details.children);
+				}
+			}
+			roots
+		};
+
+		let mut batch = DBTransaction::new();
+		match state_db.expire_storage(&mut batch, &best_root, &journalled_roots, STORAGE_EXPIRY_ITEMS) {
+			Ok(removed) => {
+				self.db.read().write_buffered(batch);
+				if removed > 0 {
+					debug!(target: "client", "Removed {} storage items of killed accounts", removed);
+				}
+			}
+			Err(e) => warn!(target: "client", "Removing storage of killed accounts failed: {}", e),
+		}
+	}
+
+	// move bodies and receipts of old blocks to the ancient store, if one is configured.
+	fn check_ancient_freeze(&self) {
+		let _import_lock = self.import_lock.lock();
+		if let Err(e) = self.chain.read().freeze_ancient(ANCIENT_FREEZE_BLOCKS) {
+			warn!(target: "client", "Moving blocks to the ancient store failed: {}", e);
+		}
+	}
+
+	// apply ancient eras left in an on-disk journal, a bounded number at a time.
+	fn check_journal_compaction(&self) {
+		let mut compaction = self.journal_compaction.lock();
+		let progress = match *compaction {
+			Some(ref mut progress) => progress,
+			None => return,
+		};
+
+		let _import_lock = self.import_lock.lock();
+		let chain = self.chain.read();
+		let best = chain.best_block_number();
+		if best < self.history { return }
+
+		progress.target_era = best - self.history;
+		if progress.next_era > progress.target_era { return }
+
+		let mut batch = DBTransaction::new();
+		let report = self.state_db.lock().compact_journal(&mut batch, progress.target_era, JOURNAL_COMPACTION_ERAS, &|era| chain.block_hash(era));
+		match report {
+			Ok(report) => {
+				self.db.read().write_buffered(batch);
+				progress.next_era = report.next_era;
+				progress.compacted_eras += report.eras;
+				progress.reclaimed_bytes += report.reclaimed_bytes;
+			}
+			Err(e) => warn!(target: "client", "State journal compaction failed: {}", e),
+		}
 	}
 
 	fn check_garbage(&self) {
@@ -781,7 +936,9 @@ impl Client {
 
 	/// Take a snapshot at the given block.
 	/// If the ID given is "latest", this will default to 1000 blocks behind.
-	pub fn take_snapshot<W: snapshot_io::SnapshotWriter + Send>(&self, writer: W, at: BlockId, p: &snapshot::Progress) -> Result<(), EthcoreError> {
+	/// Unchanged state chunks may be taken from the `base` snapshot, if incremental snapshots are enabled.
+	/// Traces of recent blocks are included when tracing is enabled and configured for snapshots.
+	pub fn take_snapshot<W: snapshot_io::SnapshotWriter + Send>(&self, writer: W, at: BlockId, base: Option<&snapshot_io::SnapshotReader>, p: &snapshot::Progress) -> Result<(), EthcoreError> {
 		let db = self.state_db.lock().journal_db().boxed_clone();
 		let best_block_number = self.chain_info().best_block_number;
 		let block_number = self.block_number(at).ok_or(snapshot::Error::InvalidStartingBlock(at))?;
@@ -810,11 +967,69 @@ impl Client {
 			},
 		};
 
-		snapshot::take_snapshot(&self.chain.read(), start_hash, db.as_hashdb(), writer, p)?;
+		let traces = match self.tracedb.read().tracing_enabled() {
+			true => Some(self.db.read().clone()),
+			false => None,
+		};
+
+		snapshot::take_snapshot(&self.chain.read(), start_hash, db.as_hashdb(), writer, base, traces.as_ref().map(|db| &**db), &self.config.snapshot, p)?;
 
 		Ok(())
 	}
 
+	/// Check database integrity for the canonical blocks in the given range, calling `progress`
+	/// with the number of each block checked.
+	///
+	/// Every block has its header, body and receipts cross-checked. State roots are checked
+	/// for every block whose state is still meant to be available, and the state trie, with
+	/// all storage tries and code, is walked in full for the last such block. Blocks within
+	/// the gap left by warp sync are skipped.
+	///
+	/// Returns the number of the first corrupt block and a description of the problem.
+	pub fn verify_database<F>(&self, from: BlockNumber, to: BlockNumber, progress: F) -> Result<(), (BlockNumber, String)>
+		where F: Fn(BlockNumber)
+	{
+		let chain = self.chain.read();
+		let db = self.state_db.lock().journal_db().boxed_clone();
+		let earliest_state = db.earliest_era().unwrap_or(0);
+		let in_gap = |number| match chain.first_block_number() {
+			Some(first) => number < first && number > chain.best_ancient_number().unwrap_or(0),
+			None => false,
+		};
+
+		let mut last_state = None;
+		for number in from..(to + 1) {
+			if in_gap(number) { continue }
+
+			let header = chain.verify_block(number).map_err(|e| (number, e))?;
+			if !db.is_pruned() || number >= earliest_state {
+				if !db.as_hashdb().contains(header.state_root()) {
+					return Err((number, format!("missing state root {}", header.state_root())));
+				}
+				last_state = Some((number, header.state_root().clone()));
+			}
+			progress(number);
+		}
+
+		match last_state {
+			Some((number, root)) => verify_state(db.as_hashdb(), &root).map_err(|e| (number, e)),
+			None => Ok(()),
+		}
+	}
+
+	/// Collect node statistics of the state trie at the given block, and of all storage
+	/// tries of the accounts in it, returned in that order.
+	pub fn trie_stats(&self, id: BlockId) -> Result<(TrieStats, TrieStats), String> {
+		let header = self.block_header(id).ok_or_else(|| "unknown block".to_owned())?;
+		let root = header.state_root();
+		let db = self.state_db.lock().journal_db().boxed_clone();
+		if !db.as_hashdb().contains(&root) {
+			return Err(format!("state of block #{} is not available", header.number()));
+		}
+
+		state_trie_stats(db.as_hashdb(), &root)
+	}
+
 	/// Ask the client what the history parameter is.
 	pub fn pruning_history(&self) -> u64 {
 		self.history
@@ -877,20 +1092,21 @@ impl snapshot::DatabaseRestore for Client {
 		db.restore(new_db)?;
 
 		let cache_size = state_db.cache_size();
-		*state_db = StateDB::new(journaldb::new(db.clone(), self.pruning, ::db::COL_STATE), cache_size);
+		let journal_db = journaldb::new_with_overlay_budget(db.clone(), self.pruning, ::db::COL_STATE, self.config.overlay_memory_budget);
+		*state_db = StateDB::new(journal_db, cache_size);
+		state_db.set_record_preimages(self.config.key_preimages);
 		*chain = Arc::new(BlockChain::new(self.config.blockchain.clone(), &[], db.clone()));
 		*tracedb = TraceDB::new(self.config.tracing.clone(), db.clone(), chain.clone());
 		Ok(())
 	}
 }
 
-impl BlockChainClient for Client {
-	fn call(&self, t: &SignedTransaction, block: BlockId, analytics: CallAnalytics) -> Result<Executed, CallError> {
-		let mut env_info = self.env_info(block).ok_or(CallError::StatePruned)?;
-		env_info.gas_limit = U256::max_value();
-
-		// that's just a copy of the state.
-		let mut state = self.state_at(block).ok_or(CallError::StatePruned)?;
+impl Client {
+	// executes a call against the given state, leaving its changes in place.
+	fn call_in_state(&self, state: &mut State<StateDB>, env_info: &EnvInfo, t: &SignedTransaction, analytics: CallAnalytics) -> Result<Executed, CallError> {
+		for (address, account) in &analytics.state_override {
+			state.apply_override(address, account).map_err(|_| CallError::StateCorrupt)?;
+		}
 		let original_state = if analytics.state_diffing { Some(state.clone()) } else { None };
 
 		let sender = t.sender();
@@ -901,8 +1117,8 @@ impl BlockChainClient for Client {
 			state.add_balance(&sender, &(needed_balance - balance), CleanupMode::NoEmpty)
 				.map_err(|_| CallError::StateCorrupt)?;
 		}
-		let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, check_nonce: false };
-		let mut ret = Executive::new(&mut state, &env_info, &*self.engine, &self.factories.vm).transact(t, options)?;
+		let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, check_nonce: false, gas_profiling: analytics.gas_profiling, struct_logging: analytics.struct_logging };
+		let mut ret = Executive::new(state, env_info, &*self.engine, &self.factories.vm).transact(t, options)?;
 
 		// TODO gav move this into Executive.
 		if let Some(original) = original_state {
@@ -911,13 +1127,40 @@ impl BlockChainClient for Client {
 
 		Ok(ret)
 	}
+}
+
+impl BlockChainClient for Client {
+	fn call(&self, t: &SignedTransaction, block: BlockId, analytics: CallAnalytics) -> Result<Executed, CallError> {
+		let mut env_info = self.env_info(block).ok_or(CallError::StatePruned)?;
+		env_info.gas_limit = U256::max_value();
+
+		// that's just a copy of the state.
+		let mut state = self.state_at(block).ok_or(CallError::StatePruned)?;
+		self.call_in_state(&mut state, &env_info, t, analytics)
+	}
+
+	fn call_many(&self, transactions: Vec<(SignedTransaction, CallAnalytics)>, block: BlockId) -> Result<Vec<Executed>, CallError> {
+		let mut env_info = self.env_info(block).ok_or(CallError::StatePruned)?;
+		env_info.gas_limit = U256::max_value();
+
+		// that's just a copy of the state, shared by the calls in order.
+		let mut state = self.state_at(block).ok_or(CallError::StatePruned)?;
+		let mut results = Vec::with_capacity(transactions.len());
+		for (t, analytics) in transactions {
+			let ret = self.call_in_state(&mut state, &env_info, &t, analytics)?;
+			env_info.gas_used = env_info.gas_used + ret.gas_used;
+			results.push(ret);
+		}
+
+		Ok(results)
+	}
 
 	fn estimate_gas(&self, t: &SignedTransaction, block: BlockId) -> Result<U256, CallError> {
-		const UPPER_CEILING: u64 = 1_000_000_000_000u64;
+		let gas_cap = self.config.estimate_gas.gas_cap;
 		let (mut upper, env_info)  = {
 			let mut env_info = self.env_info(block).ok_or(CallError::StatePruned)?;
-			let initial_upper = env_info.gas_limit;
-			env_info.gas_limit = UPPER_CEILING.into();
+			let initial_upper = ::std::cmp::min(env_info.gas_limit, gas_cap);
+			env_info.gas_limit = gas_cap;
 			(initial_upper, env_info)
 		};
 
@@ -925,14 +1168,14 @@ impl BlockChainClient for Client {
 		let original_state = self.state_at(block).ok_or(CallError::StatePruned)?;
 		let sender = t.sender();
 		let balance = original_state.balance(&sender).map_err(ExecutionError::from)?;
-		let options = TransactOptions { tracing: true, vm_tracing: false, check_nonce: false };
+		let options = TransactOptions { tracing: true, vm_tracing: false, check_nonce: false, gas_profiling: false, struct_logging: None };
 
-		let cond = |gas| {
+		let exec = |gas: U256| -> Result<Option<Executed>, CallError> {
 			let mut tx = t.as_unsigned().clone();
 			tx.gas = gas;
 			let tx = tx.fake_sign(sender);
 
-			let mut state = original_state.clone();
+			let mut state = original_state.fork();
 			let needed_balance = tx.value + tx.gas * tx.gas_price;
 			if balance < needed_balance {
 				// give the sender a sufficient balance
@@ -942,48 +1185,38 @@ impl BlockChainClient for Client {
 
 			Ok(Executive::new(&mut state, &env_info, &*self.engine, &self.factories.vm)
 				.transact(&tx, options.clone())
-				.map(|r| r.exception.is_none())
-				.unwrap_or(false))
+				.ok())
 		};
+		let cond = |gas| exec(gas).map(|r| r.map_or(false, |r| r.exception.is_none()));
 
-		if !cond(upper)? {
-			// impossible at block gas limit - try `UPPER_CEILING` instead.
-			// TODO: consider raising limit by powers of two.
-			upper = UPPER_CEILING.into();
-			if !cond(upper)? {
+		let mut executed = exec(upper)?;
+		if executed.as_ref().map_or(true, |r| r.exception.is_some()) && upper < gas_cap {
+			// impossible at block gas limit - try the gas cap instead.
+			upper = gas_cap;
+			executed = exec(upper)?;
+		}
+		let seed = match executed {
+			Some(ref r) if r.exception.is_none() => r.gas_used + r.refunded,
+			// more gas won't help if the transaction reverts on purpose.
+			Some(Executed { exception: Some(evm::Error::Reverted { .. }), output, .. }) => {
+				trace!(target: "estimate_gas", "estimate_gas reverted with {}", upper);
+				return Err(CallError::Reverted(output))
+			},
+			_ => {
 				trace!(target: "estimate_gas", "estimate_gas failed with {}", upper);
 				let err = ExecutionError::Internal(format!("Requires higher than upper limit of {}", upper));
 				return Err(err.into())
-			}
-		}
-		let lower = t.gas_required(&self.engine.schedule(&env_info)).into();
-		if cond(lower)? {
-			trace!(target: "estimate_gas", "estimate_gas succeeded with {}", lower);
-			return Ok(lower)
-		}
+			},
+		};
 
-		/// Find transition point between `lower` and `upper` where `cond` changes from `false` to `true`.
-		/// Returns the lowest value between `lower` and `upper` for which `cond` returns true.
-		/// We assert: `cond(lower) = false`, `cond(upper) = true`
-		fn binary_chop<F, E>(mut lower: U256, mut upper: U256, mut cond: F) -> Result<U256, E>
-			where F: FnMut(U256) -> Result<bool, E>
-		{
-			while upper - lower > 1.into() {
-				let mid = (lower + upper) / 2.into();
-				trace!(target: "estimate_gas", "{} .. {} .. {}", lower, mid, upper);
-				let c = cond(mid)?;
-				match c {
-					true => upper = mid,
-					false => lower = mid,
-				};
-				trace!(target: "estimate_gas", "{} => {} .. {}", c, lower, upper);
-			}
-			Ok(upper)
+		// search between the gas consumed and `upper` for the least gas the call succeeds with.
+		let mut search = GasSearch::new(seed, upper, self.config.estimate_gas.max_iterations);
+		while let Some(gas) = search.next_gas() {
+			let success = cond(gas)?;
+			trace!(target: "estimate_gas", "estimate_gas {} with {}", if success { "succeeded" } else { "failed" }, gas);
+			search.record(gas, success);
 		}
-
-		// binary chop to non-excepting call with gas somewhere between 21000 and block gas limit
-		trace!(target: "estimate_gas", "estimate_gas chopping {} .. {}", lower, upper);
-		binary_chop(lower, upper, cond)
+		Ok(search.estimate())
 	}
 
 	fn replay(&self, id: TransactionId, analytics: CallAnalytics) -> Result<Executed, CallError> {
@@ -997,7 +1230,7 @@ impl BlockChainClient for Client {
 			return Err(CallError::TransactionNotFound);
 		}
 
-		let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, check_nonce: false };
+		let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, check_nonce: false, gas_profiling: analytics.gas_profiling, struct_logging: analytics.struct_logging };
 		const PROOF: &'static str = "Transactions fetched from blockchain; blockchain transactions are valid; qed";
 		let rest = txs.split_off(address.index);
 		for t in txs {
@@ -1139,6 +1372,30 @@ impl BlockChainClient for Client {
 		self.state_at(id).and_then(|s| s.balance(address).ok())
 	}
 
+	fn latest_nonce_and_balance(&self, address: &Address) -> (U256, U256) {
+		use state::backend::Backend;
+
+		let start_nonce = self.engine.account_start_nonce();
+		let header = self.best_block_header();
+		let db = self.state_db.lock().boxed_clone_canon(&header.hash());
+
+		// fast path: accounts known to be null or present in the canonical cache
+		// don't need a trie lookup.
+		if db.is_known_null(address) {
+			return (start_nonce, U256::zero());
+		}
+		if let Some(account) = db.get_cached_account(address) {
+			return account.map_or((start_nonce, U256::zero()), |a| (*a.nonce(), *a.balance()));
+		}
+
+		let state = State::from_existing(db, header.state_root(), start_nonce, self.factories.clone())
+			.expect("State root of best block header always valid.");
+
+		let nonce = state.nonce(address).expect("latest state is never pruned; qed");
+		let balance = state.balance(address).expect("latest state is never pruned; qed");
+		(nonce, balance)
+	}
+
 	fn storage_at(&self, address: &Address, position: &H256, id: BlockId) -> Option<H256> {
 		self.state_at(id).and_then(|s| s.storage_at(address, position).ok())
 	}
@@ -1192,37 +1449,18 @@ impl BlockChainClient for Client {
 			_ => return None,
 		};
 
-		let root = match state.storage_root(account) {
-			Ok(Some(root)) => root,
-			_ => return None,
-		};
-
-		let (_, db) = state.drop();
-		let account_db = self.factories.accountdb.readonly(db.as_hashdb(), account.sha3());
-		let trie = match self.factories.trie.readonly(account_db.as_hashdb(), &root) {
-			Ok(trie) => trie,
-			_ => {
-				trace!(target: "fatdb", "list_storage: Couldn't open the DB");
-				return None;
-			}
-		};
-
-		let mut iter = match trie.iter() {
-			Ok(iter) => iter,
-			_ => return None,
-		};
-
-		if let Some(after) = after {
-			if let Err(e) = iter.seek(after) {
-				trace!(target: "fatdb", "list_accounts: Couldn't seek the DB: {:?}", e);
+		match state.storage_entries(account, after, count as usize) {
+			Ok(entries) => Some(entries.into_iter().map(|(key, _)| key).collect()),
+			Err(e) => {
+				trace!(target: "fatdb", "list_storage: Couldn't read the storage trie: {:?}", e);
+				None
 			}
 		}
+	}
 
-		let keys = iter.filter_map(|item| {
-			item.ok().map(|(key, _)| H256::from_slice(&key))
-		}).take(count as usize).collect();
-
-		Some(keys)
+	fn key_preimage(&self, hash: &H256) -> Option<Bytes> {
+		self.db.read().get(::db::COL_PREIMAGES, hash).expect("Low level database error. Some issue with disk?")
+			.map(|preimage| preimage.to_vec())
 	}
 
 	fn transaction(&self, id: TransactionId) -> Option<LocalizedTransaction> {
@@ -1262,6 +1500,40 @@ impl BlockChainClient for Client {
 			}))
 	}
 
+	fn localized_block_receipts(&self, id: BlockId) -> Option<Vec<LocalizedReceipt>> {
+		let chain = self.chain.read();
+		let hash = match Self::block_hash(&chain, id) {
+			Some(hash) => hash,
+			None => return None,
+		};
+		let number = match chain.block_number(&hash) {
+			Some(number) => number,
+			None => return None,
+		};
+		let transactions = match chain.block_body(&hash) {
+			Some(body) => body.view().localized_transactions(&hash, number),
+			None => return None,
+		};
+		let receipts = match chain.block_receipts(&hash) {
+			Some(receipts) => receipts.receipts,
+			None => return None,
+		};
+		if transactions.len() != receipts.len() {
+			return None;
+		}
+
+		let mut prior_gas_used = U256::zero();
+		let mut no_of_logs = 0;
+		Some(transactions.into_iter().zip(receipts).map(|(tx, receipt)| {
+			let gas_used = receipt.gas_used;
+			let logs = receipt.logs.len();
+			let localized = localize_receipt(tx, receipt, prior_gas_used, no_of_logs);
+			prior_gas_used = gas_used;
+			no_of_logs += logs;
+			localized
+		}).collect())
+	}
+
 	fn tree_route(&self, from: &H256, to: &H256) -> Option<TreeRoute> {
 		let chain = self.chain.read();
 		match chain.is_known(from) && chain.is_known(to) {
@@ -1340,17 +1612,46 @@ impl BlockChainClient for Client {
 	}
 
 	fn logs(&self, filter: Filter) -> Vec<LocalizedLogEntry> {
-		let blocks = filter.bloom_possibilities().iter()
-			.filter_map(|bloom| self.blocks_with_bloom(bloom, filter.from_block.clone(), filter.to_block.clone()))
-			.flat_map(|m| m)
-			// remove duplicate elements
-			.collect::<HashSet<u64>>()
-			.into_iter()
-			.collect::<Vec<u64>>();
+		let blocks = match (self.block_number(filter.from_block.clone()), self.block_number(filter.to_block.clone())) {
+			(Some(from), Some(to)) => self.chain.read().blocks_with_logs(&filter, from, to),
+			_ => Vec::new(),
+		};
 
 		self.chain.read().logs(blocks, |entry| filter.matches(entry), filter.limit)
 	}
 
+	fn logs_page(&self, filter: Filter, max_logs: usize) -> (Vec<LocalizedLogEntry>, Option<BlockNumber>) {
+		let (from, to) = match (self.block_number(filter.from_block.clone()), self.block_number(filter.to_block.clone())) {
+			(Some(from), Some(to)) => (from, to),
+			_ => return (Vec::new(), None),
+		};
+
+		let mut logs = Vec::new();
+		let mut start = from;
+		while start <= to {
+			let end = ::std::cmp::min(to, start + LOGS_CHUNK_SIZE - 1);
+			let mut chunk = filter.clone();
+			chunk.from_block = BlockId::Number(start);
+			chunk.to_block = BlockId::Number(end);
+			chunk.limit = None;
+			logs.extend(self.logs(chunk));
+
+			if logs.len() > max_logs {
+				// cut before the first block that doesn't fit, but always return at least one block.
+				let first = logs[0].block_number;
+				let cut = match logs[max_logs].block_number {
+					number if number == first => number + 1,
+					number => number,
+				};
+				logs.retain(|log| log.block_number < cut);
+				return (logs, if cut <= to { Some(cut) } else { None });
+			}
+			start = end + 1;
+		}
+
+		(logs, None)
+	}
+
 	fn filter_traces(&self, filter: TraceFilter) -> Option<Vec<LocalizedTrace>> {
 		let start = self.block_number(filter.range.start);
 		let end = self.block_number(filter.range.end);
@@ -1422,6 +1723,14 @@ impl BlockChainClient for Client {
 		self.miner.ready_transactions(number, timestamp)
 	}
 
+	fn local_pending_transactions(&self) -> Vec<H256> {
+		self.miner.local_transactions()
+			.into_iter()
+			.filter(|&(_, ref status)| *status == LocalTransactionStatus::Pending)
+			.map(|(hash, _)| hash)
+			.collect()
+	}
+
 	fn queue_consensus_message(&self, message: Bytes) {
 		let channel = self.io_channel.lock().clone();
 		if let Err(e) = channel.send(ClientIoMessage::NewMessage(message)) {
@@ -1450,6 +1759,28 @@ impl BlockChainClient for Client {
 		}
 	}
 
+	fn db_stats(&self) -> DbStats {
+		DbStats {
+			pruning: self.pruning.as_str().into(),
+			compaction: self.journal_compaction.lock().clone(),
+		}
+	}
+
+	fn evm_stats(&self) -> EvmStats {
+		self.factories.vm.stats()
+	}
+
+	fn database_stats(&self) -> DatabaseStats {
+		self.db.read().stats()
+	}
+
+	fn backup_database(&self, path: String) -> Result<(), String> {
+		info!("Backing up the database to {}", path);
+		self.db.read().backup(&path)?;
+		info!("Database backup complete");
+		Ok(())
+	}
+
 	fn call_contract(&self, block_id: BlockId, address: Address, data: Bytes) -> Result<Bytes, String> {
 		let from = Address::default();
 		let transaction = Transaction {
@@ -1618,21 +1949,32 @@ impl ::client::ProvingBlockChainClient for Client {
 	}
 
 	fn prove_transaction(&self, transaction: SignedTransaction, id: BlockId) -> Option<Vec<DBValue>> {
-		let (state, env_info) = match (self.state_at(id), self.env_info(id)) {
-			(Some(s), Some(e)) => (s, e),
-			_ => return None,
-		};
+		match self.execute_proving(&transaction, id) {
+			Ok((Err(ExecutionError::Internal(_)), _)) | Err(_) => None,
+			Ok((_, proof)) => Some(proof),
+		}
+	}
+
+	fn execute_with_proof(&self, transaction: SignedTransaction, id: BlockId) -> Result<(Executed, Vec<DBValue>), CallError> {
+		let (res, proof) = self.execute_proving(&transaction, id)?;
+		Ok((res?, proof))
+	}
+}
+
+impl Client {
+	// Execute a transaction at the given block over a `Proving` backend, returning the execution
+	// result and every trie node read while executing it.
+	fn execute_proving(&self, transaction: &SignedTransaction, id: BlockId) -> Result<(Result<Executed, ExecutionError>, Vec<DBValue>), CallError> {
+		let state = self.state_at(id).ok_or(CallError::StatePruned)?;
+		let env_info = self.env_info(id).ok_or(CallError::StatePruned)?;
 		let mut jdb = self.state_db.lock().journal_db().boxed_clone();
 		let backend = state::backend::Proving::new(jdb.as_hashdb_mut());
 
 		let mut state = state.replace_backend(backend);
-		let options = TransactOptions { tracing: false, vm_tracing: false, check_nonce: false };
-		let res = Executive::new(&mut state, &env_info, &*self.engine, &self.factories.vm).transact(&transaction, options);
+		let options = TransactOptions { tracing: false, vm_tracing: false, check_nonce: false, gas_profiling: false, struct_logging: None };
+		let res = Executive::new(&mut state, &env_info, &*self.engine, &self.factories.vm).transact(transaction, options);
 
-		match res {
-			Err(ExecutionError::Internal(_)) => return None,
-			_ => return Some(state.drop().1.extract_proof()),
-		}
+		Ok((res, state.drop().1.extract_proof()))
 	}
 }
 
@@ -1642,18 +1984,67 @@ impl Drop for Client {
 	}
 }
 
+// walk the whole state trie at `root`, with the storage trie and code of every account.
+fn verify_state(db: &HashDB, root: &H256) -> Result<(), String> {
+	let account_trie = TrieDB::new(db, root).map_err(|e| format!("state trie: {}", e))?;
+	for item in account_trie.iter().map_err(|e| format!("state trie: {}", e))? {
+		let (key, value) = item.map_err(|e| format!("state trie: {}", e))?;
+		let account: BasicAccount = UntrustedRlp::new(&value).as_val()
+			.map_err(|e| format!("undecodable account {}: {}", key.pretty(), e))?;
+
+		let account_db = AccountDB::from_hash(db, H256::from_slice(&key));
+		let storage_trie = TrieDB::new(&account_db, &account.storage_root)
+			.map_err(|e| format!("storage trie of {}: {}", key.pretty(), e))?;
+		for item in storage_trie.iter().map_err(|e| format!("storage trie of {}: {}", key.pretty(), e))? {
+			item.map_err(|e| format!("storage trie of {}: {}", key.pretty(), e))?;
+		}
+
+		if account.code_hash != SHA3_EMPTY && !account_db.contains(&account.code_hash) {
+			return Err(format!("missing code {} of {}", account.code_hash, key.pretty()));
+		}
+	}
+	Ok(())
+}
+
+// collect node statistics of the state trie at `root` and of all its storage tries.
+fn state_trie_stats(db: &HashDB, root: &H256) -> Result<(TrieStats, TrieStats), String> {
+	let mut accounts = TrieStats::default();
+	let mut storage = TrieStats::default();
+	accounts.add_trie(db, root).map_err(|e| format!("state trie: {}", e))?;
+
+	let account_trie = TrieDB::new(db, root).map_err(|e| format!("state trie: {}", e))?;
+	for item in account_trie.iter().map_err(|e| format!("state trie: {}", e))? {
+		let (key, value) = item.map_err(|e| format!("state trie: {}", e))?;
+		let account: BasicAccount = UntrustedRlp::new(&value).as_val()
+			.map_err(|e| format!("undecodable account {}: {}", key.pretty(), e))?;
+
+		if account.storage_root != SHA3_NULL_RLP {
+			let account_db = AccountDB::from_hash(db, H256::from_slice(&key));
+			storage.add_trie(&account_db, &account.storage_root)
+				.map_err(|e| format!("storage trie of {}: {}", key.pretty(), e))?;
+		}
+	}
+	Ok((accounts, storage))
+}
+
 /// Returns `LocalizedReceipt` given `LocalizedTransaction`
 /// and a vector of receipts from given block up to transaction index.
-fn transaction_receipt(mut tx: LocalizedTransaction, mut receipts: Vec<Receipt>) -> LocalizedReceipt {
+fn transaction_receipt(tx: LocalizedTransaction, mut receipts: Vec<Receipt>) -> LocalizedReceipt {
 	assert_eq!(receipts.len(), tx.transaction_index + 1, "All previous receipts are provided.");
 
-	let sender = tx.sender();
 	let receipt = receipts.pop().expect("Current receipt is provided; qed");
 	let prior_gas_used = match tx.transaction_index {
 		0 => 0.into(),
 		i => receipts.get(i - 1).expect("All previous receipts are provided; qed").gas_used,
 	};
 	let no_of_logs = receipts.into_iter().map(|receipt| receipt.logs.len()).sum::<usize>();
+	localize_receipt(tx, receipt, prior_gas_used, no_of_logs)
+}
+
+/// Localize a single receipt given the cumulative gas used and the number of logs
+/// emitted by the transactions preceding it in the block.
+fn localize_receipt(mut tx: LocalizedTransaction, receipt: Receipt, prior_gas_used: U256, no_of_logs: usize) -> LocalizedReceipt {
+	let sender = tx.sender();
 	let transaction_hash = tx.hash();
 	let block_hash = tx.block_hash;
 	let block_number = tx.block_number;
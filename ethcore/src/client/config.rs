@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::HashMap;
 use std::str::FromStr;
 use std::path::Path;
 use std::fmt::{Display, Formatter, Error as FmtError};
@@ -21,9 +22,12 @@ pub use std::time::Duration;
 pub use blockchain::Config as BlockChainConfig;
 pub use trace::Config as TraceConfig;
 pub use evm::VMType;
+pub use snapshot::SnapshotConfiguration;
 
 use verification::{VerifierType, QueueConfig};
-use util::{journaldb, CompactionProfile};
+use client::EstimateGasConfig;
+use util::{journaldb, CompactionProfile, CompressionType};
+use util::trie::NodeEncoding;
 
 /// Client state db compaction profile
 #[derive(Debug, PartialEq)]
@@ -111,6 +115,10 @@ pub struct ClientConfig {
 	pub vm_type: VMType,
 	/// Fat DB enabled?
 	pub fat_db: bool,
+	/// Record the preimages of secure trie keys?
+	pub key_preimages: bool,
+	/// How new state trie nodes are written.
+	pub trie_encoding: NodeEncoding,
 	/// The JournalDB ("pruning") algorithm to use.
 	pub pruning: journaldb::Algorithm,
 	/// The name of the client instance.
@@ -121,6 +129,8 @@ pub struct ClientConfig {
 	pub db_compaction: DatabaseCompactionProfile,
 	/// Should db have WAL enabled?
 	pub db_wal: bool,
+	/// Compression for specific db columns; others use the database default.
+	pub db_compression: HashMap<Option<u32>, CompressionType>,
 	/// Operating mode
 	pub mode: Mode,
 	/// The chain spec name
@@ -135,8 +145,20 @@ pub struct ClientConfig {
 	pub history: u64,
 	/// Ideal memory usage for state pruning history.
 	pub history_mem: usize,
+	/// Memory budget for the values of recent state nodes held in memory by the `OverlayRecent`
+	/// pruning algorithm. Least recently used nodes beyond it are read back from disk.
+	pub overlay_memory_budget: Option<usize>,
 	/// Check seal valididity on block import
 	pub check_seal: bool,
+	/// Snapshot creation options.
+	pub snapshot: SnapshotConfiguration,
+	/// Number of threads reading state ahead of block execution. 0 disables prefetching.
+	pub prefetch_threads: usize,
+	/// Number of threads executing independent transactions of a block in parallel. Experimental;
+	/// 0 executes transactions one by one.
+	pub parallel_exec_threads: usize,
+	/// Bounds of the search made to estimate the gas of calls.
+	pub estimate_gas: EstimateGasConfig,
 }
 
 #[cfg(test)]
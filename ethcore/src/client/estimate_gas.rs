@@ -0,0 +1,130 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Binary search for the gas a call needs, used by `eth_estimateGas`.
+
+use util::U256;
+
+/// Bounds of the `eth_estimateGas` search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EstimateGasConfig {
+	/// Most gas a call is executed with; calls which fail even with this much can't be estimated.
+	pub gas_cap: U256,
+	/// Most executions made after the initial one at the cap.
+	/// When reached, the least amount of gas seen to succeed is returned.
+	pub max_iterations: usize,
+}
+
+impl Default for EstimateGasConfig {
+	fn default() -> Self {
+		EstimateGasConfig {
+			gas_cap: 50_000_000.into(),
+			max_iterations: 24,
+		}
+	}
+}
+
+/// Search for the least gas a call succeeds with.
+///
+/// Seeded by an execution which succeeded with `upper` gas and consumed `seed` gas before refunds.
+/// The seed is tried first since it is usually enough; calls which need more than they consume,
+/// like those forwarding 63/64 of their gas to a subcall, are then found by bisection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GasSearch {
+	/// Most gas known or assumed to fail.
+	lower: U256,
+	/// Least gas known to succeed.
+	upper: U256,
+	seed: Option<U256>,
+	remaining: usize,
+}
+
+impl GasSearch {
+	/// Create a new search between `seed` and `upper`, giving up after `max_iterations` executions.
+	pub fn new(seed: U256, upper: U256, max_iterations: usize) -> Self {
+		let seed = ::std::cmp::min(seed, upper);
+		GasSearch {
+			lower: if seed.is_zero() { seed } else { seed - 1.into() },
+			upper: upper,
+			seed: if seed < upper { Some(seed) } else { None },
+			remaining: max_iterations,
+		}
+	}
+
+	/// Gas to execute the call with next, or `None` when the search is over.
+	pub fn next_gas(&mut self) -> Option<U256> {
+		if self.remaining == 0 || self.upper - self.lower <= 1.into() {
+			return None;
+		}
+
+		self.remaining -= 1;
+		match self.seed.take() {
+			Some(seed) => Some(seed),
+			None => Some(self.lower + (self.upper - self.lower) / 2.into()),
+		}
+	}
+
+	/// Record whether an execution with `gas` succeeded.
+	pub fn record(&mut self, gas: U256, success: bool) {
+		match success {
+			true => self.upper = gas,
+			false => self.lower = gas,
+		}
+	}
+
+	/// The current estimate: least gas known to succeed.
+	pub fn estimate(&self) -> U256 {
+		self.upper
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::U256;
+	use super::GasSearch;
+
+	fn run<F: Fn(U256) -> bool>(mut search: GasSearch, succeeds: F) -> (U256, usize) {
+		let mut executions = 0;
+		while let Some(gas) = search.next_gas() {
+			executions += 1;
+			search.record(gas, succeeds(gas));
+		}
+		(search.estimate(), executions)
+	}
+
+	#[test]
+	fn seed_is_tried_first() {
+		let s = GasSearch::new(21_000.into(), 50_000_000.into(), 24);
+		assert_eq!(run(s, |gas| gas >= 21_000.into()), (21_000.into(), 1));
+	}
+
+	#[test]
+	fn finds_gas_needed_above_consumption() {
+		// consumes 30_000 but needs 31_000 to pass the 63/64 check.
+		let s = GasSearch::new(30_000.into(), 1_000_000.into(), 24);
+		let (estimate, _) = run(s, |gas| gas >= 31_000.into());
+		assert_eq!(estimate, 31_000.into());
+	}
+
+	#[test]
+	fn stops_after_max_iterations() {
+		let s = GasSearch::new(30_000.into(), 1_000_000.into(), 3);
+		let (estimate, executions) = run(s, |gas| gas >= 31_000.into());
+		assert_eq!(executions, 3);
+		assert!(estimate >= 31_000.into());
+		assert!(estimate < 1_000_000.into());
+	}
+}
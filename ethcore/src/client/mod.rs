@@ -22,10 +22,13 @@ mod error;
 mod test_client;
 mod trace;
 mod client;
+mod prefetch;
+mod estimate_gas;
 
 pub use self::client::*;
 pub use self::config::{Mode, ClientConfig, DatabaseCompactionProfile, BlockChainConfig, VMType};
 pub use self::error::Error;
+pub use self::estimate_gas::{EstimateGasConfig, GasSearch};
 pub use self::test_client::{TestBlockChainClient, EachBlockWith};
 pub use self::chain_notify::ChainNotify;
 pub use self::traits::{BlockChainClient, MiningBlockChainClient, EngineClient};
@@ -35,7 +38,10 @@ pub use self::traits::ProvingBlockChainClient;
 pub use types::ids::*;
 pub use types::trace_filter::Filter as TraceFilter;
 pub use types::pruning_info::PruningInfo;
-pub use types::call_analytics::CallAnalytics;
+pub use types::db_stats::{DbStats, JournalCompaction};
+pub use types::evm_stats::EvmStats;
+pub use types::fee_history::FeeHistory;
+pub use types::call_analytics::{CallAnalytics, AccountOverride};
 
 pub use executive::{Executed, Executive, TransactOptions};
 pub use env_info::{LastHashes, EnvInfo};
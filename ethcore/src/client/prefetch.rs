@@ -0,0 +1,138 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Warming up state data ahead of block execution.
+//!
+//! Executing a block spends much of its time waiting on the database for accounts
+//! and code. Senders and recipients of queued transactions are known up front, so
+//! they are read on worker threads while the blocks execute, leaving the data in
+//! the database caches by the time execution needs it.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+
+use crossbeam::Scope;
+use factory::Factories;
+use state::State;
+use state_db::StateDB;
+use transaction::Action;
+use util::{Address, H256, U256};
+use verification::PreverifiedBlock;
+
+/// Accounts touched by the given blocks in order of first appearance, each flagged
+/// if it is called, which means its code and storage will likely be read as well.
+pub fn touched_accounts(blocks: &[PreverifiedBlock]) -> Vec<(Address, bool)> {
+	let mut positions = HashMap::new();
+	let mut touched = Vec::new();
+	{
+		let mut touch = |address: Address, called: bool| {
+			let position = *positions.entry(address).or_insert_with(|| {
+				touched.push((address, false));
+				touched.len() - 1
+			});
+			touched[position].1 |= called;
+		};
+
+		for block in blocks {
+			touch(*block.header.author(), false);
+			for tx in &block.transactions {
+				touch(tx.sender(), false);
+				if let Action::Call(ref to) = tx.action {
+					touch(*to, true);
+				}
+			}
+		}
+	}
+	touched
+}
+
+/// Spawn `threads` workers on `scope` which read the given accounts from the state
+/// with `root`, on top of the block `parent`. Accounts are dealt out round-robin so
+/// the earliest needed are fetched first. Workers give up once `stop` is set.
+pub fn spawn<'a>(
+	scope: &Scope<'a>,
+	db: &StateDB,
+	parent: &H256,
+	root: H256,
+	account_start_nonce: U256,
+	factories: &Factories,
+	accounts: &'a [(Address, bool)],
+	threads: usize,
+	stop: &'a AtomicBool,
+) {
+	for worker in 0..threads {
+		let db = db.boxed_clone_canon(parent);
+		let factories = factories.clone();
+		scope.spawn(move || {
+			let state = match State::from_existing(db, root, account_start_nonce, factories) {
+				Ok(state) => state,
+				Err(_) => return,
+			};
+
+			for (_, &(ref address, called)) in accounts.iter().enumerate().filter(|&(n, _)| n % threads == worker) {
+				if stop.load(AtomicOrdering::Relaxed) {
+					break;
+				}
+
+				// failures are met again, and reported, when the block is executed.
+				let _ = state.balance(address);
+				if called {
+					let _ = state.code(address);
+					// any lookup reads the storage root and the top of the storage trie.
+					let _ = state.storage_at(address, &H256::zero());
+				}
+			}
+		});
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::Address;
+	use transaction::{Transaction, Action};
+	use verification::PreverifiedBlock;
+	use header::Header;
+	use ethkey::{Generator, Random};
+	use super::touched_accounts;
+
+	#[test]
+	fn collects_touched_accounts() {
+		let keypair = Random.generate().unwrap();
+		let contract = Address::from(2);
+		let tx = |action| Transaction {
+			action: action,
+			nonce: 0.into(),
+			gas_price: 0.into(),
+			gas: 21000.into(),
+			value: 0.into(),
+			data: Vec::new(),
+		}.sign(keypair.secret(), None);
+
+		let mut header = Header::default();
+		header.set_author(Address::from(1));
+		let block = PreverifiedBlock {
+			header: header,
+			transactions: vec![tx(Action::Create), tx(Action::Call(contract)), tx(Action::Call(Address::from(1)))],
+			bytes: Vec::new(),
+		};
+
+		assert_eq!(touched_accounts(&[block]), vec![
+			(Address::from(1), true),
+			(keypair.address(), false),
+			(contract, true),
+		]);
+	}
+}
@@ -36,11 +36,13 @@ use receipt::{Receipt, LocalizedReceipt};
 use blockchain::extras::BlockReceipts;
 use error::{ImportResult, Error as EthcoreError};
 use evm::{Factory as EvmFactory, VMType, Schedule};
-use miner::{Miner, MinerService, TransactionImportResult};
+use miner::{Miner, MinerService, TransactionImportResult, LocalTransactionStatus};
 use spec::Spec;
 use types::basic_account::BasicAccount;
 use types::mode::Mode;
 use types::pruning_info::PruningInfo;
+use types::db_stats::DbStats;
+use types::evm_stats::EvmStats;
 
 use verification::queue::QueueInfo;
 use block::{OpenBlock, SealedBlock};
@@ -334,6 +336,25 @@ impl TestBlockChainClient {
 		self.insert_transaction_with_gas_price_to_queue(U256::from(20_000_000_000u64))
 	}
 
+	/// Inserts a locally submitted transaction to miners transactions queue.
+	pub fn insert_local_transaction_to_queue(&self) -> H256 {
+		let keypair = Random.generate().unwrap();
+		let tx = Transaction {
+			action: Action::Create,
+			value: U256::from(100),
+			data: "3331600055".from_hex().unwrap(),
+			gas: U256::from(100_000),
+			gas_price: U256::from(20_000_000_000u64),
+			nonce: U256::zero()
+		};
+		let signed_tx = tx.sign(keypair.secret(), None);
+		self.set_balance(signed_tx.sender(), 10_000_000_000_000_000_000u64.into());
+		let hash = signed_tx.hash();
+		let res = self.miner.import_own_transaction(self, signed_tx.into()).expect("Successful import");
+		assert_eq!(res, TransactionImportResult::Current);
+		hash
+	}
+
 	/// Set reported history size.
 	pub fn set_history(&self, h: Option<u64>) {
 		*self.history.write() = h;
@@ -341,12 +362,11 @@ impl TestBlockChainClient {
 }
 
 pub fn get_temp_state_db() -> GuardedTempResult<StateDB> {
-	let temp = RandomTempPath::new();
-	let db = Database::open(&DatabaseConfig::with_columns(NUM_COLUMNS), temp.as_str()).unwrap();
+	let db = in_memory(NUM_COLUMNS.unwrap_or(0));
 	let journal_db = journaldb::new(Arc::new(db), journaldb::Algorithm::EarlyMerge, COL_STATE);
 	let state_db = StateDB::new(journal_db, 1024 * 1024);
 	GuardedTempResult {
-		_temp: temp,
+		_temp: None,
 		result: Some(state_db)
 	}
 }
@@ -399,6 +419,10 @@ impl BlockChainClient for TestBlockChainClient {
 		Ok(21000.into())
 	}
 
+	fn call_many(&self, transactions: Vec<(SignedTransaction, CallAnalytics)>, _block: BlockId) -> Result<Vec<Executed>, CallError> {
+		transactions.iter().map(|_| self.execution_result.read().clone().unwrap()).collect()
+	}
+
 	fn replay(&self, _id: TransactionId, _analytics: CallAnalytics) -> Result<Executed, CallError> {
 		self.execution_result.read().clone().unwrap()
 	}
@@ -460,6 +484,10 @@ impl BlockChainClient for TestBlockChainClient {
 	fn list_storage(&self, _id: BlockId, _account: &Address, _after: Option<&H256>, _count: u64) -> Option<Vec<H256>> {
 		None
 	}
+
+	fn key_preimage(&self, _hash: &H256) -> Option<Bytes> {
+		None
+	}
 	fn transaction(&self, _id: TransactionId) -> Option<LocalizedTransaction> {
 		None	// Simple default.
 	}
@@ -480,6 +508,19 @@ impl BlockChainClient for TestBlockChainClient {
 		self.receipts.read().get(&id).cloned()
 	}
 
+	fn localized_block_receipts(&self, id: BlockId) -> Option<Vec<LocalizedReceipt>> {
+		let receipts = self.receipts.read();
+		let mut receipts: Vec<LocalizedReceipt> = receipts.iter()
+			.filter(|&(tx_id, _)| match *tx_id {
+				TransactionId::Location(ref block_id, _) => *block_id == id,
+				_ => false,
+			})
+			.map(|(_, receipt)| receipt.clone())
+			.collect();
+		receipts.sort_by_key(|receipt| receipt.transaction_index);
+		Some(receipts)
+	}
+
 	fn blocks_with_bloom(&self, _bloom: &H2048, _from_block: BlockId, _to_block: BlockId) -> Option<Vec<BlockNumber>> {
 		unimplemented!();
 	}
@@ -493,6 +534,18 @@ impl BlockChainClient for TestBlockChainClient {
 		}
 	}
 
+	fn logs_page(&self, filter: Filter, max_logs: usize) -> (Vec<LocalizedLogEntry>, Option<BlockNumber>) {
+		let mut logs = self.logs(Filter { limit: None, ..filter });
+		let cut = logs.get(max_logs).map(|log| log.block_number);
+		match cut {
+			Some(cut) if cut != logs[0].block_number => {
+				logs.retain(|log| log.block_number < cut);
+				(logs, Some(cut))
+			},
+			_ => (logs, None),
+		}
+	}
+
 	fn last_hashes(&self) -> LastHashes {
 		unimplemented!();
 	}
@@ -716,6 +769,14 @@ impl BlockChainClient for TestBlockChainClient {
 		self.miner.ready_transactions(info.best_block_number, info.best_block_timestamp)
 	}
 
+	fn local_pending_transactions(&self) -> Vec<H256> {
+		self.miner.local_transactions()
+			.into_iter()
+			.filter(|&(_, ref status)| *status == LocalTransactionStatus::Pending)
+			.map(|(hash, _)| hash)
+			.collect()
+	}
+
 	fn signing_network_id(&self) -> Option<u64> { None }
 
 	fn mode(&self) -> Mode { Mode::Active }
@@ -736,6 +797,25 @@ impl BlockChainClient for TestBlockChainClient {
 		}
 	}
 
+	fn db_stats(&self) -> DbStats {
+		DbStats {
+			pruning: "archive".into(),
+			compaction: None,
+		}
+	}
+
+	fn evm_stats(&self) -> EvmStats {
+		EvmStats::default()
+	}
+
+	fn database_stats(&self) -> DatabaseStats {
+		DatabaseStats::default()
+	}
+
+	fn backup_database(&self, _path: String) -> Result<(), String> {
+		Err("Test client has no database".into())
+	}
+
 	fn call_contract(&self, _id: BlockId, _address: Address, _data: Bytes) -> Result<Bytes, String> { Ok(vec![]) }
 
 	fn transact_contract(&self, address: Address, data: Bytes) -> Result<TransactionImportResult, EthcoreError> {
@@ -770,6 +850,10 @@ impl ProvingBlockChainClient for TestBlockChainClient {
 	fn prove_transaction(&self, _: SignedTransaction, _: BlockId) -> Option<Vec<DBValue>> {
 		None
 	}
+
+	fn execute_with_proof(&self, _: SignedTransaction, _: BlockId) -> Result<(Executed, Vec<DBValue>), CallError> {
+		self.execution_result.read().clone().unwrap().map(|executed| (executed, Vec::new()))
+	}
 }
 
 impl EngineClient for TestBlockChainClient {
@@ -17,6 +17,7 @@
 use std::collections::BTreeMap;
 use util::{U256, Address, H256, H2048, Bytes, Itertools};
 use util::hashdb::DBValue;
+use util::kvdb::DatabaseStats;
 use blockchain::TreeRoute;
 use verification::queue::QueueInfo as BlockQueueInfo;
 use block::{OpenBlock, SealedBlock};
@@ -41,6 +42,9 @@ use types::blockchain_info::BlockChainInfo;
 use types::block_status::BlockStatus;
 use types::mode::Mode;
 use types::pruning_info::PruningInfo;
+use types::db_stats::DbStats;
+use types::evm_stats::EvmStats;
+use types::fee_history::FeeHistory;
 use encoded;
 
 #[ipc(client_ident="RemoteClient")]
@@ -106,6 +110,14 @@ pub trait BlockChainClient : Sync + Send {
 			Therefore balance has returned Some; qed")
 	}
 
+	/// Get address nonce and balance at the latest block's state.
+	///
+	/// Used to validate incoming transactions; implementations should answer from
+	/// caches where possible rather than building a full state.
+	fn latest_nonce_and_balance(&self, address: &Address) -> (U256, U256) {
+		(self.latest_nonce(address), self.latest_balance(address))
+	}
+
 	/// Get value of the storage at given position at the given block's state.
 	///
 	/// May not return None if given BlockId::Latest.
@@ -127,6 +139,10 @@ pub trait BlockChainClient : Sync + Send {
 	/// If `after` is set the list starts with the following item.
 	fn list_storage(&self, id: BlockId, account: &Address, after: Option<&H256>, count: u64) -> Option<Vec<H256>>;
 
+	/// Get the preimage of a secure trie key: the address or storage key hashing to `hash`.
+	/// Only available for keys written while preimage recording was enabled.
+	fn key_preimage(&self, hash: &H256) -> Option<Bytes>;
+
 	/// Get transaction with given hash.
 	fn transaction(&self, id: TransactionId) -> Option<LocalizedTransaction>;
 
@@ -139,6 +155,9 @@ pub trait BlockChainClient : Sync + Send {
 	/// Get transaction receipt with given hash.
 	fn transaction_receipt(&self, id: TransactionId) -> Option<LocalizedReceipt>;
 
+	/// Get all receipts of the block with given id, in transaction order.
+	fn localized_block_receipts(&self, id: BlockId) -> Option<Vec<LocalizedReceipt>>;
+
 	/// Get a tree route between `from` and `to`.
 	/// See `BlockChain::tree_route`.
 	fn tree_route(&self, from: &H256, to: &H256) -> Option<TreeRoute>;
@@ -179,9 +198,17 @@ pub trait BlockChainClient : Sync + Send {
 	/// Returns logs matching given filter.
 	fn logs(&self, filter: Filter) -> Vec<LocalizedLogEntry>;
 
+	/// Returns logs matching given filter, scanning the range in chunks and stopping at
+	/// a block boundary once more than `max_logs` were found.
+	/// The filter limit is ignored. Also returns the first block left out, if any.
+	fn logs_page(&self, filter: Filter, max_logs: usize) -> (Vec<LocalizedLogEntry>, Option<BlockNumber>);
+
 	/// Makes a non-persistent transaction call.
 	fn call(&self, t: &SignedTransaction, block: BlockId, analytics: CallAnalytics) -> Result<Executed, CallError>;
 
+	/// Makes non-persistent transaction calls in order, each seeing the changes made by the ones before.
+	fn call_many(&self, transactions: Vec<(SignedTransaction, CallAnalytics)>, block: BlockId) -> Result<Vec<Executed>, CallError>;
+
 	/// Estimates how much gas will be necessary for a call.
 	fn estimate_gas(&self, t: &SignedTransaction, block: BlockId) -> Result<U256, CallError>;
 
@@ -212,6 +239,9 @@ pub trait BlockChainClient : Sync + Send {
 	/// List all transactions that are allowed into the next block.
 	fn ready_transactions(&self) -> Vec<PendingTransaction>;
 
+	/// Hashes of locally submitted transactions that are still pending in the queue.
+	fn local_pending_transactions(&self) -> Vec<H256>;
+
 	/// Sorted list of transaction gas prices from at least last sample_size blocks.
 	fn gas_price_corpus(&self, sample_size: usize) -> ::stats::Corpus<U256> {
 		let mut h = self.chain_info().best_block_hash;
@@ -233,6 +263,45 @@ pub trait BlockChainClient : Sync + Send {
 		corpus.into()
 	}
 
+	/// Gas usage and transaction gas price `percentiles` of at most `block_count` blocks ending
+	/// with `newest_block`. Returns `None` if the newest block is unknown.
+	fn fee_history(&self, block_count: usize, newest_block: BlockId, percentiles: &[f64]) -> Option<FeeHistory> {
+		let mut block = match self.block(newest_block) {
+			Some(block) => block,
+			None => return None,
+		};
+
+		let mut history = FeeHistory::default();
+		for _ in 0..block_count {
+			let (gas_used, gas_limit) = {
+				let header = block.header_view();
+				(header.gas_used(), header.gas_limit())
+			};
+			history.gas_used_ratio.push(match gas_limit.is_zero() {
+				true => 0.0,
+				false => gas_used.low_u64() as f64 / gas_limit.low_u64() as f64,
+			});
+
+			let corpus: ::stats::Corpus<U256> = block.transaction_views().iter().map(|t| t.gas_price()).collect();
+			history.gas_prices.push(percentiles.iter()
+				.map(|p| corpus.percentile(*p).cloned().unwrap_or_else(U256::zero))
+				.collect());
+			history.oldest_block = block.number();
+
+			if block.number() == 0 {
+				break;
+			}
+			block = match self.block(BlockId::Hash(block.parent_hash())) {
+				Some(block) => block,
+				None => break,
+			};
+		}
+
+		history.gas_used_ratio.reverse();
+		history.gas_prices.reverse();
+		Some(history)
+	}
+
 	/// Get the preferred network ID to sign on
 	fn signing_network_id(&self) -> Option<u64>;
 
@@ -261,6 +330,19 @@ pub trait BlockChainClient : Sync + Send {
 	/// Returns information about pruning/data availability.
 	fn pruning_info(&self) -> PruningInfo;
 
+	/// Returns statistics about the state database.
+	fn db_stats(&self) -> DbStats;
+
+	/// Returns statistics about the virtual machines used to run code.
+	fn evm_stats(&self) -> EvmStats;
+
+	/// Returns access statistics for the columns of the backing database.
+	fn database_stats(&self) -> DatabaseStats;
+
+	/// Copies a consistent image of the backing database to the given path, which
+	/// must not hold a database already. The client keeps running meanwhile.
+	fn backup_database(&self, path: String) -> Result<(), String>;
+
 	/// Like `call`, but with various defaults. Designed to be used for calling contracts.
 	fn call_contract(&self, id: BlockId, address: Address, data: Bytes) -> Result<Bytes, String>;
 
@@ -325,4 +407,9 @@ pub trait ProvingBlockChainClient: BlockChainClient {
 
 	/// Prove execution of a transaction at the given block.
 	fn prove_transaction(&self, transaction: SignedTransaction, id: BlockId) -> Option<Vec<DBValue>>;
+
+	/// Execute a transaction at the given block without checking its nonce, recording
+	/// every state trie node read. Returns the execution result along with the witness,
+	/// which is enough to re-execute the transaction with `state::check_proof`.
+	fn execute_with_proof(&self, transaction: SignedTransaction, id: BlockId) -> Result<(Executed, Vec<DBValue>), CallError>;
 }
@@ -38,8 +38,14 @@ pub const COL_TRACE: Option<u32> = Some(4);
 pub const COL_ACCOUNT_BLOOM: Option<u32> = Some(5);
 /// Column for general information from the local node which can persist.
 pub const COL_NODE_INFO: Option<u32> = Some(6);
+/// Column for the preimages of secure trie keys, if recorded.
+pub const COL_PREIMAGES: Option<u32> = Some(7);
+/// Column for the index of the blocks in whose traces an address appears.
+pub const COL_TRACE_INDEX: Option<u32> = Some(8);
+/// Column for the index of the blocks with logs of an address or topic, if enabled.
+pub const COL_LOG_INDEX: Option<u32> = Some(9);
 /// Number of columns in DB
-pub const NUM_COLUMNS: Option<u32> = Some(7);
+pub const NUM_COLUMNS: Option<u32> = Some(10);
 
 /// Modes for updating caches.
 #[derive(Clone, Copy)]
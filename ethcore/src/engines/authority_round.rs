@@ -235,8 +235,10 @@ impl Engine for AuthorityRound {
 		]
 	}
 
-	fn schedule(&self, _env_info: &EnvInfo) -> Schedule {
-		Schedule::new_post_eip150(usize::max_value(), true, true, true)
+	fn schedule(&self, env_info: &EnvInfo) -> Schedule {
+		let mut schedule = Schedule::new_post_eip150(usize::max_value(), true, true, true);
+		self.params().update_schedule(&mut schedule, env_info.number);
+		schedule
 	}
 
 	fn populate_from_parent(&self, header: &mut Header, parent: &Header, gas_floor_target: U256, _gas_ceil_target: U256) {
@@ -254,6 +256,10 @@ impl Engine for AuthorityRound {
 		});
 	}
 
+	fn gas_limit_bound_divisor(&self) -> Option<U256> {
+		Some(self.gas_limit_bound_divisor)
+	}
+
 	fn seals_internally(&self) -> Option<bool> {
 		Some(self.signer.address() != Address::default())
 	}
@@ -86,8 +86,10 @@ impl Engine for BasicAuthority {
 	/// Additional engine-specific information for the user/developer concerning `header`.
 	fn extra_info(&self, _header: &Header) -> BTreeMap<String, String> { map!["signature".to_owned() => "TODO".to_owned()] }
 
-	fn schedule(&self, _env_info: &EnvInfo) -> Schedule {
-		Schedule::new_homestead()
+	fn schedule(&self, env_info: &EnvInfo) -> Schedule {
+		let mut schedule = Schedule::new_homestead();
+		self.params().update_schedule(&mut schedule, env_info.number);
+		schedule
 	}
 
 	fn populate_from_parent(&self, header: &mut Header, parent: &Header, gas_floor_target: U256, _gas_ceil_target: U256) {
@@ -103,6 +105,10 @@ impl Engine for BasicAuthority {
 		});
 	}
 
+	fn gas_limit_bound_divisor(&self) -> Option<U256> {
+		Some(self.gas_limit_bound_divisor)
+	}
+
 	fn seals_internally(&self) -> Option<bool> {
 		Some(self.signer.address() != Address::default())
 	}
@@ -58,8 +58,10 @@ impl Engine for InstantSeal {
 		&self.builtins
 	}
 
-	fn schedule(&self, _env_info: &EnvInfo) -> Schedule {
-		Schedule::new_post_eip150(usize::max_value(), true, true, true)
+	fn schedule(&self, env_info: &EnvInfo) -> Schedule {
+		let mut schedule = Schedule::new_post_eip150(usize::max_value(), true, true, true);
+		self.params().update_schedule(&mut schedule, env_info.number);
+		schedule
 	}
 
 	fn seals_internally(&self) -> Option<bool> { Some(true) }
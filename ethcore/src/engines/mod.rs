@@ -42,7 +42,7 @@ use env_info::EnvInfo;
 use error::Error;
 use spec::CommonParams;
 use evm::Schedule;
-use header::Header;
+use header::{Header, BlockNumber};
 use transaction::{UnverifiedTransaction, SignedTransaction};
 use client::Client;
 
@@ -120,6 +120,11 @@ pub trait Engine : Sync + Send {
 	fn maximum_uncle_count(&self) -> usize { 2 }
 	/// The number of generations back that uncles can be.
 	fn maximum_uncle_age(&self) -> usize { 6 }
+	/// Reward of the block author, not including transaction fees and uncle inclusion rewards.
+	fn block_reward(&self) -> U256 { U256::zero() }
+	/// Rewards for including `uncle` in a block with given `number`,
+	/// for the block author and for the uncle author respectively.
+	fn uncle_rewards(&self, _number: BlockNumber, _uncle: &Header) -> (U256, U256) { (U256::zero(), U256::zero()) }
 	/// The nonce with which accounts begin.
 	fn account_start_nonce(&self) -> U256 { self.params().account_start_nonce }
 
@@ -185,6 +190,10 @@ pub trait Engine : Sync + Send {
 		header.set_gas_limit(parent.gas_limit().clone());
 	}
 
+	/// Bound on the gas limit change between consecutive blocks, as a divisor of the parent's gas limit.
+	/// `None` if the engine doesn't limit the change.
+	fn gas_limit_bound_divisor(&self) -> Option<U256> { None }
+
 	/// Handle any potential consensus messages;
 	/// updating consensus state and potentially issuing a new one.
 	fn handle_message(&self, _message: &[u8]) -> Result<(), Error> { Err(EngineError::UnexpectedMessage.into()) }
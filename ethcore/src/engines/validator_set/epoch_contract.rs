@@ -0,0 +1,282 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+/// Validator set maintained in a contract and read only at epoch transitions.
+/// Changes made during an epoch take effect at the next transition block, which makes
+/// the set of any block provable with an execution proof of `getValidators` at its transition.
+
+use std::sync::Weak;
+use ethabi;
+use util::*;
+use util::cache::MemoryLruCache;
+use types::ids::BlockId;
+use client::{Client, BlockChainClient, ProvingBlockChainClient, EnvInfo};
+use engines::Engine;
+use header::{Header, BlockNumber};
+use state::{self, ProvedExecution};
+use transaction::{Transaction, Action, SignedTransaction};
+use super::ValidatorSet;
+use super::simple_list::SimpleList;
+use super::safe_contract::{CONTRACT_INTERFACE, GET_VALIDATORS};
+
+const MEMOIZE_CAPACITY: usize = 500;
+
+/// The validator contract should have the same interface as the `safeContract` one.
+pub struct ValidatorEpochContract {
+	pub address: Address,
+	epoch_length: BlockNumber,
+	/// Validators of epochs by the hash of their transition block.
+	validators: RwLock<MemoryLruCache<H256, SimpleList>>,
+	/// Transition blocks by the hash of the parent block.
+	transitions: RwLock<MemoryLruCache<H256, H256>>,
+	client: RwLock<Option<Weak<Client>>>,
+}
+
+impl ValidatorEpochContract {
+	pub fn new(contract_address: Address, epoch_length: BlockNumber) -> Self {
+		assert!(epoch_length > 0, "Epoch length has to be greater than 0.");
+		ValidatorEpochContract {
+			address: contract_address,
+			epoch_length: epoch_length,
+			validators: RwLock::new(MemoryLruCache::new(MEMOIZE_CAPACITY)),
+			transitions: RwLock::new(MemoryLruCache::new(MEMOIZE_CAPACITY)),
+			client: RwLock::new(None),
+		}
+	}
+
+	fn client(&self) -> Option<Arc<Client>> {
+		self.client.read().as_ref().and_then(|c| c.upgrade())
+	}
+
+	fn get_validators() -> ethabi::Function {
+		let contract = ethabi::Contract::new(ethabi::Interface::load(CONTRACT_INTERFACE).expect("JSON interface is valid; qed"));
+		contract.function(GET_VALIDATORS.into()).expect("Method name is valid; qed")
+	}
+
+	fn decode_validators(output: Vec<u8>) -> Result<Vec<Address>, String> {
+		let mut tokens = Self::get_validators().decode_output(output).map_err(|e| format!("{:?}", e))?;
+		tokens.pop()
+			.and_then(|token| token.to_array())
+			.and_then(|addresses| addresses.into_iter().map(|a| a.to_address()).collect::<Option<Vec<[u8; 20]>>>())
+			.map(|addresses| addresses.into_iter().map(Address::from).collect())
+			.ok_or_else(|| "getValidators returned an invalid list".to_owned())
+	}
+
+	/// Call of `getValidators` used in proofs; the same transaction is executed when checking them.
+	fn proof_transaction(&self, engine: &Engine, gas_limit: U256) -> SignedTransaction {
+		let data = Self::get_validators().encode_call(vec![]).expect("get_validators does not take any arguments; qed");
+		Transaction {
+			nonce: engine.account_start_nonce(),
+			action: Action::Call(self.address.clone()),
+			gas: gas_limit,
+			gas_price: U256::zero(),
+			value: U256::zero(),
+			data: data,
+		}.fake_sign(Address::default())
+	}
+
+	/// Finds the transition block of the epoch the child of `parent_hash` belongs to.
+	fn transition_hash(&self, client: &Client, parent_hash: &H256) -> Option<H256> {
+		let mut hash = parent_hash.clone();
+		let transition = loop {
+			let known = self.transitions.write().get_mut(&hash).map(|h| h.clone());
+			if let Some(transition) = known {
+				break transition;
+			}
+			let header = match client.block_header(BlockId::Hash(hash)) {
+				Some(header) => header,
+				None => return None,
+			};
+			if header.number() % self.epoch_length == 0 {
+				break hash;
+			}
+			hash = header.parent_hash();
+		};
+		self.transitions.write().insert(parent_hash.clone(), transition.clone());
+		Some(transition)
+	}
+
+	/// Applies `f` to the validators of the epoch the child of `parent_hash` belongs to.
+	fn with_epoch_set<F, T>(&self, parent_hash: &H256, default: T, f: F) -> T where F: FnOnce(&SimpleList) -> T {
+		let client = match self.client() {
+			Some(client) => client,
+			None => {
+				warn!(target: "engine", "Set of validators could not be read: no client.");
+				return default;
+			},
+		};
+		let transition = match self.transition_hash(&*client, parent_hash) {
+			Some(transition) => transition,
+			None => {
+				debug!(target: "engine", "Set of validators could not be read: unknown block {}", parent_hash);
+				return default;
+			},
+		};
+
+		let mut guard = self.validators.write();
+		if let Some(list) = guard.get_mut(&transition) {
+			return f(list);
+		}
+		let data = Self::get_validators().encode_call(vec![]).expect("get_validators does not take any arguments; qed");
+		match client.call_contract(BlockId::Hash(transition), self.address.clone(), data).and_then(Self::decode_validators) {
+			Ok(validators) => {
+				debug!(target: "engine", "Set of validators for epoch starting at {} obtained: {:?}", transition, validators);
+				let list = SimpleList::new(validators);
+				let result = f(&list);
+				guard.insert(transition, list);
+				result
+			},
+			Err(e) => {
+				debug!(target: "engine", "Set of validators could not be read: {}", e);
+				default
+			},
+		}
+	}
+}
+
+impl ValidatorSet for ValidatorEpochContract {
+	fn contains(&self, parent_hash: &H256, address: &Address) -> bool {
+		self.with_epoch_set(parent_hash, false, |list| list.contains(parent_hash, address))
+	}
+
+	fn get(&self, parent_hash: &H256, nonce: usize) -> Address {
+		self.with_epoch_set(parent_hash, Default::default(), |list| list.get(parent_hash, nonce))
+	}
+
+	fn count(&self, parent_hash: &H256) -> usize {
+		self.with_epoch_set(parent_hash, usize::max_value(), |list| list.count(parent_hash))
+	}
+
+	fn register_contract(&self, client: Weak<Client>) {
+		trace!(target: "engine", "Setting up contract caller.");
+		*self.client.write() = Some(client);
+	}
+
+	fn epoch_proof(&self, transition_hash: &H256) -> Result<Vec<DBValue>, String> {
+		let client = self.client().ok_or_else(|| "No client!".to_owned())?;
+		let header = client.block_header(BlockId::Hash(*transition_hash)).ok_or_else(|| "Unknown transition block".to_owned())?;
+		if header.number() % self.epoch_length != 0 {
+			return Err(format!("Block {} is not an epoch transition", header.number()));
+		}
+		let transaction = self.proof_transaction(client.engine(), header.gas_limit());
+		client.prove_transaction(transaction, BlockId::Hash(*transition_hash))
+			.ok_or_else(|| "State of the transition block is not available".to_owned())
+	}
+
+	fn check_epoch_proof(&self, engine: &Engine, transition: &Header, proof: &[DBValue]) -> Result<Vec<Address>, String> {
+		if transition.number() % self.epoch_length != 0 {
+			return Err(format!("Block {} is not an epoch transition", transition.number()));
+		}
+		let env_info = EnvInfo {
+			number: transition.number(),
+			author: *transition.author(),
+			timestamp: transition.timestamp(),
+			difficulty: *transition.difficulty(),
+			last_hashes: Arc::new(Vec::new()),
+			gas_used: U256::zero(),
+			gas_limit: *transition.gas_limit(),
+		};
+		let transaction = self.proof_transaction(engine, *transition.gas_limit());
+		match state::check_proof(proof, *transition.state_root(), &transaction, engine, &env_info) {
+			ProvedExecution::Complete(executed) => Self::decode_validators(executed.output),
+			ProvedExecution::Failed(e) => Err(format!("getValidators call failed: {}", e)),
+			ProvedExecution::BadProof => Err("Invalid epoch proof".into()),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::*;
+	use types::ids::BlockId;
+	use spec::Spec;
+	use account_provider::AccountProvider;
+	use transaction::{Transaction, Action};
+	use client::{BlockChainClient, EngineClient};
+	use ethkey::Secret;
+	use miner::MinerService;
+	use tests::helpers::generate_dummy_client_with_spec_and_accounts;
+	use super::super::ValidatorSet;
+	use super::ValidatorEpochContract;
+
+	#[test]
+	fn changes_validators_at_epoch_transitions() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let s0 = Secret::from_slice(&"1".sha3()).unwrap();
+		let v0 = tap.insert_account(s0.clone(), "").unwrap();
+		let v1 = tap.insert_account(Secret::from_slice(&"0".sha3()).unwrap(), "").unwrap();
+		let client = generate_dummy_client_with_spec_and_accounts(Spec::new_validator_epoch_contract, Some(tap));
+		client.engine().register_client(Arc::downgrade(&client));
+		let validator_contract = Address::from_str("0000000000000000000000000000000000000005").unwrap();
+		let vc = ValidatorEpochContract::new(validator_contract, 2);
+		vc.register_contract(Arc::downgrade(&client));
+
+		client.miner().set_engine_signer(v1, "".into()).unwrap();
+		// Remove "1" validator in the first block of the epoch.
+		let tx = Transaction {
+			nonce: 0.into(),
+			gas_price: 0.into(),
+			gas: 500_000.into(),
+			action: Action::Call(validator_contract),
+			value: 0.into(),
+			data: "bfc708a000000000000000000000000082a978b3f5962a5b0957d9ee9eef472ee55b42f1".from_hex().unwrap(),
+		}.sign(&s0, None);
+		client.miner().import_own_transaction(client.as_ref(), tx.into()).unwrap();
+		client.update_sealing();
+		assert_eq!(client.chain_info().best_block_number, 1);
+
+		// Removed validator is still able to seal until the end of the epoch.
+		let tx = Transaction {
+			nonce: 1.into(),
+			gas_price: 0.into(),
+			gas: 21000.into(),
+			action: Action::Call(Address::default()),
+			value: 0.into(),
+			data: Vec::new(),
+		}.sign(&s0, None);
+		client.miner().import_own_transaction(client.as_ref(), tx.into()).unwrap();
+		client.update_sealing();
+		assert_eq!(client.chain_info().best_block_number, 2);
+
+		// Block 2 is a transition to the new set.
+		let tx = Transaction {
+			nonce: 2.into(),
+			gas_price: 0.into(),
+			gas: 21000.into(),
+			action: Action::Call(Address::default()),
+			value: 0.into(),
+			data: Vec::new(),
+		}.sign(&s0, None);
+		client.miner().import_own_transaction(client.as_ref(), tx.into()).unwrap();
+		client.update_sealing();
+		assert_eq!(client.chain_info().best_block_number, 2);
+		client.miner().set_engine_signer(v0, "".into()).unwrap();
+		client.update_sealing();
+		assert_eq!(client.chain_info().best_block_number, 3);
+
+		let first = client.block_hash(BlockId::Number(1)).unwrap();
+		let transition = client.block_hash(BlockId::Number(2)).unwrap();
+		assert!(vc.contains(&first, &v1));
+		assert!(!vc.contains(&transition, &v1));
+		assert!(vc.contains(&transition, &v0));
+
+		// The new set is provable from the transition block.
+		let proof = vc.epoch_proof(&transition).unwrap();
+		let header = client.block_header(BlockId::Hash(transition)).unwrap().decode();
+		assert_eq!(vc.check_epoch_proof(client.engine(), &header, &proof), Ok(vec![v0]));
+		assert!(vc.epoch_proof(&first).is_err());
+	}
+}
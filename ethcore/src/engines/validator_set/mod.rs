@@ -20,15 +20,19 @@ mod simple_list;
 mod safe_contract;
 mod contract;
 mod multi;
+mod epoch_contract;
 
 use std::sync::Weak;
-use util::{Address, H256};
+use util::{Address, H256, DBValue};
 use ethjson::spec::ValidatorSet as ValidatorSpec;
 use client::Client;
+use engines::Engine;
+use header::Header;
 use self::simple_list::SimpleList;
 use self::contract::ValidatorContract;
 use self::safe_contract::ValidatorSafeContract;
 use self::multi::Multi;
+use self::epoch_contract::ValidatorEpochContract;
 
 /// Creates a validator set from spec.
 pub fn new_validator_set(spec: ValidatorSpec) -> Box<ValidatorSet> {
@@ -39,6 +43,9 @@ pub fn new_validator_set(spec: ValidatorSpec) -> Box<ValidatorSet> {
 		ValidatorSpec::Multi(sequence) => Box::new(
 			Multi::new(sequence.into_iter().map(|(block, set)| (block.into(), new_validator_set(set))).collect())
 		),
+		ValidatorSpec::EpochContract(contract) => Box::new(
+			ValidatorEpochContract::new(contract.address.into(), contract.epoch_length.into())
+		),
 	}
 }
 
@@ -55,4 +62,13 @@ pub trait ValidatorSet: Send + Sync {
 	fn report_benign(&self, _validator: &Address) {}
 	/// Allows blockchain state access.
 	fn register_contract(&self, _client: Weak<Client>) {}
+	/// Proves the validator set taking effect after the given epoch transition block.
+	fn epoch_proof(&self, _transition_hash: &H256) -> Result<Vec<DBValue>, String> {
+		Err("Validator set does not change at epoch transitions.".into())
+	}
+	/// Checks the proof generated by `epoch_proof` against the header of the transition block.
+	/// Returns validators of the epoch following the transition.
+	fn check_epoch_proof(&self, _engine: &Engine, _transition: &Header, _proof: &[DBValue]) -> Result<Vec<Address>, String> {
+		Err("Validator set does not change at epoch transitions.".into())
+	}
 }
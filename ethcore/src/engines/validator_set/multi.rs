@@ -18,10 +18,11 @@
 
 use std::collections::BTreeMap;
 use std::sync::Weak;
-use util::{H256, Address, RwLock};
+use util::{H256, Address, RwLock, DBValue};
 use ids::BlockId;
-use header::BlockNumber;
+use header::{BlockNumber, Header};
 use client::{Client, BlockChainClient};
+use engines::Engine;
 use super::ValidatorSet;
 
 type BlockNumberLookup = Box<Fn(&H256) -> Result<BlockNumber, String> + Send + Sync + 'static>;
@@ -90,6 +91,18 @@ impl ValidatorSet for Multi {
 		}
 	}
 
+	fn epoch_proof(&self, transition_hash: &H256) -> Result<Vec<DBValue>, String> {
+		self.correct_set(transition_hash)
+			.ok_or_else(|| "Unknown transition block".to_owned())
+			.and_then(|set| set.epoch_proof(transition_hash))
+	}
+
+	fn check_epoch_proof(&self, engine: &Engine, transition: &Header, proof: &[DBValue]) -> Result<Vec<Address>, String> {
+		self.correct_set(&transition.hash())
+			.ok_or_else(|| "Unknown transition block".to_owned())
+			.and_then(|set| set.check_epoch_proof(engine, transition, proof))
+	}
+
 	fn register_contract(&self, client: Weak<Client>) {
 		for set in self.sets.values() {
 			set.register_contract(client.clone());
@@ -38,8 +38,13 @@ pub enum TransactionError {
 	/// Transaction is not valid anymore (state already has higher nonce)
 	Old,
 	/// Transaction has too low fee
-	/// (there is already a transaction with the same sender-nonce but higher gas price)
-	TooCheapToReplace,
+	/// (there is already a transaction with the same sender-nonce and the gas price increase is not sufficient)
+	TooCheapToReplace {
+		/// Minimal gas price required to replace the existing transaction
+		minimal: U256,
+		/// Transaction gas price
+		got: U256,
+	},
 	/// Transaction was not imported to the queue because limit has been reached.
 	LimitReached,
 	/// Transaction's gas price is below threshold.
@@ -88,7 +93,8 @@ impl fmt::Display for TransactionError {
 		let msg = match *self {
 			AlreadyImported => "Already imported".into(),
 			Old => "No longer valid".into(),
-			TooCheapToReplace => "Gas price too low to replace".into(),
+			TooCheapToReplace { minimal, got } =>
+				format!("Gas price too low to replace. Min={}, Given={}", minimal, got),
 			LimitReached => "Transaction limit reached".into(),
 			InsufficientGasPrice { minimal, got } =>
 				format!("Insufficient gas price. Min={}, Given={}", minimal, got),
@@ -20,7 +20,7 @@ use block::*;
 use builtin::Builtin;
 use env_info::EnvInfo;
 use error::{BlockError, TransactionError, Error};
-use header::Header;
+use header::{Header, BlockNumber};
 use state::CleanupMode;
 use spec::CommonParams;
 use transaction::UnverifiedTransaction;
@@ -158,6 +158,13 @@ impl Engine for Ethash {
 	fn params(&self) -> &CommonParams { &self.params }
 	fn additional_params(&self) -> HashMap<String, String> { hash_map!["registrar".to_owned() => self.ethash_params.registrar.hex()] }
 
+	fn block_reward(&self) -> U256 { self.ethash_params.block_reward }
+
+	fn uncle_rewards(&self, number: BlockNumber, uncle: &Header) -> (U256, U256) {
+		let reward = self.ethash_params.block_reward;
+		(reward / U256::from(32), reward * U256::from(8 + uncle.number() - number) / U256::from(8))
+	}
+
 	fn builtins(&self) -> &BTreeMap<Address, Builtin> {
 		&self.builtins
 	}
@@ -170,7 +177,7 @@ impl Engine for Ethash {
 	fn schedule(&self, env_info: &EnvInfo) -> Schedule {
 		trace!(target: "client", "Creating schedule. fCML={}, bGCML={}", self.ethash_params.homestead_transition, self.ethash_params.eip150_transition);
 
-		if env_info.number < self.ethash_params.homestead_transition {
+		let mut schedule = if env_info.number < self.ethash_params.homestead_transition {
 			Schedule::new_frontier()
 		} else if env_info.number < self.ethash_params.eip150_transition {
 			Schedule::new_homestead()
@@ -181,7 +188,9 @@ impl Engine for Ethash {
 				env_info.number >= self.ethash_params.eip161abc_transition,
 				env_info.number >= self.ethash_params.eip161d_transition
 			)
-		}
+		};
+		self.params().update_schedule(&mut schedule, env_info.number);
+		schedule
 	}
 
 	fn signing_network_id(&self, env_info: &EnvInfo) -> Option<u64> {
@@ -231,6 +240,10 @@ impl Engine for Ethash {
 //		info!("ethash: populate_from_parent #{}: difficulty={} and gas_limit={}", header.number(), header.difficulty(), header.gas_limit());
 	}
 
+	fn gas_limit_bound_divisor(&self) -> Option<U256> {
+		Some(self.ethash_params.gas_limit_bound_divisor)
+	}
+
 	fn on_new_block(&self, block: &mut ExecutedBlock) {
 		if block.fields().header.number() == self.ethash_params.dao_hardfork_transition {
 			// TODO: enable trigger function maybe?
@@ -544,11 +557,14 @@ mod tests {
 		let mut uncle = Header::new();
 		let uncle_author: Address = "ef2d6d194084c2de36e0dabfce45d046b37d1106".into();
 		uncle.set_author(uncle_author);
+		let (inclusion_reward, uncle_reward) = engine.uncle_rewards(1, &uncle);
 		b.push_uncle(uncle).unwrap();
 
 		let b = b.close();
 		assert_eq!(b.state().balance(&Address::zero()).unwrap(), "478eae0e571ba000".into());
 		assert_eq!(b.state().balance(&uncle_author).unwrap(), "3cb71f51fc558000".into());
+		assert_eq!(engine.block_reward() + inclusion_reward, "478eae0e571ba000".into());
+		assert_eq!(uncle_reward, "3cb71f51fc558000".into());
 	}
 
 	#[test]
@@ -118,6 +118,36 @@ fn mem_gas_calculation_increasing(gas: U256, b: &mut Bencher) {
 	});
 }
 
+#[bench]
+fn memory_per_frame_fresh(b: &mut Bencher) {
+	memory_per_frame(Factory::new(VMType::Interpreter, 1024 * 32), b)
+}
+
+#[bench]
+fn memory_per_frame_pooled(b: &mut Bencher) {
+	memory_per_frame(Factory::new(VMType::Interpreter, 1024 * 32).with_memory_pool(), b)
+}
+
+// many short-lived frames touching 16KB of memory each, like the calls of a busy block.
+fn memory_per_frame(factory: Factory, b: &mut Bencher) {
+	let mut ext = FakeExt::new();
+	let address = Address::from_str("0f572e5295c57f15886f9b263e2f6d2d6c7b5ec6").unwrap();
+	// mstore(0x4000, 1)
+	let code = Arc::new(black_box("600161400052".from_hex().unwrap()));
+
+	b.iter(|| {
+		for _ in 0..64 {
+			let mut params = ActionParams::default();
+			params.address = address.clone();
+			params.gas = U256::from(100_000);
+			params.code = Some(code.clone());
+
+			let mut vm = factory.create(params.gas);
+			result(vm.exec(params, &mut ext));
+		}
+	});
+}
+
 fn result(r: evm::Result<evm::GasLeft>) -> U256 {
 	match r {
 		Ok(evm::GasLeft::Known(v)) => v,
@@ -59,6 +59,18 @@ pub enum Error {
 		/// What was the stack limit
 		limit: usize
 	},
+	/// When a message call or contract creation would exceed the maximum call depth
+	OutOfDepth {
+		/// What was the call depth limit
+		limit: usize
+	},
+	/// `Reverted` is returned when execution hit a `REVERT` instruction.
+	/// State changes are reverted like for any other exception, but the
+	/// remaining gas is not consumed and the output holds the revert data.
+	Reverted {
+		/// Gas left when the instruction was executed.
+		gas_left: U256,
+	},
 	/// Returned on evm internal error. Should never be ignored during development.
 	/// Likely to cause consensus issues.
 	Internal(String),
@@ -78,7 +90,9 @@ impl fmt::Display for Error {
 			BadJumpDestination { .. } => "Bad jump destination",
 			BadInstruction { .. } => "Bad instruction",
 			StackUnderflow { .. } => "Stack underflow",
-			OutOfStack { .. } => "Out of stack",
+			OutOfStack { limit, .. } => return write!(f, "Out of stack (limit {})", limit),
+			OutOfDepth { limit } => return write!(f, "Out of call depth (limit {})", limit),
+			Reverted { .. } => "Reverted",
 			Internal(ref msg) => msg,
 		};
 		message.fmt(f)
@@ -96,6 +110,8 @@ pub enum GasLeft<'a> {
 	Known(U256),
 	/// Return instruction must be processed.
 	NeedsReturn(U256, &'a [u8]),
+	/// Revert instruction must be processed.
+	NeedsRevert(U256, &'a [u8]),
 }
 
 /// Types that can be "finalized" using an EVM.
@@ -112,6 +128,7 @@ impl<'a> Finalize for Result<GasLeft<'a>> {
 		match self {
 			Ok(GasLeft::Known(gas)) => Ok(gas),
 			Ok(GasLeft::NeedsReturn(gas, ret_code)) => ext.ret(&gas, ret_code),
+			Ok(GasLeft::NeedsRevert(gas, data)) => ext.revert(&gas, data),
 			Err(err) => Err(err),
 		}
 	}
@@ -21,11 +21,23 @@ use evm::{self, Schedule};
 use env_info::*;
 use types::executed::CallType;
 
+/// Specifies how an address is calculated for a new contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CreateContractAddress {
+	/// Address is calculated from the sender's address and nonce. `CREATE`.
+	FromSenderAndNonce,
+	/// Address is calculated from the sender's address, the given salt and the hash of the init code. `CREATE2`.
+	FromSenderSaltAndCodeHash(H256),
+}
+
 /// Result of externalities create function.
 pub enum ContractCreateResult {
 	/// Returned when creation was successfull.
 	/// Contains an address of newly created contract and gas left.
 	Created(Address, U256),
+	/// Returned when contract creation was reverted by `REVERT`.
+	/// Contains gas left.
+	Reverted(U256),
 	/// Returned when contract creation failed.
 	/// VM doesn't have to know the reason.
 	Failed
@@ -36,6 +48,9 @@ pub enum MessageCallResult {
 	/// Returned when message call was successfull.
 	/// Contains gas left.
 	Success(U256),
+	/// Returned when message call was reverted by `REVERT`.
+	/// Contains gas left.
+	Reverted(U256),
 	/// Returned when message call failed.
 	/// VM doesn't have to know the reason.
 	Failed
@@ -68,7 +83,7 @@ pub trait Ext {
 	/// Creates new contract.
 	///
 	/// Returns gas_left and contract address if contract creation was succesfull.
-	fn create(&mut self, gas: &U256, value: &U256, code: &[u8]) -> ContractCreateResult;
+	fn create(&mut self, gas: &U256, value: &U256, code: &[u8], address: CreateContractAddress) -> ContractCreateResult;
 
 	/// Message call.
 	///
@@ -100,6 +115,12 @@ pub trait Ext {
 	/// Returns gas_left if cost of returning the data is not too high.
 	fn ret(self, gas: &U256, data: &[u8]) -> evm::Result<U256> where Self: Sized;
 
+	/// Should be called when transaction calls `REVERT` opcode.
+	/// Makes the data available to the caller and returns `Reverted` with the gas left.
+	fn revert(self, gas: &U256, _data: &[u8]) -> evm::Result<U256> where Self: Sized {
+		Err(evm::Error::Reverted { gas_left: *gas })
+	}
+
 	/// Should be called when contract commits suicide.
 	/// Address to which funds should be refunded.
 	fn suicide(&mut self, refund_address: &Address) -> trie::Result<()> ;
@@ -19,9 +19,21 @@
 //! TODO: consider spliting it into two separate files.
 use std::fmt;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use evm::Evm;
-use util::{U256, Uint};
-use super::interpreter::SharedCache;
+use action_params::ActionParams;
+use lru_cache::LruCache;
+use types::evm_stats::EvmStats;
+use util::{U256, Uint, H256, Mutex};
+use super::interpreter::{Interpreter, MemoryPool, SharedCache};
+
+// in auto mode, a contract is run by the JIT once it has been run this many times,
+const JIT_MIN_RUNS: usize = 16;
+// provided it has at least this much code, as compiling small contracts doesn't pay off.
+#[cfg(feature = "jit")]
+const JIT_MIN_CODE_SIZE: usize = 4 * 1024;
+// number of large contracts whose runs are counted.
+const TRACKED_CONTRACTS: usize = 4096;
 
 #[derive(Debug, PartialEq, Clone)]
 /// Type of EVM to use.
@@ -30,7 +42,10 @@ pub enum VMType {
 	#[cfg(feature = "jit")]
 	Jit,
 	/// RUST EVM
-	Interpreter
+	Interpreter,
+	/// JIT EVM for large contracts which are run often, RUST EVM otherwise
+	#[cfg(feature = "jit")]
+	Auto,
 }
 
 impl fmt::Display for VMType {
@@ -38,7 +53,8 @@ impl fmt::Display for VMType {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 		write!(f, "{}", match *self {
 			VMType::Jit => "JIT",
-			VMType::Interpreter => "INT"
+			VMType::Interpreter => "INT",
+			VMType::Auto => "AUTO",
 		})
 	}
 	#[cfg(not(feature="jit"))]
@@ -79,6 +95,36 @@ impl VMType {
 	pub fn jit() -> Option<Self> {
 		Some(VMType::Jit)
 	}
+
+	/// Return new auto selection if it's possible
+	#[cfg(not(feature = "jit"))]
+	pub fn auto() -> Option<Self> {
+		None
+	}
+
+	/// Return new auto selection if it's possible
+	#[cfg(feature = "jit")]
+	pub fn auto() -> Option<Self> {
+		Some(VMType::Auto)
+	}
+}
+
+/// Counts of the VMs created, shared by a factory and its clones.
+struct Counters {
+	interpreter: AtomicUsize,
+	jit: AtomicUsize,
+	// number of runs of recently seen large contracts.
+	runs: Mutex<LruCache<H256, usize>>,
+}
+
+impl Default for Counters {
+	fn default() -> Self {
+		Counters {
+			interpreter: AtomicUsize::new(0),
+			jit: AtomicUsize::new(0),
+			runs: Mutex::new(LruCache::new(TRACKED_CONTRACTS)),
+		}
+	}
 }
 
 /// Evm factory. Creates appropriate Evm.
@@ -86,6 +132,8 @@ impl VMType {
 pub struct Factory {
 	evm: VMType,
 	evm_cache: Arc<SharedCache>,
+	counters: Arc<Counters>,
+	memory_pool: Option<Arc<MemoryPool>>,
 }
 
 impl Factory {
@@ -93,29 +141,33 @@ impl Factory {
 	/// Might choose implementation depending on supplied gas.
 	#[cfg(feature = "jit")]
 	pub fn create(&self, gas: U256) -> Box<Evm> {
-		match self.evm {
-			VMType::Jit => {
-				Box::new(super::jit::JitEvm::default())
-			},
-			VMType::Interpreter => if Self::can_fit_in_usize(gas) {
-				Box::new(super::interpreter::Interpreter::<usize>::new(self.evm_cache.clone()))
-			} else {
-				Box::new(super::interpreter::Interpreter::<U256>::new(self.evm_cache.clone()))
-			}
-		}
+		self.create_vm(self.evm == VMType::Jit, gas)
 	}
 
 	/// Create fresh instance of VM
 	/// Might choose implementation depending on supplied gas.
 	#[cfg(not(feature = "jit"))]
 	pub fn create(&self, gas: U256) -> Box<Evm> {
-		match self.evm {
-			VMType::Interpreter => if Self::can_fit_in_usize(gas) {
-				Box::new(super::interpreter::Interpreter::<usize>::new(self.evm_cache.clone()))
-			} else {
-				Box::new(super::interpreter::Interpreter::<U256>::new(self.evm_cache.clone()))
-			}
-		}
+		self.interpreter(gas)
+	}
+
+	/// Create fresh instance of VM to run the code in the given parameters.
+	/// With `VMType::Auto`, picks the JIT for large contracts which are run often.
+	#[cfg(feature = "jit")]
+	pub fn create_for(&self, params: &ActionParams) -> Box<Evm> {
+		let jit = match self.evm {
+			VMType::Jit => true,
+			VMType::Interpreter => false,
+			VMType::Auto => self.note_run(&params.code_hash, params.code.as_ref().map_or(0, |code| code.len())),
+		};
+
+		self.create_vm(jit, params.gas)
+	}
+
+	/// Create fresh instance of VM to run the code in the given parameters.
+	#[cfg(not(feature = "jit"))]
+	pub fn create_for(&self, params: &ActionParams) -> Box<Evm> {
+		self.create(params.gas)
 	}
 
 	/// Create new instance of specific `VMType` factory, with a size in bytes
@@ -124,6 +176,65 @@ impl Factory {
 		Factory {
 			evm: evm,
 			evm_cache: Arc::new(SharedCache::new(cache_size)),
+			counters: Default::default(),
+			memory_pool: None,
+		}
+	}
+
+	/// Clone of this factory whose interpreters reuse each other's memory
+	/// through a fresh pool, which lives as long as the clone and its clones.
+	/// Meant to be scoped to the execution of a block.
+	pub fn with_memory_pool(&self) -> Self {
+		Factory {
+			memory_pool: Some(Arc::new(MemoryPool::default())),
+			..self.clone()
+		}
+	}
+
+	/// Get the numbers of VMs created by this factory and its clones.
+	pub fn stats(&self) -> EvmStats {
+		EvmStats {
+			interpreter_runs: self.counters.interpreter.load(Ordering::Relaxed) as u64,
+			jit_runs: self.counters.jit.load(Ordering::Relaxed) as u64,
+			hot_contracts: self.counters.runs.lock().iter().filter(|&(_, runs)| *runs >= JIT_MIN_RUNS).count() as u64,
+		}
+	}
+
+	// note a run of the given code, returning whether it's hot enough for the JIT.
+	#[cfg(feature = "jit")]
+	fn note_run(&self, code_hash: &H256, code_size: usize) -> bool {
+		if code_size < JIT_MIN_CODE_SIZE {
+			return false;
+		}
+
+		let mut runs = self.counters.runs.lock();
+		let count = runs.get_mut(code_hash).map_or(0, |runs| { *runs += 1; *runs });
+		if count == 0 {
+			runs.insert(code_hash.clone(), 1);
+		}
+
+		count >= JIT_MIN_RUNS
+	}
+
+	#[cfg(feature = "jit")]
+	fn create_vm(&self, jit: bool, gas: U256) -> Box<Evm> {
+		match jit {
+			true => {
+				self.counters.jit.fetch_add(1, Ordering::Relaxed);
+				Box::new(super::jit::JitEvm::default())
+			},
+			false => self.interpreter(gas),
+		}
+	}
+
+	fn interpreter(&self, gas: U256) -> Box<Evm> {
+		self.counters.interpreter.fetch_add(1, Ordering::Relaxed);
+		let cache = self.evm_cache.clone();
+		match (Self::can_fit_in_usize(gas), self.memory_pool.clone()) {
+			(true, Some(pool)) => Box::new(Interpreter::<usize>::with_memory_pool(cache, pool)),
+			(true, None) => Box::new(Interpreter::<usize>::new(cache)),
+			(false, Some(pool)) => Box::new(Interpreter::<U256>::with_memory_pool(cache, pool)),
+			(false, None) => Box::new(Interpreter::<U256>::new(cache)),
 		}
 	}
 
@@ -139,6 +250,8 @@ impl Default for Factory {
 		Factory {
 			evm: VMType::Jit,
 			evm_cache: Arc::new(SharedCache::default()),
+			counters: Default::default(),
+			memory_pool: None,
 		}
 	}
 
@@ -148,6 +261,8 @@ impl Default for Factory {
 		Factory {
 			evm: VMType::Interpreter,
 			evm_cache: Arc::new(SharedCache::default()),
+			counters: Default::default(),
+			memory_pool: None,
 		}
 	}
 }
@@ -157,6 +272,60 @@ fn test_create_vm() {
 	let _vm = Factory::default().create(U256::zero());
 }
 
+#[test]
+fn counts_created_vms() {
+	let factory = Factory::default();
+	let clone = factory.clone();
+
+	let _vm = factory.create(U256::zero());
+	let _vm = clone.create_for(&ActionParams::default());
+
+	let stats = factory.stats();
+	assert_eq!(stats.interpreter_runs, 2);
+	assert_eq!(stats.jit_runs, 0);
+	assert_eq!(stats.hot_contracts, 0);
+}
+
+#[test]
+fn pooled_interpreters_share_memory() {
+	let factory = Factory::default().with_memory_pool();
+	let pool = factory.memory_pool.clone().expect("factory created with a pool; qed");
+
+	// MSTORE at 0 so the interpreter allocates a word of memory.
+	let mut params = ActionParams::default();
+	params.gas = U256::from(100_000);
+	params.code = Some(Arc::new(vec![0x60, 0x01, 0x60, 0x00, 0x52]));
+
+	{
+		let mut vm = factory.create_for(&params);
+		let mut ext = super::tests::FakeExt::new();
+		vm.exec(params.clone(), &mut ext).unwrap();
+	}
+	assert_eq!(pool.available(), 1);
+
+	let _vm = factory.create_for(&params);
+	assert_eq!(pool.available(), 0);
+}
+
+#[test]
+#[cfg(feature = "jit")]
+fn auto_picks_jit_for_hot_contracts() {
+	let factory = Factory::new(VMType::Auto, 1024 * 32);
+	let mut params = ActionParams::default();
+	params.code = Some(Arc::new(vec![0; JIT_MIN_CODE_SIZE]));
+	params.code_hash = H256::from(1);
+
+	for _ in 0..JIT_MIN_RUNS - 1 {
+		let _vm = factory.create_for(&params);
+	}
+	assert_eq!(factory.stats().jit_runs, 0);
+
+	let _vm = factory.create_for(&params);
+	let stats = factory.stats();
+	assert_eq!(stats.jit_runs, 1);
+	assert_eq!(stats.hot_contracts, 1);
+}
+
 /// Create tests by injecting different VM factories
 #[macro_export]
 macro_rules! evm_test(
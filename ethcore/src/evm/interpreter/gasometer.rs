@@ -102,7 +102,7 @@ impl<Gas: CostType> Gasometer<Gas> {
 	/// Determine how much gas is used by the given instruction, given the machine's state.
 	///
 	/// We guarantee that the final element of the returned tuple (`provided`) will be `Some`
-	/// iff the `instruction` is one of `CREATE`, or any of the `CALL` variants. In this case,
+	/// iff the `instruction` is one of `CREATE`, `CREATE2`, or any of the `CALL` variants. In this case,
 	/// it will be the amount of gas that the current context provides to the child context.
 	pub fn requirements(
 		&mut self,
@@ -164,7 +164,7 @@ impl<Gas: CostType> Gasometer<Gas> {
 			instructions::MSTORE8 => {
 				Request::GasMem(default_gas, mem_needed_const(stack.peek(0), 1)?)
 			},
-			instructions::RETURN => {
+			instructions::RETURN | instructions::REVERT => {
 				Request::GasMem(default_gas, mem_needed(stack.peek(0), stack.peek(1))?)
 			},
 			instructions::SHA3 => {
@@ -229,6 +229,15 @@ impl<Gas: CostType> Gasometer<Gas> {
 
 				Request::GasMemProvide(gas, mem, None)
 			},
+			instructions::CREATE2 => {
+				// the init code is hashed to derive the address.
+				let w = overflowing!(add_gas_usize(Gas::from_u256(*stack.peek(2))?, 31));
+				let words = w >> 5;
+				let gas = Gas::from(schedule.create_gas) + (Gas::from(schedule.sha3_word_gas) * words);
+				let mem = mem_needed(stack.peek(1), stack.peek(2))?;
+
+				Request::GasMemProvide(gas, mem, None)
+			},
 			instructions::EXP => {
 				let expon = stack.peek(1);
 				let bytes = ((expon.bits() + 7) / 8) as usize;
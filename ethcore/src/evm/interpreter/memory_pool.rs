@@ -0,0 +1,84 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Pool of interpreter memory, so call frames reuse buffers instead of allocating their own.
+
+use util::Mutex;
+
+// more buffers than the maximum call depth are never in use at once.
+const MAX_POOLED_BUFFERS: usize = 1024;
+// don't hold on to the memory of unusually hungry frames.
+const MAX_POOLED_CAPACITY: usize = 1024 * 1024;
+
+/// Memory buffers released by finished interpreters, handed out to new ones.
+#[derive(Default)]
+pub struct MemoryPool {
+	buffers: Mutex<Vec<Vec<u8>>>,
+}
+
+impl MemoryPool {
+	/// Take an empty buffer, reusing a released one if possible.
+	pub fn take(&self) -> Vec<u8> {
+		self.buffers.lock().pop().unwrap_or_else(Vec::new)
+	}
+
+	/// Release a buffer for reuse.
+	pub fn release(&self, mut buffer: Vec<u8>) {
+		if buffer.capacity() == 0 || buffer.capacity() > MAX_POOLED_CAPACITY {
+			return;
+		}
+
+		buffer.clear();
+		let mut buffers = self.buffers.lock();
+		if buffers.len() < MAX_POOLED_BUFFERS {
+			buffers.push(buffer);
+		}
+	}
+
+	/// Number of buffers ready for reuse.
+	pub fn available(&self) -> usize {
+		self.buffers.lock().len()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{MemoryPool, MAX_POOLED_CAPACITY};
+
+	#[test]
+	fn reuses_released_buffers() {
+		let pool = MemoryPool::default();
+		let mut buffer = pool.take();
+		buffer.resize(64, 0xff);
+		let capacity = buffer.capacity();
+
+		pool.release(buffer);
+		assert_eq!(pool.available(), 1);
+
+		let buffer = pool.take();
+		assert!(buffer.is_empty());
+		assert_eq!(buffer.capacity(), capacity);
+		assert_eq!(pool.available(), 0);
+	}
+
+	#[test]
+	fn drops_oversized_buffers() {
+		let pool = MemoryPool::default();
+		pool.release(vec![0; MAX_POOLED_CAPACITY + 1]);
+		pool.release(Vec::new());
+		assert_eq!(pool.available(), 0);
+	}
+}
@@ -21,18 +21,20 @@ mod informant;
 mod gasometer;
 mod stack;
 mod memory;
+mod memory_pool;
 mod shared_cache;
 
 use self::gasometer::Gasometer;
 use self::stack::{Stack, VecStack};
 use self::memory::Memory;
+pub use self::memory_pool::MemoryPool;
 pub use self::shared_cache::SharedCache;
 
 use std::marker::PhantomData;
 use action_params::{ActionParams, ActionValue};
 use types::executed::CallType;
 use evm::instructions::{self, Instruction, InstructionInfo};
-use evm::{self, MessageCallResult, ContractCreateResult, GasLeft, CostType};
+use evm::{self, MessageCallResult, ContractCreateResult, CreateContractAddress, GasLeft, CostType};
 use bit_set::BitSet;
 
 use util::*;
@@ -86,6 +88,8 @@ enum InstructionResult<Gas> {
 	JumpToPosition(U256),
 	// gas left, init_orf, init_size
 	StopExecutionNeedsReturn(Gas, U256, U256),
+	// gas left, revert_off, revert_size
+	StopExecutionNeedsRevert(Gas, U256, U256),
 	StopExecution,
 }
 
@@ -94,9 +98,18 @@ enum InstructionResult<Gas> {
 pub struct Interpreter<Cost: CostType> {
 	mem: Vec<u8>,
 	cache: Arc<SharedCache>,
+	pool: Option<Arc<MemoryPool>>,
 	_type: PhantomData<Cost>,
 }
 
+impl<Cost: CostType> Drop for Interpreter<Cost> {
+	fn drop(&mut self) {
+		if let Some(ref pool) = self.pool {
+			pool.release(::std::mem::replace(&mut self.mem, Vec::new()));
+		}
+	}
+}
+
 impl<Cost: CostType> evm::Evm for Interpreter<Cost> {
 	fn exec(&mut self, params: ActionParams, ext: &mut evm::Ext) -> evm::Result<GasLeft> {
 		self.mem.clear();
@@ -160,6 +173,10 @@ impl<Cost: CostType> evm::Evm for Interpreter<Cost> {
 					informant.done();
 					return Ok(GasLeft::NeedsReturn(gas.as_u256(), self.mem.read_slice(off, size)));
 				},
+				InstructionResult::StopExecutionNeedsRevert(gas, off, size) => {
+					informant.done();
+					return Ok(GasLeft::NeedsRevert(gas.as_u256(), self.mem.read_slice(off, size)));
+				},
 				InstructionResult::StopExecution => break,
 				_ => {},
 			}
@@ -175,6 +192,18 @@ impl<Cost: CostType> Interpreter<Cost> {
 		Interpreter {
 			mem: Vec::new(),
 			cache: cache,
+			pool: None,
+			_type: PhantomData::default(),
+		}
+	}
+
+	/// Create a new `Interpreter` instance with shared cache, whose memory
+	/// is taken from and released back to the given pool.
+	pub fn with_memory_pool(cache: Arc<SharedCache>, pool: Arc<MemoryPool>) -> Interpreter<Cost> {
+		Interpreter {
+			mem: pool.take(),
+			cache: cache,
+			pool: Some(pool),
 			_type: PhantomData::default(),
 		}
 	}
@@ -188,6 +217,18 @@ impl<Cost: CostType> Interpreter<Cost> {
 			});
 		}
 
+		if !schedule.have_create2 && instruction == instructions::CREATE2 {
+			return Err(evm::Error::BadInstruction {
+				instruction: instruction
+			});
+		}
+
+		if !schedule.have_revert && instruction == instructions::REVERT {
+			return Err(evm::Error::BadInstruction {
+				instruction: instruction
+			});
+		}
+
 		if info.tier == instructions::GasPriceTier::Invalid {
 			return Err(evm::Error::BadInstruction {
 				instruction: instruction
@@ -266,11 +307,15 @@ impl<Cost: CostType> Interpreter<Cost> {
 			instructions::JUMPDEST => {
 				// ignore
 			},
-			instructions::CREATE => {
+			instructions::CREATE | instructions::CREATE2 => {
 				let endowment = stack.pop_back();
 				let init_off = stack.pop_back();
 				let init_size = stack.pop_back();
-				let create_gas = provided.expect("`provided` comes through Self::exec from `Gasometer::get_gas_cost_mem`; `gas_gas_mem_cost` guarantees `Some` when instruction is `CALL`/`CALLCODE`/`DELEGATECALL`/`CREATE`; this is `CREATE`; qed");
+				let address_scheme = match instruction {
+					instructions::CREATE2 => CreateContractAddress::FromSenderSaltAndCodeHash(H256::from(&stack.pop_back())),
+					_ => CreateContractAddress::FromSenderAndNonce,
+				};
+				let create_gas = provided.expect("`provided` comes through Self::exec from `Gasometer::get_gas_cost_mem`; `gas_gas_mem_cost` guarantees `Some` when instruction is `CALL`/`CALLCODE`/`DELEGATECALL`/`CREATE`/`CREATE2`; this is one of `CREATE`/`CREATE2`; qed");
 
 				let contract_code = self.mem.read_slice(init_off, init_size);
 				let can_create = ext.balance(&params.address)? >= endowment && ext.depth() < ext.schedule().max_depth;
@@ -280,12 +325,16 @@ impl<Cost: CostType> Interpreter<Cost> {
 					return Ok(InstructionResult::UnusedGas(create_gas));
 				}
 
-				let create_result = ext.create(&create_gas.as_u256(), &endowment, contract_code);
+				let create_result = ext.create(&create_gas.as_u256(), &endowment, contract_code, address_scheme);
 				return match create_result {
 					ContractCreateResult::Created(address, gas_left) => {
 						stack.push(address_to_u256(address));
 						Ok(InstructionResult::UnusedGas(Cost::from_u256(gas_left).expect("Gas left cannot be greater.")))
 					},
+					ContractCreateResult::Reverted(gas_left) => {
+						stack.push(U256::zero());
+						Ok(InstructionResult::UnusedGas(Cost::from_u256(gas_left).expect("Gas left cannot be greater.")))
+					},
 					ContractCreateResult::Failed => {
 						stack.push(U256::zero());
 						Ok(InstructionResult::Ok)
@@ -349,6 +398,10 @@ impl<Cost: CostType> Interpreter<Cost> {
 						stack.push(U256::one());
 						Ok(InstructionResult::UnusedGas(Cost::from_u256(gas_left).expect("Gas left cannot be greater then current one")))
 					},
+					MessageCallResult::Reverted(gas_left) => {
+						stack.push(U256::zero());
+						Ok(InstructionResult::UnusedGas(Cost::from_u256(gas_left).expect("Gas left cannot be greater then current one")))
+					},
 					MessageCallResult::Failed  => {
 						stack.push(U256::zero());
 						Ok(InstructionResult::Ok)
@@ -361,6 +414,12 @@ impl<Cost: CostType> Interpreter<Cost> {
 
 				return Ok(InstructionResult::StopExecutionNeedsReturn(gas, init_off, init_size))
 			},
+			instructions::REVERT => {
+				let revert_off = stack.pop_back();
+				let revert_size = stack.pop_back();
+
+				return Ok(InstructionResult::StopExecutionNeedsRevert(gas, revert_off, revert_size))
+			},
 			instructions::STOP => {
 				return Ok(InstructionResult::StopExecution);
 			},
@@ -174,11 +174,15 @@ impl<'a> evmjit::Ext for ExtAdapter<'a> {
 
 		// check if balance is sufficient and we are not too deep
 		if self.ext.balance(&self.address) >= value && self.ext.depth() < self.ext.schedule().max_depth {
-			match self.ext.create(&gas, &value, code) {
+			match self.ext.create(&gas, &value, code, evm::CreateContractAddress::FromSenderAndNonce) {
 				evm::ContractCreateResult::Created(new_address, gas_left) => unsafe {
 					*address = new_address.into_jit();
 					*io_gas = gas_left.low_u64();
 				},
+				evm::ContractCreateResult::Reverted(gas_left) => unsafe {
+					*address = Address::new().into_jit();
+					*io_gas = gas_left.low_u64();
+				},
 				evm::ContractCreateResult::Failed => unsafe {
 					*address = Address::new().into_jit();
 					*io_gas = 0;
@@ -264,6 +268,10 @@ impl<'a> evmjit::Ext for ExtAdapter<'a> {
 				*io_gas = (gas + gas_left).low_u64();
 				true
 			},
+			evm::MessageCallResult::Reverted(gas_left) => unsafe {
+				*io_gas = (gas + gas_left).low_u64();
+				false
+			},
 			evm::MessageCallResult::Failed => unsafe {
 				*io_gas = gas.low_u64();
 				false
@@ -32,7 +32,17 @@ mod tests;
 mod benches;
 
 pub use self::evm::{Evm, Error, Finalize, GasLeft, Result, CostType};
-pub use self::ext::{Ext, ContractCreateResult, MessageCallResult};
+pub use self::ext::{Ext, ContractCreateResult, MessageCallResult, CreateContractAddress};
 pub use self::factory::{Factory, VMType};
 pub use self::schedule::Schedule;
 pub use types::executed::CallType;
+
+/// Mnemonic of the given instruction. Empty for undefined instructions.
+pub fn instruction_name(instruction: u8) -> &'static str {
+	instructions::INSTRUCTIONS[instruction as usize].name
+}
+
+/// Number of stack items taken by the given instruction.
+pub fn instruction_args(instruction: u8) -> usize {
+	instructions::INSTRUCTIONS[instruction as usize].args
+}
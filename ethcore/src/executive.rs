@@ -24,7 +24,7 @@ use env_info::EnvInfo;
 use error::ExecutionError;
 use evm::{self, Ext, Factory, Finalize};
 use externalities::*;
-use trace::{FlatTrace, Tracer, NoopTracer, ExecutiveTracer, VMTrace, VMTracer, ExecutiveVMTracer, NoopVMTracer};
+use trace::{FlatTrace, Tracer, NoopTracer, ExecutiveTracer, VMTrace, VMTracer, ExecutiveVMTracer, NoopVMTracer, GasProfiler, StructLogger, StructLogOptions};
 use transaction::{Action, SignedTransaction};
 use crossbeam;
 pub use types::executed::{Executed, ExecutionResult};
@@ -44,8 +44,18 @@ pub fn contract_address(address: &Address, nonce: &U256) -> Address {
 	From::from(stream.out().sha3())
 }
 
+/// Returns new address created from sender, salt and the hash of the init code (`CREATE2`).
+pub fn salted_contract_address(sender: &Address, salt: &H256, code: &[u8]) -> Address {
+	let mut buffer = [0u8; 1 + 20 + 32 + 32];
+	buffer[0] = 0xff;
+	buffer[1..(1 + 20)].copy_from_slice(&sender[..]);
+	buffer[(1 + 20)..(1 + 20 + 32)].copy_from_slice(&salt[..]);
+	buffer[(1 + 20 + 32)..].copy_from_slice(&code.sha3()[..]);
+	From::from((&buffer[..]).sha3())
+}
+
 /// Transaction execution options.
-#[derive(Default, Copy, Clone, PartialEq)]
+#[derive(Default, Clone, PartialEq)]
 pub struct TransactOptions {
 	/// Enable call tracing.
 	pub tracing: bool,
@@ -53,6 +63,10 @@ pub struct TransactOptions {
 	pub vm_tracing: bool,
 	/// Check transaction nonce before execution.
 	pub check_nonce: bool,
+	/// Record the gas used by each instruction. Replaces VM tracing.
+	pub gas_profiling: bool,
+	/// Record every executed step with the given options. Replaces VM tracing and gas profiling.
+	pub struct_logging: Option<StructLogOptions>,
 }
 
 /// Transaction executor.
@@ -102,6 +116,19 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 	/// This function should be used to execute transaction.
 	pub fn transact(&'a mut self, t: &SignedTransaction, options: TransactOptions) -> Result<Executed, ExecutionError> {
 		let check = options.check_nonce;
+		if let Some(struct_options) = options.struct_logging {
+			return match options.tracing {
+				true => self.transact_with_tracer(t, check, ExecutiveTracer::default(), StructLogger::new(struct_options)),
+				false => self.transact_with_tracer(t, check, NoopTracer, StructLogger::new(struct_options)),
+			};
+		}
+		if options.gas_profiling {
+			return match options.tracing {
+				true => self.transact_with_tracer(t, check, ExecutiveTracer::default(), GasProfiler::default()),
+				false => self.transact_with_tracer(t, check, NoopTracer, GasProfiler::default()),
+			};
+		}
+
 		match options.tracing {
 			true => match options.vm_tracing {
 				true => self.transact_with_tracer(t, check, ExecutiveTracer::default(), ExecutiveVMTracer::toplevel()),
@@ -203,7 +230,12 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 		};
 
 		// finalize here!
-		Ok(self.finalize(t, substate, gas_left, output, tracer.traces(), vm_tracer.drain())?)
+		let gas_profile = vm_tracer.gas_profile();
+		let struct_logs = vm_tracer.struct_logs();
+		let mut executed = self.finalize(t, substate, gas_left, output, tracer.traces(), vm_tracer.drain())?;
+		executed.gas_profile = gas_profile;
+		executed.struct_logs = struct_logs;
+		Ok(executed)
 	}
 
 	fn exec_vm<T, V>(
@@ -222,7 +254,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 			let vm_factory = self.vm_factory;
 			let mut ext = self.as_externalities(OriginInfo::from(&params), unconfirmed_substate, output_policy, tracer, vm_tracer);
 			trace!(target: "executive", "ext.schedule.have_delegate_call: {}", ext.schedule().have_delegate_call);
-			return vm_factory.create(params.gas).exec(params, &mut ext).finalize(ext);
+			return vm_factory.create_for(&params).exec(params, &mut ext).finalize(ext);
 		}
 
 		// Start in new thread to reset stack
@@ -233,7 +265,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 			let mut ext = self.as_externalities(OriginInfo::from(&params), unconfirmed_substate, output_policy, tracer, vm_tracer);
 
 			scope.spawn(move || {
-				vm_factory.create(params.gas).exec(params, &mut ext).finalize(ext)
+				vm_factory.create_for(&params).exec(params, &mut ext).finalize(ext)
 			})
 		}).join()
 	}
@@ -315,10 +347,12 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 				let mut unconfirmed_substate = Substate::new();
 
 				// TODO: make ActionParams pass by ref then avoid copy altogether.
-				let mut subvmtracer = vm_tracer.prepare_subtrace(params.code.as_ref().expect("scope is conditional on params.code.is_some(); qed"));
+				let mut subvmtracer = vm_tracer.prepare_subtrace_at(&params.address, params.code.as_ref().expect("scope is conditional on params.code.is_some(); qed"));
 
-				let res = {
-					self.exec_vm(params, &mut unconfirmed_substate, OutputPolicy::Return(output, trace_output.as_mut()), &mut subtracer, &mut subvmtracer)
+				// VMs don't call beyond the maximum depth, this guards against them doing so.
+				let res = match self.depth > schedule.max_depth {
+					true => Err(evm::Error::OutOfDepth { limit: schedule.max_depth }),
+					false => self.exec_vm(params, &mut unconfirmed_substate, OutputPolicy::Return(output, trace_output.as_mut()), &mut subtracer, &mut subvmtracer),
 				};
 
 				vm_tracer.done_subtrace(subvmtracer);
@@ -361,6 +395,14 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 		tracer: &mut T,
 		vm_tracer: &mut V
 	) -> evm::Result<U256> where T: Tracer, V: VMTracer {
+		// a contract can't be created over an account which already has code or a nonce,
+		// e.g. a second CREATE2 with the same salt and init code; fail consuming all gas.
+		if !self.state.nonce(&params.address)?.is_zero() || self.state.code_hash(&params.address)? != SHA3_EMPTY {
+			let trace_info = tracer.prepare_trace_create(&params);
+			tracer.trace_failed_create(trace_info, Vec::new(), (&evm::Error::OutOfGas).into());
+			return Err(evm::Error::OutOfGas);
+		}
+
 		// backup used in case of running out of gas
 		self.state.checkpoint();
 
@@ -384,10 +426,11 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 		let gas = params.gas;
 		let created = params.address.clone();
 
-		let mut subvmtracer = vm_tracer.prepare_subtrace(params.code.as_ref().expect("two ways into create (Externalities::create and Executive::transact_with_tracer); both place `Some(...)` `code` in `params`; qed"));
+		let mut subvmtracer = vm_tracer.prepare_subtrace_at(&params.address, params.code.as_ref().expect("two ways into create (Externalities::create and Executive::transact_with_tracer); both place `Some(...)` `code` in `params`; qed"));
 
-		let res = {
-			self.exec_vm(params, &mut unconfirmed_substate, OutputPolicy::InitContract(trace_output.as_mut()), &mut subtracer, &mut subvmtracer)
+		let res = match self.depth > schedule.max_depth {
+			true => Err(evm::Error::OutOfDepth { limit: schedule.max_depth }),
+			false => self.exec_vm(params, &mut unconfirmed_substate, OutputPolicy::InitContract(trace_output.as_mut()), &mut subtracer, &mut subvmtracer),
 		};
 
 		vm_tracer.done_subtrace(subvmtracer);
@@ -426,7 +469,10 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 		let refunds_bound = sstore_refunds + suicide_refunds;
 
 		// real ammount to refund
-		let gas_left_prerefund = match result { Ok(x) => x, _ => 0.into() };
+		let gas_left_prerefund = match result {
+			Ok(x) | Err(evm::Error::Reverted { gas_left: x }) => x,
+			_ => 0.into(),
+		};
 		let refunded = cmp::min(refunds_bound, (t.gas - gas_left_prerefund) >> 1);
 		let gas_left = gas_left_prerefund + refunded;
 
@@ -458,6 +504,23 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 
 		match result {
 			Err(evm::Error::Internal(msg)) => Err(ExecutionError::Internal(msg)),
+			Err(exception @ evm::Error::Reverted { .. }) => {
+				Ok(Executed {
+					exception: Some(exception),
+					gas: t.gas,
+					gas_used: gas_used,
+					refunded: refunded,
+					cumulative_gas_used: self.info.gas_used + gas_used,
+					logs: vec![],
+					contracts_created: vec![],
+					output: output,
+					trace: trace,
+					vm_trace: vm_trace,
+					state_diff: None,
+					gas_profile: None,
+					struct_logs: None,
+				})
+			},
 			Err(exception) => {
 				Ok(Executed {
 					exception: Some(exception),
@@ -471,6 +534,8 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 					trace: trace,
 					vm_trace: vm_trace,
 					state_diff: None,
+					gas_profile: None,
+					struct_logs: None,
 				})
 			},
 			_ => {
@@ -486,6 +551,8 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 					trace: trace,
 					vm_trace: vm_trace,
 					state_diff: None,
+					gas_profile: None,
+					struct_logs: None,
 				})
 			},
 		}
@@ -497,7 +564,9 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 				| Err(evm::Error::BadJumpDestination {..})
 				| Err(evm::Error::BadInstruction {.. })
 				| Err(evm::Error::StackUnderflow {..})
-				| Err(evm::Error::OutOfStack {..}) => {
+				| Err(evm::Error::OutOfStack {..})
+				| Err(evm::Error::OutOfDepth {..})
+				| Err(evm::Error::Reverted {..}) => {
 					self.state.revert_to_checkpoint();
 			},
 			Ok(_) | Err(evm::Error::Internal(_)) => {
@@ -518,7 +587,7 @@ mod tests {
 	use util::bytes::BytesRef;
 	use action_params::{ActionParams, ActionValue};
 	use env_info::EnvInfo;
-	use evm::{Factory, VMType};
+	use evm::{self, Factory, VMType};
 	use error::ExecutionError;
 	use state::{Substate, CleanupMode};
 	use tests::helpers::*;
@@ -1064,7 +1133,7 @@ mod tests {
 
 		let executed = {
 			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
-			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false };
+			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false, gas_profiling: false, struct_logging: None };
 			ex.transact(&t, opts).unwrap()
 		};
 
@@ -1102,7 +1171,7 @@ mod tests {
 
 		let res = {
 			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
-			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false };
+			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false, gas_profiling: false, struct_logging: None };
 			ex.transact(&t, opts)
 		};
 
@@ -1136,7 +1205,7 @@ mod tests {
 
 		let res = {
 			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
-			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false };
+			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false, gas_profiling: false, struct_logging: None };
 			ex.transact(&t, opts)
 		};
 
@@ -1170,7 +1239,7 @@ mod tests {
 
 		let res = {
 			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
-			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false };
+			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false, gas_profiling: false, struct_logging: None };
 			ex.transact(&t, opts)
 		};
 
@@ -1216,4 +1285,136 @@ mod tests {
 			}
 		}
 	}
+
+	evm_test!{ignorejit => test_revert: test_revert_jit, test_revert_int}
+	fn test_revert(factory: Factory) {
+		// 60 01 60 00 55 - sstore(0, 1)
+		// 60 2a 60 00 52 - mstore(0, 42)
+		// 60 20 60 00 fd - revert(0, 32)
+		let code = "6001600055602a60005260206000fd".from_hex().unwrap();
+
+		let sender = Address::from_str("0f572e5295c57f15886f9b263e2f6d2d6c7b5ec6").unwrap();
+		let address = contract_address(&sender, &U256::zero());
+		let mut params = ActionParams::default();
+		params.address = address.clone();
+		params.sender = sender.clone();
+		params.origin = sender.clone();
+		params.gas = U256::from(100_000);
+		params.code = Some(Arc::new(code));
+		params.value = ActionValue::Transfer(U256::zero());
+		let mut state_result = get_temp_state();
+		let mut state = state_result.reference_mut();
+		let info = EnvInfo::default();
+		let engine = TestEngine::new(0);
+		let mut substate = Substate::new();
+		let mut output = vec![];
+
+		let result = {
+			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
+			ex.call(params, &mut substate, BytesRef::Flexible(&mut output), &mut NoopTracer, &mut NoopVMTracer)
+		};
+
+		assert_eq!(result, Err(evm::Error::Reverted { gas_left: U256::from(79_976) }));
+		assert_eq!(output, H256::from(&U256::from(42)).to_vec());
+		assert_eq!(state.storage_at(&address, &H256::new()).unwrap(), H256::new());
+	}
+
+	evm_test!{test_call_beyond_max_depth: test_call_beyond_max_depth_jit, test_call_beyond_max_depth_int}
+	fn test_call_beyond_max_depth(factory: Factory) {
+		let sender = Address::from_str("0f572e5295c57f15886f9b263e2f6d2d6c7b5ec6").unwrap();
+		let address = contract_address(&sender, &U256::zero());
+		let mut params = ActionParams::default();
+		params.address = address.clone();
+		params.sender = sender.clone();
+		params.gas = U256::from(100_000);
+		params.code = Some(Arc::new("6001600055".from_hex().unwrap()));
+		params.value = ActionValue::Transfer(U256::zero());
+		let mut state_result = get_temp_state();
+		let mut state = state_result.reference_mut();
+		let info = EnvInfo::default();
+		let engine = TestEngine::new(2);
+		let mut substate = Substate::new();
+
+		let result = {
+			let mut ex = Executive::from_parent(&mut state, &info, &engine, &factory, 2);
+			ex.call(params, &mut substate, BytesRef::Fixed(&mut []), &mut NoopTracer, &mut NoopVMTracer)
+		};
+
+		assert_eq!(result, Err(evm::Error::OutOfDepth { limit: 2 }));
+		assert_eq!(state.storage_at(&address, &H256::new()).unwrap(), H256::new());
+	}
+
+	#[test]
+	fn test_salted_contract_address() {
+		// example 0 from EIP-1014
+		let address = salted_contract_address(&Address::zero(), &H256::zero(), &[0x00]);
+		assert_eq!(address, Address::from_str("4d1a2e2bb4f88f0250f26ffff098b0b30b26bf38").unwrap());
+	}
+
+	evm_test!{ignorejit => test_create2: test_create2_jit, test_create2_int}
+	fn test_create2(factory: Factory) {
+		// 60 2a 60 00 60 00 60 00 f5 - create2(0, 0, 0, 42)
+		// 60 00 55 - sstore(0, address)
+		let code = "602a600060006000f5600055".from_hex().unwrap();
+
+		let sender = Address::from_str("0f572e5295c57f15886f9b263e2f6d2d6c7b5ec6").unwrap();
+		let address = contract_address(&sender, &U256::zero());
+		let mut params = ActionParams::default();
+		params.address = address.clone();
+		params.sender = sender.clone();
+		params.origin = sender.clone();
+		params.gas = U256::from(100_000);
+		params.code = Some(Arc::new(code));
+		params.value = ActionValue::Transfer(U256::zero());
+		let mut state_result = get_temp_state();
+		let mut state = state_result.reference_mut();
+		let info = EnvInfo::default();
+		let engine = TestEngine::new(1024);
+		let mut substate = Substate::new();
+
+		{
+			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
+			ex.call(params, &mut substate, BytesRef::Fixed(&mut []), &mut NoopTracer, &mut NoopVMTracer).unwrap();
+		}
+
+		let created = salted_contract_address(&address, &H256::from(&U256::from(42)), &[]);
+		assert_eq!(substate.contracts_created, vec![created.clone()]);
+		assert_eq!(state.storage_at(&address, &H256::new()).unwrap(), H256::from(created));
+	}
+
+	evm_test!{ignorejit => test_create2_collision: test_create2_collision_jit, test_create2_collision_int}
+	fn test_create2_collision(factory: Factory) {
+		// 69 602a60005360016000f3 60 00 52 - mstore(0, init code returning [0x2a])
+		// 60 2a 60 0a 60 16 60 00 f5 - create2(0, 22, 10, 42)
+		// 60 00 55 - sstore(0, address)
+		// 60 2a 60 0a 60 16 60 00 f5 - create2(0, 22, 10, 42) again, onto the same address
+		let code = "69602a60005360016000f3600052602a600a60166000f5600055602a600a60166000f5".from_hex().unwrap();
+
+		let sender = Address::from_str("0f572e5295c57f15886f9b263e2f6d2d6c7b5ec6").unwrap();
+		let address = contract_address(&sender, &U256::zero());
+		let mut params = ActionParams::default();
+		params.address = address.clone();
+		params.sender = sender.clone();
+		params.origin = sender.clone();
+		params.gas = U256::from(100_000);
+		params.code = Some(Arc::new(code));
+		params.value = ActionValue::Transfer(U256::zero());
+		let mut state_result = get_temp_state();
+		let mut state = state_result.reference_mut();
+		let info = EnvInfo::default();
+		let engine = TestEngine::new(1024);
+		let mut substate = Substate::new();
+
+		let gas_left = {
+			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
+			ex.call(params, &mut substate, BytesRef::Fixed(&mut []), &mut NoopTracer, &mut NoopVMTracer).unwrap()
+		};
+
+		// the second create consumed all the gas it was given
+		assert_eq!(gas_left, U256::zero());
+		let created = salted_contract_address(&address, &H256::from(&U256::from(42)), &"602a60005360016000f3".from_hex().unwrap());
+		assert_eq!(substate.contracts_created, vec![created.clone()]);
+		assert_eq!(state.storage_at(&address, &H256::new()).unwrap(), H256::from(created.clone()));
+		assert_eq!(state.code(&created).unwrap(), Some(Arc::new(vec![0x2a])));
+	}
 }
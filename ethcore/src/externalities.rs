@@ -21,7 +21,7 @@ use state::{Backend as StateBackend, State, Substate};
 use engines::Engine;
 use env_info::EnvInfo;
 use executive::*;
-use evm::{self, Schedule, Ext, ContractCreateResult, MessageCallResult, Factory};
+use evm::{self, Schedule, Ext, ContractCreateResult, CreateContractAddress, MessageCallResult, Factory};
 use types::executed::CallType;
 use trace::{Tracer, VMTracer};
 
@@ -147,14 +147,17 @@ impl<'a, T: 'a, V: 'a, B: 'a> Ext for Externalities<'a, T, V, B>
 		}
 	}
 
-	fn create(&mut self, gas: &U256, value: &U256, code: &[u8]) -> ContractCreateResult {
+	fn create(&mut self, gas: &U256, value: &U256, code: &[u8], address_scheme: CreateContractAddress) -> ContractCreateResult {
 		// create new contract address
-		let address = match self.state.nonce(&self.origin_info.address) {
-			Ok(nonce) => contract_address(&self.origin_info.address, &nonce),
-			Err(e) => {
-				debug!(target: "ext", "Database corruption encountered: {:?}", e);
-				return ContractCreateResult::Failed
-			}
+		let address = match address_scheme {
+			CreateContractAddress::FromSenderSaltAndCodeHash(salt) => salted_contract_address(&self.origin_info.address, &salt, code),
+			CreateContractAddress::FromSenderAndNonce => match self.state.nonce(&self.origin_info.address) {
+				Ok(nonce) => contract_address(&self.origin_info.address, &nonce),
+				Err(e) => {
+					debug!(target: "ext", "Database corruption encountered: {:?}", e);
+					return ContractCreateResult::Failed
+				}
+			},
 		};
 
 		// prepare the params
@@ -184,6 +187,7 @@ impl<'a, T: 'a, V: 'a, B: 'a> Ext for Externalities<'a, T, V, B>
 				self.substate.contracts_created.push(address.clone());
 				ContractCreateResult::Created(address, gas_left)
 			},
+			Err(evm::Error::Reverted { gas_left }) => ContractCreateResult::Reverted(gas_left),
 			_ => ContractCreateResult::Failed
 		}
 	}
@@ -230,6 +234,7 @@ impl<'a, T: 'a, V: 'a, B: 'a> Ext for Externalities<'a, T, V, B>
 
 		match ex.call(params, self.substate, BytesRef::Fixed(output), self.tracer, self.vm_tracer) {
 			Ok(gas_left) => MessageCallResult::Success(gas_left),
+			Err(evm::Error::Reverted { gas_left }) => MessageCallResult::Reverted(gas_left),
 			_ => MessageCallResult::Failed
 		}
 	}
@@ -280,6 +285,28 @@ impl<'a, T: 'a, V: 'a, B: 'a> Ext for Externalities<'a, T, V, B>
 		}
 	}
 
+	fn revert(mut self, gas: &U256, data: &[u8]) -> evm::Result<U256>
+		where Self: Sized {
+		match self.output {
+			OutputPolicy::Return(BytesRef::Fixed(ref mut slice), ref mut copy) => {
+				copy.as_mut().map(|b| **b = data.to_owned());
+
+				let len = cmp::min(slice.len(), data.len());
+				(&mut slice[..len]).copy_from_slice(&data[..len]);
+			},
+			OutputPolicy::Return(BytesRef::Flexible(ref mut vec), ref mut copy) => {
+				copy.as_mut().map(|b| **b = data.to_owned());
+
+				vec.clear();
+				vec.extend_from_slice(data);
+			},
+			OutputPolicy::InitContract(ref mut copy) => {
+				copy.as_mut().map(|b| **b = data.to_owned());
+			},
+		}
+		Err(evm::Error::Reverted { gas_left: *gas })
+	}
+
 	fn log(&mut self, topics: Vec<H256>, data: &[u8]) {
 		use log_entry::LogEntry;
 
@@ -29,3 +29,15 @@ pub use self::v10::ToV10;
 
 mod v11;
 pub use self::v11::TO_V11;
+
+mod v12;
+pub use self::v12::TO_V12;
+
+mod v13;
+pub use self::v13::TO_V13;
+
+mod v14;
+pub use self::v14::TO_V14;
+
+mod v15;
+pub use self::v15::TO_V15;
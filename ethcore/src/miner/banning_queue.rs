@@ -16,6 +16,9 @@
 
 //! Banning Queue
 //! Transacton Queue wrapper maintaining additional list of banned senders and contract hashes.
+//!
+//! Senders, recipients and contract codes are penalized for transactions that take too long to execute.
+//! Senders are additionally penalized for submitting transactions that fail validation.
 
 use std::time::Duration;
 use std::ops::{Deref, DerefMut};
@@ -46,7 +49,9 @@ impl Default for Threshold {
 pub struct BanningTransactionQueue {
 	queue: TransactionQueue,
 	ban_threshold: Threshold,
+	invalid_threshold: Threshold,
 	senders_bans: TransientHashMap<Address, Count>,
+	senders_invalid: TransientHashMap<Address, Count>,
 	recipients_bans: TransientHashMap<Address, Count>,
 	codes_bans: TransientHashMap<H256, Count>,
 }
@@ -59,7 +64,9 @@ impl BanningTransactionQueue {
 		BanningTransactionQueue {
 			queue: queue,
 			ban_threshold: ban_threshold,
+			invalid_threshold: Threshold::NeverBan,
 			senders_bans: TransientHashMap::new(ban_lifetime_sec),
+			senders_invalid: TransientHashMap::new(ban_lifetime_sec),
 			recipients_bans: TransientHashMap::new(ban_lifetime_sec),
 			codes_bans: TransientHashMap::new(ban_lifetime_sec),
 		}
@@ -73,6 +80,11 @@ impl BanningTransactionQueue {
 		&mut self.queue
 	}
 
+	/// Sets the number of transactions failing validation a sender may submit before getting banned.
+	pub fn set_invalid_threshold(&mut self, threshold: Threshold) {
+		self.invalid_threshold = threshold;
+	}
+
 	/// Add to the queue taking bans into consideration.
 	/// May reject transaction because of the banlist.
 	pub fn add_with_banlist(
@@ -81,17 +93,16 @@ impl BanningTransactionQueue {
 		time: QueuingInstant,
 		details_provider: &TransactionQueueDetailsProvider,
 	) -> Result<TransactionImportResult, Error> {
+		// Check sender
+		let sender = transaction.sender();
+		if self.is_sender_banned(&sender) {
+			debug!(target: "txqueue", "Ignoring transaction {:?} because sender is banned.", transaction.hash());
+			return Err(Error::Transaction(TransactionError::SenderBanned));
+		}
+
 		if let Threshold::BanAfter(threshold) = self.ban_threshold {
 			// NOTE In all checks use direct query to avoid increasing ban timeout.
 
-			// Check sender
-			let sender = transaction.sender();
-			let count = self.senders_bans.direct().get(&sender).cloned().unwrap_or(0);
-			if count > threshold {
-				debug!(target: "txqueue", "Ignoring transaction {:?} because sender is banned.", transaction.hash());
-				return Err(Error::Transaction(TransactionError::SenderBanned));
-			}
-
 			// Check recipient
 			if let Action::Call(recipient) = transaction.action {
 				let count = self.recipients_bans.direct().get(&recipient).cloned().unwrap_or(0);
@@ -111,7 +122,67 @@ impl BanningTransactionQueue {
 				}
 			}
 		}
-		self.queue.add(transaction, TransactionOrigin::External, time, None, details_provider)
+		let hash = transaction.hash();
+		let result = self.queue.add(transaction, TransactionOrigin::External, time, None, details_provider);
+		if let Err(Error::Transaction(ref err)) = result {
+			if is_invalid(err) && self.penalize_invalid(sender) {
+				debug!(target: "txqueue", "Banning sender {:?} after too many invalid transactions (last: {:?}).", sender, hash);
+			}
+		}
+		result
+	}
+
+	/// Forgets offences and bans older than the ban lifetime.
+	pub fn prune_bans(&mut self) {
+		self.senders_bans.prune();
+		self.senders_invalid.prune();
+		self.recipients_bans.prune();
+		self.codes_bans.prune();
+	}
+
+	/// Returns senders that are currently banned,
+	/// either for heavy transactions or for submitting invalid ones.
+	pub fn banned_senders(&mut self) -> Vec<Address> {
+		self.senders_bans.prune();
+		self.senders_invalid.prune();
+
+		let mut senders = self.senders_bans.direct().keys()
+			.chain(self.senders_invalid.direct().keys())
+			.cloned()
+			.collect::<Vec<_>>();
+		senders.sort();
+		senders.dedup();
+		senders.retain(|sender| self.is_sender_banned(sender));
+		senders
+	}
+
+	fn is_sender_banned(&self, sender: &Address) -> bool {
+		// NOTE Use direct query to avoid increasing ban timeout.
+		let exceeds = |threshold: &Threshold, count: Option<&Count>| match (threshold, count) {
+			(&Threshold::BanAfter(max), Some(count)) => *count > max,
+			_ => false,
+		};
+		exceeds(&self.ban_threshold, self.senders_bans.direct().get(sender))
+			|| exceeds(&self.invalid_threshold, self.senders_invalid.direct().get(sender))
+	}
+
+	/// Records transaction failing validation for given sender, unless such senders are never banned.
+	/// Returns `true` if the sender got banned (and its transactions removed from the queue).
+	fn penalize_invalid(&mut self, address: Address) -> bool {
+		let threshold = match self.invalid_threshold {
+			Threshold::BanAfter(threshold) => threshold,
+			Threshold::NeverBan => return false,
+		};
+		let count = {
+			let mut count = self.senders_invalid.entry(address).or_insert_with(|| 0);
+			*count = count.saturating_add(1);
+			*count
+		};
+		if count > threshold {
+			self.cull(address, !U256::zero());
+			return true;
+		}
+		false
 	}
 
 	/// Ban transaction with given hash.
@@ -193,6 +264,18 @@ impl BanningTransactionQueue {
 	}
 }
 
+/// Errors caused by a transaction that is invalid in the current state (and not merely a duplicate or too cheap).
+fn is_invalid(err: &TransactionError) -> bool {
+	match *err {
+		TransactionError::InsufficientGasPrice { .. }
+		| TransactionError::InsufficientGas { .. }
+		| TransactionError::InsufficientBalance { .. }
+		| TransactionError::GasLimitExceeded { .. }
+		| TransactionError::InvalidGasLimit(_) => true,
+		_ => false,
+	}
+}
+
 impl Deref for BanningTransactionQueue {
 	type Target = TransactionQueue;
 
@@ -217,6 +300,7 @@ mod tests {
 	use miner::{TransactionQueue, TransactionOrigin};
 	use util::{Uint, U256, Address, FromHex, Hashable};
 	use miner::transaction_queue::test::DummyTransactionDetailsProvider;
+	use miner::transaction_queue::AccountDetails;
 
 	fn queue() -> BanningTransactionQueue {
 		BanningTransactionQueue::new(TransactionQueue::default(), Threshold::BanAfter(1), Duration::from_secs(180))
@@ -326,4 +410,58 @@ mod tests {
 		assert!(banlist2, "Threshold should be reached - banned.");
 		assert_eq!(unwrap_err(import2), TransactionError::CodeBanned);
 	}
+
+	#[test]
+	fn should_ban_sender_after_too_many_invalid_transactions() {
+		// given
+		let tx = transaction(Action::Create);
+		let sender = tx.sender();
+		let mut txq = queue();
+		txq.set_invalid_threshold(Threshold::BanAfter(1));
+		let no_balance = default_tx_provider().with_account(AccountDetails {
+			nonce: U256::zero(),
+			balance: U256::zero(),
+		});
+
+		// when
+		let import1 = txq.add_with_banlist(tx.clone(), 0, &no_balance);
+		let banned1 = txq.banned_senders();
+		let import2 = txq.add_with_banlist(tx.clone(), 0, &no_balance);
+		let import3 = txq.add_with_banlist(tx.clone(), 0, &default_tx_provider());
+
+		// then
+		match unwrap_err(import1) {
+			TransactionError::InsufficientBalance { .. } => {},
+			e => panic!("Unexpected error: {:?}", e),
+		}
+		assert_eq!(banned1, vec![]);
+		match unwrap_err(import2) {
+			TransactionError::InsufficientBalance { .. } => {},
+			e => panic!("Unexpected error: {:?}", e),
+		}
+		assert_eq!(unwrap_err(import3), TransactionError::SenderBanned);
+		assert_eq!(txq.banned_senders(), vec![sender]);
+	}
+
+	#[test]
+	fn should_not_record_invalid_transactions_without_threshold() {
+		// given
+		let tx = transaction(Action::Create);
+		let mut txq = queue();
+		let no_balance = default_tx_provider().with_account(AccountDetails {
+			nonce: U256::zero(),
+			balance: U256::zero(),
+		});
+
+		// when
+		let import = txq.add_with_banlist(tx, 0, &no_balance);
+
+		// then
+		match unwrap_err(import) {
+			TransactionError::InsufficientBalance { .. } => {},
+			e => panic!("Unexpected error: {:?}", e),
+		}
+		assert!(txq.senders_invalid.direct().is_empty());
+		assert_eq!(txq.banned_senders(), vec![]);
+	}
 }
@@ -0,0 +1,177 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Ordering of ready transactions in the block prepared for sealing.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use transaction::SignedTransaction;
+use util::{Address, H256};
+
+/// Strategy used to order transactions when assembling the sealing block.
+///
+/// Transactions of a single sender always keep their nonce order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockOrdering {
+	/// Keep the order of the transaction queue (highest gas price first).
+	GasPrice,
+	/// Take one transaction from each sender in turn.
+	SenderFair,
+	/// Put transactions listed in the ordering hint first (in the hinted order),
+	/// followed by the rest in the order of the transaction queue.
+	Hinted,
+}
+
+impl Default for BlockOrdering {
+	fn default() -> Self {
+		BlockOrdering::GasPrice
+	}
+}
+
+impl BlockOrdering {
+	/// Reorders transactions coming from the queue in priority order.
+	/// `hint` is only taken into account by `Hinted` ordering.
+	pub fn order(&self, transactions: Vec<SignedTransaction>, hint: &[H256]) -> Vec<SignedTransaction> {
+		match *self {
+			BlockOrdering::GasPrice => transactions,
+			BlockOrdering::SenderFair => sender_fair(transactions),
+			BlockOrdering::Hinted => hinted(transactions, hint),
+		}
+	}
+}
+
+/// Splits transactions into per-sender chains, returning senders in the order of their first transaction.
+fn by_sender(transactions: Vec<SignedTransaction>) -> (Vec<Address>, HashMap<Address, VecDeque<SignedTransaction>>) {
+	let mut senders = Vec::new();
+	let mut chains: HashMap<Address, VecDeque<SignedTransaction>> = HashMap::new();
+	for tx in transactions {
+		let sender = tx.sender();
+		if !chains.contains_key(&sender) {
+			senders.push(sender);
+		}
+		chains.entry(sender).or_insert_with(VecDeque::new).push_back(tx);
+	}
+	(senders, chains)
+}
+
+fn sender_fair(transactions: Vec<SignedTransaction>) -> Vec<SignedTransaction> {
+	let len = transactions.len();
+	let (senders, mut chains) = by_sender(transactions);
+	let mut result = Vec::with_capacity(len);
+	while result.len() < len {
+		for sender in &senders {
+			if let Some(tx) = chains.get_mut(sender).and_then(|chain| chain.pop_front()) {
+				result.push(tx);
+			}
+		}
+	}
+	result
+}
+
+fn hinted(transactions: Vec<SignedTransaction>, hint: &[H256]) -> Vec<SignedTransaction> {
+	if hint.is_empty() {
+		return transactions;
+	}
+
+	let senders = transactions.iter().map(|tx| (tx.hash(), tx.sender())).collect::<HashMap<_, _>>();
+	let mut result = Vec::with_capacity(transactions.len());
+	let mut taken = HashSet::new();
+	{
+		let (_, mut chains) = by_sender(transactions.clone());
+		for hash in hint {
+			if taken.contains(hash) {
+				continue;
+			}
+			let sender = match senders.get(hash) {
+				Some(sender) => *sender,
+				None => continue,
+			};
+			let chain = match chains.get_mut(&sender) {
+				Some(chain) => chain,
+				None => continue,
+			};
+			// Transactions with lower nonces have to go first.
+			while let Some(tx) = chain.pop_front() {
+				let tx_hash = tx.hash();
+				taken.insert(tx_hash);
+				result.push(tx);
+				if &tx_hash == hash {
+					break;
+				}
+			}
+		}
+	}
+
+	result.extend(transactions.into_iter().filter(|tx| !taken.contains(&tx.hash())));
+	result
+}
+
+#[cfg(test)]
+mod tests {
+	use super::BlockOrdering;
+	use ethkey::{Random, Generator, KeyPair};
+	use transaction::{SignedTransaction, Transaction, Action};
+	use util::{U256, H256};
+
+	fn tx(keypair: &KeyPair, nonce: u64) -> SignedTransaction {
+		Transaction {
+			action: Action::Create,
+			value: U256::zero(),
+			data: vec![],
+			gas: U256::from(100_000),
+			gas_price: U256::from(1),
+			nonce: U256::from(nonce),
+		}.sign(keypair.secret(), None)
+	}
+
+	fn hashes(transactions: &[SignedTransaction]) -> Vec<H256> {
+		transactions.iter().map(|tx| tx.hash()).collect()
+	}
+
+	#[test]
+	fn should_keep_queue_order_for_gas_price_ordering() {
+		let (a, b) = (Random.generate().unwrap(), Random.generate().unwrap());
+		let txs = vec![tx(&a, 0), tx(&a, 1), tx(&b, 0)];
+
+		let ordered = BlockOrdering::GasPrice.order(txs.clone(), &[txs[2].hash()]);
+
+		assert_eq!(hashes(&ordered), hashes(&txs));
+	}
+
+	#[test]
+	fn should_alternate_senders_for_sender_fair_ordering() {
+		let (a, b) = (Random.generate().unwrap(), Random.generate().unwrap());
+		let txs = vec![tx(&a, 0), tx(&a, 1), tx(&a, 2), tx(&b, 0), tx(&b, 1)];
+
+		let ordered = BlockOrdering::SenderFair.order(txs.clone(), &[]);
+
+		assert_eq!(hashes(&ordered), hashes(&[
+			txs[0].clone(), txs[3].clone(), txs[1].clone(), txs[4].clone(), txs[2].clone()
+		]));
+	}
+
+	#[test]
+	fn should_put_hinted_transactions_first_keeping_nonce_order() {
+		let (a, b) = (Random.generate().unwrap(), Random.generate().unwrap());
+		let txs = vec![tx(&a, 0), tx(&a, 1), tx(&b, 0), tx(&b, 1)];
+
+		// b's second transaction needs b's first one to be included before.
+		let ordered = BlockOrdering::Hinted.order(txs.clone(), &[txs[3].hash(), H256::from(5), txs[1].hash()]);
+
+		assert_eq!(hashes(&ordered), hashes(&[
+			txs[2].clone(), txs[3].clone(), txs[0].clone(), txs[1].clone()
+		]));
+	}
+}
@@ -0,0 +1,94 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Gradual adjustment of the sealing block gas limit towards a configured target.
+
+use std::cmp;
+use util::U256;
+
+/// Default number of blocks over which the remaining distance to the target is spread.
+pub const DEFAULT_SMOOTHING: u64 = 4;
+
+/// Moves the gas limit of consecutive blocks towards `target`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GasLimitTarget {
+	/// Gas limit to converge to.
+	pub target: U256,
+	/// Fraction (`1 / smoothing`) of the remaining distance covered by a single block.
+	/// `1` moves at the fastest rate the engine allows.
+	pub smoothing: u64,
+}
+
+impl GasLimitTarget {
+	/// Creates a new controller with default smoothing.
+	pub fn new(target: U256) -> Self {
+		GasLimitTarget {
+			target: target,
+			smoothing: DEFAULT_SMOOTHING,
+		}
+	}
+
+	/// Gas limit to use for a block with parent's gas limit equal to `parent_gas_limit`.
+	/// `bound_divisor` is the engine's bound on the change between consecutive blocks (if any).
+	pub fn next_gas_limit(&self, parent_gas_limit: U256, bound_divisor: Option<U256>) -> U256 {
+		let distance = match parent_gas_limit > self.target {
+			true => parent_gas_limit - self.target,
+			false => self.target - parent_gas_limit,
+		};
+		let step = cmp::max(distance / cmp::max(self.smoothing, 1).into(), cmp::min(distance, 1.into()));
+		let step = match bound_divisor {
+			Some(ref divisor) if !divisor.is_zero() => {
+				let max_step = parent_gas_limit / *divisor;
+				cmp::min(step, if max_step.is_zero() { max_step } else { max_step - 1.into() })
+			},
+			_ => step,
+		};
+
+		match parent_gas_limit > self.target {
+			true => parent_gas_limit - step,
+			false => parent_gas_limit + step,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::U256;
+	use super::GasLimitTarget;
+
+	#[test]
+	fn should_move_towards_target_within_engine_bounds() {
+		let target = GasLimitTarget::new(8_000_000.into());
+
+		// bounded by the engine
+		assert_eq!(target.next_gas_limit(4_700_000.into(), Some(1024.into())), U256::from(4_704_588));
+		assert_eq!(target.next_gas_limit(9_000_000.into(), Some(1024.into())), U256::from(8_991_212));
+		// smoothed
+		assert_eq!(target.next_gas_limit(7_990_000.into(), Some(1024.into())), U256::from(7_992_500));
+		assert_eq!(target.next_gas_limit(8_000_003.into(), Some(1024.into())), U256::from(8_000_002));
+		// no engine bounds
+		assert_eq!(target.next_gas_limit(4_000_000.into(), None), U256::from(5_000_000));
+		assert_eq!(target.next_gas_limit(8_000_000.into(), None), U256::from(8_000_000));
+	}
+
+	#[test]
+	fn should_jump_as_far_as_possible_without_smoothing() {
+		let target = GasLimitTarget { target: 8_000_000.into(), smoothing: 1 };
+
+		assert_eq!(target.next_gas_limit(7_999_000.into(), Some(1024.into())), U256::from(8_000_000));
+		assert_eq!(target.next_gas_limit(1_000_000.into(), None), U256::from(8_000_000));
+	}
+}
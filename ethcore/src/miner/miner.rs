@@ -23,19 +23,24 @@ use state::{State, CleanupMode};
 use client::{MiningBlockChainClient, Executive, Executed, EnvInfo, TransactOptions, BlockId, CallAnalytics, TransactionId};
 use client::TransactionImportResult;
 use executive::contract_address;
-use block::{ClosedBlock, IsBlock, Block};
+use block::{ClosedBlock, OpenBlock, IsBlock, Block};
 use error::*;
 use transaction::{Action, UnverifiedTransaction, PendingTransaction, SignedTransaction, Condition as TransactionCondition};
 use receipt::{Receipt, RichReceipt};
 use spec::Spec;
 use engines::{Engine, Seal};
-use miner::{MinerService, MinerStatus, TransactionQueue, TransactionQueueDetailsProvider, PrioritizationStrategy,
+use miner::{MinerService, MinerStatus, MineStats, ReorgStats, TransactionQueue, TransactionQueueDetailsProvider, PrioritizationStrategy,
 	AccountDetails, TransactionOrigin};
 use miner::banning_queue::{BanningTransactionQueue, Threshold};
-use miner::work_notify::{WorkPoster, NotifyWork};
+use miner::block_ordering::BlockOrdering;
+use miner::uncle_strategy::UncleStrategy;
+use miner::gas_limit::GasLimitTarget;
+use miner::work_notify::{WorkPoster, NotifyWork, WorkPackage};
 use miner::price_info::PriceInfo;
 use miner::local_transactions::{Status as LocalTransactionStatus};
 use miner::service_transaction_checker::ServiceTransactionChecker;
+use miner::StratumWorkerInfo;
+use ethcore_stratum::Stratum as StratumService;
 use header::BlockNumber;
 
 /// Different possible definitions for pending transaction set.
@@ -98,6 +103,10 @@ pub struct MinerOptions {
 	pub tx_queue_size: usize,
 	/// Strategy to use for prioritizing transactions in the queue.
 	pub tx_queue_strategy: PrioritizationStrategy,
+	/// Minimal gas price increase (in percents) required to replace a transaction in the queue.
+	pub tx_queue_price_bump: usize,
+	/// Raise the minimal gas price as the queue approaches its limit.
+	pub tx_queue_dynamic_gas_price: bool,
 	/// Whether we should fallback to providing all the queue's transactions or just pending.
 	pub pending_set: PendingSet,
 	/// How many historical work packages can we store before running out?
@@ -108,8 +117,17 @@ pub struct MinerOptions {
 	pub tx_queue_gas_limit: GasLimit,
 	/// Banning settings.
 	pub tx_queue_banning: Banning,
+	/// Number of invalid transactions a sender may submit before getting banned (0 disables).
+	pub tx_queue_ban_invalid: u16,
 	/// Do we refuse to accept service transactions even if sender is certified.
 	pub refuse_service_transactions: bool,
+	/// Ordering of transactions in the block prepared for sealing.
+	pub block_ordering: BlockOrdering,
+	/// Strategy of including uncles in the block prepared for sealing.
+	pub uncle_strategy: UncleStrategy,
+	/// Senders which transactions are treated like local ones: they bypass the gas price
+	/// and size limits of the queue and are included in the sealing block first.
+	pub priority_senders: HashSet<Address>,
 }
 
 impl Default for MinerOptions {
@@ -123,13 +141,19 @@ impl Default for MinerOptions {
 			tx_queue_size: 1024,
 			tx_queue_gas_limit: GasLimit::Auto,
 			tx_queue_strategy: PrioritizationStrategy::GasPriceOnly,
+			tx_queue_price_bump: 0,
+			tx_queue_dynamic_gas_price: false,
 			pending_set: PendingSet::AlwaysQueue,
 			reseal_min_period: Duration::from_secs(2),
 			reseal_max_period: Duration::from_secs(120),
 			work_queue_size: 20,
 			enable_resubmission: true,
 			tx_queue_banning: Banning::Disabled,
+			tx_queue_ban_invalid: 0,
 			refuse_service_transactions: false,
+			block_ordering: BlockOrdering::GasPrice,
+			uncle_strategy: UncleStrategy::All,
+			priority_senders: HashSet::new(),
 		}
 	}
 }
@@ -208,6 +232,36 @@ struct SealingWork {
 	enabled: bool,
 }
 
+/// Post-state of the current pending block used to answer queries against the `pending` tag.
+/// A new snapshot replaces the old one whenever work is pushed, so readers never hold
+/// the sealing lock and sealing never waits for a long running `eth_call`.
+struct PendingSnapshot {
+	number: BlockNumber,
+	author: Address,
+	timestamp: u64,
+	difficulty: U256,
+	// Only locked for the duration of a fork.
+	state: Mutex<State<::state_db::StateDB>>,
+}
+
+impl PendingSnapshot {
+	fn new(block: &ClosedBlock) -> Self {
+		let header = block.block().header();
+		PendingSnapshot {
+			number: header.number(),
+			author: *header.author(),
+			timestamp: header.timestamp(),
+			difficulty: *header.difficulty(),
+			state: Mutex::new(block.block().state().fork()),
+		}
+	}
+
+	/// Copy-on-write fork of the pending state.
+	fn state(&self) -> State<::state_db::StateDB> {
+		self.state.lock().fork()
+	}
+}
+
 /// Keeps track of transactions using priority queue and holds currently mined block.
 /// Handles preparing work for "work sealing" or seals "internally" if Engine does not require work.
 pub struct Miner {
@@ -221,12 +275,23 @@ pub struct Miner {
 	options: MinerOptions,
 
 	gas_range_target: RwLock<(U256, U256)>,
+	gas_limit_target: RwLock<Option<GasLimitTarget>>,
 	author: RwLock<Address>,
 	extra_data: RwLock<Bytes>,
+	ordering_hint: RwLock<Vec<H256>>,
+	mine_stats: RwLock<MineStats>,
+	reorg_stats: RwLock<ReorgStats>,
 	engine: Arc<Engine>,
 
 	accounts: Option<Arc<AccountProvider>>,
 	notifiers: RwLock<Vec<Box<NotifyWork>>>,
+	stratum: RwLock<Option<Arc<StratumService>>>,
+	transaction_listeners: RwLock<Vec<Box<Fn(&[H256]) + Send + Sync>>>,
+	work_listeners: RwLock<Vec<Box<Fn(&WorkPackage) + Send + Sync>>>,
+	/// Last work package pushed to the sealing queue, served to repeated work requests.
+	last_work: RwLock<Option<WorkPackage>>,
+	/// Post-state of the last pending block, shared with RPC readers.
+	pending_snapshot: RwLock<Option<Arc<PendingSnapshot>>>,
 	gas_pricer: Mutex<GasPricer>,
 	service_transaction_action: ServiceTransactionAction,
 }
@@ -238,6 +303,22 @@ impl Miner {
 		self.sealing_work.lock().enabled = true;
 	}
 
+	/// Set stratum service which statistics are reported for connected workers.
+	pub fn set_stratum_service(&self, service: Arc<StratumService>) {
+		*self.stratum.write() = Some(service);
+	}
+
+	/// Set a callback to be notified with the hashes of transactions added to the queue.
+	pub fn add_transactions_listener(&self, f: Box<Fn(&[H256]) + Send + Sync>) {
+		self.transaction_listeners.write().push(f);
+	}
+
+	/// Set a callback to be notified of every new work package.
+	/// Unlike work notifiers it doesn't enable sealing on its own.
+	pub fn add_work_listener(&self, f: Box<Fn(&WorkPackage) + Send + Sync>) {
+		self.work_listeners.write().push(f);
+	}
+
 	/// Creates new instance of miner Arc.
 	pub fn new(options: MinerOptions, gas_pricer: GasPricer, spec: &Spec, accounts: Option<Arc<AccountProvider>>) -> Arc<Miner> {
 		Arc::new(Miner::new_raw(options, gas_pricer, spec, accounts))
@@ -250,8 +331,10 @@ impl Miner {
 			_ => !U256::zero(),
 		};
 
-		let txq = TransactionQueue::with_limits(options.tx_queue_strategy, options.tx_queue_size, gas_limit, options.tx_gas_limit);
-		let txq = match options.tx_queue_banning {
+		let mut txq = TransactionQueue::with_limits(options.tx_queue_strategy, options.tx_queue_size, gas_limit, options.tx_gas_limit);
+		txq.set_replacement_gas_price_bump(options.tx_queue_price_bump);
+		txq.set_dynamic_gas_price(options.tx_queue_dynamic_gas_price);
+		let mut txq = match options.tx_queue_banning {
 			Banning::Disabled => BanningTransactionQueue::new(txq, Threshold::NeverBan, Duration::from_secs(180)),
 			Banning::Enabled { ban_duration, min_offends, .. } => BanningTransactionQueue::new(
 				txq,
@@ -259,6 +342,9 @@ impl Miner {
 				ban_duration,
 			),
 		};
+		if options.tx_queue_ban_invalid > 0 {
+			txq.set_invalid_threshold(Threshold::BanAfter(options.tx_queue_ban_invalid));
+		}
 
 		let notifiers: Vec<Box<NotifyWork>> = match options.new_work_notify.is_empty() {
 			true => Vec::new(),
@@ -282,12 +368,21 @@ impl Miner {
 					|| spec.engine.seals_internally().is_some()
 			}),
 			gas_range_target: RwLock::new((U256::zero(), U256::zero())),
+			gas_limit_target: RwLock::new(None),
 			author: RwLock::new(Address::default()),
 			extra_data: RwLock::new(Vec::new()),
+			ordering_hint: RwLock::new(Vec::new()),
+			mine_stats: RwLock::new(MineStats::default()),
+			reorg_stats: RwLock::new(ReorgStats::default()),
 			options: options,
 			accounts: accounts,
 			engine: spec.engine.clone(),
 			notifiers: RwLock::new(notifiers),
+			stratum: RwLock::new(None),
+			transaction_listeners: RwLock::new(Vec::new()),
+			work_listeners: RwLock::new(Vec::new()),
+			last_work: RwLock::new(None),
+			pending_snapshot: RwLock::new(None),
 			gas_pricer: Mutex::new(gas_pricer),
 			service_transaction_action: service_transaction_action,
 		}
@@ -310,11 +405,13 @@ impl Miner {
 	/// Clear all pending block states
 	pub fn clear(&self) {
 		self.sealing_work.lock().queue.reset();
+		self.set_pending_snapshot(None);
 	}
 
-	/// Get `Some` `clone()` of the current pending block's state or `None` if we're not sealing.
+	/// Get `Some` fork of the current pending block's state or `None` if we're not sealing.
 	pub fn pending_state(&self) -> Option<State<::state_db::StateDB>> {
-		self.sealing_work.lock().queue.peek_last_ref().map(|b| b.block().fields().state.clone())
+		let snapshot = self.pending_snapshot.read().clone();
+		snapshot.map(|s| s.state())
 	}
 
 	/// Get `Some` `clone()` of the current pending block or `None` if we're not sealing.
@@ -327,8 +424,10 @@ impl Miner {
 	fn prepare_block(&self, chain: &MiningBlockChainClient) -> (ClosedBlock, Option<H256>) {
 		let _timer = PerfTimer::new("prepare_block");
 		let chain_info = chain.chain_info();
-		let (transactions, mut open_block, original_work_hash) = {
+		let (transactions, mut open_block, fresh_block, original_work_hash) = {
 			let transactions = {self.transaction_queue.read().top_transactions_at(chain_info.best_block_number, chain_info.best_block_timestamp)};
+			let transactions = self.options.block_ordering.order(transactions, &self.ordering_hint.read());
+			let transactions = self.priority_first(transactions);
 			let mut sealing_work = self.sealing_work.lock();
 			let last_work_hash = sealing_work.queue.peek_last_ref().map(|pb| pb.block().fields().header.hash());
 			let best_hash = chain_info.best_block_hash;
@@ -340,23 +439,23 @@ impl Miner {
 			//   otherwise, leave everything alone.
 			// otherwise, author a fresh block.
 */
-			let open_block = match sealing_work.queue.pop_if(|b| b.block().fields().header.parent_hash() == &best_hash) {
+			let (open_block, fresh_block) = match sealing_work.queue.pop_if(|b| b.block().fields().header.parent_hash() == &best_hash) {
 				Some(old_block) => {
 					trace!(target: "miner", "prepare_block: Already have previous work; updating and returning");
 					// add transactions to old_block
-					old_block.reopen(&*self.engine)
+					(old_block.reopen(&*self.engine), false)
 				}
 				None => {
 					// block not found - create it.
 					trace!(target: "miner", "prepare_block: No existing work - making new block");
-					chain.prepare_open_block(
+					(chain.prepare_open_block(
 						self.author(),
-						(self.gas_floor_target(), self.gas_ceil_target()),
+						self.next_gas_range(chain),
 						self.extra_data()
-					)
+					), true)
 				}
 			};
-			(transactions, open_block, last_work_hash)
+			(transactions, open_block, fresh_block, last_work_hash)
 		};
 
 		let mut invalid_transactions = HashSet::new();
@@ -423,6 +522,11 @@ impl Miner {
 		}
 		trace!(target: "miner", "Pushed {}/{} transactions", tx_count, tx_total);
 
+		// uncles are scored once transactions (and so fees at stake) are known
+		if fresh_block && self.options.uncle_strategy != UncleStrategy::All {
+			self.select_uncles(chain, &mut open_block);
+		}
+
 		let block = open_block.close();
 
 		let fetch_nonce = |a: &Address| chain.latest_nonce(a);
@@ -439,6 +543,54 @@ impl Miner {
 		(block, original_work_hash)
 	}
 
+	/// Replaces uncles of `open_block` with candidates chosen by the uncle strategy.
+	fn select_uncles(&self, chain: &MiningBlockChainClient, open_block: &mut OpenBlock) {
+		let (number, author, parent_hash) = {
+			let header = open_block.header();
+			(header.number(), header.author().clone(), header.parent_hash().clone())
+		};
+		let candidates = chain.find_uncles(&parent_hash).unwrap_or_else(Vec::new).into_iter()
+			.filter_map(|hash| chain.block_header(BlockId::Hash(hash)))
+			.map(|header| header.decode())
+			.collect();
+
+		// transaction fees are lost together with the block reward if the block is orphaned
+		let fees = {
+			let mut gas_used = U256::zero();
+			open_block.transactions().iter().zip(open_block.receipts()).fold(U256::zero(), |fees, (tx, receipt)| {
+				let tx_gas_used = receipt.gas_used - gas_used;
+				gas_used = receipt.gas_used;
+				fees + tx.gas_price * tx_gas_used
+			})
+		};
+
+		let engine = &*self.engine;
+		let uncles = self.options.uncle_strategy.select(
+			candidates,
+			engine.maximum_uncle_count(),
+			&author,
+			engine.block_reward() + fees,
+			|uncle| engine.uncle_rewards(number, uncle),
+		);
+
+		open_block.take_uncles();
+		for uncle in uncles {
+			if let Err(e) = open_block.push_uncle(uncle) {
+				debug!(target: "miner", "Unable to include uncle: {}", e);
+			}
+		}
+		trace!(target: "miner", "Included {} uncles in block #{}", open_block.uncles().len(), number);
+	}
+
+	/// Accounts uncles of a block mined on top of `parent_hash`.
+	fn note_mined_block(&self, chain: &MiningBlockChainClient, parent_hash: &H256, uncles: usize) {
+		let candidates = chain.find_uncles(parent_hash).map_or(0, |candidates| candidates.len());
+		let mut stats = self.mine_stats.write();
+		stats.blocks += 1;
+		stats.uncles_included += uncles as u64;
+		stats.uncles_missed += candidates.saturating_sub(uncles) as u64;
+	}
+
 	/// Asynchronously updates minimal gas price for transaction queue
 	pub fn recalibrate_minimal_gas_price(&self) {
 		debug!(target: "miner", "minimal_gas_price: recalibrating...");
@@ -468,6 +620,7 @@ impl Miner {
 				trace!(target: "miner", "Miner sleeping (current {}, last {})", best_block, last_request);
 				sealing_work.enabled = false;
 				sealing_work.queue.reset();
+				self.set_pending_snapshot(None);
 				false
 			} else {
 				// sealing enabled and we don't want to sleep.
@@ -491,6 +644,7 @@ impl Miner {
 					*self.next_mandatory_reseal.write() = Instant::now() + self.options.reseal_max_period;
 					{
 						let mut sealing_work = self.sealing_work.lock();
+						self.set_pending_snapshot(Some(&block));
 						sealing_work.queue.push(block.clone());
 						sealing_work.queue.use_last_ref();
 					}
@@ -530,16 +684,17 @@ impl Miner {
 			trace!(target: "miner", "prepare_work: Checking whether we need to reseal: orig={:?} last={:?}, this={:?}", original_work_hash, last_work_hash, block.block().fields().header.hash());
 			let (work, is_new) = if last_work_hash.map_or(true, |h| h != block.block().fields().header.hash()) {
 				trace!(target: "miner", "prepare_work: Pushing a new, refreshed or borrowed pending {}...", block.block().fields().header.hash());
-				let pow_hash = block.block().fields().header.hash();
-				let number = block.block().fields().header.number();
-				let difficulty = *block.block().fields().header.difficulty();
+				let work = WorkPackage::from(&block);
 				let is_new = original_work_hash.map_or(true, |h| block.block().fields().header.hash() != h);
+				self.set_pending_snapshot(Some(&block));
 				sealing_work.queue.push(block);
+				*self.last_work.write() = Some(work.clone());
 				// If push notifications are enabled we assume all work items are used.
-				if !self.notifiers.read().is_empty() && is_new {
+				let has_listeners = !self.notifiers.read().is_empty() || !self.work_listeners.read().is_empty();
+				if has_listeners && is_new {
 					sealing_work.queue.use_last_ref();
 				}
-				(Some((pow_hash, difficulty, number)), is_new)
+				(Some(work), is_new)
 			} else {
 				(None, false)
 			};
@@ -547,9 +702,12 @@ impl Miner {
 			(work, is_new)
 		};
 		if is_new {
-			work.map(|(pow_hash, difficulty, number)| {
+			work.map(|work| {
 				for notifier in self.notifiers.read().iter() {
-					notifier.notify(pow_hash, difficulty, number)
+					notifier.notify(work.pow_hash, work.difficulty, work.number)
+				}
+				for listener in self.work_listeners.read().iter() {
+					listener(&work)
 				}
 			});
 		}
@@ -628,12 +786,16 @@ impl Miner {
 						Err(e)
 					},
 					Ok(transaction) => {
-						let origin = accounts.as_ref().and_then(|accounts| {
-							match accounts.contains(&transaction.sender()) {
-								true => Some(TransactionOrigin::Local),
-								false => None,
-							}
-						}).unwrap_or(default_origin);
+						let sender = transaction.sender();
+						let origin = match self.options.priority_senders.contains(&sender) {
+							true => TransactionOrigin::Local,
+							false => accounts.as_ref().and_then(|accounts| {
+								match accounts.contains(&sender) {
+									true => Some(TransactionOrigin::Local),
+									false => None,
+								}
+							}).unwrap_or(default_origin),
+						};
 
 						// try to install service transaction checker before appending transactions
 						self.service_transaction_action.update_from_chain_client(client);
@@ -653,9 +815,65 @@ impl Miner {
 			.collect()
 	}
 
+	/// Let the listeners know of the transactions which have been imported.
+	fn notify_imported(&self, hashes: Vec<H256>) {
+		if hashes.is_empty() {
+			return;
+		}
+		for listener in &*self.transaction_listeners.read() {
+			listener(&hashes);
+		}
+	}
+
 	/// Are we allowed to do a non-mandatory reseal?
 	fn tx_reseal_allowed(&self) -> bool { Instant::now() > *self.next_allowed_reseal.lock() }
 
+	/// Moves transactions of priority senders to the front, keeping the order otherwise.
+	fn priority_first(&self, transactions: Vec<SignedTransaction>) -> Vec<SignedTransaction> {
+		if self.options.priority_senders.is_empty() {
+			return transactions;
+		}
+		let (mut priority, rest): (Vec<_>, Vec<_>) = transactions.into_iter()
+			.partition(|tx| self.options.priority_senders.contains(&tx.sender()));
+		priority.extend(rest);
+		priority
+	}
+
+	/// Gas limit range passed to the engine for a new block.
+	/// Collapses to a single value on the way to the gas limit target, if there is one.
+	fn next_gas_range(&self, chain: &MiningBlockChainClient) -> (U256, U256) {
+		match *self.gas_limit_target.read() {
+			Some(ref target) => {
+				let gas_limit = target.next_gas_limit(chain.best_block_header().gas_limit(), self.engine.gas_limit_bound_divisor());
+				trace!(target: "miner", "next_gas_range: moving gas limit towards {}: {}", target.target, gas_limit);
+				(gas_limit, gas_limit)
+			},
+			None => *self.gas_range_target.read(),
+		}
+	}
+
+	fn set_pending_snapshot(&self, block: Option<&ClosedBlock>) {
+		*self.pending_snapshot.write() = block.map(|b| Arc::new(PendingSnapshot::new(b)));
+	}
+
+	/// Returns the pending snapshot if it's built on top of the latest block.
+	fn pending_snapshot(&self, latest_block_number: BlockNumber) -> Option<Arc<PendingSnapshot>> {
+		let snapshot = self.pending_snapshot.read().clone();
+		match snapshot {
+			Some(s) => if s.number > latest_block_number { Some(s) } else { None },
+			None => None,
+		}
+	}
+
+	#[cfg_attr(feature="dev", allow(wrong_self_convention))]
+	fn from_pending_state<H, F, G>(&self, latest_block_number: BlockNumber, from_chain: F, map_state: G) -> H
+		where F: Fn() -> H, G: Fn(&State<::state_db::StateDB>) -> H {
+		match self.pending_snapshot(latest_block_number) {
+			Some(snapshot) => map_state(&snapshot.state()),
+			None => from_chain(),
+		}
+	}
+
 	#[cfg_attr(feature="dev", allow(wrong_self_convention))]
 	#[cfg_attr(feature="dev", allow(redundant_closure))]
 	fn from_pending_block<H, F, G>(&self, latest_block_number: BlockNumber, from_chain: F, map_block: G) -> H
@@ -698,26 +916,25 @@ impl MinerService for Miner {
 	}
 
 	fn call(&self, client: &MiningBlockChainClient, t: &SignedTransaction, analytics: CallAnalytics) -> Result<Executed, CallError> {
-		let sealing_work = self.sealing_work.lock();
-		match sealing_work.queue.peek_last_ref() {
-			Some(work) => {
-				let block = work.block();
-
+		match self.pending_snapshot(client.chain_info().best_block_number) {
+			Some(snapshot) => {
 				// TODO: merge this code with client.rs's fn call somwhow.
-				let header = block.header();
 				let last_hashes = Arc::new(client.last_hashes());
 				let env_info = EnvInfo {
-					number: header.number(),
-					author: *header.author(),
-					timestamp: header.timestamp(),
-					difficulty: *header.difficulty(),
+					number: snapshot.number,
+					author: snapshot.author,
+					timestamp: snapshot.timestamp,
+					difficulty: snapshot.difficulty,
 					last_hashes: last_hashes,
 					gas_used: U256::zero(),
 					gas_limit: U256::max_value(),
 				};
 				// that's just a copy of the state.
-				let mut state = block.state().clone();
-				let original_state = if analytics.state_diffing { Some(state.clone()) } else { None };
+				let mut state = snapshot.state();
+				for (address, account) in &analytics.state_override {
+					state.apply_override(address, account).map_err(ExecutionError::from)?;
+				}
+				let original_state = if analytics.state_diffing { Some(state.fork()) } else { None };
 
 				let sender = t.sender();
 				let balance = state.balance(&sender).map_err(ExecutionError::from)?;
@@ -727,7 +944,7 @@ impl MinerService for Miner {
 					state.add_balance(&sender, &(needed_balance - balance), CleanupMode::NoEmpty)
 						.map_err(ExecutionError::from)?;
 				}
-				let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, check_nonce: false };
+				let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, check_nonce: false, gas_profiling: analytics.gas_profiling, struct_logging: analytics.struct_logging };
 				let mut ret = Executive::new(&mut state, &env_info, &*self.engine, client.vm_factory()).transact(t, options)?;
 
 				// TODO gav move this into Executive.
@@ -744,34 +961,34 @@ impl MinerService for Miner {
 	// TODO: The `chain.latest_x` actually aren't infallible, they just panic on corruption.
 	// TODO: return trie::Result<T> here, or other.
 	fn balance(&self, chain: &MiningBlockChainClient, address: &Address) -> Option<U256> {
-		self.from_pending_block(
+		self.from_pending_state(
 			chain.chain_info().best_block_number,
 			|| Some(chain.latest_balance(address)),
-			|b| b.block().fields().state.balance(address).ok(),
+			|s| s.balance(address).ok(),
 		)
 	}
 
 	fn storage_at(&self, chain: &MiningBlockChainClient, address: &Address, position: &H256) -> Option<H256> {
-		self.from_pending_block(
+		self.from_pending_state(
 			chain.chain_info().best_block_number,
 			|| Some(chain.latest_storage_at(address, position)),
-			|b| b.block().fields().state.storage_at(address, position).ok(),
+			|s| s.storage_at(address, position).ok(),
 		)
 	}
 
 	fn nonce(&self, chain: &MiningBlockChainClient, address: &Address) -> Option<U256> {
-		self.from_pending_block(
+		self.from_pending_state(
 			chain.chain_info().best_block_number,
 			|| Some(chain.latest_nonce(address)),
-			|b| b.block().fields().state.nonce(address).ok(),
+			|s| s.nonce(address).ok(),
 		)
 	}
 
 	fn code(&self, chain: &MiningBlockChainClient, address: &Address) -> Option<Option<Bytes>> {
-		self.from_pending_block(
+		self.from_pending_state(
 			chain.chain_info().best_block_number,
 			|| Some(chain.latest_code(address)),
-			|b| b.block().fields().state.code(address).ok().map(|c| c.map(|c| (&*c).clone()))
+			|s| s.code(address).ok().map(|c| c.map(|c| (&*c).clone()))
 		)
 	}
 
@@ -817,17 +1034,25 @@ impl MinerService for Miner {
 		self.gas_range_target.write().1 = target;
 	}
 
+	fn gas_limit_target(&self) -> Option<U256> {
+		self.gas_limit_target.read().as_ref().map(|t| t.target)
+	}
+
+	fn set_gas_limit_target(&self, target: Option<U256>) {
+		*self.gas_limit_target.write() = target.map(GasLimitTarget::new);
+	}
+
 	fn set_minimal_gas_price(&self, min_gas_price: U256) {
 		self.transaction_queue.write().set_minimal_gas_price(min_gas_price);
 	}
 
 	fn minimal_gas_price(&self) -> U256 {
-		*self.transaction_queue.read().minimal_gas_price()
+		self.transaction_queue.read().current_minimal_gas_price()
 	}
 
 	fn sensible_gas_price(&self) -> U256 {
 		// 10% above our minimum.
-		*self.transaction_queue.read().minimal_gas_price() * 110.into() / 100.into()
+		self.transaction_queue.read().current_minimal_gas_price() * 110.into() / 100.into()
 	}
 
 	fn sensible_gas_limit(&self) -> U256 {
@@ -846,6 +1071,10 @@ impl MinerService for Miner {
 		self.transaction_queue.write().set_tx_gas_limit(limit)
 	}
 
+	fn set_transaction_ordering_hint(&self, hashes: Vec<H256>) {
+		*self.ordering_hint.write() = hashes;
+	}
+
 	/// Get the author that we will seal blocks as.
 	fn author(&self) -> Address {
 		*self.author.read()
@@ -872,12 +1101,17 @@ impl MinerService for Miner {
 		transactions: Vec<UnverifiedTransaction>
 	) -> Vec<Result<TransactionImportResult, Error>> {
 		trace!(target: "external_tx", "Importing external transactions");
+		let hashes: Vec<_> = transactions.iter().map(|tx| tx.hash()).collect();
 		let results = {
 			let mut transaction_queue = self.transaction_queue.write();
 			self.add_transactions_to_queue(
 				chain, transactions, TransactionOrigin::External, None, &mut transaction_queue
 			)
 		};
+		self.notify_imported(hashes.into_iter().zip(results.iter())
+			.filter(|&(_, result)| result.is_ok())
+			.map(|(hash, _)| hash)
+			.collect());
 
 		if !results.is_empty() && self.options.reseal_on_external_tx &&	self.tx_reseal_allowed() {
 			// --------------------------------------------------------------------------
@@ -898,6 +1132,7 @@ impl MinerService for Miner {
 
 		trace!(target: "own_tx", "Importing transaction: {:?}", pending);
 
+		let hash = pending.transaction.hash();
 		let imported = {
 			// Be sure to release the lock before we call prepare_work_sealing
 			let mut transaction_queue = self.transaction_queue.write();
@@ -917,6 +1152,9 @@ impl MinerService for Miner {
 			}
 			import
 		};
+		if imported.is_ok() {
+			self.notify_imported(vec![hash]);
+		}
 
 		// --------------------------------------------------------------------------
 		// | NOTE Code below requires transaction_queue and sealing_work locks.     |
@@ -949,6 +1187,22 @@ impl MinerService for Miner {
 			.collect()
 	}
 
+	fn banned_transaction_senders(&self) -> Vec<Address> {
+		self.transaction_queue.write().banned_senders()
+	}
+
+	fn mine_stats(&self) -> MineStats {
+		self.mine_stats.read().clone()
+	}
+
+	fn reorg_stats(&self) -> ReorgStats {
+		self.reorg_stats.read().clone()
+	}
+
+	fn stratum_workers(&self) -> Vec<StratumWorkerInfo> {
+		self.stratum.read().as_ref().map_or_else(Vec::new, |stratum| stratum.workers())
+	}
+
 	fn future_transactions(&self) -> Vec<PendingTransaction> {
 		self.transaction_queue.read().future_transactions()
 	}
@@ -1122,6 +1376,20 @@ impl MinerService for Miner {
 		ret.map(f)
 	}
 
+	fn work_package(&self, chain: &MiningBlockChainClient) -> Option<WorkPackage> {
+		let cached = self.last_work.read().clone();
+		if let Some(work) = cached {
+			// cached package is valid as long as it's still the last one in the sealing queue
+			let is_current = self.sealing_work.lock().queue.use_last_ref().map_or(false, |b| b.hash() == work.pow_hash);
+			if is_current {
+				trace!(target: "miner", "work_package: serving cached {}", work.pow_hash);
+				*self.sealing_block_last_request.lock() = chain.chain_info().best_block_number;
+				return Some(work);
+			}
+		}
+		self.map_sealing_work(chain, |b| WorkPackage::from(b))
+	}
+
 	fn submit_seal(&self, chain: &MiningBlockChainClient, block_hash: H256, seal: Vec<Bytes>) -> Result<(), Error> {
 		let result =
 			if let Some(b) = self.sealing_work.lock().queue.get_used_if(
@@ -1144,7 +1412,10 @@ impl MinerService for Miner {
 		result.and_then(|sealed| {
 			let n = sealed.header().number();
 			let h = sealed.header().hash();
+			let parent_hash = sealed.header().parent_hash().clone();
+			let uncles = sealed.uncles().len();
 			chain.import_sealed_block(sealed)?;
+			self.note_mined_block(chain, &parent_hash, uncles);
 			info!(target: "miner", "Submitted block imported OK. #{}: {}", Colour::White.bold().paint(format!("{}", n)), Colour::White.bold().paint(h.hex()));
 			Ok(())
 		})
@@ -1164,29 +1435,49 @@ impl MinerService for Miner {
 		// Update minimal gas price
 		self.recalibrate_minimal_gas_price();
 
-		// Then import all transactions...
-		{
+		// Then import all transactions which didn't make it to the new canonical chain...
+		if !retracted.is_empty() {
+			let enacted_transactions = enacted.iter()
+				.flat_map(|hash| chain.block(BlockId::Hash(*hash))
+					.expect("Client is sending message after commit to db and inserting to chain; the block is available; qed")
+					.transaction_hashes())
+				.collect::<HashSet<_>>();
 
+			let (mut abandoned, mut reimported) = (0, 0);
 			let mut transaction_queue = self.transaction_queue.write();
 			for hash in retracted {
 				let block = chain.block(BlockId::Hash(*hash))
 					.expect("Client is sending message after commit to db and inserting to chain; the block is available; qed");
-				let txs = block.transactions();
-				let _ = self.add_transactions_to_queue(
+				let txs = block.transactions().into_iter()
+					.filter(|tx| !enacted_transactions.contains(&tx.hash()))
+					.collect::<Vec<_>>();
+				abandoned += txs.len();
+				reimported += self.add_transactions_to_queue(
 					chain, txs, TransactionOrigin::RetractedBlock, None, &mut transaction_queue
-				);
+				).into_iter().filter(|res| res.is_ok()).count();
 			}
+
+			debug!(target: "miner", "Reorg: {} blocks retracted, re-imported {} of {} abandoned transactions", retracted.len(), reimported, abandoned);
+			let mut stats = self.reorg_stats.write();
+			stats.reorgs += 1;
+			stats.abandoned_transactions += abandoned as u64;
+			stats.reimported_transactions += reimported as u64;
 		}
 
 		// ...and at the end remove the old ones
 		{
-			let fetch_account = |a: &Address| AccountDetails {
-				nonce: chain.latest_nonce(a),
-				balance: chain.latest_balance(a),
+			let fetch_account = |a: &Address| {
+				let (nonce, balance) = chain.latest_nonce_and_balance(a);
+				AccountDetails {
+					nonce: nonce,
+					balance: balance,
+				}
 			};
 			let time = chain.chain_info().best_block_number;
 			let mut transaction_queue = self.transaction_queue.write();
 			transaction_queue.remove_old(&fetch_account, time);
+			transaction_queue.update_dynamic_gas_price();
+			transaction_queue.prune_bans();
 		}
 
 		if enacted.len() > 0 {
@@ -1238,9 +1529,10 @@ impl<'a> TransactionDetailsProvider<'a> {
 
 impl<'a> TransactionQueueDetailsProvider for TransactionDetailsProvider<'a> {
 	fn fetch_account(&self, address: &Address) -> AccountDetails {
+		let (nonce, balance) = self.client.latest_nonce_and_balance(address);
 		AccountDetails {
-			nonce: self.client.latest_nonce(address),
-			balance: self.client.latest_balance(address),
+			nonce: nonce,
+			balance: balance,
 		}
 	}
 
@@ -1258,12 +1550,13 @@ mod tests {
 
 	use std::sync::Arc;
 	use std::time::Duration;
-	use super::super::{MinerService, PrioritizationStrategy};
+	use std::collections::HashSet;
+	use super::super::{MinerService, PrioritizationStrategy, ReorgStats};
 	use super::*;
 	use block::IsBlock;
-	use util::{U256, Uint, FromHex};
+	use util::{U256, Uint, FromHex, Mutex};
 	use ethkey::{Generator, Random};
-	use client::{BlockChainClient, TestBlockChainClient, EachBlockWith, TransactionImportResult};
+	use client::{BlockChainClient, BlockId, TestBlockChainClient, EachBlockWith, TransactionImportResult};
 	use header::BlockNumber;
 	use types::transaction::{SignedTransaction, Transaction, PendingTransaction, Action};
 	use spec::Spec;
@@ -1301,6 +1594,25 @@ mod tests {
 		assert!(miner.submit_seal(&client, res.unwrap(), vec![]).is_ok());
 	}
 
+	#[test]
+	fn should_serve_cached_work_package_and_notify_listeners() {
+		// given
+		let client = TestBlockChainClient::default();
+		let miner = Miner::with_spec(&Spec::new_test());
+		let notified = Arc::new(Mutex::new(Vec::new()));
+		let listener_notified = notified.clone();
+		miner.add_work_listener(Box::new(move |work| listener_notified.lock().push(work.pow_hash)));
+
+		// when
+		let work = miner.work_package(&client).expect("Expected work package");
+		let cached = miner.work_package(&client);
+
+		// then
+		assert_eq!(cached, Some(work.clone()));
+		assert_eq!(*notified.lock(), vec![work.pow_hash]);
+		assert!(miner.submit_seal(&client, work.pow_hash, vec![]).is_ok());
+	}
+
 	fn miner() -> Miner {
 		Arc::try_unwrap(Miner::new(
 			MinerOptions {
@@ -1314,11 +1626,17 @@ mod tests {
 				tx_queue_size: 1024,
 				tx_queue_gas_limit: GasLimit::None,
 				tx_queue_strategy: PrioritizationStrategy::GasFactorAndGasPrice,
+				tx_queue_price_bump: 0,
+				tx_queue_dynamic_gas_price: false,
 				pending_set: PendingSet::AlwaysSealing,
 				work_queue_size: 5,
 				enable_resubmission: true,
 				tx_queue_banning: Banning::Disabled,
+				tx_queue_ban_invalid: 0,
 				refuse_service_transactions: false,
+				block_ordering: BlockOrdering::GasPrice,
+				uncle_strategy: UncleStrategy::All,
+				priority_senders: HashSet::new(),
 			},
 			GasPricer::new_fixed(0u64.into()),
 			&Spec::new_test(),
@@ -1376,6 +1694,54 @@ mod tests {
 		assert_eq!(miner.pending_receipts(best_block).len(), 0);
 	}
 
+	#[test]
+	fn should_move_gas_limit_towards_target() {
+		// given
+		let client = TestBlockChainClient::default();
+		let miner = miner();
+		let parent_gas_limit = client.best_block_header().gas_limit();
+		miner.set_gas_floor_target(1_000_000.into());
+		miner.set_gas_ceil_target(2_000_000.into());
+
+		// when
+		miner.set_gas_limit_target(Some(parent_gas_limit + 4_000.into()));
+
+		// then
+		assert_eq!(miner.gas_limit_target(), Some(parent_gas_limit + 4_000.into()));
+		assert_eq!(miner.next_gas_range(&client), (parent_gas_limit + 1_000.into(), parent_gas_limit + 1_000.into()));
+
+		// when
+		miner.set_gas_limit_target(None);
+
+		// then
+		assert_eq!(miner.gas_limit_target(), None);
+		assert_eq!(miner.next_gas_range(&client), (1_000_000.into(), 2_000_000.into()));
+	}
+
+	#[test]
+	fn should_read_pending_state_from_snapshot() {
+		// given
+		let client = TestBlockChainClient::default();
+		let miner = miner();
+		let transaction = transaction();
+		let sender = transaction.sender();
+
+		// when
+		let res = miner.import_own_transaction(&client, PendingTransaction::new(transaction, None));
+
+		// then
+		assert_eq!(res.unwrap(), TransactionImportResult::Current);
+		assert_eq!(miner.nonce(&client, &sender), Some(U256::from(1)));
+		assert_eq!(miner.pending_state().unwrap().nonce(&sender).unwrap(), U256::from(1));
+
+		// when
+		miner.clear();
+
+		// then
+		assert_eq!(miner.nonce(&client, &sender), Some(U256::zero()));
+		assert!(miner.pending_state().is_none());
+	}
+
 	#[test]
 	fn should_import_external_transaction() {
 		// given
@@ -1396,6 +1762,58 @@ mod tests {
 		assert!(miner.prepare_work_sealing(&client));
 	}
 
+	#[test]
+	fn should_reimport_only_abandoned_transactions_after_reorg() {
+		// given
+		let mut client = TestBlockChainClient::default();
+		client.add_blocks(2, EachBlockWith::Transaction);
+		let (kept, abandoned) = (client.block_hash_delta_minus(2), client.block_hash_delta_minus(1));
+		for hash in &[kept, abandoned] {
+			let tx = client.block(BlockId::Hash(*hash)).unwrap().transactions().pop().unwrap();
+			let sender = SignedTransaction::new(tx).unwrap().sender();
+			client.set_balance(sender, U256::from(10_000_000_000_000_000_000u64));
+			client.set_nonce(sender, U256::zero());
+		}
+		let miner = miner();
+
+		// when
+		miner.chain_new_blocks(&client, &[], &[], &[kept], &[kept, abandoned]);
+
+		// then
+		assert_eq!(miner.reorg_stats(), ReorgStats {
+			reorgs: 1,
+			abandoned_transactions: 1,
+			reimported_transactions: 1,
+		});
+		assert_eq!(miner.pending_transactions().len(), 1);
+	}
+
+	#[test]
+	fn should_accept_cheap_transactions_of_priority_senders() {
+		// given
+		let client = TestBlockChainClient::default();
+		let priority = transaction();
+		let other = transaction();
+		let miner = Miner::new_raw(
+			MinerOptions {
+				priority_senders: vec![priority.sender()].into_iter().collect(),
+				..MinerOptions::default()
+			},
+			GasPricer::new_fixed(0u64.into()),
+			&Spec::new_test(),
+			None, // accounts provider
+		);
+		miner.set_minimal_gas_price(10.into());
+
+		// when
+		let mut res = miner.import_external_transactions(&client, vec![priority.into(), other.into()]);
+
+		// then
+		assert!(res.pop().unwrap().is_err());
+		assert_eq!(res.pop().unwrap().unwrap(), TransactionImportResult::Current);
+		assert_eq!(miner.pending_transactions().len(), 1);
+	}
+
 	#[test]
 	fn should_not_seal_unless_enabled() {
 		let miner = miner();
@@ -42,24 +42,31 @@
 //! ```
 
 mod banning_queue;
+mod block_ordering;
 mod external;
+mod gas_limit;
 mod local_transactions;
 mod miner;
 mod price_info;
 mod service_transaction_checker;
 mod transaction_queue;
+mod uncle_strategy;
 mod work_notify;
 mod stratum;
 
 pub use self::external::{ExternalMiner, ExternalMinerService};
+pub use self::block_ordering::BlockOrdering;
+pub use self::gas_limit::GasLimitTarget;
+pub use self::uncle_strategy::{UncleStrategy, UncleScore};
 
 pub use self::miner::{Miner, MinerOptions, Banning, PendingSet, GasPricer, GasPriceCalibratorOptions, GasLimit};
 pub use self::transaction_queue::{TransactionQueue, TransactionDetailsProvider as TransactionQueueDetailsProvider,
 	PrioritizationStrategy, AccountDetails, TransactionOrigin};
 pub use self::local_transactions::{Status as LocalTransactionStatus};
 pub use client::TransactionImportResult;
-pub use self::work_notify::NotifyWork;
+pub use self::work_notify::{NotifyWork, WorkPackage};
 pub use self::stratum::{Stratum, Error as StratumError, Options as StratumOptions};
+pub use ethcore_stratum::{WorkerInfo as StratumWorkerInfo, VarDiff as StratumVarDiff};
 
 use std::collections::BTreeMap;
 use util::{H256, U256, Address, Bytes};
@@ -110,6 +117,13 @@ pub trait MinerService : Send + Sync {
 	/// Set the upper bound of gas limit we wish to target when sealing a new block.
 	fn set_gas_ceil_target(&self, target: U256);
 
+	/// Get the gas limit the sealing blocks are gradually moved towards, if any.
+	fn gas_limit_target(&self) -> Option<U256>;
+
+	/// Set the gas limit the sealing blocks are gradually moved towards.
+	/// Overrides the floor and ceil targets, `None` restores them.
+	fn set_gas_limit_target(&self, target: Option<U256>);
+
 	/// Get current transactions limit in queue.
 	fn transactions_limit(&self) -> usize;
 
@@ -119,6 +133,9 @@ pub trait MinerService : Send + Sync {
 	/// Set maximum amount of gas allowed for any single transaction to mine.
 	fn set_tx_gas_limit(&self, limit: U256);
 
+	/// Set transactions to be put first in the sealing block (used by `BlockOrdering::Hinted`).
+	fn set_transaction_ordering_hint(&self, hashes: Vec<H256>);
+
 	/// Imports transactions to transaction queue.
 	fn import_external_transactions(&self, chain: &MiningBlockChainClient, transactions: Vec<UnverifiedTransaction>) ->
 		Vec<Result<TransactionImportResult, Error>>;
@@ -147,6 +164,11 @@ pub trait MinerService : Send + Sync {
 	fn map_sealing_work<F, T>(&self, chain: &MiningBlockChainClient, f: F) -> Option<T>
 		where F: FnOnce(&ClosedBlock) -> T, Self: Sized;
 
+	/// Get details of the current sealing work package.
+	fn work_package(&self, chain: &MiningBlockChainClient) -> Option<WorkPackage> where Self: Sized {
+		self.map_sealing_work(chain, |b| WorkPackage::from(b))
+	}
+
 	/// Query pending transactions for hash.
 	fn transaction(&self, best_block: BlockNumber, hash: &H256) -> Option<PendingTransaction>;
 
@@ -166,6 +188,18 @@ pub trait MinerService : Send + Sync {
 	/// Get a list of local transactions with statuses.
 	fn local_transactions(&self) -> BTreeMap<H256, LocalTransactionStatus>;
 
+	/// Get a list of transaction senders currently banned in the queue.
+	fn banned_transaction_senders(&self) -> Vec<Address>;
+
+	/// Get statistics of blocks mined by this node.
+	fn mine_stats(&self) -> MineStats;
+
+	/// Get statistics of transactions re-imported after chain reorganisations.
+	fn reorg_stats(&self) -> ReorgStats;
+
+	/// Get statistics of workers connected to the stratum server.
+	fn stratum_workers(&self) -> Vec<StratumWorkerInfo>;
+
 	/// Get a list of all pending receipts.
 	fn pending_receipts(&self, best_block: BlockNumber) -> BTreeMap<H256, Receipt>;
 
@@ -200,6 +234,28 @@ pub trait MinerService : Send + Sync {
 	fn code(&self, chain: &MiningBlockChainClient, address: &Address) -> Option<Option<Bytes>>;
 }
 
+/// Statistics of blocks mined by this node
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MineStats {
+	/// Number of mined blocks
+	pub blocks: u64,
+	/// Number of uncles included in mined blocks
+	pub uncles_included: u64,
+	/// Number of uncles which could have been included in mined blocks but weren't
+	pub uncles_missed: u64,
+}
+
+/// Statistics of transactions re-imported after chain reorganisations
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ReorgStats {
+	/// Number of reorganisations which retracted at least one block
+	pub reorgs: u64,
+	/// Number of transactions in retracted blocks which are not part of the new canonical chain
+	pub abandoned_transactions: u64,
+	/// Number of abandoned transactions imported back to the queue
+	pub reimported_transactions: u64,
+}
+
 /// Mining status
 #[derive(Debug)]
 pub struct MinerStatus {
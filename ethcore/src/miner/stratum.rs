@@ -18,7 +18,7 @@
 
 use ethcore_stratum::{
 	JobDispatcher, PushWorkHandler,
-	Stratum as StratumService, Error as StratumServiceError,
+	Stratum as StratumService, Error as StratumServiceError, VarDiff,
 };
 
 use std::sync::{Arc, Weak};
@@ -27,10 +27,11 @@ use std::fmt;
 
 use util::{H256, U256, H64, clean_0x};
 use ethereum::ethash::Ethash;
-use ethash::SeedHashCompute;
+use ethash::{SeedHashCompute, EthashManager, quick_get_difficulty};
 use util::Mutex;
 use miner::{self, Miner, MinerService};
-use client::Client;
+use error::Error as MinerError;
+use client::{Client, BlockChainClient};
 use block::IsBlock;
 use std::str::FromStr;
 use rlp::encode;
@@ -46,6 +47,8 @@ pub struct Options {
 	pub port: u16,
 	/// Secret for peers
 	pub secret: Option<H256>,
+	/// Variable share difficulty per worker, if enabled
+	pub vardiff: Option<VarDiff>,
 }
 
 struct SubmitPayload {
@@ -90,6 +93,15 @@ impl SubmitPayload {
 			mix_hash: mix_hash,
 		})
 	}
+
+	/// Difficulty achieved according to the submitted mix hash, which is not verified.
+	fn difficulty(&self) -> U256 {
+		Ethash::boundary_to_difficulty(&H256(quick_get_difficulty(
+			&self.pow_hash.0,
+			self.nonce.low_u64(),
+			&self.mix_hash.0
+		)))
+	}
 }
 
 #[derive(Debug)]
@@ -109,6 +121,7 @@ impl fmt::Display for PayloadError {
 /// Job dispatcher for stratum service
 pub struct StratumJobDispatcher {
 	seed_compute: Mutex<SeedHashCompute>,
+	pow: EthashManager,
 	client: Weak<Client>,
 	miner: Weak<Miner>,
 }
@@ -131,6 +144,42 @@ impl JobDispatcher for StratumJobDispatcher {
 		)
 	}
 
+	fn job_for_difficulty(&self, difficulty: U256) -> Option<String> {
+		self.with_core(|client, miner| miner.map_sealing_work(&*client, |b| {
+				let pow_hash = b.hash();
+				let number = b.block().header().number();
+				// share target can't be harder than the block target or blocks would be missed
+				let difficulty = ::std::cmp::min(difficulty, *b.block().header().difficulty());
+
+				self.payload(pow_hash, difficulty, number)
+			})
+		)
+	}
+
+	fn share_difficulty(&self, payload: Vec<String>) -> Option<U256> {
+		let payload = match SubmitPayload::from_args(payload) {
+			Ok(payload) => payload,
+			Err(_) => return None,
+		};
+
+		// shares are for the current work, or at least from its epoch
+		let number = self.with_core(|client, miner| Some(
+			miner.map_sealing_work(&*client, |b| b.block().header().number())
+				.unwrap_or_else(|| client.chain_info().best_block_number + 1)
+		));
+		let number = match number {
+			Some(number) => number,
+			None => return None,
+		};
+
+		let result = self.pow.compute_light(number, &payload.pow_hash.0, payload.nonce.low_u64());
+		if H256(result.mix_hash) != payload.mix_hash {
+			trace!(target: "stratum", "Share with invalid mix hash: expected={}, got={}", H256(result.mix_hash), payload.mix_hash);
+			return None;
+		}
+		Some(Ethash::boundary_to_difficulty(&H256(result.value)))
+	}
+
 	fn submit(&self, payload: Vec<String>) -> Result<(), StratumServiceError> {
 		let payload = SubmitPayload::from_args(payload).map_err(|e|
 			StratumServiceError::Dispatch(format!("{}", e))
@@ -144,14 +193,25 @@ impl JobDispatcher for StratumJobDispatcher {
 			payload.mix_hash,
 		);
 
-		self.with_core_void(|client, miner| {
-			let seal = vec![encode(&payload.mix_hash).to_vec(), encode(&payload.nonce).to_vec()];
-			if let Err(e) = miner.submit_seal(&*client, payload.pow_hash, seal) {
-				warn!(target: "stratum", "submit_seal error: {:?}", e);
-			};
-		});
+		self.with_core(|client, miner| {
+			// shares of current work below block difficulty are not worth a seal attempt
+			let current = miner.map_sealing_work(&*client, |b| (b.hash(), *b.block().header().difficulty()));
+			if let Some((pow_hash, difficulty)) = current {
+				if pow_hash == payload.pow_hash && payload.difficulty() < difficulty {
+					return Some(Err(StratumServiceError::Dispatch("Share below block difficulty".into())));
+				}
+			}
 
-		Ok(())
+			let seal = vec![encode(&payload.mix_hash).to_vec(), encode(&payload.nonce).to_vec()];
+			Some(match miner.submit_seal(&*client, payload.pow_hash, seal) {
+				Ok(()) => Ok(()),
+				Err(MinerError::PowHashInvalid) => Err(StratumServiceError::StaleShare),
+				Err(e) => {
+					warn!(target: "stratum", "submit_seal error: {:?}", e);
+					Err(StratumServiceError::Dispatch(format!("{:?}", e)))
+				},
+			})
+		}).unwrap_or(Ok(()))
 	}
 }
 
@@ -160,6 +220,7 @@ impl StratumJobDispatcher {
 	fn new(miner: Weak<Miner>, client: Weak<Client>) -> StratumJobDispatcher {
 		StratumJobDispatcher {
 			seed_compute: Mutex::new(SeedHashCompute::new()),
+			pow: EthashManager::new(),
 			client: client,
 			miner: miner,
 		}
@@ -180,10 +241,6 @@ impl StratumJobDispatcher {
 	fn with_core<F, R>(&self, f: F) -> Option<R> where F: Fn(Arc<Client>, Arc<Miner>) -> Option<R> {
 		self.client.upgrade().and_then(|client| self.miner.upgrade().and_then(|miner| (f)(client, miner)))
 	}
-
-	fn with_core_void<F>(&self, f: F) where F: Fn(Arc<Client>, Arc<Miner>) {
-		self.client.upgrade().map(|client| self.miner.upgrade().map(|miner| (f)(client, miner)));
-	}
 }
 
 /// Wrapper for dedicated stratum service
@@ -233,6 +290,7 @@ impl Stratum {
 			&SocketAddr::new(IpAddr::from_str(&options.listen_addr)?, options.port),
 			dispatcher.clone(),
 			options.secret.clone(),
+			options.vardiff.clone(),
 		)?;
 
 		Ok(Stratum {
@@ -244,6 +302,7 @@ impl Stratum {
 	/// Start STRATUM job dispatcher and register it in the miner
 	pub fn register(cfg: &Options, miner: Arc<Miner>, client: Weak<Client>) -> Result<(), Error> {
 		let stratum = miner::Stratum::start(cfg, Arc::downgrade(&miner.clone()), client)?;
+		miner.set_stratum_service(stratum.service.clone());
 		miner.push_notifier(Box::new(stratum) as Box<miner::NotifyWork>);
 		Ok(())
 	}
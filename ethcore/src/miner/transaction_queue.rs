@@ -21,6 +21,10 @@
 //! transaction's nonce and next nonce expected from this sender). If nonces are equal transaction's gas price is used
 //! for comparison (higher gas price = higher priority).
 //!
+//! Transactions of every sender are kept in chains ordered by nonce. Ready transactions are returned by merging
+//! those chains: a priority heap holds the next transaction of each sender, so the queue never has to be re-sorted
+//! and a sender's transactions are always returned in nonce order.
+//!
 //! # Usage Example
 //!
 //! ```rust
@@ -103,10 +107,10 @@
 use std::ops::Deref;
 use std::cmp::Ordering;
 use std::cmp;
-use std::collections::{HashSet, HashMap, BTreeSet, BTreeMap};
+use std::collections::{HashSet, HashMap, BTreeSet, BTreeMap, BinaryHeap};
+use std::collections::hash_map::Keys;
 use linked_hash_map::LinkedHashMap;
 use util::{Address, H256, Uint, U256};
-use util::table::Table;
 use transaction::*;
 use error::{Error, TransactionError};
 use client::TransactionImportResult;
@@ -267,6 +271,26 @@ impl Ord for TransactionOrder {
 	}
 }
 
+/// `TransactionOrder` with reversed ordering, so that `BinaryHeap` pops the highest priority first.
+struct ReadyOrder(TransactionOrder);
+
+impl Eq for ReadyOrder {}
+impl PartialEq for ReadyOrder {
+	fn eq(&self, other: &ReadyOrder) -> bool {
+		self.0 == other.0
+	}
+}
+impl PartialOrd for ReadyOrder {
+	fn partial_cmp(&self, other: &ReadyOrder) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+impl Ord for ReadyOrder {
+	fn cmp(&self, b: &ReadyOrder) -> Ordering {
+		b.0.cmp(&self.0)
+	}
+}
+
 /// Verified transaction
 #[derive(Debug)]
 struct VerifiedTransaction {
@@ -310,17 +334,41 @@ impl VerifiedTransaction {
 #[derive(Debug, Default)]
 struct GasPriceQueue {
 	backing: BTreeMap<U256, HashSet<H256>>,
+	count: usize,
 }
 
 impl GasPriceQueue {
 	/// Insert an item into a BTreeMap/HashSet "multimap".
 	pub fn insert(&mut self, gas_price: U256, hash: H256) -> bool {
-		self.backing.entry(gas_price).or_insert_with(Default::default).insert(hash)
+		let inserted = self.backing.entry(gas_price).or_insert_with(Default::default).insert(hash);
+		if inserted {
+			self.count += 1;
+		}
+		inserted
 	}
 
 	/// Remove an item from a BTreeMap/HashSet "multimap".
 	/// Returns true if the item was removed successfully.
 	pub fn remove(&mut self, gas_price: &U256, hash: &H256) -> bool {
+		let removed = self.remove_internal(gas_price, hash);
+		if removed {
+			self.count -= 1;
+		}
+		removed
+	}
+
+	/// Number of hashes in all gas-price Sets.
+	pub fn count(&self) -> usize {
+		self.count
+	}
+
+	/// Drop all items.
+	pub fn clear(&mut self) {
+		self.backing.clear();
+		self.count = 0;
+	}
+
+	fn remove_internal(&mut self, gas_price: &U256, hash: &H256) -> bool {
 		if let Some(mut hashes) = self.backing.get_mut(gas_price) {
 			let only_one_left = hashes.len() == 1;
 			if !only_one_left {
@@ -348,6 +396,72 @@ impl Deref for GasPriceQueue {
 	}
 }
 
+/// Transactions of each sender, ordered by nonce.
+#[derive(Debug, Default)]
+struct SenderChains {
+	chains: HashMap<Address, BTreeMap<U256, TransactionOrder>>,
+	len: usize,
+}
+
+impl SenderChains {
+	/// Senders with at least one transaction.
+	fn keys(&self) -> Keys<Address, BTreeMap<U256, TransactionOrder>> {
+		self.chains.keys()
+	}
+
+	/// Total number of transactions of all senders.
+	fn len(&self) -> usize {
+		self.len
+	}
+
+	/// Transactions of given sender, ordered by nonce.
+	fn row(&self, sender: &Address) -> Option<&BTreeMap<U256, TransactionOrder>> {
+		self.chains.get(sender)
+	}
+
+	/// Transaction of given sender with given nonce.
+	fn get(&self, sender: &Address, nonce: &U256) -> Option<&TransactionOrder> {
+		self.chains.get(sender).and_then(|chain| chain.get(nonce))
+	}
+
+	/// Transaction of given sender with the lowest nonce.
+	fn head(&self, sender: &Address) -> Option<&TransactionOrder> {
+		self.chains.get(sender).and_then(|chain| chain.values().next())
+	}
+
+	/// Inserts transaction to the chain of its sender, returns transaction it replaces (if any).
+	fn insert(&mut self, sender: Address, nonce: U256, order: TransactionOrder) -> Option<TransactionOrder> {
+		let replaced = self.chains.entry(sender).or_insert_with(BTreeMap::new).insert(nonce, order);
+		if replaced.is_none() {
+			self.len += 1;
+		}
+		replaced
+	}
+
+	/// Removes transaction from the chain of its sender. Empty chains are removed.
+	fn remove(&mut self, sender: &Address, nonce: &U256) -> Option<TransactionOrder> {
+		let (removed, is_empty) = match self.chains.get_mut(sender) {
+			Some(chain) => {
+				let removed = chain.remove(nonce);
+				(removed, chain.is_empty())
+			},
+			None => return None,
+		};
+		if is_empty {
+			self.chains.remove(sender);
+		}
+		if removed.is_some() {
+			self.len -= 1;
+		}
+		removed
+	}
+
+	fn clear(&mut self) {
+		self.chains.clear();
+		self.len = 0;
+	}
+}
+
 /// Holds transactions accessible by (address, nonce) and by priority
 ///
 /// `TransactionSet` keeps number of entries below limit, but it doesn't
@@ -355,13 +469,27 @@ impl Deref for GasPriceQueue {
 /// You have to call `enforce_limit` to remove lowest priority transactions from set.
 struct TransactionSet {
 	by_priority: BTreeSet<TransactionOrder>,
-	by_address: Table<Address, U256, TransactionOrder>,
+	by_address: SenderChains,
 	by_gas_price: GasPriceQueue,
+	/// Lowest nonce transaction of every sender, ordered by priority.
+	heads: BTreeSet<TransactionOrder>,
 	limit: usize,
 	gas_limit: U256,
 }
 
 impl TransactionSet {
+	/// Creates an empty set with given limits.
+	fn new(limit: usize, gas_limit: U256) -> Self {
+		TransactionSet {
+			by_priority: BTreeSet::new(),
+			by_address: SenderChains::default(),
+			by_gas_price: Default::default(),
+			heads: BTreeSet::new(),
+			limit: limit,
+			gas_limit: gas_limit,
+		}
+	}
+
 	/// Inserts `TransactionOrder` to this set. Transaction does not need to be unique -
 	/// the same transaction may be validly inserted twice. Any previous transaction that
 	/// it replaces (i.e. with the same `sender` and `nonce`) should be returned.
@@ -371,6 +499,7 @@ impl TransactionSet {
 		}
 		let order_hash = order.hash.clone();
 		let order_gas_price = order.gas_price.clone();
+		let old_head = self.by_address.head(&sender).cloned();
 		let by_address_replaced = self.by_address.insert(sender, nonce, order);
 		// If transaction was replaced remove it from priority queue
 		if let Some(ref old_order) = by_address_replaced {
@@ -379,11 +508,26 @@ impl TransactionSet {
 				"hash is in `by_address`; all transactions' gas_prices in `by_address` must be in `by_gas_limit`; qed");
 		}
 		self.by_gas_price.insert(order_gas_price, order_hash);
+		self.update_head(&sender, old_head);
 		assert_eq!(self.by_priority.len(), self.by_address.len());
-		assert_eq!(self.by_gas_price.values().map(|v| v.len()).fold(0, |a, b| a + b), self.by_address.len());
+		assert_eq!(self.by_gas_price.count(), self.by_address.len());
 		by_address_replaced
 	}
 
+	/// Replaces previous lowest nonce transaction of the sender in `heads` with the current one.
+	fn update_head(&mut self, sender: &Address, old_head: Option<TransactionOrder>) {
+		let new_head = self.by_address.head(sender).cloned();
+		if old_head == new_head {
+			return;
+		}
+		if let Some(old_head) = old_head {
+			assert!(self.heads.remove(&old_head), "head of every sender is in `heads`; qed");
+		}
+		if let Some(new_head) = new_head {
+			self.heads.insert(new_head);
+		}
+	}
+
 	/// Remove low priority transactions if there is more than specified by given `limit`.
 	///
 	/// It drops transactions from this set but also removes associated `VerifiedTransaction`.
@@ -430,17 +574,19 @@ impl TransactionSet {
 
 	/// Drop transaction from this set (remove from `by_priority` and `by_address`)
 	fn drop(&mut self, sender: &Address, nonce: &U256) -> Option<TransactionOrder> {
+		let old_head = self.by_address.head(sender).cloned();
 		if let Some(tx_order) = self.by_address.remove(sender, nonce) {
 			assert!(self.by_gas_price.remove(&tx_order.gas_price, &tx_order.hash),
 				"hash is in `by_address`; all transactions' gas_prices in `by_address` must be in `by_gas_limit`; qed");
 			assert!(self.by_priority.remove(&tx_order),
 				"hash is in `by_address`; all transactions' gas_prices in `by_address` must be in `by_priority`; qed");
+			self.update_head(sender, old_head);
 			assert_eq!(self.by_priority.len(), self.by_address.len());
-			assert_eq!(self.by_gas_price.values().map(|v| v.len()).fold(0, |a, b| a + b), self.by_address.len());
+			assert_eq!(self.by_gas_price.count(), self.by_address.len());
 			return Some(tx_order);
 		}
 		assert_eq!(self.by_priority.len(), self.by_address.len());
-		assert_eq!(self.by_gas_price.values().map(|v| v.len()).fold(0, |a, b| a + b), self.by_address.len());
+		assert_eq!(self.by_gas_price.count(), self.by_address.len());
 		None
 	}
 
@@ -448,7 +594,8 @@ impl TransactionSet {
 	fn clear(&mut self) {
 		self.by_priority.clear();
 		self.by_address.clear();
-		self.by_gas_price.backing.clear();
+		self.by_gas_price.clear();
+		self.heads.clear();
 	}
 
 	/// Sets new limit for number of transactions in this `TransactionSet`.
@@ -486,6 +633,12 @@ pub struct AccountDetails {
 
 /// Transactions with `gas > (gas_limit + gas_limit * Factor(in percents))` are not imported to the queue.
 const GAS_LIMIT_HYSTERESIS: usize = 200; // (100/GAS_LIMIT_HYSTERESIS) %
+/// Dynamic gas price floor rises when `current` is filled above this percentage of its limit...
+const DYNAMIC_GAS_PRICE_RISE_THRESHOLD: usize = 80;
+/// ...and decays when it is filled below this percentage.
+const DYNAMIC_GAS_PRICE_DECAY_THRESHOLD: usize = 50;
+/// Dynamic gas price floor changes by `1/DYNAMIC_GAS_PRICE_STEP` of its value per update.
+const DYNAMIC_GAS_PRICE_STEP: usize = 8;
 
 /// Describes the strategy used to prioritize transactions in the queue.
 #[cfg_attr(feature="dev", allow(enum_variant_names))]
@@ -530,6 +683,11 @@ pub struct TransactionQueue {
 	strategy: PrioritizationStrategy,
 	/// Gas Price threshold for transactions that can be imported to this queue (defaults to 0)
 	minimal_gas_price: U256,
+	/// Minimal gas price increase (in percents) required to replace a transaction with the same sender and nonce (defaults to 0)
+	replacement_gas_price_bump: usize,
+	/// Gas price floor raised above `minimal_gas_price` when the queue is close to its limit.
+	/// `None` if the floor is not adjusted dynamically (default).
+	dynamic_gas_price: Option<U256>,
 	/// The maximum amount of gas any individual transaction may use.
 	tx_gas_limit: U256,
 	/// Current gas limit (block gas limit * factor). Transactions above the limit will not be accepted (default to !0)
@@ -564,25 +722,14 @@ impl TransactionQueue {
 
 	/// Create new instance of this Queue with specified limits
 	pub fn with_limits(strategy: PrioritizationStrategy, limit: usize, gas_limit: U256, tx_gas_limit: U256) -> Self {
-		let current = TransactionSet {
-			by_priority: BTreeSet::new(),
-			by_address: Table::new(),
-			by_gas_price: Default::default(),
-			limit: limit,
-			gas_limit: gas_limit,
-		};
-
-		let future = TransactionSet {
-			by_priority: BTreeSet::new(),
-			by_address: Table::new(),
-			by_gas_price: Default::default(),
-			limit: limit,
-			gas_limit: gas_limit,
-		};
+		let current = TransactionSet::new(limit, gas_limit);
+		let future = TransactionSet::new(limit, gas_limit);
 
 		TransactionQueue {
 			strategy: strategy,
 			minimal_gas_price: U256::zero(),
+			replacement_gas_price_bump: 0,
+			dynamic_gas_price: None,
 			tx_gas_limit: tx_gas_limit,
 			gas_limit: !U256::zero(),
 			max_time_in_queue: DEFAULT_QUEUING_PERIOD,
@@ -619,6 +766,62 @@ impl TransactionQueue {
 		self.minimal_gas_price = min_gas_price;
 	}
 
+	/// Get the minimal gas price increase (in percents) required to replace a transaction.
+	pub fn replacement_gas_price_bump(&self) -> usize {
+		self.replacement_gas_price_bump
+	}
+
+	/// Sets minimal gas price increase (in percents) a transaction needs
+	/// to replace another transaction with the same sender and nonce.
+	pub fn set_replacement_gas_price_bump(&mut self, bump: usize) {
+		self.replacement_gas_price_bump = bump;
+	}
+
+	/// Enables or disables adjusting the gas price threshold to queue saturation.
+	pub fn set_dynamic_gas_price(&mut self, enabled: bool) {
+		self.dynamic_gas_price = match enabled {
+			true => Some(self.minimal_gas_price),
+			false => None,
+		};
+	}
+
+	/// Get the gas price threshold currently in effect for incoming transactions:
+	/// the minimal gas price or the dynamic floor, whichever is higher.
+	pub fn current_minimal_gas_price(&self) -> U256 {
+		match self.dynamic_gas_price {
+			Some(floor) => cmp::max(floor, self.minimal_gas_price),
+			None => self.minimal_gas_price,
+		}
+	}
+
+	/// Updates the dynamic gas price floor according to current queue saturation.
+	/// The floor rises as the queue approaches its limit and decays back to the minimal gas price
+	/// as the queue drains. Does nothing if dynamic gas price is disabled.
+	pub fn update_dynamic_gas_price(&mut self) {
+		let floor = match self.dynamic_gas_price {
+			Some(_) => self.current_minimal_gas_price(),
+			None => return,
+		};
+		let saturation = self.current.by_priority.len() * 100 / cmp::max(self.current.limit, 1);
+		let step = cmp::max(floor / U256::from(DYNAMIC_GAS_PRICE_STEP), U256::one());
+
+		let new_floor = if saturation >= DYNAMIC_GAS_PRICE_RISE_THRESHOLD {
+			match floor.overflowing_add(step) {
+				(_, true) => !U256::zero(),
+				(val, false) => val,
+			}
+		} else if saturation < DYNAMIC_GAS_PRICE_DECAY_THRESHOLD && floor > self.minimal_gas_price {
+			cmp::max(floor - step, self.minimal_gas_price)
+		} else {
+			floor
+		};
+
+		if new_floor != floor {
+			debug!(target: "txqueue", "Dynamic minimal gas price: {} -> {} (queue filled in {}%)", floor, new_floor, saturation);
+		}
+		self.dynamic_gas_price = Some(new_floor);
+	}
+
 	/// Get one more than the lowest gas price in the queue iff the pool is
 	/// full, otherwise 0.
 	pub fn effective_minimum_gas_price(&self) -> U256 {
@@ -707,7 +910,8 @@ impl TransactionQueue {
 		condition: Option<Condition>,
 		details_provider: &TransactionDetailsProvider,
 	) -> Result<TransactionImportResult, Error> {
-		if origin != TransactionOrigin::Local && tx.gas_price < self.minimal_gas_price {
+		let minimal_gas_price = self.current_minimal_gas_price();
+		if origin != TransactionOrigin::Local && tx.gas_price < minimal_gas_price {
 			// if it is non-service-transaction => drop
 			let is_service_transaction = tx.gas_price.is_zero();
 			if !is_service_transaction {
@@ -715,11 +919,11 @@ impl TransactionQueue {
 					"Dropping transaction below minimal gas price threshold: {:?} (gp: {} < {})",
 					tx.hash(),
 					tx.gas_price,
-					self.minimal_gas_price
+					minimal_gas_price
 				);
 
 				return Err(Error::Transaction(TransactionError::InsufficientGasPrice {
-					minimal: self.minimal_gas_price,
+					minimal: minimal_gas_price,
 					got: tx.gas_price,
 				}));
 			}
@@ -748,7 +952,7 @@ impl TransactionQueue {
 
 			if !is_service_transaction_accepted {
 				return Err(Error::Transaction(TransactionError::InsufficientGasPrice {
-						minimal: self.minimal_gas_price,
+						minimal: minimal_gas_price,
 						got: tx.gas_price,
 					}));
 			}
@@ -1075,23 +1279,23 @@ impl TransactionQueue {
 	fn filter_pending_transaction<F>(&self, best_block: BlockNumber, best_timestamp: u64, mut f: F)
 		where F: FnMut(&VerifiedTransaction) {
 
-		let mut delayed = HashSet::new();
-		for t in self.current.by_priority.iter() {
-			let tx = self.by_hash.get(&t.hash).expect("All transactions in `current` and `future` are always included in `by_hash`");
-			let sender = tx.sender();
-			if delayed.contains(&sender) {
-				continue;
-			}
+		// Merge chains of all senders: the heap holds the next transaction of every sender.
+		let mut ready: BinaryHeap<_> = self.current.heads.iter().cloned().map(ReadyOrder).collect();
+		while let Some(ReadyOrder(order)) = ready.pop() {
+			let tx = self.by_hash.get(&order.hash).expect("All transactions in `current` and `future` are always included in `by_hash`");
 			let delay = match tx.condition {
 				Some(Condition::Number(n)) => n > best_block,
 				Some(Condition::Timestamp(t)) => t > best_timestamp,
 				None => false,
 			};
 			if delay {
-				delayed.insert(sender);
+				// Subsequent transactions of this sender have to wait as well.
 				continue;
 			}
 			f(&tx);
+			if let Some(next) = self.current.by_address.get(&tx.sender(), &(tx.nonce() + U256::one())) {
+				ready.push(ReadyOrder(next.clone()));
+			}
 		}
 	}
 
@@ -1158,29 +1362,18 @@ impl TransactionQueue {
 	/// (because nonce matches).
 	fn move_matching_future_to_current(&mut self, address: Address, mut current_nonce: U256, first_nonce: U256) {
 		let mut update_last_nonce_to = None;
-		{
-			let by_nonce = self.future.by_address.row_mut(&address);
-			if by_nonce.is_none() {
-				return;
+		while let Some(order) = self.future.drop(&address, &current_nonce) {
+			// Put to current
+			let order = order.update_height(current_nonce, first_nonce);
+			if order.origin.is_local() {
+				self.local_transactions.mark_pending(order.hash);
 			}
-			let mut by_nonce = by_nonce.expect("None is tested in early-exit condition above; qed");
-			while let Some(order) = by_nonce.remove(&current_nonce) {
-				// remove also from priority and gas_price
-				self.future.by_priority.remove(&order);
-				self.future.by_gas_price.remove(&order.gas_price, &order.hash);
-				// Put to current
-				let order = order.update_height(current_nonce, first_nonce);
-				if order.origin.is_local() {
-					self.local_transactions.mark_pending(order.hash);
-				}
-				if let Some(old) = self.current.insert(address, current_nonce, order.clone()) {
-					Self::replace_orders(address, current_nonce, old, order, &mut self.current, &mut self.by_hash, &mut self.local_transactions);
-				}
-				update_last_nonce_to = Some(current_nonce);
-				current_nonce = current_nonce + U256::one();
+			if let Some(old) = self.current.insert(address, current_nonce, order.clone()) {
+				Self::replace_orders(address, current_nonce, old, order, &mut self.current, &mut self.by_hash, &mut self.local_transactions);
 			}
+			update_last_nonce_to = Some(current_nonce);
+			current_nonce = current_nonce + U256::one();
 		}
-		self.future.by_address.clear_if_empty(&address);
 		if let Some(x) = update_last_nonce_to {
 			// Update last inserted nonce
 			self.last_nonces.insert(address, x);
@@ -1237,9 +1430,7 @@ impl TransactionQueue {
 		if nonce > next_nonce {
 			// We have a gap - put to future.
 			// Insert transaction (or replace old one with lower gas price)
-			check_too_cheap(
-				Self::replace_transaction(tx, state_nonce, min_gas_price, &mut self.future, &mut self.by_hash, &mut self.local_transactions)
-			)?;
+			Self::replace_transaction(tx, state_nonce, min_gas_price, self.replacement_gas_price_bump, &mut self.future, &mut self.by_hash, &mut self.local_transactions)?;
 			// Enforce limit in Future
 			let removed = self.future.enforce_limit(&mut self.by_hash, &mut self.local_transactions);
 			// Return an error if this transaction was not imported because of limit.
@@ -1255,9 +1446,7 @@ impl TransactionQueue {
 		self.move_matching_future_to_current(address, nonce + U256::one(), state_nonce);
 
 		// Replace transaction if any
-		check_too_cheap(
-			Self::replace_transaction(tx, state_nonce, min_gas_price, &mut self.current, &mut self.by_hash, &mut self.local_transactions)
-		)?;
+		Self::replace_transaction(tx, state_nonce, min_gas_price, self.replacement_gas_price_bump, &mut self.current, &mut self.by_hash, &mut self.local_transactions)?;
 		// Keep track of highest nonce stored in current
 		let new_max = self.last_nonces.get(&address).map_or(nonce, |n| cmp::max(nonce, *n));
 		self.last_nonces.insert(address, new_max);
@@ -1289,19 +1478,21 @@ impl TransactionQueue {
 
 	/// Replaces transaction in given set (could be `future` or `current`).
 	///
-	/// If there is already transaction with same `(sender, nonce)` it will be replaced iff `gas_price` is higher.
+	/// If there is already transaction with same `(sender, nonce)` it will be replaced iff `gas_price`
+	/// is higher by at least `price_bump` percents.
 	/// One of the transactions is dropped from set and also removed from queue entirely (from `by_hash`).
 	///
-	/// Returns `true` if transaction actually got to the queue (`false` if there was already a transaction with higher
-	/// gas_price)
+	/// Returns `TooCheapToReplace` error if there was already a transaction with the same nonce
+	/// and the new one doesn't pay enough to replace it.
 	fn replace_transaction(
 		tx: VerifiedTransaction,
 		base_nonce: U256,
 		min_gas_price: (U256, PrioritizationStrategy),
+		price_bump: usize,
 		set: &mut TransactionSet,
 		by_hash: &mut HashMap<H256, VerifiedTransaction>,
 		local: &mut LocalTransactionsList,
-	) -> bool {
+	) -> Result<(), TransactionError> {
 		let order = TransactionOrder::for_transaction(&tx, base_nonce, min_gas_price.0, min_gas_price.1);
 		let hash = tx.hash();
 		let address = tx.sender();
@@ -1313,10 +1504,25 @@ impl TransactionQueue {
 		trace!(target: "txqueue", "Inserting: {:?}", order);
 
 		if let Some(old) = set.insert(address, nonce, order.clone()) {
-			Self::replace_orders(address, nonce, old, order, set, by_hash, local)
-		} else {
-			true
+			let minimal = bumped_gas_price(old.gas_price, price_bump);
+			if order.gas_price < minimal {
+				trace!(target: "txqueue", "Didn't replace transaction because gas price bump was too low: {:?} ({:?} stays in the queue)", order.hash, old.hash);
+				let old_hash = old.hash;
+				let old_fee = old.gas_price;
+				// Put back old transaction and remove the new one
+				set.insert(address, nonce, old);
+				let tx = by_hash.remove(&hash).expect("The hash has been just inserted and no other line is altering `by_hash`.");
+				if tx.origin.is_local() {
+					local.mark_replaced(tx.transaction, old_fee, old_hash);
+				}
+				return Err(TransactionError::TooCheapToReplace {
+					minimal: minimal,
+					got: order.gas_price,
+				});
+			}
+			Self::replace_orders(address, nonce, old, order, set, by_hash, local);
 		}
+		Ok(())
 	}
 
 	fn replace_orders(
@@ -1355,11 +1561,15 @@ impl TransactionQueue {
 	}
 }
 
-fn check_too_cheap(is_in: bool) -> Result<(), TransactionError> {
-	if is_in {
-		Ok(())
-	} else {
-		Err(TransactionError::TooCheapToReplace)
+/// Returns gas price increased by `bump` percents (saturating).
+fn bumped_gas_price(gas_price: U256, bump: usize) -> U256 {
+	let extra = match gas_price.overflowing_mul(U256::from(bump)) {
+		(_, true) => return !U256::zero(),
+		(val, false) => val / U256::from(100),
+	};
+	match gas_price.overflowing_add(extra) {
+		(_, true) => !U256::zero(),
+		(val, false) => val,
 	}
 }
 
@@ -1379,13 +1589,12 @@ fn check_if_removed(sender: &Address, nonce: &U256, dropped: Option<HashMap<Addr
 #[cfg(test)]
 pub mod test {
 	extern crate rustc_serialize;
-	use util::table::*;
 	use util::*;
 	use ethkey::{Random, Generator};
 	use error::{Error, TransactionError};
 	use super::*;
 	use super::{TransactionSet, TransactionOrder, VerifiedTransaction};
-	use miner::local_transactions::LocalTransactionsList;
+	use miner::local_transactions::{LocalTransactionsList, Status as LocalTransactionStatus};
 	use client::TransactionImportResult;
 	use transaction::{SignedTransaction, Transaction, Action, Condition};
 
@@ -1582,13 +1791,7 @@ pub mod test {
 	fn should_create_transaction_set() {
 		// given
 		let mut local = LocalTransactionsList::default();
-		let mut set = TransactionSet {
-			by_priority: BTreeSet::new(),
-			by_address: Table::new(),
-			by_gas_price: Default::default(),
-			limit: 1,
-			gas_limit: !U256::zero(),
-		};
+		let mut set = TransactionSet::new(1, !U256::zero());
 		let (tx1, tx2) = new_tx_pair_default(1.into(), 0.into());
 		let tx1 = VerifiedTransaction::new(tx1, TransactionOrigin::External, 0, None);
 		let tx2 = VerifiedTransaction::new(tx2, TransactionOrigin::External, 0, None);
@@ -1623,13 +1826,7 @@ pub mod test {
 
 	#[test]
 	fn should_replace_transaction_in_set() {
-		let mut set = TransactionSet {
-			by_priority: BTreeSet::new(),
-			by_address: Table::new(),
-			by_gas_price: Default::default(),
-			limit: 1,
-			gas_limit: !U256::zero(),
-		};
+		let mut set = TransactionSet::new(1, !U256::zero());
 		// Create two transactions with same nonce
 		// (same hash)
 		let (tx1, tx2) = new_tx_pair_default(0.into(), 0.into());
@@ -1672,13 +1869,7 @@ pub mod test {
 
 	#[test]
 	fn should_not_insert_same_transaction_twice_into_set() {
-		let mut set = TransactionSet {
-			by_priority: BTreeSet::new(),
-			by_address: Table::new(),
-			by_gas_price: Default::default(),
-			limit: 2,
-			gas_limit: !U256::zero(),
-		};
+		let mut set = TransactionSet::new(2, !U256::zero());
 		let tx = new_tx_default();
 		let tx1 = VerifiedTransaction::new(tx.clone(), TransactionOrigin::External, 0, None);
 		let order1 = TransactionOrder::for_transaction(&tx1, 0.into(), 1.into(), PrioritizationStrategy::GasPriceOnly);
@@ -1690,13 +1881,7 @@ pub mod test {
 
 	#[test]
 	fn should_give_correct_gas_price_entry_limit() {
-		let mut set = TransactionSet {
-			by_priority: BTreeSet::new(),
-			by_address: Table::new(),
-			by_gas_price: Default::default(),
-			limit: 1,
-			gas_limit: !U256::zero(),
-		};
+		let mut set = TransactionSet::new(1, !U256::zero());
 
 		assert_eq!(set.gas_price_entry_limit(), 0.into());
 		let tx = new_tx_default();
@@ -1919,6 +2104,38 @@ pub mod test {
 		assert_eq!(stats.future, 0);
 	}
 
+	#[test]
+	fn should_adjust_dynamic_gas_price_to_queue_saturation() {
+		// given
+		let mut txq = TransactionQueue::with_limits(PrioritizationStrategy::GasPriceOnly, 2, !U256::zero(), !U256::zero());
+		txq.set_dynamic_gas_price(true);
+		let (tx1, tx2) = new_tx_pair_default(1.into(), 0.into());
+		txq.add(tx1, TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+		txq.add(tx2, TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+		assert_eq!(txq.current_minimal_gas_price(), U256::zero());
+
+		// when
+		txq.update_dynamic_gas_price();
+		txq.update_dynamic_gas_price();
+
+		// then
+		assert_eq!(txq.current_minimal_gas_price(), U256::from(2));
+		let res = txq.add(new_tx_default(), TransactionOrigin::External, 0, None, &default_tx_provider());
+		assert_eq!(unwrap_tx_err(res), TransactionError::InsufficientGasPrice {
+			minimal: U256::from(2),
+			got: U256::from(1),
+		});
+
+		// and when the queue drains
+		txq.clear();
+		txq.update_dynamic_gas_price();
+		txq.update_dynamic_gas_price();
+		txq.update_dynamic_gas_price();
+
+		// then
+		assert_eq!(txq.current_minimal_gas_price(), U256::zero());
+	}
+
 	#[test]
 	fn should_import_transaction_below_min_gas_price_threshold_if_local() {
 		// given
@@ -1954,6 +2171,52 @@ pub mod test {
 		assert_eq!(top.len(), 2);
 	}
 
+	#[test]
+	fn should_return_transactions_from_same_sender_in_nonce_order() {
+		// given
+		let mut txq = TransactionQueue::default();
+		let (tx, tx2) = new_tx_pair_default(1.into(), 0.into());
+
+		// when
+		// transactions from retracted blocks have higher priority than external ones
+		txq.add(tx.clone(), TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+		txq.add(tx2.clone(), TransactionOrigin::RetractedBlock, 0, None, &default_tx_provider()).unwrap();
+
+		// then
+		assert_eq!(txq.top_transactions(), vec![tx, tx2]);
+	}
+
+	#[test]
+	fn should_track_lowest_nonce_transaction_of_each_sender() {
+		// given
+		let mut txq = TransactionQueue::default();
+		let (tx, tx2) = new_tx_pair_default(1.into(), 0.into());
+		let (tx3, _) = new_tx_pair_default(1.into(), 0.into());
+
+		// when
+		txq.add(tx2.clone(), TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+		txq.add(tx.clone(), TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+		txq.add(tx3.clone(), TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+
+		// then
+		let mut heads = txq.current.heads.iter().map(|order| order.hash).collect::<Vec<_>>();
+		heads.sort();
+		let mut expected = vec![tx.hash(), tx3.hash()];
+		expected.sort();
+		assert_eq!(heads, expected);
+
+		// when
+		txq.remove_invalid(&tx.hash(), &|_| default_nonce() + 1.into());
+
+		// then
+		let mut heads = txq.current.heads.iter().map(|order| order.hash).collect::<Vec<_>>();
+		heads.sort();
+		let mut expected = vec![tx2.hash(), tx3.hash()];
+		expected.sort();
+		assert_eq!(heads, expected);
+		assert_eq!(txq.top_transactions().len(), 2);
+	}
+
 	#[test]
 	fn should_prioritize_local_transactions_within_same_nonce_height() {
 		// given
@@ -2477,6 +2740,37 @@ pub mod test {
 		assert_eq!(txq.top_transactions()[0].gas_price, U256::from(200));
 	}
 
+	#[test]
+	fn should_require_gas_price_bump_to_replace_transaction() {
+		// given
+		let mut txq = TransactionQueue::default();
+		txq.set_replacement_gas_price_bump(10);
+		let keypair = Random.generate().unwrap();
+		let tx = new_unsigned_tx(123.into(), default_gas_val(), 100.into()).sign(keypair.secret(), None);
+		let with_gas_price = |gas_price: u64| {
+			let mut tx2 = (**tx).clone();
+			tx2.gas_price = U256::from(gas_price);
+			tx2.sign(keypair.secret(), None)
+		};
+		let tx2 = with_gas_price(109);
+		let tx3 = with_gas_price(110);
+		txq.add(tx.clone(), TransactionOrigin::Local, 0, None, &default_tx_provider()).unwrap();
+
+		// when
+		let res = txq.add(tx2, TransactionOrigin::External, 0, None, &default_tx_provider());
+		txq.add(tx3.clone(), TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+
+		// then
+		assert_eq!(unwrap_tx_err(res), TransactionError::TooCheapToReplace {
+			minimal: U256::from(110),
+			got: U256::from(109),
+		});
+		assert_eq!(txq.status().pending, 1);
+		assert_eq!(txq.top_transactions()[0].hash(), tx3.hash());
+		let local = txq.local_transactions();
+		assert_eq!(local.get(&tx.hash()), Some(&LocalTransactionStatus::Replaced(tx.clone(), U256::from(110), tx3.hash())));
+	}
+
 	#[test]
 	fn should_replace_same_transaction_when_importing_to_futures() {
 		// given
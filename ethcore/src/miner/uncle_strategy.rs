@@ -0,0 +1,156 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Selection of uncles included in the block prepared for sealing.
+
+use header::{Header, Seal};
+use util::{Address, U256};
+
+/// Expected increase of the block orphan probability is one in `ORPHAN_RISK_BYTES` per byte added.
+/// (Roughly 2% per kB, as observed for block propagation on the main network.)
+const ORPHAN_RISK_BYTES: u64 = 50_000;
+
+/// Strategy used to include uncles in the sealing block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UncleStrategy {
+	/// Include as many uncles as the engine allows, oldest first.
+	All,
+	/// Include only uncles which additional reward outweighs the cost of propagating them, best first.
+	Profitable,
+	/// Never include uncles.
+	Disabled,
+}
+
+impl Default for UncleStrategy {
+	fn default() -> Self {
+		UncleStrategy::All
+	}
+}
+
+/// Profitability of including a single uncle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UncleScore {
+	/// Additional reward of the block author.
+	pub reward: U256,
+	/// Expected loss caused by slower propagation of a bigger block.
+	pub cost: U256,
+}
+
+impl UncleScore {
+	/// Scores `uncle` given `rewards` of its inclusion (for the block author and uncle author)
+	/// and `block_value` at risk if the block is orphaned.
+	pub fn new(uncle: &Header, author: &Address, rewards: (U256, U256), block_value: U256) -> Self {
+		let (inclusion_reward, uncle_reward) = rewards;
+		let reward = match uncle.author() == author {
+			true => inclusion_reward + uncle_reward,
+			false => inclusion_reward,
+		};
+		let size = uncle.rlp(Seal::With).len();
+		UncleScore {
+			reward: reward,
+			cost: block_value * U256::from(size) / U256::from(ORPHAN_RISK_BYTES),
+		}
+	}
+
+	/// Whether including the uncle pays off.
+	pub fn is_profitable(&self) -> bool {
+		self.reward > self.cost
+	}
+
+	fn profit(&self) -> U256 {
+		match self.is_profitable() {
+			true => self.reward - self.cost,
+			false => U256::zero(),
+		}
+	}
+}
+
+impl UncleStrategy {
+	/// Selects at most `limit` of `candidates` (oldest first) to include in a block of `author`
+	/// carrying `block_value` of rewards and fees.
+	/// `rewards` returns inclusion rewards of the block author and the uncle author.
+	pub fn select<F>(&self, candidates: Vec<Header>, limit: usize, author: &Address, block_value: U256, rewards: F) -> Vec<Header> where
+		F: Fn(&Header) -> (U256, U256),
+	{
+		match *self {
+			UncleStrategy::All => candidates.into_iter().take(limit).collect(),
+			UncleStrategy::Disabled => Vec::new(),
+			UncleStrategy::Profitable => {
+				let mut scored: Vec<_> = candidates.into_iter()
+					.map(|uncle| {
+						let score = UncleScore::new(&uncle, author, rewards(&uncle), block_value);
+						(score, uncle)
+					})
+					.filter(|&(ref score, ref uncle)| {
+						trace!(target: "miner", "Uncle {} scored {:?}", uncle.hash(), score);
+						score.is_profitable()
+					})
+					.collect();
+				// stable sort keeps older uncles first among equally profitable ones
+				scored.sort_by(|a, b| b.0.profit().cmp(&a.0.profit()));
+				scored.into_iter().take(limit).map(|(_, uncle)| uncle).collect()
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use header::Header;
+	use util::{Address, U256};
+	use super::*;
+
+	fn uncle(number: u64, author: Address, extra_data: usize) -> Header {
+		let mut header = Header::default();
+		header.set_number(number);
+		header.set_author(author);
+		header.set_extra_data(vec![0u8; extra_data]);
+		header
+	}
+
+	fn rewards(_uncle: &Header) -> (U256, U256) {
+		(U256::from(1000), U256::from(7000))
+	}
+
+	#[test]
+	fn should_keep_candidates_order_for_all() {
+		let author = Address::from(1);
+		let candidates = vec![uncle(1, Address::from(2), 0), uncle(2, author, 0), uncle(3, Address::from(3), 0)];
+
+		let selected = UncleStrategy::All.select(candidates.clone(), 2, &author, U256::zero(), rewards);
+
+		assert_eq!(selected, candidates[..2].to_vec());
+		assert!(UncleStrategy::Disabled.select(candidates, 2, &author, U256::zero(), rewards).is_empty());
+	}
+
+	#[test]
+	fn should_prefer_profitable_uncles() {
+		// given
+		let author = Address::from(1);
+		let other = uncle(1, Address::from(2), 0);
+		let own = uncle(2, author, 0);
+		let big = uncle(3, Address::from(3), 1000);
+		let block_value = U256::from(50_000);
+		assert!(UncleScore::new(&other, &author, rewards(&other), block_value).is_profitable());
+		assert!(!UncleScore::new(&big, &author, rewards(&big), block_value).is_profitable());
+
+		// when
+		let selected = UncleStrategy::Profitable.select(vec![other.clone(), big, own.clone()], 2, &author, block_value, rewards);
+
+		// then
+		assert_eq!(selected, vec![own, other]);
+	}
+}
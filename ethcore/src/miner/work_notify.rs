@@ -25,6 +25,7 @@ use ethash::SeedHashCompute;
 use hyper::Url;
 use util::*;
 use ethereum::ethash::Ethash;
+use block::{ClosedBlock, IsBlock};
 
 /// Trait for notifying about new mining work
 pub trait NotifyWork : Send + Sync {
@@ -32,6 +33,30 @@ pub trait NotifyWork : Send + Sync {
 	fn notify(&self, pow_hash: H256, difficulty: U256, number: u64);
 }
 
+/// Details of a sealing work package.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkPackage {
+	/// Hash of the block header without seal.
+	pub pow_hash: H256,
+	/// Block difficulty.
+	pub difficulty: U256,
+	/// Block number.
+	pub number: u64,
+	/// Block timestamp.
+	pub timestamp: u64,
+}
+
+impl<'a> From<&'a ClosedBlock> for WorkPackage {
+	fn from(b: &'a ClosedBlock) -> Self {
+		WorkPackage {
+			pow_hash: b.hash(),
+			difficulty: *b.block().header().difficulty(),
+			number: b.block().header().number(),
+			timestamp: b.block().header().timestamp(),
+		}
+	}
+}
+
 pub struct WorkPoster {
 	urls: Vec<Url>,
 	client: Mutex<Client<PostHandler>>,
@@ -24,7 +24,7 @@ use client::{Client, ClientConfig, ChainNotify};
 use miner::Miner;
 
 use snapshot::ManifestData;
-use snapshot::service::{Service as SnapshotService, ServiceParams as SnapServiceParams};
+use snapshot::service::{Service as SnapshotService, ServiceParams as SnapServiceParams, ChunkPipeline};
 use std::sync::atomic::AtomicBool;
 
 #[cfg(feature="ipc")]
@@ -88,6 +88,9 @@ impl ClientService {
 
 		db_config.compaction = config.db_compaction.compaction_profile(client_path);
 		db_config.wal = config.db_wal;
+		for (col, compression) in &config.db_compression {
+			db_config.set_compression(*col, *compression);
+		}
 
 		let db = Arc::new(Database::open(
 			&db_config,
@@ -96,6 +99,7 @@ impl ClientService {
 
 
 		let pruning = config.pruning;
+		let snapshot_retain = config.snapshot.retain;
 		let client = Client::new(config, &spec, db.clone(), miner, io_service.channel())?;
 
 		let snapshot_params = SnapServiceParams {
@@ -106,6 +110,7 @@ impl ClientService {
 			channel: io_service.channel(),
 			snapshot_root: snapshot_path.into(),
 			db_restore: client.clone(),
+			retain: snapshot_retain,
 		};
 		let snapshot = Arc::new(SnapshotService::new(snapshot_params)?);
 
@@ -113,6 +118,7 @@ impl ClientService {
 		let client_io = Arc::new(ClientIoHandler {
 			client: client.clone(),
 			snapshot: snapshot.clone(),
+			restoration: ChunkPipeline::new(snapshot.clone(), ::num_cpus::get()),
 		});
 		io_service.register_handler(client_io)?;
 
@@ -170,6 +176,7 @@ impl MayPanic for ClientService {
 struct ClientIoHandler {
 	client: Arc<Client>,
 	snapshot: Arc<SnapshotService>,
+	restoration: ChunkPipeline,
 }
 
 const CLIENT_TICK_TIMER: TimerToken = 0;
@@ -206,8 +213,8 @@ impl IoHandler<ClientIoMessage> for ClientIoHandler {
 					warn!("Failed to initialize snapshot restoration: {}", e);
 				}
 			}
-			ClientIoMessage::FeedStateChunk(ref hash, ref chunk) => self.snapshot.feed_state_chunk(*hash, chunk),
-			ClientIoMessage::FeedBlockChunk(ref hash, ref chunk) => self.snapshot.feed_block_chunk(*hash, chunk),
+			ClientIoMessage::FeedStateChunk(ref hash, ref chunk) => self.restoration.feed_state_chunk(*hash, chunk.clone()),
+			ClientIoMessage::FeedBlockChunk(ref hash, ref chunk) => self.restoration.feed_block_chunk(*hash, chunk.clone()),
 			ClientIoMessage::TakeSnapshot(num) => {
 				let client = self.client.clone();
 				let snapshot = self.snapshot.clone();
@@ -55,6 +55,8 @@ pub enum Error {
 	Io(::std::io::Error),
 	/// Snapshot version is not supported.
 	VersionNotSupported(u64),
+	/// Chunk doesn't match its hash.
+	ChunkHashMismatch(H256, H256),
 }
 
 impl fmt::Display for Error {
@@ -75,7 +77,9 @@ impl fmt::Display for Error {
 			Error::Io(ref err) => err.fmt(f),
 			Error::Decoder(ref err) => err.fmt(f),
 			Error::Trie(ref err) => err.fmt(f),
-			Error::VersionNotSupported(ref ver) => write!(f, "Snapshot version {} is not supprted.", ver),
+			Error::VersionNotSupported(ref ver) => write!(f, "Snapshot format version {} is not supported. Supported versions: {:?}",
+				ver, super::supported_versions()),
+			Error::ChunkHashMismatch(ref expected, ref found) => write!(f, "Chunk has wrong hash. Expected {:?}, got {:?}", expected, found),
 		}
 	}
 }
@@ -24,14 +24,97 @@ use std::collections::HashMap;
 use std::io::{self, Read, Seek, SeekFrom, Write};
 use std::fs::{self, File};
 use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
 
+use byteorder::{ByteOrder, BigEndian};
 use util::Bytes;
 use util::hash::H256;
+use util::trie::CursorPosition;
 use rlp::{self, Encodable, RlpStream, UntrustedRlp};
 
-use super::ManifestData;
+use super::{ManifestData, SNAPSHOT_VERSION};
 
-const SNAPSHOT_VERSION: u64 = 2;
+const STATE_CHECKPOINT_FILE: &'static str = "STATE_CHECKPOINT";
+
+const STATE_SEGMENTS_FILE: &'static str = "STATE_SEGMENTS";
+
+/// A run of state chunks ending between two accounts, described so a later snapshot
+/// can reuse the chunks if none of the accounts they cover have changed.
+///
+/// A segment covers the accounts after the end of the one before it, up to and
+/// including its own end.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateSegment {
+	/// Hashes of the segment's chunks, in order.
+	pub chunks: Vec<H256>,
+	/// Hash of the address of the last account covered.
+	pub end: H256,
+	/// Digest of the accounts covered, as stored in the account trie.
+	pub accounts: H256,
+	/// Code included in full.
+	pub provides: Vec<H256>,
+	/// Code referred to by hash only, which is included in full in other segments.
+	pub needs: Vec<H256>,
+}
+
+impl Encodable for StateSegment {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(5);
+		s.append_list(&self.chunks)
+			.append(&self.end)
+			.append(&self.accounts)
+			.append_list(&self.provides)
+			.append_list(&self.needs);
+	}
+}
+
+impl rlp::Decodable for StateSegment {
+	fn decode(rlp: &UntrustedRlp) -> Result<Self, rlp::DecoderError> {
+		Ok(StateSegment {
+			chunks: rlp.list_at(0)?,
+			end: rlp.val_at(1)?,
+			accounts: rlp.val_at(2)?,
+			provides: rlp.list_at(3)?,
+			needs: rlp.list_at(4)?,
+		})
+	}
+}
+
+/// How far the state chunks of a snapshot have been written, so an interrupted
+/// snapshot can continue from there.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateCheckpoint {
+	/// Hashes of the state chunks written, in order.
+	pub hashes: Vec<H256>,
+	/// Position in the account trie after the last account written in full.
+	pub position: CursorPosition,
+	/// Segments made of the chunks written. Empty if they aren't known.
+	pub segments: Vec<StateSegment>,
+}
+
+impl Encodable for StateCheckpoint {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(3);
+		s.append_list(&self.hashes).append(&self.position).append_list(&self.segments);
+	}
+}
+
+impl rlp::Decodable for StateCheckpoint {
+	fn decode(rlp: &UntrustedRlp) -> Result<Self, rlp::DecoderError> {
+		// checkpoints written before segments were recorded have none.
+		let segments = match rlp.item_count()? {
+			2 => Vec::new(),
+			_ => rlp.list_at(2)?,
+		};
+
+		Ok(StateCheckpoint {
+			hashes: rlp.list_at(0)?,
+			position: rlp.val_at(1)?,
+			segments: segments,
+		})
+	}
+}
 
 /// Something which can write snapshots.
 /// Writing the same chunk multiple times will lead to implementation-defined
@@ -43,6 +126,29 @@ pub trait SnapshotWriter {
 	/// Write a compressed block chunk.
 	fn write_block_chunk(&mut self, hash: H256, chunk: &[u8]) -> io::Result<()>;
 
+	/// Write a compressed trace chunk. Writers which don't tell chunk kinds apart
+	/// write it like a block chunk.
+	fn write_trace_chunk(&mut self, hash: H256, chunk: &[u8]) -> io::Result<()> {
+		self.write_block_chunk(hash, chunk)
+	}
+
+	/// Record that the state chunks in `checkpoint` have been written. Writers which
+	/// can't resume an interrupted snapshot ignore this.
+	fn write_state_checkpoint(&mut self, _checkpoint: &StateCheckpoint) -> io::Result<()> {
+		Ok(())
+	}
+
+	/// The last state checkpoint recorded by an interrupted snapshot, if any.
+	fn state_checkpoint(&self) -> Option<StateCheckpoint> {
+		None
+	}
+
+	/// Record the segments made of the snapshot's state chunks, covering all of them.
+	/// Writers which can't serve as the base of a later snapshot ignore this.
+	fn write_state_segments(&mut self, _segments: &[StateSegment]) -> io::Result<()> {
+		Ok(())
+	}
+
 	/// Complete writing. The manifest's chunk lists must be consistent
 	/// with the chunks written.
 	fn finish(self, manifest: ManifestData) -> io::Result<()> where Self: Sized;
@@ -81,6 +187,7 @@ pub struct PackedWriter {
 	file: File,
 	state_hashes: Vec<ChunkInfo>,
 	block_hashes: Vec<ChunkInfo>,
+	trace_hashes: Vec<ChunkInfo>,
 	cur_len: u64,
 }
 
@@ -91,6 +198,7 @@ impl PackedWriter {
 			file: File::create(path)?,
 			state_hashes: Vec::new(),
 			block_hashes: Vec::new(),
+			trace_hashes: Vec::new(),
 			cur_len: 0,
 		})
 	}
@@ -117,10 +225,21 @@ impl SnapshotWriter for PackedWriter {
 		Ok(())
 	}
 
+	fn write_trace_chunk(&mut self, hash: H256, chunk: &[u8]) -> io::Result<()> {
+		self.file.write_all(chunk)?;
+
+		let len = chunk.len() as u64;
+		self.trace_hashes.push(ChunkInfo(hash, len, self.cur_len));
+
+		self.cur_len += len;
+		Ok(())
+	}
+
 	fn finish(mut self, manifest: ManifestData) -> io::Result<()> {
 		// we ignore the hashes fields of the manifest under the assumption that
 		// they are consistent with ours.
-		let mut stream = RlpStream::new_list(6);
+		let with_traces = !self.trace_hashes.is_empty();
+		let mut stream = RlpStream::new_list(if with_traces { 7 } else { 6 });
 		stream
 			.append(&SNAPSHOT_VERSION)
 			.append_list(&self.state_hashes)
@@ -128,6 +247,9 @@ impl SnapshotWriter for PackedWriter {
 			.append(&manifest.state_root)
 			.append(&manifest.block_number)
 			.append(&manifest.block_hash);
+		if with_traces {
+			stream.append_list(&self.trace_hashes);
+		}
 
 		let manifest_rlp = stream.out();
 
@@ -156,38 +278,75 @@ impl SnapshotWriter for PackedWriter {
 /// A "loose" writer writes chunk files into a directory.
 pub struct LooseWriter {
 	dir: PathBuf,
+	// state chunks written since the last state checkpoint.
+	unsynced: Vec<File>,
 }
 
 impl LooseWriter {
 	/// Create a new LooseWriter which will write into the given directory,
 	/// creating it if it doesn't exist.
+	///
+	/// Chunks already in the directory are kept, so a snapshot interrupted
+	/// after a state checkpoint can be continued.
 	pub fn new(path: PathBuf) -> io::Result<Self> {
 		fs::create_dir_all(&path)?;
 
 		Ok(LooseWriter {
 			dir: path,
+			unsynced: Vec::new(),
 		})
 	}
 
 	// writing logic is the same for both kinds of chunks.
-	fn write_chunk(&mut self, hash: H256, chunk: &[u8]) -> io::Result<()> {
+	fn write_chunk(&mut self, hash: H256, chunk: &[u8]) -> io::Result<File> {
 		let mut file_path = self.dir.clone();
 		file_path.push(hash.hex());
 
 		let mut file = File::create(file_path)?;
 		file.write_all(chunk)?;
 
-		Ok(())
+		Ok(file)
 	}
 }
 
 impl SnapshotWriter for LooseWriter {
 	fn write_state_chunk(&mut self, hash: H256, chunk: &[u8]) -> io::Result<()> {
-		self.write_chunk(hash, chunk)
+		let file = self.write_chunk(hash, chunk)?;
+		self.unsynced.push(file);
+		Ok(())
 	}
 
 	fn write_block_chunk(&mut self, hash: H256, chunk: &[u8]) -> io::Result<()> {
-		self.write_chunk(hash, chunk)
+		self.write_chunk(hash, chunk).map(|_| ())
+	}
+
+	fn write_state_checkpoint(&mut self, checkpoint: &StateCheckpoint) -> io::Result<()> {
+		// chunks must be on disk before the checkpoint naming them.
+		for file in self.unsynced.drain(..) {
+			file.sync_all()?;
+		}
+
+		// replace the old checkpoint atomically.
+		let temp_path = self.dir.join(format!("{}.tmp", STATE_CHECKPOINT_FILE));
+		{
+			let mut file = File::create(&temp_path)?;
+			file.write_all(&rlp::encode(checkpoint))?;
+			file.sync_all()?;
+		}
+		fs::rename(temp_path, self.dir.join(STATE_CHECKPOINT_FILE))
+	}
+
+	fn write_state_segments(&mut self, segments: &[StateSegment]) -> io::Result<()> {
+		let mut file = File::create(self.dir.join(STATE_SEGMENTS_FILE))?;
+		file.write_all(&rlp::encode_list(segments))
+	}
+
+	fn state_checkpoint(&self) -> Option<StateCheckpoint> {
+		let mut bytes = Vec::new();
+		match File::open(self.dir.join(STATE_CHECKPOINT_FILE)).and_then(|mut file| file.read_to_end(&mut bytes)) {
+			Ok(_) => UntrustedRlp::new(&bytes).as_val().ok(),
+			Err(_) => None,
+		}
 	}
 
 	fn finish(self, manifest: ManifestData) -> io::Result<()> {
@@ -198,10 +357,88 @@ impl SnapshotWriter for LooseWriter {
 		let mut file = File::create(path)?;
 		file.write_all(&rlp[..])?;
 
+		// the snapshot is complete and won't be resumed.
+		if let Err(e) = fs::remove_file(self.dir.join(STATE_CHECKPOINT_FILE)) {
+			if e.kind() != io::ErrorKind::NotFound {
+				return Err(e);
+			}
+		}
+
 		Ok(())
 	}
 }
 
+/// Writer which passes chunks on to another writer no faster than a given number of
+/// bytes per second, so taking a snapshot leaves disk bandwidth for everything else.
+pub struct RateLimitedWriter<W> {
+	inner: W,
+	rate: Option<u64>,
+	// when the chunks written so far are due at the limited rate.
+	due: Instant,
+}
+
+impl<W: SnapshotWriter> RateLimitedWriter<W> {
+	/// Wrap `inner`, limiting it to `rate` bytes per second. `None` doesn't limit it.
+	pub fn new(inner: W, rate: Option<usize>) -> Self {
+		RateLimitedWriter {
+			inner: inner,
+			rate: rate.map(|rate| rate as u64),
+			due: Instant::now(),
+		}
+	}
+
+	// wait until the previous chunks are due, then account for a chunk of `len` bytes.
+	fn throttle(&mut self, len: usize) {
+		let rate = match self.rate {
+			Some(rate) if rate > 0 => rate,
+			_ => return,
+		};
+
+		let now = Instant::now();
+		if self.due > now {
+			thread::sleep(self.due - now);
+		} else {
+			self.due = now;
+		}
+
+		let nanos = len as u64 * 1_000_000_000 / rate;
+		self.due += Duration::new(nanos / 1_000_000_000, (nanos % 1_000_000_000) as u32);
+	}
+}
+
+impl<W: SnapshotWriter> SnapshotWriter for RateLimitedWriter<W> {
+	fn write_state_chunk(&mut self, hash: H256, chunk: &[u8]) -> io::Result<()> {
+		self.throttle(chunk.len());
+		self.inner.write_state_chunk(hash, chunk)
+	}
+
+	fn write_block_chunk(&mut self, hash: H256, chunk: &[u8]) -> io::Result<()> {
+		self.throttle(chunk.len());
+		self.inner.write_block_chunk(hash, chunk)
+	}
+
+	fn write_trace_chunk(&mut self, hash: H256, chunk: &[u8]) -> io::Result<()> {
+		self.throttle(chunk.len());
+		self.inner.write_trace_chunk(hash, chunk)
+	}
+
+	fn write_state_checkpoint(&mut self, checkpoint: &StateCheckpoint) -> io::Result<()> {
+		self.inner.write_state_checkpoint(checkpoint)
+	}
+
+	fn state_checkpoint(&self) -> Option<StateCheckpoint> {
+		self.inner.state_checkpoint()
+	}
+
+	fn write_state_segments(&mut self, segments: &[StateSegment]) -> io::Result<()> {
+		self.inner.write_state_segments(segments)
+	}
+
+	fn finish(self, manifest: ManifestData) -> io::Result<()> {
+		self.inner.finish(manifest)
+	}
+}
+
 /// Something which can read compressed snapshots.
 pub trait SnapshotReader {
 	/// Get the manifest data for this snapshot.
@@ -210,6 +447,11 @@ pub trait SnapshotReader {
 	/// Get raw chunk data by hash. implementation defined behavior
 	/// if a chunk not in the manifest is requested.
 	fn chunk(&self, hash: H256) -> io::Result<Bytes>;
+
+	/// Get the segments made of the state chunks, if they were recorded.
+	fn state_segments(&self) -> Option<Vec<StateSegment>> {
+		None
+	}
 }
 
 /// Packed snapshot reader.
@@ -264,12 +506,14 @@ impl PackedReader {
 			(1, rlp.val_at(0)?)
 		};
 
-		if version > SNAPSHOT_VERSION {
-			return Err(::snapshot::error::Error::VersionNotSupported(version));
-		}
+		::snapshot::chunk_decoders(version)?;
 
 		let state: Vec<ChunkInfo> = rlp.list_at(0 + start)?;
 		let blocks: Vec<ChunkInfo> = rlp.list_at(1 + start)?;
+		let traces: Vec<ChunkInfo> = match rlp.item_count()? > 5 + start {
+			true => rlp.list_at(5 + start)?,
+			false => Vec::new(),
+		};
 
 		let manifest = ManifestData {
 			version: version,
@@ -278,12 +522,14 @@ impl PackedReader {
 			state_root: rlp.val_at(2 + start)?,
 			block_number: rlp.val_at(3 + start)?,
 			block_hash: rlp.val_at(4 + start)?,
+			trace_hashes: traces.iter().map(|c| c.0).collect(),
 		};
 
+		// trace chunks are looked up with the block chunks.
 		Ok(Some(PackedReader {
 			file: file,
 			state_hashes: state.into_iter().map(|c| (c.0, (c.1, c.2))).collect(),
-			block_hashes: blocks.into_iter().map(|c| (c.0, (c.1, c.2))).collect(),
+			block_hashes: blocks.into_iter().chain(traces).map(|c| (c.0, (c.1, c.2))).collect(),
 			manifest: manifest
 		}))
 	}
@@ -352,6 +598,93 @@ impl SnapshotReader for LooseReader {
 
 		Ok(buf)
 	}
+
+	fn state_segments(&self) -> Option<Vec<StateSegment>> {
+		let mut bytes = Vec::new();
+		match File::open(self.dir.join(STATE_SEGMENTS_FILE)).and_then(|mut file| file.read_to_end(&mut bytes)) {
+			Ok(_) => UntrustedRlp::new(&bytes).as_list().ok(),
+			Err(_) => None,
+		}
+	}
+}
+
+/// Writes state chunks one after another into a single stream, each
+/// preceded by its length as a big-endian 32-bit integer.
+///
+/// Used for state dumps, which carry no block chunks and no manifest.
+pub struct StateStreamWriter<W: Write> {
+	out: W,
+}
+
+impl<W: Write> StateStreamWriter<W> {
+	/// Create a new stream writer wrapping the given output.
+	pub fn new(out: W) -> Self {
+		StateStreamWriter {
+			out: out,
+		}
+	}
+
+	/// Write a single length-prefixed record.
+	pub fn write_record(&mut self, data: &[u8]) -> io::Result<()> {
+		let len = data.len();
+		if len > u32::max_value() as usize {
+			return Err(io::Error::new(io::ErrorKind::InvalidInput, "record too large for state stream"));
+		}
+
+		let mut len_bytes = [0u8; 4];
+		BigEndian::write_u32(&mut len_bytes, len as u32);
+
+		self.out.write_all(&len_bytes)?;
+		self.out.write_all(data)
+	}
+
+	/// Write the empty terminating record and flush the output.
+	pub fn close(mut self) -> io::Result<()> {
+		self.write_record(&[])?;
+		self.out.flush()
+	}
+}
+
+impl<W: Write> SnapshotWriter for StateStreamWriter<W> {
+	fn write_state_chunk(&mut self, _hash: H256, chunk: &[u8]) -> io::Result<()> {
+		self.write_record(chunk)
+	}
+
+	fn write_block_chunk(&mut self, _hash: H256, _chunk: &[u8]) -> io::Result<()> {
+		Err(io::Error::new(io::ErrorKind::Other, "state streams cannot contain block chunks"))
+	}
+
+	fn finish(self, _manifest: ManifestData) -> io::Result<()> {
+		self.close()
+	}
+}
+
+/// Reads records written by a `StateStreamWriter`.
+pub struct StateStreamReader<R: Read> {
+	input: R,
+}
+
+impl<R: Read> StateStreamReader<R> {
+	/// Create a new stream reader wrapping the given input.
+	pub fn new(input: R) -> Self {
+		StateStreamReader {
+			input: input,
+		}
+	}
+
+	/// Read the next record. Yields `None` once the terminating record is reached.
+	pub fn next_record(&mut self) -> io::Result<Option<Bytes>> {
+		let mut len_bytes = [0u8; 4];
+		self.input.read_exact(&mut len_bytes)?;
+
+		let len = BigEndian::read_u32(&len_bytes) as usize;
+		if len == 0 { return Ok(None) }
+
+		let mut buf = vec![0; len];
+		self.input.read_exact(&mut buf)?;
+
+		Ok(Some(buf))
+	}
 }
 
 #[cfg(test)]
@@ -361,9 +694,11 @@ mod tests {
 
 	use snapshot::ManifestData;
 	use super::{SnapshotWriter, SnapshotReader, PackedWriter, PackedReader, LooseWriter, LooseReader, SNAPSHOT_VERSION};
+	use super::{StateStreamWriter, StateStreamReader, RateLimitedWriter};
 
 	const STATE_CHUNKS: &'static [&'static [u8]] = &[b"dog", b"cat", b"hello world", b"hi", b"notarealchunk"];
 	const BLOCK_CHUNKS: &'static [&'static [u8]] = &[b"hello!", b"goodbye!", b"abcdefg", b"hijklmnop", b"qrstuvwxy", b"and", b"z"];
+	const TRACE_CHUNKS: &'static [&'static [u8]] = &[b"traces", b"more traces"];
 
 	#[test]
 	fn packed_write_and_read() {
@@ -385,6 +720,13 @@ mod tests {
 			writer.write_block_chunk(chunk.sha3(), chunk).unwrap();
 		}
 
+		let mut trace_hashes = Vec::new();
+		for chunk in TRACE_CHUNKS {
+			let hash = chunk.sha3();
+			trace_hashes.push(hash.clone());
+			writer.write_trace_chunk(hash, chunk).unwrap();
+		}
+
 		let manifest = ManifestData {
 			version: SNAPSHOT_VERSION,
 			state_hashes: state_hashes,
@@ -392,6 +734,7 @@ mod tests {
 			state_root: b"notarealroot".sha3(),
 			block_number: 12345678987654321,
 			block_hash: b"notarealblock".sha3(),
+			trace_hashes: trace_hashes,
 		};
 
 		writer.finish(manifest.clone()).unwrap();
@@ -399,7 +742,7 @@ mod tests {
 		let reader = PackedReader::new(path.as_path()).unwrap().unwrap();
 		assert_eq!(reader.manifest(), &manifest);
 
-		for hash in manifest.state_hashes.iter().chain(&manifest.block_hashes) {
+		for hash in manifest.state_hashes.iter().chain(&manifest.block_hashes).chain(&manifest.trace_hashes) {
 			reader.chunk(hash.clone()).unwrap();
 		}
 	}
@@ -431,6 +774,7 @@ mod tests {
 			state_root: b"notarealroot".sha3(),
 			block_number: 12345678987654321,
 			block_hash: b"notarealblock".sha3(),
+			trace_hashes: Vec::new(),
 		};
 
 		writer.finish(manifest.clone()).unwrap();
@@ -442,4 +786,39 @@ mod tests {
 			reader.chunk(hash.clone()).unwrap();
 		}
 	}
+
+	#[test]
+	fn state_stream() {
+		let mut buf = Vec::new();
+		{
+			let mut writer = StateStreamWriter::new(&mut buf);
+			for chunk in STATE_CHUNKS {
+				writer.write_state_chunk(chunk.sha3(), chunk).unwrap();
+			}
+			assert!(writer.write_block_chunk(BLOCK_CHUNKS[0].sha3(), BLOCK_CHUNKS[0]).is_err());
+			writer.close().unwrap();
+		}
+
+		let mut reader = StateStreamReader::new(&buf[..]);
+		for chunk in STATE_CHUNKS {
+			assert_eq!(&reader.next_record().unwrap().unwrap()[..], *chunk);
+		}
+		assert!(reader.next_record().unwrap().is_none());
+	}
+
+	#[test]
+	fn rate_limited_write() {
+		use std::time::{Duration, Instant};
+
+		let path = RandomTempPath::new();
+		let mut writer = RateLimitedWriter::new(LooseWriter::new(path.as_path().into()).unwrap(), Some(1000));
+
+		// each chunk of 100 bytes takes a tenth of a second at 1000 bytes per second.
+		let start = Instant::now();
+		for i in 0..3u8 {
+			let chunk = [i; 100];
+			writer.write_state_chunk((&chunk[..]).sha3(), &chunk).unwrap();
+		}
+		assert!(start.elapsed() >= Duration::from_millis(200));
+	}
 }
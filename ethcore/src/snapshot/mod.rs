@@ -20,28 +20,31 @@
 //! https://github.com/ethcore/parity/wiki/%22PV64%22-Snapshot-Format
 
 use std::collections::{HashMap, HashSet, VecDeque};
-use std::sync::Arc;
+use std::io::{Read, Write};
+use std::sync::{mpsc, Arc};
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
 use account_db::{AccountDB, AccountDBMut};
 use blockchain::{BlockChain, BlockProvider};
 use engines::Engine;
+use db::{Readable, Writable};
 use header::Header;
 use ids::BlockId;
+use trace::FlatBlockTraces;
 use views::BlockView;
 
 use util::{Bytes, Hashable, HashDB, DBValue, snappy, U256, Uint};
 use util::Mutex;
 use util::hash::{H256};
 use util::journaldb::{self, Algorithm, JournalDB};
-use util::kvdb::Database;
-use util::trie::{TrieDB, TrieDBMut, Trie, TrieMut};
+use util::kvdb::KeyValueDB;
+use util::trie::{TrieDB, TrieDBMut, TrieMut, TrieCursor, CursorPosition};
 use util::sha3::SHA3_NULL_RLP;
 use rlp::{RlpStream, UntrustedRlp};
 use bloom_journal::Bloom;
 
 use self::block::AbridgedBlock;
-use self::io::SnapshotWriter;
+use self::io::{SnapshotReader, SnapshotWriter, RateLimitedWriter, StateCheckpoint, StateSegment, StateStreamReader, StateStreamWriter};
 
 use super::state_db::StateDB;
 use super::state::Account as StateAccount;
@@ -89,6 +92,43 @@ const MAX_STORAGE_ENTRIES_PER_ACCOUNT_RECORD: usize = 80_000;
 // How many blocks to include in a snapshot, starting from the head of the chain.
 const SNAPSHOT_BLOCKS: u64 = 30000;
 
+// Version of the state stream format written by `dump_state`.
+const STATE_STREAM_VERSION: u64 = 1;
+
+/// Version of the snapshot format written by this node.
+pub const SNAPSHOT_VERSION: u64 = 2;
+
+/// Decoders for the chunks of a single snapshot format version.
+///
+/// Chunks carry no header of their own: the version in the manifest determines how all
+/// of a snapshot's chunks are decoded. A new format adds an entry to the registry rather
+/// than changing the decoders of existing versions, so older snapshots stay restorable.
+pub struct ChunkDecoders {
+	/// The format version, as recorded in manifests.
+	pub version: u64,
+	/// Feed a decompressed state chunk to a state rebuilder.
+	pub state: fn(&mut StateRebuilder, &[u8], &AtomicBool) -> Result<(), ::error::Error>,
+	/// Feed a decompressed block chunk to a block rebuilder, returning the number of blocks fed.
+	pub blocks: fn(&mut BlockRebuilder, &[u8], &Engine, &AtomicBool) -> Result<u64, ::error::Error>,
+}
+
+// versions 1 and 2 differ only in the manifest, which gained an explicit version.
+static CHUNK_DECODERS: &'static [ChunkDecoders] = &[
+	ChunkDecoders { version: 1, state: StateRebuilder::feed, blocks: BlockRebuilder::feed },
+	ChunkDecoders { version: 2, state: StateRebuilder::feed, blocks: BlockRebuilder::feed },
+];
+
+/// Get the chunk decoders for the given snapshot format version.
+pub fn chunk_decoders(version: u64) -> Result<&'static ChunkDecoders, Error> {
+	CHUNK_DECODERS.iter().find(|decoders| decoders.version == version)
+		.ok_or(Error::VersionNotSupported(version))
+}
+
+/// The snapshot format versions which can be restored.
+pub fn supported_versions() -> Vec<u64> {
+	CHUNK_DECODERS.iter().map(|decoders| decoders.version).collect()
+}
+
 /// A progress indicator for snapshots.
 #[derive(Debug, Default)]
 pub struct Progress {
@@ -123,12 +163,46 @@ impl Progress {
 	pub fn done(&self) -> bool  { self.done.load(Ordering::Acquire) }
 
 }
+/// Options for taking snapshots.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotConfiguration {
+	/// Number of threads compressing state chunks.
+	pub threads: usize,
+	/// Maximum rate chunks are written at, in bytes per second. `None` for no limit.
+	pub max_write_rate: Option<usize>,
+	/// Whether to reuse the state chunks of the previous snapshot which are unchanged.
+	pub incremental: bool,
+	/// Number of periodic snapshots to keep on disk, including the latest.
+	pub retain: usize,
+	/// Number of recent blocks to include the traces of, when tracing is enabled.
+	pub trace_blocks: u64,
+}
+
+impl Default for SnapshotConfiguration {
+	fn default() -> Self {
+		SnapshotConfiguration {
+			threads: 1,
+			max_write_rate: None,
+			incremental: false,
+			retain: 1,
+			trace_blocks: 0,
+		}
+	}
+}
+
 /// Take a snapshot using the given blockchain, starting block hash, and database, writing into the given writer.
+///
+/// If the configuration allows incremental snapshots, unchanged state chunks are taken from
+/// the `base` snapshot, if given. The traces of recent blocks are included if the configuration
+/// asks for them and a database holding them is given as `traces`.
 pub fn take_snapshot<W: SnapshotWriter + Send>(
 	chain: &BlockChain,
 	block_at: H256,
 	state_db: &HashDB,
 	writer: W,
+	base: Option<&SnapshotReader>,
+	traces: Option<&KeyValueDB>,
+	config: &SnapshotConfiguration,
 	p: &Progress
 ) -> Result<(), Error> {
 	let start_header = chain.block_header(&block_at)
@@ -138,25 +212,48 @@ pub fn take_snapshot<W: SnapshotWriter + Send>(
 
 	info!("Taking snapshot starting at block {}", number);
 
-	let writer = Mutex::new(writer);
+	// continue the state chunks of an interrupted snapshot of the same state.
+	let resume = writer.state_checkpoint().and_then(|checkpoint| match &checkpoint.position.root == state_root {
+		true => Some(checkpoint),
+		false => None,
+	});
+	if let Some(ref checkpoint) = resume {
+		info!("Resuming snapshot after {} state chunks", checkpoint.hashes.len());
+	}
+
+	let base = match config.incremental {
+		true => base,
+		false => None,
+	};
+	if let Some(base) = base {
+		info!("Reusing unchanged state chunks of the snapshot at #{}", base.manifest().block_number);
+	}
+
+	let writer = Mutex::new(RateLimitedWriter::new(writer, config.max_write_rate));
 	let (state_hashes, block_hashes) = scope(|scope| {
 		let block_guard = scope.spawn(|| chunk_blocks(chain, block_at, &writer, p));
-		let state_res = chunk_state(state_db, state_root, &writer, p);
+		let state_res = chunk_state_from(state_db, state_root, resume, base, config.threads, &writer, p);
 
 		state_res.and_then(|state_hashes| {
 			block_guard.join().map(|block_hashes| (state_hashes, block_hashes))
 		})
 	})?;
 
-	info!("produced {} state chunks and {} block chunks.", state_hashes.len(), block_hashes.len());
+	let trace_hashes = match traces {
+		Some(traces) if config.trace_blocks > 0 => chunk_traces(chain, block_at, traces, config.trace_blocks, &writer)?,
+		_ => Vec::new(),
+	};
+
+	info!("produced {} state chunks, {} block chunks and {} trace chunks.", state_hashes.len(), block_hashes.len(), trace_hashes.len());
 
 	let manifest_data = ManifestData {
-		version: 2,
+		version: SNAPSHOT_VERSION,
 		state_hashes: state_hashes,
 		block_hashes: block_hashes,
 		state_root: *state_root,
 		block_number: number,
 		block_hash: block_at,
+		trace_hashes: trace_hashes,
 	};
 
 	writer.into_inner().finish(manifest_data)?;
@@ -290,14 +387,130 @@ pub fn chunk_blocks<'a>(chain: &'a BlockChain, start_hash: H256, writer: &Mutex<
 	Ok(chunker.hashes)
 }
 
+/// Create and write out chunks holding the traces of the `blocks` most recent blocks up to
+/// the starting block, returning their hashes. Blocks with no traces recorded are skipped.
+///
+/// Each chunk is a list of `[block_hash, traces]` pairs.
+pub fn chunk_traces<'a>(chain: &BlockChain, start_hash: H256, db: &KeyValueDB, blocks: u64, writer: &Mutex<SnapshotWriter + 'a>) -> Result<Vec<H256>, Error> {
+	fn write_chunk<'b>(pairs: &mut Vec<Bytes>, snappy_buffer: &mut Vec<u8>, writer: &Mutex<SnapshotWriter + 'b>) -> Result<H256, Error> {
+		let mut stream = RlpStream::new_list(pairs.len());
+		for pair in pairs.drain(..) {
+			stream.append_raw(&pair, 1);
+		}
+		let raw_data = stream.out();
+
+		let size = snappy::compress_into(&raw_data, snappy_buffer);
+		let compressed = &snappy_buffer[..size];
+		let hash = compressed.sha3();
+
+		writer.lock().write_trace_chunk(hash, compressed)?;
+		trace!(target: "snapshot", "wrote trace chunk. hash: {}, size: {}, uncompressed size: {}", hash.hex(), size, raw_data.len());
+
+		Ok(hash)
+	}
+
+	let mut hashes = Vec::new();
+	let mut pairs: Vec<Bytes> = Vec::new();
+	let mut loaded_size = 0;
+	let mut snappy_buffer = vec![0; snappy::max_compressed_len(PREFERRED_CHUNK_SIZE)];
+
+	let genesis_hash = chain.genesis_hash();
+	let mut current_hash = start_hash;
+	for _ in 0..blocks {
+		if current_hash == genesis_hash { break }
+
+		if let Some(traces) = db.read::<FlatBlockTraces, _>(::db::COL_TRACE, &current_hash) {
+			let mut pair = RlpStream::new_list(2);
+			pair.append(&current_hash).append(&traces);
+			let pair = pair.out();
+
+			// cut off the chunk if too large.
+			if loaded_size + pair.len() > PREFERRED_CHUNK_SIZE && !pairs.is_empty() {
+				hashes.push(write_chunk(&mut pairs, &mut snappy_buffer, writer)?);
+				loaded_size = 0;
+			}
+
+			loaded_size += pair.len();
+			pairs.push(pair);
+		}
+
+		current_hash = match chain.block_details(&current_hash) {
+			Some(details) => details.parent,
+			None => return Err(Error::BlockNotFound(current_hash)),
+		};
+	}
+
+	if !pairs.is_empty() {
+		hashes.push(write_chunk(&mut pairs, &mut snappy_buffer, writer)?);
+	}
+
+	Ok(hashes)
+}
+
+/// Restore the traces in a decompressed trace chunk, writing them into the given database.
+pub fn restore_traces(db: &KeyValueDB, chunk: &[u8]) -> Result<(), ::error::Error> {
+	let rlp = UntrustedRlp::new(chunk);
+	let mut batch = db.transaction();
+
+	for pair in rlp.iter() {
+		let hash: H256 = pair.val_at(0)?;
+		let traces: FlatBlockTraces = pair.val_at(1)?;
+		batch.write(::db::COL_TRACE, &hash, &traces);
+	}
+
+	db.write_buffered(batch);
+	Ok(())
+}
+
 /// State trie chunker.
+///
+/// Chunks are compressed on worker threads and written out on the walking thread, in
+/// the order they were made.
 struct StateChunker<'a> {
 	hashes: Vec<H256>,
 	rlps: Vec<Bytes>,
 	cur_size: usize,
-	snappy_buffer: Vec<u8>,
 	writer: &'a Mutex<SnapshotWriter + 'a>,
 	progress: &'a Progress,
+	// position after the last account pushed in full.
+	completed: CursorPosition,
+	// uncompressed chunks for the workers, by index.
+	jobs: mpsc::SyncSender<(usize, Bytes)>,
+	// compressed chunks and their hashes from the workers, by index.
+	compressed: mpsc::Receiver<(usize, H256, Bytes)>,
+	// number of entries and end of segment for each chunk not yet written.
+	pending: VecDeque<(usize, Option<SegmentEnd>)>,
+	// compressed chunks waiting for earlier ones.
+	ready: HashMap<usize, (H256, Bytes)>,
+	submitted: usize,
+	max_pending: usize,
+	// accounts pushed in full since the last segment ended.
+	segment: SegmentAccounts,
+	// chunks written since the last segment ended.
+	segment_chunks: Vec<H256>,
+	// segments written, unless continuing a snapshot which didn't record them.
+	segments: Option<Vec<StateSegment>>,
+	// base segment whose accounts are being skipped.
+	reusing: Option<usize>,
+}
+
+// accounts in the segment being built.
+#[derive(Default)]
+struct SegmentAccounts {
+	count: usize,
+	end: H256,
+	accounts: H256,
+	provides: HashSet<H256>,
+	needs: HashSet<H256>,
+}
+
+// the end of a segment, recorded once the chunk ending it is written.
+struct SegmentEnd {
+	position: CursorPosition,
+	end: H256,
+	accounts: H256,
+	provides: Vec<H256>,
+	needs: Vec<H256>,
 }
 
 impl<'a> StateChunker<'a> {
@@ -313,7 +526,12 @@ impl<'a> StateChunker<'a> {
 		};
 
 		if force_chunk || self.cur_size + pair.len() >= PREFERRED_CHUNK_SIZE {
-			self.write_chunk()?;
+			// only whole accounts have been pushed unless this continues an account.
+			let end = match force_chunk {
+				true => None,
+				false => self.end_segment(),
+			};
+			self.write_chunk(end)?;
 		}
 
 		self.cur_size += pair.len();
@@ -322,72 +540,401 @@ impl<'a> StateChunker<'a> {
 		Ok(())
 	}
 
-	// Write out the buffer to disk, pushing the created chunk's hash to
-	// the list.
-	fn write_chunk(&mut self) -> Result<(), Error> {
+	// Note an account pushed in full, and whether its code was included in full.
+	fn note_account(&mut self, account_hash: H256, thin_rlp: &[u8], code_hash: H256, code_included: bool) {
+		let segment = &mut self.segment;
+		segment.count += 1;
+		segment.end = account_hash;
+		segment.accounts = account_digest(&segment.accounts, &account_hash, thin_rlp);
+
+		if code_included {
+			segment.provides.insert(code_hash);
+		} else if code_hash != ::util::SHA3_EMPTY {
+			segment.needs.insert(code_hash);
+		}
+	}
+
+	// End the segment with the accounts pushed in full since the last one, if any.
+	fn end_segment(&mut self) -> Option<SegmentEnd> {
+		if self.segment.count == 0 { return None }
+
+		let segment = ::std::mem::replace(&mut self.segment, SegmentAccounts::default());
+		let needs = segment.needs.difference(&segment.provides).cloned().collect();
+
+		Some(SegmentEnd {
+			position: self.completed.clone(),
+			end: segment.end,
+			accounts: segment.accounts,
+			provides: segment.provides.into_iter().collect(),
+			needs: needs,
+		})
+	}
+
+	// Write out what's left in the buffer, ending the segment.
+	fn flush(&mut self) -> Result<(), Error> {
+		if self.cur_size == 0 { return Ok(()) }
+
+		let end = self.end_segment();
+		self.write_chunk(end)
+	}
+
+	// Write out the chunks of a segment of the base snapshot in place of its accounts,
+	// which have all been skipped.
+	fn reuse(&mut self, segment: &StateSegment, base: &SnapshotReader) -> Result<(), Error> {
+		for (i, hash) in segment.chunks.iter().enumerate() {
+			let chunk = base.chunk(*hash)?;
+			if &chunk.sha3() != hash {
+				return Err(::std::io::Error::new(::std::io::ErrorKind::InvalidData, "base snapshot chunk doesn't match its hash").into());
+			}
+
+			let end = match i + 1 == segment.chunks.len() {
+				true => Some(SegmentEnd {
+					position: self.completed.clone(),
+					end: segment.end,
+					accounts: segment.accounts,
+					provides: segment.provides.clone(),
+					needs: segment.needs.clone(),
+				}),
+				false => None,
+			};
+
+			self.ready.insert(self.submitted, (*hash, chunk));
+			self.submitted += 1;
+			self.pending.push_back((0, end));
+		}
+
+		trace!(target: "snapshot", "reused {} state chunks up to account {}", segment.chunks.len(), segment.end);
+
+		let max_pending = self.max_pending;
+		self.write_compressed(max_pending)
+	}
+
+	// Hand the buffer to the workers to be compressed, recording the end of the
+	// segment once it's written.
+	fn write_chunk(&mut self, end: Option<SegmentEnd>) -> Result<(), Error> {
 		let num_entries = self.rlps.len();
 		let mut stream = RlpStream::new_list(num_entries);
 		for rlp in self.rlps.drain(..) {
 			stream.append_raw(&rlp, 1);
 		}
+		self.cur_size = 0;
 
-		let raw_data = stream.out();
+		self.jobs.send((self.submitted, stream.out())).map_err(|_| workers_failed())?;
+		self.submitted += 1;
+		self.pending.push_back((num_entries, end));
 
-		let compressed_size = snappy::compress_into(&raw_data, &mut self.snappy_buffer);
-		let compressed = &self.snappy_buffer[..compressed_size];
-		let hash = compressed.sha3();
+		let max_pending = self.max_pending;
+		self.write_compressed(max_pending)
+	}
+
+	// Write out compressed chunks in order, waiting for the workers while more than
+	// `max_pending` chunks are left with them.
+	fn write_compressed(&mut self, max_pending: usize) -> Result<(), Error> {
+		loop {
+			let next = self.submitted - self.pending.len();
+			if let Some((hash, chunk)) = self.ready.remove(&next) {
+				let (num_entries, end) = self.pending.pop_front().expect("every submitted chunk is pending until written; qed");
+				self.write_out(hash, &chunk, num_entries, end)?;
+				continue;
+			}
+
+			let (index, hash, chunk) = match self.pending.len() > max_pending {
+				true => self.compressed.recv().map_err(|_| workers_failed())?,
+				false => match self.compressed.try_recv() {
+					Ok(compressed) => compressed,
+					Err(mpsc::TryRecvError::Empty) => return Ok(()),
+					Err(mpsc::TryRecvError::Disconnected) => return Err(workers_failed()),
+				},
+			};
+			self.ready.insert(index, (hash, chunk));
+		}
+	}
 
-		self.writer.lock().write_state_chunk(hash, compressed)?;
-		trace!(target: "snapshot", "wrote state chunk. size: {}, uncompressed size: {}", compressed_size, raw_data.len());
+	fn write_out(&mut self, hash: H256, chunk: &[u8], num_entries: usize, end: Option<SegmentEnd>) -> Result<(), Error> {
+		self.writer.lock().write_state_chunk(hash, chunk)?;
+		trace!(target: "snapshot", "wrote state chunk. size: {}, entries: {}", chunk.len(), num_entries);
 
 		self.progress.accounts.fetch_add(num_entries, Ordering::SeqCst);
-		self.progress.size.fetch_add(compressed_size, Ordering::SeqCst);
+		self.progress.size.fetch_add(chunk.len(), Ordering::SeqCst);
 
 		self.hashes.push(hash);
-		self.cur_size = 0;
+		self.segment_chunks.push(hash);
+
+		if let Some(end) = end {
+			let chunks = ::std::mem::replace(&mut self.segment_chunks, Vec::new());
+			if let Some(ref mut segments) = self.segments {
+				segments.push(StateSegment {
+					chunks: chunks,
+					end: end.end,
+					accounts: end.accounts,
+					provides: end.provides,
+					needs: end.needs,
+				});
+			}
+
+			self.writer.lock().write_state_checkpoint(&StateCheckpoint {
+				hashes: self.hashes.clone(),
+				position: end.position,
+				segments: self.segments.clone().unwrap_or_else(Vec::new),
+			})?;
+		}
 
 		Ok(())
 	}
 }
 
+// compress and hash state chunks until there are no more.
+fn compress_state_chunks(jobs: &Mutex<mpsc::Receiver<(usize, Bytes)>>, compressed: mpsc::Sender<(usize, H256, Bytes)>) {
+	let mut snappy_buffer = vec![0; snappy::max_compressed_len(PREFERRED_CHUNK_SIZE)];
+	loop {
+		let (index, raw_data) = match jobs.lock().recv() {
+			Ok(job) => job,
+			Err(_) => return,
+		};
+
+		let compressed_size = snappy::compress_into(&raw_data, &mut snappy_buffer);
+		let chunk = snappy_buffer[..compressed_size].to_vec();
+		if compressed.send((index, chunk.sha3(), chunk)).is_err() {
+			return;
+		}
+	}
+}
+
+fn workers_failed() -> Error {
+	Error::Io(::std::io::Error::new(::std::io::ErrorKind::Other, "state chunk compression failed"))
+}
+
+// fold an account, as stored in the account trie, into the digest of a segment.
+fn account_digest(digest: &H256, account_hash: &H256, thin_rlp: &[u8]) -> H256 {
+	let mut buf = Vec::with_capacity(64 + thin_rlp.len());
+	buf.extend_from_slice(&**digest);
+	buf.extend_from_slice(&**account_hash);
+	buf.extend_from_slice(thin_rlp);
+	buf.sha3()
+}
+
+/// Segments of a previous snapshot whose chunks may be reused by a new one.
+struct SegmentBase<'a> {
+	reader: &'a SnapshotReader,
+	segments: Vec<StateSegment>,
+	reusable: Vec<bool>,
+	// first segment which may cover the accounts still to come.
+	next: usize,
+}
+
+impl<'a> SegmentBase<'a> {
+	// Find which segments of `reader` cover accounts unchanged in the given account trie.
+	fn new(reader: &'a SnapshotReader, account_trie: &TrieDB) -> Result<Option<Self>, Error> {
+		let segments = match reader.state_segments() {
+			Some(segments) => segments,
+			None => return Ok(None),
+		};
+
+		// digest the accounts each segment covers now. this only reads the account trie.
+		let mut digests = vec![H256::zero(); segments.len()];
+		let mut current = 0;
+		for item in TrieCursor::new(account_trie)? {
+			let (account_key, account_data) = item?;
+			let account_hash = H256::from_slice(&account_key);
+
+			while current < segments.len() && account_hash > segments[current].end {
+				current += 1;
+			}
+			if current == segments.len() { break }
+
+			digests[current] = account_digest(&digests[current], &account_hash, &account_data);
+		}
+
+		let reusable = segments.iter().zip(&digests).map(|(segment, digest)| {
+			!segment.chunks.is_empty() && &segment.accounts == digest
+		}).collect();
+
+		let mut base = SegmentBase {
+			reader: reader,
+			segments: segments,
+			reusable: reusable,
+			next: 0,
+		};
+
+		// reused segments may only need code included in full by other reused ones.
+		loop {
+			let provided = base.provided_code();
+
+			let mut changed = false;
+			for (segment, reusable) in base.segments.iter().zip(base.reusable.iter_mut()) {
+				if *reusable && !segment.needs.iter().all(|code| provided.contains(code)) {
+					*reusable = false;
+					changed = true;
+				}
+			}
+
+			if !changed { break }
+		}
+
+		trace!(target: "snapshot", "{} of {} base segments unchanged", base.reusable.iter().filter(|r| **r).count(), base.segments.len());
+
+		Ok(Some(base))
+	}
+
+	// Code included in full by the reused segments.
+	fn provided_code(&self) -> HashSet<H256> {
+		self.segments.iter().zip(&self.reusable)
+			.filter(|&(_, reusable)| *reusable)
+			.flat_map(|(segment, _)| segment.provides.iter().cloned())
+			.collect()
+	}
+
+	// The reused segment covering the given account, if any. Accounts must be given in order.
+	fn reused_segment(&mut self, account_hash: &H256) -> Option<usize> {
+		while self.next < self.segments.len() && *account_hash > self.segments[self.next].end {
+			self.next += 1;
+		}
+
+		match self.next < self.segments.len() && self.reusable[self.next] {
+			true => Some(self.next),
+			false => None,
+		}
+	}
+}
+
 /// Walk the given state database starting from the given root,
 /// creating chunks and writing them out.
 ///
 /// Returns a list of hashes of chunks created, or any error it may
 /// have encountered.
 pub fn chunk_state<'a>(db: &HashDB, root: &H256, writer: &Mutex<SnapshotWriter + 'a>, progress: &'a Progress) -> Result<Vec<H256>, Error> {
+	chunk_state_from(db, root, None, None, 1, writer, progress)
+}
+
+/// Like `chunk_state`, but continuing after the state chunks listed in `checkpoint`, if given,
+/// and compressing chunks on `threads` threads.
+///
+/// State checkpoints are passed to the writer whenever a chunk ends between two accounts.
+/// Code already written before the checkpoint is repeated in full the first time it's met
+/// again.
+///
+/// If a `base` snapshot which recorded its state segments is given, the chunks of every
+/// segment covering only unchanged accounts are copied from it rather than made again.
+/// The segments of the new chunks are passed to the writer once all are written.
+pub fn chunk_state_from<'a>(
+	db: &HashDB,
+	root: &H256,
+	checkpoint: Option<StateCheckpoint>,
+	base: Option<&SnapshotReader>,
+	threads: usize,
+	writer: &Mutex<SnapshotWriter + 'a>,
+	progress: &'a Progress
+) -> Result<Vec<H256>, Error> {
 	let account_trie = TrieDB::new(db, &root)?;
 
-	let mut chunker = StateChunker {
-		hashes: Vec::new(),
-		rlps: Vec::new(),
-		cur_size: 0,
-		snappy_buffer: vec![0; snappy::max_compressed_len(PREFERRED_CHUNK_SIZE)],
-		writer: writer,
-		progress: progress,
+	let mut base = match base {
+		Some(reader) => SegmentBase::new(reader, &account_trie)?,
+		None => None,
+	};
+
+	let (hashes, segments, mut cursor) = match checkpoint {
+		Some(checkpoint) => {
+			// the segments are only of use if they cover every chunk.
+			let covered = checkpoint.segments.iter().map(|segment| segment.chunks.len()).sum::<usize>();
+			let segments = match covered == checkpoint.hashes.len() {
+				true => Some(checkpoint.segments),
+				false => None,
+			};
+			(checkpoint.hashes, segments, TrieCursor::resume(&account_trie, checkpoint.position)?)
+		}
+		None => (Vec::new(), Some(Vec::new()), TrieCursor::new(&account_trie)?),
 	};
 
-	let mut used_code = HashSet::new();
+	let threads = ::std::cmp::max(threads, 1);
+	let (jobs, job_queue) = mpsc::sync_channel(threads);
+	let job_queue = Mutex::new(job_queue);
+	let (compressed_tx, compressed) = mpsc::channel();
+
+	scope(|scope| -> Result<Vec<H256>, Error> {
+		for _ in 0..threads {
+			let job_queue = &job_queue;
+			let compressed_tx = compressed_tx.clone();
+			scope.spawn(move || compress_state_chunks(job_queue, compressed_tx));
+		}
+		drop(compressed_tx);
+
+		// the workers stop once the chunker, holding the other end of their queue, is dropped.
+		let mut chunker = StateChunker {
+			hashes: hashes,
+			rlps: Vec::new(),
+			cur_size: 0,
+			writer: writer,
+			progress: progress,
+			completed: cursor.position().clone(),
+			jobs: jobs,
+			compressed: compressed,
+			pending: VecDeque::new(),
+			ready: HashMap::new(),
+			submitted: 0,
+			max_pending: threads * 2,
+			segment: SegmentAccounts::default(),
+			segment_chunks: Vec::new(),
+			segments: segments,
+			reusing: None,
+		};
+
+		// code in reused chunks needn't be repeated.
+		let mut used_code = base.as_ref().map_or_else(HashSet::new, |base| base.provided_code());
+
+		// account_key here is the address' hash.
+		while let Some(item) = cursor.next() {
+			let (account_key, account_data) = item?;
+			let account_key_hash = H256::from_slice(&account_key);
+
+			if let Some(ref mut base) = base {
+				let reused = base.reused_segment(&account_key_hash);
+				if reused != chunker.reusing {
+					if let Some(index) = chunker.reusing.take() {
+						chunker.reuse(&base.segments[index], base.reader)?;
+					}
+
+					// the chunks before a reused segment end with the segment before it.
+					if reused.is_some() {
+						chunker.flush()?;
+						chunker.reusing = reused;
+					}
+				}
+
+				if reused.is_some() {
+					progress.accounts.fetch_add(1, Ordering::SeqCst);
+					chunker.completed = cursor.position().clone();
+					continue;
+				}
+			}
+
+			let account: BasicAccount = ::rlp::decode(&*account_data);
+			let account_db = AccountDB::from_hash(db, account_key_hash);
 
-	// account_key here is the address' hash.
-	for item in account_trie.iter()? {
-		let (account_key, account_data) = item?;
-		let account = ::rlp::decode(&*account_data);
-		let account_key_hash = H256::from_slice(&account_key);
+			let code_known = used_code.contains(&account.code_hash);
+			let fat_rlps = account::to_fat_rlps(&account, &account_db, &mut used_code, MAX_STORAGE_ENTRIES_PER_ACCOUNT_RECORD)?;
+			for (i, fat_rlp) in fat_rlps.into_iter().enumerate() {
+				chunker.push(account_key.clone(), fat_rlp, i > 0)?;
+			}
 
-		let account_db = AccountDB::from_hash(db, account_key_hash);
+			let code_included = !code_known && used_code.contains(&account.code_hash);
+			chunker.note_account(account_key_hash, &account_data, account.code_hash, code_included);
+			chunker.completed = cursor.position().clone();
+		}
 
-		let fat_rlps = account::to_fat_rlps(&account, &account_db, &mut used_code, MAX_STORAGE_ENTRIES_PER_ACCOUNT_RECORD)?;
-		for (i, fat_rlp) in fat_rlps.into_iter().enumerate() {
-			chunker.push(account_key.clone(), fat_rlp, i > 0)?;
+		if let Some(index) = chunker.reusing.take() {
+			let base = base.as_ref().expect("only base segments are reused; qed");
+			chunker.reuse(&base.segments[index], base.reader)?;
 		}
-	}
 
-	if chunker.cur_size != 0 {
-		chunker.write_chunk()?;
-	}
+		chunker.flush()?;
+		chunker.write_compressed(0)?;
 
-	Ok(chunker.hashes)
+		if let Some(ref segments) = chunker.segments {
+			writer.lock().write_state_segments(segments)?;
+		}
+
+		Ok(chunker.hashes)
+	})
 }
 
 /// Used to rebuild the state trie piece by piece.
@@ -398,11 +945,12 @@ pub struct StateRebuilder {
 	missing_code: HashMap<H256, Vec<H256>>, // maps code hashes to lists of accounts missing that code.
 	bloom: Bloom,
 	known_storage_roots: HashMap<H256, H256>, // maps account hashes to last known storage root. Only filled for last account per chunk.
+	changes: Bytes, // what feeding the last chunk changed in memory.
 }
 
 impl StateRebuilder {
 	/// Create a new state rebuilder to write into the given backing DB.
-	pub fn new(db: Arc<Database>, pruning: Algorithm) -> Self {
+	pub fn new(db: Arc<KeyValueDB>, pruning: Algorithm) -> Self {
 		StateRebuilder {
 			db: journaldb::new(db.clone(), pruning, ::db::COL_STATE),
 			state_root: SHA3_NULL_RLP,
@@ -410,6 +958,7 @@ impl StateRebuilder {
 			missing_code: HashMap::new(),
 			bloom: StateDB::load_bloom(&*db),
 			known_storage_roots: HashMap::new(),
+			changes: Vec::new(),
 		}
 	}
 
@@ -431,15 +980,15 @@ impl StateRebuilder {
 			flag
 		)?;
 
-		for (addr_hash, code_hash) in status.missing_code {
+		for &(addr_hash, code_hash) in &status.missing_code {
 			self.missing_code.entry(code_hash).or_insert_with(Vec::new).push(addr_hash);
 		}
 
 		// patch up all missing code. must be done after collecting all new missing code entries.
-		for (code_hash, code, first_with) in status.new_code {
+		for &(code_hash, ref code, first_with) in &status.new_code {
 			for addr_hash in self.missing_code.remove(&code_hash).unwrap_or_else(Vec::new) {
 				let mut db = AccountDBMut::from_hash(self.db.as_hashdb_mut(), addr_hash);
-				db.emplace(code_hash, DBValue::from_slice(&code));
+				db.emplace(code_hash, DBValue::from_slice(code));
 			}
 
 			self.known_code.insert(code_hash, first_with);
@@ -471,6 +1020,60 @@ impl StateRebuilder {
 		self.db.inject(&mut batch)?;
 		backing.write_buffered(batch);
 		trace!(target: "snapshot", "current state root: {:?}", self.state_root);
+
+		self.changes = {
+			let mut stream = RlpStream::new_list(4);
+			stream.append(&self.state_root);
+			stream.begin_list(status.missing_code.len());
+			for &(ref addr_hash, ref code_hash) in &status.missing_code {
+				stream.begin_list(2).append(addr_hash).append(code_hash);
+			}
+			stream.begin_list(status.new_code.len());
+			for &(ref code_hash, _, ref first_with) in &status.new_code {
+				stream.begin_list(2).append(code_hash).append(first_with);
+			}
+			stream.begin_list(status.storage_roots.len());
+			for &(ref hash, ref storage_root) in &status.storage_roots {
+				stream.begin_list(2).append(hash).append(storage_root);
+			}
+			stream.out()
+		};
+
+		Ok(())
+	}
+
+	/// What feeding the last chunk changed in the rebuilder's memory, as RLP.
+	///
+	/// Everything else it changed is in the database, so a rebuilder over the same database
+	/// can be brought to where this one is by replaying the changes of every chunk fed, in order.
+	pub fn changes(&self) -> &[u8] {
+		&self.changes
+	}
+
+	/// Replay the changes made by feeding a chunk, as given by `changes`.
+	pub fn replay(&mut self, changes: &[u8]) -> Result<(), ::error::Error> {
+		let rlp = UntrustedRlp::new(changes);
+		self.state_root = rlp.val_at(0)?;
+
+		let missing_code = rlp.at(1)?;
+		for item in missing_code.iter() {
+			let code_hash: H256 = item.val_at(1)?;
+			self.missing_code.entry(code_hash).or_insert_with(Vec::new).push(item.val_at(0)?);
+		}
+
+		let new_code = rlp.at(2)?;
+		for item in new_code.iter() {
+			let code_hash: H256 = item.val_at(0)?;
+			self.missing_code.remove(&code_hash);
+			self.known_code.insert(code_hash, item.val_at(1)?);
+		}
+
+		let storage_roots = rlp.at(3)?;
+		for item in storage_roots.iter() {
+			self.known_storage_roots.insert(item.val_at(0)?, item.val_at(1)?);
+		}
+
+		self.changes = changes.to_vec();
 		Ok(())
 	}
 
@@ -478,8 +1081,7 @@ impl StateRebuilder {
 	/// journal entry.
 	/// Once all chunks have been fed, there should be nothing missing.
 	pub fn finalize(mut self, era: u64, id: H256) -> Result<(), ::error::Error> {
-		let missing = self.missing_code.keys().cloned().collect::<Vec<_>>();
-		if !missing.is_empty() { return Err(Error::MissingCode(missing).into()) }
+		self.check_missing()?;
 
 		let mut batch = self.db.backing().transaction();
 		self.db.journal_under(&mut batch, era, &id)?;
@@ -488,15 +1090,80 @@ impl StateRebuilder {
 		Ok(())
 	}
 
+	/// Check that no account is still missing its code.
+	pub fn check_missing(&self) -> Result<(), Error> {
+		let missing = self.missing_code.keys().cloned().collect::<Vec<_>>();
+		match missing.is_empty() {
+			true => Ok(()),
+			false => Err(Error::MissingCode(missing)),
+		}
+	}
+
 	/// Get the state root of the rebuilder.
 	pub fn state_root(&self) -> H256 { self.state_root }
 }
 
+/// Write the state with the given root to `out` as a stream of length-prefixed records:
+/// an RLP header of `[version, state_root]`, followed by snappy-compressed state chunks
+/// in the snapshot format and an empty terminating record.
+pub fn dump_state<W: Write>(db: &HashDB, root: &H256, out: W) -> Result<(), Error> {
+	let mut header = RlpStream::new_list(2);
+	header.append(&STATE_STREAM_VERSION).append(root);
+
+	let mut writer = StateStreamWriter::new(out);
+	writer.write_record(&header.out())?;
+
+	let writer = Mutex::new(writer);
+	let hashes = chunk_state(db, root, &writer, &Progress::default())?;
+	trace!(target: "snapshot", "dumped state {} in {} chunks", root, hashes.len());
+
+	writer.into_inner().close()?;
+	Ok(())
+}
+
+/// Restore state from a stream produced by `dump_state` into the state column of the
+/// given database. Returns the root of the restored state, which is checked against
+/// the one recorded in the stream.
+///
+/// Nodes are inserted alongside any existing state, so this may be used to import into
+/// a live database.
+pub fn restore_state<R: Read>(input: R, db: Arc<KeyValueDB>, pruning: Algorithm) -> Result<H256, ::error::Error> {
+	let mut reader = StateStreamReader::new(input);
+
+	let header = reader.next_record()?
+		.ok_or_else(|| ::std::io::Error::new(::std::io::ErrorKind::UnexpectedEof, "missing state stream header"))?;
+	let header = UntrustedRlp::new(&header);
+	let version: u64 = header.val_at(0)?;
+	if version != STATE_STREAM_VERSION {
+		return Err(Error::VersionNotSupported(version).into());
+	}
+	let expected_root: H256 = header.val_at(1)?;
+
+	let flag = AtomicBool::new(true);
+	let mut rebuilder = StateRebuilder::new(db, pruning);
+	let mut buffer = Vec::new();
+
+	while let Some(chunk) = reader.next_record()? {
+		let len = snappy::decompress_into(&chunk, &mut buffer)?;
+		rebuilder.feed(&buffer[..len], &flag)?;
+	}
+
+	rebuilder.check_missing()?;
+
+	let root = rebuilder.state_root();
+	if root != expected_root {
+		return Err(Error::WrongStateRoot(expected_root, root).into());
+	}
+
+	Ok(root)
+}
+
 #[derive(Default)]
 struct RebuiltStatus {
 	// new code that's become available. (code_hash, code, addr_hash)
 	new_code: Vec<(H256, Bytes, H256)>,
 	missing_code: Vec<(H256, H256)>, // accounts that are missing code.
+	storage_roots: Vec<(H256, H256)>, // storage roots noted for the first and last accounts.
 }
 
 // rebuild a set of accounts and their storage.
@@ -555,10 +1222,14 @@ fn rebuild_accounts(
 		*out = (hash, thin_rlp);
 	}
 	if let Some(&(ref hash, ref rlp)) = out_chunk.iter().last() {
-		known_storage_roots.insert(*hash, ::rlp::decode::<BasicAccount>(rlp).storage_root);
+		let storage_root = ::rlp::decode::<BasicAccount>(rlp).storage_root;
+		known_storage_roots.insert(*hash, storage_root);
+		status.storage_roots.push((*hash, storage_root));
 	}
 	if let Some(&(ref hash, ref rlp)) = out_chunk.iter().next() {
-		known_storage_roots.insert(*hash, ::rlp::decode::<BasicAccount>(rlp).storage_root);
+		let storage_root = ::rlp::decode::<BasicAccount>(rlp).storage_root;
+		known_storage_roots.insert(*hash, storage_root);
+		status.storage_roots.push((*hash, storage_root));
 	}
 	Ok(status)
 }
@@ -591,18 +1262,19 @@ pub fn verify_old_block(rng: &mut OsRng, header: &Header, engine: &Engine, chain
 /// After all chunks have been submitted, we "glue" the chunks together.
 pub struct BlockRebuilder {
 	chain: BlockChain,
-	db: Arc<Database>,
+	db: Arc<KeyValueDB>,
 	rng: OsRng,
 	disconnected: Vec<(u64, H256)>,
 	best_number: u64,
 	best_hash: H256,
 	best_root: H256,
 	fed_blocks: u64,
+	changes: Bytes, // what feeding the last chunk changed in memory.
 }
 
 impl BlockRebuilder {
 	/// Create a new BlockRebuilder.
-	pub fn new(chain: BlockChain, db: Arc<Database>, manifest: &ManifestData) -> Result<Self, ::error::Error> {
+	pub fn new(chain: BlockChain, db: Arc<KeyValueDB>, manifest: &ManifestData) -> Result<Self, ::error::Error> {
 		Ok(BlockRebuilder {
 			chain: chain,
 			db: db,
@@ -612,6 +1284,7 @@ impl BlockRebuilder {
 			best_hash: manifest.block_hash,
 			best_root: manifest.state_root,
 			fed_blocks: 0,
+			changes: Vec::new(),
 		})
 	}
 
@@ -632,6 +1305,8 @@ impl BlockRebuilder {
 			return Err(Error::TooManyBlocks(SNAPSHOT_BLOCKS, self.fed_blocks).into())
 		}
 
+		let disconnected = self.disconnected.len();
+
 		// todo: assert here that these values are consistent with chunks being in order.
 		let mut cur_number = rlp.val_at::<u64>(0)? + 1;
 		let mut parent_hash = rlp.val_at::<H256>(1)?;
@@ -690,9 +1365,41 @@ impl BlockRebuilder {
 
 		self.fed_blocks += num_blocks;
 
+		self.changes = {
+			let mut stream = RlpStream::new_list(2);
+			stream.append(&num_blocks);
+			stream.begin_list(self.disconnected.len() - disconnected);
+			for &(ref number, ref hash) in &self.disconnected[disconnected..] {
+				stream.begin_list(2).append(number).append(hash);
+			}
+			stream.out()
+		};
+
 		Ok(num_blocks)
 	}
 
+	/// What feeding the last chunk changed in the rebuilder's memory, as RLP.
+	///
+	/// Everything else it changed is in the database, so a rebuilder over the same database
+	/// can be brought to where this one is by replaying the changes of every chunk fed.
+	pub fn changes(&self) -> &[u8] {
+		&self.changes
+	}
+
+	/// Replay the changes made by feeding a chunk, as given by `changes`.
+	pub fn replay(&mut self, changes: &[u8]) -> Result<(), ::error::Error> {
+		let rlp = UntrustedRlp::new(changes);
+		self.fed_blocks += rlp.val_at::<u64>(0)?;
+
+		let disconnected = rlp.at(1)?;
+		for item in disconnected.iter() {
+			self.disconnected.push((item.val_at(0)?, item.val_at(1)?));
+		}
+
+		self.changes = changes.to_vec();
+		Ok(())
+	}
+
 	/// Glue together any disconnected chunks and check that the chain is complete.
 	pub fn finalize(self, canonical: HashMap<u64, H256>) -> Result<(), Error> {
 		let mut batch = self.db.transaction();
@@ -17,13 +17,14 @@
 //! Snapshot network service implementation.
 
 use std::collections::{HashMap, HashSet};
-use std::io::ErrorKind;
-use std::fs;
+use std::io::{ErrorKind, Read, Write};
+use std::fs::{self, File};
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{mpsc, Arc};
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
 
-use super::{ManifestData, StateRebuilder, BlockRebuilder, RestorationStatus, SnapshotService};
+use super::{ManifestData, StateRebuilder, BlockRebuilder, ChunkDecoders, RestorationStatus, SnapshotService};
 use super::io::{SnapshotReader, LooseReader, SnapshotWriter, LooseWriter};
 
 use blockchain::BlockChain;
@@ -35,11 +36,30 @@ use service::ClientIoMessage;
 
 use io::IoChannel;
 
-use util::{Bytes, H256, Mutex, RwLock, RwLockReadGuard, UtilError};
+use byteorder::{BigEndian, ByteOrder};
+use rlp::{RlpStream, UntrustedRlp, EMPTY_LIST_RLP};
+
+use util::{Bytes, H256, Hashable, Mutex, RwLock, RwLockReadGuard, UtilError};
 use util::journaldb::Algorithm;
-use util::kvdb::{Database, DatabaseConfig};
+use util::kvdb::{Database, DatabaseConfig, KeyValueDB};
 use util::snappy;
 
+// file in the restoration dir holding the manifest of the snapshot being restored.
+const RESTORATION_MANIFEST_FILE: &'static str = "MANIFEST";
+
+// prefix of the keys under which the chunks fed are recorded in the restoration database,
+// followed by the big-endian index of the chunk.
+const FED_CHUNK_PREFIX: &'static [u8] = b"restoration-fed-chunk";
+
+// the chunks recorded as fed to a restoration database, in order: their hashes, whether
+// they're state chunks, and what they changed in memory.
+fn fed_chunks(db: &KeyValueDB) -> Result<Vec<(H256, bool, Bytes)>, Error> {
+	db.iter_from_prefix(::db::COL_EXTRA, FED_CHUNK_PREFIX).map(|(_, record)| -> Result<_, Error> {
+		let rlp = UntrustedRlp::new(&record);
+		Ok((rlp.val_at(0)?, rlp.val_at(1)?, rlp.at(2)?.as_raw().to_vec()))
+	}).collect()
+}
+
 /// Helper for removing directories in case of error.
 struct Guard(bool, PathBuf);
 
@@ -68,14 +88,16 @@ struct Restoration {
 	manifest: ManifestData,
 	state_chunks_left: HashSet<H256>,
 	block_chunks_left: HashSet<H256>,
+	trace_chunks: HashSet<H256>, // trace chunks are fed along with the block chunks.
+	decoders: &'static ChunkDecoders,
 	state: StateRebuilder,
 	blocks: BlockRebuilder,
 	writer: Option<LooseWriter>,
-	snappy_buffer: Bytes,
 	final_state_root: H256,
 	guard: Guard,
 	canonical_hashes: HashMap<u64, H256>,
 	db: Arc<Database>,
+	fed: u64, // number of chunks recorded as fed.
 }
 
 struct RestorationParams<'a> {
@@ -86,6 +108,8 @@ struct RestorationParams<'a> {
 	writer: Option<LooseWriter>, // writer for recovered snapshot.
 	genesis: &'a [u8], // genesis block of the chain.
 	guard: Guard, // guard for the restoration directory.
+	resume: bool, // whether to resume from the chunks recorded in the database.
+	decoders: &'static ChunkDecoders, // decoders for the snapshot's format version.
 }
 
 impl Restoration {
@@ -94,7 +118,8 @@ impl Restoration {
 		let manifest = params.manifest;
 
 		let state_chunks = manifest.state_hashes.iter().cloned().collect();
-		let block_chunks = manifest.block_hashes.iter().cloned().collect();
+		let trace_chunks: HashSet<H256> = manifest.trace_hashes.iter().cloned().collect();
+		let block_chunks = manifest.block_hashes.iter().chain(trace_chunks.iter()).cloned().collect();
 
 		let raw_db = Arc::new(Database::open(params.db_config, &*params.db_path.to_string_lossy())
 			.map_err(UtilError::SimpleString)?);
@@ -103,27 +128,89 @@ impl Restoration {
 		let blocks = BlockRebuilder::new(chain, raw_db.clone(), &manifest)?;
 
 		let root = manifest.state_root.clone();
-		Ok(Restoration {
+		let mut restoration = Restoration {
 			manifest: manifest,
 			state_chunks_left: state_chunks,
 			block_chunks_left: block_chunks,
+			trace_chunks: trace_chunks,
+			decoders: params.decoders,
 			state: StateRebuilder::new(raw_db.clone(), params.pruning),
 			blocks: blocks,
 			writer: params.writer,
-			snappy_buffer: Vec::new(),
 			final_state_root: root,
 			guard: params.guard,
 			canonical_hashes: HashMap::new(),
 			db: raw_db,
-		})
+			fed: 0,
+		};
+
+		if params.resume {
+			for (hash, is_state, changes) in fed_chunks(&*restoration.db)? {
+				match is_state {
+					true => {
+						restoration.state_chunks_left.remove(&hash);
+						restoration.state.replay(&changes)?;
+					}
+					false => {
+						restoration.block_chunks_left.remove(&hash);
+						if !restoration.trace_chunks.contains(&hash) {
+							restoration.blocks.replay(&changes)?;
+						}
+					}
+				}
+				restoration.fed += 1;
+			}
+		}
+
+		Ok(restoration)
 	}
 
-	// feeds a state chunk, aborts early if `flag` becomes false.
-	fn feed_state(&mut self, hash: H256, chunk: &[u8], flag: &AtomicBool) -> Result<(), Error> {
-		if self.state_chunks_left.remove(&hash) {
-			let len = snappy::decompress_into(chunk, &mut self.snappy_buffer)?;
+	// record a chunk as fed, along with what it changed in the rebuilder's memory.
+	// this is written with the chunk's own changes to the database.
+	fn note_fed(&mut self, hash: H256, is_state: bool) {
+		let record = {
+			// trace chunks change nothing kept in memory.
+			let changes = match is_state {
+				true => self.state.changes(),
+				false if self.trace_chunks.contains(&hash) => &EMPTY_LIST_RLP[..],
+				false => self.blocks.changes(),
+			};
+
+			let mut stream = RlpStream::new_list(3);
+			stream.append(&hash).append(&is_state).append_raw(changes, 1);
+			stream.out()
+		};
 
-			self.state.feed(&self.snappy_buffer[..len], flag)?;
+		let mut key = FED_CHUNK_PREFIX.to_vec();
+		let mut index = [0u8; 8];
+		BigEndian::write_u64(&mut index, self.fed);
+		key.extend_from_slice(&index);
+
+		let mut batch = self.db.transaction();
+		batch.put(::db::COL_EXTRA, &key, &record);
+		self.db.write_buffered(batch);
+		self.fed += 1;
+	}
+
+	// hashes of the chunks fed so far.
+	fn completed_chunks(&self) -> Vec<H256> {
+		self.manifest.state_hashes.iter().filter(|hash| !self.state_chunks_left.contains(hash))
+			.chain(self.manifest.block_hashes.iter().filter(|hash| !self.block_chunks_left.contains(hash)))
+			.chain(self.manifest.trace_hashes.iter().filter(|hash| !self.block_chunks_left.contains(hash)))
+			.cloned()
+			.collect()
+	}
+
+	// stop restoring, but keep everything on disk so the restoration can be resumed.
+	fn suspend(self) {
+		self.guard.disarm();
+	}
+
+	// feeds a state chunk, given decompressed as `raw`. aborts early if `flag` becomes false.
+	fn feed_state(&mut self, hash: H256, chunk: &[u8], raw: &[u8], flag: &AtomicBool) -> Result<(), Error> {
+		if self.state_chunks_left.remove(&hash) {
+			(self.decoders.state)(&mut self.state, raw, flag)?;
+			self.note_fed(hash, true);
 
 			if let Some(ref mut writer) = self.writer.as_mut() {
 				writer.write_state_chunk(hash, chunk)?;
@@ -133,12 +220,25 @@ impl Restoration {
 		Ok(())
 	}
 
-	// feeds a block chunk
-	fn feed_blocks(&mut self, hash: H256, chunk: &[u8], engine: &Engine, flag: &AtomicBool) -> Result<(), Error> {
+	// feeds a block or trace chunk, given decompressed as `raw`.
+	fn feed_blocks(&mut self, hash: H256, chunk: &[u8], raw: &[u8], engine: &Engine, flag: &AtomicBool) -> Result<(), Error> {
+		if self.trace_chunks.contains(&hash) {
+			if self.block_chunks_left.remove(&hash) {
+				::snapshot::restore_traces(&*self.db, raw)?;
+				self.note_fed(hash, false);
+
+				if let Some(ref mut writer) = self.writer.as_mut() {
+					writer.write_trace_chunk(hash, chunk)?;
+				}
+			}
+
+			return Ok(());
+		}
+
 		if self.block_chunks_left.remove(&hash) {
-			let len = snappy::decompress_into(chunk, &mut self.snappy_buffer)?;
+			(self.decoders.blocks)(&mut self.blocks, raw, engine, flag)?;
+			self.note_fed(hash, false);
 
-			self.blocks.feed(&self.snappy_buffer[..len], engine, flag)?;
 			if let Some(ref mut writer) = self.writer.as_mut() {
 				 writer.write_block_chunk(hash, chunk)?;
 			}
@@ -165,6 +265,13 @@ impl Restoration {
 			return Err(TrieError::InvalidStateRoot(root).into());
 		}
 
+		// the records of chunks fed are only of use until now.
+		let mut batch = self.db.transaction();
+		for (key, _) in self.db.iter_from_prefix(::db::COL_EXTRA, FED_CHUNK_PREFIX) {
+			batch.delete(::db::COL_EXTRA, &key);
+		}
+		self.db.write_buffered(batch);
+
 		// check for missing code.
 		self.state.finalize(self.manifest.block_number, self.manifest.block_hash)?;
 
@@ -205,6 +312,8 @@ pub struct ServiceParams {
 	pub snapshot_root: PathBuf,
 	/// A handle for database restoration.
 	pub db_restore: Arc<DatabaseRestore>,
+	/// Number of snapshots to keep on disk, including the current one.
+	pub retain: usize,
 }
 
 /// `SnapshotService` implementation.
@@ -225,6 +334,9 @@ pub struct Service {
 	progress: super::Progress,
 	taking_snapshot: AtomicBool,
 	restoring_snapshot: AtomicBool,
+	// snapshot of an interrupted restoration and the chunks it restored.
+	resumable: Mutex<Option<(ManifestData, Vec<H256>)>>,
+	retain: usize,
 }
 
 impl Service {
@@ -246,6 +358,8 @@ impl Service {
 			progress: Default::default(),
 			taking_snapshot: AtomicBool::new(false),
 			restoring_snapshot: AtomicBool::new(false),
+			resumable: Mutex::new(None),
+			retain: ::std::cmp::max(params.retain, 1),
 		};
 
 		// create the root snapshot dir if it doesn't exist.
@@ -255,11 +369,26 @@ impl Service {
 			}
 		}
 
-		// delete the temporary restoration dir if it does exist.
-		if let Err(e) = fs::remove_dir_all(service.restoration_dir()) {
-			if e.kind() != ErrorKind::NotFound {
-				return Err(e.into())
+		// keep the restoration dir of an interrupted restoration, to be resumed.
+		let resumable = match service.load_resumable() {
+			Ok(resumable) => resumable,
+			Err(e) => {
+				warn!("Unable to resume interrupted snapshot restoration: {}", e);
+				None
 			}
+		};
+
+		match resumable {
+			Some(resumable) => {
+				info!("Found interrupted restoration of snapshot at #{} with {} chunks restored", resumable.0.block_number, resumable.1.len());
+				*service.resumable.get_mut() = Some(resumable);
+			}
+			// delete the temporary restoration dir if it does exist.
+			None => if let Err(e) = fs::remove_dir_all(service.restoration_dir()) {
+				if e.kind() != ErrorKind::NotFound {
+					return Err(e.into())
+				}
+			},
 		}
 
 		// delete the temporary snapshot dir if it does exist.
@@ -269,6 +398,9 @@ impl Service {
 			}
 		}
 
+		// the number of snapshots to keep may have been lowered.
+		service.prune_history()?;
+
 		let reader = LooseReader::new(service.snapshot_dir()).ok();
 		*service.reader.get_mut() = reader;
 
@@ -289,6 +421,13 @@ impl Service {
 		dir
 	}
 
+	// get the dir of past snapshots, kept in subdirs named after their block numbers.
+	fn history_dir(&self) -> PathBuf {
+		let mut dir = self.snapshot_root.clone();
+		dir.push("history");
+		dir
+	}
+
 	// get the restoration directory.
 	fn restoration_dir(&self) -> PathBuf {
 		let mut dir = self.snapshot_root.clone();
@@ -310,6 +449,83 @@ impl Service {
 		dir
 	}
 
+	// restoration manifest path.
+	fn restoration_manifest(&self) -> PathBuf {
+		let mut dir = self.restoration_dir();
+		dir.push(RESTORATION_MANIFEST_FILE);
+		dir
+	}
+
+	// the snapshot and chunks restored by an interrupted restoration, if there is one.
+	fn load_resumable(&self) -> Result<Option<(ManifestData, Vec<H256>)>, Error> {
+		let mut manifest = Vec::new();
+		match File::open(self.restoration_manifest()) {
+			Ok(mut file) => file.read_to_end(&mut manifest)?,
+			Err(ref e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+			Err(e) => return Err(e.into()),
+		};
+		let manifest = ManifestData::from_rlp(&manifest)?;
+
+		if !self.restoration_db().exists() { return Ok(None) }
+		let db = Database::open(&self.db_config, &*self.restoration_db().to_string_lossy())
+			.map_err(UtilError::SimpleString)?;
+		let chunks = fed_chunks(&db)?.into_iter().map(|(hash, _, _)| hash).collect();
+
+		Ok(Some((manifest, chunks)))
+	}
+
+	// move the current snapshot, taken at block `num`, into the history or delete it
+	// if past snapshots aren't kept.
+	fn retire_snapshot(&self, num: u64) -> Result<(), Error> {
+		let snapshot_dir = self.snapshot_dir();
+		if !snapshot_dir.exists() { return Ok(()) }
+
+		if self.retain == 1 {
+			fs::remove_dir_all(&snapshot_dir)?;
+			return Ok(());
+		}
+
+		let mut past_dir = self.history_dir();
+		fs::create_dir_all(&past_dir)?;
+		past_dir.push(num.to_string());
+
+		if past_dir.exists() {
+			fs::remove_dir_all(&past_dir)?;
+		}
+		fs::rename(&snapshot_dir, &past_dir)?;
+
+		self.prune_history()
+	}
+
+	// delete the oldest past snapshots beyond the number kept.
+	fn prune_history(&self) -> Result<(), Error> {
+		let history_dir = self.history_dir();
+		let entries = match fs::read_dir(&history_dir) {
+			Ok(entries) => entries,
+			Err(ref e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+			Err(e) => return Err(e.into()),
+		};
+
+		let mut past = Vec::new();
+		for entry in entries {
+			let path = entry?.path();
+			match path.file_name().and_then(|name| name.to_str()).and_then(|name| name.parse::<u64>().ok()) {
+				Some(num) => past.push((num, path)),
+				None => trace!(target: "snapshot", "ignoring unexpected file in snapshot history: {}", path.display()),
+			}
+		}
+
+		// the current snapshot is one of those kept.
+		past.sort();
+		let excess = past.len().saturating_sub(self.retain - 1);
+		for (num, path) in past.into_iter().take(excess) {
+			trace!(target: "snapshot", "removing snapshot at #{}", num);
+			fs::remove_dir_all(&path)?;
+		}
+
+		Ok(())
+	}
+
 	// replace one the client's database with our own.
 	fn replace_client_db(&self) -> Result<(), Error> {
 		let our_db = self.restoration_db();
@@ -348,12 +564,24 @@ impl Service {
 		let temp_dir = self.temp_snapshot_dir();
 		let snapshot_dir = self.snapshot_dir();
 
-		let _ = fs::remove_dir_all(&temp_dir);
+		// keep the chunks of a snapshot of the same state interrupted by a restart.
+		let state_root = client.block_header(BlockId::Number(num)).map(|header| header.state_root());
+		let resumable = LooseWriter::new(temp_dir.clone()).ok()
+			.and_then(|writer| writer.state_checkpoint())
+			.map_or(false, |checkpoint| Some(checkpoint.position.root) == state_root);
+
+		if !resumable {
+			let _ = fs::remove_dir_all(&temp_dir);
+		}
 
 		let writer = LooseWriter::new(temp_dir.clone())?;
 
 		let guard = Guard::new(temp_dir.clone());
-		let res = client.take_snapshot(writer, BlockId::Number(num), &self.progress);
+		let res = {
+			// the current snapshot is the base of an incremental one.
+			let base = self.reader.read();
+			client.take_snapshot(writer, BlockId::Number(num), base.as_ref().map(|r| r as &SnapshotReader), &self.progress)
+		};
 
 		self.taking_snapshot.store(false, Ordering::SeqCst);
 		if let Err(e) = res {
@@ -373,11 +601,13 @@ impl Service {
 
 		let mut reader = self.reader.write();
 
-		// destroy the old snapshot reader.
-		*reader = None;
-
-		if snapshot_dir.exists() {
-			fs::remove_dir_all(&snapshot_dir)?;
+		// destroy the old snapshot reader, keeping the snapshot if configured to.
+		let old_num = reader.take().map(|r| r.manifest().block_number);
+		match old_num {
+			Some(old_num) => self.retire_snapshot(old_num)?,
+			None => if snapshot_dir.exists() {
+				fs::remove_dir_all(&snapshot_dir)?;
+			},
 		}
 
 		fs::rename(temp_dir, &snapshot_dir)?;
@@ -390,7 +620,12 @@ impl Service {
 
 	/// Initialize the restoration synchronously.
 	/// The recover flag indicates whether to recover the restored snapshot.
+	///
+	/// A restoration of the same snapshot which was interrupted, even by a restart,
+	/// is resumed rather than started over.
 	pub fn init_restore(&self, manifest: ManifestData, recover: bool) -> Result<(), Error> {
+		// refuse formats which can't be decoded before touching anything on disk.
+		let decoders = super::chunk_decoders(manifest.version)?;
 		let rest_dir = self.restoration_dir();
 
 		let mut res = self.restoration.lock();
@@ -398,21 +633,38 @@ impl Service {
 		self.state_chunks.store(0, Ordering::SeqCst);
 		self.block_chunks.store(0, Ordering::SeqCst);
 
-		// tear down existing restoration.
-		*res = None;
+		// tear down existing restoration, keeping it on disk if it's of the same snapshot.
+		let resume = match res.take() {
+			Some(rest) => match rest.manifest == manifest {
+				true => {
+					rest.suspend();
+					true
+				}
+				false => false,
+			},
+			None => self.resumable.lock().take().map_or(false, |(resumable, _)| resumable == manifest),
+		};
 
-		// delete and restore the restoration dir.
-		if let Err(e) = fs::remove_dir_all(&rest_dir) {
-			match e.kind() {
-				ErrorKind::NotFound => {},
-				_ => return Err(e.into()),
+		if !resume {
+			// delete and restore the restoration dir.
+			if let Err(e) = fs::remove_dir_all(&rest_dir) {
+				match e.kind() {
+					ErrorKind::NotFound => {},
+					_ => return Err(e.into()),
+				}
 			}
-		}
 
-		fs::create_dir_all(&rest_dir)?;
+			fs::create_dir_all(&rest_dir)?;
 
-		// make new restoration.
-		let writer = match recover {
+			// note the snapshot being restored, so the restoration can be resumed.
+			let mut file = File::create(self.restoration_manifest())?;
+			file.write_all(&manifest.clone().into_rlp())?;
+			file.sync_all()?;
+		}
+
+		// make new restoration. the snapshot can only be recovered if the chunks restored
+		// so far were kept.
+		let writer = match recover && (!resume || self.temp_recovery_dir().exists()) {
 			true => Some(LooseWriter::new(self.temp_recovery_dir())?),
 			false => None
 		};
@@ -425,12 +677,23 @@ impl Service {
 			writer: writer,
 			genesis: &self.genesis_block,
 			guard: Guard::new(rest_dir),
+			resume: resume,
+			decoders: decoders,
 		};
 
 		let state_chunks = params.manifest.state_hashes.len();
-		let block_chunks = params.manifest.block_hashes.len();
+		let block_chunks = params.manifest.block_hashes.len() + params.manifest.trace_hashes.len();
+
+		let rest = Restoration::new(params)?;
+		let state_chunks_done = state_chunks - rest.state_chunks_left.len();
+		let block_chunks_done = block_chunks - rest.block_chunks_left.len();
+		if resume {
+			info!("Resuming restoration after {} state chunks and {} block chunks", state_chunks_done, block_chunks_done);
+		}
 
-		*res = Some(Restoration::new(params)?);
+		self.state_chunks.store(state_chunks_done, Ordering::SeqCst);
+		self.block_chunks.store(block_chunks_done, Ordering::SeqCst);
+		*res = Some(rest);
 
 		*self.status.lock() = RestorationStatus::Ongoing {
 			state_chunks: state_chunks as u32,
@@ -478,8 +741,8 @@ impl Service {
 		Ok(())
 	}
 
-	/// Feed a chunk of either kind. no-op if no restoration or status is wrong.
-	fn feed_chunk(&self, hash: H256, chunk: &[u8], is_state: bool) -> Result<(), Error> {
+	/// Feed a decompressed chunk of either kind. no-op if no restoration or status is wrong.
+	fn feed_chunk(&self, hash: H256, chunk: &[u8], raw: &[u8], is_state: bool) -> Result<(), Error> {
 		// TODO: be able to process block chunks and state chunks at same time?
 		let (result, db) = {
 			let mut restoration = self.restoration.lock();
@@ -494,8 +757,8 @@ impl Service {
 						};
 
 						(match is_state {
-							true => rest.feed_state(hash, chunk, &self.restoring_snapshot),
-							false => rest.feed_blocks(hash, chunk, &*self.engine, &self.restoring_snapshot),
+							true => rest.feed_state(hash, chunk, raw, &self.restoring_snapshot),
+							false => rest.feed_blocks(hash, chunk, raw, &*self.engine, &self.restoring_snapshot),
 						}.map(|_| rest.is_done()), rest.db.clone())
 					};
 
@@ -526,23 +789,23 @@ impl Service {
 
 	/// Feed a state chunk to be processed synchronously.
 	pub fn feed_state_chunk(&self, hash: H256, chunk: &[u8]) {
-		match self.feed_chunk(hash, chunk, true) {
-			Ok(()) => (),
-			Err(e) => {
-				warn!("Encountered error during state restoration: {}", e);
-				*self.restoration.lock() = None;
-				*self.status.lock() = RestorationStatus::Failed;
-				let _ = fs::remove_dir_all(self.restoration_dir());
-			}
-		}
+		self.feed_verified_chunk(hash, chunk, verify_chunk(hash, chunk), true)
 	}
 
 	/// Feed a block chunk to be processed synchronously.
 	pub fn feed_block_chunk(&self, hash: H256, chunk: &[u8]) {
-		match self.feed_chunk(hash, chunk, false) {
+		self.feed_verified_chunk(hash, chunk, verify_chunk(hash, chunk), false)
+	}
+
+	// feed a chunk which has been verified and decompressed, or has failed to be.
+	fn feed_verified_chunk(&self, hash: H256, chunk: &[u8], raw: Result<Bytes, Error>, is_state: bool) {
+		match raw.and_then(|raw| self.feed_chunk(hash, chunk, &raw, is_state)) {
 			Ok(()) => (),
 			Err(e) => {
-				warn!("Encountered error during block restoration: {}", e);
+				match is_state {
+					true => warn!("Encountered error during state restoration: {}", e),
+					false => warn!("Encountered error during block restoration: {}", e),
+				}
 				*self.restoration.lock() = None;
 				*self.status.lock() = RestorationStatus::Failed;
 				let _ = fs::remove_dir_all(self.restoration_dir());
@@ -551,6 +814,113 @@ impl Service {
 	}
 }
 
+// check a chunk against its hash and decompress it.
+fn verify_chunk(hash: H256, chunk: &[u8]) -> Result<Bytes, Error> {
+	let found = chunk.sha3();
+	if found != hash {
+		return Err(::snapshot::Error::ChunkHashMismatch(hash, found).into());
+	}
+
+	Ok(snappy::decompress(chunk)?)
+}
+
+// a chunk to be verified: its hash, the chunk, and whether it's a state chunk.
+type ChunkJob = (H256, Bytes, bool);
+
+// a chunk which has been verified and decompressed, or has failed to be.
+type VerifiedChunk = (H256, Bytes, Result<Bytes, Error>, bool);
+
+/// Restoration pipeline: chunks are checked against their hashes and decompressed
+/// on several threads, and fed to the snapshot service on a single one.
+///
+/// The queues between the stages are bounded, so a slow database applies
+/// back-pressure to whoever is feeding chunks.
+pub struct ChunkPipeline {
+	jobs: Mutex<Option<mpsc::SyncSender<ChunkJob>>>,
+	threads: Vec<thread::JoinHandle<()>>,
+}
+
+impl ChunkPipeline {
+	/// Start a pipeline feeding the given service, verifying on `threads` threads.
+	pub fn new(service: Arc<Service>, threads: usize) -> Self {
+		let threads = ::std::cmp::max(threads, 1);
+		let (jobs, job_queue) = mpsc::sync_channel(threads);
+		let job_queue = Arc::new(Mutex::new(job_queue));
+		let (verified_tx, verified) = mpsc::sync_channel(threads);
+
+		let mut handles = Vec::with_capacity(threads + 1);
+		for i in 0..threads {
+			let job_queue = job_queue.clone();
+			let verified_tx = verified_tx.clone();
+			handles.push(thread::Builder::new()
+				.name(format!("Chunk Verifier #{}", i))
+				.spawn(move || verify_chunks(&job_queue, verified_tx))
+				.expect("Error starting chunk verifier thread"));
+		}
+		drop(verified_tx);
+
+		// the applying thread stops once all the verifiers have.
+		handles.push(thread::Builder::new()
+			.name("Chunk Restorer".into())
+			.spawn(move || {
+				for (hash, chunk, raw, is_state) in verified {
+					service.feed_verified_chunk(hash, &chunk, raw, is_state);
+				}
+			})
+			.expect("Error starting chunk restorer thread"));
+
+		ChunkPipeline {
+			jobs: Mutex::new(Some(jobs)),
+			threads: handles,
+		}
+	}
+
+	/// Queue a state chunk to be restored. Blocks while the pipeline is full.
+	pub fn feed_state_chunk(&self, hash: H256, chunk: Bytes) {
+		self.feed(hash, chunk, true)
+	}
+
+	/// Queue a block chunk to be restored. Blocks while the pipeline is full.
+	pub fn feed_block_chunk(&self, hash: H256, chunk: Bytes) {
+		self.feed(hash, chunk, false)
+	}
+
+	fn feed(&self, hash: H256, chunk: Bytes, is_state: bool) {
+		// clone the sender so a full queue doesn't block other feeders on the lock.
+		let jobs = self.jobs.lock().clone();
+		if let Some(jobs) = jobs {
+			if jobs.send((hash, chunk, is_state)).is_err() {
+				trace!(target: "snapshot", "Chunk pipeline shut down, dropping chunk {:?}", hash);
+			}
+		}
+	}
+}
+
+impl Drop for ChunkPipeline {
+	fn drop(&mut self) {
+		// closing the queue stops the verifiers, which in turn stops the restorer.
+		self.jobs.lock().take();
+		for handle in self.threads.drain(..) {
+			let _ = handle.join();
+		}
+	}
+}
+
+// verify and decompress chunks until there are no more.
+fn verify_chunks(jobs: &Mutex<mpsc::Receiver<ChunkJob>>, verified: mpsc::SyncSender<VerifiedChunk>) {
+	loop {
+		let (hash, chunk, is_state) = match jobs.lock().recv() {
+			Ok(job) => job,
+			Err(_) => return,
+		};
+
+		let raw = verify_chunk(hash, &chunk);
+		if verified.send((hash, chunk, raw, is_state)).is_err() {
+			return;
+		}
+	}
+}
+
 impl SnapshotService for Service {
 	fn manifest(&self) -> Option<ManifestData> {
 		self.reader.read().as_ref().map(|r| r.manifest().clone())
@@ -601,11 +971,28 @@ impl SnapshotService for Service {
 			rest.note_canonical(canonical);
 		}
 	}
+
+	fn completed_chunks(&self, manifest: &ManifestData) -> Vec<H256> {
+		if let Some((ref resumable, ref chunks)) = *self.resumable.lock() {
+			if resumable == manifest {
+				return chunks.clone();
+			}
+		}
+
+		match *self.restoration.lock() {
+			Some(ref rest) if &rest.manifest == manifest => rest.completed_chunks(),
+			_ => Vec::new(),
+		}
+	}
 }
 
 impl Drop for Service {
 	fn drop(&mut self) {
-		self.abort_restore();
+		// keep an unfinished restoration, to be resumed after a restart.
+		self.restoring_snapshot.store(false, Ordering::SeqCst);
+		if let Some(rest) = self.restoration.get_mut().take() {
+			rest.suspend();
+		}
 	}
 }
 
@@ -647,6 +1034,7 @@ mod tests {
 			channel: service.channel(),
 			snapshot_root: dir,
 			db_restore: Arc::new(NoopDBRestore),
+			retain: 1,
 		};
 
 		let service = Service::new(snapshot_params).unwrap();
@@ -662,6 +1050,7 @@ mod tests {
 			state_root: Default::default(),
 			block_number: 0,
 			block_hash: Default::default(),
+			trace_hashes: Vec::new(),
 		};
 
 		service.begin_restore(manifest);
@@ -52,6 +52,11 @@ pub trait SnapshotService : Sync + Send {
 	/// Give the restoration in-progress some canonical block hashes for
 	/// extra verification (performed at the end)
 	fn provide_canon_hashes(&self, canonical: &[(u64, H256)]);
+
+	/// Hashes of the chunks of the given snapshot which have already been restored,
+	/// either by the restoration in progress or by an interrupted one which
+	/// `begin_restore` will resume.
+	fn completed_chunks(&self, manifest: &ManifestData) -> Vec<H256>;
 }
 
 impl IpcConfig for SnapshotService { }
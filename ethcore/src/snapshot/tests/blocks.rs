@@ -21,7 +21,7 @@ use error::Error;
 
 use blockchain::generator::{ChainGenerator, ChainIterator, BlockFinalizer};
 use blockchain::BlockChain;
-use snapshot::{chunk_blocks, BlockRebuilder, Error as SnapshotError, Progress};
+use snapshot::{chunk_blocks, chunk_traces, restore_traces, BlockRebuilder, Error as SnapshotError, Progress};
 use snapshot::io::{PackedReader, PackedWriter, SnapshotReader, SnapshotWriter};
 
 use util::{Mutex, snappy};
@@ -69,6 +69,7 @@ fn chunk_and_restore(amount: u64) {
 		state_root: ::util::sha3::SHA3_NULL_RLP,
 		block_number: amount,
 		block_hash: best_hash,
+		trace_hashes: Vec::new(),
 	};
 
 	writer.into_inner().finish(manifest.clone()).unwrap();
@@ -98,6 +99,71 @@ fn chunk_and_restore_500() { chunk_and_restore(500) }
 #[test]
 fn chunk_and_restore_40k() { chunk_and_restore(40000) }
 
+#[test]
+fn chunk_and_restore_traces() {
+	use db::{Readable, Writable};
+	use trace::{FlatBlockTraces, FlatTransactionTraces};
+
+	let mut canon_chain = ChainGenerator::default();
+	let mut finalizer = BlockFinalizer::default();
+	let genesis = canon_chain.generate(&mut finalizer).unwrap();
+	let db_cfg = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+
+	let orig_path = RandomTempPath::create_dir();
+	let new_path = RandomTempPath::create_dir();
+	let mut snapshot_path = new_path.as_path().to_owned();
+	snapshot_path.push("SNAP");
+
+	let old_db = Arc::new(Database::open(&db_cfg, orig_path.as_str()).unwrap());
+	let bc = BlockChain::new(Default::default(), &genesis, old_db.clone());
+
+	// build the blockchain, recording traces for every block.
+	let traces = FlatBlockTraces::from(vec![FlatTransactionTraces::from(Vec::new())]);
+	let mut hashes = Vec::new();
+	let mut batch = old_db.transaction();
+	for _ in 0..10 {
+		let block = canon_chain.generate(&mut finalizer).unwrap();
+		bc.insert_block(&mut batch, &block, vec![]);
+		bc.commit();
+		let hash = bc.best_block_hash();
+		batch.write(::db::COL_TRACE, &hash, &traces);
+		hashes.push(hash);
+	}
+
+	old_db.write(batch).unwrap();
+
+	// snapshot the traces of the last 4 blocks.
+	let writer = Mutex::new(PackedWriter::new(&snapshot_path).unwrap());
+	let trace_hashes = chunk_traces(&bc, bc.best_block_hash(), &*old_db, 4, &writer).unwrap();
+	assert_eq!(trace_hashes.len(), 1);
+
+	let manifest = ::snapshot::ManifestData {
+		version: 2,
+		state_hashes: Vec::new(),
+		block_hashes: Vec::new(),
+		state_root: ::util::sha3::SHA3_NULL_RLP,
+		block_number: 10,
+		block_hash: bc.best_block_hash(),
+		trace_hashes: trace_hashes,
+	};
+
+	writer.into_inner().finish(manifest).unwrap();
+
+	// restore them.
+	let new_db = Database::open(&db_cfg, new_path.as_str()).unwrap();
+	let reader = PackedReader::new(&snapshot_path).unwrap().unwrap();
+	for hash in &reader.manifest().trace_hashes {
+		let compressed = reader.chunk(*hash).unwrap();
+		let chunk = snappy::decompress(&compressed).unwrap();
+		restore_traces(&new_db, &chunk).unwrap();
+	}
+
+	for hash in &hashes[6..] {
+		assert_eq!(new_db.read::<FlatBlockTraces, _>(::db::COL_TRACE, hash), Some(traces.clone()));
+	}
+	assert!(new_db.read::<FlatBlockTraces, _>(::db::COL_TRACE, &hashes[5]).is_none());
+}
+
 #[test]
 fn checks_flag() {
 	use rlp::RlpStream;
@@ -132,6 +198,7 @@ fn checks_flag() {
 		state_root: ::util::sha3::SHA3_NULL_RLP,
 		block_number: 102,
 		block_hash: H256::default(),
+		trace_hashes: Vec::new(),
 	};
 
 	let mut rebuilder = BlockRebuilder::new(chain, db.clone(), &manifest).unwrap();
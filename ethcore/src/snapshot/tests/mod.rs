@@ -33,6 +33,22 @@ fn manifest_rlp() {
 		block_number: 1234567,
 		state_root: Default::default(),
 		block_hash: Default::default(),
+		trace_hashes: Vec::new(),
+	};
+	let raw = manifest.clone().into_rlp();
+	assert_eq!(ManifestData::from_rlp(&raw).unwrap(), manifest);
+}
+
+#[test]
+fn manifest_rlp_with_traces() {
+	let manifest = ManifestData {
+		version: 2,
+		block_hashes: vec![1.into()],
+		state_hashes: vec![2.into()],
+		block_number: 1234567,
+		state_root: Default::default(),
+		block_hash: Default::default(),
+		trace_hashes: vec![3.into(), 4.into()],
 	};
 	let raw = manifest.clone().into_rlp();
 	assert_eq!(ManifestData::from_rlp(&raw).unwrap(), manifest);
@@ -20,7 +20,7 @@ use std::sync::Arc;
 
 use client::{BlockChainClient, Client};
 use ids::BlockId;
-use snapshot::service::{Service, ServiceParams};
+use snapshot::service::{Service, ServiceParams, ChunkPipeline};
 use snapshot::{self, ManifestData, SnapshotService};
 use spec::Spec;
 use tests::helpers::generate_dummy_client_with_spec_and_data;
@@ -73,6 +73,7 @@ fn restored_is_equivalent() {
 		channel: IoChannel::disconnected(),
 		snapshot_root: path,
 		db_restore: client2.clone(),
+		retain: 1,
 	};
 
 	let service = Service::new(service_params).unwrap();
@@ -116,6 +117,7 @@ fn guards_delete_folders() {
 		channel: IoChannel::disconnected(),
 		snapshot_root: path.clone(),
 		db_restore: Arc::new(NoopDBRestore),
+		retain: 1,
 	};
 
 	let service = Service::new(service_params).unwrap();
@@ -127,6 +129,7 @@ fn guards_delete_folders() {
 		block_hashes: vec![],
 		block_number: 0,
 		block_hash: Default::default(),
+		trace_hashes: Vec::new(),
 		state_root: Default::default(),
 	};
 
@@ -139,6 +142,215 @@ fn guards_delete_folders() {
 	service.init_restore(manifest.clone(), true).unwrap();
 	assert!(path.exists());
 
+	// an unfinished restoration is kept to be resumed.
 	drop(service);
+	assert!(path.exists());
+}
+
+#[test]
+fn rejects_unsupported_version() {
+	let spec = Spec::new_null();
+	let path = RandomTempPath::create_dir();
+	let mut path = path.as_path().clone();
+	let service_params = ServiceParams {
+		engine: spec.engine.clone(),
+		genesis_block: spec.genesis_block(),
+		db_config: DatabaseConfig::with_columns(::db::NUM_COLUMNS),
+		pruning: ::util::journaldb::Algorithm::Archive,
+		channel: IoChannel::disconnected(),
+		snapshot_root: path.clone(),
+		db_restore: Arc::new(NoopDBRestore),
+		retain: 1,
+	};
+
+	let service = Service::new(service_params).unwrap();
+	path.push("restoration");
+
+	let manifest = ManifestData {
+		version: snapshot::SNAPSHOT_VERSION + 1,
+		state_hashes: vec![],
+		block_hashes: vec![],
+		block_number: 0,
+		block_hash: Default::default(),
+		trace_hashes: Vec::new(),
+		state_root: Default::default(),
+	};
+
+	match service.init_restore(manifest, true) {
+		Err(::error::Error::Snapshot(snapshot::Error::VersionNotSupported(v))) => assert_eq!(v, snapshot::SNAPSHOT_VERSION + 1),
+		other => panic!("unexpected result: {:?}", other),
+	}
+
 	assert!(!path.exists());
+	assert_eq!(service.status(), ::snapshot::RestorationStatus::Inactive);
+}
+
+#[test]
+fn resumes_restoration_after_restart() {
+	const NUM_BLOCKS: u32 = 400;
+
+	let gas_prices = vec![1.into(), 2.into(), 3.into(), 999.into()];
+	let client = generate_dummy_client_with_spec_and_data(Spec::new_null, NUM_BLOCKS, 5, &gas_prices);
+
+	let path = RandomTempPath::create_dir();
+	let db_config = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+	let client_db = Database::open(&db_config, path.as_path().join("client_db").to_str().unwrap()).unwrap();
+
+	let spec = Spec::new_null();
+	let client2 = Client::new(
+		Default::default(),
+		&spec,
+		Arc::new(client_db),
+		Arc::new(::miner::Miner::with_spec(&spec)),
+		IoChannel::disconnected(),
+	).unwrap();
+
+	let new_service = || Service::new(ServiceParams {
+		engine: spec.engine.clone(),
+		genesis_block: spec.genesis_block(),
+		db_config: db_config.clone(),
+		pruning: ::util::journaldb::Algorithm::Archive,
+		channel: IoChannel::disconnected(),
+		snapshot_root: path.as_path().join("snapshot"),
+		db_restore: client2.clone(),
+		retain: 1,
+	}).unwrap();
+
+	let service = new_service();
+	service.take_snapshot(&client, NUM_BLOCKS as u64).unwrap();
+	let manifest = service.manifest().unwrap();
+
+	service.init_restore(manifest.clone(), true).unwrap();
+	for hash in &manifest.state_hashes {
+		let chunk = service.chunk(*hash).unwrap();
+		service.feed_state_chunk(*hash, &chunk);
+	}
+	drop(service);
+
+	// after a restart, the state chunks aren't needed again.
+	let service = new_service();
+	assert_eq!(service.completed_chunks(&manifest), manifest.state_hashes);
+	service.init_restore(manifest.clone(), true).unwrap();
+	match service.status() {
+		::snapshot::RestorationStatus::Ongoing { state_chunks_done, block_chunks_done, .. } => {
+			assert_eq!(state_chunks_done as usize, manifest.state_hashes.len());
+			assert_eq!(block_chunks_done, 0);
+		}
+		other => panic!("unexpected restoration status: {:?}", other),
+	}
+
+	for hash in &manifest.block_hashes {
+		let chunk = service.chunk(*hash).unwrap();
+		service.feed_block_chunk(*hash, &chunk);
+	}
+	assert_eq!(service.status(), ::snapshot::RestorationStatus::Inactive);
+
+	for x in 0..NUM_BLOCKS {
+		let block1 = client.block(BlockId::Number(x as u64)).unwrap();
+		let block2 = client2.block(BlockId::Number(x as u64)).unwrap();
+
+		assert_eq!(block1, block2);
+	}
+}
+
+#[test]
+fn restores_through_pipeline() {
+	const NUM_BLOCKS: u32 = 400;
+
+	let gas_prices = vec![1.into(), 2.into(), 3.into(), 999.into()];
+	let client = generate_dummy_client_with_spec_and_data(Spec::new_null, NUM_BLOCKS, 5, &gas_prices);
+
+	let path = RandomTempPath::create_dir();
+	let db_config = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+	let client_db = Database::open(&db_config, path.as_path().join("client_db").to_str().unwrap()).unwrap();
+
+	let spec = Spec::new_null();
+	let client2 = Client::new(
+		Default::default(),
+		&spec,
+		Arc::new(client_db),
+		Arc::new(::miner::Miner::with_spec(&spec)),
+		IoChannel::disconnected(),
+	).unwrap();
+
+	let service = Arc::new(Service::new(ServiceParams {
+		engine: spec.engine.clone(),
+		genesis_block: spec.genesis_block(),
+		db_config: db_config,
+		pruning: ::util::journaldb::Algorithm::Archive,
+		channel: IoChannel::disconnected(),
+		snapshot_root: path.as_path().join("snapshot"),
+		db_restore: client2.clone(),
+		retain: 1,
+	}).unwrap());
+
+	service.take_snapshot(&client, NUM_BLOCKS as u64).unwrap();
+	let manifest = service.manifest().unwrap();
+
+	// a chunk not matching its hash fails the restoration.
+	service.init_restore(manifest.clone(), true).unwrap();
+	let mut chunk = service.chunk(manifest.state_hashes[0]).unwrap();
+	chunk.push(0);
+	service.feed_state_chunk(manifest.state_hashes[0], &chunk);
+	assert_eq!(service.status(), ::snapshot::RestorationStatus::Failed);
+
+	service.init_restore(manifest.clone(), true).unwrap();
+	let pipeline = ChunkPipeline::new(service.clone(), 4);
+	for hash in &manifest.state_hashes {
+		pipeline.feed_state_chunk(*hash, service.chunk(*hash).unwrap());
+	}
+	for hash in &manifest.block_hashes {
+		pipeline.feed_block_chunk(*hash, service.chunk(*hash).unwrap());
+	}
+
+	// dropping the pipeline waits for the queued chunks to be restored.
+	drop(pipeline);
+	assert_eq!(service.status(), ::snapshot::RestorationStatus::Inactive);
+
+	for x in 0..NUM_BLOCKS {
+		let block1 = client.block(BlockId::Number(x as u64)).unwrap();
+		let block2 = client2.block(BlockId::Number(x as u64)).unwrap();
+
+		assert_eq!(block1, block2);
+	}
+}
+
+#[test]
+fn retains_past_snapshots() {
+	const NUM_BLOCKS: u32 = 20;
+
+	let gas_prices = vec![1.into(), 2.into()];
+	let client = generate_dummy_client_with_spec_and_data(Spec::new_null, NUM_BLOCKS, 2, &gas_prices);
+
+	let path = RandomTempPath::create_dir();
+	let snapshot_root = path.as_path().join("snapshot");
+	let history = snapshot_root.join("history");
+	for num in &["1", "2", "3"] {
+		::std::fs::create_dir_all(history.join(num)).unwrap();
+	}
+
+	let spec = Spec::new_null();
+	let service = Service::new(ServiceParams {
+		engine: spec.engine.clone(),
+		genesis_block: spec.genesis_block(),
+		db_config: DatabaseConfig::with_columns(::db::NUM_COLUMNS),
+		pruning: ::util::journaldb::Algorithm::Archive,
+		channel: IoChannel::disconnected(),
+		snapshot_root: snapshot_root.clone(),
+		db_restore: Arc::new(NoopDBRestore),
+		retain: 2,
+	}).unwrap();
+
+	// only the most recent past snapshot is kept alongside the current one.
+	assert!(!history.join("1").exists());
+	assert!(!history.join("2").exists());
+	assert!(history.join("3").exists());
+
+	service.take_snapshot(&client, NUM_BLOCKS as u64).unwrap();
+	assert!(history.join("3").exists());
+
+	service.take_snapshot(&client, NUM_BLOCKS as u64).unwrap();
+	assert!(!history.join("3").exists());
+	assert!(history.join(NUM_BLOCKS.to_string()).exists());
+	assert_eq!(service.manifest().unwrap().block_number, NUM_BLOCKS as u64);
 }
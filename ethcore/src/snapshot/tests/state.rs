@@ -18,8 +18,8 @@
 
 use basic_account::BasicAccount;
 use snapshot::account;
-use snapshot::{chunk_state, Error as SnapshotError, Progress, StateRebuilder};
-use snapshot::io::{PackedReader, PackedWriter, SnapshotReader, SnapshotWriter};
+use snapshot::{chunk_state, chunk_state_from, dump_state, restore_state, Error as SnapshotError, Progress, StateRebuilder};
+use snapshot::io::{PackedReader, PackedWriter, LooseReader, LooseWriter, SnapshotReader, SnapshotWriter, StateCheckpoint, StateSegment};
 use super::helpers::{compare_dbs, StateProducer};
 
 use error::Error;
@@ -33,6 +33,7 @@ use util::Mutex;
 use devtools::RandomTempPath;
 
 use util::sha3::SHA3_NULL_RLP;
+use util::trie::{TrieDB, TrieCursor};
 
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
@@ -64,6 +65,7 @@ fn snap_and_restore() {
 		state_root: state_root,
 		block_number: 1000,
 		block_hash: H256::default(),
+		trace_hashes: Vec::new(),
 	}).unwrap();
 
 	let mut db_path = snap_dir.as_path().to_owned();
@@ -94,6 +96,192 @@ fn snap_and_restore() {
 	compare_dbs(&old_db, new_db.as_hashdb());
 }
 
+#[test]
+fn chunks_on_several_threads() {
+	let mut producer = StateProducer::new();
+	let mut rng = XorShiftRng::from_seed([5, 6, 7, 8]);
+	let mut old_db = MemoryDB::new();
+
+	for _ in 0..150 {
+		producer.tick(&mut rng, &mut old_db);
+	}
+
+	let state_root = producer.state_root();
+	let snap_dir = RandomTempPath::create_dir();
+
+	let serial = Progress::default();
+	let writer = Mutex::new(LooseWriter::new(snap_dir.as_path().join("serial")).unwrap());
+	let serial_hashes = chunk_state_from(&old_db, &state_root, None, None, 1, &writer, &serial).unwrap();
+
+	let parallel = Progress::default();
+	let writer = Mutex::new(LooseWriter::new(snap_dir.as_path().join("parallel")).unwrap());
+	let parallel_hashes = chunk_state_from(&old_db, &state_root, None, None, 4, &writer, &parallel).unwrap();
+
+	assert_eq!(parallel_hashes, serial_hashes);
+	assert_eq!(parallel.accounts(), serial.accounts());
+	assert_eq!(parallel.size(), serial.size());
+}
+
+#[test]
+fn resumes_from_checkpoint() {
+	let mut producer = StateProducer::new();
+	let mut rng = XorShiftRng::from_seed([9, 10, 11, 12]);
+	let mut old_db = MemoryDB::new();
+
+	for _ in 0..150 {
+		producer.tick(&mut rng, &mut old_db);
+	}
+
+	let state_root = producer.state_root();
+	let snap_dir = RandomTempPath::create_dir();
+
+	let full = Progress::default();
+	let writer = Mutex::new(LooseWriter::new(snap_dir.as_path().join("full")).unwrap());
+	chunk_state(&old_db, &state_root, &writer, &full).unwrap();
+
+	// stop after a third of the accounts.
+	let position = {
+		let trie = TrieDB::new(&old_db, &state_root).unwrap();
+		let mut cursor = TrieCursor::new(&trie).unwrap();
+		for item in cursor.by_ref().take(full.accounts() / 3) {
+			item.unwrap();
+		}
+		cursor.position().clone()
+	};
+	let checkpoint = StateCheckpoint {
+		hashes: vec![H256::from(1)],
+		position: position,
+		segments: Vec::new(),
+	};
+
+	let path = snap_dir.as_path().join("resumed");
+	let resumed = Progress::default();
+	let writer = Mutex::new(LooseWriter::new(path.clone()).unwrap());
+	let hashes = chunk_state_from(&old_db, &state_root, Some(checkpoint.clone()), None, 1, &writer, &resumed).unwrap();
+
+	assert_eq!(hashes[0], H256::from(1));
+	assert_eq!(resumed.accounts(), full.accounts() - full.accounts() / 3);
+
+	// the checkpoint survives the writer, until the snapshot is finished.
+	writer.into_inner().write_state_checkpoint(&checkpoint).unwrap();
+	let writer = LooseWriter::new(path).unwrap();
+	assert_eq!(writer.state_checkpoint(), Some(checkpoint));
+	writer.finish(::snapshot::ManifestData {
+		version: 2,
+		state_hashes: hashes,
+		block_hashes: Vec::new(),
+		state_root: state_root,
+		block_number: 1000,
+		block_hash: H256::default(),
+		trace_hashes: Vec::new(),
+	}).unwrap();
+	assert_eq!(LooseWriter::new(snap_dir.as_path().join("resumed")).unwrap().state_checkpoint(), None);
+}
+
+#[test]
+fn reuses_unchanged_chunks() {
+	use std::cell::Cell;
+	use snapshot::ManifestData;
+
+	struct CountingReader(LooseReader, Cell<usize>);
+
+	impl SnapshotReader for CountingReader {
+		fn manifest(&self) -> &ManifestData { self.0.manifest() }
+
+		fn chunk(&self, hash: H256) -> ::std::io::Result<::util::Bytes> {
+			self.1.set(self.1.get() + 1);
+			self.0.chunk(hash)
+		}
+
+		fn state_segments(&self) -> Option<Vec<StateSegment>> { self.0.state_segments() }
+	}
+
+	let mut producer = StateProducer::new();
+	let mut rng = XorShiftRng::from_seed([13, 14, 15, 16]);
+	let mut old_db = MemoryDB::new();
+
+	for _ in 0..150 {
+		producer.tick(&mut rng, &mut old_db);
+	}
+
+	let state_root = producer.state_root();
+	let snap_dir = RandomTempPath::create_dir();
+
+	let take = |name: &str, base: Option<&SnapshotReader>, root: &H256, db: &MemoryDB| {
+		let writer = Mutex::new(LooseWriter::new(snap_dir.as_path().join(name)).unwrap());
+		let hashes = chunk_state_from(db, root, None, base, 1, &writer, &Progress::default()).unwrap();
+		writer.into_inner().finish(ManifestData {
+			version: 2,
+			state_hashes: hashes.clone(),
+			block_hashes: Vec::new(),
+			state_root: *root,
+			block_number: 1000,
+			block_hash: H256::default(),
+			trace_hashes: Vec::new(),
+		}).unwrap();
+		hashes
+	};
+
+	let base_hashes = take("base", None, &state_root, &old_db);
+	let base = CountingReader(LooseReader::new(snap_dir.as_path().join("base")).unwrap(), Cell::new(0));
+	let segments = base.state_segments().unwrap();
+	assert_eq!(segments.iter().flat_map(|segment| segment.chunks.clone()).collect::<Vec<_>>(), base_hashes);
+
+	// nothing has changed, so every chunk is copied over.
+	let same_hashes = take("same", Some(&base), &state_root, &old_db);
+	assert_eq!(same_hashes, base_hashes);
+	assert_eq!(base.1.get(), base_hashes.len());
+	assert_eq!(LooseReader::new(snap_dir.as_path().join("same")).unwrap().state_segments(), Some(segments));
+
+	// the chunks of changed accounts are made again.
+	for _ in 0..5 {
+		producer.tick(&mut rng, &mut old_db);
+	}
+	let new_root = producer.state_root();
+	let new_hashes = take("changed", Some(&base), &new_root, &old_db);
+
+	let db_cfg = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+	let db = Arc::new(Database::open(&db_cfg, &snap_dir.as_path().join("db").to_string_lossy()).unwrap());
+	let mut rebuilder = StateRebuilder::new(db, Algorithm::Archive);
+	let reader = LooseReader::new(snap_dir.as_path().join("changed")).unwrap();
+	let flag = AtomicBool::new(true);
+	for hash in &new_hashes {
+		let chunk = ::util::snappy::decompress(&reader.chunk(*hash).unwrap()).unwrap();
+		rebuilder.feed(&chunk, &flag).unwrap();
+	}
+	rebuilder.check_missing().unwrap();
+	assert_eq!(rebuilder.state_root(), new_root);
+}
+
+#[test]
+fn dump_and_restore_stream() {
+	let mut producer = StateProducer::new();
+	let mut rng = XorShiftRng::from_seed([5, 6, 7, 8]);
+	let mut old_db = MemoryDB::new();
+	let db_cfg = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+
+	for _ in 0..150 {
+		producer.tick(&mut rng, &mut old_db);
+	}
+
+	let state_root = producer.state_root();
+	let mut stream = Vec::new();
+	dump_state(&old_db, &state_root, &mut stream).unwrap();
+
+	let db_path = RandomTempPath::create_dir();
+	let db = Arc::new(Database::open(&db_cfg, db_path.as_str()).unwrap());
+	assert_eq!(restore_state(&stream[..], db.clone(), Algorithm::Archive).unwrap(), state_root);
+
+	// a truncated stream is rejected.
+	let truncated = &stream[..stream.len() - 4];
+	let other_path = RandomTempPath::create_dir();
+	let other_db = Arc::new(Database::open(&db_cfg, other_path.as_str()).unwrap());
+	assert!(restore_state(truncated, other_db, Algorithm::Archive).is_err());
+
+	let new_db = journaldb::new(db, Algorithm::Archive, ::db::COL_STATE);
+	compare_dbs(&old_db, new_db.as_hashdb());
+}
+
 #[test]
 fn get_code_from_prev_chunk() {
 	use std::collections::HashSet;
@@ -177,6 +365,7 @@ fn checks_flag() {
 		state_root: state_root,
 		block_number: 0,
 		block_hash: H256::default(),
+		trace_hashes: Vec::new(),
 	}).unwrap();
 
 	let mut db_path = snap_dir.as_path().to_owned();
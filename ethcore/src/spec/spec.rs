@@ -17,7 +17,8 @@
 //! Parameters for a block chain.
 
 use util::*;
-use builtin::Builtin;
+use builtin::{self, Builtin};
+use evm::Schedule;
 use engines::{Engine, NullEngine, InstantSeal, BasicAuthority, AuthorityRound, Tendermint};
 use factory::Factories;
 use executive::Executive;
@@ -55,6 +56,14 @@ pub struct CommonParams {
 	pub fork_block: Option<(BlockNumber, H256)>,
 	/// Number of first block where EIP-98 rules begin.
 	pub eip98_transition: BlockNumber,
+	/// Number of first block where EIP-140 rules (`REVERT`) begin.
+	pub eip140_transition: BlockNumber,
+	/// Number of first block where EIP-1014 rules (`CREATE2`) begin.
+	pub eip1014_transition: BlockNumber,
+	/// Maximum depth of nested calls and creations, overriding the engine's schedule.
+	pub max_call_depth: Option<usize>,
+	/// Maximum number of items on the EVM stack, overriding the engine's schedule.
+	pub stack_limit: Option<usize>,
 	/// Validate block receipts root.
 	pub validate_receipts_transition: u64,
 }
@@ -70,11 +79,29 @@ impl From<ethjson::spec::Params> for CommonParams {
 			min_gas_limit: p.min_gas_limit.into(),
 			fork_block: if let (Some(n), Some(h)) = (p.fork_block, p.fork_hash) { Some((n.into(), h.into())) } else { None },
 			eip98_transition: p.eip98_transition.map_or(0, Into::into),
+			eip140_transition: p.eip140_transition.map_or(BlockNumber::max_value(), Into::into),
+			eip1014_transition: p.eip1014_transition.map_or(BlockNumber::max_value(), Into::into),
+			max_call_depth: p.max_call_depth.map(Into::into),
+			stack_limit: p.stack_limit.map(Into::into),
 			validate_receipts_transition: p.validate_receipts_transition.map_or(0, Into::into),
 		}
 	}
 }
 
+impl CommonParams {
+	/// Apply common spec config parameters to the schedule.
+	pub fn update_schedule(&self, schedule: &mut Schedule, block_number: BlockNumber) {
+		schedule.have_revert = block_number >= self.eip140_transition;
+		schedule.have_create2 = block_number >= self.eip1014_transition;
+		if let Some(max_call_depth) = self.max_call_depth {
+			schedule.max_depth = max_call_depth;
+		}
+		if let Some(stack_limit) = self.stack_limit {
+			schedule.stack_limit = stack_limit;
+		}
+	}
+}
+
 /// Parameters for a block chain; includes both those intrinsic to the design of the
 /// chain and those to be interpreted by the active chain engine.
 pub struct Spec {
@@ -324,10 +351,16 @@ impl Spec {
 
 	/// Loads spec from json file.
 	pub fn load<R>(reader: R) -> Result<Self, String> where R: Read {
-		match ethjson::spec::Spec::load(reader) {
-			Ok(spec) => Ok(spec.into()),
-			Err(e) => Err(format!("Spec json is invalid: {}", e)),
+		let spec = ethjson::spec::Spec::load(reader).map_err(|e| format!("Spec json is invalid: {}", e))?;
+		for b in spec.accounts.builtins().values() {
+			if b.natives().is_empty() {
+				return Err(format!("Spec declares builtin {} without natives", b.name));
+			}
+			if let Some(name) = b.natives().into_iter().find(|name| !builtin::is_registered(name)) {
+				return Err(format!("Spec declares builtin {} with unknown native: {}", b.name, name));
+			}
 		}
+		Ok(spec.into())
 	}
 
 	/// Create a new Spec which conforms to the Frontier-era Morden chain except that it's a NullEngine consensus.
@@ -364,6 +397,10 @@ impl Spec {
 	/// Create a new Spec with BasicAuthority which uses multiple validator sets changing with height.
 	/// Account with secrets "0".sha3() is the validator for block 1 and with "1".sha3() onwards.
 	pub fn new_validator_multi() -> Self { load_bundled!("validator_multi") }
+
+	/// The same as the `safeContract`, but the set of validators is read only at blocks divisible by epoch length 2.
+	/// Changes made in between take effect from the block following the next transition.
+	pub fn new_validator_epoch_contract() -> Self { load_bundled!("validator_epoch_contract") }
 }
 
 #[cfg(test)]
@@ -27,6 +27,8 @@ use std::sync::Arc;
 use state::Account;
 use util::{Address, MemoryDB, Mutex, H256};
 use util::hashdb::{AsHashDB, HashDB, DBValue};
+use util::sha3::Hashable;
+use util::trie::compact;
 
 /// State backend. See module docs for more details.
 pub trait Backend: Send {
@@ -61,11 +63,60 @@ pub trait Backend: Send {
 	/// Note that an account with the given address is non-null.
 	fn note_non_null_account(&self, address: &Address);
 
+	/// Note the preimage of a secure trie key written in this state: an address
+	/// or a storage key.
+	fn note_key_preimage(&mut self, preimage: &[u8]);
+
+	/// Note that an account with the given address hash was removed from the state,
+	/// leaving its storage trie at `storage_root` to be reclaimed.
+	fn note_killed_storage(&mut self, address_hash: H256, storage_root: H256);
+
 	/// Check whether an account is known to be empty. Returns true if known to be
 	/// empty, false otherwise.
 	fn is_known_null(&self, address: &Address) -> bool;
 }
 
+/// A basic backend. Just wraps the given database, directly inserting into and deleting from
+/// it. Doesn't cache anything.
+///
+/// Used with a `MemoryDB` this is a pure in-memory backend, suitable for tests and for
+/// executing against state which doesn't live in the journalled database.
+pub struct Basic<H>(pub H);
+
+impl<H: AsHashDB + Send + Sync> Backend for Basic<H> {
+	fn as_hashdb(&self) -> &HashDB {
+		self.0.as_hashdb()
+	}
+
+	fn as_hashdb_mut(&mut self) -> &mut HashDB {
+		self.0.as_hashdb_mut()
+	}
+
+	fn add_to_account_cache(&mut self, _: Address, _: Option<Account>, _: bool) { }
+
+	fn cache_code(&self, _: H256, _: Arc<Vec<u8>>) { }
+
+	fn get_cached_account(&self, _: &Address) -> Option<Option<Account>> { None }
+
+	fn get_cached<F, U>(&self, _: &Address, _: F) -> Option<U>
+		where F: FnOnce(Option<&mut Account>) -> U
+	{
+		None
+	}
+
+	fn get_cached_code(&self, _: &H256) -> Option<Arc<Vec<u8>>> { None }
+	fn note_non_null_account(&self, _: &Address) { }
+	fn note_key_preimage(&mut self, _: &[u8]) { }
+	fn note_killed_storage(&mut self, _: H256, _: H256) { }
+	fn is_known_null(&self, _: &Address) -> bool { false }
+}
+
+impl<H: AsHashDB + Clone> Clone for Basic<H> {
+	fn clone(&self) -> Self {
+		Basic(self.0.clone())
+	}
+}
+
 /// A raw backend used to check proofs of execution.
 ///
 /// This doesn't delete anything since execution proofs won't have mangled keys
@@ -79,7 +130,7 @@ impl ProofCheck {
 	/// Create a new `ProofCheck` backend from the given state items.
 	pub fn new(proof: &[DBValue]) -> Self {
 		let mut db = MemoryDB::new();
-		for item in proof { db.insert(item); }
+		db.insert_batch(&proof.iter().map(|item| &item[..]).collect::<Vec<_>>());
 		ProofCheck(db)
 	}
 }
@@ -118,6 +169,8 @@ impl Backend for ProofCheck {
 	}
 	fn get_cached_code(&self, _hash: &H256) -> Option<Arc<Vec<u8>>> { None }
 	fn note_non_null_account(&self, _address: &Address) {}
+	fn note_key_preimage(&mut self, _preimage: &[u8]) {}
+	fn note_killed_storage(&mut self, _address_hash: H256, _storage_root: H256) {}
 	fn is_known_null(&self, _address: &Address) -> bool { false }
 }
 
@@ -142,7 +195,7 @@ impl<H: AsHashDB + Send + Sync> HashDB for Proving<H> {
 	fn get(&self, key: &H256) -> Option<DBValue> {
 		match self.base.as_hashdb().get(key) {
 			Some(val) => {
-				self.proof.lock().insert(val.clone());
+				self.note(key, &val);
 				Some(val)
 			}
 			None => self.changed.get(key)
@@ -157,6 +210,10 @@ impl<H: AsHashDB + Send + Sync> HashDB for Proving<H> {
 		self.changed.insert(value)
 	}
 
+	fn insert_batch(&mut self, values: &[&[u8]]) -> Vec<H256> {
+		self.changed.insert_batch(values)
+	}
+
 	fn emplace(&mut self, key: H256, value: DBValue) {
 		self.changed.emplace(key, value)
 	}
@@ -192,6 +249,8 @@ impl<H: AsHashDB + Send + Sync> Backend for Proving<H> {
 
 	fn get_cached_code(&self, _: &H256) -> Option<Arc<Vec<u8>>> { None }
 	fn note_non_null_account(&self, _: &Address) { }
+	fn note_key_preimage(&mut self, _: &[u8]) { }
+	fn note_killed_storage(&mut self, _: H256, _: H256) { }
 	fn is_known_null(&self, _: &Address) -> bool { false }
 }
 
@@ -207,8 +266,27 @@ impl<H: AsHashDB> Proving<H> {
 	}
 
 	/// Consume the backend, extracting the gathered proof.
+	/// Every node appears once and nodes are sorted, so the same execution
+	/// always produces the same witness.
 	pub fn extract_proof(self) -> Vec<DBValue> {
-		self.proof.into_inner().into_iter().collect()
+		let mut proof: Vec<DBValue> = self.proof.into_inner().into_iter().collect();
+		proof.sort_by(|a, b| a[..].cmp(&b[..]));
+		proof
+	}
+
+	// record a value read from the base. Trie nodes stored in the compact encoding are
+	// recorded canonically, along with the children embedded in them. Keys may be mangled
+	// past their first 12 bytes, so only those are checked against the canonical hash.
+	fn note(&self, key: &H256, val: &DBValue) {
+		let mut embedded = Vec::new();
+		let mut proof = self.proof.lock();
+		match compact::canonical(val, &mut embedded) {
+			Some(ref node) if node.sha3()[..12] == key[..12] => {
+				proof.insert(DBValue::from_slice(node));
+				proof.extend(embedded.into_iter().map(|(child, _)| DBValue::from_vec(child)));
+			}
+			_ => { proof.insert(val.clone()); }
+		}
 	}
 }
 
@@ -34,6 +34,7 @@ use pod_state::{self, PodState};
 use types::basic_account::BasicAccount;
 use types::executed::{Executed, ExecutionError};
 use types::state_diff::StateDiff;
+use types::call_analytics::AccountOverride;
 use transaction::SignedTransaction;
 use state_db::StateDB;
 
@@ -43,12 +44,14 @@ use util::trie;
 use util::trie::recorder::Recorder;
 
 mod account;
+mod parallel;
 mod substate;
 
 pub mod backend;
 
 pub use self::account::Account;
 pub use self::backend::Backend;
+pub use self::parallel::{Speculation, Merge};
 pub use self::substate::Substate;
 
 /// Used to return information about an `State::apply` operation.
@@ -241,16 +244,38 @@ pub fn check_proof(
 /// checkpoint can be discateded with `discard_checkpoint`. All of the orignal
 /// backed-up values are moved into a parent checkpoint (if any).
 ///
+/// A checkpoint may also be created under a label with `savepoint()` and later
+/// reverted or discarded by that label, together with any checkpoints
+/// created after it.
+///
+/// State forking.
+///
+/// `fork()` creates a copy of the state for speculative execution. Instead of
+/// cloning the local cache, the dirty entries are frozen into a snapshot which is
+/// shared between the parent and all its forks. A fork copies an entry from the
+/// snapshot into its own local cache the first time the account is accessed.
+///
 pub struct State<B: Backend> {
 	db: B,
 	root: H256,
 	cache: RefCell<HashMap<Address, AccountEntry>>,
 	// The original account is preserved in
 	checkpoints: RefCell<Vec<HashMap<Address, Option<AccountEntry>>>>,
+	// Labelled checkpoints and the checkpoint depth they were created at.
+	savepoints: Vec<(String, usize)>,
+	// Dirty entries inherited from the state this one was forked from.
+	forked: Option<ForkSnapshot>,
+	// Snapshot of our own dirty entries handed out to forks. Reset on modification.
+	fork_snapshot: RefCell<Option<ForkSnapshot>>,
+	// Accounts read so far. Only recorded by forks used for speculative execution.
+	reads: Option<RefCell<HashSet<Address>>>,
 	account_start_nonce: U256,
 	factories: Factories,
 }
 
+// Frozen dirty account entries shared between forks of a state.
+type ForkSnapshot = Arc<Mutex<HashMap<Address, AccountEntry>>>;
+
 #[derive(Copy, Clone)]
 enum RequireCache {
 	None,
@@ -287,6 +312,10 @@ impl<B: Backend> State<B> {
 			root: root,
 			cache: RefCell::new(HashMap::new()),
 			checkpoints: RefCell::new(Vec::new()),
+			savepoints: Vec::new(),
+			forked: None,
+			fork_snapshot: RefCell::new(None),
+			reads: None,
 			account_start_nonce: account_start_nonce,
 			factories: factories,
 		}
@@ -303,6 +332,10 @@ impl<B: Backend> State<B> {
 			root: root,
 			cache: RefCell::new(HashMap::new()),
 			checkpoints: RefCell::new(Vec::new()),
+			savepoints: Vec::new(),
+			forked: None,
+			fork_snapshot: RefCell::new(None),
+			reads: None,
 			account_start_nonce: account_start_nonce,
 			factories: factories
 		};
@@ -318,6 +351,10 @@ impl<B: Backend> State<B> {
 			root: self.root,
 			cache: self.cache,
 			checkpoints: self.checkpoints,
+			savepoints: self.savepoints,
+			forked: self.forked,
+			fork_snapshot: self.fork_snapshot,
+			reads: self.reads,
 			account_start_nonce: self.account_start_nonce,
 			factories: self.factories,
 		}
@@ -343,10 +380,12 @@ impl<B: Backend> State<B> {
 				}
 			}
 		}
+		self.prune_savepoints();
 	}
 
 	/// Revert to the last checkpoint and discard it.
 	pub fn revert_to_checkpoint(&mut self) {
+		*self.fork_snapshot.get_mut() = None;
 		if let Some(mut checkpoint) = self.checkpoints.get_mut().pop() {
 			for (k, v) in checkpoint.drain() {
 				match v {
@@ -372,6 +411,53 @@ impl<B: Backend> State<B> {
 				}
 			}
 		}
+		self.prune_savepoints();
+	}
+
+	/// Create a recoverable checkpoint of this state labelled with `label`.
+	/// Labels may be reused; the most recent savepoint with a label shadows older ones.
+	pub fn savepoint(&mut self, label: &str) {
+		let depth = self.checkpoints.get_mut().len();
+		self.savepoints.push((label.to_owned(), depth));
+		self.checkpoint();
+	}
+
+	/// Revert to the most recent savepoint labelled `label`, discarding it and every
+	/// checkpoint created after it. Returns `false` if there is no such savepoint.
+	pub fn revert_to_savepoint(&mut self, label: &str) -> bool {
+		match self.savepoint_depth(label) {
+			Some(depth) => {
+				while self.checkpoints.get_mut().len() > depth {
+					self.revert_to_checkpoint();
+				}
+				true
+			}
+			None => false,
+		}
+	}
+
+	/// Merge the most recent savepoint labelled `label`, and every checkpoint created
+	/// after it, into the previous checkpoint. Returns `false` if there is no such savepoint.
+	pub fn discard_savepoint(&mut self, label: &str) -> bool {
+		match self.savepoint_depth(label) {
+			Some(depth) => {
+				while self.checkpoints.get_mut().len() > depth {
+					self.discard_checkpoint();
+				}
+				true
+			}
+			None => false,
+		}
+	}
+
+	fn savepoint_depth(&self, label: &str) -> Option<usize> {
+		self.savepoints.iter().rev().find(|&&(ref l, _)| l == label).map(|&(_, depth)| depth)
+	}
+
+	// drop labels of savepoints whose checkpoints no longer exist.
+	fn prune_savepoints(&mut self) {
+		let depth = self.checkpoints.get_mut().len();
+		self.savepoints.retain(|&(_, d)| d < depth);
 	}
 
 	fn insert_cache(&self, address: &Address, account: AccountEntry) {
@@ -381,6 +467,7 @@ impl<B: Backend> State<B> {
 		// In all other cases account is read as clean first, and after that made
 		// dirty in and added to the checkpoint with `note_cache`.
 		if account.is_dirty() {
+			*self.fork_snapshot.borrow_mut() = None;
 			if let Some(ref mut checkpoint) = self.checkpoints.borrow_mut().last_mut() {
 				if !checkpoint.contains_key(address) {
 					checkpoint.insert(address.clone(), self.cache.borrow_mut().insert(address.clone(), account));
@@ -392,6 +479,7 @@ impl<B: Backend> State<B> {
 	}
 
 	fn note_cache(&self, address: &Address) {
+		*self.fork_snapshot.borrow_mut() = None;
 		if let Some(ref mut checkpoint) = self.checkpoints.borrow_mut().last_mut() {
 			if !checkpoint.contains_key(address) {
 				checkpoint.insert(address.clone(), self.cache.borrow().get(address).map(AccountEntry::clone_dirty));
@@ -458,6 +546,9 @@ impl<B: Backend> State<B> {
 		// 2. If there's an entry for the account in the global cache check for the key or load it into that account.
 		// 3. If account is missing in the global cache load it into the local cache and cache the key there.
 
+		self.note_read(address);
+		self.pull_from_fork(address);
+
 		// check local cache first without updating
 		{
 			let local_cache = self.cache.borrow_mut();
@@ -513,6 +604,35 @@ impl<B: Backend> State<B> {
 		r
 	}
 
+	/// Get up to `limit` committed storage entries of account `a`, ordered by trie key and
+	/// starting strictly after `after`, so the last key of a page can be used to fetch the next one.
+	/// Keys are the raw trie keys: hashes of the storage keys unless the trie is fat.
+	/// Returns an empty list for a non-existent account or one with uncommitted storage changes.
+	pub fn storage_entries(&self, a: &Address, after: Option<&H256>, limit: usize) -> trie::Result<Vec<(H256, H256)>> {
+		let root = match self.storage_root(a)? {
+			Some(root) => root,
+			None => return Ok(Vec::new()),
+		};
+
+		let account_db = self.factories.accountdb.readonly(self.db.as_hashdb(), a.sha3());
+		let trie = self.factories.trie.readonly(account_db.as_hashdb(), &root)?;
+		let mut iter = trie.iter()?;
+		if let Some(after) = after {
+			iter.seek(after)?;
+		}
+
+		let mut entries = Vec::new();
+		for item in iter {
+			if entries.len() == limit { break }
+			let (key, value) = item?;
+			let key = H256::from_slice(&key);
+			if after.map_or(false, |after| *after == key) { continue }
+			let value: U256 = ::rlp::decode(&value);
+			entries.push((key, value.into()));
+		}
+		Ok(entries)
+	}
+
 	/// Get accounts' code.
 	pub fn code(&self, a: &Address) -> trie::Result<Option<Arc<Bytes>>> {
 		self.ensure_cached(a, RequireCache::Code, true,
@@ -594,6 +714,29 @@ impl<B: Backend> State<B> {
 		Ok(())
 	}
 
+	/// Replace the given parts of account `a`, to simulate calls against a modified state.
+	pub fn apply_override(&mut self, a: &Address, o: &AccountOverride) -> trie::Result<()> {
+		if let Some(ref balance) = o.balance {
+			let current = self.balance(a)?;
+			if *balance > current {
+				self.add_balance(a, &(*balance - current), CleanupMode::ForceCreate)?;
+			} else {
+				self.sub_balance(a, &(current - *balance))?;
+			}
+		}
+		if let Some(ref nonce) = o.nonce {
+			self.require(a, false)?.set_nonce(*nonce);
+		}
+		if let Some(ref code) = o.code {
+			self.reset_code(a, code.clone())?;
+		}
+		for (key, value) in &o.storage {
+			self.set_storage(a, *key, *value)?;
+		}
+
+		Ok(())
+	}
+
 	/// Execute a given transaction, producing a receipt and an optional trace.
 	/// This will change the state accordingly.
 	pub fn apply(&mut self, env_info: &EnvInfo, engine: &Engine, t: &SignedTransaction, tracing: bool) -> ApplyResult {
@@ -614,7 +757,7 @@ impl<B: Backend> State<B> {
 
 	// Execute a given transaction.
 	fn execute(&mut self, env_info: &EnvInfo, engine: &Engine, t: &SignedTransaction, tracing: bool) -> Result<Executed, ExecutionError> {
-		let options = TransactOptions { tracing: tracing, vm_tracing: false, check_nonce: true };
+		let options = TransactOptions { tracing: tracing, vm_tracing: false, check_nonce: true, gas_profiling: false, struct_logging: None };
 		let vm_factory = self.factories.vm.clone();
 
 		Executive::new(self, env_info, engine, &vm_factory).transact(t, options)
@@ -634,6 +777,11 @@ impl<B: Backend> State<B> {
 		// first, commit the sub trees.
 		for (address, ref mut a) in accounts.iter_mut().filter(|&(_, ref a)| a.is_dirty()) {
 			if let Some(ref mut account) = a.account {
+				db.note_key_preimage(address);
+				for key in account.storage_changes().keys() {
+					db.note_key_preimage(key);
+				}
+
 				let addr_hash = account.address_hash(address);
 				{
 					let mut account_db = factories.accountdb.create(db.as_hashdb_mut(), addr_hash);
@@ -646,6 +794,14 @@ impl<B: Backend> State<B> {
 			}
 		}
 
+		// storage nodes of killed accounts can only be reclaimed when they are not
+		// shared with other accounts, i.e. when the keys are mangled.
+		let note_killed = match factories.accountdb {
+			::account_db::Factory::Mangled => true,
+			::account_db::Factory::Plain => false,
+		};
+		let mut killed = Vec::new();
+
 		{
 			let mut trie = factories.trie.from_existing(db.as_hashdb_mut(), root)?;
 			for (address, ref mut a) in accounts.iter_mut().filter(|&(_, ref a)| a.is_dirty()) {
@@ -655,12 +811,21 @@ impl<B: Backend> State<B> {
 						trie.insert(address, &account.rlp())?;
 					},
 					None => {
-						trie.remove(address)?;
+						if let Some(old) = trie.remove(address)? {
+							let old: BasicAccount = ::rlp::decode(&old);
+							if note_killed && old.storage_root != SHA3_NULL_RLP {
+								killed.push((address.sha3(), old.storage_root));
+							}
+						}
 					},
 				}
 			}
 		}
 
+		for (address_hash, storage_root) in killed {
+			db.note_killed_storage(address_hash, storage_root);
+		}
+
 		Ok(())
 	}
 
@@ -669,6 +834,15 @@ impl<B: Backend> State<B> {
 		let mut addresses = self.cache.borrow_mut();
 		trace!("Committing cache {:?} entries", addresses.len());
 		for (address, a) in addresses.drain().filter(|&(_, ref a)| a.state == AccountState::Committed || a.state == AccountState::CleanFresh) {
+			if let Some(ref account) = a.account {
+				// code loaded or deployed by this state is shared by code hash,
+				// so later executions of the same contract skip the database.
+				if account.code_hash() != SHA3_EMPTY {
+					if let Some(code) = account.code() {
+						self.db.cache_code(account.code_hash(), code);
+					}
+				}
+			}
 			self.db.add_to_account_cache(address, a.account, a.state == AccountState::Committed);
 		}
 	}
@@ -676,12 +850,16 @@ impl<B: Backend> State<B> {
 	/// Commits our cached account changes into the trie.
 	pub fn commit(&mut self) -> Result<(), Error> {
 		assert!(self.checkpoints.borrow().is_empty());
+		self.pull_all_from_fork();
+		self.forked = None;
+		*self.fork_snapshot.get_mut() = None;
 		Self::commit_into(&self.factories, &mut self.db, &mut self.root, &mut *self.cache.borrow_mut())
 	}
 
 	/// Clear state cache
 	pub fn clear(&mut self) {
 		self.cache.borrow_mut().clear();
+		*self.fork_snapshot.get_mut() = None;
 	}
 
 	#[cfg(test)]
@@ -689,6 +867,7 @@ impl<B: Backend> State<B> {
 	/// Populate the state from `accounts`.
 	pub fn populate_from(&mut self, accounts: PodState) {
 		assert!(self.checkpoints.borrow().is_empty());
+		*self.fork_snapshot.get_mut() = None;
 		for (add, acc) in accounts.drain().into_iter() {
 			self.cache.borrow_mut().insert(add, AccountEntry::new_dirty(Some(Account::from_pod(acc))));
 		}
@@ -697,6 +876,7 @@ impl<B: Backend> State<B> {
 	/// Populate a PodAccount map from this state.
 	pub fn to_pod(&self) -> PodState {
 		assert!(self.checkpoints.borrow().is_empty());
+		self.pull_all_from_fork();
 		// TODO: handle database rather than just the cache.
 		// will need fat db.
 		PodState::from(self.cache.borrow().iter().fold(BTreeMap::new(), |mut m, (add, opt)| {
@@ -765,6 +945,8 @@ impl<B: Backend> State<B> {
 	/// Populates local cache if nothing found.
 	fn ensure_cached<F, U>(&self, a: &Address, require: RequireCache, check_null: bool, f: F) -> trie::Result<U>
 		where F: Fn(Option<&Account>) -> U {
+		self.note_read(a);
+		self.pull_from_fork(a);
 		// check local cache first
 		if let Some(ref mut maybe_acc) = self.cache.borrow_mut().get_mut(a) {
 			if let Some(ref mut account) = maybe_acc.account {
@@ -802,6 +984,53 @@ impl<B: Backend> State<B> {
 		}
 	}
 
+	// Record that `a` was read, if reads are being recorded.
+	fn note_read(&self, a: &Address) {
+		if let Some(ref reads) = self.reads {
+			reads.borrow_mut().insert(a.clone());
+		}
+	}
+
+	// Copy the entry for `a` inherited from the forked-from state into the local
+	// cache unless it has already been accessed locally.
+	fn pull_from_fork(&self, a: &Address) {
+		if let Some(ref base) = self.forked {
+			if self.cache.borrow().contains_key(a) {
+				return;
+			}
+			if let Some(entry) = base.lock().get(a) {
+				self.cache.borrow_mut().insert(a.clone(), entry.clone_dirty());
+			}
+		}
+	}
+
+	// Copy every inherited entry not yet accessed locally into the local cache.
+	fn pull_all_from_fork(&self) {
+		if let Some(ref base) = self.forked {
+			let mut cache = self.cache.borrow_mut();
+			for (address, entry) in base.lock().iter() {
+				if !cache.contains_key(address) {
+					cache.insert(address.clone(), entry.clone_dirty());
+				}
+			}
+		}
+	}
+
+	// Freeze the dirty entries of this state, including inherited ones.
+	fn dirty_snapshot(&self) -> ForkSnapshot {
+		let mut dirty: HashMap<Address, AccountEntry> = match self.forked {
+			Some(ref base) => base.lock().iter().map(|(a, e)| (a.clone(), e.clone_dirty())).collect(),
+			None => HashMap::new(),
+		};
+		for (address, entry) in self.cache.borrow().iter() {
+			match entry.clone_if_dirty() {
+				Some(entry) => { dirty.insert(address.clone(), entry); },
+				None => { dirty.remove(address); },
+			}
+		}
+		Arc::new(Mutex::new(dirty))
+	}
+
 	/// Pull account `a` in our cache from the trie DB. `require_code` requires that the code be cached, too.
 	fn require<'a>(&'a self, a: &Address, require_code: bool) -> trie::Result<RefMut<'a, Account>> {
 		self.require_or_from(a, require_code, || Account::new_basic(U256::from(0u8), self.account_start_nonce), |_|{})
@@ -812,6 +1041,7 @@ impl<B: Backend> State<B> {
 	fn require_or_from<'a, F, G>(&'a self, a: &Address, require_code: bool, default: F, not_default: G) -> trie::Result<RefMut<'a, Account>>
 		where F: FnOnce() -> Account, G: FnOnce(&mut Account),
 	{
+		self.pull_from_fork(a);
 		let contains_key = self.cache.borrow().contains_key(a);
 		if !contains_key {
 			match self.db.get_cached_account(a) {
@@ -904,6 +1134,38 @@ impl<B: Backend> fmt::Debug for State<B> {
 	}
 }
 
+impl State<StateDB> {
+	/// Create a copy of this state for speculative execution, e.g. `eth_call`.
+	/// Uncommitted changes are shared with the fork through a frozen snapshot and
+	/// copied lazily; the global account cache is shared through the `StateDB`.
+	/// Must not be called while there are active checkpoints.
+	pub fn fork(&self) -> State<StateDB> {
+		assert!(self.checkpoints.borrow().is_empty());
+		let existing = self.fork_snapshot.borrow().clone();
+		let snapshot = match existing {
+			Some(snapshot) => snapshot,
+			None => {
+				let snapshot = self.dirty_snapshot();
+				*self.fork_snapshot.borrow_mut() = Some(snapshot.clone());
+				snapshot
+			}
+		};
+
+		State {
+			db: self.db.boxed_clone(),
+			root: self.root.clone(),
+			cache: RefCell::new(HashMap::new()),
+			checkpoints: RefCell::new(Vec::new()),
+			savepoints: Vec::new(),
+			forked: Some(snapshot),
+			fork_snapshot: RefCell::new(None),
+			reads: None,
+			account_start_nonce: self.account_start_nonce.clone(),
+			factories: self.factories.clone(),
+		}
+	}
+}
+
 // TODO: cloning for `State` shouldn't be possible in general; Remove this and use
 // checkpoints where possible.
 impl Clone for State<StateDB> {
@@ -923,6 +1185,10 @@ impl Clone for State<StateDB> {
 			root: self.root.clone(),
 			cache: RefCell::new(cache),
 			checkpoints: RefCell::new(Vec::new()),
+			savepoints: Vec::new(),
+			forked: self.forked.clone(),
+			fork_snapshot: RefCell::new(None),
+			reads: None,
 			account_start_nonce: self.account_start_nonce.clone(),
 			factories: self.factories.clone(),
 		}
@@ -946,6 +1212,7 @@ mod tests {
 	use ethcore_logger::init_log;
 	use trace::{FlatTrace, TraceError, trace};
 	use types::executed::CallType;
+	use types::call_analytics::AccountOverride;
 
 	fn secret() -> Secret {
 		Secret::from_slice(&"".sha3()).unwrap()
@@ -1812,6 +2079,22 @@ mod tests {
 		assert_eq!(state.code(&a).unwrap(), Some(Arc::new([1u8, 2, 3].to_vec())));
 	}
 
+	#[test]
+	fn committed_code_is_shared_by_hash() {
+		let a = Address::zero();
+		let temp = RandomTempPath::new();
+		let (_, db) = {
+			let mut state = get_temp_state_in(temp.as_path());
+			state.require_or_from(&a, false, ||Account::new_contract(42.into(), 0.into()), |_|{}).unwrap();
+			state.init_code(&a, vec![1, 2, 3]).unwrap();
+			state.commit().unwrap();
+			state.drop()
+		};
+
+		let hash = vec![1u8, 2, 3].sha3();
+		assert_eq!(db.get_cached_code(&hash), Some(Arc::new(vec![1u8, 2, 3])));
+	}
+
 	#[test]
 	fn storage_at_from_database() {
 		let a = Address::zero();
@@ -1827,6 +2110,46 @@ mod tests {
 		assert_eq!(s.storage_at(&a, &H256::from(&U256::from(1u64))).unwrap(), H256::from(&U256::from(69u64)));
 	}
 
+	#[test]
+	fn storage_entries_paginated() {
+		let a = Address::zero();
+		let temp = RandomTempPath::new();
+		let mut state = get_temp_state_in(temp.as_path());
+		for i in 1..4u64 {
+			state.set_storage(&a, H256::from(&U256::from(i)), H256::from(&U256::from(i * 10))).unwrap();
+		}
+		state.commit().unwrap();
+
+		let all = state.storage_entries(&a, None, 10).unwrap();
+		assert_eq!(all.len(), 3);
+		let mut values: Vec<_> = all.iter().map(|&(_, ref v)| U256::from(&**v)).collect();
+		values.sort();
+		assert_eq!(values, vec![10.into(), 20.into(), 30.into()]);
+
+		let first = state.storage_entries(&a, None, 1).unwrap();
+		assert_eq!(first, vec![all[0].clone()]);
+		let rest = state.storage_entries(&a, Some(&first[0].0), 10).unwrap();
+		assert_eq!(rest, all[1..].to_vec());
+
+		assert!(state.storage_entries(&1.into(), None, 10).unwrap().is_empty());
+	}
+
+	#[test]
+	fn in_memory_backend() {
+		let a = Address::zero();
+		let (root, db) = {
+			let mut state = State::new(backend::Basic(MemoryDB::new()), U256::from(0u8), Default::default());
+			state.add_balance(&a, &U256::from(69u64), CleanupMode::NoEmpty).unwrap();
+			state.set_storage(&a, H256::from(&U256::from(1u64)), H256::from(&U256::from(69u64))).unwrap();
+			state.commit().unwrap();
+			state.drop()
+		};
+
+		let state = State::from_existing(db, root, U256::from(0u8), Default::default()).unwrap();
+		assert_eq!(state.balance(&a).unwrap(), U256::from(69u64));
+		assert_eq!(state.storage_at(&a, &H256::from(&U256::from(1u64))).unwrap(), H256::from(&U256::from(69u64)));
+	}
+
 	#[test]
 	fn get_from_database() {
 		let a = Address::zero();
@@ -1962,6 +2285,33 @@ mod tests {
 		assert_eq!(state.nonce(&a).unwrap(), U256::from(3u64));
 	}
 
+	#[test]
+	fn apply_override() {
+		let mut state_result = get_temp_state();
+		let mut state = state_result.reference_mut();
+		let a = Address::zero();
+		state.add_balance(&a, &U256::from(69u64), CleanupMode::NoEmpty).unwrap();
+		state.inc_nonce(&a).unwrap();
+		state.commit().unwrap();
+
+		let mut storage = BTreeMap::new();
+		storage.insert(H256::from(1u64), H256::from(2u64));
+		state.apply_override(&a, &AccountOverride {
+			balance: Some(42u64.into()),
+			nonce: Some(5u64.into()),
+			code: Some(vec![0x60, 0x00]),
+			storage: storage,
+		}).unwrap();
+		assert_eq!(state.balance(&a).unwrap(), U256::from(42u64));
+		assert_eq!(state.nonce(&a).unwrap(), U256::from(5u64));
+		assert_eq!(state.code(&a).unwrap(), Some(Arc::new(vec![0x60, 0x00])));
+		assert_eq!(state.storage_at(&a, &H256::from(1u64)).unwrap(), H256::from(2u64));
+
+		let b = 1u64.into();
+		state.apply_override(&b, &AccountOverride { balance: Some(7u64.into()), ..Default::default() }).unwrap();
+		assert_eq!(state.balance(&b).unwrap(), U256::from(7u64));
+	}
+
 	#[test]
 	fn balance_nonce() {
 		let mut state_result = get_temp_state();
@@ -2016,6 +2366,74 @@ mod tests {
 		assert_eq!(state.balance(&a).unwrap(), U256::from(0));
 	}
 
+	#[test]
+	fn savepoint_revert_by_label() {
+		let mut state_result = get_temp_state();
+		let mut state = state_result.reference_mut();
+		let a = Address::zero();
+		state.savepoint("outer");
+		state.add_balance(&a, &U256::from(69u64), CleanupMode::NoEmpty).unwrap();
+		state.savepoint("before-subcall");
+		state.checkpoint();
+		state.add_balance(&a, &U256::from(1u64), CleanupMode::NoEmpty).unwrap();
+		assert_eq!(state.balance(&a).unwrap(), U256::from(70u64));
+		assert!(state.revert_to_savepoint("before-subcall"));
+		assert_eq!(state.balance(&a).unwrap(), U256::from(69u64));
+		assert!(!state.revert_to_savepoint("before-subcall"));
+		assert!(state.discard_savepoint("outer"));
+		assert_eq!(state.balance(&a).unwrap(), U256::from(69u64));
+		assert!(!state.revert_to_savepoint("outer"));
+	}
+
+	#[test]
+	fn savepoint_dropped_with_checkpoint() {
+		let mut state_result = get_temp_state();
+		let mut state = state_result.reference_mut();
+		let a = Address::zero();
+		state.savepoint("label");
+		state.add_balance(&a, &U256::from(69u64), CleanupMode::NoEmpty).unwrap();
+		state.revert_to_checkpoint();
+		assert!(!state.revert_to_savepoint("label"));
+		assert_eq!(state.balance(&a).unwrap(), U256::from(0));
+	}
+
+	#[test]
+	fn fork_sees_uncommitted_changes() {
+		let mut state_result = get_temp_state();
+		let mut state = state_result.reference_mut();
+		let a = Address::zero();
+		let b: Address = 1.into();
+		state.add_balance(&a, &U256::from(69u64), CleanupMode::NoEmpty).unwrap();
+
+		let mut fork = state.fork();
+		assert_eq!(fork.balance(&a).unwrap(), U256::from(69u64));
+		fork.add_balance(&a, &U256::from(1u64), CleanupMode::NoEmpty).unwrap();
+		fork.add_balance(&b, &U256::from(5u64), CleanupMode::NoEmpty).unwrap();
+		assert_eq!(fork.balance(&a).unwrap(), U256::from(70u64));
+
+		// the original is unaffected by changes in the fork.
+		assert_eq!(state.balance(&a).unwrap(), U256::from(69u64));
+		assert_eq!(state.balance(&b).unwrap(), U256::from(0u64));
+
+		// forks of a fork carry both sets of changes.
+		let nested = fork.fork();
+		assert_eq!(nested.balance(&a).unwrap(), U256::from(70u64));
+		assert_eq!(nested.balance(&b).unwrap(), U256::from(5u64));
+	}
+
+	#[test]
+	fn fork_commits_inherited_changes() {
+		let mut state_result = get_temp_state();
+		let mut state = state_result.reference_mut();
+		let a = Address::zero();
+		state.add_balance(&a, &U256::from(69u64), CleanupMode::NoEmpty).unwrap();
+
+		let mut fork = state.fork();
+		fork.commit().unwrap();
+		state.commit().unwrap();
+		assert_eq!(fork.root(), state.root());
+	}
+
 	#[test]
 	fn create_empty() {
 		let mut state_result = get_temp_state();
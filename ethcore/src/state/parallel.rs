@@ -0,0 +1,162 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Speculative parallel execution of transactions.
+//!
+//! Each transaction of a batch is executed on its own fork of the state, so all of
+//! them see the state from before the batch. Forks record the accounts read during
+//! execution; the accounts written are the dirty entries left in the fork.
+//!
+//! Outcomes are merged back in block order. A transaction which read an account
+//! written by an earlier transaction of the batch may have seen stale data, so it
+//! has to be re-executed serially. Balance credits to accounts a transaction never
+//! read, like the fees paid to the block author, commute and are merged as deltas.
+
+use std::cmp;
+
+use crossbeam;
+use engines::Engine;
+use env_info::EnvInfo;
+use error::Error;
+use receipt::Receipt;
+use state_db::StateDB;
+use transaction::SignedTransaction;
+use types::executed::{Executed, ExecutionError};
+use util::*;
+
+use super::{State, AccountEntry, ApplyOutcome, CleanupMode, RequireCache};
+
+/// Outcome of executing a transaction on a fork of the state.
+pub struct Speculation {
+	executed: Executed,
+	reads: HashSet<Address>,
+	changes: HashMap<Address, AccountEntry>,
+}
+
+/// Balance, nonce and code hash of an account.
+type AccountSummary = (U256, U256, H256);
+
+/// Tracks the accounts written while merging a batch of speculations.
+#[derive(Default)]
+pub struct Merge {
+	// accounts written by the merged transactions with their summary from before the batch.
+	written: HashMap<Address, Option<AccountSummary>>,
+}
+
+impl State<StateDB> {
+	/// Execute each of `transactions` on its own fork of this state, on up to `threads` threads.
+	/// None of the transactions sees the effects of the others.
+	pub fn speculate(
+		&self,
+		env_info: &EnvInfo,
+		engine: &Engine,
+		transactions: &[SignedTransaction],
+		tracing: bool,
+		threads: usize,
+	) -> Vec<Result<Speculation, ExecutionError>> {
+		let threads = cmp::max(1, cmp::min(threads, transactions.len()));
+		let mut jobs: Vec<Vec<_>> = (0..threads).map(|_| Vec::new()).collect();
+		for (i, t) in transactions.iter().enumerate() {
+			let mut fork = self.fork();
+			fork.reads = Some(RefCell::new(HashSet::new()));
+			jobs[i % threads].push((i, fork, t));
+		}
+
+		let mut results: Vec<Option<Result<Speculation, ExecutionError>>> = transactions.iter().map(|_| None).collect();
+		crossbeam::scope(|scope| {
+			let handles: Vec<_> = jobs.into_iter().map(|job| scope.spawn(move || {
+				job.into_iter()
+					.map(|(i, fork, t)| (i, fork.speculate_one(env_info, engine, t, tracing)))
+					.collect::<Vec<_>>()
+			})).collect();
+
+			for handle in handles {
+				for (i, result) in handle.join() {
+					results[i] = Some(result);
+				}
+			}
+		});
+
+		results.into_iter().map(|r| r.expect("every transaction is dealt to a worker; qed")).collect()
+	}
+
+	fn speculate_one(mut self, env_info: &EnvInfo, engine: &Engine, t: &SignedTransaction, tracing: bool) -> Result<Speculation, ExecutionError> {
+		let executed = self.execute(env_info, engine, t, tracing)?;
+		let reads = self.reads.take().map_or_else(HashSet::new, RefCell::into_inner);
+		let changes = self.cache.into_inner().into_iter()
+			.filter(|&(_, ref entry)| entry.is_dirty())
+			.collect();
+
+		Ok(Speculation {
+			executed: executed,
+			reads: reads,
+			changes: changes,
+		})
+	}
+
+	/// Merge the outcome of the next transaction of a batch into this state, which must be the
+	/// state the batch was forked from with the earlier speculations of the batch merged.
+	/// Returns `None`, leaving the state untouched, if the transaction conflicts with an earlier
+	/// one or doesn't fit in the block; it then has to be executed with `apply`.
+	pub fn merge(&mut self, merge: &mut Merge, env_info: &EnvInfo, engine: &Engine, speculation: Speculation) -> Result<Option<ApplyOutcome>, Error> {
+		let Speculation { executed, reads, changes } = speculation;
+
+		if env_info.gas_used + executed.gas > env_info.gas_limit {
+			return Ok(None);
+		}
+		if reads.iter().any(|a| merge.written.contains_key(a)) {
+			trace!(target: "state", "Speculation read an account written earlier in the batch");
+			return Ok(None);
+		}
+
+		// accounts written earlier in the batch were only credited, check it's the same here.
+		let mut credits = Vec::new();
+		for (address, entry) in &changes {
+			if let Some(base) = merge.written.get(address) {
+				let credit = match (base, &entry.account) {
+					(&Some((ref balance, ref nonce, ref code_hash)), &Some(ref account))
+						if account.nonce() == nonce && account.code_hash() == *code_hash
+						&& account.storage_changes().is_empty() && account.balance() >= balance
+						=> *account.balance() - *balance,
+					_ => return Ok(None),
+				};
+				credits.push((address.clone(), credit));
+			}
+		}
+
+		for (address, entry) in changes {
+			if merge.written.contains_key(&address) { continue }
+			let base = self.ensure_cached(&address, RequireCache::None, true,
+				|a| a.map(|a| (*a.balance(), *a.nonce(), a.code_hash())))?;
+			merge.written.insert(address.clone(), base);
+			self.insert_cache(&address, entry);
+		}
+		for (address, credit) in credits {
+			self.add_balance(&address, &credit, CleanupMode::NoEmpty)?;
+		}
+
+		let cumulative_gas_used = env_info.gas_used + executed.gas_used;
+		let state_root = if env_info.number < engine.params().eip98_transition {
+			self.commit()?;
+			Some(self.root().clone())
+		} else {
+			None
+		};
+		let receipt = Receipt::new(state_root, cumulative_gas_used, executed.logs);
+		trace!(target: "state", "Transaction receipt: {:?}", receipt);
+		Ok(Some(ApplyOutcome { receipt: receipt, trace: executed.trace }))
+	}
+}
@@ -14,18 +14,23 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::{VecDeque, HashSet};
+use std::collections::{VecDeque, HashSet, HashMap};
 use lru_cache::LruCache;
 use util::cache::MemoryLruCache;
-use util::journaldb::JournalDB;
+use util::journaldb::{JournalDB, CompactionReport};
 use util::kvdb::KeyValueDB;
 use util::hash::{H256};
 use util::hashdb::HashDB;
 use state::{self, Account};
 use header::BlockNumber;
-use util::{Arc, Address, DBTransaction, UtilError, Mutex, Hashable};
+use util::{Arc, Address, Bytes, DBTransaction, UtilError, Mutex, Hashable};
+use util::{Trie, TrieDB, TrieDBMut, TrieError, SHA3_NULL_RLP};
+use util::trie;
+use rlp::{self, RlpStream, UntrustedRlp};
+use account_db::{AccountDB, AccountDBMut};
+use types::basic_account::BasicAccount;
 use bloom_journal::{Bloom, BloomJournal};
-use db::COL_ACCOUNT_BLOOM;
+use db::{COL_ACCOUNT_BLOOM, COL_PREIMAGES, COL_EXTRA};
 use byteorder::{LittleEndian, ByteOrder};
 
 pub const ACCOUNT_BLOOM_SPACE: usize = 1048576;
@@ -38,12 +43,27 @@ const STATE_CACHE_BLOCKS: usize = 12;
 // The percentage of supplied cache size to go to accounts.
 const ACCOUNT_CACHE_RATIO: usize = 90;
 
-/// Shared canonical state cache.
+// Prefix of the keys under which the storage tries of accounts killed in the blocks
+// of an era are kept until one of them is marked canonical.
+const KILLED_STORAGE_PREFIX: &'static [u8] = b"killed_storage";
+
+// Key of the queue of storage tries of accounts killed in canonical blocks.
+const EXPIRED_STORAGE_KEY: &'static [u8] = b"expired_storage";
+
+/// Shared state cache.
+///
+/// Two levels: the canonical cache reflects the state of the best block, while
+/// the branch cache holds accounts as of recently committed non-canonical blocks.
+/// When a branch becomes canonical its entries are promoted into the canonical
+/// cache, so a reorg only invalidates the accounts touched by the retracted blocks.
 struct AccountCache {
 	/// DB Account cache. `None` indicates that account is known to be missing.
 	// When changing the type of the values here, be sure to update `mem_used` and
 	// `new`.
 	accounts: LruCache<Address, Option<Account>>,
+	/// Accounts as of the given non-canonical block. Only kept for blocks present
+	/// in `modifications`.
+	branches: HashMap<H256, HashMap<Address, Option<Account>>>,
 	/// Information on the modifications in recently committed blocks; specifically which addresses
 	/// changed in which block. Ordered by block number.
 	modifications: VecDeque<BlockChanges>,
@@ -108,6 +128,12 @@ pub struct StateDB {
 	commit_hash: Option<H256>,
 	/// Number of the committing block or `None` if not committed yet.
 	commit_number: Option<BlockNumber>,
+	/// Whether to record the preimages of secure trie keys.
+	record_preimages: bool,
+	/// Preimages noted since the last commit, by hash.
+	preimages: HashMap<H256, Bytes>,
+	/// Storage tries of accounts killed since the last commit: address hash and storage root.
+	killed_storage: Vec<(H256, H256)>,
 }
 
 impl StateDB {
@@ -126,6 +152,7 @@ impl StateDB {
 			db: db,
 			account_cache: Arc::new(Mutex::new(AccountCache {
 				accounts: LruCache::new(cache_items),
+				branches: HashMap::new(),
 				modifications: VecDeque::new(),
 			})),
 			code_cache: Arc::new(Mutex::new(MemoryLruCache::new(code_cache_size))),
@@ -135,6 +162,9 @@ impl StateDB {
 			parent_hash: None,
 			commit_hash: None,
 			commit_number: None,
+			record_preimages: false,
+			preimages: HashMap::new(),
+			killed_storage: Vec::new(),
 		}
 	}
 
@@ -180,12 +210,32 @@ impl StateDB {
 		Ok(())
 	}
 
+	/// Reload the account bloom from the backing database, picking up accounts
+	/// written there directly, e.g. by a state import.
+	pub fn reload_bloom(&self) {
+		*self.account_bloom.lock() = Self::load_bloom(&**self.db.backing());
+	}
+
+	/// Set whether the preimages of secure trie keys written through this instance
+	/// and its clones are recorded in the database.
+	pub fn set_record_preimages(&mut self, record: bool) {
+		self.record_preimages = record;
+	}
+
 	/// Journal all recent operations under the given era and ID.
 	pub fn journal_under(&mut self, batch: &mut DBTransaction, now: u64, id: &H256) -> Result<u32, UtilError> {
 		{
  			let mut bloom_lock = self.account_bloom.lock();
  			Self::commit_bloom(batch, bloom_lock.drain_journal())?;
  		}
+		// preimages never change, so they don't need journalling.
+		for (hash, preimage) in self.preimages.drain() {
+			batch.put(COL_PREIMAGES, &hash, &preimage);
+		}
+		if !self.killed_storage.is_empty() {
+			let killed = ::std::mem::replace(&mut self.killed_storage, Vec::new());
+			self.journal_killed_storage(batch, now, id, killed)?;
+		}
 		let records = self.db.journal_under(batch, now, id)?;
 		self.commit_hash = Some(id.clone());
 		self.commit_number = Some(now);
@@ -195,9 +245,159 @@ impl StateDB {
 	/// Mark a given candidate from an ancient era as canonical, enacting its removals from the
 	/// backing database and reverting any non-canonical historical commit's insertions.
 	pub fn mark_canonical(&mut self, batch: &mut DBTransaction, end_era: u64, canon_id: &H256) -> Result<u32, UtilError> {
+		self.expire_killed_storage(batch, end_era, canon_id)?;
 		self.db.mark_canonical(batch, end_era, canon_id)
 	}
 
+	// record the storage tries killed in block `id` of era `now`, alongside those of other
+	// blocks of the same era.
+	fn journal_killed_storage(&self, batch: &mut DBTransaction, now: u64, id: &H256, killed: Vec<(H256, H256)>) -> Result<(), UtilError> {
+		let key = killed_storage_key(now);
+		let mut blocks = match self.db.backing().get(COL_EXTRA, &key)? {
+			Some(raw) => decode_killed_blocks(&raw)?,
+			None => Vec::new(),
+		};
+		trace!(target: "state_db", "Journalling {} killed storage tries of block {}", killed.len(), id);
+		blocks.retain(|&(ref hash, _)| hash != id);
+		blocks.push((id.clone(), killed));
+
+		let mut stream = RlpStream::new_list(blocks.len());
+		for &(ref hash, ref tries) in &blocks {
+			stream.begin_list(2).append(hash);
+			append_storage_tries(&mut stream, tries);
+		}
+		batch.put(COL_EXTRA, &key, &stream.out());
+		Ok(())
+	}
+
+	// queue the storage tries killed in the canonical block of `era` for removal, and forget
+	// those killed in the competing blocks.
+	fn expire_killed_storage(&self, batch: &mut DBTransaction, era: u64, canon_id: &H256) -> Result<(), UtilError> {
+		let key = killed_storage_key(era);
+		let blocks = match self.db.backing().get(COL_EXTRA, &key)? {
+			Some(raw) => decode_killed_blocks(&raw)?,
+			None => return Ok(()),
+		};
+		batch.delete(COL_EXTRA, &key);
+
+		if let Some((_, killed)) = blocks.into_iter().find(|&(ref hash, _)| hash == canon_id) {
+			debug!(target: "state_db", "Queueing {} storage tries killed in block #{} for removal", killed.len(), era);
+			let mut queue = self.expired_storage()?;
+			queue.extend(killed);
+			write_expired_storage(batch, &queue);
+		}
+		Ok(())
+	}
+
+	// storage tries of accounts killed in canonical blocks which are yet to be removed.
+	fn expired_storage(&self) -> Result<Vec<(H256, H256)>, UtilError> {
+		match self.db.backing().get(COL_EXTRA, EXPIRED_STORAGE_KEY)? {
+			Some(raw) => Ok(decode_storage_tries(&UntrustedRlp::new(&raw))?),
+			None => Ok(Vec::new()),
+		}
+	}
+
+	/// Remove up to `limit` storage items of accounts killed in canonical blocks through the
+	/// journal database, writing the removals and the remaining queue to `batch`. Tries of accounts
+	/// which exist again with storage in the state at `best_root` are left in place, since the
+	/// mangled keys of their nodes may coincide. A kill is only queued once its era is canonical,
+	/// but blocks of later eras on other branches may have re-created the account as well, so tries
+	/// of accounts existing in any of the journalled states given by `journalled_roots` are kept
+	/// queued until those branches are gone. Returns the number of removed items.
+	pub fn expire_storage(&mut self, batch: &mut DBTransaction, best_root: &H256, journalled_roots: &Fn() -> Vec<H256>, limit: usize) -> Result<usize, UtilError> {
+		let mut queue = self.expired_storage()?;
+		if queue.is_empty() {
+			return Ok(0);
+		}
+		let journalled_roots = journalled_roots();
+
+		// the same trie may be queued twice when an account was killed twice; removing
+		// it twice in one pass would decrement the references of its nodes twice.
+		let mut seen = HashSet::new();
+		queue.retain(|entry| seen.insert(entry.clone()));
+
+		let mut removed = 0;
+		let mut deferred = Vec::new();
+		while removed < limit {
+			let (address_hash, storage_root) = match queue.pop() {
+				Some(entry) => entry,
+				None => break,
+			};
+
+			match self.has_storage(best_root, &address_hash) {
+				Ok(false) => {},
+				Ok(true) => {
+					debug!(target: "state_db", "Leaving storage trie {} of killed account {} in place: account exists again", storage_root, address_hash);
+					continue;
+				}
+				Err(e) => {
+					debug!(target: "state_db", "Leaving storage trie {} of killed account {} in place: {}", storage_root, address_hash, e);
+					continue;
+				}
+			}
+			if journalled_roots.iter().any(|root| self.has_storage(root, &address_hash).unwrap_or(false)) {
+				trace!(target: "state_db", "Deferring removal of storage trie {} of killed account {}", storage_root, address_hash);
+				deferred.push((address_hash, storage_root));
+				continue;
+			}
+
+			match self.remove_storage(address_hash, storage_root, limit - removed) {
+				Ok((count, root)) => {
+					removed += count;
+					if root != SHA3_NULL_RLP {
+						queue.push((address_hash, root));
+					}
+				}
+				Err(e) => debug!(target: "state_db", "Leaving storage trie {} of killed account {} in place: {}", storage_root, address_hash, e),
+			}
+		}
+		queue.extend(deferred);
+
+		self.db.inject(batch)?;
+		write_expired_storage(batch, &queue);
+		trace!(target: "state_db", "Removed {} storage items of killed accounts, {} tries left", removed, queue.len());
+		Ok(removed)
+	}
+
+	// whether the account exists with storage in the state at `root`.
+	fn has_storage(&self, root: &H256, address_hash: &H256) -> Result<bool, String> {
+		let state = TrieDB::new(self.db.as_hashdb(), root).map_err(trie_error)?;
+		match state.get(address_hash).map_err(trie_error)? {
+			Some(raw) => Ok(rlp::decode::<BasicAccount>(&raw).storage_root != SHA3_NULL_RLP),
+			None => Ok(false),
+		}
+	}
+
+	// remove up to `limit` items of a killed account's storage trie, returning their number and
+	// the root of the remaining trie.
+	fn remove_storage(&mut self, address_hash: H256, mut storage_root: H256, limit: usize) -> Result<(usize, H256), String> {
+		let keys = {
+			let account_db = AccountDB::from_hash(self.db.as_hashdb(), address_hash);
+			let trie = TrieDB::new(&account_db, &storage_root).map_err(trie_error)?;
+			let keys = trie.iter().map_err(trie_error)?
+				.take(limit)
+				.map(|item| item.map(|(key, _)| key))
+				.collect::<trie::Result<Vec<_>>>()
+				.map_err(trie_error)?;
+			keys
+		};
+
+		{
+			let mut account_db = AccountDBMut::from_hash(self.db.as_hashdb_mut(), address_hash);
+			let mut trie = TrieDBMut::from_existing(&mut account_db, &mut storage_root).map_err(trie_error)?;
+			for key in &keys {
+				trie.remove(key).map_err(trie_error)?;
+			}
+		}
+		Ok((keys.len(), storage_root))
+	}
+
+	/// Apply and remove on-disk journal records of ancient eras which were never marked
+	/// canonical. See `JournalDB::compact`.
+	pub fn compact_journal(&mut self, batch: &mut DBTransaction, up_to: u64, limit: usize, canonical: &Fn(u64) -> Option<H256>) -> Result<CompactionReport, UtilError> {
+		self.db.compact(batch, up_to, limit, canonical)
+	}
+
 	/// Propagate local cache into the global cache and synchonize
 	/// the global cache with the best block state.
 	/// This function updates the global cache by removing entries
@@ -209,16 +409,16 @@ impl StateDB {
 		let mut cache = self.account_cache.lock();
 		let mut cache = &mut *cache;
 
-		// Purge changes from re-enacted and retracted blocks.
+		// Purge changes from retracted and re-enacted blocks.
 		// Filter out commiting block if any.
 		let mut clear = false;
-		for block in enacted.iter().filter(|h| self.commit_hash.as_ref().map_or(true, |p| *h != p)) {
+		for block in retracted {
 			clear = clear || {
 				if let Some(ref mut m) = cache.modifications.iter_mut().find(|m| &m.hash == block) {
-					trace!("Reverting enacted block {:?}", block);
-					m.is_canon = true;
+					trace!("Retracting block {:?}", block);
+					m.is_canon = false;
 					for a in &m.accounts {
-						trace!("Reverting enacted address {:?}", a);
+						trace!("Retracted address {:?}", a);
 						cache.accounts.remove(a);
 					}
 					false
@@ -228,14 +428,25 @@ impl StateDB {
 			};
 		}
 
-		for block in retracted {
+		// Enacted blocks are ordered from the oldest, so later blocks override
+		// the promoted entries of earlier ones.
+		for block in enacted.iter().filter(|h| self.commit_hash.as_ref().map_or(true, |p| *h != p)) {
 			clear = clear || {
 				if let Some(ref mut m) = cache.modifications.iter_mut().find(|m| &m.hash == block) {
-					trace!("Retracting block {:?}", block);
-					m.is_canon = false;
+					trace!("Reverting enacted block {:?}", block);
+					m.is_canon = true;
+					let mut branch = cache.branches.remove(block).unwrap_or_else(HashMap::new);
 					for a in &m.accounts {
-						trace!("Retracted address {:?}", a);
-						cache.accounts.remove(a);
+						match branch.remove(a) {
+							Some(account) => {
+								trace!("Promoting enacted address {:?}", a);
+								cache.accounts.insert(a.clone(), account);
+							}
+							None => {
+								trace!("Reverting enacted address {:?}", a);
+								cache.accounts.remove(a);
+							}
+						}
 					}
 					false
 				} else {
@@ -247,6 +458,7 @@ impl StateDB {
 			// We don't know anything about the block; clear everything
 			trace!("Wiping cache");
 			cache.accounts.clear();
+			cache.branches.clear();
 			cache.modifications.clear();
 		}
 
@@ -255,15 +467,20 @@ impl StateDB {
 		// (contributed to canonical state cache)
 		if let (Some(ref number), Some(ref hash), Some(ref parent)) = (self.commit_number, self.commit_hash, self.parent_hash) {
 			if cache.modifications.len() == STATE_CACHE_BLOCKS {
-				cache.modifications.pop_back();
+				if let Some(dropped) = cache.modifications.pop_back() {
+					cache.branches.remove(&dropped.hash);
+				}
 			}
 			let mut modifications = HashSet::new();
+			let mut branch = HashMap::new();
 			trace!("committing {} cache entries", self.local_cache.len());
 			for account in self.local_cache.drain(..) {
 				if account.modified {
 					modifications.insert(account.address.clone());
 				}
-				if is_best {
+				if !is_best {
+					branch.insert(account.address, account.account);
+				} else {
 					if let Some(&mut Some(ref mut existing)) = cache.accounts.get_mut(&account.address) {
 						if let Some(new) = account.account {
 							if account.modified {
@@ -276,6 +493,10 @@ impl StateDB {
 				}
 			}
 
+			if !branch.is_empty() {
+				cache.branches.insert(hash.clone(), branch);
+			}
+
 			// Save modified accounts. These are ordered by the block number.
 			let block_changes = BlockChanges {
 				accounts: modifications,
@@ -314,6 +535,9 @@ impl StateDB {
 			parent_hash: None,
 			commit_hash: None,
 			commit_number: None,
+			record_preimages: self.record_preimages,
+			preimages: HashMap::new(),
+			killed_storage: Vec::new(),
 		}
 	}
 
@@ -329,6 +553,9 @@ impl StateDB {
 			parent_hash: Some(parent.clone()),
 			commit_hash: None,
 			commit_number: None,
+			record_preimages: self.record_preimages,
+			preimages: HashMap::new(),
+			killed_storage: Vec::new(),
 		}
 	}
 
@@ -341,7 +568,10 @@ impl StateDB {
 	pub fn mem_used(&self) -> usize {
 		// TODO: account for LRU-cache overhead; this is a close approximation.
 		self.db.mem_used() + {
-			let accounts = self.account_cache.lock().accounts.len();
+			let accounts = {
+				let cache = self.account_cache.lock();
+				cache.accounts.len() + cache.branches.values().map(HashMap::len).sum::<usize>()
+			};
 			let code_size = self.code_cache.lock().current_size();
 			code_size + accounts * ::std::mem::size_of::<Option<Account>>()
 		}
@@ -390,6 +620,40 @@ impl StateDB {
 		trace!("Cache lookup skipped for {:?}: parent hash is unknown", addr);
 		false
 	}
+
+	/// Look the account up in the branch cache of the non-canonical blocks between the
+	/// current block parent and the canonical chain. Returns `None` if the account
+	/// is not cached for the most recent of those blocks to touch it.
+	fn branch_lookup<'a>(addr: &Address, parent_hash: &Option<H256>, cache: &'a mut AccountCache) -> Option<&'a mut Option<Account>> {
+		let mut parent = match *parent_hash {
+			Some(ref parent) => parent.clone(),
+			None => return None,
+		};
+		let mut found = None;
+		for m in &cache.modifications {
+			if m.hash != parent {
+				continue;
+			}
+			if m.is_canon {
+				break;
+			}
+			if cache.branches.get(&m.hash).map_or(false, |b| b.contains_key(addr)) {
+				found = Some(m.hash.clone());
+				break;
+			}
+			if m.accounts.contains(addr) {
+				break;
+			}
+			parent = m.parent.clone();
+		}
+		match found {
+			Some(hash) => {
+				trace!("Branch cache hit for {:?} at {:?}", addr, hash);
+				cache.branches.get_mut(&hash).and_then(|b| b.get_mut(addr))
+			}
+			None => None,
+		}
+	}
 }
 
 impl state::Backend for StateDB {
@@ -418,7 +682,7 @@ impl state::Backend for StateDB {
 	fn get_cached_account(&self, addr: &Address) -> Option<Option<Account>> {
 		let mut cache = self.account_cache.lock();
 		if !Self::is_allowed(addr, &self.parent_hash, &cache.modifications) {
-			return None;
+			return Self::branch_lookup(addr, &self.parent_hash, &mut cache).map(|a| a.as_ref().map(|a| a.clone_basic()));
 		}
 		cache.accounts.get_mut(addr).map(|a| a.as_ref().map(|a| a.clone_basic()))
 	}
@@ -434,7 +698,7 @@ impl state::Backend for StateDB {
 		where F: FnOnce(Option<&mut Account>) -> U {
 		let mut cache = self.account_cache.lock();
 		if !Self::is_allowed(a, &self.parent_hash, &cache.modifications) {
-			return None;
+			return Self::branch_lookup(a, &self.parent_hash, &mut cache).map(|c| f(c.as_mut()));
 		}
 		cache.accounts.get_mut(a).map(|c| f(c.as_mut()))
 	}
@@ -450,13 +714,70 @@ impl state::Backend for StateDB {
 		let bloom = self.account_bloom.lock();
 		!bloom.check(&*address.sha3())
 	}
+
+	fn note_key_preimage(&mut self, preimage: &[u8]) {
+		if self.record_preimages {
+			self.preimages.insert(preimage.sha3(), preimage.to_vec());
+		}
+	}
+
+	fn note_killed_storage(&mut self, address_hash: H256, storage_root: H256) {
+		// an archive keeps the storage of killed accounts along with the rest of history.
+		if self.db.is_pruned() {
+			self.killed_storage.push((address_hash, storage_root));
+		}
+	}
+}
+
+fn killed_storage_key(era: u64) -> Vec<u8> {
+	let mut era_bytes = [0u8; 8];
+	LittleEndian::write_u64(&mut era_bytes, era);
+	let mut key = KILLED_STORAGE_PREFIX.to_vec();
+	key.extend_from_slice(&era_bytes);
+	key
+}
+
+fn trie_error(e: Box<TrieError>) -> String {
+	format!("{}", e)
+}
+
+fn append_storage_tries(stream: &mut RlpStream, tries: &[(H256, H256)]) {
+	stream.begin_list(tries.len());
+	for &(ref address_hash, ref storage_root) in tries {
+		stream.begin_list(2).append(address_hash).append(storage_root);
+	}
+}
+
+fn decode_storage_tries(rlp: &UntrustedRlp) -> Result<Vec<(H256, H256)>, rlp::DecoderError> {
+	rlp.iter()
+		.map(|entry| -> Result<_, rlp::DecoderError> { Ok((entry.val_at(0)?, entry.val_at(1)?)) })
+		.collect()
+}
+
+fn decode_killed_blocks(raw: &[u8]) -> Result<Vec<(H256, Vec<(H256, H256)>)>, rlp::DecoderError> {
+	UntrustedRlp::new(raw).iter()
+		.map(|block| -> Result<_, rlp::DecoderError> { Ok((block.val_at(0)?, decode_storage_tries(&block.at(1)?)?)) })
+		.collect()
+}
+
+fn write_expired_storage(batch: &mut DBTransaction, queue: &[(H256, H256)]) {
+	if queue.is_empty() {
+		batch.delete(COL_EXTRA, EXPIRED_STORAGE_KEY);
+	} else {
+		let mut stream = RlpStream::new();
+		append_storage_tries(&mut stream, queue);
+		batch.put(COL_EXTRA, EXPIRED_STORAGE_KEY, &stream.out());
+	}
 }
 
 #[cfg(test)]
 mod tests {
-	use util::{U256, H256, Address, DBTransaction};
+	use util::{U256, H256, Address, DBTransaction, Hashable, HashDB};
 	use tests::helpers::*;
-	use state::{Account, Backend};
+	use state::{Account, Backend, State, CleanupMode};
+	use account_db::AccountDB;
+	use db::COL_PREIMAGES;
+	use super::StateDB;
 	use ethcore_logger::init_log;
 
 	#[test]
@@ -526,4 +847,133 @@ mod tests {
 		let s = state_db.boxed_clone_canon(&h3a);
 		assert!(s.get_cached_account(&address).is_none());
 	}
+
+	#[test]
+	fn branch_cache_survives_reorg() {
+		init_log();
+
+		let mut state_db_result = get_temp_state_db();
+		let state_db = state_db_result.take();
+		let root_parent = H256::random();
+		let address = Address::random();
+		let h0 = H256::random();
+		let h1a = H256::random();
+		let h1b = H256::random();
+		let h2b = H256::random();
+		let mut batch = DBTransaction::new();
+
+		// blocks  [ 2b 1b 1a(c) 0(c) ]
+		// balance [ 3  3  2     2    ]
+		let mut s = state_db.boxed_clone_canon(&root_parent);
+		s.add_to_account_cache(address, Some(Account::new_basic(2.into(), 0.into())), true);
+		s.journal_under(&mut batch, 0, &h0).unwrap();
+		s.sync_cache(&[], &[], true);
+
+		let mut s = state_db.boxed_clone_canon(&h0);
+		s.journal_under(&mut batch, 1, &h1a).unwrap();
+		s.sync_cache(&[], &[], true);
+
+		let mut s = state_db.boxed_clone_canon(&h0);
+		s.add_to_account_cache(address, Some(Account::new_basic(3.into(), 0.into())), true);
+		s.journal_under(&mut batch, 1, &h1b).unwrap();
+		s.sync_cache(&[], &[], false);
+
+		// the side branch is served from the branch cache.
+		let s = state_db.boxed_clone_canon(&h1b);
+		assert_eq!(s.get_cached_account(&address).unwrap().unwrap().balance(), &U256::from(3));
+		let s = state_db.boxed_clone_canon(&h1a);
+		assert_eq!(s.get_cached_account(&address).unwrap().unwrap().balance(), &U256::from(2));
+
+		// reorg to 2b: the entry from 1b is promoted to the canonical cache.
+		let mut s = state_db.boxed_clone_canon(&h1b);
+		s.journal_under(&mut batch, 2, &h2b).unwrap();
+		s.sync_cache(&[h1b.clone(), h2b.clone()], &[h1a.clone()], true);
+
+		let s = state_db.boxed_clone_canon(&h2b);
+		assert_eq!(s.get_cached_account(&address).unwrap().unwrap().balance(), &U256::from(3));
+		let s = state_db.boxed_clone_canon(&h1a);
+		assert!(s.get_cached_account(&address).is_none());
+	}
+
+	#[test]
+	fn records_key_preimages() {
+		let mut state_db_result = get_temp_state_db();
+		let mut state_db = state_db_result.take();
+		state_db.set_record_preimages(true);
+
+		let address = Address::from(1);
+		let key = H256::from(2);
+		let mut state = State::new(state_db, U256::zero(), Default::default());
+		state.add_balance(&address, &U256::from(1), CleanupMode::NoEmpty).unwrap();
+		state.set_storage(&address, key, H256::from(3)).unwrap();
+		state.commit().unwrap();
+
+		let (_, mut state_db) = state.drop();
+		let mut batch = DBTransaction::new();
+		state_db.journal_under(&mut batch, 0, &H256::random()).unwrap();
+		let backing = state_db.journal_db().backing().clone();
+		backing.write(batch).unwrap();
+
+		let preimage = |hash: H256| backing.get(COL_PREIMAGES, &hash).unwrap().map(|v| v.to_vec());
+		assert_eq!(preimage(address.sha3()), Some(address.to_vec()));
+		assert_eq!(preimage(key.sha3()), Some(key.to_vec()));
+		assert_eq!(preimage(H256::from(4).sha3()), None);
+	}
+
+	#[test]
+	fn removes_storage_of_killed_accounts_once_canonical() {
+		let mut state_db_result = get_temp_state_db();
+		let mut state_db = state_db_result.take();
+		let backing = state_db.journal_db().backing().clone();
+		let address = Address::from(1);
+		let (h0, h1a, h1b) = (H256::from(10), H256::from(11), H256::from(12));
+
+		let mut state = State::new(state_db.boxed_clone(), U256::zero(), Default::default());
+		state.set_storage(&address, H256::from(1), H256::from(2)).unwrap();
+		state.commit().unwrap();
+		let storage_root = state.storage_root(&address).unwrap().unwrap();
+		let (root0, mut s) = state.drop();
+		let mut batch = DBTransaction::new();
+		s.journal_under(&mut batch, 0, &h0).unwrap();
+		backing.write(batch).unwrap();
+
+		// the account is killed in 1a, but not in the competing 1b.
+		let mut state = State::from_existing(state_db.boxed_clone(), root0, U256::zero(), Default::default()).unwrap();
+		state.kill_account(&address);
+		state.commit().unwrap();
+		let (root1, mut s) = state.drop();
+		let mut batch = DBTransaction::new();
+		s.journal_under(&mut batch, 1, &h1a).unwrap();
+		backing.write(batch).unwrap();
+
+		let mut batch = DBTransaction::new();
+		state_db.boxed_clone().journal_under(&mut batch, 1, &h1b).unwrap();
+		backing.write(batch).unwrap();
+
+		let contains_storage = |db: &StateDB| AccountDB::from_hash(db.as_hashdb(), address.sha3()).contains(&storage_root);
+		let mut batch = DBTransaction::new();
+		assert_eq!(state_db.expire_storage(&mut batch, &root1, &|| Vec::new(), 100).unwrap(), 0);
+		backing.write(batch).unwrap();
+		assert!(contains_storage(&state_db));
+
+		let mut batch = DBTransaction::new();
+		state_db.mark_canonical(&mut batch, 0, &h0).unwrap();
+		state_db.mark_canonical(&mut batch, 1, &h1a).unwrap();
+		backing.write(batch).unwrap();
+		assert!(contains_storage(&state_db));
+
+		// kept queued while any journalled state, not only the best one, holds the account's storage.
+		let mut batch = DBTransaction::new();
+		assert_eq!(state_db.expire_storage(&mut batch, &root1, &|| vec![root0], 100).unwrap(), 0);
+		backing.write(batch).unwrap();
+		assert!(contains_storage(&state_db));
+
+		let mut batch = DBTransaction::new();
+		assert_eq!(state_db.expire_storage(&mut batch, &root1, &|| Vec::new(), 100).unwrap(), 1);
+		backing.write(batch).unwrap();
+		assert!(!contains_storage(&state_db));
+
+		let mut batch = DBTransaction::new();
+		assert_eq!(state_db.expire_storage(&mut batch, &root1, &|| Vec::new(), 100).unwrap(), 0);
+	}
 }
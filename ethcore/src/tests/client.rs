@@ -15,7 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use io::IoChannel;
-use client::{BlockChainClient, MiningBlockChainClient, Client, ClientConfig, BlockId};
+use client::{BlockChainClient, MiningBlockChainClient, ProvingBlockChainClient, Client, ClientConfig, BlockId};
 use state::{self, State, CleanupMode};
 use executive::Executive;
 use ethereum;
@@ -105,6 +105,29 @@ fn imports_good_block() {
 	assert!(!block.into_inner().is_empty());
 }
 
+#[test]
+fn imports_good_block_with_prefetch() {
+	let dir = RandomTempPath::new();
+	let spec = get_test_spec();
+	let db_config = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+	let client_db = Arc::new(Database::open(&db_config, dir.as_path().to_str().unwrap()).unwrap());
+
+	let mut config = ClientConfig::default();
+	config.prefetch_threads = 2;
+	let client = Client::new(
+		config,
+		&spec,
+		client_db,
+		Arc::new(Miner::with_spec(&spec)),
+		IoChannel::disconnected(),
+	).unwrap();
+	client.import_block(get_good_dummy_block()).unwrap();
+	client.flush_queue();
+	client.import_verified_blocks();
+
+	assert!(client.block_header(BlockId::Number(1)).is_some());
+}
+
 #[test]
 fn query_none_block() {
 	let dir = RandomTempPath::new();
@@ -172,6 +195,22 @@ fn returns_logs_with_limit() {
 	assert_eq!(logs.len(), 0);
 }
 
+#[test]
+fn returns_logs_page() {
+	let dummy_block = get_good_dummy_block();
+	let client_result = get_test_client_with_blocks(vec![dummy_block.clone()]);
+	let client = client_result.reference();
+	let (logs, next_block) = client.logs_page(Filter {
+		from_block: BlockId::Earliest,
+		to_block: BlockId::Latest,
+		address: None,
+		topics: vec![],
+		limit: None,
+	}, 1);
+	assert_eq!(logs.len(), 0);
+	assert_eq!(next_block, None);
+}
+
 #[test]
 fn returns_block_body() {
 	let dummy_block = get_good_dummy_block();
@@ -202,6 +241,24 @@ fn can_collect_garbage() {
 	assert!(client.blockchain_cache_info().blocks < 100 * 1024);
 }
 
+#[test]
+fn verifies_healthy_database() {
+	let client_result = generate_dummy_client_with_data(4, 2, slice_into![1, 2, 3]);
+	let client = client_result.reference();
+	let best = client.chain_info().best_block_number;
+	assert_eq!(client.verify_database(0, best, |_| {}), Ok(()));
+}
+
+#[test]
+fn collects_trie_stats() {
+	let client_result = generate_dummy_client_with_data(4, 2, slice_into![1, 2, 3]);
+	let client = client_result.reference();
+	let (accounts, storage) = client.trie_stats(BlockId::Latest).unwrap();
+	assert_eq!(accounts.tries, 1);
+	assert!(accounts.leaves > 0);
+	assert_eq!(accounts.embedded_nodes + accounts.stored_nodes, accounts.nodes());
+	assert_eq!(storage.embedded_nodes + storage.stored_nodes, storage.nodes());
+}
 
 #[test]
 fn can_generate_gas_price_median() {
@@ -214,6 +271,25 @@ fn can_generate_gas_price_median() {
 	assert_eq!(Some(&U256::from(3)), client.gas_price_corpus(3).median());
 }
 
+#[test]
+fn can_generate_fee_history() {
+	let client_result = generate_dummy_client_with_data(4, 1, slice_into![1, 4, 3, 2]);
+	let client = client_result.reference();
+
+	let history = client.fee_history(3, BlockId::Latest, &[50.0]).unwrap();
+	assert_eq!(history.oldest_block, 2);
+	assert_eq!(history.gas_prices, vec![vec![U256::from(4)], vec![U256::from(3)], vec![U256::from(2)]]);
+	assert_eq!(history.gas_used_ratio.len(), 3);
+	assert!(history.gas_used_ratio.iter().all(|ratio| *ratio > 0.0 && *ratio < 1.0));
+
+	// stops at the genesis, which has no transactions.
+	let history = client.fee_history(10, BlockId::Latest, &[0.0, 100.0]).unwrap();
+	assert_eq!(history.oldest_block, 0);
+	assert_eq!(history.gas_prices.len(), 5);
+	assert_eq!(history.gas_prices[0], vec![U256::zero(), U256::zero()]);
+	assert!(client.fee_history(3, BlockId::Number(10), &[50.0]).is_none());
+}
+
 #[test]
 fn can_generate_gas_price_histogram() {
 	let client_result = generate_dummy_client_with_data(20, 1, slice_into![6354,8593,6065,4842,7845,7002,689,4958,4250,6098,5804,4320,643,8895,2296,8589,7145,2000,2512,1408]);
@@ -309,6 +385,42 @@ fn change_history_size() {
 	assert_eq!(client.state().balance(&address).unwrap(), 100.into());
 }
 
+#[test]
+fn proves_state_of_journalled_blocks() {
+	let dir = RandomTempPath::new();
+	let test_spec = Spec::new_null();
+	let db_config = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+	let client_db = Arc::new(Database::open(&db_config, dir.as_path().to_str().unwrap()).unwrap());
+
+	// the journal is kept well beyond the minimum history while under the memory target.
+	let mut config = ClientConfig::default();
+	config.history = 2;
+	config.history_mem = usize::max_value();
+	let client = Client::new(
+		config,
+		&test_spec,
+		client_db,
+		Arc::new(Miner::with_spec(&test_spec)),
+		IoChannel::disconnected(),
+	).unwrap();
+
+	let address = Address::random();
+	for _ in 0..10 {
+		let mut b = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![]);
+		b.block_mut().fields_mut().state.add_balance(&address, &5.into(), CleanupMode::NoEmpty).unwrap();
+		b.block_mut().fields_mut().state.commit().unwrap();
+		let b = b.close_and_lock().seal(&*test_spec.engine, vec![]).unwrap();
+		client.import_sealed_block(b).unwrap();
+	}
+
+	let (proof, account) = client.prove_account(address.sha3(), BlockId::Number(1)).unwrap();
+	assert_eq!(account.balance, 5.into());
+	assert!(!proof.is_empty());
+
+	let (_, account) = client.prove_account(address.sha3(), BlockId::Latest).unwrap();
+	assert_eq!(account.balance, 50.into());
+}
+
 #[test]
 fn does_not_propagate_delayed_transactions() {
 	let key = KeyPair::from_secret(Secret::from_slice(&"test".sha3()).unwrap()).unwrap();
@@ -381,3 +493,176 @@ fn transaction_proof() {
 	assert_eq!(state.balance(&Address::default()).unwrap(), 5.into());
 	assert_eq!(state.balance(&address).unwrap(), 95.into());
 }
+
+#[test]
+fn execution_with_proof() {
+	use ::client::ProvingBlockChainClient;
+
+	let client_result = generate_dummy_client(0);
+	let client = client_result.reference();
+	let address = Address::random();
+	let test_spec = Spec::new_test();
+	let mut b = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![]);
+	b.block_mut().fields_mut().state.add_balance(&address, &5.into(), CleanupMode::NoEmpty).unwrap();
+	b.block_mut().fields_mut().state.commit().unwrap();
+	let b = b.close_and_lock().seal(&*test_spec.engine, vec![]).unwrap();
+	client.import_sealed_block(b).unwrap();
+
+	let transaction = Transaction {
+		nonce: 0.into(),
+		gas_price: 0.into(),
+		gas: 21000.into(),
+		action: Action::Call(Address::default()),
+		value: 5.into(),
+		data: Vec::new(),
+	}.fake_sign(address);
+
+	let (executed, proof) = client.execute_with_proof(transaction.clone(), BlockId::Latest).unwrap();
+	assert_eq!(executed.gas_used, 21000.into());
+	assert_eq!(Some(proof), client.prove_transaction(transaction, BlockId::Latest));
+}
+
+#[test]
+fn replays_transaction_after_preceding_ones() {
+	use client::{CallAnalytics, TransactionId};
+	use types::account_diff::Diff;
+
+	let client_result = generate_dummy_client(0);
+	let client = client_result.reference();
+	let key = KeyPair::from_secret(Secret::from_slice(&"replay".sha3()).unwrap()).unwrap();
+	let recipient = Address::random();
+	let test_spec = Spec::new_test();
+	let transfer = |nonce: u64, value: u64| Transaction {
+		nonce: nonce.into(),
+		gas_price: 0.into(),
+		gas: 21000.into(),
+		action: Action::Call(recipient),
+		value: value.into(),
+		data: Vec::new(),
+	}.sign(key.secret(), None);
+	let (first, second) = (transfer(0, 5), transfer(1, 7));
+
+	let mut b = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![]);
+	b.block_mut().fields_mut().state.add_balance(&key.address(), &100.into(), CleanupMode::NoEmpty).unwrap();
+	b.block_mut().fields_mut().state.commit().unwrap();
+	let b = b.close_and_lock().seal(&*test_spec.engine, vec![]).unwrap();
+	client.import_sealed_block(b).unwrap();
+
+	let mut b = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![]);
+	b.push_transaction(first, None).unwrap();
+	b.push_transaction(second.clone(), None).unwrap();
+	let b = b.close_and_lock().seal(&*test_spec.engine, vec![]).unwrap();
+	client.import_sealed_block(b).unwrap();
+
+	let analytics = CallAnalytics { transaction_tracing: true, state_diffing: true, ..Default::default() };
+	let executed = client.replay(TransactionId::Hash(second.hash()), analytics).unwrap();
+
+	assert_eq!(executed.trace.len(), 1);
+	let diff = executed.state_diff.unwrap();
+	assert_eq!(diff.get()[&recipient].balance, Diff::Changed(5.into(), 12.into()));
+	assert_eq!(diff.get()[&key.address()].nonce, Diff::Changed(1.into(), 2.into()));
+}
+
+#[test]
+fn returns_all_block_receipts() {
+	use client::TransactionId;
+
+	let client_result = generate_dummy_client(0);
+	let client = client_result.reference();
+	let key = KeyPair::from_secret(Secret::from_slice(&"receipts".sha3()).unwrap()).unwrap();
+	let test_spec = Spec::new_test();
+	let transfer = |nonce: u64| Transaction {
+		nonce: nonce.into(),
+		gas_price: 0.into(),
+		gas: 21000.into(),
+		action: Action::Call(Address::random()),
+		value: 1.into(),
+		data: Vec::new(),
+	}.sign(key.secret(), None);
+
+	let mut b = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![]);
+	b.block_mut().fields_mut().state.add_balance(&key.address(), &100.into(), CleanupMode::NoEmpty).unwrap();
+	b.block_mut().fields_mut().state.commit().unwrap();
+	let b = b.close_and_lock().seal(&*test_spec.engine, vec![]).unwrap();
+	client.import_sealed_block(b).unwrap();
+
+	let transactions = vec![transfer(0), transfer(1), transfer(2)];
+	let mut b = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![]);
+	for transaction in &transactions {
+		b.push_transaction(transaction.clone(), None).unwrap();
+	}
+	let b = b.close_and_lock().seal(&*test_spec.engine, vec![]).unwrap();
+	client.import_sealed_block(b).unwrap();
+
+	let receipts = client.localized_block_receipts(BlockId::Latest).unwrap();
+	assert_eq!(receipts.len(), 3);
+	for (transaction, receipt) in transactions.iter().zip(receipts) {
+		assert_eq!(receipt.gas_used, 21000.into());
+		assert_eq!(client.transaction_receipt(TransactionId::Hash(transaction.hash())), Some(receipt));
+	}
+	assert_eq!(client.localized_block_receipts(BlockId::Number(1)), Some(vec![]));
+	assert_eq!(client.localized_block_receipts(BlockId::Number(10)), None);
+}
+
+#[test]
+fn dump_and_restore_state() {
+	let source_result = generate_dummy_client_with_data(3, 2, &[1.into()]);
+	let source = source_result.reference();
+	let target_result = generate_dummy_client(0);
+	let target = target_result.reference();
+
+	let root = source.state_at(BlockId::Latest).unwrap().root().clone();
+	assert!(target.state_data(&root).is_none());
+
+	let mut dump = Vec::new();
+	source.dump_state(BlockId::Latest, &mut dump).unwrap();
+
+	assert_eq!(target.restore_state(&dump[..]).unwrap(), root);
+	assert!(target.state_data(&root).is_some());
+}
+
+#[test]
+fn latest_nonce_and_balance_matches_state() {
+	let client_result = generate_dummy_client(0);
+	let client = client_result.reference();
+	let address = Address::random();
+	let test_spec = Spec::new_test();
+	let mut b = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![]);
+	b.block_mut().fields_mut().state.add_balance(&address, &5.into(), CleanupMode::NoEmpty).unwrap();
+	b.block_mut().fields_mut().state.commit().unwrap();
+	let b = b.close_and_lock().seal(&*test_spec.engine, vec![]).unwrap();
+	client.import_sealed_block(b).unwrap();
+
+	for addr in &[address, Address::random()] {
+		let (nonce, balance) = client.latest_nonce_and_balance(addr);
+		assert_eq!(nonce, client.latest_nonce(addr));
+		assert_eq!(balance, client.latest_balance(addr));
+	}
+	assert_eq!(client.latest_nonce_and_balance(&address).1, 5.into());
+}
+
+#[test]
+fn call_many_threads_state() {
+	use client::CallAnalytics;
+	use types::account_diff::Diff;
+
+	let client_result = generate_dummy_client(0);
+	let client = client_result.reference();
+	let sender = Address::random();
+	let recipient = Address::random();
+	let transfer = |nonce: u64| Transaction {
+		nonce: nonce.into(),
+		gas_price: 0.into(),
+		gas: 21000.into(),
+		action: Action::Call(recipient),
+		value: 5.into(),
+		data: Vec::new(),
+	}.fake_sign(sender);
+	let diffing = CallAnalytics { state_diffing: true, ..Default::default() };
+
+	let results = client.call_many(vec![(transfer(0), diffing.clone()), (transfer(1), diffing)], BlockId::Latest).unwrap();
+
+	assert_eq!(results.len(), 2);
+	let diff = results[1].state_diff.as_ref().unwrap();
+	assert_eq!(diff.get()[&recipient].balance, Diff::Changed(5.into(), 10.into()));
+}
@@ -76,6 +76,8 @@ impl Engine for TestEngine {
 	fn schedule(&self, _env_info: &EnvInfo) -> Schedule {
 		let mut schedule = Schedule::new_frontier();
 		schedule.max_depth = self.max_depth;
+		schedule.have_revert = true;
+		schedule.have_create2 = true;
 		schedule
 	}
 }
@@ -151,10 +153,8 @@ pub fn generate_dummy_client_with_spec_and_accounts<F>(get_test_spec: F, account
 }
 
 pub fn generate_dummy_client_with_spec_accounts_and_data<F>(get_test_spec: F, accounts: Option<Arc<AccountProvider>>, block_number: u32, txs_per_block: usize, tx_gas_prices: &[U256]) -> GuardedTempResult<Arc<Client>> where F: Fn()->Spec {
-	let dir = RandomTempPath::new();
 	let test_spec = get_test_spec();
-	let db_config = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
-	let client_db = Arc::new(Database::open(&db_config, dir.as_path().to_str().unwrap()).unwrap());
+	let client_db = new_db();
 
 	let client = Client::new(
 		ClientConfig::default(),
@@ -222,7 +222,7 @@ pub fn generate_dummy_client_with_spec_accounts_and_data<F>(get_test_spec: F, ac
 	client.import_verified_blocks();
 
 	GuardedTempResult::<Arc<Client>> {
-		_temp: dir,
+		_temp: None,
 		result: Some(client)
 	}
 }
@@ -257,10 +257,8 @@ pub fn push_blocks_to_client(client: &Arc<Client>, timestamp_salt: u64, starting
 }
 
 pub fn get_test_client_with_blocks(blocks: Vec<Bytes>) -> GuardedTempResult<Arc<Client>> {
-	let dir = RandomTempPath::new();
 	let test_spec = get_test_spec();
-	let db_config = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
-	let client_db = Arc::new(Database::open(&db_config, dir.as_path().to_str().unwrap()).unwrap());
+	let client_db = new_db();
 
 	let client = Client::new(
 		ClientConfig::default(),
@@ -279,21 +277,17 @@ pub fn get_test_client_with_blocks(blocks: Vec<Bytes>) -> GuardedTempResult<Arc<
 	client.import_verified_blocks();
 
 	GuardedTempResult::<Arc<Client>> {
-		_temp: dir,
+		_temp: None,
 		result: Some(client)
 	}
 }
 
-fn new_db(path: &str) -> Arc<Database> {
-	Arc::new(
-		Database::open(&DatabaseConfig::with_columns(::db::NUM_COLUMNS), path)
-		.expect("Opening database for tests should always work.")
-	)
+fn new_db() -> Arc<KeyValueDB> {
+	Arc::new(::util::kvdb::in_memory(::db::NUM_COLUMNS.unwrap_or(0)))
 }
 
 pub fn generate_dummy_blockchain(block_number: u32) -> GuardedTempResult<BlockChain> {
-	let temp = RandomTempPath::new();
-	let db = new_db(temp.as_str());
+	let db = new_db();
 	let bc = BlockChain::new(BlockChainConfig::default(), &create_unverifiable_block(0, H256::zero()), db.clone());
 
 	let mut batch = db.transaction();
@@ -304,14 +298,13 @@ pub fn generate_dummy_blockchain(block_number: u32) -> GuardedTempResult<BlockCh
 	db.write(batch).unwrap();
 
 	GuardedTempResult::<BlockChain> {
-		_temp: temp,
+		_temp: None,
 		result: Some(bc)
 	}
 }
 
 pub fn generate_dummy_blockchain_with_extra(block_number: u32) -> GuardedTempResult<BlockChain> {
-	let temp = RandomTempPath::new();
-	let db = new_db(temp.as_str());
+	let db = new_db();
 	let bc = BlockChain::new(BlockChainConfig::default(), &create_unverifiable_block(0, H256::zero()), db.clone());
 
 
@@ -323,45 +316,43 @@ pub fn generate_dummy_blockchain_with_extra(block_number: u32) -> GuardedTempRes
 	db.write(batch).unwrap();
 
 	GuardedTempResult::<BlockChain> {
-		_temp: temp,
+		_temp: None,
 		result: Some(bc)
 	}
 }
 
 pub fn generate_dummy_empty_blockchain() -> GuardedTempResult<BlockChain> {
-	let temp = RandomTempPath::new();
-	let db = new_db(temp.as_str());
+	let db = new_db();
 	let bc = BlockChain::new(BlockChainConfig::default(), &create_unverifiable_block(0, H256::zero()), db.clone());
 
 	GuardedTempResult::<BlockChain> {
-		_temp: temp,
+		_temp: None,
 		result: Some(bc)
 	}
 }
 
 pub fn get_temp_state_db() -> GuardedTempResult<StateDB> {
-	let temp = RandomTempPath::new();
-	let journal_db = get_temp_state_db_in(temp.as_path());
-
 	GuardedTempResult {
-		_temp: temp,
-		result: Some(journal_db)
+		_temp: None,
+		result: Some(new_state_db(new_db())),
 	}
 }
 
 pub fn get_temp_state() -> GuardedTempResult<State<::state_db::StateDB>> {
-	let temp = RandomTempPath::new();
-	let journal_db = get_temp_state_db_in(temp.as_path());
-
 	GuardedTempResult {
-	    _temp: temp,
-		result: Some(State::new(journal_db, U256::from(0), Default::default())),
+		_temp: None,
+		result: Some(State::new(new_state_db(new_db()), U256::from(0), Default::default())),
 	}
 }
 
 pub fn get_temp_state_db_in(path: &Path) -> StateDB {
-	let db = new_db(path.to_str().expect("Only valid utf8 paths for tests."));
-	let journal_db = journaldb::new(db.clone(), journaldb::Algorithm::EarlyMerge, ::db::COL_STATE);
+	let db = Database::open(&DatabaseConfig::with_columns(::db::NUM_COLUMNS), path.to_str().expect("Only valid utf8 paths for tests."))
+		.expect("Opening database for tests should always work.");
+	new_state_db(Arc::new(db))
+}
+
+fn new_state_db(db: Arc<KeyValueDB>) -> StateDB {
+	let journal_db = journaldb::new(db, journaldb::Algorithm::EarlyMerge, ::db::COL_STATE);
 	StateDB::new(journal_db, 5 * 1024 * 1024)
 }
 
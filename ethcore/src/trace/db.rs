@@ -15,12 +15,15 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 //! Trace database.
+use std::cmp;
 use std::ops::Deref;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeSet, HashMap, VecDeque};
 use std::sync::Arc;
 use bloomchain::{Number, Config as BloomConfig};
 use bloomchain::group::{BloomGroupDatabase, BloomGroupChain, GroupPosition, BloomGroup};
-use util::{H256, H264, KeyValueDB, DBTransaction, RwLock, HeapSizeOf};
+use byteorder::{BigEndian, ByteOrder};
+use rlp;
+use util::{Address, H256, H264, KeyValueDB, DBTransaction, RwLock, HeapSizeOf};
 use header::BlockNumber;
 use trace::{LocalizedTrace, Config, Filter, Database as TraceDatabase, ImportRequest, DatabaseExtras};
 use db::{self, Key, Writable, Readable, CacheUpdatePolicy};
@@ -29,6 +32,8 @@ use super::flat::{FlatTrace, FlatBlockTraces, FlatTransactionTraces};
 use cache_manager::CacheManager;
 
 const TRACE_DB_VER: &'static [u8] = b"1.0";
+/// Key of the number of the first block in the address index.
+const ADDRESS_INDEX_START_KEY: &'static [u8] = b"address-index-start";
 
 #[derive(Debug, Copy, Clone)]
 #[cfg_attr(feature="dev", allow(enum_variant_names))]
@@ -93,6 +98,16 @@ impl Key<blooms::BloomGroup> for TraceGroupPosition {
 	}
 }
 
+/// Key of the address index entry noting that `address` appears in the traces of block `number`.
+///
+/// Entries of an address sort by block number, so those in a range of blocks can be iterated over.
+fn address_index_key(address: &Address, number: BlockNumber) -> [u8; 28] {
+	let mut key = [0u8; 28];
+	key[..20].copy_from_slice(address);
+	BigEndian::write_u64(&mut key[20..], number);
+	key
+}
+
 #[derive(Debug, Hash, Eq, PartialEq)]
 enum CacheId {
 	Trace(H256),
@@ -111,6 +126,8 @@ pub struct TraceDB<T> where T: DatabaseExtras {
 	bloom_config: BloomConfig,
 	// tracing enabled
 	enabled: bool,
+	// first block in the address index, blocks before it being found through the blooms only.
+	address_index_start: RwLock<Option<BlockNumber>>,
 	// extras
 	extras: Arc<T>,
 }
@@ -134,6 +151,10 @@ impl<T> TraceDB<T> where T: DatabaseExtras {
 		batch.put(db::COL_TRACE, b"version", TRACE_DB_VER);
 		tracesdb.write(batch).expect("failed to update version");
 
+		let address_index_start = tracesdb.get(db::COL_TRACE, ADDRESS_INDEX_START_KEY)
+			.expect("Low-level database error. Some issue with disk?")
+			.map(|start| rlp::decode(&start));
+
 		TraceDB {
 			traces: RwLock::new(HashMap::new()),
 			blooms: RwLock::new(HashMap::new()),
@@ -141,6 +162,7 @@ impl<T> TraceDB<T> where T: DatabaseExtras {
 			tracesdb: tracesdb,
 			bloom_config: config.blooms,
 			enabled: config.enabled,
+			address_index_start: RwLock::new(address_index_start),
 			extras: extras,
 		}
 	}
@@ -191,6 +213,63 @@ impl<T> TraceDB<T> where T: DatabaseExtras {
 		self.traces(block_hash).map(Into::into)
 	}
 
+	/// Numbers of the blocks in the range of the filter whose traces may match it, found in the
+	/// address index for the indexed part of the range. `None` if the filter doesn't restrict
+	/// the addresses or nothing has been indexed yet.
+	fn indexed_filter(&self, filter: &Filter) -> Option<Vec<Number>> {
+		let index_start = match *self.address_index_start.read() {
+			Some(start) => start as Number,
+			None => return None,
+		};
+
+		if filter.from_address.matches_all() && filter.to_address.matches_all() {
+			return None;
+		}
+
+		// blocks imported before the index existed are only found through the blooms.
+		let mut numbers = if filter.range.start < index_start {
+			let unindexed = Filter {
+				range: filter.range.start..cmp::min(filter.range.end, index_start - 1),
+				..filter.clone()
+			};
+			BloomGroupChain::new(self.bloom_config, self).filter(&unindexed)
+		} else {
+			Vec::new()
+		};
+
+		if filter.range.end >= index_start {
+			let start = cmp::max(filter.range.start, index_start) as BlockNumber;
+			let end = filter.range.end as BlockNumber + 1;
+			let from = self.indexed_blocks(filter.from_address.addresses(), start, end);
+			let to = self.indexed_blocks(filter.to_address.addresses(), start, end);
+			let indexed: Vec<Number> = match (from, to) {
+				(Some(from), Some(to)) => from.intersection(&to).cloned().collect(),
+				(Some(blocks), None) | (None, Some(blocks)) => blocks.into_iter().collect(),
+				(None, None) => unreachable!("at least one of the address filters is restricted; qed"),
+			};
+			numbers.extend(indexed);
+		}
+
+		Some(numbers)
+	}
+
+	/// Numbers of the blocks within `[start, end)` in whose traces any of the addresses appear,
+	/// or `None` if no addresses are given.
+	fn indexed_blocks(&self, addresses: &[Address], start: BlockNumber, end: BlockNumber) -> Option<BTreeSet<Number>> {
+		if addresses.is_empty() {
+			return None;
+		}
+
+		let blocks = addresses.iter()
+			.flat_map(|address| {
+				self.tracesdb.iter_range(db::COL_TRACE_INDEX, &address_index_key(address, start), &address_index_key(address, end))
+					.map(|(key, _)| BigEndian::read_u64(&key[20..]) as Number)
+					.collect::<Vec<_>>()
+			})
+			.collect();
+		Some(blocks)
+	}
+
 	fn matching_block_traces(
 		&self,
 		filter: &Filter,
@@ -291,6 +370,27 @@ impl<T> TraceDatabase for TraceDB<T> where T: DatabaseExtras {
 			for key in blooms_keys {
 				self.note_used(CacheId::Bloom(key));
 			}
+
+			// index the addresses appearing in the enacted blocks. Entries of retracted blocks
+			// are left behind; they only cost a lookup of the canon block's traces when filtering.
+			for (offset, block_hash) in request.enacted.iter().enumerate() {
+				let number = (range_start + offset) as BlockNumber;
+				let addresses = if block_hash == &request.block_hash {
+					request.traces.addresses()
+				} else {
+					self.traces(block_hash).expect("Traces database is incomplete.").addresses()
+				};
+				for address in addresses {
+					batch.put(db::COL_TRACE_INDEX, &address_index_key(&address, number), &[]);
+				}
+			}
+
+			let mut index_start = self.address_index_start.write();
+			if index_start.is_none() {
+				let start = range_start as BlockNumber;
+				batch.put(db::COL_TRACE, ADDRESS_INDEX_START_KEY, &rlp::encode(&start));
+				*index_start = Some(start);
+			}
 		}
 
 		// insert new block traces into the cache and the database
@@ -385,8 +485,10 @@ impl<T> TraceDatabase for TraceDB<T> where T: DatabaseExtras {
 	}
 
 	fn filter(&self, filter: &Filter) -> Vec<LocalizedTrace> {
-		let chain = BloomGroupChain::new(self.bloom_config, self);
-		let numbers = chain.filter(filter);
+		let numbers = match self.indexed_filter(filter) {
+			Some(numbers) => numbers,
+			None => BloomGroupChain::new(self.bloom_config, self).filter(filter),
+		};
 		numbers.into_iter()
 			.flat_map(|n| {
 				let number = n as BlockNumber;
@@ -657,6 +759,59 @@ mod tests {
 		assert_eq!(tracedb.trace(2, 0, vec![]).unwrap(), create_simple_localized_trace(2, block_2.clone(), tx_2.clone()));
 	}
 
+	#[test]
+	fn filter_with_address_index() {
+		let db = new_db();
+		let mut config = Config::default();
+		config.enabled = true;
+		let block_1 = H256::from(0xa1);
+		let block_2 = H256::from(0xa2);
+		let tx_1 = H256::from(0xff);
+		let tx_2 = H256::from(0xaf);
+
+		let mut extras = Extras::default();
+		extras.block_hashes.insert(0, H256::default());
+		extras.block_hashes.insert(1, block_1.clone());
+		extras.block_hashes.insert(2, block_2.clone());
+		extras.transaction_hashes.insert(1, vec![tx_1.clone()]);
+		extras.transaction_hashes.insert(2, vec![tx_2.clone()]);
+
+		{
+			let tracedb = TraceDB::new(config.clone(), db.clone(), Arc::new(extras.clone()));
+			for &(number, ref hash) in &[(1, block_1.clone()), (2, block_2.clone())] {
+				let mut batch = DBTransaction::new();
+				tracedb.import(&mut batch, create_simple_import_request(number, hash.clone()));
+				db.write(batch).unwrap();
+			}
+		}
+
+		// forget block 1 was indexed, as if it was imported before the index existed.
+		let mut batch = DBTransaction::new();
+		batch.delete(::db::COL_TRACE_INDEX, &super::address_index_key(&Address::from(1), 1));
+		batch.delete(::db::COL_TRACE_INDEX, &super::address_index_key(&Address::from(2), 1));
+		batch.put(::db::COL_TRACE, super::ADDRESS_INDEX_START_KEY, &::rlp::encode(&2u64));
+		db.write(batch).unwrap();
+
+		let tracedb = TraceDB::new(config, db.clone(), Arc::new(extras));
+		let filter = |from: Vec<Address>, to: Vec<Address>| Filter {
+			range: (1..2),
+			from_address: AddressesFilter::from(from),
+			to_address: AddressesFilter::from(to),
+		};
+
+		let traces = tracedb.filter(&filter(vec![Address::from(1)], vec![Address::from(2)]));
+		assert_eq!(traces, vec![
+			create_simple_localized_trace(1, block_1.clone(), tx_1.clone()),
+			create_simple_localized_trace(2, block_2.clone(), tx_2.clone()),
+		]);
+
+		let traces = tracedb.filter(&filter(vec![], vec![Address::from(2)]));
+		assert_eq!(traces.len(), 2);
+
+		assert!(tracedb.filter(&filter(vec![Address::from(2)], vec![])).is_empty());
+		assert!(tracedb.filter(&filter(vec![Address::from(1)], vec![Address::from(3)])).is_empty());
+	}
+
 	#[test]
 	fn query_trace_after_reopen() {
 		let db = new_db();
@@ -0,0 +1,128 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Per-instruction gas profiler.
+
+use std::collections::BTreeMap;
+use util::U256;
+use trace::VMTracer;
+use trace::trace::{VMTrace, GasProfile, OpcodeGas};
+
+/// VM tracer recording the gas used by each instruction, including in nested calls.
+/// Produces a `GasProfile` instead of a VM trace.
+#[derive(Default)]
+pub struct GasProfiler {
+	// executions and gas used, by instruction.
+	instructions: BTreeMap<u8, (u64, U256)>,
+	// gas used at this and deeper levels.
+	total: U256,
+	// the instruction being executed and its cost.
+	pending: Option<(u8, U256)>,
+	// gas left after the last instruction executed at this level.
+	gas_left: Option<U256>,
+	// gas used at deeper levels by the instruction being executed.
+	sub_gas: U256,
+}
+
+impl GasProfiler {
+	fn note(&mut self, instruction: u8, count: u64, gas: U256) {
+		let entry = self.instructions.entry(instruction).or_insert((0, U256::zero()));
+		entry.0 += count;
+		entry.1 = entry.1 + gas;
+	}
+}
+
+impl VMTracer for GasProfiler {
+	fn trace_prepare_execute(&mut self, _pc: usize, instruction: u8, gas_cost: &U256) -> bool {
+		self.pending = Some((instruction, *gas_cost));
+		self.sub_gas = U256::zero();
+		true
+	}
+
+	fn trace_executed(&mut self, gas_left: U256, _stack_push: &[U256], _mem_diff: Option<(usize, &[u8])>, _store_diff: Option<(U256, U256)>) {
+		if let Some((instruction, gas_cost)) = self.pending.take() {
+			// the cost of calls includes the gas given to the callee, so what they used is
+			// found from the gas left before and after, less what deeper levels used.
+			let gas = match self.gas_left {
+				Some(before) if before >= gas_left + self.sub_gas => before - gas_left - self.sub_gas,
+				_ => gas_cost,
+			};
+			self.note(instruction, 1, gas);
+			self.total = self.total + gas;
+		}
+		self.gas_left = Some(gas_left);
+	}
+
+	fn prepare_subtrace(&self, _code: &[u8]) -> Self {
+		GasProfiler::default()
+	}
+
+	fn done_subtrace(&mut self, sub: Self) {
+		self.sub_gas = self.sub_gas + sub.total;
+		self.total = self.total + sub.total;
+		for (instruction, (count, gas)) in sub.instructions {
+			self.note(instruction, count, gas);
+		}
+	}
+
+	fn gas_profile(&self) -> Option<GasProfile> {
+		Some(GasProfile {
+			instructions: self.instructions.iter().map(|(&instruction, &(count, gas))| OpcodeGas {
+				instruction: instruction,
+				count: count,
+				gas: gas,
+			}).collect(),
+		})
+	}
+
+	fn drain(self) -> Option<VMTrace> {
+		None
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::U256;
+	use trace::VMTracer;
+	use trace::trace::OpcodeGas;
+	use super::GasProfiler;
+
+	#[test]
+	fn attributes_gas_used_by_calls_to_callee() {
+		let mut profiler = GasProfiler::default();
+
+		// PUSH1 with 100 gas left.
+		profiler.trace_prepare_execute(0, 0x60, &3.into());
+		profiler.trace_executed(97.into(), &[], None, None);
+
+		// CALL costing 40 plus 50 given to the callee, which uses 20 and returns the rest.
+		profiler.trace_prepare_execute(2, 0xf1, &90.into());
+		let mut sub = profiler.prepare_subtrace(&[]);
+		sub.trace_prepare_execute(0, 0x60, &3.into());
+		sub.trace_executed(47.into(), &[], None, None);
+		sub.trace_prepare_execute(2, 0x55, &17.into());
+		sub.trace_executed(30.into(), &[], None, None);
+		profiler.done_subtrace(sub);
+		profiler.trace_executed(37.into(), &[], None, None);
+
+		let profile = profiler.gas_profile().unwrap();
+		assert_eq!(profile.instructions, vec![
+			OpcodeGas { instruction: 0x55, count: 1, gas: U256::from(17) },
+			OpcodeGas { instruction: 0x60, count: 2, gas: U256::from(6) },
+			OpcodeGas { instruction: 0xf1, count: 1, gas: U256::from(40) },
+		]);
+	}
+}
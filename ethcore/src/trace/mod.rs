@@ -20,17 +20,23 @@ mod bloom;
 mod config;
 mod db;
 mod executive_tracer;
+mod gas_profiler;
 mod import;
 mod noop_tracer;
+mod step_filter;
+mod struct_logger;
 
 pub use types::trace_types::{filter, flat, localized, trace};
 pub use types::trace_types::error::Error as TraceError;
 pub use self::config::Config;
 pub use self::db::TraceDB;
-pub use types::trace_types::trace::{VMTrace, VMOperation, VMExecutedOperation, MemoryDiff, StorageDiff};
+pub use types::trace_types::trace::{VMTrace, VMOperation, VMExecutedOperation, MemoryDiff, StorageDiff, GasProfile, OpcodeGas, StructLog, StructLogOptions};
 pub use types::trace_types::flat::{FlatTrace, FlatTransactionTraces, FlatBlockTraces};
 pub use self::noop_tracer::{NoopTracer, NoopVMTracer};
 pub use self::executive_tracer::{ExecutiveTracer, ExecutiveVMTracer};
+pub use self::gas_profiler::GasProfiler;
+pub use self::struct_logger::StructLogger;
+pub use self::step_filter::StepFilter;
 pub use types::trace_types::filter::{Filter, AddressesFilter};
 pub use self::import::ImportRequest;
 pub use self::localized::LocalizedTrace;
@@ -97,9 +103,20 @@ pub trait VMTracer: Send {
 	/// Spawn subtracer which will be used to trace deeper levels of execution.
 	fn prepare_subtrace(&self, code: &[u8]) -> Self where Self: Sized;
 
+	/// Spawn subtracer for the execution of `code` with the storage of `address`.
+	fn prepare_subtrace_at(&self, _address: &Address, code: &[u8]) -> Self where Self: Sized {
+		self.prepare_subtrace(code)
+	}
+
 	/// Spawn subtracer which will be used to trace deeper levels of execution.
 	fn done_subtrace(&mut self, sub: Self) where Self: Sized;
 
+	/// Returns the gas used by each instruction, if profiled. Called before `drain`.
+	fn gas_profile(&self) -> Option<GasProfile> { None }
+
+	/// Takes the steps recorded by a struct logger. Called before `drain`.
+	fn struct_logs(&mut self) -> Option<Vec<StructLog>> { None }
+
 	/// Consumes self and returns the VM trace.
 	fn drain(self) -> Option<VMTrace>;
 }
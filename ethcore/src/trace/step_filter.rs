@@ -0,0 +1,294 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Expression filter selecting the steps recorded by the struct logger.
+//!
+//! A filter compares the fields `op`, `pc`, `depth` and `address` of a step with
+//! `==`, `!=`, `<`, `<=`, `>` and `>=`, and combines comparisons with `!`, `&&`, `||`
+//! and parentheses, e.g. `op == SSTORE && address == 0x0000000000000000000000000000000000000042`.
+//! Values are instruction mnemonics, decimal numbers or `0x` prefixed hex numbers.
+
+use std::str::FromStr;
+use util::{U256, H256, Address};
+use evm;
+
+/// Longest filter accepted, in characters.
+pub const MAX_FILTER_LENGTH: usize = 4096;
+/// Deepest nesting of `!` and parentheses accepted in a filter.
+pub const MAX_FILTER_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+	Op,
+	Pc,
+	Depth,
+	Address,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparison {
+	Eq,
+	Ne,
+	Lt,
+	Le,
+	Gt,
+	Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+	Or(Box<Expr>, Box<Expr>),
+	And(Box<Expr>, Box<Expr>),
+	Not(Box<Expr>),
+	Compare(Field, Comparison, U256),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+	Word(String),
+	Comparison(Comparison),
+	And,
+	Or,
+	Not,
+	Open,
+	Close,
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+	let chars: Vec<char> = s.chars().collect();
+	let mut tokens = Vec::new();
+	let mut i = 0;
+	while i < chars.len() {
+		let c = chars[i];
+		let next = chars.get(i + 1).cloned();
+		let (token, len) = match (c, next) {
+			(c, _) if c.is_whitespace() => { i += 1; continue; },
+			('&', Some('&')) => (Token::And, 2),
+			('|', Some('|')) => (Token::Or, 2),
+			('=', Some('=')) => (Token::Comparison(Comparison::Eq), 2),
+			('!', Some('=')) => (Token::Comparison(Comparison::Ne), 2),
+			('<', Some('=')) => (Token::Comparison(Comparison::Le), 2),
+			('>', Some('=')) => (Token::Comparison(Comparison::Ge), 2),
+			('<', _) => (Token::Comparison(Comparison::Lt), 1),
+			('>', _) => (Token::Comparison(Comparison::Gt), 1),
+			('!', _) => (Token::Not, 1),
+			('(', _) => (Token::Open, 1),
+			(')', _) => (Token::Close, 1),
+			(c, _) if c.is_alphanumeric() || c == '_' => {
+				let len = chars[i..].iter().take_while(|c| c.is_alphanumeric() || **c == '_').count();
+				(Token::Word(chars[i..i + len].iter().cloned().collect()), len)
+			},
+			(c, _) => return Err(format!("Unexpected character '{}' at {}", c, i)),
+		};
+		tokens.push(token);
+		i += len;
+	}
+	Ok(tokens)
+}
+
+struct Parser {
+	tokens: Vec<Token>,
+	position: usize,
+	// nesting of the expression being parsed.
+	depth: usize,
+}
+
+impl Parser {
+	fn peek(&self) -> Option<&Token> {
+		self.tokens.get(self.position)
+	}
+
+	fn next(&mut self) -> Option<Token> {
+		let token = self.tokens.get(self.position).cloned();
+		self.position += 1;
+		token
+	}
+
+	fn or(&mut self) -> Result<Expr, String> {
+		let mut expr = self.and()?;
+		while self.peek() == Some(&Token::Or) {
+			self.next();
+			expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+		}
+		Ok(expr)
+	}
+
+	fn and(&mut self) -> Result<Expr, String> {
+		let mut expr = self.unary()?;
+		while self.peek() == Some(&Token::And) {
+			self.next();
+			expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+		}
+		Ok(expr)
+	}
+
+	// parse a nested expression with `f`, failing if nested too deeply.
+	fn nested<F>(&mut self, f: F) -> Result<Expr, String> where F: FnOnce(&mut Self) -> Result<Expr, String> {
+		if self.depth >= MAX_FILTER_DEPTH {
+			return Err(format!("Filter nested deeper than {} levels", MAX_FILTER_DEPTH));
+		}
+		self.depth += 1;
+		let expr = f(self);
+		self.depth -= 1;
+		expr
+	}
+
+	fn unary(&mut self) -> Result<Expr, String> {
+		match self.next() {
+			Some(Token::Not) => self.nested(|parser| Ok(Expr::Not(Box::new(parser.unary()?)))),
+			Some(Token::Open) => {
+				let expr = self.nested(|parser| parser.or())?;
+				match self.next() {
+					Some(Token::Close) => Ok(expr),
+					other => Err(format!("Expected ')', got {:?}", other)),
+				}
+			},
+			Some(Token::Word(field)) => {
+				let field = match field.as_str() {
+					"op" => Field::Op,
+					"pc" => Field::Pc,
+					"depth" => Field::Depth,
+					"address" => Field::Address,
+					other => return Err(format!("Unknown field '{}'", other)),
+				};
+				let comparison = match self.next() {
+					Some(Token::Comparison(comparison)) => comparison,
+					other => return Err(format!("Expected a comparison, got {:?}", other)),
+				};
+				let value = match self.next() {
+					Some(Token::Word(value)) => parse_value(field, &value)?,
+					other => return Err(format!("Expected a value, got {:?}", other)),
+				};
+				Ok(Expr::Compare(field, comparison, value))
+			},
+			other => Err(format!("Expected a comparison, got {:?}", other)),
+		}
+	}
+}
+
+fn parse_value(field: Field, value: &str) -> Result<U256, String> {
+	if value.starts_with("0x") {
+		return U256::from_str(&value[2..]).map_err(|_| format!("Invalid hex number '{}'", value));
+	}
+	if let Ok(number) = value.parse::<u64>() {
+		return Ok(U256::from(number));
+	}
+	match field {
+		Field::Op => (0..0x100usize)
+			.find(|&i| evm::instruction_name(i as u8) == value)
+			.map(|i| U256::from(i as u64))
+			.ok_or_else(|| format!("Unknown instruction '{}'", value)),
+		_ => Err(format!("Invalid number '{}'", value)),
+	}
+}
+
+impl Expr {
+	fn matches(&self, values: &[U256; 4]) -> bool {
+		match *self {
+			Expr::Or(ref a, ref b) => a.matches(values) || b.matches(values),
+			Expr::And(ref a, ref b) => a.matches(values) && b.matches(values),
+			Expr::Not(ref a) => !a.matches(values),
+			Expr::Compare(field, comparison, ref value) => {
+				let actual = &values[field as usize];
+				match comparison {
+					Comparison::Eq => actual == value,
+					Comparison::Ne => actual != value,
+					Comparison::Lt => actual < value,
+					Comparison::Le => actual <= value,
+					Comparison::Gt => actual > value,
+					Comparison::Ge => actual >= value,
+				}
+			},
+		}
+	}
+}
+
+/// Filter selecting the steps recorded by the struct logger.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepFilter(Expr);
+
+impl StepFilter {
+	/// Whether the step executing `instruction` at `pc`, in the frame at `depth`
+	/// running with the storage of `address`, should be recorded.
+	pub fn matches(&self, pc: usize, instruction: u8, depth: usize, address: &Address) -> bool {
+		let values = [U256::from(instruction as u64), U256::from(pc as u64), U256::from(depth as u64), U256::from(H256::from(address))];
+		self.0.matches(&values)
+	}
+}
+
+impl FromStr for StepFilter {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		if s.len() > MAX_FILTER_LENGTH {
+			return Err(format!("Filter longer than {} characters", MAX_FILTER_LENGTH));
+		}
+		let mut parser = Parser {
+			tokens: tokenize(s)?,
+			position: 0,
+			depth: 0,
+		};
+		let expr = parser.or()?;
+		match parser.next() {
+			None => Ok(StepFilter(expr)),
+			Some(token) => Err(format!("Unexpected {:?}", token)),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::iter;
+	use util::Address;
+	use super::{StepFilter, MAX_FILTER_LENGTH, MAX_FILTER_DEPTH};
+
+	#[test]
+	fn matches_steps() {
+		let contract: Address = 0x42.into();
+		let filter: StepFilter = "op == SSTORE && address == 0x42 || !(depth <= 2) && pc >= 0x10".parse().unwrap();
+
+		assert!(filter.matches(0, 0x55, 1, &contract));
+		assert!(!filter.matches(0, 0x55, 1, &0x43.into()));
+		assert!(!filter.matches(0, 0x54, 1, &contract));
+		assert!(filter.matches(16, 0x01, 3, &0x43.into()));
+		assert!(!filter.matches(15, 0x01, 3, &0x43.into()));
+	}
+
+	#[test]
+	fn rejects_invalid_filters() {
+		assert!("".parse::<StepFilter>().is_err());
+		assert!("op == NOTANOP".parse::<StepFilter>().is_err());
+		assert!("gas > 10".parse::<StepFilter>().is_err());
+		assert!("(op == ADD".parse::<StepFilter>().is_err());
+		assert!("op == ADD pc".parse::<StepFilter>().is_err());
+		assert!("op = ADD".parse::<StepFilter>().is_err());
+	}
+
+	#[test]
+	fn rejects_oversized_filters() {
+		let nested = |depth: usize| {
+			let open: String = iter::repeat("!(").take(depth).collect();
+			let close: String = iter::repeat(")").take(depth).collect();
+			format!("{}pc > 0{}", open, close)
+		};
+		assert!(nested(MAX_FILTER_DEPTH / 2).parse::<StepFilter>().is_ok());
+		assert!(nested(MAX_FILTER_DEPTH / 2 + 1).parse::<StepFilter>().is_err());
+		assert!(nested(100_000).parse::<StepFilter>().is_err());
+
+		let long: String = iter::repeat("pc > 0 || ").take(MAX_FILTER_LENGTH / 10 + 1).collect();
+		assert!(format!("{}pc > 0", long).parse::<StepFilter>().is_err());
+	}
+}
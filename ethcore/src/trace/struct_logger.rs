@@ -0,0 +1,261 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Step-by-step struct logger.
+
+use std::cmp;
+use std::mem;
+use std::collections::BTreeMap;
+use util::{U256, Bytes, Address};
+use evm;
+use trace::{VMTracer, StepFilter};
+use trace::trace::{VMTrace, StructLog, StructLogOptions};
+
+const SLOAD: u8 = 0x54;
+const SSTORE: u8 = 0x55;
+
+/// VM tracer recording every executed step, with snapshots of the stack, memory and storage
+/// as requested. Produces struct logs instead of a VM trace.
+#[derive(Default)]
+pub struct StructLogger {
+	options: StructLogOptions,
+	filter: Option<StepFilter>,
+	// call depth of this level; the top level of execution is entered as a subtrace at depth 1.
+	depth: usize,
+	// address whose storage this level runs with.
+	address: Address,
+	logs: Vec<StructLog>,
+	// steps left to record, shared with deeper levels.
+	remaining: Option<usize>,
+	// the state of this level, rebuilt from the executed instructions.
+	stack: Vec<U256>,
+	memory: Bytes,
+	storage: BTreeMap<U256, U256>,
+	// gas left after the last instruction executed at this level.
+	gas_left: Option<U256>,
+	// log of the instruction being executed if recorded, the instruction and the key it loads.
+	pending: Option<(Option<usize>, u8, Option<U256>)>,
+}
+
+impl StructLogger {
+	/// Create a struct logger for the top level of execution.
+	pub fn new(options: StructLogOptions) -> Self {
+		let remaining = match options.limit {
+			0 => None,
+			limit => Some(limit),
+		};
+		// filters are validated when given, invalid ones are ignored here.
+		let filter = options.filter.as_ref().and_then(|f| f.parse().ok());
+
+		StructLogger {
+			options: options,
+			filter: filter,
+			depth: 0,
+			remaining: remaining,
+			..Default::default()
+		}
+	}
+}
+
+impl VMTracer for StructLogger {
+	fn trace_prepare_execute(&mut self, pc: usize, instruction: u8, gas_cost: &U256) -> bool {
+		if self.options.max_depth != 0 && self.depth > self.options.max_depth {
+			return false;
+		}
+		if self.remaining == Some(0) {
+			return false;
+		}
+
+		let len = self.stack.len();
+		// storage is shown as it is once the step is executed.
+		if instruction == SSTORE && len >= 2 {
+			self.storage.insert(self.stack[len - 1], self.stack[len - 2]);
+		}
+		let load_key = match instruction == SLOAD {
+			true => self.stack.last().cloned(),
+			false => None,
+		};
+
+		// steps not recorded are still followed to keep the state of this level.
+		let record = match self.filter {
+			Some(ref filter) => filter.matches(pc, instruction, self.depth, &self.address),
+			None => true,
+		};
+		if !record {
+			self.pending = Some((None, instruction, load_key));
+			return true;
+		}
+
+		self.logs.push(StructLog {
+			pc: pc,
+			instruction: instruction,
+			// patched once executed when the first step of a level.
+			gas: self.gas_left.unwrap_or_else(U256::zero),
+			gas_cost: *gas_cost,
+			depth: self.depth,
+			stack: if self.options.stack { Some(self.stack.clone()) } else { None },
+			memory: if self.options.memory { Some(self.memory.clone()) } else { None },
+			storage: if self.options.storage { Some(self.storage.clone()) } else { None },
+		});
+		self.remaining = self.remaining.map(|r| r - 1);
+		self.pending = Some((Some(self.logs.len() - 1), instruction, load_key));
+		true
+	}
+
+	fn trace_executed(&mut self, gas_left: U256, stack_push: &[U256], mem_diff: Option<(usize, &[u8])>, store_diff: Option<(U256, U256)>) {
+		if let Some((index, instruction, load_key)) = self.pending.take() {
+			if let (Some(index), None) = (index, self.gas_left) {
+				let log = &mut self.logs[index];
+				log.gas = gas_left + log.gas_cost;
+			}
+
+			let len = self.stack.len();
+			self.stack.truncate(len - cmp::min(evm::instruction_args(instruction), len));
+			self.stack.extend_from_slice(stack_push);
+
+			if let Some((offset, data)) = mem_diff {
+				let end = offset + data.len();
+				if self.memory.len() < end {
+					self.memory.resize((end + 31) / 32 * 32, 0);
+				}
+				self.memory[offset..end].copy_from_slice(data);
+			}
+			if let Some((key, value)) = store_diff {
+				self.storage.insert(key, value);
+			}
+			if let (Some(key), Some(value)) = (load_key, stack_push.first()) {
+				self.storage.insert(key, *value);
+			}
+		}
+		self.gas_left = Some(gas_left);
+	}
+
+	fn prepare_subtrace(&self, code: &[u8]) -> Self {
+		self.prepare_subtrace_at(&self.address, code)
+	}
+
+	fn prepare_subtrace_at(&self, address: &Address, _code: &[u8]) -> Self {
+		StructLogger {
+			options: self.options.clone(),
+			filter: self.filter.clone(),
+			depth: self.depth + 1,
+			address: address.clone(),
+			remaining: self.remaining,
+			..Default::default()
+		}
+	}
+
+	fn done_subtrace(&mut self, sub: Self) {
+		self.remaining = sub.remaining;
+		self.logs.extend(sub.logs);
+	}
+
+	fn struct_logs(&mut self) -> Option<Vec<StructLog>> {
+		Some(mem::replace(&mut self.logs, Vec::new()))
+	}
+
+	fn drain(self) -> Option<VMTrace> {
+		None
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::U256;
+	use trace::VMTracer;
+	use trace::trace::StructLogOptions;
+	use super::StructLogger;
+
+	fn options() -> StructLogOptions {
+		StructLogOptions { stack: true, memory: true, storage: true, limit: 0, max_depth: 0, filter: None }
+	}
+
+	#[test]
+	fn rebuilds_stack_memory_and_storage() {
+		let mut logger = StructLogger::new(options()).prepare_subtrace(&[]);
+
+		// PUSH1 0x2a, PUSH1 0, MSTORE, PUSH1 0x2a, PUSH1 1, SSTORE
+		logger.trace_prepare_execute(0, 0x60, &3.into());
+		logger.trace_executed(97.into(), &[0x2a.into()], None, None);
+		logger.trace_prepare_execute(2, 0x60, &3.into());
+		logger.trace_executed(94.into(), &[0.into()], None, None);
+		logger.trace_prepare_execute(4, 0x52, &6.into());
+		let mut word = [0u8; 32];
+		word[31] = 0x2a;
+		logger.trace_executed(88.into(), &[], Some((0, &word)), None);
+		logger.trace_prepare_execute(5, 0x60, &3.into());
+		logger.trace_executed(85.into(), &[0x2a.into()], None, None);
+		logger.trace_prepare_execute(7, 0x60, &3.into());
+		logger.trace_executed(82.into(), &[1.into()], None, None);
+		logger.trace_prepare_execute(9, 0x55, &20000.into());
+
+		let logs = logger.struct_logs().unwrap();
+		assert_eq!(logs.len(), 6);
+		assert_eq!(logs[0].gas, U256::from(100));
+		assert_eq!(logs[2].gas, U256::from(94));
+		assert_eq!(logs[2].stack, Some(vec![0x2a.into(), 0.into()]));
+		assert_eq!(logs[3].stack, Some(vec![]));
+		assert_eq!(logs[3].memory, Some(word.to_vec()));
+		assert_eq!(logs[5].stack, Some(vec![0x2a.into(), 1.into()]));
+		assert_eq!(logs[5].storage.as_ref().unwrap().get(&U256::from(1)), Some(&U256::from(0x2a)));
+	}
+
+	#[test]
+	fn respects_limit_and_max_depth() {
+		let mut root = StructLogger::new(StructLogOptions { limit: 2, max_depth: 1, ..options() });
+		let mut logger = root.prepare_subtrace(&[]);
+
+		assert!(logger.trace_prepare_execute(0, 0xf1, &700.into()));
+		let mut sub = logger.prepare_subtrace(&[]);
+		assert!(!sub.trace_prepare_execute(0, 0x60, &3.into()));
+		logger.done_subtrace(sub);
+		logger.trace_executed(1000.into(), &[1.into()], None, None);
+		assert!(logger.trace_prepare_execute(1, 0x60, &3.into()));
+		logger.trace_executed(997.into(), &[1.into()], None, None);
+		assert!(!logger.trace_prepare_execute(3, 0x60, &3.into()));
+		root.done_subtrace(logger);
+
+		let logs = root.struct_logs().unwrap();
+		assert_eq!(logs.iter().map(|l| (l.pc, l.depth)).collect::<Vec<_>>(), vec![(0, 1), (1, 1)]);
+	}
+
+	#[test]
+	fn records_filtered_steps() {
+		let root = StructLogger::new(StructLogOptions { filter: Some("op == SSTORE && address == 0x42".into()), ..options() });
+		let mut logger = root.prepare_subtrace_at(&0x42.into(), &[]);
+
+		// PUSH1 0x2a, PUSH1 1, SSTORE
+		logger.trace_prepare_execute(0, 0x60, &3.into());
+		logger.trace_executed(97.into(), &[0x2a.into()], None, None);
+		logger.trace_prepare_execute(2, 0x60, &3.into());
+		logger.trace_executed(94.into(), &[1.into()], None, None);
+		logger.trace_prepare_execute(4, 0x55, &20000.into());
+
+		let mut other = root.prepare_subtrace_at(&0x43.into(), &[]);
+		other.trace_prepare_execute(0, 0x60, &3.into());
+		other.trace_executed(97.into(), &[0x2a.into()], None, None);
+		other.trace_prepare_execute(2, 0x60, &3.into());
+		other.trace_executed(94.into(), &[1.into()], None, None);
+		other.trace_prepare_execute(4, 0x55, &20000.into());
+
+		let logs = logger.struct_logs().unwrap();
+		assert_eq!(logs.len(), 1);
+		assert_eq!(logs[0].pc, 4);
+		assert_eq!(logs[0].gas, U256::from(94));
+		assert_eq!(logs[0].stack, Some(vec![0x2a.into(), 1.into()]));
+		assert_eq!(other.struct_logs().unwrap().len(), 0);
+	}
+}
@@ -16,8 +16,12 @@
 
 //! Call analytics related types
 
+use std::collections::BTreeMap;
+use util::{Address, Bytes, H256, U256};
+use trace::StructLogOptions;
+
 /// Options concerning what analytics we run on the call.
-#[derive(Eq, PartialEq, Default, Clone, Copy, Debug)]
+#[derive(Eq, PartialEq, Default, Clone, Debug)]
 #[cfg_attr(feature = "ipc", binary)]
 pub struct CallAnalytics {
 	/// Make a transaction trace.
@@ -26,4 +30,24 @@ pub struct CallAnalytics {
 	pub vm_tracing: bool,
 	/// Make a diff.
 	pub state_diffing: bool,
+	/// Profile the gas used by each instruction. Replaces the VM trace.
+	pub gas_profiling: bool,
+	/// Record every executed step with the given options. Replaces the VM trace and gas profile.
+	pub struct_logging: Option<StructLogOptions>,
+	/// Accounts to modify in the state the call is executed against. Ignored when replaying.
+	pub state_override: BTreeMap<Address, AccountOverride>,
+}
+
+/// Changes made to an account before executing a call.
+#[derive(Eq, PartialEq, Default, Clone, Debug)]
+#[cfg_attr(feature = "ipc", binary)]
+pub struct AccountOverride {
+	/// Replace the balance.
+	pub balance: Option<U256>,
+	/// Replace the nonce.
+	pub nonce: Option<U256>,
+	/// Replace the code.
+	pub code: Option<Bytes>,
+	/// Replace the values of these storage slots.
+	pub storage: BTreeMap<H256, H256>,
 }
@@ -0,0 +1,42 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Statistics about the state database.
+
+/// Progress of the online compaction of an on-disk state journal.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "ipc", binary)]
+pub struct JournalCompaction {
+	/// The next era to be examined.
+	pub next_era: u64,
+	/// The latest era which may currently be compacted.
+	pub target_era: u64,
+	/// Number of eras whose journal records have been applied and removed since startup.
+	pub compacted_eras: u64,
+	/// Journal bytes reclaimed since startup.
+	pub reclaimed_bytes: u64,
+}
+
+/// State database statistics.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ipc", binary)]
+pub struct DbStats {
+	/// Name of the pruning algorithm in use.
+	pub pruning: String,
+	/// Journal compaction progress. `None` unless the pruning algorithm keeps
+	/// its whole journal on disk.
+	pub compaction: Option<JournalCompaction>,
+}
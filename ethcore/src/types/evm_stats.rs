@@ -0,0 +1,29 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Statistics about the virtual machines used.
+
+/// Numbers of virtual machines created to run code since startup.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "ipc", binary)]
+pub struct EvmStats {
+	/// Runs of the interpreter.
+	pub interpreter_runs: u64,
+	/// Runs of the JIT.
+	pub jit_runs: u64,
+	/// Number of contracts run often enough to be picked for the JIT.
+	pub hot_contracts: u64,
+}
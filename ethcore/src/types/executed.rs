@@ -19,7 +19,8 @@
 use util::{Bytes, U256, Address, U512, trie};
 use rlp::*;
 use evm;
-use trace::{VMTrace, FlatTrace};
+use basic_types::LogBloom;
+use trace::{VMTrace, FlatTrace, GasProfile, StructLog};
 use types::log_entry::LogEntry;
 use types::state_diff::StateDiff;
 use std::fmt;
@@ -104,6 +105,17 @@ pub struct Executed {
 	pub vm_trace: Option<VMTrace>,
 	/// The state diff, if we traced it.
 	pub state_diff: Option<StateDiff>,
+	/// The gas used by each instruction, if we profiled it.
+	pub gas_profile: Option<GasProfile>,
+	/// The steps executed, if we recorded them.
+	pub struct_logs: Option<Vec<StructLog>>,
+}
+
+impl Executed {
+	/// The OR-wide combination of all logs' blooms, as stored in the transaction's receipt.
+	pub fn log_bloom(&self) -> LogBloom {
+		self.logs.iter().fold(LogBloom::default(), |b, l| &b | &l.bloom())
+	}
 }
 
 /// Result of executing the transaction.
@@ -194,6 +206,8 @@ pub enum CallError {
 	StateCorrupt,
 	/// Error executing.
 	Execution(ExecutionError),
+	/// Execution was reverted by `REVERT`; contains the revert data.
+	Reverted(Bytes),
 }
 
 impl From<ExecutionError> for CallError {
@@ -212,6 +226,7 @@ impl fmt::Display for CallError {
 			Exceptional => "An exception happened in the execution".into(),
 			StateCorrupt => "Stored state found to be corrupted.".into(),
 			Execution(ref e) => format!("{}", e),
+			Reverted(_) => "Execution reverted".into(),
 		};
 
 		f.write_fmt(format_args!("Transaction execution error ({}).", msg))
@@ -224,7 +239,11 @@ pub type ExecutionResult = Result<Executed, ExecutionError>;
 #[cfg(test)]
 mod tests {
 	use rlp::*;
-	use super::CallType;
+	use util::{Address, H256, Hashable};
+	use util::bloom::Bloomable;
+	use receipt::Receipt;
+	use types::log_entry::LogEntry;
+	use super::{CallType, Executed};
 
 	#[test]
 	fn encode_call_type() {
@@ -245,4 +264,31 @@ mod tests {
 		let decoded = decode(&encoded);
 		assert_eq!(original, decoded);
 	}
+
+	#[test]
+	fn log_bloom_matches_receipt() {
+		let logs = vec![
+			LogEntry { address: Address::from(1), topics: vec![H256::from(2)], data: vec![] },
+			LogEntry { address: Address::from(3), topics: vec![], data: vec![4] },
+		];
+		let executed = Executed {
+			exception: None,
+			gas: 0.into(),
+			gas_used: 0.into(),
+			refunded: 0.into(),
+			cumulative_gas_used: 0.into(),
+			logs: logs.clone(),
+			contracts_created: vec![],
+			output: vec![],
+			trace: vec![],
+			vm_trace: None,
+			state_diff: None,
+			gas_profile: None,
+			struct_logs: None,
+		};
+
+		let bloom = executed.log_bloom();
+		assert_eq!(bloom, Receipt::new(None, 0.into(), logs).log_bloom);
+		assert!(bloom.contains_bloomed(&Address::from(3).sha3()));
+	}
 }
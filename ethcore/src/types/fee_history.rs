@@ -0,0 +1,32 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Gas usage and gas prices of a range of blocks.
+
+use util::U256;
+use header::BlockNumber;
+
+/// Gas usage and gas price percentiles of consecutive blocks, oldest first.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FeeHistory {
+	/// Number of the oldest block of the range.
+	pub oldest_block: BlockNumber,
+	/// Ratio of the gas used to the gas limit of each block.
+	pub gas_used_ratio: Vec<f64>,
+	/// Gas prices of the transactions of each block at the requested percentiles,
+	/// zero for blocks without transactions.
+	pub gas_prices: Vec<Vec<U256>>,
+}
@@ -36,18 +36,25 @@ pub struct ManifestData {
 	pub block_number: u64,
 	/// Block hash this snapshot was taken at.
 	pub block_hash: H256,
+	/// List of chunk hashes of the traces of recent blocks. Optional.
+	pub trace_hashes: Vec<H256>,
 }
 
 impl ManifestData {
 	/// Encode the manifest data to rlp.
 	pub fn into_rlp(self) -> Bytes {
-		let mut stream = RlpStream::new_list(6);
+		// trace chunks are left out entirely when there are none, for older readers.
+		let with_traces = !self.trace_hashes.is_empty();
+		let mut stream = RlpStream::new_list(if with_traces { 7 } else { 6 });
 		stream.append(&self.version);
 		stream.append_list(&self.state_hashes);
 		stream.append_list(&self.block_hashes);
 		stream.append(&self.state_root);
 		stream.append(&self.block_number);
 		stream.append(&self.block_hash);
+		if with_traces {
+			stream.append_list(&self.trace_hashes);
+		}
 
 		stream.out()
 	}
@@ -66,6 +73,10 @@ impl ManifestData {
 		let state_root: H256 = decoder.val_at(start + 2)?;
 		let block_number: u64 = decoder.val_at(start + 3)?;
 		let block_hash: H256 = decoder.val_at(start + 4)?;
+		let trace_hashes: Vec<H256> = match decoder.item_count()? > start + 5 {
+			true => decoder.list_at(start + 5)?,
+			false => Vec::new(),
+		};
 
 		Ok(ManifestData {
 			version: version,
@@ -74,6 +85,7 @@ impl ManifestData {
 			state_root: state_root,
 			block_number: block_number,
 			block_hash: block_hash,
+			trace_hashes: trace_hashes,
 		})
 	}
 }
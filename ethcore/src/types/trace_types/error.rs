@@ -35,6 +35,10 @@ pub enum Error {
 	StackUnderflow,
 	/// When execution would exceed defined Stack Limit
 	OutOfStack,
+	/// When a call or creation would exceed the maximum call depth
+	OutOfDepth,
+	/// Execution was reverted by `REVERT`.
+	Reverted,
 	/// Returned on evm internal error. Should never be ignored during development.
 	/// Likely to cause consensus issues.
 	Internal,
@@ -48,6 +52,8 @@ impl<'a> From<&'a EvmError> for Error {
 			EvmError::BadInstruction { .. } => Error::BadInstruction,
 			EvmError::StackUnderflow { .. } => Error::StackUnderflow,
 			EvmError::OutOfStack { .. } => Error::OutOfStack,
+			EvmError::OutOfDepth { .. } => Error::OutOfDepth,
+			EvmError::Reverted { .. } => Error::Reverted,
 			EvmError::Internal(_) => Error::Internal,
 		}
 	}
@@ -68,7 +74,9 @@ impl fmt::Display for Error {
 			BadInstruction => "Bad instruction",
 			StackUnderflow => "Stack underflow",
 			OutOfStack => "Out of stack",
+			OutOfDepth => "Out of call depth",
 			Internal => "Internal error",
+			Reverted => "Reverted",
 		};
 		message.fmt(f)
 	}
@@ -84,6 +92,8 @@ impl Encodable for Error {
 			StackUnderflow => 3,
 			OutOfStack => 4,
 			Internal => 5,
+			Reverted => 6,
+			OutOfDepth => 7,
 		};
 
 		s.append_internal(&value);
@@ -101,6 +111,8 @@ impl Decodable for Error {
 			3 => Ok(StackUnderflow),
 			4 => Ok(OutOfStack),
 			5 => Ok(Internal),
+			6 => Ok(Reverted),
+			7 => Ok(OutOfDepth),
 			_ => Err(DecoderError::Custom("Invalid error type")),
 		}
 	}
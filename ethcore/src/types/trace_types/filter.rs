@@ -28,7 +28,7 @@ use types::trace_types::trace::{Action, Res};
 /// Addresses filter.
 ///
 /// Used to create bloom possibilities and match filters.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[cfg_attr(feature = "ipc", binary)]
 pub struct AddressesFilter {
 	list: Vec<Address>
@@ -51,6 +51,11 @@ impl AddressesFilter {
 		self.list.is_empty()
 	}
 
+	/// Returns the searched addresses.
+	pub fn addresses(&self) -> &[Address] {
+		&self.list
+	}
+
 	/// Returns blooms of this addresses filter.
 	pub fn blooms(&self) -> Vec<LogBloom> {
 		match self.list.is_empty() {
@@ -75,7 +80,7 @@ impl AddressesFilter {
 	}
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[cfg_attr(feature = "ipc", binary)]
 /// Traces filter.
 pub struct Filter {
@@ -16,9 +16,9 @@
 
 //! Flat trace module
 
-use std::collections::VecDeque;
+use std::collections::{HashSet, VecDeque};
 use rlp::*;
-use util::HeapSizeOf;
+use util::{Address, HeapSizeOf};
 use basic_types::LogBloom;
 use super::trace::{Action, Res};
 
@@ -45,6 +45,19 @@ impl FlatTrace {
 	pub fn bloom(&self) -> LogBloom {
 		self.action.bloom() | self.result.bloom()
 	}
+
+	/// Returns the addresses the bloom of the trace is made of.
+	pub fn addresses(&self) -> Vec<Address> {
+		let mut addresses = match self.action {
+			Action::Call(ref call) => vec![call.from.clone(), call.to.clone()],
+			Action::Create(ref create) => vec![create.from.clone()],
+			Action::Suicide(ref suicide) => vec![suicide.address.clone(), suicide.refund_address.clone()],
+		};
+		if let Res::Create(ref create) = self.result {
+			addresses.push(create.address.clone());
+		}
+		addresses
+	}
 }
 
 impl HeapSizeOf for FlatTrace {
@@ -139,6 +152,14 @@ impl FlatBlockTraces {
 	pub fn bloom(&self) -> LogBloom {
 		self.0.iter().fold(Default::default(), | bloom, tx_traces | bloom | tx_traces.bloom())
 	}
+
+	/// Returns every address appearing in the traces of the block.
+	pub fn addresses(&self) -> HashSet<Address> {
+		self.0.iter()
+			.flat_map(|tx_traces| tx_traces.0.iter())
+			.flat_map(|trace| trace.addresses())
+			.collect()
+	}
 }
 
 impl Encodable for FlatBlockTraces {
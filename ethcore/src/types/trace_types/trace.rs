@@ -16,6 +16,7 @@
 
 //! Tracing datatypes.
 
+use std::collections::BTreeMap;
 use util::{U256, Bytes, Address};
 use util::sha3::Hashable;
 use util::bloom::Bloomable;
@@ -557,3 +558,63 @@ impl Decodable for VMTrace {
 		Ok(res)
 	}
 }
+
+/// Gas used by the executions of a single instruction.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "ipc", binary)]
+pub struct OpcodeGas {
+	/// The instruction.
+	pub instruction: u8,
+	/// Number of times it was executed.
+	pub count: u64,
+	/// Total gas used by its executions.
+	pub gas: U256,
+}
+
+/// Gas used by each instruction executed by a transaction, including in nested calls.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "ipc", binary)]
+pub struct GasProfile {
+	/// The instructions executed, ordered by instruction.
+	pub instructions: Vec<OpcodeGas>,
+}
+
+/// Options of the step-by-step struct logger.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "ipc", binary)]
+pub struct StructLogOptions {
+	/// Record the stack at each step.
+	pub stack: bool,
+	/// Record the memory at each step.
+	pub memory: bool,
+	/// Record the storage touched so far at each step.
+	pub storage: bool,
+	/// Maximum number of steps to record; 0 for no limit.
+	pub limit: usize,
+	/// Maximum call depth to record steps at; 0 for no limit.
+	pub max_depth: usize,
+	/// `StepFilter` expression selecting the steps to record; all steps if none.
+	pub filter: Option<String>,
+}
+
+/// A single step of execution, as recorded by the struct logger.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "ipc", binary)]
+pub struct StructLog {
+	/// The program counter.
+	pub pc: usize,
+	/// The instruction.
+	pub instruction: u8,
+	/// Gas left before executing the instruction.
+	pub gas: U256,
+	/// Gas cost of the instruction.
+	pub gas_cost: U256,
+	/// Call depth, starting with 1 at the top level.
+	pub depth: usize,
+	/// The stack before executing the instruction, bottom first.
+	pub stack: Option<Vec<U256>>,
+	/// The memory before executing the instruction.
+	pub memory: Option<Bytes>,
+	/// The storage of the executing contract touched so far.
+	pub storage: Option<BTreeMap<U256, U256>>,
+}
@@ -20,7 +20,7 @@ use {json, Error, crypto};
 use crypto::Keccak256;
 use random::Random;
 use smallvec::SmallVec;
-use account::{Cipher, Kdf, Aes128Ctr, Pbkdf2, Prf};
+use account::{Cipher, Kdf, KdfParams, Aes128Ctr, Pbkdf2, Scrypt, Prf};
 
 /// Encrypted data
 #[derive(Debug, PartialEq, Clone)]
@@ -62,13 +62,25 @@ impl Crypto {
 		Crypto::with_plain(&*secret, password, iterations)
 	}
 
+	pub fn with_secret_kdf(secret: &Secret, password: &str, kdf: &KdfParams) -> Result<Self, Error> {
+		Crypto::with_plain_kdf(&*secret, password, kdf)
+	}
+
 	pub fn with_plain(plain: &[u8], password: &str, iterations: u32) -> Self {
+		Crypto::with_plain_kdf(plain, password, &KdfParams::Pbkdf2 { iterations: iterations })
+			.expect("PBKDF2 key derivation never fails; qed")
+	}
+
+	pub fn with_plain_kdf(plain: &[u8], password: &str, kdf: &KdfParams) -> Result<Self, Error> {
 		let salt: [u8; 32] = Random::random();
 		let iv: [u8; 16] = Random::random();
 
 		// two parts of derived key
 		// DK = [ DK[0..15] DK[16..31] ] = [derived_left_bits, derived_right_bits]
-		let (derived_left_bits, derived_right_bits) = crypto::derive_key_iterations(password, &salt, iterations);
+		let (derived_left_bits, derived_right_bits) = match *kdf {
+			KdfParams::Pbkdf2 { iterations } => crypto::derive_key_iterations(password, &salt, iterations),
+			KdfParams::Scrypt { n, p, r } => crypto::derive_key_scrypt(password, &salt, n, p, r)?,
+		};
 
 		// preallocated (on-stack in case of `Secret`) buffer to hold cipher
 		// length = length(plain) as we are using CTR-approach
@@ -83,19 +95,30 @@ impl Crypto {
 		// KECCAK(DK[16..31] ++ <ciphertext>), where DK[16..31] - derived_right_bits
 		let mac = crypto::derive_mac(&derived_right_bits, &*ciphertext).keccak256();
 
-		Crypto {
-			cipher: Cipher::Aes128Ctr(Aes128Ctr {
-				iv: iv,
-			}),
-			ciphertext: (*ciphertext).to_vec(),
-			kdf: Kdf::Pbkdf2(Pbkdf2 {
+		let kdf = match *kdf {
+			KdfParams::Pbkdf2 { iterations } => Kdf::Pbkdf2(Pbkdf2 {
 				dklen: crypto::KEY_LENGTH as u32,
 				salt: salt,
 				c: iterations,
 				prf: Prf::HmacSha256,
 			}),
+			KdfParams::Scrypt { n, p, r } => Kdf::Scrypt(Scrypt {
+				dklen: crypto::KEY_LENGTH as u32,
+				salt: salt,
+				n: n,
+				p: p,
+				r: r,
+			}),
+		};
+
+		Ok(Crypto {
+			cipher: Cipher::Aes128Ctr(Aes128Ctr {
+				iv: iv,
+			}),
+			ciphertext: (*ciphertext).to_vec(),
+			kdf: kdf,
 			mac: mac,
-		}
+		})
 	}
 
 	pub fn secret(&self, password: &str) -> Result<Secret, Error> {
@@ -144,6 +167,7 @@ impl Crypto {
 #[cfg(test)]
 mod tests {
 	use ethkey::{Generator, Random};
+	use account::{Kdf, KdfParams};
 	use super::Crypto;
 
 	#[test]
@@ -162,6 +186,26 @@ mod tests {
 		let _ = crypto.secret("this is sparta!").unwrap();
 	}
 
+	#[test]
+	fn crypto_with_secret_scrypt() {
+		let keypair = Random.generate().unwrap();
+		let kdf = KdfParams::Scrypt { n: 1024, p: 1, r: 8 };
+		let crypto = Crypto::with_secret_kdf(keypair.secret(), "this is sparta", &kdf).unwrap();
+		match crypto.kdf {
+			Kdf::Scrypt(ref params) => assert_eq!((params.n, params.p, params.r), (1024, 1, 8)),
+			_ => panic!("expected scrypt key derivation"),
+		}
+		let secret = crypto.secret("this is sparta").unwrap();
+		assert_eq!(keypair.secret(), &secret);
+	}
+
+	#[test]
+	fn crypto_with_invalid_scrypt_params() {
+		let keypair = Random.generate().unwrap();
+		let kdf = KdfParams::Scrypt { n: 1 << 20, p: 1, r: 1 };
+		assert!(Crypto::with_secret_kdf(keypair.secret(), "this is sparta", &kdf).is_err());
+	}
+
 	#[test]
 	fn crypto_with_null_plain_data() {
 		let original_data = b"";
@@ -44,6 +44,36 @@ pub enum Kdf {
 	Scrypt(Scrypt),
 }
 
+/// Key derivation function and its cost parameters used to encrypt new keys.
+#[derive(Debug, PartialEq, Clone)]
+pub enum KdfParams {
+	/// PBKDF2 with HMAC-SHA256.
+	Pbkdf2 {
+		/// Number of iterations.
+		iterations: u32,
+	},
+	/// Scrypt.
+	Scrypt {
+		/// CPU/memory cost, must be a power of two.
+		n: u32,
+		/// Parallelization factor.
+		p: u32,
+		/// Block size.
+		r: u32,
+	},
+}
+
+impl KdfParams {
+	/// Scrypt with the "standard" cost parameters used by other Ethereum clients.
+	pub fn scrypt() -> Self {
+		KdfParams::Scrypt {
+			n: 1 << 18,
+			p: 1,
+			r: 8,
+		}
+	}
+}
+
 impl From<json::Prf> for Prf {
 	fn from(json: json::Prf) -> Self {
 		match json {
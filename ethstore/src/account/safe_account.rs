@@ -16,7 +16,7 @@
 
 use ethkey::{KeyPair, sign, Address, Signature, Message, Public};
 use {json, Error, crypto};
-use account::Version;
+use account::{Version, KdfParams};
 use super::crypto::Crypto;
 
 /// Account representation.
@@ -72,6 +72,26 @@ impl SafeAccount {
 		}
 	}
 
+	/// Create a new account encrypted with given key derivation function.
+	pub fn create_with_kdf(
+		keypair: &KeyPair,
+		id: [u8; 16],
+		password: &str,
+		kdf: &KdfParams,
+		name: String,
+		meta: String
+	) -> Result<Self, Error> {
+		Ok(SafeAccount {
+			id: id,
+			version: Version::V3,
+			crypto: Crypto::with_secret_kdf(keypair.secret(), password, kdf)?,
+			address: keypair.address(),
+			filename: None,
+			name: name,
+			meta: meta,
+		})
+	}
+
 	/// Create a new `SafeAccount` from the given `json`; if it was read from a
 	/// file, the `filename` should be `Some` name. If it is as yet anonymous, then it
 	/// can be left `None`.
@@ -141,13 +161,13 @@ impl SafeAccount {
 		Ok(KeyPair::from_secret(secret)?.public().clone())
 	}
 
-	/// Change account's password.
-	pub fn change_password(&self, old_password: &str, new_password: &str, iterations: u32) -> Result<Self, Error> {
+	/// Change account's password, re-encrypting the key with given key derivation function.
+	pub fn change_password(&self, old_password: &str, new_password: &str, kdf: &KdfParams) -> Result<Self, Error> {
 		let secret = self.crypto.secret(old_password)?;
 		let result = SafeAccount {
 			id: self.id.clone(),
 			version: self.version.clone(),
-			crypto: Crypto::with_secret(&secret, new_password, iterations),
+			crypto: Crypto::with_secret_kdf(&secret, new_password, kdf)?,
 			address: self.address.clone(),
 			filename: self.filename.clone(),
 			name: self.name.clone(),
@@ -165,6 +185,7 @@ impl SafeAccount {
 #[cfg(test)]
 mod tests {
 	use ethkey::{Generator, Random, verify_public, Message};
+	use account::{Kdf, KdfParams};
 	use super::SafeAccount;
 
 	#[test]
@@ -185,10 +206,27 @@ mod tests {
 		let i = 10240;
 		let message = Message::default();
 		let account = SafeAccount::create(&keypair, [0u8; 16], first_password, i, "Test".to_owned(), "{}".to_owned());
-		let new_account = account.change_password(first_password, sec_password, i).unwrap();
+		let new_account = account.change_password(first_password, sec_password, &KdfParams::Pbkdf2 { iterations: i }).unwrap();
 		assert!(account.sign(first_password, &message).is_ok());
 		assert!(account.sign(sec_password, &message).is_err());
 		assert!(new_account.sign(first_password, &message).is_err());
 		assert!(new_account.sign(sec_password, &message).is_ok());
 	}
+
+	#[test]
+	fn reencrypt_with_scrypt() {
+		let keypair = Random.generate().unwrap();
+		let password = "hello world";
+		let message = Message::default();
+		let account = SafeAccount::create(&keypair, [1u8; 16], password, 1024, "Test".to_owned(), "{}".to_owned());
+		let kdf = KdfParams::Scrypt { n: 1024, p: 1, r: 8 };
+		let new_account = account.change_password(password, password, &kdf).unwrap();
+		assert_eq!(new_account.id, account.id);
+		assert_eq!(new_account.address, account.address);
+		match new_account.crypto.kdf {
+			Kdf::Scrypt(_) => {},
+			_ => panic!("expected scrypt key derivation"),
+		}
+		assert!(new_account.sign(password, &message).is_ok());
+	}
 }
@@ -23,7 +23,7 @@ use crypto::KEY_ITERATIONS;
 use random::Random;
 use ethkey::{self, Signature, Address, Message, Secret, Public, KeyPair, ExtendedKeyPair};
 use dir::{KeyDirectory, VaultKeyDirectory, VaultKey, SetKeyError};
-use account::SafeAccount;
+use account::{SafeAccount, KdfParams};
 use presale::PresaleWallet;
 use json::{self, Uuid, OpaqueKeyFile};
 use {import, Error, SimpleSecretStore, SecretStore, SecretVaultRef, StoreAccountRef, Derivation};
@@ -46,6 +46,13 @@ impl EthStore {
 		})
 	}
 
+	/// Open a new account store with given key directory backend and key derivation function for new keys.
+	pub fn open_with_kdf(directory: Box<KeyDirectory>, kdf: KdfParams) -> Result<Self, Error> {
+		Ok(EthStore {
+			store: EthMultiStore::open_with_kdf(directory, kdf)?,
+		})
+	}
+
 	fn get(&self, account: &StoreAccountRef) -> Result<SafeAccount, Error> {
 		let mut accounts = self.store.get_accounts(account)?.into_iter();
 		accounts.next().ok_or(Error::InvalidAccount)
@@ -122,6 +129,10 @@ impl SimpleSecretStore for EthStore {
 		self.store.list_opened_vaults()
 	}
 
+	fn test_vault_password(&self, name: &str, password: &str) -> Result<bool, Error> {
+		self.store.test_vault_password(name, password)
+	}
+
 	fn change_vault_password(&self, name: &str, new_password: &str) -> Result<(), Error> {
 		self.store.change_vault_password(name, new_password)
 	}
@@ -234,6 +245,7 @@ impl SecretStore for EthStore {
 pub struct EthMultiStore {
 	dir: Box<KeyDirectory>,
 	iterations: u32,
+	kdf: KdfParams,
 	// order lock: cache, then vaults
 	cache: RwLock<BTreeMap<StoreAccountRef, Vec<SafeAccount>>>,
 	vaults: Mutex<HashMap<String, Box<VaultKeyDirectory>>>,
@@ -248,10 +260,21 @@ impl EthMultiStore {
 
 	/// Open new multi-accounts store with given key directory backend and custom number of iterations for new keys.
 	pub fn open_with_iterations(directory: Box<KeyDirectory>, iterations: u32) -> Result<Self, Error> {
+		Self::open_with_kdf(directory, KdfParams::Pbkdf2 { iterations: iterations })
+	}
+
+	/// Open new multi-accounts store with given key directory backend and key derivation function for new keys.
+	/// Vault keys are always derived with PBKDF2.
+	pub fn open_with_kdf(directory: Box<KeyDirectory>, kdf: KdfParams) -> Result<Self, Error> {
+		let iterations = match kdf {
+			KdfParams::Pbkdf2 { iterations } => iterations,
+			KdfParams::Scrypt { .. } => KEY_ITERATIONS as u32,
+		};
 		let store = EthMultiStore {
 			dir: directory,
 			vaults: Mutex::new(HashMap::new()),
 			iterations: iterations,
+			kdf: kdf,
 			cache: Default::default(),
 			dir_hash: Default::default(),
 		};
@@ -408,7 +431,7 @@ impl SimpleSecretStore for EthMultiStore {
 	fn insert_account(&self, vault: SecretVaultRef, secret: Secret, password: &str) -> Result<StoreAccountRef, Error> {
 		let keypair = KeyPair::from_secret(secret).map_err(|_| Error::CreationFailed)?;
 		let id: [u8; 16] = Random::random();
-		let account = SafeAccount::create(&keypair, id, password, self.iterations, "".to_owned(), "{}".to_owned());
+		let account = SafeAccount::create_with_kdf(&keypair, id, password, &self.kdf, "".to_owned(), "{}".to_owned())?;
 		self.import(vault, account)
 	}
 
@@ -417,8 +440,13 @@ impl SimpleSecretStore for EthMultiStore {
 	{
 		let accounts = self.get_matching(account_ref, password)?;
 		for account in accounts {
+			// remember where the key came from so it can be restored from the parent secret
+			let meta = format!(r#"{{"derivation":{{"parent":"0x{:?}","path":"{}"}}}}"#, account_ref.address, derivation);
 			let extended = self.generate(account.crypto.secret(password)?, derivation)?;
-			return self.insert_account(vault, extended.secret().as_raw().clone(), password);
+			let keypair = KeyPair::from_secret(extended.secret().as_raw().clone()).map_err(|_| Error::CreationFailed)?;
+			let id: [u8; 16] = Random::random();
+			let derived = SafeAccount::create_with_kdf(&keypair, id, password, &self.kdf, "".to_owned(), meta)?;
+			return self.import(vault, derived);
 		}
 		Err(Error::InvalidPassword)
 	}
@@ -478,7 +506,7 @@ impl SimpleSecretStore for EthMultiStore {
 
 		for account in accounts {
 			// Change password
-			let new_account = account.change_password(old_password, new_password, self.iterations)?;
+			let new_account = account.change_password(old_password, new_password, &self.kdf)?;
 			self.update(account_ref, account, new_account)?;
 		}
 
@@ -562,6 +590,15 @@ impl SimpleSecretStore for EthMultiStore {
 		Ok(self.vaults.lock().keys().cloned().collect())
 	}
 
+	fn test_vault_password(&self, name: &str, password: &str) -> Result<bool, Error> {
+		let vault_provider = self.dir.as_vault_provider().ok_or(Error::VaultsAreNotSupported)?;
+		match vault_provider.open(name, VaultKey::new(password, self.iterations)) {
+			Ok(_) => Ok(true),
+			Err(Error::InvalidPassword) => Ok(false),
+			Err(e) => Err(e),
+		}
+	}
+
 	fn change_vault_password(&self, name: &str, new_password: &str) -> Result<(), Error> {
 		let old_key = self.vaults.lock().get(name).map(|v| v.key()).ok_or(Error::VaultNotFound)?;
 		let vault_provider = self.dir.as_vault_provider().ok_or(Error::VaultsAreNotSupported)?;
@@ -623,7 +660,7 @@ mod tests {
 
 	use dir::{KeyDirectory, MemoryDirectory, RootDiskDirectory};
 	use ethkey::{Random, Generator, KeyPair};
-	use secret_store::{SimpleSecretStore, SecretStore, SecretVaultRef, StoreAccountRef, Derivation};
+	use secret_store::{SimpleSecretStore, SecretStore, SecretVaultRef, StoreAccountRef, Derivation, IndexDerivation};
 	use super::{EthStore, EthMultiStore};
 	use devtools::RandomTempPath;
 	use util::H256;
@@ -1019,6 +1056,32 @@ mod tests {
 		assert!(store.sign(&derived, "test", &Default::default()).is_ok(), "Second password should work for second store.");
 	}
 
+	#[test]
+	fn should_record_derivation_path_in_meta() {
+		// given
+		let store = store();
+		let keypair = keypair();
+		let address = store.insert_account(SecretVaultRef::Root, keypair.secret().clone(), "test").unwrap();
+
+		// when
+		let derived = store.insert_derived(
+			SecretVaultRef::Root,
+			&address,
+			"test",
+			Derivation::Hierarchical(vec![
+				IndexDerivation { soft: false, index: 44 },
+				IndexDerivation { soft: true, index: 1 },
+			]),
+		).unwrap();
+
+		// then
+		assert_eq!(
+			store.meta(&derived).unwrap(),
+			format!(r#"{{"derivation":{{"parent":"0x{:?}","path":"m/44'/1"}}}}"#, address.address)
+		);
+		assert_eq!(store.meta(&address).unwrap(), "{}".to_owned());
+	}
+
 	#[test]
 	fn should_save_meta_when_setting_before_password() {
 		// given
@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::path::PathBuf;
 use ethkey::{Address, Message, Signature, Secret, Public};
@@ -76,6 +77,8 @@ pub trait SimpleSecretStore: Send + Sync {
 	fn list_vaults(&self) -> Result<Vec<String>, Error>;
 	/// List all currently opened vaults
 	fn list_opened_vaults(&self) -> Result<Vec<String>, Error>;
+	/// Checks if password matches given vault, whether it is opened or not.
+	fn test_vault_password(&self, name: &str, password: &str) -> Result<bool, Error>;
 	/// Change vault password
 	fn change_vault_password(&self, name: &str, new_password: &str) -> Result<(), Error>;
 	/// Cnage account' vault
@@ -163,3 +166,19 @@ pub enum Derivation {
 	/// Hash derivation, hard.
 	HardHash(H256),
 }
+
+impl fmt::Display for Derivation {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			Derivation::Hierarchical(ref path) => {
+				write!(f, "m")?;
+				for item in path {
+					write!(f, "/{}{}", item.index, if item.soft { "" } else { "'" })?;
+				}
+				Ok(())
+			},
+			Derivation::SoftHash(ref hash) => write!(f, "soft:0x{:?}", hash),
+			Derivation::HardHash(ref hash) => write!(f, "hard:0x{:?}", hash),
+		}
+	}
+}
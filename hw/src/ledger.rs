@@ -52,6 +52,16 @@ pub enum KeyPath {
 	EthereumClassic,
 }
 
+impl KeyPath {
+	/// BIP32 derivation path in the big-endian encoding expected by the Ethereum app.
+	fn derivation_path(&self) -> &'static [u8] {
+		match *self {
+			KeyPath::Ethereum => &ETH_DERIVATION_PATH_BE[..],
+			KeyPath::EthereumClassic => &ETC_DERIVATION_PATH_BE[..],
+		}
+	}
+}
+
 /// Hardware waller error.
 #[derive(Debug)]
 pub enum Error {
@@ -166,13 +176,7 @@ impl Manager {
 			return Err(Error::Protocol("App version 1.0.3 is required."));
 		}
 
-		let eth_path = &ETH_DERIVATION_PATH_BE[..];
-		let etc_path = &ETC_DERIVATION_PATH_BE[..];
-		let derivation_path = match key_path {
-			KeyPath::Ethereum => eth_path,
-			KeyPath::EthereumClassic => etc_path,
-		};
-		let key_and_address = Self::send_apdu(handle, commands::GET_ETH_PUBLIC_ADDRESS, 0, 0, derivation_path)?;
+		let key_and_address = Self::send_apdu(handle, commands::GET_ETH_PUBLIC_ADDRESS, 0, 0, key_path.derivation_path())?;
 		if key_and_address.len() != 107 { // 1 + 65 PK + 1 + 40 Addr (ascii-hex)
 			return Err(Error::Protocol("Key packet size mismatch"));
 		}
@@ -202,12 +206,7 @@ impl Manager {
 
 		let handle = self.open_path(&device.path)?;
 
-		let eth_path = &ETH_DERIVATION_PATH_BE[..];
-		let etc_path = &ETC_DERIVATION_PATH_BE[..];
-		let derivation_path = match self.key_path {
-			KeyPath::Ethereum => eth_path,
-			KeyPath::EthereumClassic => etc_path,
-		};
+		let derivation_path = self.key_path.derivation_path();
 		const MAX_CHUNK_SIZE: usize = 255;
 		let mut chunk: [u8; MAX_CHUNK_SIZE] = [0; MAX_CHUNK_SIZE];
 		&mut chunk[0..derivation_path.len()].copy_from_slice(derivation_path);
@@ -49,6 +49,8 @@ macro_rules! impl_serialization {
 
 mod account_meta;
 mod dapps_settings;
+mod vault_policy;
 
 pub use self::dapps_settings::{DappsSettings, DappsHistory, NewDappsPolicy};
+pub use self::vault_policy::VaultPolicy;
 pub use self::account_meta::AccountMeta;
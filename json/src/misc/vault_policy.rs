@@ -0,0 +1,33 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Vault policy de/serialization.
+
+use hash;
+use uint;
+
+/// Restrictions on signing with accounts stored in a vault.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct VaultPolicy {
+	/// Maximal value of a single transaction.
+	pub max_value: Option<uint::Uint>,
+	/// Recipients transactions are allowed to.
+	pub allowed_recipients: Option<Vec<hash::Address>>,
+	/// Signing operations that are allowed.
+	pub allowed_methods: Option<Vec<String>>,
+}
+
+impl_serialization!(String => VaultPolicy);
@@ -50,12 +50,25 @@ pub enum Pricing {
 pub struct Builtin {
 	/// Builtin name.
 	pub name: String,
+	/// Native implementations run in turn, each on the output of the previous one.
+	/// The one named like the builtin if none.
+	pub natives: Option<Vec<String>>,
 	/// Builtin pricing.
 	pub pricing: Pricing,
 	/// Activation block.
 	pub activate_at: Option<Uint>,
 }
 
+impl Builtin {
+	/// Names of the native implementations the builtin runs.
+	pub fn natives(&self) -> Vec<&str> {
+		match self.natives {
+			Some(ref natives) => natives.iter().map(|n| n.as_str()).collect(),
+			None => vec![self.name.as_str()],
+		}
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use serde_json;
@@ -72,6 +85,20 @@ mod tests {
 		assert_eq!(deserialized.name, "ecrecover");
 		assert_eq!(deserialized.pricing, Pricing::Linear(Linear { base: 3000, word: 0 }));
 		assert!(deserialized.activate_at.is_none());
+		assert_eq!(deserialized.natives(), vec!["ecrecover"]);
+	}
+
+	#[test]
+	fn natives() {
+		let s = r#"{
+			"name": "hash160",
+			"natives": ["sha256", "ripemd160"],
+			"pricing": { "linear": { "base": 720, "word": 132 } }
+		}"#;
+
+		let deserialized: Builtin = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.name, "hash160");
+		assert_eq!(deserialized.natives(), vec!["sha256", "ripemd160"]);
 	}
 
 	#[test]
@@ -40,7 +40,7 @@ pub use self::seal::{Seal, Ethereum, AuthorityRoundSeal, TendermintSeal};
 pub use self::engine::Engine;
 pub use self::state::State;
 pub use self::ethash::{Ethash, EthashParams};
-pub use self::validator_set::ValidatorSet;
+pub use self::validator_set::{ValidatorSet, EpochContract};
 pub use self::instant_seal::{InstantSeal, InstantSealParams};
 pub use self::basic_authority::{BasicAuthority, BasicAuthorityParams};
 pub use self::authority_round::{AuthorityRound, AuthorityRoundParams};
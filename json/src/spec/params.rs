@@ -54,6 +54,18 @@ pub struct Params {
 	#[serde(rename="eip98Transition")]
 	pub eip98_transition: Option<Uint>,
 	/// See `CommonParams` docs.
+	#[serde(rename="eip140Transition")]
+	pub eip140_transition: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(rename="eip1014Transition")]
+	pub eip1014_transition: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(rename="maxCallDepth")]
+	pub max_call_depth: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(rename="stackLimit")]
+	pub stack_limit: Option<Uint>,
+	/// See `CommonParams` docs.
 	#[serde(rename="validateReceiptsTransition")]
 	pub validate_receipts_transition: Option<Uint>,
 }
@@ -61,6 +73,7 @@ pub struct Params {
 #[cfg(test)]
 mod tests {
 	use serde_json;
+	use uint::Uint;
 	use spec::params::Params;
 
 	#[test]
@@ -72,10 +85,14 @@ mod tests {
 			"chainID" : "0x15",
 			"subprotocolName" : "exp",
 			"minGasLimit": "0x1388",
-			"accountStartNonce": "0x00"
+			"accountStartNonce": "0x00",
+			"maxCallDepth": "0x10",
+			"stackLimit": "0x20"
 		}"#;
 
-		let _deserialized: Params = serde_json::from_str(s).unwrap();
+		let deserialized: Params = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.max_call_depth, Some(Uint(16.into())));
+		assert_eq!(deserialized.stack_limit, Some(Uint(32.into())));
 		// TODO: validate all fields
 	}
 }
@@ -35,6 +35,19 @@ pub enum ValidatorSet {
 	/// A map of starting blocks for each validator set.
 	#[serde(rename="multi")]
 	Multi(BTreeMap<Uint, ValidatorSet>),
+	/// Address of a contract that indicates the list of authorities, read only at epoch transitions.
+	#[serde(rename="epochContract")]
+	EpochContract(EpochContract),
+}
+
+/// Validator set contract read at epoch transitions.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct EpochContract {
+	/// Address of the contract.
+	pub address: Address,
+	/// Number of blocks in an epoch. Changes of the set take effect at the next multiple of it.
+	#[serde(rename="epochLength")]
+	pub epoch_length: Uint,
 }
 
 #[cfg(test)]
@@ -56,6 +69,11 @@ mod tests {
 				"10": { "list": ["0xd6d9d2cd449a754c494264e1809c50e34d64562b"] },
 				"20": { "contract": "0xc6d9d2cd449a754c494264e1809c50e34d64562b" }
 			}
+		}, {
+			"epochContract": {
+				"address": "0xc6d9d2cd449a754c494264e1809c50e34d64562b",
+				"epochLength": "0x7530"
+			}
 		}]"#;
 
 		let _deserialized: Vec<ValidatorSet> = serde_json::from_str(s).unwrap();
@@ -18,7 +18,7 @@
 
 use std::fmt;
 use std::str::FromStr;
-use serde::{Deserialize, Deserializer};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde::de::{Error, Visitor};
 use util::{U256, Uint as U};
 
@@ -50,6 +50,13 @@ impl Into<u8> for Uint {
 	}
 }
 
+impl Serialize for Uint {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+		where S: Serializer {
+		serializer.serialize_str(&format!("0x{}", self.0.to_hex()))
+	}
+}
+
 impl Deserialize for Uint {
 	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
 		where D: Deserializer {
@@ -109,6 +116,14 @@ mod test {
 		]);
 	}
 
+	#[test]
+	fn uint_serialization() {
+		let serialized = serde_json::to_string(&Uint(U256::from(255))).unwrap();
+		assert_eq!(serialized, r#""0xff""#);
+		let deserialized: Uint = serde_json::from_str(&serialized).unwrap();
+		assert_eq!(deserialized, Uint(U256::from(255)));
+	}
+
 	#[test]
 	fn uint_into() {
 		assert_eq!(U256::from(10), Uint(U256::from(10)).into());
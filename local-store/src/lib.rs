@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-//! Manages local node data: pending local transactions, sync security level
+//! Manages local node data: pending local and queued transactions, sync security level
 
 use std::sync::Arc;
 use std::fmt;
@@ -45,6 +45,7 @@ extern crate log;
 extern crate ethkey;
 
 const LOCAL_TRANSACTIONS_KEY: &'static [u8] = &*b"LOCAL_TXS";
+const QUEUED_TRANSACTIONS_KEY: &'static [u8] = &*b"QUEUED_TXS";
 
 const UPDATE_TIMER: ::io::TimerToken = 0;
 const UPDATE_TIMEOUT_MS: u64 = 15 * 60 * 1000; // once every 15 minutes.
@@ -131,6 +132,11 @@ impl From<PendingTransaction> for TransactionEntry {
 pub trait NodeInfo: Send + Sync {
 	/// Get all pending transactions of local origin.
 	fn pending_transactions(&self) -> Vec<PendingTransaction>;
+
+	/// Get other transactions waiting in the queue which should survive a restart.
+	fn queued_transactions(&self) -> Vec<PendingTransaction> {
+		Vec::new()
+	}
 }
 
 /// Create a new local data store, given a database, a column to write to, and a node.
@@ -156,35 +162,50 @@ pub struct LocalDataStore<T: NodeInfo> {
 impl<T: NodeInfo> LocalDataStore<T> {
 	/// Attempt to read pending transactions out of the local store.
 	pub fn pending_transactions(&self) -> Result<Vec<PendingTransaction>, Error> {
-		if let Some(val) = self.db.get(self.col, LOCAL_TRANSACTIONS_KEY).map_err(Error::Database)? {
-			let local_txs: Vec<_> = ::serde_json::from_slice::<Vec<TransactionEntry>>(&val)
+		self.read_transactions(LOCAL_TRANSACTIONS_KEY)
+	}
+
+	/// Attempt to read other queued transactions out of the local store.
+	pub fn queued_transactions(&self) -> Result<Vec<PendingTransaction>, Error> {
+		self.read_transactions(QUEUED_TRANSACTIONS_KEY)
+	}
+
+	/// Update the entries in the database.
+	pub fn update(&self) -> Result<(), Error> {
+		trace!(target: "local_store", "Updating local store entries.");
+
+		let mut batch = self.db.transaction();
+
+		let local_json = Self::transactions_json(self.node.pending_transactions())?;
+		let queued_json = Self::transactions_json(self.node.queued_transactions())?;
+
+		batch.put_vec(self.col, LOCAL_TRANSACTIONS_KEY, local_json.into_bytes());
+		batch.put_vec(self.col, QUEUED_TRANSACTIONS_KEY, queued_json.into_bytes());
+		self.db.write(batch).map_err(Error::Database)
+	}
+
+	fn read_transactions(&self, key: &[u8]) -> Result<Vec<PendingTransaction>, Error> {
+		if let Some(val) = self.db.get(self.col, key).map_err(Error::Database)? {
+			let txs: Vec<_> = ::serde_json::from_slice::<Vec<TransactionEntry>>(&val)
 				.map_err(Error::Json)?
 				.into_iter()
 				.filter_map(TransactionEntry::into_pending)
 				.collect();
 
-			Ok(local_txs)
+			Ok(txs)
 		} else {
 			Ok(Vec::new())
 		}
 	}
 
-	/// Update the entries in the database.
-	pub fn update(&self) -> Result<(), Error> {
-		trace!(target: "local_store", "Updating local store entries.");
-
-		let mut batch = self.db.transaction();
-
-		let local_entries: Vec<TransactionEntry> = self.node.pending_transactions()
+	fn transactions_json(transactions: Vec<PendingTransaction>) -> Result<String, Error> {
+		let entries: Vec<TransactionEntry> = transactions
 			.into_iter()
 			.map(Into::into)
 			.collect();
 
-		let local_json = ::serde_json::to_value(&local_entries).map_err(Error::Json)?;
-		let json_str = format!("{}", local_json);
-
-		batch.put_vec(self.col, LOCAL_TRANSACTIONS_KEY, json_str.into_bytes());
-		self.db.write(batch).map_err(Error::Database)
+		let json = ::serde_json::to_value(&entries).map_err(Error::Json)?;
+		Ok(format!("{}", json))
 	}
 }
 
@@ -312,4 +333,39 @@ mod tests {
 			assert_eq!(loaded, transactions);
 		}
 	}
+
+	#[test]
+	fn stores_queued_transactions_separately() {
+		struct DummyQueue(Vec<PendingTransaction>);
+		impl NodeInfo for DummyQueue {
+			fn pending_transactions(&self) -> Vec<PendingTransaction> { Vec::new() }
+			fn queued_transactions(&self) -> Vec<PendingTransaction> { self.0.clone() }
+		}
+
+		let keypair = Brain::new("abcd".into()).generate().unwrap();
+		let transactions: Vec<_> = (0..3u64).map(|nonce| {
+			let mut tx = Transaction::default();
+			tx.nonce = nonce.into();
+
+			PendingTransaction::new(tx.sign(keypair.secret(), None), None)
+		}).collect();
+
+		let db = Arc::new(::util::kvdb::in_memory(0));
+		{
+			// nothing written yet, will write queued.
+			let store = super::create(db.clone(), None, DummyQueue(transactions.clone()));
+			assert_eq!(store.queued_transactions().unwrap(), vec![])
+		}
+		{
+			// queued written, local ones untouched.
+			let store = super::create(db.clone(), None, Dummy(vec![]));
+			assert_eq!(store.queued_transactions().unwrap(), transactions);
+			assert_eq!(store.pending_transactions().unwrap(), vec![])
+		}
+		{
+			// queued removed by a node without queue.
+			let store = super::create(db.clone(), None, Dummy(vec![]));
+			assert_eq!(store.queued_transactions().unwrap(), vec![])
+		}
+	}
 }
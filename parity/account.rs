@@ -15,17 +15,18 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::path::PathBuf;
-use ethcore::ethstore::{EthStore, SecretStore, import_accounts, read_geth_accounts};
+use ethcore::ethstore::{EthStore, SimpleSecretStore, SecretStore, KdfParams, import_accounts, read_geth_accounts};
 use ethcore::ethstore::dir::RootDiskDirectory;
 use ethcore::ethstore::SecretVaultRef;
 use ethcore::account_provider::{AccountProvider, AccountProviderSettings};
-use helpers::{password_prompt, password_from_file};
+use helpers::{password_prompt, password_from_file, passwords_from_files};
 use params::SpecType;
 
 #[derive(Debug, PartialEq)]
 pub enum AccountCmd {
 	New(NewAccount),
 	List(ListAccounts),
+	Reencrypt(ReencryptAccounts),
 	Import(ImportAccounts),
 	ImportFromGeth(ImportFromGethAccounts)
 }
@@ -38,12 +39,22 @@ pub struct ListAccounts {
 
 #[derive(Debug, PartialEq)]
 pub struct NewAccount {
-	pub iterations: u32,
+	pub kdf: KdfParams,
 	pub path: String,
 	pub spec: SpecType,
 	pub password_file: Option<String>,
 }
 
+/// Parameters for re-encrypting existing keys with a new key derivation function.
+#[derive(Debug, PartialEq)]
+pub struct ReencryptAccounts {
+	pub kdf: KdfParams,
+	pub path: String,
+	pub spec: SpecType,
+	/// files with passwords of the accounts to re-encrypt
+	pub password_files: Vec<String>,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct ImportAccounts {
 	pub from: Vec<String>,
@@ -65,6 +76,7 @@ pub fn execute(cmd: AccountCmd) -> Result<String, String> {
 	match cmd {
 		AccountCmd::New(new_cmd) => new(new_cmd),
 		AccountCmd::List(list_cmd) => list(list_cmd),
+		AccountCmd::Reencrypt(reencrypt_cmd) => reencrypt(reencrypt_cmd),
 		AccountCmd::Import(import_cmd) => import(import_cmd),
 		AccountCmd::ImportFromGeth(import_geth_cmd) => import_geth(import_geth_cmd)
 	}
@@ -77,9 +89,9 @@ fn keys_dir(path: String, spec: SpecType) -> Result<RootDiskDirectory, String> {
 	RootDiskDirectory::create(path).map_err(|e| format!("Could not open keys directory: {}", e))
 }
 
-fn secret_store(dir: Box<RootDiskDirectory>, iterations: Option<u32>) -> Result<EthStore, String> {
-	match iterations {
-		Some(i) => EthStore::open_with_iterations(dir, i),
+fn secret_store(dir: Box<RootDiskDirectory>, kdf: Option<KdfParams>) -> Result<EthStore, String> {
+	match kdf {
+		Some(kdf) => EthStore::open_with_kdf(dir, kdf),
 		_ => EthStore::open(dir) 
 	}.map_err(|e| format!("Could not open keys store: {}", e))
 }
@@ -91,7 +103,7 @@ fn new(n: NewAccount) -> Result<String, String> {
 	};
 
 	let dir = Box::new(keys_dir(n.path, n.spec)?);
-	let secret_store = Box::new(secret_store(dir, Some(n.iterations))?);
+	let secret_store = Box::new(secret_store(dir, Some(n.kdf))?);
 	let acc_provider = AccountProvider::new(secret_store, AccountProviderSettings::default());
 	let new_account = acc_provider.new_account(&password).map_err(|e| format!("Could not create new account: {}", e))?;
 	Ok(format!("{:?}", new_account))
@@ -110,6 +122,33 @@ fn list(list_cmd: ListAccounts) -> Result<String, String> {
 	Ok(result)
 }
 
+fn reencrypt(r: ReencryptAccounts) -> Result<String, String> {
+	let passwords = passwords_from_files(&r.password_files)?;
+	if passwords.is_empty() {
+		return Err("Provide account passwords with --password to re-encrypt keys.".into());
+	}
+
+	let dir = Box::new(keys_dir(r.path, r.spec)?);
+	let secret_store = secret_store(dir, Some(r.kdf))?;
+	let accounts = secret_store.accounts().map_err(|e| format!("Could not list accounts: {}", e))?;
+	let mut reencrypted = 0;
+	let mut skipped = Vec::new();
+	for account in &accounts {
+		// keys are rewritten in place, so id and address are preserved
+		if passwords.iter().any(|p| secret_store.change_password(account, p, p).is_ok()) {
+			reencrypted += 1;
+		} else {
+			skipped.push(format!("{:?}", account.address));
+		}
+	}
+
+	let mut result = format!("{} account(s) re-encrypted", reencrypted);
+	if !skipped.is_empty() {
+		result.push_str(&format!("\nNo matching password for: {}", skipped.join(", ")));
+	}
+	Ok(result)
+}
+
 fn import(i: ImportAccounts) -> Result<String, String> {
 	let to = keys_dir(i.to, i.spec)?;
 	let mut imported = 0;
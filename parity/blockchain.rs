@@ -20,12 +20,15 @@ use std::io::{BufReader, BufRead};
 use std::time::{Instant, Duration};
 use std::thread::sleep;
 use std::sync::Arc;
+use std::path::{Path, PathBuf};
 use rustc_serialize::hex::FromHex;
 use io::{PanicHandler, ForwardPanic};
 use util::{ToPretty, Uint, U256, H256, Address, Hashable};
+use util::kvdb::{Database, DatabaseConfig};
 use rlp::PayloadInfo;
 use ethcore::service::ClientService;
 use ethcore::client::{Mode, DatabaseCompactionProfile, VMType, BlockImportError, BlockChainClient, BlockId};
+use ethcore::db;
 use ethcore::error::ImportError;
 use ethcore::miner::Miner;
 use ethcore::verification::queue::VerifierSettings;
@@ -36,6 +39,7 @@ use helpers::{to_client_config, execute_upgrades};
 use dir::Directories;
 use user_defaults::UserDefaults;
 use fdlimit;
+use migration;
 
 #[derive(Debug, PartialEq)]
 pub enum DataFormat {
@@ -64,6 +68,11 @@ impl FromStr for DataFormat {
 #[derive(Debug, PartialEq)]
 pub enum BlockchainCmd {
 	Kill(KillBlockchain),
+	Migrate(MigrateBlockchain),
+	Backup(DatabaseBackup),
+	Restore(DatabaseBackup),
+	Verify(VerifyBlockchain),
+	Stats(DatabaseStats),
 	Import(ImportBlockchain),
 	Export(ExportBlockchain),
 	ExportState(ExportState),
@@ -76,6 +85,54 @@ pub struct KillBlockchain {
 	pub pruning: Pruning,
 }
 
+#[derive(Debug, PartialEq)]
+pub struct MigrateBlockchain {
+	pub spec: SpecType,
+	pub dirs: Directories,
+	pub pruning: Pruning,
+	pub compaction: DatabaseCompactionProfile,
+	pub dry_run: bool,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct DatabaseBackup {
+	pub spec: SpecType,
+	pub dirs: Directories,
+	pub pruning: Pruning,
+	pub path: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct VerifyBlockchain {
+	pub spec: SpecType,
+	pub cache_config: CacheConfig,
+	pub dirs: Directories,
+	pub pruning: Pruning,
+	pub pruning_history: u64,
+	pub pruning_memory: usize,
+	pub compaction: DatabaseCompactionProfile,
+	pub wal: bool,
+	pub tracing: Switch,
+	pub fat_db: Switch,
+	pub from_block: BlockId,
+	pub to_block: BlockId,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct DatabaseStats {
+	pub spec: SpecType,
+	pub cache_config: CacheConfig,
+	pub dirs: Directories,
+	pub pruning: Pruning,
+	pub pruning_history: u64,
+	pub pruning_memory: usize,
+	pub compaction: DatabaseCompactionProfile,
+	pub wal: bool,
+	pub tracing: Switch,
+	pub fat_db: Switch,
+	pub at: BlockId,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct ImportBlockchain {
 	pub spec: SpecType,
@@ -139,6 +196,11 @@ pub struct ExportState {
 pub fn execute(cmd: BlockchainCmd) -> Result<(), String> {
 	match cmd {
 		BlockchainCmd::Kill(kill_cmd) => kill_db(kill_cmd),
+		BlockchainCmd::Migrate(migrate_cmd) => migrate_db(migrate_cmd),
+		BlockchainCmd::Backup(backup_cmd) => backup_db(backup_cmd),
+		BlockchainCmd::Restore(restore_cmd) => restore_db(restore_cmd),
+		BlockchainCmd::Verify(verify_cmd) => verify_db(verify_cmd),
+		BlockchainCmd::Stats(stats_cmd) => db_stats(stats_cmd),
 		BlockchainCmd::Import(import_cmd) => execute_import(import_cmd),
 		BlockchainCmd::Export(export_cmd) => execute_export(export_cmd),
 		BlockchainCmd::ExportState(export_cmd) => execute_export_state(export_cmd),
@@ -432,6 +494,68 @@ fn execute_export(cmd: ExportBlockchain) -> Result<(), String> {
 	Ok(())
 }
 
+fn verify_db(cmd: VerifyBlockchain) -> Result<(), String> {
+	let service = start_client(
+		cmd.dirs,
+		cmd.spec,
+		cmd.pruning,
+		cmd.pruning_history,
+		cmd.pruning_memory,
+		cmd.tracing,
+		cmd.fat_db,
+		cmd.compaction,
+		cmd.wal,
+		cmd.cache_config
+	)?;
+	let panic_handler = PanicHandler::new_in_arc();
+
+	panic_handler.forward_from(&service);
+	let client = service.client();
+
+	let from = client.block_number(cmd.from_block).ok_or("From block could not be found")?;
+	let to = client.block_number(cmd.to_block).ok_or("To block could not be found")?;
+
+	info!("Verifying blocks #{} to #{}", from, to);
+	let result = client.verify_database(from, to, |number| if number % 10000 == 0 {
+		info!("#{}", number);
+	});
+
+	match result {
+		Ok(()) => {
+			info!("Database verified, no corruption found.");
+			Ok(())
+		},
+		Err((number, reason)) => Err(format!("Block #{} is corrupt: {}. Blocks from #{} on should be re-imported.", number, reason, number)),
+	}
+}
+
+fn db_stats(cmd: DatabaseStats) -> Result<(), String> {
+	let service = start_client(
+		cmd.dirs,
+		cmd.spec,
+		cmd.pruning,
+		cmd.pruning_history,
+		cmd.pruning_memory,
+		cmd.tracing,
+		cmd.fat_db,
+		cmd.compaction,
+		cmd.wal,
+		cmd.cache_config
+	)?;
+	let panic_handler = PanicHandler::new_in_arc();
+
+	panic_handler.forward_from(&service);
+	let client = service.client();
+
+	let number = client.block_number(cmd.at).ok_or("Block could not be found")?;
+	info!("Collecting trie statistics at block #{}", number);
+	let (accounts, storage) = client.trie_stats(cmd.at)?;
+
+	println!("Account trie:\n{}", accounts);
+	println!("Storage tries:\n{}", storage);
+	Ok(())
+}
+
 fn execute_export_state(cmd: ExportState) -> Result<(), String> {
 	// Setup panic handler
 	let service = start_client(
@@ -537,6 +661,79 @@ pub fn kill_db(cmd: KillBlockchain) -> Result<(), String> {
 	Ok(())
 }
 
+pub fn migrate_db(cmd: MigrateBlockchain) -> Result<(), String> {
+	let spec = cmd.spec.spec()?;
+	let genesis_hash = spec.genesis_header().hash();
+	let db_dirs = cmd.dirs.database(genesis_hash, None, spec.data_dir);
+	let user_defaults_path = db_dirs.user_defaults_path();
+	let user_defaults = UserDefaults::load(&user_defaults_path)?;
+	let algorithm = cmd.pruning.to_algorithm(&user_defaults);
+	let compaction = cmd.compaction.compaction_profile(db_dirs.db_root_path().as_path());
+
+	if !cmd.dry_run {
+		execute_upgrades(&cmd.dirs.base, &db_dirs, algorithm, compaction)?;
+		info!("Database migrated.");
+		return Ok(());
+	}
+
+	match migration::estimate(&db_dirs.db_path(algorithm), algorithm, compaction).map_err(|e| format!("{}", e))? {
+		None => info!("Database is up to date."),
+		Some(estimate) => info!(
+			"Migration would run {} migrations, rewriting {} keys ({} MB) in about {} minutes and needing {} MB of additional disk space.",
+			estimate.migrations,
+			estimate.keys,
+			estimate.bytes / (1024 * 1024),
+			(estimate.duration.as_secs() + 59) / 60,
+			estimate.space / (1024 * 1024)
+		),
+	}
+	Ok(())
+}
+
+fn client_db_path(spec: &SpecType, dirs: &Directories, pruning: &Pruning) -> Result<PathBuf, String> {
+	let spec = spec.spec()?;
+	let genesis_hash = spec.genesis_header().hash();
+	let db_dirs = dirs.database(genesis_hash, None, spec.data_dir);
+	let user_defaults = UserDefaults::load(&db_dirs.user_defaults_path())?;
+	let algorithm = pruning.to_algorithm(&user_defaults);
+	Ok(db_dirs.client_path(algorithm))
+}
+
+fn open_client_db(path: &Path) -> Result<Database, String> {
+	let config = DatabaseConfig::with_columns(db::NUM_COLUMNS);
+	let path = path.to_str().ok_or_else(|| "Invalid database path".to_owned())?;
+	Database::open(&config, path)
+}
+
+pub fn backup_db(cmd: DatabaseBackup) -> Result<(), String> {
+	let db_path = client_db_path(&cmd.spec, &cmd.dirs, &cmd.pruning)?;
+	let db = open_client_db(&db_path).map_err(|e| format!("Cannot open database: {}. \
+		The database of a running node can be backed up with the parity_backupDatabase RPC.", e))?;
+
+	db.backup(&cmd.path)?;
+	info!("Database backed up to {}.", cmd.path);
+	Ok(())
+}
+
+pub fn restore_db(cmd: DatabaseBackup) -> Result<(), String> {
+	if !Path::new(&cmd.path).is_dir() {
+		return Err(format!("No database backup at {}", cmd.path));
+	}
+
+	let db_path = client_db_path(&cmd.spec, &cmd.dirs, &cmd.pruning)?;
+	let db = open_client_db(&db_path).map_err(|e| format!("Cannot open database: {}. Is parity running?", e))?;
+
+	// copy the backup aside first, so it survives being moved into place.
+	let staging = db_path.with_file_name("restore_db");
+	let _ = fs::remove_dir_all(&staging);
+	let staging = staging.to_str().ok_or_else(|| "Invalid database path".to_owned())?.to_owned();
+	open_client_db(Path::new(&cmd.path))?.backup(&staging)?;
+
+	db.restore(&staging).map_err(|e| format!("Error restoring database: {}", e))?;
+	info!("Database restored from {}.", cmd.path);
+	Ok(())
+}
+
 #[cfg(test)]
 mod test {
 	use super::DataFormat;
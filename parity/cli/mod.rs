@@ -26,6 +26,7 @@ usage! {
 		cmd_account: bool,
 		cmd_new: bool,
 		cmd_list: bool,
+		cmd_reencrypt: bool,
 		cmd_export: bool,
 		cmd_blocks: bool,
 		cmd_state: bool,
@@ -41,6 +42,10 @@ usage! {
 		cmd_tools: bool,
 		cmd_hash: bool,
 		cmd_kill: bool,
+		cmd_migrate: bool,
+		cmd_backup: bool,
+		cmd_verify: bool,
+		cmd_stats: bool,
 		cmd_db: bool,
 
 		// Arguments
@@ -101,6 +106,8 @@ usage! {
 			or |c: &Config| otry!(c.account).password.clone(),
 		flag_keys_iterations: u32 = 10240u32,
 			or |c: &Config| otry!(c.account).keys_iterations.clone(),
+		flag_keys_kdf: String = "pbkdf2",
+			or |c: &Config| otry!(c.account).keys_kdf.clone(),
 		flag_no_hardware_wallets: bool = false,
 			or |c: &Config| otry!(c.account).disable_hardware.clone(),
 
@@ -115,6 +122,8 @@ usage! {
 			or |c: &Config| otry!(c.ui).interface.clone(),
 		flag_ui_path: String = "$BASE/signer",
 			or |c: &Config| otry!(c.ui).path.clone(),
+		flag_ui_request_timeout: Option<u64> = None,
+			or |c: &Config| otry!(c.ui).request_timeout.clone().map(Some),
 		// NOTE [todr] For security reasons don't put this to config files
 		flag_ui_no_validation: bool = false, or |_| None,
 
@@ -131,6 +140,8 @@ usage! {
 			or |c: &Config| otry!(c.network).max_pending_peers.clone(),
 		flag_snapshot_peers: u16 = 0u16,
 			or |c: &Config| otry!(c.network).snapshot_peers.clone(),
+		flag_snapshot_manifest_votes: usize = 3usize,
+			or |c: &Config| otry!(c.network).snapshot_manifest_votes.clone(),
 		flag_nat: String = "any",
 			or |c: &Config| otry!(c.network).nat.clone(),
 		flag_allow_ips: String = "all",
@@ -163,6 +174,46 @@ usage! {
 			or |c: &Config| otry!(c.rpc).apis.as_ref().map(|vec| vec.join(",")),
 		flag_jsonrpc_hosts: String = "none",
 			or |c: &Config| otry!(c.rpc).hosts.as_ref().map(|vec| vec.join(",")),
+		flag_jsonrpc_interface_apis: String = "none",
+			or |c: &Config| otry!(c.rpc).interface_apis.as_ref().map(|vec| vec.join(";")),
+		flag_jsonrpc_methods: String = "all",
+			or |c: &Config| otry!(c.rpc).methods.as_ref().map(|vec| vec.join(",")),
+		flag_jsonrpc_trace_page_size: usize = 10000usize,
+			or |c: &Config| otry!(c.rpc).trace_page_size.clone(),
+		flag_jsonrpc_max_logs: usize = 0usize,
+			or |c: &Config| otry!(c.rpc).max_logs.clone(),
+		flag_jsonrpc_estimate_gas_cap: String = "50000000",
+			or |c: &Config| otry!(c.rpc).estimate_gas_cap.clone(),
+		flag_jsonrpc_estimate_gas_iterations: usize = 24usize,
+			or |c: &Config| otry!(c.rpc).estimate_gas_iterations.clone(),
+		flag_jsonrpc_max_batch_size: usize = 0usize,
+			or |c: &Config| otry!(c.rpc).max_batch_size.clone(),
+		flag_jsonrpc_method_concurrency: String = "none",
+			or |c: &Config| otry!(c.rpc).method_concurrency.as_ref().map(|vec| vec.join(",")),
+		flag_jsonrpc_rate_limit: u32 = 0u32,
+			or |c: &Config| otry!(c.rpc).rate_limit.clone(),
+		flag_jsonrpc_api_keys: String = "none",
+			or |c: &Config| otry!(c.rpc).api_keys.as_ref().map(|vec| vec.join(",")),
+		flag_rpc_read_only: bool = false,
+			or |c: &Config| otry!(c.rpc).read_only.clone(),
+
+		// WS
+		flag_no_ws: bool = false,
+			or |c: &Config| otry!(c.websockets).disable.clone(),
+		flag_ws_port: u16 = 8546u16,
+			or |c: &Config| otry!(c.websockets).port.clone(),
+		flag_ws_interface: String  = "local",
+			or |c: &Config| otry!(c.websockets).interface.clone(),
+		flag_ws_apis: String = "web3,eth,net,parity,traces,rpc",
+			or |c: &Config| otry!(c.websockets).apis.as_ref().map(|vec| vec.join(",")),
+		flag_ws_origins: String = "none",
+			or |c: &Config| otry!(c.websockets).origins.as_ref().map(|vec| vec.join(",")),
+		flag_ws_hosts: String = "none",
+			or |c: &Config| otry!(c.websockets).hosts.as_ref().map(|vec| vec.join(",")),
+		flag_ws_max_subscriptions: usize = 100usize,
+			or |c: &Config| otry!(c.websockets).max_subscriptions.clone(),
+		flag_ws_methods: String = "all",
+			or |c: &Config| otry!(c.websockets).methods.as_ref().map(|vec| vec.join(",")),
 
 		// IPC
 		flag_no_ipc: bool = false,
@@ -171,6 +222,8 @@ usage! {
 			or |c: &Config| otry!(c.ipc).path.clone(),
 		flag_ipc_apis: String = "web3,eth,net,parity,parity_accounts,traces,rpc",
 			or |c: &Config| otry!(c.ipc).apis.as_ref().map(|vec| vec.join(",")),
+		flag_ipc_methods: String = "all",
+			or |c: &Config| otry!(c.ipc).methods.as_ref().map(|vec| vec.join(",")),
 
 		// DAPPS
 		flag_no_dapps: bool = false,
@@ -244,6 +297,8 @@ usage! {
 			or |c: &Config| otry!(c.mining).gas_floor_target.clone(),
 		flag_gas_cap: String = "6283184",
 			or |c: &Config| otry!(c.mining).gas_cap.clone(),
+		flag_gas_limit_target: Option<String> = None,
+			or |c: &Config| otry!(c.mining).gas_limit_target.clone().map(Some),
 		flag_extra_data: Option<String> = None,
 			or |c: &Config| otry!(c.mining).extra_data.clone().map(Some),
 		flag_tx_queue_size: usize = 1024usize,
@@ -252,16 +307,28 @@ usage! {
 			or |c: &Config| otry!(c.mining).tx_queue_gas.clone(),
 		flag_tx_queue_strategy: String = "gas_price",
 			or |c: &Config| otry!(c.mining).tx_queue_strategy.clone(),
+		flag_tx_queue_price_bump: usize = 0usize,
+			or |c: &Config| otry!(c.mining).tx_queue_price_bump.clone(),
+		flag_block_ordering: String = "gas_price",
+			or |c: &Config| otry!(c.mining).block_ordering.clone(),
+		flag_uncle_strategy: String = "all",
+			or |c: &Config| otry!(c.mining).uncle_strategy.clone(),
 		flag_tx_queue_ban_count: u16 = 1u16,
 			or |c: &Config| otry!(c.mining).tx_queue_ban_count.clone(),
 		flag_tx_queue_ban_time: u16 = 180u16,
 			or |c: &Config| otry!(c.mining).tx_queue_ban_time.clone(),
+		flag_tx_queue_ban_invalid: u16 = 0u16,
+			or |c: &Config| otry!(c.mining).tx_queue_ban_invalid.clone(),
 		flag_remove_solved: bool = false,
 			or |c: &Config| otry!(c.mining).remove_solved.clone(),
 		flag_notify_work: Option<String> = None,
 			or |c: &Config| otry!(c.mining).notify_work.as_ref().map(|vec| Some(vec.join(","))),
+		flag_priority_senders: Option<String> = None,
+			or |c: &Config| otry!(c.mining).priority_senders.as_ref().map(|vec| Some(vec.join(","))),
 		flag_refuse_service_transactions: bool = false,
 			or |c: &Config| otry!(c.mining).refuse_service_transactions.clone(),
+		flag_dynamic_gas_price: bool = false,
+			or |c: &Config| otry!(c.mining).dynamic_gas_price.clone(),
 
 		flag_stratum: bool = false,
 			or |c: &Config| Some(c.stratum.is_some()),
@@ -271,6 +338,10 @@ usage! {
 			or |c: &Config| otry!(c.stratum).port.clone(),
 		flag_stratum_secret: Option<String> = None,
 			or |c: &Config| otry!(c.stratum).secret.clone().map(Some),
+		flag_stratum_vardiff: Option<String> = None,
+			or |c: &Config| otry!(c.stratum).vardiff.clone().map(Some),
+		flag_stratum_share_interval: u64 = 15u64,
+			or |c: &Config| otry!(c.stratum).share_interval.clone(),
 
 		// -- Footprint Options
 		flag_tracing: String = "auto",
@@ -281,6 +352,8 @@ usage! {
 			or |c: &Config| otry!(c.footprint).pruning_history.clone(),
 		flag_pruning_memory: usize = 75usize,
 			or |c: &Config| otry!(c.footprint).pruning_memory.clone(),
+		flag_pruning_overlay_memory: Option<usize> = None,
+			or |c: &Config| otry!(c.footprint).pruning_overlay_memory.clone().map(Some),
 		flag_cache_size_db: u32 = 64u32,
 			or |c: &Config| otry!(c.footprint).cache_size_db.clone(),
 		flag_cache_size_blocks: u32 = 8u32,
@@ -297,10 +370,22 @@ usage! {
 			or |c: &Config| otry!(c.footprint).db_compaction.clone(),
 		flag_fat_db: String = "auto",
 			or |c: &Config| otry!(c.footprint).fat_db.clone(),
+		flag_key_preimages: bool = false,
+			or |c: &Config| otry!(c.footprint).key_preimages.clone(),
+		flag_experimental_parallel_exec: bool = false,
+			or |c: &Config| otry!(c.footprint).experimental_parallel_exec.clone(),
+		flag_trie_encoding: String = "canonical",
+			or |c: &Config| otry!(c.footprint).trie_encoding.clone(),
+		flag_db_compression: String = "auto",
+			or |c: &Config| otry!(c.footprint).db_compression.clone(),
 		flag_scale_verifiers: bool = false,
 			or |c: &Config| otry!(c.footprint).scale_verifiers.clone(),
 		flag_num_verifiers: Option<usize> = None,
 			or |c: &Config| otry!(c.footprint).num_verifiers.clone().map(Some),
+		flag_ancient_path: Option<String> = None,
+			or |c: &Config| otry!(c.footprint).ancient_path.clone().map(Some),
+		flag_log_index: bool = false,
+			or |c: &Config| otry!(c.footprint).log_index.clone(),
 
 		// -- Import/Export Options
 		flag_from: String = "1", or |_| None,
@@ -311,15 +396,31 @@ usage! {
 		flag_no_code: bool = false, or |_| None,
 		flag_min_balance: Option<String> = None, or |_| None,
 		flag_max_balance: Option<String> = None, or |_| None,
+		flag_dry_run: bool = false, or |_| None,
+		flag_trie: bool = false, or |_| None,
 
 		// -- Snapshot Optons
 		flag_at: String = "latest", or |_| None,
 		flag_no_periodic_snapshot: bool = false,
 			or |c: &Config| otry!(c.snapshots).disable_periodic.clone(),
+		flag_snapshot_period: u64 = 10000u64,
+			or |c: &Config| otry!(c.snapshots).period.clone(),
+		flag_snapshot_retain: usize = 1usize,
+			or |c: &Config| otry!(c.snapshots).retain.clone(),
+		flag_snapshot_trace_blocks: u64 = 0u64,
+			or |c: &Config| otry!(c.snapshots).trace_blocks.clone(),
+		flag_snapshot_threads: usize = 1usize,
+			or |c: &Config| otry!(c.snapshots).threads.clone(),
+		flag_snapshot_write_rate: Option<usize> = None,
+			or |c: &Config| otry!(c.snapshots).write_rate.clone().map(Some),
+		flag_incremental_snapshots: bool = false,
+			or |c: &Config| otry!(c.snapshots).incremental.clone(),
 
 		// -- Virtual Machine Options
 		flag_jitvm: bool = false,
 			or |c: &Config| otry!(c.vm).jit.clone(),
+		flag_jitvm_auto: bool = false,
+			or |c: &Config| otry!(c.vm).jit_auto.clone(),
 
 		// -- Miscellaneous Options
 		flag_config: String = "$BASE/config.toml", or |_| None,
@@ -329,6 +430,8 @@ usage! {
 			or |c: &Config| otry!(c.misc).log_file.clone().map(Some),
 		flag_no_color: bool = false,
 			or |c: &Config| otry!(c.misc).color.map(|c| !c).clone(),
+		flag_ntp_servers: String = "0.pool.ntp.org:123,1.pool.ntp.org:123,2.pool.ntp.org:123,3.pool.ntp.org:123",
+			or |c: &Config| otry!(c.misc).ntp_servers.as_ref().map(|vec| vec.join(",")),
 	}
 	{
 		// Values with optional default value.
@@ -346,6 +449,7 @@ struct Config {
 	ui: Option<Ui>,
 	network: Option<Network>,
 	rpc: Option<Rpc>,
+	websockets: Option<Ws>,
 	ipc: Option<Ipc>,
 	dapps: Option<Dapps>,
 	secretstore: Option<SecretStore>,
@@ -379,6 +483,7 @@ struct Account {
 	unlock: Option<Vec<String>>,
 	password: Option<Vec<String>>,
 	keys_iterations: Option<u32>,
+	keys_kdf: Option<String>,
 	disable_hardware: Option<bool>,
 }
 
@@ -389,6 +494,7 @@ struct Ui {
 	port: Option<u16>,
 	interface: Option<String>,
 	path: Option<String>,
+	request_timeout: Option<u64>,
 }
 
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
@@ -398,6 +504,7 @@ struct Network {
 	min_peers: Option<u16>,
 	max_peers: Option<u16>,
 	snapshot_peers: Option<u16>,
+	snapshot_manifest_votes: Option<usize>,
 	max_pending_peers: Option<u16>,
 	nat: Option<String>,
 	allow_ips: Option<String>,
@@ -417,6 +524,29 @@ struct Rpc {
 	cors: Option<String>,
 	apis: Option<Vec<String>>,
 	hosts: Option<Vec<String>>,
+	interface_apis: Option<Vec<String>>,
+	methods: Option<Vec<String>>,
+	trace_page_size: Option<usize>,
+	max_logs: Option<usize>,
+	estimate_gas_cap: Option<String>,
+	estimate_gas_iterations: Option<usize>,
+	max_batch_size: Option<usize>,
+	method_concurrency: Option<Vec<String>>,
+	rate_limit: Option<u32>,
+	api_keys: Option<Vec<String>>,
+	read_only: Option<bool>,
+}
+
+#[derive(Default, Debug, PartialEq, RustcDecodable)]
+struct Ws {
+	disable: Option<bool>,
+	port: Option<u16>,
+	interface: Option<String>,
+	apis: Option<Vec<String>>,
+	origins: Option<Vec<String>>,
+	hosts: Option<Vec<String>>,
+	max_subscriptions: Option<usize>,
+	methods: Option<Vec<String>>,
 }
 
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
@@ -424,6 +554,7 @@ struct Ipc {
 	disable: Option<bool>,
 	path: Option<String>,
 	apis: Option<Vec<String>>,
+	methods: Option<Vec<String>>,
 }
 
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
@@ -472,15 +603,22 @@ struct Mining {
 	price_update_period: Option<String>,
 	gas_floor_target: Option<String>,
 	gas_cap: Option<String>,
+	gas_limit_target: Option<String>,
 	extra_data: Option<String>,
 	tx_queue_size: Option<usize>,
 	tx_queue_gas: Option<String>,
 	tx_queue_strategy: Option<String>,
+	tx_queue_price_bump: Option<usize>,
+	block_ordering: Option<String>,
+	uncle_strategy: Option<String>,
 	tx_queue_ban_count: Option<u16>,
 	tx_queue_ban_time: Option<u16>,
+	tx_queue_ban_invalid: Option<u16>,
 	remove_solved: Option<bool>,
 	notify_work: Option<Vec<String>>,
+	priority_senders: Option<Vec<String>>,
 	refuse_service_transactions: Option<bool>,
+	dynamic_gas_price: Option<bool>,
 }
 
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
@@ -488,6 +626,8 @@ struct Stratum {
 	interface: Option<String>,
 	port: Option<u16>,
 	secret: Option<String>,
+	vardiff: Option<String>,
+	share_interval: Option<u64>,
 }
 
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
@@ -496,6 +636,7 @@ struct Footprint {
 	pruning: Option<String>,
 	pruning_history: Option<u64>,
 	pruning_memory: Option<usize>,
+	pruning_overlay_memory: Option<usize>,
 	fast_and_loose: Option<bool>,
 	cache_size: Option<u32>,
 	cache_size_db: Option<u32>,
@@ -504,18 +645,31 @@ struct Footprint {
 	cache_size_state: Option<u32>,
 	db_compaction: Option<String>,
 	fat_db: Option<String>,
+	key_preimages: Option<bool>,
+	experimental_parallel_exec: Option<bool>,
+	trie_encoding: Option<String>,
+	db_compression: Option<String>,
 	scale_verifiers: Option<bool>,
 	num_verifiers: Option<usize>,
+	ancient_path: Option<String>,
+	log_index: Option<bool>,
 }
 
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
 struct Snapshots {
 	disable_periodic: Option<bool>,
+	period: Option<u64>,
+	retain: Option<usize>,
+	trace_blocks: Option<u64>,
+	threads: Option<usize>,
+	write_rate: Option<usize>,
+	incremental: Option<bool>,
 }
 
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
 struct VM {
 	jit: Option<bool>,
+	jit_auto: Option<bool>,
 }
 
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
@@ -523,13 +677,14 @@ struct Misc {
 	logging: Option<String>,
 	log_file: Option<String>,
 	color: Option<bool>,
+	ntp_servers: Option<Vec<String>>,
 }
 
 #[cfg(test)]
 mod tests {
 	use super::{
 		Args, ArgsError,
-		Config, Operating, Account, Ui, Network, Rpc, Ipc, Dapps, Ipfs, Mining, Footprint,
+		Config, Operating, Account, Ui, Network, Rpc, Ws, Ipc, Dapps, Ipfs, Mining, Footprint,
 		Snapshots, VM, Misc, SecretStore,
 	};
 	use toml;
@@ -594,6 +749,7 @@ mod tests {
 			cmd_account: false,
 			cmd_new: false,
 			cmd_list: false,
+			cmd_reencrypt: false,
 			cmd_export: false,
 			cmd_state: false,
 			cmd_blocks: false,
@@ -610,6 +766,10 @@ mod tests {
 			cmd_hash: false,
 			cmd_db: false,
 			cmd_kill: false,
+			cmd_migrate: false,
+			cmd_backup: false,
+			cmd_verify: false,
+			cmd_stats: false,
 
 			// Arguments
 			arg_pid_file: "".into(),
@@ -635,6 +795,7 @@ mod tests {
 			flag_unlock: Some("0xdeadbeefcafe0000000000000000000000000000".into()),
 			flag_password: vec!["~/.safe/password.file".into()],
 			flag_keys_iterations: 10240u32,
+			flag_keys_kdf: "pbkdf2".into(),
 			flag_no_hardware_wallets: false,
 
 			flag_force_ui: false,
@@ -642,6 +803,7 @@ mod tests {
 			flag_ui_port: 8180u16,
 			flag_ui_interface: "127.0.0.1".into(),
 			flag_ui_path: "$HOME/.parity/signer".into(),
+			flag_ui_request_timeout: Some(3600u64),
 			flag_ui_no_validation: false,
 
 			// -- Networking Options
@@ -651,6 +813,7 @@ mod tests {
 			flag_max_peers: 50u16,
 			flag_max_pending_peers: 64u16,
 			flag_snapshot_peers: 0u16,
+			flag_snapshot_manifest_votes: 3usize,
 			flag_allow_ips: "all".into(),
 			flag_nat: "any".into(),
 			flag_network_id: Some(1),
@@ -669,11 +832,33 @@ mod tests {
 			flag_jsonrpc_cors: Some("null".into()),
 			flag_jsonrpc_apis: "web3,eth,net,parity,traces,rpc".into(),
 			flag_jsonrpc_hosts: "none".into(),
+			flag_jsonrpc_interface_apis: "127.0.0.1:8547=web3,eth,net,personal".into(),
+			flag_jsonrpc_methods: "all".into(),
+			flag_jsonrpc_trace_page_size: 10000usize,
+			flag_jsonrpc_max_logs: 0usize,
+			flag_jsonrpc_estimate_gas_cap: "50000000".into(),
+			flag_jsonrpc_estimate_gas_iterations: 24usize,
+			flag_jsonrpc_max_batch_size: 0usize,
+			flag_jsonrpc_method_concurrency: "eth_getLogs:4".into(),
+			flag_jsonrpc_rate_limit: 0u32,
+			flag_jsonrpc_api_keys: "none".into(),
+			flag_rpc_read_only: false,
+
+			// WS
+			flag_no_ws: false,
+			flag_ws_port: 8546u16,
+			flag_ws_interface: "local".into(),
+			flag_ws_apis: "web3,eth,net,parity,traces,rpc".into(),
+			flag_ws_origins: "none".into(),
+			flag_ws_hosts: "none".into(),
+			flag_ws_max_subscriptions: 100usize,
+			flag_ws_methods: "all".into(),
 
 			// IPC
 			flag_no_ipc: false,
 			flag_ipc_path: "$HOME/.parity/jsonrpc.ipc".into(),
 			flag_ipc_apis: "web3,eth,net,parity,parity_accounts,personal,traces,rpc".into(),
+			flag_ipc_methods: "all".into(),
 
 			// DAPPS
 			flag_no_dapps: false,
@@ -714,26 +899,36 @@ mod tests {
 			flag_price_update_period: "hourly".into(),
 			flag_gas_floor_target: "4700000".into(),
 			flag_gas_cap: "6283184".into(),
+			flag_gas_limit_target: Some("8000000".into()),
 			flag_extra_data: Some("Parity".into()),
 			flag_tx_queue_size: 1024usize,
 			flag_tx_queue_gas: "auto".into(),
 			flag_tx_queue_strategy: "gas_factor".into(),
+			flag_tx_queue_price_bump: 10usize,
+			flag_block_ordering: "sender_fair".into(),
+			flag_uncle_strategy: "profitable".into(),
 			flag_tx_queue_ban_count: 1u16,
 			flag_tx_queue_ban_time: 180u16,
+			flag_tx_queue_ban_invalid: 0u16,
 			flag_remove_solved: false,
 			flag_notify_work: Some("http://localhost:3001".into()),
+			flag_priority_senders: Some("0xdeadbeefcafe0000000000000000000000000002".into()),
 			flag_refuse_service_transactions: false,
+			flag_dynamic_gas_price: false,
 
 			flag_stratum: false,
 			flag_stratum_interface: "local".to_owned(),
 			flag_stratum_port: 8008u16,
 			flag_stratum_secret: None,
+			flag_stratum_vardiff: None,
+			flag_stratum_share_interval: 15u64,
 
 			// -- Footprint Options
 			flag_tracing: "auto".into(),
 			flag_pruning: "auto".into(),
 			flag_pruning_history: 64u64,
 			flag_pruning_memory: 500usize,
+			flag_pruning_overlay_memory: None,
 			flag_cache_size_db: 64u32,
 			flag_cache_size_blocks: 8u32,
 			flag_cache_size_queue: 50u32,
@@ -742,8 +937,14 @@ mod tests {
 			flag_fast_and_loose: false,
 			flag_db_compaction: "ssd".into(),
 			flag_fat_db: "auto".into(),
+			flag_key_preimages: false,
+			flag_experimental_parallel_exec: false,
+			flag_trie_encoding: "canonical".into(),
+			flag_db_compression: "auto".into(),
 			flag_scale_verifiers: true,
 			flag_num_verifiers: Some(6),
+			flag_ancient_path: None,
+			flag_log_index: false,
 
 			// -- Import/Export Options
 			flag_from: "1".into(),
@@ -754,13 +955,22 @@ mod tests {
 			flag_no_storage: false,
 			flag_min_balance: None,
 			flag_max_balance: None,
+			flag_dry_run: false,
+			flag_trie: false,
 
 			// -- Snapshot Optons
 			flag_at: "latest".into(),
 			flag_no_periodic_snapshot: false,
+			flag_snapshot_period: 10000,
+			flag_snapshot_retain: 1,
+			flag_snapshot_trace_blocks: 0,
+			flag_snapshot_threads: 1,
+			flag_snapshot_write_rate: None,
+			flag_incremental_snapshots: false,
 
 			// -- Virtual Machine Options
 			flag_jitvm: false,
+			flag_jitvm_auto: false,
 
 			// -- Legacy Options
 			flag_geth: false,
@@ -796,6 +1006,7 @@ mod tests {
 			flag_logging: Some("own_tx=trace".into()),
 			flag_log_file: Some("/var/log/parity.log".into()),
 			flag_no_color: false,
+			flag_ntp_servers: "0.parity.pool.ntp.org:123,1.parity.pool.ntp.org:123".into(),
 			flag_no_config: false,
 		});
 	}
@@ -837,6 +1048,7 @@ mod tests {
 				unlock: Some(vec!["0x1".into(), "0x2".into(), "0x3".into()]),
 				password: Some(vec!["passwdfile path".into()]),
 				keys_iterations: None,
+				keys_kdf: None,
 				disable_hardware: None,
 			}),
 			ui: Some(Ui {
@@ -845,6 +1057,7 @@ mod tests {
 				port: None,
 				interface: None,
 				path: None,
+				request_timeout: None,
 			}),
 			network: Some(Network {
 				warp: Some(false),
@@ -853,6 +1066,7 @@ mod tests {
 				max_peers: Some(20),
 				max_pending_peers: Some(30),
 				snapshot_peers: Some(40),
+				snapshot_manifest_votes: None,
 				allow_ips: Some("public".into()),
 				nat: Some("any".into()),
 				id: None,
@@ -869,11 +1083,33 @@ mod tests {
 				cors: None,
 				apis: None,
 				hosts: None,
+				interface_apis: None,
+				methods: None,
+				trace_page_size: None,
+				max_logs: None,
+				estimate_gas_cap: None,
+				estimate_gas_iterations: None,
+				max_batch_size: None,
+				method_concurrency: None,
+				rate_limit: None,
+				api_keys: None,
+				read_only: None,
+			}),
+			websockets: Some(Ws {
+				disable: Some(true),
+				port: Some(8547),
+				interface: None,
+				apis: None,
+				origins: Some(vec!["none".into()]),
+				hosts: None,
+				max_subscriptions: None,
+				methods: None,
 			}),
 			ipc: Some(Ipc {
 				disable: None,
 				path: None,
 				apis: Some(vec!["rpc".into(), "eth".into()]),
+				methods: None,
 			}),
 			dapps: Some(Dapps {
 				disable: None,
@@ -912,23 +1148,31 @@ mod tests {
 				price_update_period: Some("hourly".into()),
 				gas_floor_target: None,
 				gas_cap: None,
+				gas_limit_target: None,
 				tx_queue_size: Some(1024),
 				tx_queue_gas: Some("auto".into()),
 				tx_queue_strategy: None,
+				tx_queue_price_bump: None,
+				block_ordering: None,
+				uncle_strategy: None,
 				tx_queue_ban_count: None,
 				tx_queue_ban_time: None,
+				tx_queue_ban_invalid: None,
 				tx_gas_limit: None,
 				tx_time_limit: None,
 				extra_data: None,
 				remove_solved: None,
 				notify_work: None,
+				priority_senders: None,
 				refuse_service_transactions: None,
+				dynamic_gas_price: None,
 			}),
 			footprint: Some(Footprint {
 				tracing: Some("on".into()),
 				pruning: Some("fast".into()),
 				pruning_history: Some(64),
 				pruning_memory: None,
+				pruning_overlay_memory: None,
 				fast_and_loose: None,
 				cache_size: None,
 				cache_size_db: Some(128),
@@ -937,19 +1181,33 @@ mod tests {
 				cache_size_state: Some(25),
 				db_compaction: Some("ssd".into()),
 				fat_db: Some("off".into()),
+				key_preimages: None,
+				experimental_parallel_exec: None,
+				trie_encoding: None,
+				db_compression: None,
 				scale_verifiers: Some(false),
 				num_verifiers: None,
+				ancient_path: None,
+				log_index: None,
 			}),
 			snapshots: Some(Snapshots {
 				disable_periodic: Some(true),
+				period: None,
+				retain: None,
+				trace_blocks: None,
+				threads: None,
+				write_rate: None,
+				incremental: None,
 			}),
 			vm: Some(VM {
 				jit: Some(false),
+				jit_auto: None,
 			}),
 			misc: Some(Misc {
 				logging: Some("own_tx=trace".into()),
 				log_file: Some("/var/log/parity.log".into()),
 				color: Some(true),
+				ntp_servers: None,
 			}),
 			stratum: None,
 		});
@@ -15,6 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::time::Duration;
+use std::collections::{HashMap, HashSet};
 use std::io::{Read, Write, stderr};
 use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
@@ -22,18 +23,24 @@ use std::cmp::max;
 use cli::{Args, ArgsError};
 use util::{Hashable, H256, U256, Uint, Bytes, version_data, Address};
 use util::journaldb::Algorithm;
+use util::trie::NodeEncoding;
 use util::Colour;
 use ethsync::{NetworkConfiguration, is_valid_node_url, AllowIP};
 use ethcore::ethstore::ethkey::Secret;
-use ethcore::client::{VMType};
-use ethcore::miner::{MinerOptions, Banning, StratumOptions};
+use ethcore::ethstore::KdfParams;
+use ethcore::client::{VMType, EstimateGasConfig};
+use ethcore::miner::{MinerOptions, Banning, StratumOptions, StratumVarDiff};
 use ethcore::verification::queue::VerifierSettings;
+use ethcore::snapshot::SnapshotConfiguration;
 
-use rpc::{IpcConfiguration, HttpConfiguration};
+use rpc::{IpcConfiguration, HttpConfiguration, WsConfiguration};
+use rpc_apis::ApiSet;
 use ethcore_rpc::NetworkSettings;
+use ethcore_rpc::limits::LimitsConfig;
 use cache::CacheConfig;
 use helpers::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_price, replace_home, replace_home_for_db,
-geth_ipc_path, parity_ipc_path, to_bootnodes, to_addresses, to_address, to_gas_limit, to_queue_strategy};
+geth_ipc_path, parity_ipc_path, to_bootnodes, to_addresses, to_address, to_gas_limit, to_queue_strategy, to_block_ordering,
+to_uncle_strategy, to_db_compression};
 use params::{SpecType, ResealPolicy, AccountsConfig, GasPricerConfig, MinerExtras, Pruning, Switch};
 use ethcore_logger::Config as LogConfig;
 use dir::{self, Directories, default_hypervisor_path, default_local_path, default_data_path};
@@ -43,9 +50,9 @@ use signer::{Configuration as SignerConfiguration};
 use secretstore::Configuration as SecretStoreConfiguration;
 use updater::{UpdatePolicy, UpdateFilter, ReleaseTrack};
 use run::RunCmd;
-use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, KillBlockchain, ExportState, DataFormat};
+use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, KillBlockchain, MigrateBlockchain, DatabaseBackup, VerifyBlockchain, DatabaseStats, ExportState, DataFormat};
 use presale::ImportWallet;
-use account::{AccountCmd, NewAccount, ListAccounts, ImportAccounts, ImportFromGethAccounts};
+use account::{AccountCmd, NewAccount, ListAccounts, ReencryptAccounts, ImportAccounts, ImportFromGethAccounts};
 use snapshot::{self, SnapshotCommand};
 
 const AUTHCODE_FILENAME: &'static str = "authcodes";
@@ -114,6 +121,7 @@ impl Configuration {
 		let logger_config = self.logger_config();
 		let http_conf = self.http_config()?;
 		let ipc_conf = self.ipc_config()?;
+		let ws_conf = self.ws_config()?;
 		let net_conf = self.net_config()?;
 		let network_id = self.network_id();
 		let cache_config = self.cache_config();
@@ -177,10 +185,58 @@ impl Configuration {
 				dirs: dirs,
 				pruning: pruning,
 			}))
+		} else if self.args.cmd_db && self.args.cmd_migrate {
+			Cmd::Blockchain(BlockchainCmd::Migrate(MigrateBlockchain {
+				spec: spec,
+				dirs: dirs,
+				pruning: pruning,
+				compaction: compaction,
+				dry_run: self.args.flag_dry_run,
+			}))
+		} else if self.args.cmd_db && (self.args.cmd_backup || self.args.cmd_restore) {
+			let backup = DatabaseBackup {
+				spec: spec,
+				dirs: dirs,
+				pruning: pruning,
+				path: self.args.arg_path.first().cloned().expect("<path> is required by both commands; qed"),
+			};
+			match self.args.cmd_backup {
+				true => Cmd::Blockchain(BlockchainCmd::Backup(backup)),
+				false => Cmd::Blockchain(BlockchainCmd::Restore(backup)),
+			}
+		} else if self.args.cmd_db && self.args.cmd_verify {
+			Cmd::Blockchain(BlockchainCmd::Verify(VerifyBlockchain {
+				spec: spec,
+				cache_config: cache_config,
+				dirs: dirs,
+				pruning: pruning,
+				pruning_history: pruning_history,
+				pruning_memory: self.args.flag_pruning_memory,
+				compaction: compaction,
+				wal: wal,
+				tracing: tracing,
+				fat_db: fat_db,
+				from_block: to_block_id(&self.args.flag_from)?,
+				to_block: to_block_id(&self.args.flag_to)?,
+			}))
+		} else if self.args.cmd_db && self.args.cmd_stats {
+			Cmd::Blockchain(BlockchainCmd::Stats(DatabaseStats {
+				spec: spec,
+				cache_config: cache_config,
+				dirs: dirs,
+				pruning: pruning,
+				pruning_history: pruning_history,
+				pruning_memory: self.args.flag_pruning_memory,
+				compaction: compaction,
+				wal: wal,
+				tracing: tracing,
+				fat_db: fat_db,
+				at: to_block_id(&self.args.flag_at)?,
+			}))
 		} else if self.args.cmd_account {
 			let account_cmd = if self.args.cmd_new {
 				let new_acc = NewAccount {
-					iterations: self.args.flag_keys_iterations,
+					kdf: self.keys_kdf()?,
 					path: dirs.keys,
 					spec: spec,
 					password_file: self.args.flag_password.first().cloned(),
@@ -192,6 +248,14 @@ impl Configuration {
 					spec: spec,
 				};
 				AccountCmd::List(list_acc)
+			} else if self.args.cmd_reencrypt {
+				let reencrypt_acc = ReencryptAccounts {
+					kdf: self.keys_kdf()?,
+					path: dirs.keys,
+					spec: spec,
+					password_files: self.args.flag_password.clone(),
+				};
+				AccountCmd::Reencrypt(reencrypt_acc)
 			} else if self.args.cmd_import {
 				let import_acc = ImportAccounts {
 					from: self.args.arg_path.clone(),
@@ -212,6 +276,30 @@ impl Configuration {
 				}
 			);
 			Cmd::Account(account_cmd)
+		} else if self.args.cmd_snapshot {
+			let kind = if self.args.cmd_export {
+				snapshot::Kind::Export
+			} else if self.args.cmd_import {
+				snapshot::Kind::Import
+			} else {
+				snapshot::Kind::Take
+			};
+			let snapshot_cmd = SnapshotCommand {
+				cache_config: cache_config,
+				dirs: dirs,
+				spec: spec,
+				pruning: pruning,
+				pruning_history: pruning_history,
+				pruning_memory: self.args.flag_pruning_memory,
+				tracing: tracing,
+				fat_db: fat_db,
+				compaction: compaction,
+				file_path: self.args.arg_file.clone(),
+				wal: wal,
+				kind: kind,
+				block_at: to_block_id(&self.args.flag_at)?,
+			};
+			Cmd::Snapshot(snapshot_cmd)
 		} else if self.args.cmd_wallet {
 			let presale_cmd = ImportWallet {
 				iterations: self.args.flag_keys_iterations,
@@ -285,23 +373,6 @@ impl Configuration {
 			} else {
 				unreachable!();
 			}
-		} else if self.args.cmd_snapshot {
-			let snapshot_cmd = SnapshotCommand {
-				cache_config: cache_config,
-				dirs: dirs,
-				spec: spec,
-				pruning: pruning,
-				pruning_history: pruning_history,
-				pruning_memory: self.args.flag_pruning_memory,
-				tracing: tracing,
-				fat_db: fat_db,
-				compaction: compaction,
-				file_path: self.args.arg_file.clone(),
-				wal: wal,
-				kind: snapshot::Kind::Take,
-				block_at: to_block_id(&self.args.flag_at)?,
-			};
-			Cmd::Snapshot(snapshot_cmd)
 		} else if self.args.cmd_restore {
 			let restore_cmd = SnapshotCommand {
 				cache_config: cache_config,
@@ -341,11 +412,13 @@ impl Configuration {
 				pruning: pruning,
 				pruning_history: pruning_history,
 				pruning_memory: self.args.flag_pruning_memory,
+				pruning_overlay_memory: self.args.flag_pruning_overlay_memory,
 				daemon: daemon,
 				logger_config: logger_config.clone(),
 				miner_options: miner_options,
 				http_conf: http_conf,
 				ipc_conf: ipc_conf,
+				ws_conf: ws_conf,
 				net_conf: net_conf,
 				network_id: network_id,
 				acc_conf: self.accounts_config()?,
@@ -356,12 +429,26 @@ impl Configuration {
 				mode: mode,
 				tracing: tracing,
 				fat_db: fat_db,
+				key_preimages: self.args.flag_key_preimages,
+				experimental_parallel_exec: self.args.flag_experimental_parallel_exec,
+				trie_encoding: self.trie_encoding()?,
 				compaction: compaction,
 				wal: wal,
 				vm_type: vm_type,
 				warp_sync: warp_sync,
+				snapshot_manifest_votes: self.args.flag_snapshot_manifest_votes,
 				geth_compatibility: geth_compatibility,
+				trace_page_size: self.args.flag_jsonrpc_trace_page_size,
+				max_logs: match self.args.flag_jsonrpc_max_logs {
+					0 => None,
+					max => Some(max),
+				},
+				estimate_gas: self.estimate_gas_config()?,
+				rpc_limits: self.rpc_limits()?,
+				rpc_read_only: self.args.flag_rpc_read_only,
+				ntp_servers: self.ntp_servers(),
 				ui_address: ui_address,
+				ui_request_timeout: self.args.flag_ui_request_timeout.map(Duration::from_secs),
 				net_settings: self.network_settings(),
 				dapps_conf: dapps_conf,
 				ipfs_conf: ipfs_conf,
@@ -372,9 +459,20 @@ impl Configuration {
 				name: self.args.flag_identity,
 				custom_bootnodes: self.args.flag_bootnodes.is_some(),
 				no_periodic_snapshot: self.args.flag_no_periodic_snapshot,
+				snapshot_period: self.snapshot_period()?,
+				snapshot_conf: SnapshotConfiguration {
+					threads: self.args.flag_snapshot_threads,
+					max_write_rate: self.args.flag_snapshot_write_rate.map(|mb| mb * 1024 * 1024),
+					incremental: self.args.flag_incremental_snapshots,
+					retain: self.args.flag_snapshot_retain,
+					trace_blocks: self.args.flag_snapshot_trace_blocks,
+				},
 				check_seal: !self.args.flag_no_seal_check,
 				download_old_blocks: !self.args.flag_no_ancient_blocks,
 				verifier_settings: verifier_settings,
+				ancient_path: self.ancient_path(),
+				log_index: self.args.flag_log_index,
+				db_compression: to_db_compression(&self.args.flag_db_compression)?,
 			};
 			Cmd::Run(run_cmd)
 		};
@@ -386,7 +484,9 @@ impl Configuration {
 	}
 
 	fn vm_type(&self) -> Result<VMType, String> {
-		if self.args.flag_jitvm {
+		if self.args.flag_jitvm_auto {
+			VMType::auto().ok_or("Parity is built without the JIT EVM.".into())
+		} else if self.args.flag_jitvm {
 			VMType::jit().ok_or("Parity is built without the JIT EVM.".into())
 		} else {
 			Ok(VMType::Interpreter)
@@ -399,6 +499,10 @@ impl Configuration {
 			extra_data: self.extra_data()?,
 			gas_floor_target: to_u256(&self.args.flag_gas_floor_target)?,
 			gas_ceil_target: to_u256(&self.args.flag_gas_cap)?,
+			gas_limit_target: match self.args.flag_gas_limit_target {
+				Some(ref d) => Some(to_u256(d)?),
+				None => None,
+			},
 			transactions_limit: self.args.flag_tx_queue_size,
 			engine_signer: self.engine_signer()?,
 		};
@@ -474,6 +578,13 @@ impl Configuration {
 		self.args.flag_max_pending_peers as u32
 	}
 
+	fn snapshot_period(&self) -> Result<u64, String> {
+		match self.args.flag_snapshot_period {
+			0 => Err("Invalid snapshot period: must be greater than 0.".into()),
+			period => Ok(period),
+		}
+	}
+
 	fn snapshot_peers(&self) -> u32 {
 		self.args.flag_snapshot_peers as u32
 	}
@@ -484,7 +595,7 @@ impl Configuration {
 
 	fn accounts_config(&self) -> Result<AccountsConfig, String> {
 		let cfg = AccountsConfig {
-			iterations: self.args.flag_keys_iterations,
+			kdf: self.keys_kdf()?,
 			testnet: self.args.flag_testnet,
 			password_files: self.args.flag_password.clone(),
 			unlocked_accounts: to_addresses(&self.args.flag_unlock)?,
@@ -494,6 +605,29 @@ impl Configuration {
 		Ok(cfg)
 	}
 
+	fn estimate_gas_config(&self) -> Result<EstimateGasConfig, String> {
+		Ok(EstimateGasConfig {
+			gas_cap: to_u256(&self.args.flag_jsonrpc_estimate_gas_cap)?,
+			max_iterations: self.args.flag_jsonrpc_estimate_gas_iterations,
+		})
+	}
+
+	fn trie_encoding(&self) -> Result<NodeEncoding, String> {
+		match self.args.flag_trie_encoding.as_str() {
+			"canonical" => Ok(NodeEncoding::Canonical),
+			"compact" => Ok(NodeEncoding::Compact),
+			other => Err(format!("Invalid trie encoding: {}. Expected canonical or compact.", other)),
+		}
+	}
+
+	fn keys_kdf(&self) -> Result<KdfParams, String> {
+		match self.args.flag_keys_kdf.as_str() {
+			"pbkdf2" => Ok(KdfParams::Pbkdf2 { iterations: self.args.flag_keys_iterations }),
+			"scrypt" => Ok(KdfParams::scrypt()),
+			other => Err(format!("Invalid key derivation function: {}. Expected pbkdf2 or scrypt.", other)),
+		}
+	}
+
 	fn stratum_options(&self) -> Result<Option<StratumOptions>, String> {
 		if self.args.flag_stratum {
 			Ok(Some(StratumOptions {
@@ -501,6 +635,13 @@ impl Configuration {
 				listen_addr: self.stratum_interface(),
 				port: self.args.flag_stratum_port,
 				secret: self.args.flag_stratum_secret.as_ref().map(|s| s.parse::<H256>().unwrap_or_else(|_| s.sha3())),
+				vardiff: match self.args.flag_stratum_vardiff {
+					Some(ref d) => Some(StratumVarDiff {
+						min_difficulty: to_u256(d)?,
+						share_interval: Duration::from_secs(self.args.flag_stratum_share_interval),
+					}),
+					None => None,
+				},
 			}))
 		} else { Ok(None) }
 	}
@@ -520,6 +661,8 @@ impl Configuration {
 			tx_queue_size: self.args.flag_tx_queue_size,
 			tx_queue_gas_limit: to_gas_limit(&self.args.flag_tx_queue_gas)?,
 			tx_queue_strategy: to_queue_strategy(&self.args.flag_tx_queue_strategy)?,
+			tx_queue_price_bump: self.args.flag_tx_queue_price_bump,
+			tx_queue_dynamic_gas_price: self.args.flag_dynamic_gas_price,
 			pending_set: to_pending_set(&self.args.flag_relay_set)?,
 			reseal_min_period: Duration::from_millis(reseal_min_period),
 			reseal_max_period: Duration::from_millis(self.args.flag_reseal_max_period),
@@ -533,7 +676,11 @@ impl Configuration {
 				},
 				None => Banning::Disabled,
 			},
+			tx_queue_ban_invalid: self.args.flag_tx_queue_ban_invalid,
 			refuse_service_transactions: self.args.flag_refuse_service_transactions,
+			block_ordering: to_block_ordering(&self.args.flag_block_ordering)?,
+			uncle_strategy: to_uncle_strategy(&self.args.flag_uncle_strategy)?,
+			priority_senders: to_addresses(&self.args.flag_priority_senders)?.into_iter().collect(),
 		};
 
 		Ok(options)
@@ -750,6 +897,14 @@ impl Configuration {
 		Self::hosts(&self.args.flag_jsonrpc_hosts)
 	}
 
+	fn ws_hosts(&self) -> Option<Vec<String>> {
+		Self::hosts(&self.args.flag_ws_hosts)
+	}
+
+	fn ws_origins(&self) -> Option<Vec<String>> {
+		Self::hosts(&self.args.flag_ws_origins)
+	}
+
 	fn dapps_hosts(&self) -> Option<Vec<String>> {
 		Self::hosts(&self.args.flag_dapps_hosts)
 	}
@@ -758,6 +913,29 @@ impl Configuration {
 		Self::hosts(&self.args.flag_ipfs_api_hosts)
 	}
 
+	fn methods(methods: &str) -> Option<Vec<String>> {
+		match methods {
+			"all" => None,
+			methods => Some(methods.split(',').map(Into::into).collect()),
+		}
+	}
+
+	fn rpc_interface_apis(&self) -> Result<Vec<(SocketAddr, ApiSet)>, String> {
+		if self.args.flag_jsonrpc_interface_apis == "none" {
+			return Ok(Vec::new());
+		}
+
+		self.args.flag_jsonrpc_interface_apis.split(';').map(|entry| {
+			let mut parts = entry.splitn(2, '=');
+			let addr = parts.next().expect("splitn always yields at least one item; qed");
+			let addr: SocketAddr = addr.parse().map_err(|_| format!("Invalid JSONRPC listen host/port given: {}", addr))?;
+			match parts.next() {
+				Some(apis) => Ok((addr, apis.parse()?)),
+				None => Err(format!("Invalid interface API set given: {}. Expected IP:PORT=APIS.", entry)),
+			}
+		}).collect()
+	}
+
 	fn ipc_config(&self) -> Result<IpcConfiguration, String> {
 		let conf = IpcConfiguration {
 			enabled: !(self.args.flag_ipcdisable || self.args.flag_ipc_off || self.args.flag_no_ipc),
@@ -772,6 +950,7 @@ impl Configuration {
 				}
 				apis.parse()?
 			},
+			methods: Self::methods(&self.args.flag_ipc_methods),
 		};
 
 		Ok(conf)
@@ -785,11 +964,66 @@ impl Configuration {
 			apis: self.rpc_apis().parse()?,
 			hosts: self.rpc_hosts(),
 			cors: self.rpc_cors(),
+			interface_apis: self.rpc_interface_apis()?,
+			methods: Self::methods(&self.args.flag_jsonrpc_methods),
+		};
+
+		Ok(conf)
+	}
+
+	fn ws_config(&self) -> Result<WsConfiguration, String> {
+		let conf = WsConfiguration {
+			enabled: !self.args.flag_no_ws,
+			interface: self.ws_interface(),
+			port: self.args.flag_ws_port,
+			apis: self.args.flag_ws_apis.parse()?,
+			origins: self.ws_origins(),
+			hosts: self.ws_hosts(),
+			max_subscriptions: self.args.flag_ws_max_subscriptions,
+			methods: Self::methods(&self.args.flag_ws_methods),
 		};
 
 		Ok(conf)
 	}
 
+	fn ntp_servers(&self) -> Vec<String> {
+		self.args.flag_ntp_servers.split(',')
+			.map(str::trim)
+			.filter(|s| !s.is_empty())
+			.map(Into::into)
+			.collect()
+	}
+
+	fn rpc_limits(&self) -> Result<LimitsConfig, String> {
+		let mut method_concurrency = HashMap::new();
+		if self.args.flag_jsonrpc_method_concurrency != "none" {
+			for limit in self.args.flag_jsonrpc_method_concurrency.split(',') {
+				let mut parts = limit.splitn(2, ':');
+				let method = parts.next().expect("splitn always yields at least one item; qed");
+				let max = parts.next()
+					.and_then(|max| max.parse().ok())
+					.ok_or_else(|| format!("Invalid method concurrency limit given: {}. Expected METHOD:NUM.", limit))?;
+				method_concurrency.insert(method.to_owned(), max);
+			}
+		}
+
+		Ok(LimitsConfig {
+			max_batch_size: match self.args.flag_jsonrpc_max_batch_size {
+				0 => None,
+				max => Some(max),
+			},
+			method_concurrency: method_concurrency,
+			rate_limit: match self.args.flag_jsonrpc_rate_limit {
+				0 => None,
+				rate => Some(rate),
+			},
+			api_keys: match self.args.flag_jsonrpc_api_keys.as_str() {
+				"none" => HashSet::new(),
+				keys => keys.split(',').map(Into::into).collect(),
+			},
+		})
+	}
+
 	fn network_settings(&self) -> NetworkSettings {
 		NetworkSettings {
 			name: self.args.flag_identity.clone(),
@@ -902,6 +1136,10 @@ impl Configuration {
 		Self::interface(&self.network_settings().rpc_interface)
 	}
 
+	fn ws_interface(&self) -> String {
+		Self::interface(&self.args.flag_ws_interface)
+	}
+
 	fn dapps_interface(&self) -> String {
 		match self.args.flag_dapps_interface.as_str() {
 			"local" => "127.0.0.1",
@@ -944,6 +1182,10 @@ impl Configuration {
 		!ui_disabled
 	}
 
+	fn ancient_path(&self) -> Option<String> {
+		self.args.flag_ancient_path.as_ref().map(|path| replace_home(&self.directories().base, path))
+	}
+
 	fn verifier_settings(&self) -> VerifierSettings {
 		let mut settings = VerifierSettings::default();
 		settings.scale_verifiers = self.args.flag_scale_verifiers;
@@ -969,7 +1211,8 @@ mod tests {
 	use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, DataFormat, ExportState};
 	use presale::ImportWallet;
 	use params::SpecType;
-	use account::{AccountCmd, NewAccount, ImportAccounts, ListAccounts};
+	use account::{AccountCmd, NewAccount, ImportAccounts, ListAccounts, ReencryptAccounts};
+	use snapshot::{self, SnapshotCommand};
 	use devtools::{RandomTempPath};
 	use updater::{UpdatePolicy, UpdateFilter, ReleaseTrack};
 	use std::io::Write;
@@ -997,13 +1240,32 @@ mod tests {
 		let args = vec!["parity", "account", "new"];
 		let conf = parse(&args);
 		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Account(AccountCmd::New(NewAccount {
-			iterations: 10240,
+			kdf: KdfParams::Pbkdf2 { iterations: 10240 },
 			path: Directories::default().keys,
 			password_file: None,
 			spec: SpecType::default(),
 		})));
 	}
 
+	#[test]
+	fn test_command_account_reencrypt() {
+		let args = vec!["parity", "account", "reencrypt", "--keys-kdf", "scrypt", "--password", "pwd"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Account(AccountCmd::Reencrypt(ReencryptAccounts {
+			kdf: KdfParams::scrypt(),
+			path: Directories::default().keys,
+			spec: SpecType::default(),
+			password_files: vec!["pwd".into()],
+		})));
+	}
+
+	#[test]
+	fn should_reject_unknown_keys_kdf() {
+		let args = vec!["parity", "account", "new", "--keys-kdf", "md5"];
+		let conf = parse(&args);
+		assert!(conf.into_command().is_err());
+	}
+
 	#[test]
 	fn test_command_account_list() {
 		let args = vec!["parity", "account", "list"];
@@ -1112,6 +1374,27 @@ mod tests {
 		})));
 	}
 
+	#[test]
+	fn test_command_snapshot_export() {
+		let args = vec!["parity", "snapshot", "export", "snapshot.bin"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Snapshot(SnapshotCommand {
+			cache_config: Default::default(),
+			dirs: Default::default(),
+			spec: Default::default(),
+			pruning: Default::default(),
+			pruning_history: 64,
+			pruning_memory: 75,
+			tracing: Default::default(),
+			fat_db: Default::default(),
+			compaction: Default::default(),
+			file_path: Some("snapshot.bin".into()),
+			wal: true,
+			kind: snapshot::Kind::Export,
+			block_at: BlockId::Latest,
+		}));
+	}
+
 	#[test]
 	fn test_command_blockchain_export_with_custom_format() {
 		let args = vec!["parity", "export", "blocks", "--format", "hex", "blockchain.json"];
@@ -1160,14 +1443,17 @@ mod tests {
 			pruning: Default::default(),
 			pruning_history: 64,
 			pruning_memory: 75,
+			pruning_overlay_memory: None,
 			daemon: None,
 			logger_config: Default::default(),
 			miner_options: Default::default(),
 			http_conf: Default::default(),
 			ipc_conf: Default::default(),
+			ws_conf: Default::default(),
 			net_conf: default_network_config(),
 			network_id: None,
 			warp_sync: true,
+			snapshot_manifest_votes: 3,
 			acc_conf: Default::default(),
 			gas_pricer: Default::default(),
 			miner_extras: Default::default(),
@@ -1178,7 +1464,19 @@ mod tests {
 			wal: true,
 			vm_type: Default::default(),
 			geth_compatibility: false,
+			trace_page_size: 10000,
+			max_logs: None,
+			estimate_gas: Default::default(),
+			rpc_limits: Default::default(),
+			rpc_read_only: false,
+			ntp_servers: vec![
+				"0.pool.ntp.org:123".into(),
+				"1.pool.ntp.org:123".into(),
+				"2.pool.ntp.org:123".into(),
+				"3.pool.ntp.org:123".into(),
+			],
 			ui_address: Some(("127.0.0.1".into(), 8180)),
+			ui_request_timeout: None,
 			net_settings: Default::default(),
 			dapps_conf: Default::default(),
 			ipfs_conf: Default::default(),
@@ -1189,11 +1487,19 @@ mod tests {
 			name: "".into(),
 			custom_bootnodes: false,
 			fat_db: Default::default(),
+			key_preimages: false,
+			experimental_parallel_exec: false,
+			trie_encoding: Default::default(),
 			no_periodic_snapshot: false,
+			snapshot_period: 10000,
+			snapshot_conf: Default::default(),
 			stratum: None,
 			check_seal: true,
 			download_old_blocks: true,
 			verifier_settings: Default::default(),
+			ancient_path: None,
+			log_index: false,
+			db_compression: Default::default(),
 		};
 		expected.secretstore_conf.enabled = cfg!(feature = "secretstore");
 		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Run(expected));
@@ -1455,4 +1761,45 @@ mod tests {
 			_ => panic!("Should be Cmd::Run"),
 		}
 	}
+
+	#[test]
+	fn should_parse_rpc_limits() {
+		// given
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--jsonrpc-max-batch-size", "50", "--jsonrpc-method-concurrency", "eth_getLogs:4,trace_filter:1", "--jsonrpc-rate-limit", "100", "--jsonrpc-api-keys", "a,b"]);
+		let conf2 = parse(&["parity", "--jsonrpc-method-concurrency", "eth_getLogs"]);
+
+		// then
+		assert_eq!(conf0.rpc_limits(), Ok(Default::default()));
+		let mut method_concurrency = HashMap::new();
+		method_concurrency.insert("eth_getLogs".to_owned(), 4);
+		method_concurrency.insert("trace_filter".to_owned(), 1);
+		assert_eq!(conf1.rpc_limits(), Ok(LimitsConfig {
+			max_batch_size: Some(50),
+			method_concurrency: method_concurrency,
+			rate_limit: Some(100),
+			api_keys: vec!["a".to_owned(), "b".to_owned()].into_iter().collect(),
+		}));
+		assert!(conf2.rpc_limits().is_err());
+	}
+
+	#[test]
+	fn should_parse_rpc_access_control() {
+		// given
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--jsonrpc-interface-apis", "127.0.0.1:8547=web3,eth;10.0.0.1:8548=personal", "--jsonrpc-methods", "eth_blockNumber,eth_call"]);
+		let conf2 = parse(&["parity", "--jsonrpc-interface-apis", "127.0.0.1:8547"]);
+		let conf3 = parse(&["parity", "--jsonrpc-interface-apis", "https://wallet.example.com=personal"]);
+
+		// then
+		assert_eq!(conf0.http_config().unwrap().interface_apis, Vec::new());
+		assert_eq!(conf0.http_config().unwrap().methods, None);
+		assert_eq!(conf1.http_config().unwrap().interface_apis, vec![
+			("127.0.0.1:8547".parse().unwrap(), "web3,eth".parse().unwrap()),
+			("10.0.0.1:8548".parse().unwrap(), "personal".parse().unwrap()),
+		]);
+		assert_eq!(conf1.http_config().unwrap().methods, Some(vec!["eth_blockNumber".into(), "eth_call".into()]));
+		assert!(conf2.http_config().is_err());
+		assert!(conf3.http_config().is_err());
+	}
 }
@@ -15,13 +15,15 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::{io, env};
+use std::collections::HashMap;
 use std::io::{Write, BufReader, BufRead};
 use std::time::Duration;
 use std::fs::File;
-use util::{clean_0x, U256, Uint, Address, CompactionProfile};
+use util::{clean_0x, U256, Uint, Address, CompactionProfile, CompressionType};
 use util::journaldb::Algorithm;
 use ethcore::client::{Mode, BlockId, VMType, DatabaseCompactionProfile, ClientConfig, VerifierType};
-use ethcore::miner::{PendingSet, GasLimit, PrioritizationStrategy};
+use ethcore::miner::{PendingSet, GasLimit, PrioritizationStrategy, BlockOrdering, UncleStrategy};
+use ethcore::db;
 use cache::CacheConfig;
 use dir::DatabaseDirectories;
 use upgrade::{upgrade, upgrade_data_paths};
@@ -94,6 +96,46 @@ pub fn to_pending_set(s: &str) -> Result<PendingSet, String> {
 	}
 }
 
+/// Dictionary size used by `zstd-dict` column compression.
+const ZSTD_DICTIONARY_BYTES: u32 = 16 * 1024;
+
+/// Parse per-column database compression, given either as `auto` or as a comma-separated
+/// list of `column:type` pairs. `balanced` stores state uncompressed and uses dictionary
+/// compression for bodies and receipts.
+pub fn to_db_compression(s: &str) -> Result<HashMap<Option<u32>, CompressionType>, String> {
+	let spec = match s {
+		"auto" => return Ok(HashMap::new()),
+		"balanced" => "state:none,bodies:zstd-dict,extras:zstd-dict",
+		other => other,
+	};
+
+	spec.split(',').map(|pair| {
+		let mut parts = pair.splitn(2, ':');
+		let column = match parts.next().unwrap_or("") {
+			"state" => db::COL_STATE,
+			"headers" => db::COL_HEADERS,
+			"bodies" => db::COL_BODIES,
+			"extras" => db::COL_EXTRA,
+			"traces" => db::COL_TRACE,
+			"account-bloom" => db::COL_ACCOUNT_BLOOM,
+			"node-info" => db::COL_NODE_INFO,
+			"preimages" => db::COL_PREIMAGES,
+			"trace-index" => db::COL_TRACE_INDEX,
+			"log-index" => db::COL_LOG_INDEX,
+			other => return Err(format!("Invalid database column: {:?}", other)),
+		};
+		let compression = match parts.next().unwrap_or("") {
+			"none" => CompressionType::None,
+			"snappy" => CompressionType::Snappy,
+			"lz4" => CompressionType::Lz4,
+			"zstd" => CompressionType::Zstd,
+			"zstd-dict" => CompressionType::ZstdDictionary(ZSTD_DICTIONARY_BYTES),
+			other => return Err(format!("Invalid compression type in {:?}: {:?}", pair, other)),
+		};
+		Ok((column, compression))
+	}).collect()
+}
+
 pub fn to_gas_limit(s: &str) -> Result<GasLimit, String> {
 	match s {
 		"auto" => Ok(GasLimit::Auto),
@@ -111,6 +153,24 @@ pub fn to_queue_strategy(s: &str) -> Result<PrioritizationStrategy, String> {
 	}
 }
 
+pub fn to_block_ordering(s: &str) -> Result<BlockOrdering, String> {
+	match s {
+		"gas_price" => Ok(BlockOrdering::GasPrice),
+		"sender_fair" => Ok(BlockOrdering::SenderFair),
+		"hint" => Ok(BlockOrdering::Hinted),
+		other => Err(format!("Invalid block ordering: {}", other)),
+	}
+}
+
+pub fn to_uncle_strategy(s: &str) -> Result<UncleStrategy, String> {
+	match s {
+		"all" => Ok(UncleStrategy::All),
+		"profitable" => Ok(UncleStrategy::Profitable),
+		"none" => Ok(UncleStrategy::Disabled),
+		other => Err(format!("Invalid uncle strategy: {}", other)),
+	}
+}
+
 pub fn to_address(s: Option<String>) -> Result<Address, String> {
 	match s {
 		Some(ref a) => clean_0x(a).parse().map_err(|_| format!("Invalid address: {:?}", a)),
@@ -264,6 +324,8 @@ pub fn to_client_config(
 	client_config.name = name;
 	client_config.verifier_type = if check_seal { VerifierType::Canon } else { VerifierType::CanonNoSeal };
 	client_config.spec_name = spec_name;
+	// leave cores for execution and verification.
+	client_config.prefetch_threads = ::std::cmp::min(4, ::num_cpus::get() / 4);
 	client_config
 }
 
@@ -344,7 +406,8 @@ mod tests {
 	use util::{U256};
 	use ethcore::client::{Mode, BlockId};
 	use ethcore::miner::PendingSet;
-	use super::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_address, to_addresses, to_price, geth_ipc_path, to_bootnodes, password_from_file};
+	use util::CompressionType;
+	use super::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_address, to_addresses, to_price, geth_ipc_path, to_bootnodes, password_from_file, to_db_compression};
 
 	#[test]
 	fn test_to_duration() {
@@ -394,6 +457,17 @@ mod tests {
 		assert!(to_u256("u").is_err())
 	}
 
+	#[test]
+	fn test_db_compression() {
+		assert!(to_db_compression("auto").unwrap().is_empty());
+		let balanced = to_db_compression("balanced").unwrap();
+		assert_eq!(balanced[&Some(0)], CompressionType::None);
+		assert_eq!(balanced[&Some(2)], CompressionType::ZstdDictionary(16 * 1024));
+		assert_eq!(to_db_compression("traces:lz4").unwrap()[&Some(4)], CompressionType::Lz4);
+		assert!(to_db_compression("state").is_err());
+		assert!(to_db_compression("blocks:none").is_err());
+	}
+
 	#[test]
 	fn test_pending_set() {
 		assert_eq!(to_pending_set("cheap").unwrap(), PendingSet::AlwaysQueue);
@@ -172,8 +172,12 @@ impl Informant {
 				),
 				_ => String::new(),
 			},
-			format!("{} db {} chain {} queue{}",
+			format!("{} db{} {} chain {} queue{}",
 				paint(Blue.bold(), format!("{:>8}", format_bytes(report.state_db_mem))),
+				match report.state_db_overlay.spilled_bytes {
+					0 => String::new(),
+					spilled => format!(" ({} on disk)", paint(Blue.bold(), format_bytes(spilled))),
+				},
 				paint(Blue.bold(), format!("{:>8}", format_bytes(cache_info.total()))),
 				paint(Blue.bold(), format!("{:>8}", format_bytes(queue_info.mem_used))),
 				match sync_status {
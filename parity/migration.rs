@@ -21,7 +21,7 @@ use std::path::{Path, PathBuf};
 use std::fmt::{Display, Formatter, Error as FmtError};
 use std::sync::Arc;
 use util::journaldb::Algorithm;
-use util::migration::{Manager as MigrationManager, Config as MigrationConfig, Error as MigrationError, Migration};
+use util::migration::{Manager as MigrationManager, Config as MigrationConfig, Error as MigrationError, Migration, Estimate as MigrationEstimate};
 use util::kvdb::{CompactionProfile, Database, DatabaseConfig};
 use ethcore::migrations;
 use ethcore::db;
@@ -30,7 +30,7 @@ use ethcore::migrations::Extract;
 /// Database is assumed to be at default version, when no version file is found.
 const DEFAULT_VERSION: u32 = 5;
 /// Current version of database models.
-const CURRENT_VERSION: u32 = 11;
+const CURRENT_VERSION: u32 = 15;
 /// First version of the consolidated database.
 const CONSOLIDATION_VERSION: u32 = 9;
 /// Defines how many items are migrated to the new version of database at once.
@@ -147,6 +147,10 @@ fn consolidated_database_migrations(compaction_profile: &CompactionProfile) -> R
 	let mut manager = MigrationManager::new(default_migration_settings(compaction_profile));
 	manager.add_migration(migrations::ToV10::new()).map_err(|_| Error::MigrationImpossible)?;
 	manager.add_migration(migrations::TO_V11).map_err(|_| Error::MigrationImpossible)?;
+	manager.add_migration(migrations::TO_V12).map_err(|_| Error::MigrationImpossible)?;
+	manager.add_migration(migrations::TO_V13).map_err(|_| Error::MigrationImpossible)?;
+	manager.add_migration(migrations::TO_V14).map_err(|_| Error::MigrationImpossible)?;
+	manager.add_migration(migrations::TO_V15).map_err(|_| Error::MigrationImpossible)?;
 	Ok(manager)
 }
 
@@ -170,6 +174,7 @@ fn consolidate_database(
 		compaction: config.compaction_profile,
 		columns: None,
 		wal: true,
+		compression: Default::default(),
 	};
 
 	let old_path_str = old_db_path.to_str().ok_or(Error::MigrationImpossible)?;
@@ -219,6 +224,14 @@ fn migrate_database(version: u32, db_path: PathBuf, mut migrations: MigrationMan
 	fs::remove_dir_all(&backup_path).map_err(Into::into)
 }
 
+/// Adds the cost of migrating the database at given position to the estimate.
+fn estimate_database(estimate: &mut MigrationEstimate, version: u32, db_path: PathBuf, mut migrations: MigrationManager) -> Result<(), Error> {
+	if migrations.is_needed(version) {
+		estimate.accrue(migrations.estimate(&db_path, version)?);
+	}
+	Ok(())
+}
+
 fn exists(path: &Path) -> bool {
 	fs::metadata(path).is_ok()
 }
@@ -276,6 +289,35 @@ pub fn migrate(path: &Path, pruning: Algorithm, compaction_profile: CompactionPr
 	update_version(path)
 }
 
+/// Estimates the cost of migrating the database without altering it.
+/// Returns `None` if the database is already up to date.
+pub fn estimate(path: &Path, pruning: Algorithm, compaction_profile: CompactionProfile) -> Result<Option<MigrationEstimate>, Error> {
+	let version = current_version(path)?;
+	if version > CURRENT_VERSION {
+		return Err(Error::FutureDBVersion);
+	}
+
+	if version == CURRENT_VERSION {
+		return Ok(None)
+	}
+
+	let mut estimate = MigrationEstimate::default();
+
+	// the consolidation of the legacy databases itself is a plain copy and isn't included.
+	if version < CONSOLIDATION_VERSION && exists(&legacy::blocks_database_path(path)) {
+		estimate_database(&mut estimate, version, legacy::extras_database_path(path), legacy::extras_database_migrations(&compaction_profile)?)?;
+		estimate_database(&mut estimate, version, legacy::state_database_path(path), legacy::state_database_migrations(pruning, &compaction_profile)?)?;
+		estimate_database(&mut estimate, version, legacy::blocks_database_path(path), legacy::blocks_database_migrations(&compaction_profile)?)?;
+	}
+
+	let version = ::std::cmp::max(CONSOLIDATION_VERSION, version);
+	if version < CURRENT_VERSION && exists(&consolidated_database_path(path)) {
+		estimate_database(&mut estimate, version, consolidated_database_path(path), consolidated_database_migrations(&compaction_profile)?)?;
+	}
+
+	Ok(Some(estimate))
+}
+
 /// Old migrations utilities
 mod legacy {
 	use super::*;
@@ -22,6 +22,7 @@ use ethcore::spec::Spec;
 use ethcore::ethereum;
 use ethcore::client::Mode;
 use ethcore::miner::{GasPricer, GasPriceCalibratorOptions};
+use ethcore::ethstore::KdfParams;
 use user_defaults::UserDefaults;
 
 #[derive(Debug, PartialEq)]
@@ -175,7 +176,7 @@ impl str::FromStr for ResealPolicy {
 
 #[derive(Debug, PartialEq)]
 pub struct AccountsConfig {
-	pub iterations: u32,
+	pub kdf: KdfParams,
 	pub testnet: bool,
 	pub password_files: Vec<String>,
 	pub unlocked_accounts: Vec<Address>,
@@ -185,7 +186,7 @@ pub struct AccountsConfig {
 impl Default for AccountsConfig {
 	fn default() -> Self {
 		AccountsConfig {
-			iterations: 10240,
+			kdf: KdfParams::Pbkdf2 { iterations: 10240 },
 			testnet: false,
 			password_files: Vec::new(),
 			unlocked_accounts: Vec::new(),
@@ -243,6 +244,7 @@ pub struct MinerExtras {
 	pub extra_data: Vec<u8>,
 	pub gas_floor_target: U256,
 	pub gas_ceil_target: U256,
+	pub gas_limit_target: Option<U256>,
 	pub transactions_limit: usize,
 	pub engine_signer: Address,
 }
@@ -254,6 +256,7 @@ impl Default for MinerExtras {
 			extra_data: version_data(),
 			gas_floor_target: U256::from(4_700_000),
 			gas_ceil_target: U256::from(6_283_184),
+			gas_limit_target: None,
 			transactions_limit: 1024,
 			engine_signer: Default::default(),
 		}
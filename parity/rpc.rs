@@ -16,11 +16,13 @@
 
 use std::fmt;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::net::SocketAddr;
 use std::io;
 
 use dir::default_data_path;
 use ethcore_rpc::{self as rpc, HttpServerError, Metadata, Origin, AccessControlAllowOrigin, Host};
+use ethcore_rpc::acl::Acl;
 use ethcore_rpc::informant::{RpcStats, Middleware};
 use helpers::parity_ipc_path;
 use hyper;
@@ -29,7 +31,7 @@ use rpc_apis;
 use rpc_apis::ApiSet;
 use parity_reactor::TokioRemote;
 
-pub use ethcore_rpc::{IpcServer, HttpServer};
+pub use ethcore_rpc::{IpcServer, HttpServer, WsServer};
 
 #[derive(Debug, PartialEq)]
 pub struct HttpConfiguration {
@@ -39,6 +41,10 @@ pub struct HttpConfiguration {
 	pub apis: ApiSet,
 	pub cors: Option<Vec<String>>,
 	pub hosts: Option<Vec<String>>,
+	/// Additional endpoints, each serving its own API set instead of `apis`.
+	pub interface_apis: Vec<(SocketAddr, ApiSet)>,
+	/// Methods allowed on this interface, `None` for all.
+	pub methods: Option<Vec<String>>,
 }
 
 impl Default for HttpConfiguration {
@@ -50,6 +56,35 @@ impl Default for HttpConfiguration {
 			apis: ApiSet::UnsafeContext,
 			cors: None,
 			hosts: Some(Vec::new()),
+			interface_apis: Vec::new(),
+			methods: None,
+		}
+	}
+}
+
+#[derive(Debug, PartialEq)]
+pub struct WsConfiguration {
+	pub enabled: bool,
+	pub interface: String,
+	pub port: u16,
+	pub apis: ApiSet,
+	pub origins: Option<Vec<String>>,
+	pub hosts: Option<Vec<String>>,
+	pub max_subscriptions: usize,
+	pub methods: Option<Vec<String>>,
+}
+
+impl Default for WsConfiguration {
+	fn default() -> Self {
+		WsConfiguration {
+			enabled: true,
+			interface: "127.0.0.1".into(),
+			port: 8546,
+			apis: ApiSet::UnsafeContext,
+			origins: Some(Vec::new()),
+			hosts: Some(Vec::new()),
+			max_subscriptions: rpc::v1::DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION,
+			methods: None,
 		}
 	}
 }
@@ -59,6 +94,7 @@ pub struct IpcConfiguration {
 	pub enabled: bool,
 	pub socket_addr: String,
 	pub apis: ApiSet,
+	pub methods: Option<Vec<String>>,
 }
 
 impl Default for IpcConfiguration {
@@ -68,6 +104,7 @@ impl Default for IpcConfiguration {
 			enabled: true,
 			socket_addr: parity_ipc_path(&data_dir, "$BASE/jsonrpc.ipc"),
 			apis: ApiSet::IpcContext,
+			methods: None,
 		}
 	}
 }
@@ -86,6 +123,7 @@ pub struct Dependencies {
 	pub apis: Arc<rpc_apis::Dependencies>,
 	pub remote: TokioRemote,
 	pub stats: Arc<RpcStats>,
+	pub read_only: bool,
 }
 
 pub struct RpcExtractor;
@@ -94,33 +132,80 @@ impl rpc::HttpMetaExtractor<Metadata> for RpcExtractor {
 		let origin = req.headers().get::<hyper::header::Origin>()
 			.map(|origin| format!("{}://{}", origin.scheme, origin.host))
 			.unwrap_or_else(|| "unknown".into());
+		let api_key = req.headers().get_raw("x-api-key")
+			.and_then(|values| values.first())
+			.map(|value| String::from_utf8_lossy(value).into_owned());
 		let mut metadata = Metadata::default();
 		metadata.origin = Origin::Rpc(origin);
+		metadata.api_key = api_key;
+		metadata.ip = req.transport().0.peer_addr().ok().map(|addr| addr.ip());
 		metadata
 	}
 }
 
-impl rpc::IpcMetaExtractor<Metadata> for RpcExtractor {
+/// Metadata extractor of the IPC server.
+///
+/// The metadata is extracted once per connection, so every client connected to the socket
+/// gets its own session and the requests it queues for confirmation can be told apart.
+#[derive(Default)]
+pub struct IpcExtractor {
+	last_session: AtomicUsize,
+}
+
+impl rpc::IpcMetaExtractor<Metadata> for IpcExtractor {
 	fn extract(&self, _req: &rpc::IpcRequestContext) -> Metadata {
 		let mut metadata = Metadata::default();
-		// TODO [ToDr] Extract proper session id when it's available in context.
-		metadata.origin = Origin::Ipc(1.into());
+		let session = self.last_session.fetch_add(1, Ordering::SeqCst) as u64 + 1;
+		metadata.origin = Origin::Ipc(session.into());
+		metadata
+	}
+}
+
+impl rpc::WsMetaExtractor<Metadata> for RpcExtractor {
+	fn extract(&self, req: &rpc::WsRequestContext) -> Metadata {
+		let mut metadata = Metadata::default();
+		let id = req.session_id as u64;
+		metadata.origin = Origin::Ws(id.into());
+		metadata.session = Some(Arc::new(rpc::PubSubSession::new(req.sender())));
 		metadata
 	}
 }
 
-pub fn new_http(conf: HttpConfiguration, deps: &Dependencies) -> Result<Option<HttpServer>, String> {
+/// Starts the HTTP JSON-RPC server and one more server for every additional endpoint.
+///
+/// API sets are only ever granted per listening address, so exposing a privileged set
+/// requires a client to be able to reach the dedicated endpoint.
+pub fn new_http(conf: HttpConfiguration, deps: &Dependencies) -> Result<Vec<HttpServer>, String> {
 	if !conf.enabled {
-		return Ok(None);
+		return Ok(Vec::new());
 	}
 
 	let url = format!("{}:{}", conf.interface, conf.port);
 	let addr = url.parse().map_err(|_| format!("Invalid JSONRPC listen host/port given: {}", url))?;
-	Ok(Some(setup_http_rpc_server(deps, &addr, conf.cors, conf.hosts, conf.apis)?))
+	let mut endpoints = vec![(addr, conf.apis)];
+	endpoints.extend(conf.interface_apis);
+
+	let mut servers = Vec::with_capacity(endpoints.len());
+	for (addr, apis) in endpoints {
+		let acl = AccessControl {
+			methods: conf.methods.clone(),
+		};
+		servers.push(setup_http_rpc_server(deps, &addr, conf.cors.clone(), conf.hosts.clone(), apis, acl)?);
+	}
+	Ok(servers)
 }
 
-fn setup_apis(apis: ApiSet, deps: &Dependencies) -> MetaIoHandler<Metadata, Middleware> {
-	rpc_apis::setup_rpc(deps.stats.clone(), deps.apis.clone(), apis)
+/// Access control applied on top of the interface API set.
+#[derive(Debug, Default, PartialEq)]
+pub struct AccessControl {
+	/// Methods allowed on the interface, `None` for all.
+	pub methods: Option<Vec<String>>,
+}
+
+fn setup_apis(apis: ApiSet, acl: AccessControl, deps: &Dependencies) -> MetaIoHandler<Metadata, Acl<Middleware>> {
+	let handler = rpc_apis::setup_rpc(deps.stats.clone(), deps.apis.clone(), apis);
+	let methods = acl.methods.map(|methods| methods.into_iter().collect());
+	MetaIoHandler::with_middleware(Acl::new(handler, methods).read_only(deps.read_only))
 }
 
 pub fn setup_http_rpc_server(
@@ -128,9 +213,10 @@ pub fn setup_http_rpc_server(
 	url: &SocketAddr,
 	cors_domains: Option<Vec<String>>,
 	allowed_hosts: Option<Vec<String>>,
-	apis: ApiSet
+	apis: ApiSet,
+	acl: AccessControl
 ) -> Result<HttpServer, String> {
-	let handler = setup_apis(apis, dependencies);
+	let handler = setup_apis(apis, acl, dependencies);
 	let remote = dependencies.remote.clone();
 	let cors_domains: Option<Vec<_>> = cors_domains.map(|domains| domains.into_iter().map(AccessControlAllowOrigin::from).collect());
 	let allowed_hosts: Option<Vec<_>> = allowed_hosts.map(|hosts| hosts.into_iter().map(Host::from).collect());
@@ -147,14 +233,48 @@ pub fn setup_http_rpc_server(
 
 pub fn new_ipc(conf: IpcConfiguration, deps: &Dependencies) -> Result<Option<IpcServer>, String> {
 	if !conf.enabled { return Ok(None); }
-	Ok(Some(setup_ipc_rpc_server(deps, &conf.socket_addr, conf.apis)?))
+	let acl = AccessControl {
+		methods: conf.methods,
+	};
+	Ok(Some(setup_ipc_rpc_server(deps, &conf.socket_addr, conf.apis, acl)?))
 }
 
-pub fn setup_ipc_rpc_server(dependencies: &Dependencies, addr: &str, apis: ApiSet) -> Result<IpcServer, String> {
-	let handler = setup_apis(apis, dependencies);
+pub fn setup_ipc_rpc_server(dependencies: &Dependencies, addr: &str, apis: ApiSet, acl: AccessControl) -> Result<IpcServer, String> {
+	let handler = setup_apis(apis, acl, dependencies);
 	let remote = dependencies.remote.clone();
-	match rpc::start_ipc(addr, handler, remote, RpcExtractor) {
+	match rpc::start_ipc(addr, handler, remote, IpcExtractor::default()) {
 		Err(io_error) => Err(format!("RPC io error: {}", io_error)),
 		Ok(server) => Ok(server)
 	}
 }
+
+pub fn new_ws(conf: WsConfiguration, deps: &Dependencies) -> Result<Option<WsServer>, String> {
+	if !conf.enabled {
+		return Ok(None);
+	}
+
+	let url = format!("{}:{}", conf.interface, conf.port);
+	let addr = url.parse().map_err(|_| format!("Invalid WebSockets listen host/port given: {}", url))?;
+	let acl = AccessControl {
+		methods: conf.methods,
+	};
+	Ok(Some(setup_ws_rpc_server(deps, &addr, conf.origins, conf.hosts, conf.apis, acl)?))
+}
+
+pub fn setup_ws_rpc_server(
+	dependencies: &Dependencies,
+	url: &SocketAddr,
+	allowed_origins: Option<Vec<String>>,
+	allowed_hosts: Option<Vec<String>>,
+	apis: ApiSet,
+	acl: AccessControl
+) -> Result<WsServer, String> {
+	let handler = setup_apis(apis, acl, dependencies);
+	let remote = dependencies.remote.clone();
+	let allowed_origins: Option<Vec<_>> = allowed_origins.map(|origins| origins.into_iter().map(rpc::ws::Origin::from).collect());
+	let allowed_hosts: Option<Vec<_>> = allowed_hosts.map(|hosts| hosts.into_iter().map(rpc::ws::Host::from).collect());
+	match rpc::start_ws(url, handler, remote, allowed_origins.into(), allowed_hosts.into(), RpcExtractor) {
+		Err(e) => Err(format!("WebSockets error: {:?}. Make sure that another instance of an Ethereum client is not running or change the address using the --ws-port and --ws-interface options.", e)),
+		Ok(server) => Ok(server),
+	}
+}
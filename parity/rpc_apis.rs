@@ -23,15 +23,18 @@ use std::sync::Arc;
 pub use ethcore_rpc::SignerService;
 
 use ethcore::account_provider::AccountProvider;
-use ethcore::client::Client;
-use ethcore::miner::{Miner, ExternalMiner};
+use ethcore::client::{BlockChainClient, Client};
+use ethcore::miner::{Miner, MinerService, ExternalMiner};
 use ethcore::snapshot::SnapshotService;
 use ethcore_rpc::{Metadata, NetworkSettings};
 use ethcore_rpc::informant::{Middleware, RpcStats, ClientNotifier};
+use ethcore_rpc::limits::RequestLimits;
+use ethcore_rpc::node_health::NodeHealth;
 use ethcore_rpc::dispatch::FullDispatcher;
 use ethsync::{ManageNetwork, SyncProvider};
 use hash_fetch::fetch::Client as FetchClient;
 use jsonrpc_core::{MetaIoHandler};
+use parity_reactor::Remote;
 use updater::Updater;
 use ethcore_logger::RotatingLogger;
 
@@ -126,9 +129,15 @@ pub struct Dependencies {
 	pub net_service: Arc<ManageNetwork>,
 	pub updater: Arc<Updater>,
 	pub geth_compatibility: bool,
+	pub trace_page_size: usize,
+	pub max_logs: Option<usize>,
+	pub max_subscriptions: usize,
+	pub remote: Remote,
+	pub request_limits: Arc<RequestLimits>,
 	pub dapps_interface: Option<String>,
 	pub dapps_port: Option<u16>,
 	pub fetch: FetchClient,
+	pub health: Arc<NodeHealth>,
 }
 
 fn to_modules(apis: &[Api]) -> BTreeMap<String, String> {
@@ -192,7 +201,7 @@ pub fn setup_rpc(stats: Arc<RpcStats>, deps: Arc<Dependencies>, apis: ApiSet) ->
 
 	let mut handler = MetaIoHandler::with_middleware(Middleware::new(stats, ClientNotifier {
 		client: deps.client.clone(),
-	}));
+	}, deps.request_limits.clone()));
 
 	// it's turned into vector, cause ont of the cases requires &[]
 	let apis = apis.list_apis().into_iter().collect::<Vec<_>>();
@@ -218,6 +227,7 @@ pub fn setup_rpc(stats: Arc<RpcStats>, deps: Arc<Dependencies>, apis: ApiSet) ->
 						pending_nonce_from_queue: deps.geth_compatibility,
 						allow_pending_receipt_query: !deps.geth_compatibility,
 						send_block_number_in_get_work: !deps.geth_compatibility,
+						max_logs: deps.max_logs,
 					}
 				);
 				handler.extend_with(client.to_delegate());
@@ -225,6 +235,30 @@ pub fn setup_rpc(stats: Arc<RpcStats>, deps: Arc<Dependencies>, apis: ApiSet) ->
 				let filter_client = EthFilterClient::new(&deps.client, &deps.miner);
 				handler.extend_with(filter_client.to_delegate());
 
+				let pubsub_client = EthPubSubClient::new(&deps.client, &deps.sync, deps.remote.clone(), deps.max_subscriptions);
+				let notifier = pubsub_client.handler();
+				deps.client.add_notify(notifier.clone());
+				let notifier = Arc::downgrade(&notifier);
+				let work_notifier = notifier.clone();
+				deps.miner.add_work_listener(Box::new(move |work| {
+					if let Some(notifier) = work_notifier.upgrade() {
+						notifier.new_work(work);
+					}
+				}));
+				let (client, miner) = (Arc::downgrade(&deps.client), Arc::downgrade(&deps.miner));
+				deps.miner.add_transactions_listener(Box::new(move |hashes| {
+					if let Some(notifier) = notifier.upgrade() {
+						notifier.new_pending_transactions(hashes, |hash| {
+							let (client, miner) = match (client.upgrade(), miner.upgrade()) {
+								(Some(client), Some(miner)) => (client, miner),
+								_ => return None,
+							};
+							miner.transaction(client.chain_info().best_block_number, hash).map(|pending| pending.transaction)
+						});
+					}
+				}));
+				handler.extend_with(pubsub_client.to_delegate());
+
 				add_signing_methods!(EthSigning, handler, deps);
 			},
 			Api::Personal => {
@@ -250,6 +284,7 @@ pub fn setup_rpc(stats: Arc<RpcStats>, deps: Arc<Dependencies>, apis: ApiSet) ->
 					signer,
 					deps.dapps_interface.clone(),
 					deps.dapps_port,
+					deps.health.clone(),
 				).to_delegate());
 
 				add_signing_methods!(EthSigning, handler, deps);
@@ -268,7 +303,7 @@ pub fn setup_rpc(stats: Arc<RpcStats>, deps: Arc<Dependencies>, apis: ApiSet) ->
 				).to_delegate())
 			},
 			Api::Traces => {
-				handler.extend_with(TracesClient::new(&deps.client, &deps.miner).to_delegate())
+				handler.extend_with(TracesClient::new(&deps.client, &deps.miner, deps.trace_page_size).to_delegate())
 			},
 			Api::Rpc => {
 				let modules = to_modules(&apis);
@@ -14,19 +14,25 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::net::{TcpListener};
+use std::time::Duration;
 use ctrlc::CtrlC;
 use fdlimit::raise_fd_limit;
 use ethcore_rpc::{NetworkSettings, informant, is_major_importing};
+use ethcore_rpc::limits::{LimitsConfig, RequestLimits};
+use ethcore_rpc::node_health::NodeHealth;
 use ethsync::NetworkConfiguration;
-use util::{Colour, version, Mutex, Condvar};
+use util::{Colour, version, Mutex, Condvar, CompressionType};
+use util::kvdb::Database;
+use util::trie::NodeEncoding;
 use io::{MayPanic, ForwardPanic, PanicHandler};
 use ethcore_logger::{Config as LogConfig, RotatingLogger};
 use ethcore::miner::{StratumOptions, Stratum};
-use ethcore::client::{Client, Mode, DatabaseCompactionProfile, VMType, BlockChainClient};
+use ethcore::client::{Client, Mode, DatabaseCompactionProfile, VMType, BlockChainClient, EstimateGasConfig};
 use ethcore::service::ClientService;
-use ethcore::account_provider::{AccountProvider, AccountProviderSettings};
+use ethcore::account_provider::{AccountProvider, AccountProviderSettings, SigningLog};
 use ethcore::miner::{Miner, MinerService, ExternalMiner, MinerOptions};
 use ethcore::snapshot;
 use ethcore::verification::queue::VerifierSettings;
@@ -36,7 +42,7 @@ use updater::{UpdatePolicy, Updater};
 use parity_reactor::EventLoop;
 use hash_fetch::fetch::{Fetch, Client as FetchClient};
 
-use rpc::{HttpConfiguration, IpcConfiguration};
+use rpc::{HttpConfiguration, IpcConfiguration, WsConfiguration};
 use params::{
 	SpecType, Pruning, AccountsConfig, GasPricerConfig, MinerExtras, Switch,
 	tracing_switch_to_bool, fatdb_switch_to_bool, mode_switch_to_bool
@@ -55,9 +61,6 @@ use rpc_apis;
 use rpc;
 use url;
 
-// how often to take periodic snapshots.
-const SNAPSHOT_PERIOD: u64 = 10000;
-
 // how many blocks to wait before starting a periodic snapshot.
 const SNAPSHOT_HISTORY: u64 = 100;
 
@@ -72,15 +75,18 @@ pub struct RunCmd {
 	pub pruning: Pruning,
 	pub pruning_history: u64,
 	pub pruning_memory: usize,
+	pub pruning_overlay_memory: Option<usize>,
 	/// Some if execution should be daemonized. Contains pid_file path.
 	pub daemon: Option<String>,
 	pub logger_config: LogConfig,
 	pub miner_options: MinerOptions,
 	pub http_conf: HttpConfiguration,
 	pub ipc_conf: IpcConfiguration,
+	pub ws_conf: WsConfiguration,
 	pub net_conf: NetworkConfiguration,
 	pub network_id: Option<u64>,
 	pub warp_sync: bool,
+	pub snapshot_manifest_votes: usize,
 	pub acc_conf: AccountsConfig,
 	pub gas_pricer: GasPricerConfig,
 	pub miner_extras: MinerExtras,
@@ -88,11 +94,21 @@ pub struct RunCmd {
 	pub mode: Option<Mode>,
 	pub tracing: Switch,
 	pub fat_db: Switch,
+	pub key_preimages: bool,
+	pub experimental_parallel_exec: bool,
+	pub trie_encoding: NodeEncoding,
 	pub compaction: DatabaseCompactionProfile,
 	pub wal: bool,
 	pub vm_type: VMType,
 	pub geth_compatibility: bool,
+	pub trace_page_size: usize,
+	pub max_logs: Option<usize>,
+	pub estimate_gas: EstimateGasConfig,
+	pub rpc_limits: LimitsConfig,
+	pub rpc_read_only: bool,
+	pub ntp_servers: Vec<String>,
 	pub ui_address: Option<(String, u16)>,
+	pub ui_request_timeout: Option<Duration>,
 	pub net_settings: NetworkSettings,
 	pub dapps_conf: dapps::Configuration,
 	pub ipfs_conf: ipfs::Configuration,
@@ -104,9 +120,14 @@ pub struct RunCmd {
 	pub custom_bootnodes: bool,
 	pub stratum: Option<StratumOptions>,
 	pub no_periodic_snapshot: bool,
+	pub snapshot_period: u64,
+	pub snapshot_conf: snapshot::SnapshotConfiguration,
 	pub check_seal: bool,
 	pub download_old_blocks: bool,
 	pub verifier_settings: VerifierSettings,
+	pub ancient_path: Option<String>,
+	pub log_index: bool,
+	pub db_compression: HashMap<Option<u32>, CompressionType>,
 }
 
 pub fn open_ui(dapps_conf: &dapps::Configuration, signer_conf: &signer::Configuration) -> Result<(), String> {
@@ -150,6 +171,15 @@ impl ::local_store::NodeInfo for FullNodeInfo {
 			.filter(|tx| local_txs.contains_key(&tx.hash()))
 			.collect()
 	}
+
+	fn queued_transactions(&self) -> Vec<::ethcore::transaction::PendingTransaction> {
+		let local_txs = self.miner.local_transactions();
+		self.miner.pending_transactions()
+			.into_iter()
+			.chain(self.miner.future_transactions())
+			.filter(|tx| !local_txs.contains_key(&tx.hash()))
+			.collect()
+	}
 }
 
 pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> Result<(bool, Option<String>), String> {
@@ -247,6 +277,7 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	}
 	sync_config.fork_block = spec.fork_block();
 	sync_config.warp_sync = cmd.warp_sync;
+	sync_config.snapshot_manifest_votes = cmd.snapshot_manifest_votes;
 	sync_config.download_old_blocks = cmd.download_old_blocks;
 
 	let passwords = passwords_from_files(&cmd.acc_conf.password_files)?;
@@ -260,6 +291,7 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	miner.set_author(cmd.miner_extras.author);
 	miner.set_gas_floor_target(cmd.miner_extras.gas_floor_target);
 	miner.set_gas_ceil_target(cmd.miner_extras.gas_ceil_target);
+	miner.set_gas_limit_target(cmd.miner_extras.gas_limit_target);
 	miner.set_extra_data(cmd.miner_extras.extra_data);
 	miner.set_transactions_limit(cmd.miner_extras.transactions_limit);
 	miner.set_minimal_gas_price(initial_min_gas_price);
@@ -301,6 +333,18 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	);
 
 	client_config.queue.verifier_settings = cmd.verifier_settings;
+	client_config.overlay_memory_budget = cmd.pruning_overlay_memory.map(|mb| mb * 1024 * 1024);
+	client_config.blockchain.ancient_path = cmd.ancient_path.map(Into::into);
+	client_config.blockchain.log_index = cmd.log_index;
+	client_config.db_compression = cmd.db_compression;
+	client_config.key_preimages = cmd.key_preimages;
+	client_config.parallel_exec_threads = match cmd.experimental_parallel_exec {
+		true => ::num_cpus::get(),
+		false => 0,
+	};
+	client_config.trie_encoding = cmd.trie_encoding;
+	client_config.estimate_gas = cmd.estimate_gas;
+	client_config.snapshot = cmd.snapshot_conf;
 
 	// set up bootnodes
 	let mut net_conf = cmd.net_conf;
@@ -355,6 +399,21 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 			Err(e) => warn!("Error loading cached pending transactions from disk: {}", e),
 		}
 
+		// re-validate and re-queue the rest of the transaction pool.
+		match store.queued_transactions() {
+			Ok(queued) => {
+				let transactions = queued.into_iter().map(|tx| tx.transaction.into()).collect();
+				let failed = miner.import_external_transactions(&*client, transactions)
+					.into_iter()
+					.filter(|res| res.is_err())
+					.count();
+				if failed > 0 {
+					debug!("{} saved queued transactions are no longer valid", failed);
+				}
+			}
+			Err(e) => warn!("Error loading cached queued transactions from disk: {}", e),
+		}
+
 		Arc::new(store)
 	};
 
@@ -410,7 +469,7 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	let deps_for_rpc_apis = Arc::new(rpc_apis::Dependencies {
 		signer_service: Arc::new(rpc_apis::SignerService::new(move || {
 			signer::generate_new_token(signer_path.clone()).map_err(|e| format!("{:?}", e))
-		}, cmd.ui_address)),
+		}, cmd.ui_address, cmd.ui_request_timeout)),
 		snapshot: snapshot_service.clone(),
 		client: client.clone(),
 		sync: sync_provider.clone(),
@@ -423,6 +482,11 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 		net_service: manage_network.clone(),
 		updater: updater.clone(),
 		geth_compatibility: cmd.geth_compatibility,
+		trace_page_size: cmd.trace_page_size,
+		max_logs: cmd.max_logs,
+		max_subscriptions: cmd.ws_conf.max_subscriptions,
+		remote: event_loop.remote(),
+		request_limits: Arc::new(RequestLimits::new(cmd.rpc_limits.clone())),
 		dapps_interface: match cmd.dapps_conf.enabled {
 			true => Some(cmd.dapps_conf.interface.clone()),
 			false => None,
@@ -432,17 +496,26 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 			false => None,
 		},
 		fetch: fetch.clone(),
+		health: Arc::new(NodeHealth::new(
+			client.clone(),
+			sync_provider.clone(),
+			manage_network.clone(),
+			db_dirs.db_root_path(),
+			cmd.ntp_servers.clone(),
+		)),
 	});
 
 	let dependencies = rpc::Dependencies {
 		apis: deps_for_rpc_apis.clone(),
 		remote: event_loop.raw_remote(),
 		stats: rpc_stats.clone(),
+		read_only: cmd.rpc_read_only,
 	};
 
 	// start rpc servers
-	let http_server = rpc::new_http(cmd.http_conf, &dependencies)?;
+	let http_servers = rpc::new_http(cmd.http_conf, &dependencies)?;
 	let ipc_server = rpc::new_ipc(cmd.ipc_conf, &dependencies)?;
+	let ws_server = rpc::new_ws(cmd.ws_conf, &dependencies)?;
 
 	// the dapps server
 	let dapps_deps = dapps::Dependencies {
@@ -508,7 +581,7 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 				service.client(),
 				move || is_major_importing(Some(sync.status().state), client.queue_info()),
 				service.io().channel(),
-				SNAPSHOT_PERIOD,
+				cmd.snapshot_period,
 				SNAPSHOT_HISTORY,
 			));
 
@@ -530,7 +603,7 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	let restart = wait_for_exit(panic_handler, Some(updater), Some(client), can_restart);
 
 	// drop this stuff as soon as exit detected.
-	drop((http_server, ipc_server, dapps_server, signer_server, secretstore_key_server, ipfs_server, event_loop));
+	drop((http_servers, ipc_server, ws_server, dapps_server, signer_server, secretstore_key_server, ipfs_server, event_loop));
 
 	info!("Finishing work, please wait...");
 
@@ -575,9 +648,15 @@ fn prepare_account_provider(spec: &SpecType, dirs: &Directories, data_dir: &str,
 		hardware_wallet_classic_key: spec == &SpecType::Classic,
 	};
 	let account_provider = AccountProvider::new(
-		Box::new(EthStore::open_with_iterations(dir, cfg.iterations).map_err(|e| format!("Could not open keys directory: {}", e))?),
+		Box::new(EthStore::open_with_kdf(dir, cfg.kdf).map_err(|e| format!("Could not open keys directory: {}", e))?),
 		account_settings);
 
+	// record signing operations in a database kept next to the keys.
+	let log_path = path.join("signing_log");
+	let log_path = log_path.to_str().ok_or_else(|| "Invalid signing log path".to_owned())?;
+	let log_db = Database::open_default(log_path).map_err(|e| format!("Could not open signing log: {}", e))?;
+	account_provider.set_signing_log(SigningLog::new(Arc::new(log_db), None));
+
 	for a in cfg.unlocked_accounts {
 		// Check if the account exists
 		if !account_provider.has_account(a).unwrap_or(false) {
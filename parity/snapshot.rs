@@ -21,7 +21,7 @@ use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use ethcore::snapshot::{Progress, RestorationStatus, SnapshotService as SS};
-use ethcore::snapshot::io::{SnapshotReader, PackedReader, PackedWriter};
+use ethcore::snapshot::io::{SnapshotReader, SnapshotWriter, PackedReader, PackedWriter};
 use ethcore::snapshot::service::Service as SnapshotService;
 use ethcore::service::ClientService;
 use ethcore::client::{Mode, DatabaseCompactionProfile, VMType};
@@ -43,7 +43,11 @@ pub enum Kind {
 	/// Take a snapshot.
 	Take,
 	/// Restore a snapshot.
-	Restore
+	Restore,
+	/// Export the local snapshot to a single file.
+	Export,
+	/// Import a snapshot from a single file.
+	Import,
 }
 
 /// Command for snapshot creation or restoration.
@@ -77,7 +81,7 @@ fn restore_using<R: SnapshotReader>(snapshot: Arc<SnapshotService>, reader: &R,
 		format!("Failed to begin restoration: {}", e)
 	})?;
 
-	let (num_state, num_blocks) = (manifest.state_hashes.len(), manifest.block_hashes.len());
+	let (num_state, num_blocks) = (manifest.state_hashes.len(), manifest.block_hashes.len() + manifest.trace_hashes.len());
 
 	let informant_handle = snapshot.clone();
 	::std::thread::spawn(move || {
@@ -106,7 +110,7 @@ fn restore_using<R: SnapshotReader>(snapshot: Arc<SnapshotService>, reader: &R,
  	}
 
 	info!("Restoring blocks");
-	for &block_hash in &manifest.block_hashes {
+	for &block_hash in manifest.block_hashes.iter().chain(manifest.trace_hashes.iter()) {
 		if snapshot.status() == RestorationStatus::Failed {
 			return Err("Restoration failed".into());
 		}
@@ -261,7 +265,7 @@ impl SnapshotCommand {
 			}
  		});
 
-		if let Err(e) = service.client().take_snapshot(writer, block_at, &*progress) {
+		if let Err(e) = service.client().take_snapshot(writer, block_at, None, &*progress) {
 			let _ = ::std::fs::remove_file(&file_path);
 			return Err(format!("Encountered fatal error while creating snapshot: {}", e));
 		}
@@ -273,6 +277,68 @@ impl SnapshotCommand {
 
 		Ok(())
 	}
+
+	/// Export the local snapshot into a single file, to be moved
+	/// out-of-band and imported elsewhere.
+	pub fn export_snapshot(self) -> Result<(), String> {
+		let file_path = self.file_path.clone().ok_or("No file path provided.".to_owned())?;
+		let file_path: PathBuf = file_path.into();
+		let (service, _panic_handler) = self.start_service()?;
+
+		let snapshot = service.snapshot_service();
+		let reader = snapshot.reader();
+		let reader = reader.as_ref().ok_or("No local snapshot found.".to_owned())?;
+		let manifest = reader.manifest().clone();
+
+		info!("Exporting snapshot at block #{} (0x{:?}) to '{}'", manifest.block_number, manifest.block_hash, file_path.display());
+
+		let export = || -> Result<(), String> {
+			let mut writer = PackedWriter::new(&file_path)
+				.map_err(|e| format!("Failed to open snapshot writer: {}", e))?;
+
+			for &hash in &manifest.state_hashes {
+				let chunk = reader.chunk(hash)
+					.map_err(|e| format!("Encountered error while reading chunk {:?}: {}", hash, e))?;
+				writer.write_state_chunk(hash, &chunk)
+					.map_err(|e| format!("Failed to write chunk {:?}: {}", hash, e))?;
+			}
+
+			for &hash in &manifest.block_hashes {
+				let chunk = reader.chunk(hash)
+					.map_err(|e| format!("Encountered error while reading chunk {:?}: {}", hash, e))?;
+				writer.write_block_chunk(hash, &chunk)
+					.map_err(|e| format!("Failed to write chunk {:?}: {}", hash, e))?;
+			}
+
+			for &hash in &manifest.trace_hashes {
+				let chunk = reader.chunk(hash)
+					.map_err(|e| format!("Encountered error while reading chunk {:?}: {}", hash, e))?;
+				writer.write_trace_chunk(hash, &chunk)
+					.map_err(|e| format!("Failed to write chunk {:?}: {}", hash, e))?;
+			}
+
+			writer.finish(manifest.clone())
+				.map_err(|e| format!("Failed to write snapshot manifest: {}", e))
+		};
+
+		if let Err(e) = export() {
+			let _ = ::std::fs::remove_file(&file_path);
+			return Err(e);
+		}
+
+		info!("Exported {} state chunks and {} block chunks.", manifest.state_hashes.len(), manifest.block_hashes.len());
+		Ok(())
+	}
+
+	/// Import a snapshot from a single file. The node is restored from it
+	/// and it becomes the local snapshot, to be served to warp peers.
+	pub fn import_snapshot(self) -> Result<(), String> {
+		if self.file_path.is_none() {
+			return Err("No file path provided.".into());
+		}
+
+		self.restore()
+	}
 }
 
 /// Execute this snapshot command.
@@ -280,6 +346,8 @@ pub fn execute(cmd: SnapshotCommand) -> Result<String, String> {
 	match cmd.kind {
 		Kind::Take => cmd.take_snapshot()?,
 		Kind::Restore => cmd.restore()?,
+		Kind::Export => cmd.export_snapshot()?,
+		Kind::Import => cmd.import_snapshot()?,
 	}
 
 	Ok(String::new())
@@ -29,8 +29,10 @@ extern crate time;
 extern crate transient_hashmap;
 
 extern crate jsonrpc_core;
+extern crate jsonrpc_pubsub;
 pub extern crate jsonrpc_http_server as http;
 pub extern crate jsonrpc_ipc_server as ipc;
+pub extern crate jsonrpc_ws_server as ws;
 
 extern crate ethash;
 extern crate ethcore;
@@ -66,8 +68,10 @@ pub mod v1;
 
 pub use ipc::{Server as IpcServer, MetaExtractor as IpcMetaExtractor, RequestContext as IpcRequestContext};
 pub use http::{HttpMetaExtractor, Server as HttpServer, Error as HttpServerError, AccessControlAllowOrigin, Host};
+pub use ws::{Server as WsServer, Error as WsError, MetaExtractor as WsMetaExtractor, RequestContext as WsRequestContext};
+pub use jsonrpc_pubsub::Session as PubSubSession;
 
-pub use v1::{SigningQueue, SignerService, ConfirmationsQueue, NetworkSettings, Metadata, Origin, informant, dispatch};
+pub use v1::{SigningQueue, SignerService, ConfirmationsQueue, NetworkSettings, Metadata, Origin, acl, informant, limits, dispatch, node_health};
 pub use v1::block_import::is_major_importing;
 
 use std::net::SocketAddr;
@@ -112,3 +116,25 @@ pub fn start_ipc<M, S, H, T>(
 		.session_metadata_extractor(extractor)
 		.start(addr)
 }
+
+/// Start ws server asynchronously and returns result with `Server` handle on success or an error.
+pub fn start_ws<M, S, H, T>(
+	addr: &SocketAddr,
+	handler: H,
+	remote: tokio_core::reactor::Remote,
+	allowed_origins: ws::DomainsValidation<ws::Origin>,
+	allowed_hosts: ws::DomainsValidation<ws::Host>,
+	extractor: T,
+) -> Result<WsServer, WsError> where
+	M: jsonrpc_core::Metadata,
+	S: jsonrpc_core::Middleware<M>,
+	H: Into<jsonrpc_core::MetaIoHandler<M, S>>,
+	T: WsMetaExtractor<M>,
+{
+	ws::ServerBuilder::new(handler)
+		.event_loop_remote(remote)
+		.allowed_origins(allowed_origins)
+		.allowed_hosts(allowed_hosts)
+		.session_meta_extractor(extractor)
+		.start(addr)
+}
@@ -0,0 +1,156 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Per-interface RPC access control.
+
+use std::collections::HashSet;
+
+use futures::{self, Future};
+use jsonrpc_core as rpc;
+
+use v1::helpers::errors;
+use v1::helpers::limits;
+use v1::Metadata;
+
+/// Methods sending transactions, signing, or changing the node and its accounts.
+const MUTATING_METHODS: &'static [&'static str] = &[
+	"eth_sendRawTransaction", "eth_sendTransaction", "eth_submitTransaction", "eth_sign", "eth_signTransaction",
+	"eth_submitWork", "eth_submitHashrate",
+	"parity_postTransaction", "parity_postSign", "parity_removeTransaction",
+	"parity_addReservedPeer", "parity_removeReservedPeer", "parity_dropNonReservedPeers", "parity_acceptNonReservedPeers",
+	"parity_startNetwork", "parity_stopNetwork", "parity_executeUpgrade", "parity_backupDatabase",
+	"parity_killAccount", "parity_removeAddress", "parity_importGethAccounts", "parity_changePassword",
+	"parity_newVault", "parity_openVault", "parity_closeVault", "parity_changeVault", "parity_changeVaultPassword",
+];
+
+/// Prefixes of the families of mutating methods.
+const MUTATING_PREFIXES: &'static [&'static str] = &["personal_", "signer_", "parity_set", "parity_newAccount"];
+
+/// Returns true if the method changes the state of the node or submits anything to the network.
+pub fn is_mutating(method: &str) -> bool {
+	MUTATING_METHODS.contains(&method) || MUTATING_PREFIXES.iter().any(|prefix| method.starts_with(prefix))
+}
+
+/// Access control middleware.
+///
+/// Calls to methods outside of the allowed list, or mutating ones in read-only mode,
+/// are rejected. Everything else is passed to the handler exposing the API set of the interface.
+pub struct Acl<S: rpc::Middleware<Metadata>> {
+	methods: Option<HashSet<String>>,
+	read_only: bool,
+	handler: rpc::MetaIoHandler<Metadata, S>,
+}
+
+impl<S: rpc::Middleware<Metadata>> Acl<S> {
+	/// Creates new access control middleware, `None` allows all methods.
+	pub fn new(handler: rpc::MetaIoHandler<Metadata, S>, methods: Option<HashSet<String>>) -> Self {
+		Acl {
+			methods: methods,
+			read_only: false,
+			handler: handler,
+		}
+	}
+
+	/// Rejects calls to mutating methods, whatever the allowed methods are.
+	pub fn read_only(mut self, read_only: bool) -> Self {
+		self.read_only = read_only;
+		self
+	}
+}
+
+impl<S: rpc::Middleware<Metadata>> rpc::Middleware<Metadata> for Acl<S> {
+	fn on_request<F>(&self, request: rpc::Request, meta: Metadata, _process: F) -> rpc::FutureResponse where
+		F: FnOnce(rpc::Request, Metadata) -> rpc::FutureResponse,
+	{
+		if self.read_only {
+			let denied = limits::methods(&request).into_iter().find(|method| is_mutating(method)).map(errors::read_only);
+			if let Some(error) = denied {
+				return futures::future::ok(limits::reject(&request, error)).boxed();
+			}
+		}
+
+		if let Some(ref allowed) = self.methods {
+			let denied = limits::methods(&request).into_iter().find(|method| !allowed.contains(*method)).map(errors::method_not_allowed);
+			if let Some(error) = denied {
+				return futures::future::ok(limits::reject(&request, error)).boxed();
+			}
+		}
+
+		self.handler.handle_rpc_request(request, meta)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashSet;
+	use jsonrpc_core::{MetaIoHandler, Value};
+	use v1::types::Origin;
+	use v1::Metadata;
+	use super::{Acl, is_mutating};
+
+	fn handler(method: &str) -> MetaIoHandler<Metadata> {
+		let mut io = MetaIoHandler::default();
+		io.add_method(method, |_| Ok(Value::Bool(true)));
+		io
+	}
+
+	fn metadata(origin: &str) -> Metadata {
+		let mut meta = Metadata::default();
+		meta.origin = Origin::Rpc(origin.into());
+		meta
+	}
+
+	#[test]
+	fn should_reject_methods_not_allowed() {
+		let mut methods = HashSet::new();
+		methods.insert("eth_blockNumber".to_owned());
+		let mut default = handler("eth_blockNumber");
+		default.add_method("eth_getLogs", |_| Ok(Value::Bool(true)));
+		let io = MetaIoHandler::with_middleware(Acl::new(default, Some(methods)));
+
+		let request = r#"{"jsonrpc":"2.0","method":"eth_getLogs","params":[],"id":1}"#;
+		let response = r#"{"jsonrpc":"2.0","error":{"code":-32082,"message":"Method eth_getLogs is not allowed on this interface."},"id":1}"#;
+		assert_eq!(io.handle_request_sync(request, metadata("unknown")), Some(response.to_owned()));
+
+		let request = r#"{"jsonrpc":"2.0","method":"eth_blockNumber","params":[],"id":1}"#;
+		let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+		assert_eq!(io.handle_request_sync(request, metadata("unknown")), Some(response.to_owned()));
+	}
+
+	#[test]
+	fn should_reject_mutating_methods_in_read_only_mode() {
+		let mut default = handler("eth_blockNumber");
+		default.add_method("personal_unlockAccount", |_| Ok(Value::Bool(true)));
+		let io = MetaIoHandler::with_middleware(Acl::new(default, None).read_only(true));
+
+		let request = r#"{"jsonrpc":"2.0","method":"personal_unlockAccount","params":[],"id":1}"#;
+		let response = r#"{"jsonrpc":"2.0","error":{"code":-32084,"message":"Method personal_unlockAccount changes the state of the node, which is disabled in read-only mode."},"id":1}"#;
+		assert_eq!(io.handle_request_sync(request, metadata("unknown")), Some(response.to_owned()));
+
+		let request = r#"{"jsonrpc":"2.0","method":"eth_blockNumber","params":[],"id":1}"#;
+		let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+		assert_eq!(io.handle_request_sync(request, metadata("unknown")), Some(response.to_owned()));
+	}
+
+	#[test]
+	fn should_classify_mutating_methods() {
+		assert!(is_mutating("eth_sendRawTransaction"));
+		assert!(is_mutating("parity_setMinGasPrice"));
+		assert!(is_mutating("personal_sendTransaction"));
+		assert!(!is_mutating("eth_call"));
+		assert!(!is_mutating("parity_pendingTransactions"));
+	}
+}
@@ -0,0 +1,81 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Cache of `eth_call` outputs, so repeated view calls don't re-execute the EVM.
+
+use util::{Address, Bytes, H256};
+use util::cache::MemoryLruCache;
+
+/// Identifies a call: the block it executes against, its sender and the hash of its unsigned transaction.
+pub type CallKey = (H256, Address, H256);
+
+/// LRU cache of call outputs, dropped whenever the best block changes.
+pub struct CallCache {
+	best_block: H256,
+	outputs: MemoryLruCache<CallKey, Bytes>,
+	max_size: usize,
+}
+
+impl CallCache {
+	/// Create a new cache holding up to `max_size` bytes of output.
+	pub fn new(max_size: usize) -> Self {
+		CallCache {
+			best_block: H256::default(),
+			outputs: MemoryLruCache::new(max_size),
+			max_size: max_size,
+		}
+	}
+
+	/// Output of the call identified by `key`, if it was executed since `best_block` became the best block.
+	pub fn get(&mut self, best_block: &H256, key: &CallKey) -> Option<Bytes> {
+		self.update_best_block(best_block);
+		self.outputs.get_mut(key).map(|output| output.clone())
+	}
+
+	/// Remember the output of the call identified by `key`, executed while `best_block` was the best block.
+	pub fn insert(&mut self, best_block: &H256, key: CallKey, output: Bytes) {
+		self.update_best_block(best_block);
+		self.outputs.insert(key, output);
+	}
+
+	fn update_best_block(&mut self, best_block: &H256) {
+		if *best_block != self.best_block {
+			trace!(target: "rpc", "Best block changed to {}, dropping cached calls", best_block);
+			self.best_block = *best_block;
+			self.outputs = MemoryLruCache::new(self.max_size);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::{Address, H256};
+	use super::CallCache;
+
+	#[test]
+	fn invalidates_on_new_best_block() {
+		let mut cache = CallCache::new(1024);
+		let (best, next) = (H256::from(1), H256::from(2));
+		let key = (best, Address::from(3), H256::from(4));
+
+		assert_eq!(cache.get(&best, &key), None);
+		cache.insert(&best, key, vec![1, 2, 3]);
+		assert_eq!(cache.get(&best, &key), Some(vec![1, 2, 3]));
+
+		// same block, but a new best block arrived since.
+		assert_eq!(cache.get(&next, &key), None);
+	}
+}
@@ -36,19 +36,25 @@ use ethcore::ids::BlockId;
 use ethcore::miner::MinerService;
 use ethcore::client::MiningBlockChainClient;
 use ethcore::transaction::{Action, SignedTransaction, PendingTransaction, Transaction};
-use ethcore::account_provider::AccountProvider;
+use ethcore::account_provider::{AccountProvider, SigningDecision};
 
 use jsonrpc_core::Error;
-use v1::helpers::{errors, TransactionRequest, FilledTransactionRequest, ConfirmationPayload};
+use v1::helpers::{errors, TransactionRequest, FilledTransactionRequest, ConfirmationPayload, TypedDataRequest, RequestSource};
 use v1::types::{
 	H256 as RpcH256, H520 as RpcH520, Bytes as RpcBytes,
 	RichRawTransaction as RpcRichRawTransaction,
 	ConfirmationPayload as RpcConfirmationPayload,
 	ConfirmationResponse,
 	SignRequest as RpcSignRequest,
+	SignTypedDataRequest as RpcSignTypedDataRequest,
 	DecryptRequest as RpcDecryptRequest,
 };
 
+/// Number of recent blocks the default gas price is derived from.
+const GAS_PRICE_SAMPLE_BLOCKS: usize = 100;
+/// Percentile of the gas prices of each block the default gas price is derived from.
+const GAS_PRICE_PERCENTILE: f64 = 60.0;
+
 /// Has the capability to dispatch, sign, and decrypt.
 ///
 /// Requires a clone implementation, with the implication that it be cheap;
@@ -452,9 +458,17 @@ pub fn execute<D: Dispatcher + 'static>(
 	dispatcher: D,
 	accounts: Arc<AccountProvider>,
 	payload: ConfirmationPayload,
-	pass: SignWith
+	pass: SignWith,
+	source: RequestSource,
 ) -> BoxFuture<WithToken<ConfirmationResponse>, Error> {
-	match payload {
+	let (account, payload_hash) = signing_subject(&payload);
+	if let Err(e) = check_vault_policy(&accounts, &payload) {
+		record_signing(&accounts, &source, account, payload_hash, SigningDecision::Rejected);
+		return future::err(e).boxed();
+	}
+
+	let log_accounts = accounts.clone();
+	let response = match payload {
 		ConfirmationPayload::SendTransaction(request) => {
 			let condition = request.condition.clone().map(Into::into);
 			dispatcher.sign(accounts, request, pass)
@@ -475,21 +489,19 @@ pub fn execute<D: Dispatcher + 'static>(
 					.map(ConfirmationResponse::SignTransaction)
 				).boxed()
 		},
-		ConfirmationPayload::Signature(address, mut data) => {
-			let mut message_data =
-				format!("\x19Ethereum Signed Message:\n{}", data.len())
-				.into_bytes();
-			message_data.append(&mut data);
-			let res = signature(&accounts, address, message_data.sha3(), pass)
+		ConfirmationPayload::Signature(address, data) => {
+			let res = signature(&accounts, address, eth_data_hash(&data), pass)
 				.map(|result| result
-					.map(|rsv| {
-						let mut vrs = [0u8; 65];
-						let rsv = rsv.as_ref();
-						vrs[0] = rsv[64] + 27;
-						vrs[1..33].copy_from_slice(&rsv[0..32]);
-						vrs[33..65].copy_from_slice(&rsv[32..64]);
-						H520(vrs)
-					})
+					.map(eth_signature)
+					.map(RpcH520::from)
+					.map(ConfirmationResponse::Signature)
+				);
+			future::done(res).boxed()
+		},
+		ConfirmationPayload::SignTypedData(request) => {
+			let res = signature(&accounts, request.address, request.hash, pass)
+				.map(|result| result
+					.map(eth_signature)
 					.map(RpcH520::from)
 					.map(ConfirmationResponse::Signature)
 				);
@@ -503,9 +515,88 @@ pub fn execute<D: Dispatcher + 'static>(
 				);
 			future::done(res).boxed()
 		},
+	};
+
+	response.then(move |result| {
+		let (hash, decision) = match result {
+			Ok(ref response) => (response_hash(response).or(payload_hash), SigningDecision::Signed),
+			Err(_) => (payload_hash, SigningDecision::Failed),
+		};
+		record_signing(&log_accounts, &source, account, hash, decision);
+		result
+	}).boxed()
+}
+
+/// Account requested to sign the payload and the hash of the signed data, if it is known before signing.
+pub fn signing_subject(payload: &ConfirmationPayload) -> (Address, Option<H256>) {
+	match *payload {
+		ConfirmationPayload::SendTransaction(ref request) => (request.from, None),
+		ConfirmationPayload::SignTransaction(ref request) => (request.from, None),
+		ConfirmationPayload::Signature(address, ref data) => (address, Some(eth_data_hash(data))),
+		ConfirmationPayload::SignTypedData(ref request) => (request.address, Some(request.hash)),
+		ConfirmationPayload::Decrypt(address, ref data) => (address, Some(data.sha3())),
+	}
+}
+
+/// Hash of the transaction a signing request produced, if any.
+pub fn response_hash(response: &ConfirmationResponse) -> Option<H256> {
+	match *response {
+		ConfirmationResponse::SendTransaction(ref hash) => Some(hash.clone().into()),
+		ConfirmationResponse::SignTransaction(ref tx) => Some(tx.transaction.hash.clone().into()),
+		_ => None,
 	}
 }
 
+/// Record the outcome of a signing request in the signing log of the account provider.
+pub fn record_signing(accounts: &AccountProvider, source: &RequestSource, account: Address, hash: Option<H256>, decision: SigningDecision) {
+	accounts.record_signing(account, source.origin.to_string(), source.method.clone(), hash, decision);
+}
+
+/// Reject requests from accounts stored in vaults whose policy does not allow them.
+pub fn check_vault_policy(accounts: &AccountProvider, payload: &ConfirmationPayload) -> Result<(), Error> {
+	let (sender, method, transaction) = match *payload {
+		ConfirmationPayload::SendTransaction(ref request) => (request.from, "sendTransaction", Some(request)),
+		ConfirmationPayload::SignTransaction(ref request) => (request.from, "signTransaction", Some(request)),
+		ConfirmationPayload::Signature(address, _) => (address, "sign", None),
+		ConfirmationPayload::SignTypedData(ref request) => (request.address, "signTypedData", None),
+		ConfirmationPayload::Decrypt(address, _) => (address, "decrypt", None),
+	};
+
+	let policy = match accounts.account_policy(&sender) {
+		Some(policy) => policy,
+		None => return Ok(()),
+	};
+
+	if !policy.allows_method(method) {
+		return Err(errors::policy_violation(&format!("{} is not allowed", method)));
+	}
+
+	match transaction {
+		Some(request) if !policy.allows_transaction(request.to.as_ref(), &request.value) =>
+			Err(errors::policy_violation("recipient or value is not allowed")),
+		_ => Ok(()),
+	}
+}
+
+// hash of the prefixed message signed by `eth_sign`.
+fn eth_data_hash(data: &[u8]) -> H256 {
+	let mut message_data =
+		format!("\x19Ethereum Signed Message:\n{}", data.len())
+		.into_bytes();
+	message_data.extend_from_slice(data);
+	message_data.sha3()
+}
+
+// convert an `rsv` signature into the `vrs` form returned by `eth_sign`.
+fn eth_signature(rsv: Signature) -> H520 {
+	let mut vrs = [0u8; 65];
+	let rsv = rsv.as_ref();
+	vrs[0] = rsv[64] + 27;
+	vrs[1..33].copy_from_slice(&rsv[0..32]);
+	vrs[33..65].copy_from_slice(&rsv[32..64]);
+	H520(vrs)
+}
+
 fn signature(accounts: &AccountProvider, address: Address, hash: H256, password: SignWith) -> Result<WithToken<Signature>, Error> {
 	match password.clone() {
 		SignWith::Nothing => accounts.sign(address, None, hash).map(WithToken::No),
@@ -550,10 +641,20 @@ fn decrypt(accounts: &AccountProvider, address: Address, msg: Bytes, password: S
 }
 
 /// Extract the default gas price from a client and miner.
+///
+/// This is the median over the recent blocks with transactions of the gas price at a fixed
+/// percentile of each block, so that a few blocks full of cheap or expensive transactions
+/// don't dominate it.
 pub fn default_gas_price<C, M>(client: &C, miner: &M) -> U256
 	where C: MiningBlockChainClient, M: MinerService
 {
-	client.gas_price_corpus(100).median().cloned().unwrap_or_else(|| miner.sensible_gas_price())
+	let history = client.fee_history(GAS_PRICE_SAMPLE_BLOCKS, BlockId::Latest, &[GAS_PRICE_PERCENTILE]);
+	let corpus: Corpus<U256> = history.map_or_else(Vec::new, |history| history.gas_prices)
+		.into_iter()
+		.filter_map(|prices| prices.into_iter().next())
+		.filter(|price| !price.is_zero())
+		.collect();
+	corpus.median().cloned().unwrap_or_else(|| miner.sensible_gas_price())
 }
 
 /// Convert RPC confirmation payload to signer confirmation payload.
@@ -578,5 +679,13 @@ pub fn from_rpc<D>(payload: RpcConfirmationPayload, default_account: Address, di
 		RpcConfirmationPayload::Signature(RpcSignRequest { address, data }) => {
 			future::ok(ConfirmationPayload::Signature(address.into(), data.into())).boxed()
 		},
+		RpcConfirmationPayload::SignTypedData(RpcSignTypedDataRequest { address, hash, primary_type, fields }) => {
+			future::ok(ConfirmationPayload::SignTypedData(TypedDataRequest {
+				address: address.into(),
+				hash: hash.into(),
+				primary_type: primary_type,
+				fields: fields,
+			})).boxed()
+		},
 	}
 }
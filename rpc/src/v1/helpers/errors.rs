@@ -22,16 +22,26 @@ macro_rules! rpc_unimplemented {
 
 use std::fmt;
 use rlp::DecoderError;
+use rustc_serialize::hex::ToHex;
+use util::U256;
 use ethcore::error::{Error as EthcoreError, CallError, TransactionError};
+use ethcore::evm::Error as EvmError;
 use ethcore::account_provider::{SignError as AccountError};
+use ethstore::Error as StoreError;
 use jsonrpc_core::{Error, ErrorCode, Value};
 
-mod codes {
+/// Registry of server error codes.
+///
+/// Codes are stable: once released a code keeps its meaning, so clients can branch on them.
+/// Errors mapped from `ethcore` error enums additionally carry a `data` object whose `kind`
+/// field names the exact error (e.g. `StatePruned`, `TransactionUnderpriced`, `AccountLocked`).
+pub mod codes {
 	// NOTE [ToDr] Codes from [-32099, -32000]
 	pub const UNSUPPORTED_REQUEST: i64 = -32000;
 	pub const NO_WORK: i64 = -32001;
 	pub const NO_AUTHOR: i64 = -32002;
 	pub const NO_NEW_WORK: i64 = -32003;
+	pub const STATE_PRUNED: i64 = -32004;
 	pub const UNKNOWN_ERROR: i64 = -32009;
 	pub const TRANSACTION_ERROR: i64 = -32010;
 	pub const EXECUTION_ERROR: i64 = -32015;
@@ -40,13 +50,36 @@ mod codes {
 	pub const ACCOUNT_LOCKED: i64 = -32020;
 	pub const PASSWORD_INVALID: i64 = -32021;
 	pub const ACCOUNT_ERROR: i64 = -32023;
+	pub const POLICY_VIOLATION: i64 = -32024;
 	pub const REQUEST_REJECTED: i64 = -32040;
 	pub const REQUEST_REJECTED_LIMIT: i64 = -32041;
 	pub const REQUEST_NOT_FOUND: i64 = -32042;
+	pub const REQUEST_EXPIRED: i64 = -32043;
 	pub const ENCRYPTION_ERROR: i64 = -32055;
 	pub const FETCH_ERROR: i64 = -32060;
 	pub const NO_LIGHT_PEERS: i64 = -32065;
 	pub const DEPRECATED: i64 = -32070;
+	pub const SUBSCRIPTIONS_LIMIT: i64 = -32075;
+	pub const SUBSCRIPTION_LAGGING: i64 = -32076;
+	pub const RATE_LIMITED: i64 = -32080;
+	pub const METHOD_BUSY: i64 = -32081;
+	pub const METHOD_NOT_ALLOWED: i64 = -32082;
+	pub const TOO_MANY_LOGS: i64 = -32083;
+	pub const READ_ONLY: i64 = -32084;
+}
+
+/// Structured `data` payload: a stable `kind` identifier plus extra fields.
+fn payload(kind: &str, fields: Vec<(&str, Value)>) -> Option<Value> {
+	let mut data = ::serde_json::Map::new();
+	data.insert("kind".into(), Value::String(kind.into()));
+	for (name, value) in fields {
+		data.insert(name.into(), value);
+	}
+	Some(Value::Object(data))
+}
+
+fn hex(value: U256) -> Value {
+	Value::String(format!("{:#x}", value))
 }
 
 pub fn unimplemented(details: Option<String>) -> Error {
@@ -81,6 +114,14 @@ pub fn request_rejected() -> Error {
 	}
 }
 
+pub fn request_expired() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::REQUEST_EXPIRED),
+		message: "Request has expired before it was confirmed.".into(),
+		data: None,
+	}
+}
+
 pub fn request_rejected_limit() -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::REQUEST_REJECTED_LIMIT),
@@ -89,6 +130,14 @@ pub fn request_rejected_limit() -> Error {
 	}
 }
 
+pub fn policy_violation(reason: &str) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::POLICY_VIOLATION),
+		message: format!("Request violates vault policy: {}", reason),
+		data: None,
+	}
+}
+
 pub fn account<T: fmt::Debug>(error: &str, details: T) -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::ACCOUNT_ERROR),
@@ -124,11 +173,66 @@ pub fn execution<T: fmt::Debug>(data: T) -> Error {
 	}
 }
 
+/// Error for a call which ended in an exception; `output` is what the call returned.
+pub fn vm(error: &EvmError, output: &[u8]) -> Error {
+	match *error {
+		EvmError::Reverted { .. } => reverted(output),
+		ref e => Error {
+			code: ErrorCode::ServerError(codes::EXECUTION_ERROR),
+			message: format!("VM execution error: {}", e),
+			data: None,
+		},
+	}
+}
+
+/// Error for a call reverted with `output`; standard `Error(string)` reasons are decoded into the message.
+pub fn reverted(output: &[u8]) -> Error {
+	let message = match revert_reason(output) {
+		Some(reason) => format!("revert: {}", reason),
+		None => "revert".into(),
+	};
+
+	Error {
+		code: ErrorCode::ServerError(codes::EXECUTION_ERROR),
+		message: message,
+		data: Some(Value::String(format!("0x{}", output.to_hex()))),
+	}
+}
+
+/// Decodes the ABI-encoded `Error(string)` payload used by Solidity's `revert("...")` and `require(.., "...")`.
+fn revert_reason(output: &[u8]) -> Option<String> {
+	// first four bytes of sha3("Error(string)")
+	const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+	if output.len() < 4 || output[..4] != ERROR_SELECTOR {
+		return None;
+	}
+
+	let data = &output[4..];
+	let word = |pos: usize| if data.len() >= 32 && pos <= data.len() - 32 {
+		Some(U256::from(&data[pos..pos + 32]))
+	} else {
+		None
+	};
+
+	let offset = match word(0) {
+		Some(ref offset) if *offset <= U256::from(data.len()) => offset.low_u64() as usize,
+		_ => return None,
+	};
+	let len = match word(offset) {
+		Some(ref len) if *len <= U256::from(data.len() - offset - 32) => len.low_u64() as usize,
+		_ => return None,
+	};
+	let start = offset + 32;
+
+	String::from_utf8(data[start..start + len].to_vec()).ok()
+}
+
 pub fn state_pruned() -> Error {
 	Error {
-		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
+		code: ErrorCode::ServerError(codes::STATE_PRUNED),
 		message: "This request is not supported because your node is running with state pruning. Run with --pruning=archive.".into(),
-		data: None
+		data: payload("StatePruned", vec![]),
 	}
 }
 
@@ -232,11 +336,22 @@ pub fn from_fetch_error<T: fmt::Debug>(error: T) -> Error {
 	}
 }
 
+/// Stable name of an account error, used as the `kind` of the error payload.
+fn account_error_kind(error: &AccountError) -> &'static str {
+	match *error {
+		AccountError::NotUnlocked => "AccountLocked",
+		AccountError::NotFound | AccountError::SStore(StoreError::InvalidAccount) => "AccountNotFound",
+		AccountError::SStore(StoreError::InvalidPassword) => "InvalidPassword",
+		AccountError::Hardware(_) => "HardwareWalletError",
+		AccountError::SStore(_) => "KeyStoreError",
+	}
+}
+
 pub fn from_signing_error(error: AccountError) -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::ACCOUNT_LOCKED),
 		message: "Your account is locked. Unlock the account via CLI, personal_unlockAccount or use Trusted Signer.".into(),
-		data: Some(Value::String(format!("{:?}", error))),
+		data: payload(account_error_kind(&error), vec![("details", Value::String(format!("{:?}", error)))]),
 	}
 }
 
@@ -244,7 +359,7 @@ pub fn from_password_error(error: AccountError) -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::PASSWORD_INVALID),
 		message: "Account password is invalid or account does not exist.".into(),
-		data: Some(Value::String(format!("{:?}", error))),
+		data: payload(account_error_kind(&error), vec![("details", Value::String(format!("{:?}", error)))]),
 	}
 }
 
@@ -254,8 +369,8 @@ pub fn transaction_message(error: TransactionError) -> String {
 	match error {
 		AlreadyImported => "Transaction with the same hash was already imported.".into(),
 		Old => "Transaction nonce is too low. Try incrementing the nonce.".into(),
-		TooCheapToReplace => {
-			"Transaction gas price is too low. There is another transaction with same nonce in the queue. Try increasing the gas price or incrementing the nonce.".into()
+		TooCheapToReplace { minimal, got } => {
+			format!("Transaction gas price is too low. There is another transaction with same nonce in the queue (minimal: {}, got: {}). Try increasing the gas price or incrementing the nonce.", minimal, got)
 		},
 		LimitReached => {
 			"There are too many transactions in the queue. Your transaction was dropped due to limit. Try increasing the fee.".into()
@@ -280,13 +395,34 @@ pub fn transaction_message(error: TransactionError) -> String {
 	}
 }
 
+/// Structured payload describing a transaction error.
+fn transaction_payload(error: TransactionError) -> Option<Value> {
+	use ethcore::error::TransactionError::*;
+
+	match error {
+		AlreadyImported => payload("TransactionAlreadyImported", vec![]),
+		Old => payload("TransactionNonceTooLow", vec![]),
+		TooCheapToReplace { minimal, got } => payload("TransactionReplacementUnderpriced", vec![("minimal", hex(minimal)), ("got", hex(got))]),
+		LimitReached => payload("TransactionQueueFull", vec![]),
+		InsufficientGas { minimal, got } => payload("TransactionGasTooLow", vec![("minimal", hex(minimal)), ("got", hex(got))]),
+		InsufficientGasPrice { minimal, got } => payload("TransactionUnderpriced", vec![("minimal", hex(minimal)), ("got", hex(got))]),
+		InsufficientBalance { balance, cost } => payload("InsufficientFunds", vec![("balance", hex(balance)), ("cost", hex(cost))]),
+		GasLimitExceeded { limit, got } => payload("TransactionGasLimitExceeded", vec![("limit", hex(limit)), ("got", hex(got))]),
+		InvalidNetworkId => payload("InvalidNetworkId", vec![]),
+		InvalidGasLimit(bounds) => payload("InvalidGasLimit", vec![("got", hex(bounds.found))]),
+		SenderBanned => payload("SenderBanned", vec![]),
+		RecipientBanned => payload("RecipientBanned", vec![]),
+		CodeBanned => payload("CodeBanned", vec![]),
+	}
+}
+
 pub fn from_transaction_error(error: EthcoreError) -> Error {
 
 	if let EthcoreError::Transaction(e) = error {
 		Error {
 			code: ErrorCode::ServerError(codes::TRANSACTION_ERROR),
 			message: transaction_message(e),
-			data: None,
+			data: transaction_payload(e),
 		}
 	} else {
 		Error {
@@ -311,6 +447,7 @@ pub fn from_call_error(error: CallError) -> Error {
 		CallError::StateCorrupt => state_corrupt(),
 		CallError::Exceptional => exceptional(),
 		CallError::Execution(e) => execution(e),
+		CallError::Reverted(output) => reverted(&output),
 		CallError::TransactionNotFound => internal("{}, this should not be the case with eth_call, most likely a bug.", CallError::TransactionNotFound),
 	}
 }
@@ -323,6 +460,72 @@ pub fn unknown_block() -> Error {
 	}
 }
 
+pub fn subscriptions_limit(limit: usize) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::SUBSCRIPTIONS_LIMIT),
+		message: format!("Too many subscriptions on this connection. At most {} are allowed.", limit),
+		data: None,
+	}
+}
+
+pub fn subscription_lagging() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::SUBSCRIPTION_LAGGING),
+		message: "Subscription dropped: notifications were not consumed fast enough.".into(),
+		data: None,
+	}
+}
+
+pub fn batch_too_large(limit: usize) -> Error {
+	Error {
+		code: ErrorCode::InvalidRequest,
+		message: format!("Batch request too large. At most {} calls are allowed.", limit),
+		data: None,
+	}
+}
+
+pub fn rate_limited() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::RATE_LIMITED),
+		message: "Too many requests. Try again later.".into(),
+		data: None,
+	}
+}
+
+pub fn method_busy(method: &str) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::METHOD_BUSY),
+		message: format!("Too many concurrent {} calls. Try again later.", method),
+		data: None,
+	}
+}
+
+pub fn method_not_allowed(method: &str) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::METHOD_NOT_ALLOWED),
+		message: format!("Method {} is not allowed on this interface.", method),
+		data: None,
+	}
+}
+
+pub fn read_only(method: &str) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::READ_ONLY),
+		message: format!("Method {} changes the state of the node, which is disabled in read-only mode.", method),
+		data: None,
+	}
+}
+
+pub fn too_many_logs(limit: usize, next_block: u64) -> Error {
+	let mut data = ::serde_json::Map::new();
+	data.insert("nextBlock".into(), Value::String(format!("0x{:x}", next_block)));
+	Error {
+		code: ErrorCode::ServerError(codes::TOO_MANY_LOGS),
+		message: format!("Query returned more than {} logs. Query up to the block before nextBlock and continue from nextBlock.", limit),
+		data: Some(Value::Object(data)),
+	}
+}
+
 pub fn no_light_peers() -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::NO_LIGHT_PEERS),
@@ -338,3 +541,34 @@ pub fn deprecated<T: Into<Option<String>>>(message: T) -> Error {
 		data: message.into().map(Value::String),
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use ethcore::error::{Error as EthcoreError, TransactionError};
+	use ethcore::account_provider::SignError;
+	use super::{from_transaction_error, from_signing_error};
+
+	#[test]
+	fn should_describe_transaction_errors() {
+		let error = from_transaction_error(EthcoreError::Transaction(TransactionError::InsufficientGasPrice {
+			minimal: 20.into(),
+			got: 1.into(),
+		}));
+
+		assert_eq!(
+			serde_json::to_string(&error.data).unwrap(),
+			r#"{"got":"0x1","kind":"TransactionUnderpriced","minimal":"0x14"}"#
+		);
+	}
+
+	#[test]
+	fn should_describe_account_errors() {
+		let error = from_signing_error(SignError::NotFound);
+
+		assert_eq!(
+			serde_json::to_string(&error.data).unwrap(),
+			r#"{"details":"NotFound","kind":"AccountNotFound"}"#
+		);
+	}
+}
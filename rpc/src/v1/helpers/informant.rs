@@ -20,10 +20,12 @@ use std::fmt;
 use std::sync::Arc;
 use std::sync::atomic::{self, AtomicUsize};
 use std::time;
-use futures::Future;
+use futures::{self, Future};
 use jsonrpc_core as rpc;
 use order_stat;
 use util::RwLock;
+use v1::helpers::limits::{self, RequestLimits};
+use v1::Metadata;
 
 const RATE_SECONDS: usize = 10;
 const STATS_SAMPLES: usize = 60;
@@ -180,18 +182,20 @@ pub trait ActivityNotifier: Send + Sync + 'static {
 	fn active(&self);
 }
 
-/// Stats-counting RPC middleware
+/// Stats-counting and request-limiting RPC middleware
 pub struct Middleware<T: ActivityNotifier = ClientNotifier> {
 	stats: Arc<RpcStats>,
 	notifier: T,
+	limits: Arc<RequestLimits>,
 }
 
 impl<T: ActivityNotifier> Middleware<T> {
-	/// Create new Middleware with stats counter and activity notifier.
-	pub fn new(stats: Arc<RpcStats>, notifier: T) -> Self {
+	/// Create new Middleware with stats counter, activity notifier and request limits.
+	pub fn new(stats: Arc<RpcStats>, notifier: T, limits: Arc<RequestLimits>) -> Self {
 		Middleware {
 			stats: stats,
 			notifier: notifier,
+			limits: limits,
 		}
 	}
 
@@ -200,17 +204,24 @@ impl<T: ActivityNotifier> Middleware<T> {
 	}
 }
 
-impl<M: rpc::Metadata, T: ActivityNotifier> rpc::Middleware<M> for Middleware<T> {
-	fn on_request<F>(&self, request: rpc::Request, meta: M, process: F) -> rpc::FutureResponse where
-		F: FnOnce(rpc::Request, M) -> rpc::FutureResponse,
+impl<T: ActivityNotifier> rpc::Middleware<Metadata> for Middleware<T> {
+	fn on_request<F>(&self, request: rpc::Request, meta: Metadata, process: F) -> rpc::FutureResponse where
+		F: FnOnce(rpc::Request, Metadata) -> rpc::FutureResponse,
 	{
 		let start = time::Instant::now();
-		let response = process(request, meta);
-
 		self.notifier.active();
 		let stats = self.stats.clone();
 		stats.count_request();
+
+		let permit = match self.limits.check(&request, &self.limits.client_key(&meta)) {
+			Ok(permit) => permit,
+			Err(error) => return futures::future::ok(limits::reject(&request, error)).boxed(),
+		};
+
+		let response = process(request, meta);
 		response.map(move |res| {
+			// the request is done, release its concurrency slots.
+			drop(permit);
 			stats.add_roundtrip(Self::as_micro(start.elapsed()));
 			res
 		}).boxed()
@@ -0,0 +1,335 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! RPC request limits.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use jsonrpc_core as rpc;
+use util::Mutex;
+
+use v1::helpers::errors;
+use v1::Metadata;
+use v1::types::Origin;
+
+/// Number of clients whose buckets are kept. The buckets of the clients seen first are forgotten beyond it.
+const MAX_TRACKED_CLIENTS: usize = 4096;
+
+/// Request limits configuration.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LimitsConfig {
+	/// Maximal number of calls in a single batch request.
+	pub max_batch_size: Option<usize>,
+	/// Maximal number of calls to given method processed at the same time.
+	pub method_concurrency: HashMap<String, usize>,
+	/// Number of calls per second a single client can make.
+	pub rate_limit: Option<u32>,
+	/// API keys telling clients apart for rate limiting, instead of their address.
+	pub api_keys: HashSet<String>,
+}
+
+/// Token bucket of a single client.
+struct Bucket {
+	tokens: f64,
+	updated: Instant,
+}
+
+impl Bucket {
+	fn refill(&mut self, rate: f64) {
+		let elapsed = self.updated.elapsed();
+		let elapsed = elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 / 1_000_000_000f64;
+		self.tokens = (self.tokens + elapsed * rate).min(rate);
+		self.updated = Instant::now();
+	}
+}
+
+/// Releases the concurrency slots taken by a request when dropped.
+pub struct Permit {
+	slots: Vec<Arc<AtomicUsize>>,
+}
+
+impl Drop for Permit {
+	fn drop(&mut self) {
+		for slot in &self.slots {
+			slot.fetch_sub(1, Ordering::SeqCst);
+		}
+	}
+}
+
+/// Token buckets of the most recently seen clients.
+#[derive(Default)]
+struct Buckets {
+	buckets: HashMap<String, Bucket>,
+	// clients in the order their buckets were created.
+	clients: VecDeque<String>,
+}
+
+/// Enforces `LimitsConfig` on incoming requests.
+pub struct RequestLimits {
+	max_batch_size: Option<usize>,
+	rate_limit: Option<u32>,
+	api_keys: HashSet<String>,
+	in_flight: HashMap<String, (usize, Arc<AtomicUsize>)>,
+	buckets: Mutex<Buckets>,
+}
+
+impl Default for RequestLimits {
+	fn default() -> Self {
+		RequestLimits::new(LimitsConfig::default())
+	}
+}
+
+impl RequestLimits {
+	/// Creates new request limits.
+	pub fn new(config: LimitsConfig) -> Self {
+		RequestLimits {
+			max_batch_size: config.max_batch_size,
+			rate_limit: config.rate_limit,
+			api_keys: config.api_keys,
+			in_flight: config.method_concurrency.into_iter()
+				.map(|(method, limit)| (method, (limit, Arc::new(AtomicUsize::new(0)))))
+				.collect(),
+			buckets: Mutex::new(Buckets::default()),
+		}
+	}
+
+	/// Identifies the client making a request for rate limiting.
+	/// Clients presenting a configured API key are told apart by the key, others by their address,
+	/// or by their origin when connected locally.
+	/// WebSocket sessions expose no peer address, so they all share a single bucket;
+	/// otherwise a client would get a fresh one on every reconnect.
+	pub fn client_key(&self, meta: &Metadata) -> String {
+		match meta.api_key {
+			Some(ref key) if self.api_keys.contains(key) => format!("key:{}", key),
+			_ => match (meta.ip, &meta.origin) {
+				(Some(ip), _) => format!("ip:{}", ip),
+				(None, &Origin::Ws(_)) => "ws".into(),
+				(None, origin) => origin.to_string(),
+			},
+		}
+	}
+
+	/// Checks the request against all limits.
+	/// The returned permit should be kept until the request is processed.
+	pub fn check(&self, request: &rpc::Request, client: &str) -> Result<Permit, rpc::Error> {
+		let calls = match *request {
+			rpc::Request::Single(_) => 1,
+			rpc::Request::Batch(ref calls) => {
+				if let Some(max) = self.max_batch_size {
+					if calls.len() > max {
+						return Err(errors::batch_too_large(max));
+					}
+				}
+				calls.len()
+			},
+		};
+
+		self.consume(client, calls)?;
+		self.acquire(methods(request).into_iter())
+	}
+
+	fn method(call: &rpc::Call) -> Option<&str> {
+		match *call {
+			rpc::Call::MethodCall(ref call) => Some(&call.method),
+			rpc::Call::Notification(ref notification) => Some(&notification.method),
+			rpc::Call::Invalid(_) => None,
+		}
+	}
+
+	fn consume(&self, client: &str, calls: usize) -> Result<(), rpc::Error> {
+		let rate = match self.rate_limit {
+			Some(rate) => rate as f64,
+			None => return Ok(()),
+		};
+
+		let mut guard = self.buckets.lock();
+		let buckets = &mut *guard;
+		if !buckets.buckets.contains_key(client) {
+			if buckets.clients.len() >= MAX_TRACKED_CLIENTS {
+				if let Some(oldest) = buckets.clients.pop_front() {
+					buckets.buckets.remove(&oldest);
+				}
+			}
+			buckets.clients.push_back(client.to_owned());
+			buckets.buckets.insert(client.to_owned(), Bucket {
+				tokens: rate,
+				updated: Instant::now(),
+			});
+		}
+
+		let bucket = buckets.buckets.get_mut(client).expect("inserted above if missing; qed");
+		bucket.refill(rate);
+
+		if bucket.tokens < calls as f64 {
+			trace!(target: "rpc", "Rate limiting requests of {}", client);
+			return Err(errors::rate_limited());
+		}
+		bucket.tokens -= calls as f64;
+		Ok(())
+	}
+
+	fn acquire<'a, I: Iterator<Item = &'a str>>(&self, methods: I) -> Result<Permit, rpc::Error> {
+		let mut permit = Permit { slots: Vec::new() };
+		for method in methods {
+			if let Some(&(limit, ref slot)) = self.in_flight.get(method) {
+				if slot.fetch_add(1, Ordering::SeqCst) >= limit {
+					slot.fetch_sub(1, Ordering::SeqCst);
+					// dropping the permit releases slots taken so far.
+					return Err(errors::method_busy(method));
+				}
+				permit.slots.push(slot.clone());
+			}
+		}
+		Ok(permit)
+	}
+}
+
+/// Builds the response to a request rejected as a whole.
+pub fn reject(request: &rpc::Request, error: rpc::Error) -> Option<rpc::Response> {
+	match *request {
+		rpc::Request::Single(rpc::Call::MethodCall(ref call)) => Some(rpc::Response::Single(rpc::Output::Failure(rpc::Failure {
+			jsonrpc: call.jsonrpc.clone(),
+			error: error,
+			id: call.id.clone(),
+		}))),
+		rpc::Request::Single(_) => None,
+		rpc::Request::Batch(_) => Some(rpc::Response::Single(rpc::Output::Failure(rpc::Failure {
+			jsonrpc: Some(rpc::Version::V2),
+			error: error,
+			id: rpc::Id::Null,
+		}))),
+	}
+}
+
+/// Returns names of the methods called by the request.
+pub fn methods(request: &rpc::Request) -> Vec<&str> {
+	match *request {
+		rpc::Request::Single(ref call) => RequestLimits::method(call).into_iter().collect(),
+		rpc::Request::Batch(ref calls) => calls.iter().filter_map(RequestLimits::method).collect(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+	use jsonrpc_core as rpc;
+	use serde_json;
+	use v1::Metadata;
+	use v1::types::Origin;
+	use super::{LimitsConfig, RequestLimits, MAX_TRACKED_CLIENTS};
+
+	fn request(json: &str) -> rpc::Request {
+		serde_json::from_str(json).unwrap()
+	}
+
+	#[test]
+	fn should_reject_large_batches() {
+		let limits = RequestLimits::new(LimitsConfig {
+			max_batch_size: Some(1),
+			..Default::default()
+		});
+
+		let single = request(r#"{"jsonrpc":"2.0","method":"eth_blockNumber","params":[],"id":1}"#);
+		let batch = request(r#"[{"jsonrpc":"2.0","method":"eth_blockNumber","params":[],"id":1},{"jsonrpc":"2.0","method":"eth_blockNumber","params":[],"id":2}]"#);
+
+		assert!(limits.check(&single, "client").is_ok());
+		assert!(limits.check(&batch, "client").is_err());
+	}
+
+	#[test]
+	fn should_limit_concurrent_calls_of_method() {
+		let mut concurrency = HashMap::new();
+		concurrency.insert("eth_getLogs".to_owned(), 1);
+		let limits = RequestLimits::new(LimitsConfig {
+			method_concurrency: concurrency,
+			..Default::default()
+		});
+
+		let logs = request(r#"{"jsonrpc":"2.0","method":"eth_getLogs","params":[{}],"id":1}"#);
+		let other = request(r#"{"jsonrpc":"2.0","method":"eth_blockNumber","params":[],"id":1}"#);
+
+		let permit = limits.check(&logs, "client").unwrap();
+		assert!(limits.check(&logs, "client").is_err());
+		assert!(limits.check(&other, "client").is_ok());
+
+		drop(permit);
+		assert!(limits.check(&logs, "client").is_ok());
+	}
+
+	#[test]
+	fn should_rate_limit_each_client() {
+		let limits = RequestLimits::new(LimitsConfig {
+			rate_limit: Some(2),
+			..Default::default()
+		});
+
+		let call = request(r#"{"jsonrpc":"2.0","method":"eth_blockNumber","params":[],"id":1}"#);
+
+		assert!(limits.check(&call, "a").is_ok());
+		assert!(limits.check(&call, "a").is_ok());
+		assert!(limits.check(&call, "a").is_err());
+		assert!(limits.check(&call, "b").is_ok());
+	}
+
+	#[test]
+	fn should_forget_oldest_clients() {
+		let limits = RequestLimits::new(LimitsConfig {
+			rate_limit: Some(1),
+			..Default::default()
+		});
+
+		let call = request(r#"{"jsonrpc":"2.0","method":"eth_blockNumber","params":[],"id":1}"#);
+
+		assert!(limits.check(&call, "first").is_ok());
+		assert!(limits.check(&call, "first").is_err());
+		for i in 0..MAX_TRACKED_CLIENTS {
+			assert!(limits.check(&call, &i.to_string()).is_ok());
+		}
+		assert!(limits.check(&call, "first").is_ok());
+	}
+
+	#[test]
+	fn should_only_honour_configured_api_keys() {
+		let limits = RequestLimits::new(LimitsConfig {
+			api_keys: vec!["known".to_owned()].into_iter().collect(),
+			..Default::default()
+		});
+
+		let mut meta = Metadata::default();
+		meta.ip = Some("10.0.0.1".parse().unwrap());
+		assert_eq!(limits.client_key(&meta), "ip:10.0.0.1");
+
+		meta.api_key = Some("forged".into());
+		assert_eq!(limits.client_key(&meta), "ip:10.0.0.1");
+
+		meta.api_key = Some("known".into());
+		assert_eq!(limits.client_key(&meta), "key:known");
+	}
+
+	#[test]
+	fn should_share_bucket_between_ws_sessions() {
+		let limits = RequestLimits::new(LimitsConfig::default());
+
+		let mut meta = Metadata::default();
+		meta.origin = Origin::Ws(1.into());
+		let first = limits.client_key(&meta);
+		meta.origin = Origin::Ws(2.into());
+		assert_eq!(limits.client_key(&meta), first);
+	}
+}
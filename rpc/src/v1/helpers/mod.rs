@@ -17,28 +17,41 @@
 #[macro_use]
 pub mod errors;
 
+pub mod acl;
 pub mod block_import;
 pub mod dispatch;
 pub mod fake_sign;
 pub mod informant;
+pub mod limits;
+pub mod node_health;
 pub mod oneshot;
 
+mod call_cache;
 mod network_settings;
 mod poll_manager;
 mod poll_filter;
 mod requests;
 mod signer;
 mod signing_queue;
+mod subscribers;
+mod trace_pages;
+mod typed_data;
 
+pub use self::call_cache::CallCache;
 pub use self::dispatch::{Dispatcher, FullDispatcher};
+pub use ethcore::client::{EstimateGasConfig, GasSearch};
 pub use self::network_settings::NetworkSettings;
 pub use self::poll_manager::PollManager;
 pub use self::poll_filter::{PollFilter, limit_logs};
 pub use self::requests::{
 	TransactionRequest, FilledTransactionRequest, ConfirmationRequest, ConfirmationPayload, CallRequest,
+	TypedDataRequest, RequestSource,
 };
 pub use self::signing_queue::{
 	ConfirmationsQueue, ConfirmationPromise, ConfirmationResult, SigningQueue, QueueEvent, DefaultAccount,
 	QUEUE_LIMIT as SIGNING_QUEUE_LIMIT,
 };
 pub use self::signer::SignerService;
+pub use self::subscribers::{Subscribers, DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION};
+pub use self::trace_pages::{TracePages, DEFAULT_TRACE_PAGE_SIZE};
+pub use self::typed_data::hash_typed_data;
@@ -0,0 +1,332 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Node health checks shared by `parity_nodeHealth` and the dapps `/api/health` endpoint.
+
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::path::PathBuf;
+use std::sync::{Arc, Weak};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ethcore::client::BlockChainClient;
+use ethsync::{SyncProvider, ManageNetwork};
+use util::{self, RwLock};
+
+use v1::helpers::block_import::is_major_importing;
+use v1::types::{Health, HealthInfo, HealthStatus, PeersHealth};
+
+/// Number of blocks the node may be behind its peers before it needs attention.
+const MAX_BLOCKS_BEHIND: u64 = 10;
+/// Clock drift in milliseconds above which the node needs attention.
+const DRIFT_WARNING_MS: i64 = 500;
+/// Clock drift in milliseconds above which the node is considered broken.
+const DRIFT_ERROR_MS: i64 = 10_000;
+/// Free disk space below which the node needs attention.
+const DISK_WARNING_BYTES: u64 = 10 * 1024 * 1024 * 1024;
+/// Free disk space below which the node is considered broken.
+const DISK_ERROR_BYTES: u64 = 1024 * 1024 * 1024;
+/// Interval between clock drift measurements.
+const NTP_CHECK_INTERVAL_SECS: u64 = 600;
+/// How long to wait for a single NTP server to respond.
+const NTP_TIMEOUT_MS: u64 = 1000;
+/// Seconds between the NTP epoch (1900) and the unix epoch (1970).
+const NTP_UNIX_OFFSET: i64 = 2_208_988_800;
+
+/// Computes node health from sync, network, clock and disk state.
+pub struct NodeHealth {
+	client: Arc<BlockChainClient>,
+	sync: Arc<SyncProvider>,
+	net: Arc<ManageNetwork>,
+	data_dir: PathBuf,
+	time: Arc<TimeChecker>,
+}
+
+impl NodeHealth {
+	/// Creates new `NodeHealth`. Disk space is measured on the filesystem holding `data_dir`
+	/// and clock drift is measured against `ntp_servers` (`host:port`) on a background thread;
+	/// an empty list disables the clock check.
+	pub fn new(
+		client: Arc<BlockChainClient>,
+		sync: Arc<SyncProvider>,
+		net: Arc<ManageNetwork>,
+		data_dir: PathBuf,
+		ntp_servers: Vec<String>,
+	) -> Self {
+		NodeHealth {
+			client: client,
+			sync: sync,
+			net: net,
+			data_dir: data_dir,
+			time: TimeChecker::start(ntp_servers),
+		}
+	}
+
+	/// Returns current health of the node.
+	pub fn health(&self) -> Health {
+		let status = self.sync.status();
+		let best_block = self.client.chain_info().best_block_number;
+		let highest_block = status.highest_block_number.unwrap_or(best_block);
+		let min_peers = self.net.network_config().min_peers as usize;
+
+		let peers = peers_health(status.num_peers, min_peers);
+		let sync = sync_health(
+			is_major_importing(Some(status.state), self.client.queue_info()),
+			highest_block.saturating_sub(best_block),
+		);
+		let time = match self.time.servers.is_empty() {
+			true => HealthInfo { status: HealthStatus::Ok, message: "Clock drift checks are disabled.".into(), details: 0 },
+			false => time_health(self.time.drift.read().clone()),
+		};
+		let disk = disk_health(util::misc::available_disk_space(&self.data_dir));
+
+		Health {
+			status: *[peers.status, sync.status, time.status, disk.status].iter().max().expect("array is not empty; qed"),
+			peers: peers,
+			sync: sync,
+			time: time,
+			disk: disk,
+		}
+	}
+}
+
+fn peers_health(connected: usize, minimum: usize) -> HealthInfo<PeersHealth> {
+	let (status, message) = if connected == 0 {
+		(HealthStatus::Bad, "You are not connected to any peers. There is most likely some network issue. Fix connectivity.".to_owned())
+	} else if connected < minimum {
+		(HealthStatus::NeedsAttention, format!("You are connected to {} peers only, below the configured minimum of {}.", connected, minimum))
+	} else {
+		(HealthStatus::Ok, format!("Connected to {} peers.", connected))
+	};
+
+	HealthInfo {
+		status: status,
+		message: message,
+		details: PeersHealth {
+			connected: connected,
+			minimum: minimum,
+		},
+	}
+}
+
+fn sync_health(major_importing: bool, blocks_behind: u64) -> HealthInfo<u64> {
+	let (status, message) = if major_importing {
+		(HealthStatus::Bad, format!("Your node is still syncing, {} blocks behind its peers.", blocks_behind))
+	} else if blocks_behind > MAX_BLOCKS_BEHIND {
+		(HealthStatus::NeedsAttention, format!("Your node is {} blocks behind its peers.", blocks_behind))
+	} else {
+		(HealthStatus::Ok, "Your node is fully synced.".to_owned())
+	};
+
+	HealthInfo { status: status, message: message, details: blocks_behind }
+}
+
+fn time_health(drift: Option<Result<i64, String>>) -> HealthInfo<i64> {
+	match drift {
+		None => HealthInfo { status: HealthStatus::Ok, message: "Clock drift has not been measured yet.".into(), details: 0 },
+		Some(Ok(drift)) => {
+			let (status, message) = if drift.abs() > DRIFT_ERROR_MS {
+				(HealthStatus::Bad, format!("Your clock is off by {}ms. Blocks may be rejected; synchronize your clock.", drift))
+			} else if drift.abs() > DRIFT_WARNING_MS {
+				(HealthStatus::NeedsAttention, format!("Your clock is off by {}ms. Consider synchronizing your clock.", drift))
+			} else {
+				(HealthStatus::Ok, format!("Your clock is off by {}ms.", drift))
+			};
+			HealthInfo { status: status, message: message, details: drift }
+		},
+		Some(Err(err)) => HealthInfo {
+			status: HealthStatus::NeedsAttention,
+			message: format!("Unable to determine clock drift: {}", err),
+			details: 0,
+		},
+	}
+}
+
+fn disk_health(available: io::Result<u64>) -> HealthInfo<u64> {
+	match available {
+		Ok(bytes) => {
+			let (status, message) = if bytes < DISK_ERROR_BYTES {
+				(HealthStatus::Bad, format!("Only {} MB of disk space left. Free some space now.", bytes / 1024 / 1024))
+			} else if bytes < DISK_WARNING_BYTES {
+				(HealthStatus::NeedsAttention, format!("Only {} MB of disk space left.", bytes / 1024 / 1024))
+			} else {
+				(HealthStatus::Ok, format!("{} MB of disk space left.", bytes / 1024 / 1024))
+			};
+			HealthInfo { status: status, message: message, details: bytes }
+		},
+		Err(err) => HealthInfo {
+			status: HealthStatus::NeedsAttention,
+			message: format!("Unable to determine available disk space: {}", err),
+			details: 0,
+		},
+	}
+}
+
+/// Measures local clock drift against NTP servers.
+struct TimeChecker {
+	servers: Vec<String>,
+	/// Last measured drift, `None` until the first measurement completes.
+	drift: RwLock<Option<Result<i64, String>>>,
+}
+
+impl TimeChecker {
+	/// Creates the checker and, unless there are no servers, spawns a thread refreshing the drift
+	/// periodically, so that health requests never wait for the network. The thread stops once the
+	/// checker is dropped.
+	fn start(servers: Vec<String>) -> Arc<Self> {
+		let checker = Arc::new(TimeChecker {
+			servers: servers,
+			drift: RwLock::new(None),
+		});
+
+		if !checker.servers.is_empty() {
+			let weak = Arc::downgrade(&checker);
+			let spawned = thread::Builder::new()
+				.name("ntp-check".into())
+				.spawn(move || Self::run(weak));
+			if let Err(err) = spawned {
+				warn!(target: "rpc", "Unable to start clock drift checks: {}", err);
+			}
+		}
+
+		checker
+	}
+
+	fn run(checker: Weak<Self>) {
+		loop {
+			match checker.upgrade() {
+				Some(checker) => {
+					let drift = checker.query();
+					*checker.drift.write() = Some(drift);
+				},
+				None => return,
+			}
+			thread::sleep(Duration::from_secs(NTP_CHECK_INTERVAL_SECS));
+		}
+	}
+
+	/// Returns drift of the local clock in milliseconds (positive when the local clock is behind).
+	fn query(&self) -> Result<i64, String> {
+		let mut error = String::new();
+		for server in &self.servers {
+			match sntp_offset(server) {
+				Ok(offset) => return Ok(offset),
+				Err(err) => {
+					debug!(target: "rpc", "NTP query to {} failed: {}", server, err);
+					error = format!("NTP query to {} failed: {}", server, err);
+				},
+			}
+		}
+		Err(error)
+	}
+}
+
+/// Queries every address `server` resolves to until one responds and returns its clock offset.
+fn sntp_offset(server: &str) -> io::Result<i64> {
+	let mut error = io::Error::new(io::ErrorKind::NotFound, "No address resolved.");
+	for addr in server.to_socket_addrs()? {
+		match sntp_query(&addr) {
+			Ok(offset) => return Ok(offset),
+			Err(err) => error = err,
+		}
+	}
+	Err(error)
+}
+
+/// Queries a single address with SNTP (RFC 4330) and returns the clock offset in milliseconds.
+fn sntp_query(addr: &SocketAddr) -> io::Result<i64> {
+	let local = match *addr {
+		SocketAddr::V4(_) => "0.0.0.0:0",
+		SocketAddr::V6(_) => "[::]:0",
+	};
+	let socket = UdpSocket::bind(local)?;
+	socket.set_read_timeout(Some(Duration::from_millis(NTP_TIMEOUT_MS)))?;
+	socket.connect(addr)?;
+
+	let mut packet = [0u8; 48];
+	// LI = 0, VN = 3, Mode = 3 (client)
+	packet[0] = 0x1b;
+	let originate = unix_millis();
+	socket.send(&packet)?;
+	let len = socket.recv(&mut packet)?;
+	let destination = unix_millis();
+	if len < packet.len() {
+		return Err(io::Error::new(io::ErrorKind::InvalidData, "Truncated NTP response."));
+	}
+
+	let receive = ntp_millis(&packet[32..40]);
+	let transmit = ntp_millis(&packet[40..48]);
+	Ok(((receive - originate) + (transmit - destination)) / 2)
+}
+
+fn unix_millis() -> i64 {
+	let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or(Duration::from_secs(0));
+	now.as_secs() as i64 * 1000 + (now.subsec_nanos() / 1_000_000) as i64
+}
+
+/// Converts an NTP timestamp (seconds since 1900 and a 32-bit fraction) to unix milliseconds.
+fn ntp_millis(timestamp: &[u8]) -> i64 {
+	let read_u32 = |bytes: &[u8]| bytes.iter().fold(0i64, |acc, b| (acc << 8) | *b as i64);
+	let seconds = read_u32(&timestamp[0..4]) - NTP_UNIX_OFFSET;
+	let fraction = read_u32(&timestamp[4..8]);
+	seconds * 1000 + ((fraction * 1000) >> 32)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io;
+	use v1::types::HealthStatus;
+	use super::{peers_health, sync_health, time_health, disk_health, ntp_millis};
+
+	#[test]
+	fn should_rate_peers() {
+		assert_eq!(peers_health(0, 25).status, HealthStatus::Bad);
+		assert_eq!(peers_health(3, 25).status, HealthStatus::NeedsAttention);
+		assert_eq!(peers_health(25, 25).status, HealthStatus::Ok);
+	}
+
+	#[test]
+	fn should_rate_sync() {
+		assert_eq!(sync_health(true, 0).status, HealthStatus::Bad);
+		assert_eq!(sync_health(false, 11).status, HealthStatus::NeedsAttention);
+		assert_eq!(sync_health(false, 2).status, HealthStatus::Ok);
+	}
+
+	#[test]
+	fn should_rate_time() {
+		assert_eq!(time_health(Some(Ok(-20_000))).status, HealthStatus::Bad);
+		assert_eq!(time_health(Some(Ok(600))).status, HealthStatus::NeedsAttention);
+		assert_eq!(time_health(Some(Ok(-20))).status, HealthStatus::Ok);
+		assert_eq!(time_health(Some(Err("timeout".into()))).status, HealthStatus::NeedsAttention);
+		assert_eq!(time_health(None).status, HealthStatus::Ok);
+	}
+
+	#[test]
+	fn should_rate_disk() {
+		assert_eq!(disk_health(Ok(1024)).status, HealthStatus::Bad);
+		assert_eq!(disk_health(Ok(5 * 1024 * 1024 * 1024)).status, HealthStatus::NeedsAttention);
+		assert_eq!(disk_health(Ok(100 * 1024 * 1024 * 1024)).status, HealthStatus::Ok);
+		assert_eq!(disk_health(Err(io::Error::new(io::ErrorKind::Other, "unsupported"))).status, HealthStatus::NeedsAttention);
+	}
+
+	#[test]
+	fn should_convert_ntp_timestamps() {
+		// 2017-01-01T00:00:00.5Z
+		let timestamp = [0xdc, 0x12, 0xc5, 0x00, 0x80, 0x00, 0x00, 0x00];
+		assert_eq!(ntp_millis(&timestamp), 1_483_228_800_500);
+	}
+}
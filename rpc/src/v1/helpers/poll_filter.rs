@@ -2,7 +2,7 @@
 
 use std::collections::HashSet;
 use util::hash::H256;
-use v1::types::{Filter, Log};
+use v1::types::{Filter, Log, TransactionFilter};
 
 pub type BlockNumber = u64;
 
@@ -13,6 +13,9 @@ pub enum PollFilter {
 	Block(BlockNumber),
 	/// Hashes of all transactions which client was notified about.
 	PendingTransaction(Vec<H256>),
+	/// Hashes of all pending transactions seen so far and the predicates of the ones the client
+	/// is notified about.
+	MatchingPendingTransaction(Vec<H256>, TransactionFilter),
 	/// Number of From block number, pending logs and log filter itself.
 	Logs(BlockNumber, HashSet<Log>, Filter)
 }
@@ -14,8 +14,10 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use util::{Address, U256, Bytes};
-use v1::types::{Origin, TransactionCondition};
+use std::net::IpAddr;
+
+use util::{Address, U256, H256, Bytes};
+use v1::types::{Origin, TransactionCondition, TypedDataField};
 
 /// Transaction request coming from RPC
 #[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
@@ -77,7 +79,7 @@ impl From<FilledTransactionRequest> for TransactionRequest {
 }
 
 /// Call request
-#[derive(Debug, Default, PartialEq)]
+#[derive(Debug, Default, PartialEq, Clone)]
 pub struct CallRequest {
 	/// From
 	pub from: Option<Address>,
@@ -104,6 +106,59 @@ pub struct ConfirmationRequest {
 	pub payload: ConfirmationPayload,
 	/// Request origin
 	pub origin: Origin,
+	/// Name of the RPC method that created the request
+	pub method: String,
+	/// IP address of the requesting client, if known
+	pub ip: Option<IpAddr>,
+	/// UNIX timestamp (in seconds) of the moment the request was queued
+	pub created: u64,
+	/// UNIX timestamp (in seconds) after which the request is rejected automatically
+	pub expires: Option<u64>,
+}
+
+impl ConfirmationRequest {
+	/// Details of the RPC call that created this request.
+	pub fn source(&self) -> RequestSource {
+		RequestSource {
+			origin: self.origin.clone(),
+			method: self.method.clone(),
+			ip: self.ip,
+		}
+	}
+}
+
+/// Details of the RPC call that created a confirmation request.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct RequestSource {
+	/// Request origin
+	pub origin: Origin,
+	/// Name of the RPC method
+	pub method: String,
+	/// IP address of the client, if known
+	pub ip: Option<IpAddr>,
+}
+
+impl From<Origin> for RequestSource {
+	fn from(origin: Origin) -> Self {
+		RequestSource {
+			origin: origin,
+			method: String::new(),
+			ip: None,
+		}
+	}
+}
+
+/// Typed data sign request with the hash already computed
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct TypedDataRequest {
+	/// Signing account
+	pub address: Address,
+	/// Hash of the typed data
+	pub hash: H256,
+	/// Struct type of the signed message
+	pub primary_type: String,
+	/// Signed fields in a human-readable form
+	pub fields: Vec<TypedDataField>,
 }
 
 /// Payload to confirm in Trusted Signer
@@ -115,6 +170,8 @@ pub enum ConfirmationPayload {
 	SignTransaction(FilledTransactionRequest),
 	/// Sign request
 	Signature(Address, Bytes),
+	/// Typed data sign request
+	SignTypedData(TypedDataRequest),
 	/// Decrypt request
 	Decrypt(Address, Bytes),
 }
@@ -125,6 +182,7 @@ impl ConfirmationPayload {
 			ConfirmationPayload::SendTransaction(ref request) => request.from,
 			ConfirmationPayload::SignTransaction(ref request) => request.from,
 			ConfirmationPayload::Signature(ref address, _) => *address,
+			ConfirmationPayload::SignTypedData(ref request) => request.address,
 			ConfirmationPayload::Decrypt(ref address, _) => *address,
 		}
 	}
@@ -16,6 +16,7 @@
 
 use std::sync::Arc;
 use std::ops::Deref;
+use std::time::Duration;
 use util::Mutex;
 use transient_hashmap::TransientHashMap;
 
@@ -35,10 +36,11 @@ pub struct SignerService {
 
 impl SignerService {
 	/// Creates new Signer Service given function to generate new tokens.
-	pub fn new<F>(new_token: F, address: Option<(String, u16)>) -> Self
+	/// Confirmation requests left unanswered for `request_timeout` are rejected.
+	pub fn new<F>(new_token: F, address: Option<(String, u16)>, request_timeout: Option<Duration>) -> Self
 		where F: Fn() -> Result<String, String> + Send + Sync + 'static {
 		SignerService {
-			queue: Arc::new(ConfirmationsQueue::default()),
+			queue: Arc::new(ConfirmationsQueue::new(request_timeout)),
 			web_proxy_tokens: Mutex::new(TransientHashMap::new(TOKEN_LIFETIME_SECS)),
 			generate_new_token: Box::new(new_token),
 			address: address,
@@ -82,7 +84,7 @@ impl SignerService {
 	#[cfg(test)]
 	/// Creates new Signer Service for tests.
 	pub fn new_test(address: Option<(String, u16)>) -> Self {
-		SignerService::new(|| Ok("new_token".into()), address)
+		SignerService::new(|| Ok("new_token".into()), address, None)
 	}
 }
 
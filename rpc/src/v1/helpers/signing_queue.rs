@@ -18,11 +18,12 @@ use std::mem;
 use std::cell::RefCell;
 use std::sync::{mpsc, Arc};
 use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use jsonrpc_core;
 use util::{Mutex, RwLock, U256, Address};
 use ethcore::account_provider::DappId;
-use v1::helpers::{ConfirmationRequest, ConfirmationPayload};
-use v1::types::{ConfirmationResponse, H160 as RpcH160, Origin, DappId as RpcDappId};
+use v1::helpers::{errors, ConfirmationRequest, ConfirmationPayload, RequestSource};
+use v1::types::{ConfirmationResponse, H160 as RpcH160, DappId as RpcDappId};
 
 /// Result that can be returned from JSON RPC.
 pub type RpcResult = Result<ConfirmationResponse, jsonrpc_core::Error>;
@@ -59,6 +60,8 @@ pub enum QueueEvent {
 	RequestRejected(U256),
 	/// Request resolved.
 	RequestConfirmed(U256),
+	/// Request was not resolved in time.
+	RequestExpired(U256),
 }
 
 /// Defines possible errors returned from queue receiving method.
@@ -83,7 +86,7 @@ pub const QUEUE_LIMIT: usize = 50;
 pub trait SigningQueue: Send + Sync {
 	/// Add new request to the queue.
 	/// Returns a `ConfirmationPromise` that can be used to await for resolution of given request.
-	fn add_request(&self, request: ConfirmationPayload, origin: Origin) -> Result<ConfirmationPromise, QueueAddError>;
+	fn add_request(&self, request: ConfirmationPayload, source: RequestSource) -> Result<ConfirmationPromise, QueueAddError>;
 
 	/// Removes a request from the queue.
 	/// Notifies possible token holders that request was rejected.
@@ -183,16 +186,30 @@ impl ConfirmationPromise {
 }
 
 
+fn unix_now() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
 /// Queue for all unconfirmed requests.
 pub struct ConfirmationsQueue {
 	id: Mutex<U256>,
 	queue: RwLock<BTreeMap<U256, ConfirmationToken>>,
 	sender: Mutex<mpsc::Sender<QueueEvent>>,
 	receiver: Mutex<Option<mpsc::Receiver<QueueEvent>>>,
+	request_timeout: Option<Duration>,
 }
 
 impl Default for ConfirmationsQueue {
 	fn default() -> Self {
+		ConfirmationsQueue::new(None)
+	}
+}
+
+impl ConfirmationsQueue {
+	/// Creates a new queue.
+	/// Requests that are not resolved within `request_timeout` are rejected automatically;
+	/// they are removed the next time the queue is accessed.
+	pub fn new(request_timeout: Option<Duration>) -> Self {
 		let (send, recv) = mpsc::channel();
 
 		ConfirmationsQueue {
@@ -200,11 +217,9 @@ impl Default for ConfirmationsQueue {
 			queue: RwLock::new(BTreeMap::new()),
 			sender: Mutex::new(send),
 			receiver: Mutex::new(Some(recv)),
+			request_timeout: request_timeout,
 		}
 	}
-}
-
-impl ConfirmationsQueue {
 
 	/// Blocks the thread and starts listening for notifications regarding all actions in the queue.
 	/// For each event, `listener` callback will be invoked.
@@ -241,15 +256,12 @@ impl ConfirmationsQueue {
 
 	/// Removes requests from this queue and notifies `ConfirmationPromise` holders about the result.
 	/// Notifies also a receiver about that event.
-	fn remove(&self, id: U256, result: Option<RpcResult>) -> Option<ConfirmationRequest> {
+	fn remove(&self, id: U256, result: Option<RpcResult>, event: QueueEvent) -> Option<ConfirmationRequest> {
 		let token = self.queue.write().remove(&id);
 
 		if let Some(token) = token {
 			// notify receiver about the event
-			self.notify(result.clone().map_or_else(
-				|| QueueEvent::RequestRejected(id),
-				|_| QueueEvent::RequestConfirmed(id)
-			));
+			self.notify(event);
 			// notify token holders about resolution
 			token.resolve(result);
 			// return a result
@@ -257,6 +269,25 @@ impl ConfirmationsQueue {
 		}
 		None
 	}
+
+	/// Rejects all requests that were not resolved before their expiry time.
+	fn remove_expired(&self) {
+		if self.request_timeout.is_none() {
+			return;
+		}
+
+		let now = unix_now();
+		let expired: Vec<U256> = self.queue.read()
+			.values()
+			.filter(|token| token.request.expires.map_or(false, |expires| expires <= now))
+			.map(|token| token.request.id)
+			.collect();
+
+		for id in expired {
+			debug!(target: "own_tx", "Signer: Request expired ({:?}).", id);
+			self.remove(id, Some(Err(errors::request_expired())), QueueEvent::RequestExpired(id));
+		}
+	}
 }
 
 impl Drop for ConfirmationsQueue {
@@ -266,7 +297,8 @@ impl Drop for ConfirmationsQueue {
 }
 
 impl SigningQueue for ConfirmationsQueue {
-	fn add_request(&self, request: ConfirmationPayload, origin: Origin) -> Result<ConfirmationPromise, QueueAddError> {
+	fn add_request(&self, request: ConfirmationPayload, source: RequestSource) -> Result<ConfirmationPromise, QueueAddError> {
+		self.remove_expired();
 		if self.len() > QUEUE_LIMIT {
 			return Err(QueueAddError::LimitReached);
 		}
@@ -277,6 +309,8 @@ impl SigningQueue for ConfirmationsQueue {
 			*last_id = *last_id + U256::from(1);
 			*last_id
 		};
+		let created = unix_now();
+		let expires = self.request_timeout.map(|timeout| created + timeout.as_secs());
 		// Add request to queue
 		let res = {
 			debug!(target: "own_tx", "Signer: New entry ({:?}) in confirmation queue.", id);
@@ -289,7 +323,11 @@ impl SigningQueue for ConfirmationsQueue {
 				request: ConfirmationRequest {
 					id: id,
 					payload: request,
-					origin: origin,
+					origin: source.origin,
+					method: source.method,
+					ip: source.ip,
+					created: created,
+					expires: expires,
 				},
 			});
 			queue.get(&id).map(|token| token.as_promise()).expect("Token was just inserted.")
@@ -300,20 +338,22 @@ impl SigningQueue for ConfirmationsQueue {
 	}
 
 	fn peek(&self, id: &U256) -> Option<ConfirmationRequest> {
+		self.remove_expired();
 		self.queue.read().get(id).map(|token| token.request.clone())
 	}
 
 	fn request_rejected(&self, id: U256) -> Option<ConfirmationRequest> {
 		debug!(target: "own_tx", "Signer: Request rejected ({:?}).", id);
-		self.remove(id, None)
+		self.remove(id, None, QueueEvent::RequestRejected(id))
 	}
 
 	fn request_confirmed(&self, id: U256, result: RpcResult) -> Option<ConfirmationRequest> {
 		debug!(target: "own_tx", "Signer: Transaction confirmed ({:?}).", id);
-		self.remove(id, Some(result))
+		self.remove(id, Some(result), QueueEvent::RequestConfirmed(id))
 	}
 
 	fn requests(&self) -> Vec<ConfirmationRequest> {
+		self.remove_expired();
 		let queue = self.queue.read();
 		queue.values().map(|token| token.request.clone()).collect()
 	}
@@ -336,8 +376,11 @@ mod test {
 	use std::thread;
 	use std::sync::{mpsc, Arc};
 	use util::{Address, U256, Mutex};
-	use v1::helpers::{SigningQueue, ConfirmationsQueue, QueueEvent, FilledTransactionRequest, ConfirmationPayload};
-	use v1::types::ConfirmationResponse;
+	use v1::helpers::{
+		errors, SigningQueue, ConfirmationsQueue, ConfirmationResult, QueueEvent, FilledTransactionRequest,
+		ConfirmationPayload, RequestSource,
+	};
+	use v1::types::{ConfirmationResponse, Origin};
 
 	fn request() -> ConfirmationPayload {
 		ConfirmationPayload::SendTransaction(FilledTransactionRequest {
@@ -422,4 +465,40 @@ mod test {
 		assert_eq!(el.id, U256::from(1));
 		assert_eq!(el.payload, request);
 	}
+
+	#[test]
+	fn should_record_request_source() {
+		// given
+		let queue = ConfirmationsQueue::new(Some(Duration::from_secs(60)));
+		let source = RequestSource {
+			origin: Origin::Rpc("http://parity.io".into()),
+			method: "eth_sendTransaction".into(),
+			ip: Some("127.0.0.1".parse().unwrap()),
+		};
+
+		// when
+		queue.add_request(request(), source.clone()).unwrap();
+		let all = queue.requests();
+
+		// then
+		let el = all.get(0).unwrap();
+		assert_eq!(el.origin, source.origin);
+		assert_eq!(el.method, source.method);
+		assert_eq!(el.ip, source.ip);
+		assert_eq!(el.expires, Some(el.created + 60));
+	}
+
+	#[test]
+	fn should_reject_expired_requests() {
+		// given
+		let queue = ConfirmationsQueue::new(Some(Duration::from_secs(0)));
+		let promise = queue.add_request(request(), Default::default()).unwrap();
+
+		// when
+		let all = queue.requests();
+
+		// then
+		assert!(all.is_empty());
+		assert_eq!(promise.result(), ConfirmationResult::Confirmed(Err(errors::request_expired())));
+	}
 }
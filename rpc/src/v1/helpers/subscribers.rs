@@ -0,0 +1,131 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A map of subscribers.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use futures::Future;
+use jsonrpc_macros::pubsub::{Subscriber, Sink};
+use jsonrpc_pubsub::{Session, SubscriptionId};
+use parity_reactor::Remote;
+use util::H64;
+
+use v1::helpers::errors;
+use v1::types::PubSubResult;
+
+/// Default number of subscriptions a single connection may hold.
+pub const DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION: usize = 100;
+
+// Notifications a subscriber may leave unsent before it's considered too slow and dropped.
+const MAX_PENDING_NOTIFICATIONS: usize = 1024;
+
+struct Subscription<T> {
+	sink: Sink<PubSubResult>,
+	session: usize,
+	pending: Arc<AtomicUsize>,
+	data: T,
+}
+
+/// Subscriptions of all connections, along with data describing what they're subscribed to.
+pub struct Subscribers<T> {
+	max_per_session: usize,
+	subscriptions: HashMap<SubscriptionId, Subscription<T>>,
+}
+
+impl<T> Subscribers<T> {
+	/// Create an empty map allowing at most `max_per_session` subscriptions per connection.
+	pub fn new(max_per_session: usize) -> Self {
+		Subscribers {
+			max_per_session: max_per_session,
+			subscriptions: HashMap::new(),
+		}
+	}
+
+	/// Assign an id to the subscriber and keep it, unless its connection
+	/// already holds the maximum number of subscriptions.
+	pub fn push(&mut self, session: &Arc<Session>, subscriber: Subscriber<PubSubResult>, data: T) {
+		// connections hold their sessions for as long as they're open and unsubscribe
+		// everything when closing, so the address identifies the connection in the meantime.
+		let session = &**session as *const Session as usize;
+		if self.subscriptions.values().filter(|s| s.session == session).count() >= self.max_per_session {
+			let _ = subscriber.reject(errors::subscriptions_limit(self.max_per_session));
+			return;
+		}
+
+		let id = self.next_id();
+		if let Ok(sink) = subscriber.assign_id(id.clone()) {
+			self.subscriptions.insert(id, Subscription {
+				sink: sink,
+				session: session,
+				pending: Default::default(),
+				data: data,
+			});
+		}
+	}
+
+	/// Remove the subscription with given id, returning whether it existed.
+	pub fn remove(&mut self, id: &SubscriptionId) -> bool {
+		self.subscriptions.remove(id).is_some()
+	}
+
+	/// Number of subscriptions.
+	pub fn len(&self) -> usize {
+		self.subscriptions.len()
+	}
+
+	/// Send every subscriber the results `f` returns for it.
+	///
+	/// Subscribers whose connections don't keep up with the notifications are dropped.
+	pub fn notify<F>(&mut self, remote: &Remote, mut f: F) where F: FnMut(&T) -> Vec<PubSubResult> {
+		let mut lagging = Vec::new();
+		for (id, subscription) in &self.subscriptions {
+			for result in f(&subscription.data) {
+				if subscription.pending.fetch_add(1, Ordering::SeqCst) >= MAX_PENDING_NOTIFICATIONS {
+					warn!(target: "rpc", "Dropping subscription {:?}: too many notifications pending.", id);
+					lagging.push(id.clone());
+					break;
+				}
+
+				let pending = subscription.pending.clone();
+				remote.spawn(subscription.sink.notify(Ok(result)).then(move |res| {
+					pending.fetch_sub(1, Ordering::SeqCst);
+					if let Err(e) = res {
+						debug!(target: "rpc", "Unable to send notification: {}", e);
+					}
+					Ok(())
+				}));
+			}
+		}
+
+		for id in lagging {
+			if let Some(subscription) = self.subscriptions.remove(&id) {
+				let _ = subscription.sink.notify(Err(errors::subscription_lagging()));
+			}
+		}
+	}
+
+	fn next_id(&self) -> SubscriptionId {
+		loop {
+			let id = SubscriptionId::String(format!("0x{}", H64::random().hex()));
+			if !self.subscriptions.contains_key(&id) {
+				return id;
+			}
+		}
+	}
+}
@@ -0,0 +1,144 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Server-side pagination of large traces.
+//!
+//! A trace longer than the page size is returned a page at a time, the rest being
+//! left behind a continuation token. Pages end where a subtree at the shallowest
+//! depth within reach ends, so calls are split from their subcalls as rarely as possible.
+
+use std::cmp;
+use std::collections::BTreeMap;
+
+use ethcore::trace::FlatTrace;
+
+/// Default maximum number of traces in a page.
+pub const DEFAULT_TRACE_PAGE_SIZE: usize = 10_000;
+
+// Uncollected continuations are dropped, oldest first, beyond this number.
+const MAX_PENDING: usize = 16;
+
+/// Traces waiting to be collected with a continuation token.
+pub struct TracePages {
+	page_size: usize,
+	next_token: u64,
+	pending: BTreeMap<u64, Vec<FlatTrace>>,
+}
+
+impl TracePages {
+	/// Create a new store handing out pages of at most `page_size` traces.
+	pub fn new(page_size: usize) -> Self {
+		TracePages {
+			page_size: cmp::max(1, page_size),
+			next_token: 0,
+			pending: BTreeMap::new(),
+		}
+	}
+
+	/// First page of `traces`, along with the token for the rest if they don't fit.
+	pub fn paginate(&mut self, mut traces: Vec<FlatTrace>) -> (Vec<FlatTrace>, Option<u64>) {
+		let end = page_end(&traces, self.page_size);
+		if end == traces.len() {
+			return (traces, None);
+		}
+
+		let rest = traces.split_off(end);
+		let token = self.next_token;
+		self.next_token += 1;
+		self.pending.insert(token, rest);
+		while self.pending.len() > MAX_PENDING {
+			let oldest = *self.pending.keys().next().expect("more than MAX_PENDING entries; qed");
+			trace!(target: "rpc", "Dropping uncollected trace continuation {}", oldest);
+			self.pending.remove(&oldest);
+		}
+		(traces, Some(token))
+	}
+
+	/// Next page of the traces left behind `token`, or `None` if the token is unknown,
+	/// was already used or has been dropped.
+	pub fn next(&mut self, token: u64) -> Option<(Vec<FlatTrace>, Option<u64>)> {
+		let traces = match self.pending.remove(&token) {
+			Some(traces) => traces,
+			None => return None,
+		};
+		Some(self.paginate(traces))
+	}
+}
+
+// Length of the first page of `traces`: the traces up to the last one within
+// `page_size` which starts a subtree at the shallowest depth.
+fn page_end(traces: &[FlatTrace], page_size: usize) -> usize {
+	if traces.len() <= page_size {
+		return traces.len();
+	}
+
+	let mut end = page_size;
+	let mut depth = traces[page_size].trace_address.len();
+	for i in (1..page_size).rev() {
+		let d = traces[i].trace_address.len();
+		if d < depth {
+			depth = d;
+			end = i;
+		}
+	}
+	end
+}
+
+#[cfg(test)]
+mod tests {
+	use ethcore::trace::{FlatTrace, trace};
+	use super::TracePages;
+
+	fn at(address: &[usize]) -> FlatTrace {
+		FlatTrace {
+			action: trace::Action::Suicide(trace::Suicide {
+				address: 0.into(),
+				refund_address: 0.into(),
+				balance: 0.into(),
+			}),
+			result: trace::Res::None,
+			subtraces: 0,
+			trace_address: address.iter().cloned().collect(),
+		}
+	}
+
+	#[test]
+	fn short_traces_are_not_paginated() {
+		let mut pages = TracePages::new(3);
+		let (page, token) = pages.paginate(vec![at(&[]), at(&[0]), at(&[1])]);
+		assert_eq!(page.len(), 3);
+		assert_eq!(token, None);
+	}
+
+	#[test]
+	fn pages_end_at_shallowest_subtree() {
+		let mut pages = TracePages::new(4);
+		let traces = vec![at(&[]), at(&[0]), at(&[0, 0]), at(&[1]), at(&[1, 0]), at(&[1, 1]), at(&[2])];
+
+		// the subtree under [1] doesn't fit, so the page ends before it.
+		let (page, token) = pages.paginate(traces);
+		assert_eq!(page.len(), 3);
+		let token = token.unwrap();
+
+		let (page, next) = pages.next(token).unwrap();
+		assert_eq!(page.len(), 4);
+		assert_eq!(page[0].trace_address, at(&[1]).trace_address);
+		assert_eq!(next, None);
+
+		// tokens can only be used once.
+		assert!(pages.next(token).is_none());
+	}
+}
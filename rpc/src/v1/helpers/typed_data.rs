@@ -0,0 +1,373 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Hashing of typed structured data (EIP-712).
+//!
+//! Every struct instance is hashed as `keccak(typeHash ‖ encodeData(instance))`, where
+//! `typeHash` is the hash of the struct signature, e.g. `Mail(Person from,string contents)`,
+//! followed by the signatures of all referenced struct types sorted by name.
+//! Members are encoded as 32-byte words in declaration order:
+//!
+//! - `bool`, `address`, `uint<N>`, `int<N>` and `bytes<N>` are padded to a single word,
+//! - `string` and `bytes` are replaced by the hash of their contents,
+//! - nested structs are replaced by their struct hash,
+//! - arrays are replaced by the hash of their concatenated encoded items.
+//!
+//! The signed hash is `keccak(0x19 ‖ 0x01 ‖ hashStruct(domain) ‖ hashStruct(message))`.
+//! Unlike the reference scheme, members which are not part of the type definition are
+//! rejected, so that everything shown to the user is covered by the signature.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+
+use serde_json::Value;
+use util::{Address, H256, U256, Uint, FromHex, ToPretty};
+use util::sha3::Hashable;
+
+use jsonrpc_core::Error;
+use v1::helpers::errors;
+use v1::types::{TypedData, TypedDataMember, TypedDataField};
+
+/// Name of the struct type describing the signing domain.
+pub const DOMAIN_TYPE: &'static str = "EIP712Domain";
+
+/// Computes the hash to sign for given typed data, together with all signed
+/// atomic fields rendered for display.
+pub fn hash_typed_data(data: &TypedData) -> Result<(H256, Vec<TypedDataField>), Error> {
+	hash(data).map_err(|e| errors::invalid_params("typedData", e))
+}
+
+fn hash(data: &TypedData) -> Result<(H256, Vec<TypedDataField>), String> {
+	let mut encoder = Encoder {
+		types: &data.types,
+		fields: Vec::new(),
+	};
+	let domain = encoder.hash_struct(DOMAIN_TYPE, &data.domain, "domain")?;
+	let message = encoder.hash_struct(&data.primary_type, &data.message, "message")?;
+
+	let mut preimage = vec![0x19, 0x01];
+	preimage.extend_from_slice(&*domain);
+	preimage.extend_from_slice(&*message);
+	Ok((preimage.sha3(), encoder.fields))
+}
+
+struct Encoder<'a> {
+	types: &'a BTreeMap<String, Vec<TypedDataMember>>,
+	fields: Vec<TypedDataField>,
+}
+
+impl<'a> Encoder<'a> {
+	fn members(&self, kind: &str) -> Result<&'a [TypedDataMember], String> {
+		self.types.get(kind)
+			.map(|members| &members[..])
+			.ok_or_else(|| format!("Type {} is not defined.", kind))
+	}
+
+	fn signature(&self, kind: &str) -> Result<String, String> {
+		let members = self.members(kind)?
+			.iter()
+			.map(|member| format!("{} {}", member.kind, member.name))
+			.collect::<Vec<_>>();
+		Ok(format!("{}({})", kind, members.join(",")))
+	}
+
+	fn collect_dependencies(&self, kind: &str, dependencies: &mut BTreeSet<String>) -> Result<(), String> {
+		if dependencies.contains(kind) {
+			return Ok(());
+		}
+		dependencies.insert(kind.to_owned());
+
+		for member in self.members(kind)? {
+			let base = base_type(&member.kind);
+			if self.types.contains_key(base) {
+				self.collect_dependencies(base, dependencies)?;
+			}
+		}
+		Ok(())
+	}
+
+	fn encode_type(&self, kind: &str) -> Result<String, String> {
+		let mut dependencies = BTreeSet::new();
+		self.collect_dependencies(kind, &mut dependencies)?;
+		dependencies.remove(kind);
+
+		let mut encoded = self.signature(kind)?;
+		for dependency in &dependencies {
+			encoded.push_str(&self.signature(dependency)?);
+		}
+		Ok(encoded)
+	}
+
+	fn hash_struct(&mut self, kind: &str, value: &Value, path: &str) -> Result<H256, String> {
+		let members = self.members(kind)?;
+		let object = value.as_object().ok_or_else(|| format!("{} must be an object of type {}.", path, kind))?;
+
+		if let Some(unknown) = object.keys().find(|key| members.iter().all(|member| &member.name != *key)) {
+			return Err(format!("{}.{} is not a member of {}.", path, unknown, kind));
+		}
+
+		let mut encoded = self.encode_type(kind)?.sha3().to_vec();
+		for member in members {
+			let path = format!("{}.{}", path, member.name);
+			let value = object.get(&member.name).ok_or_else(|| format!("{} is missing.", path))?;
+			let word = self.encode_value(&member.kind, value, &path)?;
+			encoded.extend_from_slice(&*word);
+		}
+		Ok(encoded.sha3())
+	}
+
+	fn encode_value(&mut self, kind: &str, value: &Value, path: &str) -> Result<H256, String> {
+		if kind.ends_with(']') {
+			let open = kind.rfind('[').ok_or_else(|| format!("Type {} is invalid.", kind))?;
+			let (item_kind, length) = (&kind[..open], &kind[open + 1..kind.len() - 1]);
+			let items = value.as_array().ok_or_else(|| format!("{} must be an array.", path))?;
+			if !length.is_empty() {
+				let length: usize = length.parse().map_err(|_| format!("Type {} is invalid.", kind))?;
+				if items.len() != length {
+					return Err(format!("{} must have exactly {} items.", path, length));
+				}
+			}
+
+			let mut encoded = Vec::with_capacity(items.len() * 32);
+			for (index, item) in items.iter().enumerate() {
+				let word = self.encode_value(item_kind, item, &format!("{}[{}]", path, index))?;
+				encoded.extend_from_slice(&*word);
+			}
+			return Ok(encoded.sha3());
+		}
+
+		if self.types.contains_key(kind) {
+			return self.hash_struct(kind, value, path);
+		}
+
+		let (word, rendered) = encode_atomic(kind, value).map_err(|e| format!("{}: {}", path, e))?;
+		self.fields.push(TypedDataField {
+			name: path.to_owned(),
+			kind: kind.to_owned(),
+			value: rendered,
+		});
+		Ok(word)
+	}
+}
+
+fn base_type(kind: &str) -> &str {
+	kind.find('[').map_or(kind, |index| &kind[..index])
+}
+
+/// Parses the size suffix of sized types such as `uint64` or `bytes4`.
+fn size_suffix(kind: &str, prefix: &str, default: Option<usize>, max: usize) -> Option<usize> {
+	if !kind.starts_with(prefix) {
+		return None;
+	}
+	let suffix = &kind[prefix.len()..];
+	let size = if suffix.is_empty() { default } else { suffix.parse().ok() };
+	match size {
+		Some(size) if size > 0 && size <= max => Some(size),
+		_ => None,
+	}
+}
+
+fn hex_bytes(value: &Value) -> Result<Vec<u8>, String> {
+	let s = value.as_str().ok_or_else(|| "expected a hex string".to_owned())?;
+	if !s.starts_with("0x") {
+		return Err("expected a 0x-prefixed hex string".into());
+	}
+	s[2..].from_hex().map_err(|e| format!("invalid hex: {}", e))
+}
+
+/// Parses a JSON number or a decimal/hex string, returning the magnitude and sign.
+fn number(value: &Value) -> Result<(U256, bool), String> {
+	if let Some(n) = value.as_u64() {
+		return Ok((n.into(), false));
+	}
+	if let Some(n) = value.as_i64() {
+		return Ok(((n as u64).wrapping_neg().into(), true));
+	}
+
+	let s = value.as_str().ok_or_else(|| "expected a number".to_owned())?;
+	let (negative, s) = if s.starts_with('-') { (true, &s[1..]) } else { (false, s) };
+	let magnitude = if s.starts_with("0x") {
+		U256::from_str(&s[2..]).map_err(|_| format!("invalid hex number {}", s))?
+	} else {
+		U256::from_dec_str(s).map_err(|_| format!("invalid number {}", s))?
+	};
+	Ok((magnitude, negative && !magnitude.is_zero()))
+}
+
+fn encode_atomic(kind: &str, value: &Value) -> Result<(H256, String), String> {
+	let mut word = [0u8; 32];
+
+	if kind == "bool" {
+		let b = value.as_bool().ok_or_else(|| "expected a boolean".to_owned())?;
+		word[31] = b as u8;
+		return Ok((H256(word), format!("{}", b)));
+	}
+
+	if kind == "address" {
+		let s = value.as_str().ok_or_else(|| "expected an address".to_owned())?;
+		let address = if s.starts_with("0x") { Address::from_str(&s[2..]).ok() } else { None };
+		let address = address.ok_or_else(|| format!("invalid address {}", s))?;
+		return Ok((address.into(), format!("0x{:?}", address)));
+	}
+
+	if kind == "string" {
+		let s = value.as_str().ok_or_else(|| "expected a string".to_owned())?;
+		return Ok((s.sha3(), s.to_owned()));
+	}
+
+	if kind == "bytes" {
+		let bytes = hex_bytes(value)?;
+		let rendered = format!("0x{}", bytes.to_hex());
+		return Ok((bytes.sha3(), rendered));
+	}
+
+	if let Some(size) = size_suffix(kind, "bytes", None, 32) {
+		let bytes = hex_bytes(value)?;
+		if bytes.len() != size {
+			return Err(format!("expected exactly {} bytes", size));
+		}
+		word[..size].copy_from_slice(&bytes);
+		return Ok((H256(word), format!("0x{}", bytes.to_hex())));
+	}
+
+	let (bits, signed) = match (size_suffix(kind, "uint", Some(256), 256), size_suffix(kind, "int", Some(256), 256)) {
+		(Some(bits), _) => (bits, false),
+		(None, Some(bits)) => (bits, true),
+		_ => return Err(format!("unsupported type {}", kind)),
+	};
+	if bits % 8 != 0 {
+		return Err(format!("unsupported type {}", kind));
+	}
+
+	let (magnitude, negative) = number(value)?;
+	let in_range = match (signed, negative) {
+		(false, true) => false,
+		(false, false) => magnitude.bits() <= bits,
+		(true, false) => magnitude.bits() < bits,
+		// the most negative value is -2^(bits - 1)
+		(true, true) => magnitude <= U256::one() << (bits - 1),
+	};
+	if !in_range {
+		return Err(format!("value out of range for {}", kind));
+	}
+
+	let encoded = if negative { U256::zero().overflowing_sub(magnitude).0 } else { magnitude };
+	encoded.to_big_endian(&mut word);
+	let sign = if negative { "-" } else { "" };
+	Ok((H256(word), format!("{}{}", sign, magnitude)))
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use util::H256;
+	use v1::types::TypedData;
+	use super::hash;
+
+	fn mail() -> TypedData {
+		serde_json::from_str(r#"{
+			"types": {
+				"EIP712Domain": [
+					{"name": "name", "type": "string"},
+					{"name": "version", "type": "string"},
+					{"name": "chainId", "type": "uint256"},
+					{"name": "verifyingContract", "type": "address"}
+				],
+				"Person": [
+					{"name": "name", "type": "string"},
+					{"name": "wallet", "type": "address"}
+				],
+				"Mail": [
+					{"name": "from", "type": "Person"},
+					{"name": "to", "type": "Person"},
+					{"name": "contents", "type": "string"}
+				]
+			},
+			"primaryType": "Mail",
+			"domain": {
+				"name": "Ether Mail",
+				"version": "1",
+				"chainId": 1,
+				"verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+			},
+			"message": {
+				"from": {"name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"},
+				"to": {"name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB"},
+				"contents": "Hello, Bob!"
+			}
+		}"#).unwrap()
+	}
+
+	#[test]
+	fn should_hash_typed_data() {
+		let (signed_hash, fields) = hash(&mail()).unwrap();
+
+		assert_eq!(signed_hash, H256::from("be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"));
+		let rendered = fields.iter().map(|f| format!("{}={}", f.name, f.value)).collect::<Vec<_>>();
+		assert_eq!(rendered, vec![
+			"domain.name=Ether Mail",
+			"domain.version=1",
+			"domain.chainId=1",
+			"domain.verifyingContract=0xcccccccccccccccccccccccccccccccccccccccc",
+			"message.from.name=Cow",
+			"message.from.wallet=0xcd2a3d9f938e13cd947ec05abc7fe734df8dd826",
+			"message.to.name=Bob",
+			"message.to.wallet=0xbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
+			"message.contents=Hello, Bob!",
+		]);
+	}
+
+	#[test]
+	fn should_encode_dependent_types_in_order() {
+		let data = mail();
+		let encoder = super::Encoder { types: &data.types, fields: Vec::new() };
+
+		assert_eq!(encoder.encode_type("Mail").unwrap(), "Mail(Person from,Person to,string contents)Person(string name,address wallet)");
+	}
+
+	#[test]
+	fn should_reject_unknown_and_missing_members() {
+		let mut data = mail();
+		data.message.as_object_mut().unwrap().insert("contents".into(), serde_json::Value::Bool(true));
+		assert_eq!(hash(&data).unwrap_err(), "message.contents: expected a string");
+
+		let mut data = mail();
+		data.message.as_object_mut().unwrap().remove("contents");
+		assert_eq!(hash(&data).unwrap_err(), "message.contents is missing.");
+
+		let mut data = mail();
+		data.message.as_object_mut().unwrap().insert("hidden".into(), serde_json::Value::Bool(true));
+		assert_eq!(hash(&data).unwrap_err(), "message.hidden is not a member of Mail.");
+	}
+
+	#[test]
+	fn should_check_integer_ranges() {
+		use serde_json::Value;
+		use super::encode_atomic;
+
+		assert!(encode_atomic("uint8", &Value::String("255".into())).is_ok());
+		assert!(encode_atomic("uint8", &Value::String("256".into())).is_err());
+		assert!(encode_atomic("uint8", &Value::String("-1".into())).is_err());
+		assert!(encode_atomic("int8", &Value::String("-128".into())).is_ok());
+		assert!(encode_atomic("int8", &Value::String("128".into())).is_err());
+		assert_eq!(
+			encode_atomic("int256", &Value::String("-1".into())).unwrap().0,
+			H256::from("ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff")
+		);
+		assert!(encode_atomic("uint7", &Value::String("1".into())).is_err());
+		assert!(encode_atomic("fixed128x18", &Value::String("1".into())).is_err());
+	}
+}
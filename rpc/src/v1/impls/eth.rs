@@ -16,6 +16,7 @@
 
 //! Eth rpc implementation.
 
+use std::cmp;
 use std::thread;
 use std::time::{Instant, Duration};
 use std::sync::{Arc, Weak};
@@ -29,8 +30,7 @@ use util::Mutex;
 
 use ethash::SeedHashCompute;
 use ethcore::account_provider::{AccountProvider, DappId};
-use ethcore::block::IsBlock;
-use ethcore::client::{MiningBlockChainClient, BlockId, TransactionId, UncleId};
+use ethcore::client::{MiningBlockChainClient, BlockId, TransactionId, UncleId, CallAnalytics, Executed};
 use ethcore::ethereum::Ethash;
 use ethcore::filter::Filter as EthcoreFilter;
 use ethcore::header::{Header as BlockHeader, BlockNumber as EthBlockNumber};
@@ -43,17 +43,20 @@ use ethsync::{SyncProvider};
 use jsonrpc_core::Error;
 use jsonrpc_macros::Trailing;
 
-use v1::helpers::{errors, limit_logs, fake_sign};
+use v1::helpers::{errors, limit_logs, fake_sign, CallCache};
 use v1::helpers::dispatch::{Dispatcher, FullDispatcher, default_gas_price};
 use v1::helpers::block_import::is_major_importing;
 use v1::traits::Eth;
 use v1::types::{
 	RichBlock, Block, BlockTransactions, BlockNumber, Bytes, SyncStatus, SyncInfo,
-	Transaction, CallRequest, Index, Filter, Log, Receipt, Work,
+	Transaction, CallRequest, Index, FeeHistory, Filter, Log, Receipt, Work, StateOverride,
 	H64 as RpcH64, H256 as RpcH256, H160 as RpcH160, U256 as RpcU256,
 };
 use v1::metadata::Metadata;
 
+/// Maximal number of blocks of a fee history.
+const MAX_FEE_HISTORY_BLOCKS: u64 = 1024;
+
 const EXTRA_INFO_PROOF: &'static str = "Object exists in in blockchain (fetched earlier), extra_info is always available if object exists; qed";
 
 /// Eth RPC options
@@ -64,6 +67,8 @@ pub struct EthClientOptions {
 	pub allow_pending_receipt_query: bool,
 	/// Send additional block number when asking for work
 	pub send_block_number_in_get_work: bool,
+	/// Maximal number of logs returned by `eth_getLogs` without an explicit limit.
+	pub max_logs: Option<usize>,
 }
 
 impl EthClientOptions {
@@ -82,10 +87,14 @@ impl Default for EthClientOptions {
 			pending_nonce_from_queue: false,
 			allow_pending_receipt_query: true,
 			send_block_number_in_get_work: true,
+			max_logs: None,
 		}
 	}
 }
 
+/// Memory set aside for `eth_call` outputs.
+const CALL_CACHE_SIZE: usize = 4 * 1024 * 1024;
+
 /// Eth rpc implementation.
 pub struct EthClient<C, SN: ?Sized, S: ?Sized, M, EM> where
 	C: MiningBlockChainClient,
@@ -101,6 +110,7 @@ pub struct EthClient<C, SN: ?Sized, S: ?Sized, M, EM> where
 	miner: Weak<M>,
 	external_miner: Arc<EM>,
 	seed_compute: Mutex<SeedHashCompute>,
+	call_cache: Mutex<CallCache>,
 	options: EthClientOptions,
 }
 
@@ -129,6 +139,7 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> EthClient<C, SN, S, M, EM> where
 			accounts: Arc::downgrade(accounts),
 			external_miner: em.clone(),
 			seed_compute: Mutex::new(SeedHashCompute::new()),
+			call_cache: Mutex::new(CallCache::new(CALL_CACHE_SIZE)),
 			options: options,
 		}
 	}
@@ -229,6 +240,53 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> EthClient<C, SN, S, M, EM> where
 			.and_then(|_| store.dapp_addresses(dapp))
 			.map_err(|e| errors::account("Could not fetch accounts.", e))
 	}
+
+	fn call_with_analytics(&self, request: CallRequest, num: BlockNumber, analytics: CallAnalytics) -> Result<Bytes, Error> {
+		let request = CallRequest::into(request);
+		let signed = fake_sign::sign_call(&self.client, &self.miner, request)?;
+
+		let result = match num {
+			BlockNumber::Pending => take_weak!(self.miner).call(&*take_weak!(self.client), &signed, analytics),
+			num => take_weak!(self.client).call(&signed, num.into(), analytics),
+		};
+
+		result
+			.map_err(errors::from_call_error)
+			.and_then(call_output)
+	}
+
+	/// Plain `eth_call` against a block in the chain, answered from the cache when the same call
+	/// was already made against that block since the best block last changed.
+	fn cached_call(&self, request: CallRequest, num: BlockNumber) -> Result<Bytes, Error> {
+		let client = take_weak!(self.client);
+		let block = match client.block_hash(num.clone().into()) {
+			Some(hash) => hash,
+			None => return self.call_with_analytics(request, num, Default::default()),
+		};
+
+		let signed = fake_sign::sign_call(&self.client, &self.miner, request.into())?;
+		let key = (block, signed.sender(), signed.as_unsigned().hash(None));
+		let best_block = client.chain_info().best_block_hash;
+		if let Some(output) = self.call_cache.lock().get(&best_block, &key) {
+			return Ok(output.into());
+		}
+
+		let executed = client.call(&signed, BlockId::Hash(block), Default::default())
+			.map_err(errors::from_call_error)?;
+		if executed.exception.is_none() {
+			self.call_cache.lock().insert(&best_block, key, executed.output.clone());
+		}
+		call_output(executed)
+	}
+}
+
+/// Output of a call, or an error describing the exception it ended in.
+fn call_output(executed: Executed) -> Result<Bytes, Error> {
+	let Executed { exception, output, .. } = executed;
+	match exception {
+		Some(e) => Err(errors::vm(&e, &output)),
+		None => Ok(output.into()),
+	}
 }
 
 pub fn pending_logs<M>(miner: &M, best_block: EthBlockNumber, filter: &EthcoreFilter) -> Vec<Log> where M: MinerService {
@@ -335,6 +393,28 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 		Ok(RpcU256::from(default_gas_price(&*client, &*miner)))
 	}
 
+	fn fee_history(&self, block_count: RpcU256, newest_block: BlockNumber, percentiles: Trailing<Vec<f64>>) -> Result<FeeHistory, Error> {
+		let block_count: U256 = block_count.into();
+		if block_count.is_zero() || block_count > U256::from(MAX_FEE_HISTORY_BLOCKS) {
+			return Err(errors::invalid_params("blockCount", format!("must be between 1 and {}", MAX_FEE_HISTORY_BLOCKS)));
+		}
+
+		let percentiles = percentiles.0;
+		let in_range = percentiles.iter().all(|p| *p >= 0.0 && *p <= 100.0);
+		let increasing = percentiles.windows(2).all(|w| w[0] <= w[1]);
+		if !in_range || !increasing {
+			return Err(errors::invalid_params("rewardPercentiles", "must be increasing values between 0 and 100"));
+		}
+
+		let newest_block = match newest_block {
+			BlockNumber::Pending => BlockId::Latest,
+			number => number.into(),
+		};
+		take_weak!(self.client).fee_history(block_count.low_u64() as usize, newest_block, &percentiles)
+			.map(Into::into)
+			.ok_or_else(errors::unknown_block)
+	}
+
 	fn accounts(&self, meta: Metadata) -> BoxFuture<Vec<RpcH160>, Error> {
 		let dapp = meta.dapp_id();
 
@@ -540,8 +620,17 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 	fn logs(&self, filter: Filter) -> Result<Vec<Log>, Error> {
 		let include_pending = filter.to_block == Some(BlockNumber::Pending);
 		let filter: EthcoreFilter = filter.into();
-		let mut logs = take_weak!(self.client).logs(filter.clone())
-			.into_iter()
+		let logs = match self.options.max_logs {
+			Some(max_logs) => {
+				let page_size = filter.limit.map_or(max_logs, |limit| cmp::min(limit, max_logs));
+				match take_weak!(self.client).logs_page(filter.clone(), page_size) {
+					(_, Some(next_block)) => return Err(errors::too_many_logs(page_size, next_block)),
+					(logs, None) => logs,
+				}
+			},
+			None => take_weak!(self.client).logs(filter.clone()),
+		};
+		let mut logs = logs.into_iter()
 			.map(From::from)
 			.collect::<Vec<Log>>();
 
@@ -581,24 +670,22 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 			warn!(target: "miner", "Cannot give work package - no author is configured. Use --author to configure!");
 			return Err(errors::no_author())
 		}
-		miner.map_sealing_work(&*client, |b| {
-			let pow_hash = b.hash();
-			let target = Ethash::difficulty_to_boundary(b.block().header().difficulty());
-			let seed_hash = self.seed_compute.lock().get_seedhash(b.block().header().number());
+		miner.work_package(&*client).map(|work| {
+			let target = Ethash::difficulty_to_boundary(&work.difficulty);
+			let seed_hash = self.seed_compute.lock().get_seedhash(work.number);
 
-			if no_new_work_timeout > 0 && b.block().header().timestamp() + no_new_work_timeout < get_time().sec as u64 {
+			if no_new_work_timeout > 0 && work.timestamp + no_new_work_timeout < get_time().sec as u64 {
 				Err(errors::no_new_work())
 			} else if self.options.send_block_number_in_get_work {
-				let block_number = b.block().header().number();
 				Ok(Work {
-					pow_hash: pow_hash.into(),
+					pow_hash: work.pow_hash.into(),
 					seed_hash: seed_hash.into(),
 					target: target.into(),
-					number: Some(block_number),
+					number: Some(work.number),
 				})
 			} else {
 				Ok(Work {
-					pow_hash: pow_hash.into(),
+					pow_hash: work.pow_hash.into(),
 					seed_hash: seed_hash.into(),
 					target: target.into(),
 					number: None
@@ -640,20 +727,29 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 	}
 
 	fn call(&self, request: CallRequest, num: Trailing<BlockNumber>) -> BoxFuture<Bytes, Error> {
-		let request = CallRequest::into(request);
-		let signed = match fake_sign::sign_call(&self.client, &self.miner, request) {
-			Ok(signed) => signed,
-			Err(e) => return future::err(e).boxed(),
-		};
+		future::done(match num.0 {
+			BlockNumber::Pending => self.call_with_analytics(request, BlockNumber::Pending, Default::default()),
+			num => self.cached_call(request, num),
+		}).boxed()
+	}
 
-		let result = match num.0 {
-			BlockNumber::Pending => take_weakf!(self.miner).call(&*take_weakf!(self.client), &signed, Default::default()),
-			num => take_weakf!(self.client).call(&signed, num.into(), Default::default()),
-		};
+	fn call_with_overrides(&self, request: CallRequest, num: BlockNumber, overrides: StateOverride) -> BoxFuture<Bytes, Error> {
+		let analytics = CallAnalytics { state_override: overrides.into(), ..Default::default() };
+		future::done(self.call_with_analytics(request, num, analytics)).boxed()
+	}
+
+	fn call_many(&self, requests: Vec<CallRequest>, num: Trailing<BlockNumber>) -> BoxFuture<Vec<Bytes>, Error> {
+		let mut transactions = Vec::with_capacity(requests.len());
+		for request in requests {
+			match fake_sign::sign_call(&self.client, &self.miner, request.into()) {
+				Ok(signed) => transactions.push((signed, Default::default())),
+				Err(e) => return future::err(e).boxed(),
+			}
+		}
 
-		future::done(result
-			.map(|b| b.output.into())
+		future::done(take_weakf!(self.client).call_many(transactions, num.0.into())
 			.map_err(errors::from_call_error)
+			.and_then(|results| results.into_iter().map(call_output).collect())
 		).boxed()
 	}
 
@@ -24,7 +24,7 @@ use ethcore::filter::Filter as EthcoreFilter;
 use ethcore::client::{BlockChainClient, BlockId};
 use util::Mutex;
 use v1::traits::EthFilter;
-use v1::types::{BlockNumber, Index, Filter, FilterChanges, Log, H256 as RpcH256, U256 as RpcU256};
+use v1::types::{BlockNumber, Index, Filter, FilterChanges, Log, TransactionFilter, H256 as RpcH256, U256 as RpcU256};
 use v1::helpers::{PollFilter, PollManager, limit_logs};
 use v1::impls::eth::pending_logs;
 
@@ -76,6 +76,14 @@ impl<C, M> EthFilter for EthFilterClient<C, M>
 		Ok(id.into())
 	}
 
+	fn new_matching_pending_transaction_filter(&self, filter: TransactionFilter) -> Result<RpcU256, Error> {
+		let mut polls = self.polls.lock();
+		let best_block = take_weak!(self.client).chain_info().best_block_number;
+		let pending_transactions = take_weak!(self.miner).pending_transactions_hashes(best_block);
+		let id = polls.create_poll(PollFilter::MatchingPendingTransaction(pending_transactions, filter));
+		Ok(id.into())
+	}
+
 	fn filter_changes(&self, index: Index) -> Result<FilterChanges, Error> {
 		let client = take_weak!(self.client);
 		let mut polls = self.polls.lock();
@@ -119,6 +127,28 @@ impl<C, M> EthFilter for EthFilterClient<C, M>
 					// return new hashes
 					Ok(FilterChanges::Hashes(new_hashes))
 				},
+				PollFilter::MatchingPendingTransaction(ref mut previous_hashes, ref filter) => {
+					let best_block = client.chain_info().best_block_number;
+					let miner = take_weak!(self.miner);
+					let current_hashes = miner.pending_transactions_hashes(best_block);
+
+					// only the transactions which are new since the last poll need to be matched
+					let new_hashes = {
+						let previous_hashes_set = previous_hashes.iter().collect::<HashSet<_>>();
+
+						current_hashes
+							.iter()
+							.filter(|hash| !previous_hashes_set.contains(hash))
+							.filter_map(|hash| miner.transaction(best_block, hash))
+							.filter(|transaction| filter.matches(transaction))
+							.map(|transaction| transaction.hash().into())
+							.collect::<Vec<RpcH256>>()
+					};
+
+					*previous_hashes = current_hashes;
+
+					Ok(FilterChanges::Hashes(new_hashes))
+				},
 				PollFilter::Logs(ref mut block_number, ref mut previous_logs, ref filter) => {
 					// retrive the current block number
 					let current_number = client.chain_info().best_block_number;
@@ -0,0 +1,297 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Eth PUB-SUB rpc implementation.
+
+use std::sync::{Arc, Weak};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use futures::{self, BoxFuture, Future};
+use jsonrpc_core::Error;
+use jsonrpc_macros::Trailing;
+use jsonrpc_macros::pubsub::Subscriber;
+use jsonrpc_pubsub::SubscriptionId;
+
+use ethash::SeedHashCompute;
+use ethcore::client::{BlockChainClient, BlockId, ChainNotify};
+use ethcore::ethereum::Ethash;
+use ethcore::filter::Filter as EthFilter;
+use ethcore::log_entry::LocalizedLogEntry;
+use ethcore::receipt::Receipt;
+use ethcore::miner::WorkPackage;
+use ethcore::transaction::SignedTransaction;
+use ethsync::{SyncProvider, SyncStatus as EthSyncStatus};
+use parity_reactor::Remote;
+use rlp::UntrustedRlp;
+use util::{Bytes, H256, Mutex};
+
+use v1::helpers::{errors, Subscribers};
+use v1::helpers::block_import::is_major_importing;
+use v1::metadata::Metadata;
+use v1::traits::EthPubSub;
+use v1::types::{ChainReorg, Header, Log, PubSubKind, PubSubParams, PubSubResult, SyncInfo, SyncStatus, TransactionFilter, Work};
+
+/// What a subscription is notified of.
+enum Target {
+	Heads,
+	Logs(EthFilter),
+	PendingTransactions(Option<TransactionFilter>),
+	Syncing,
+	ChainReorg,
+	Work,
+}
+
+/// Eth PubSub implementation.
+pub struct EthPubSubClient<C, S: ?Sized> {
+	handler: Arc<ChainNotificationHandler<C, S>>,
+	subscribers: Arc<Mutex<Subscribers<Target>>>,
+}
+
+impl<C, S: ?Sized> EthPubSubClient<C, S> {
+	/// Creates new `EthPubSubClient`, allowing at most `max_subscriptions` per connection.
+	pub fn new(client: &Arc<C>, sync: &Arc<S>, remote: Remote, max_subscriptions: usize) -> Self {
+		let subscribers = Arc::new(Mutex::new(Subscribers::new(max_subscriptions)));
+		EthPubSubClient {
+			handler: Arc::new(ChainNotificationHandler {
+				client: Arc::downgrade(client),
+				sync: Arc::downgrade(sync),
+				remote: remote,
+				subscribers: subscribers.clone(),
+				syncing: AtomicBool::new(false),
+				seed_compute: Mutex::new(SeedHashCompute::new()),
+			}),
+			subscribers: subscribers,
+		}
+	}
+
+	/// Returns the handler to be notified of chain and transaction queue events.
+	pub fn handler(&self) -> Arc<ChainNotificationHandler<C, S>> {
+		self.handler.clone()
+	}
+}
+
+/// Notifies the subscribers of chain and transaction queue events.
+pub struct ChainNotificationHandler<C, S: ?Sized> {
+	client: Weak<C>,
+	sync: Weak<S>,
+	remote: Remote,
+	subscribers: Arc<Mutex<Subscribers<Target>>>,
+	/// Whether the last sync snapshot sent out was a syncing one.
+	syncing: AtomicBool,
+	seed_compute: Mutex<SeedHashCompute>,
+}
+
+impl<C, S: ?Sized> ChainNotificationHandler<C, S> {
+	/// Notify the subscribers of transactions newly added to the queue.
+	///
+	/// `transaction` looks up a queued transaction; it's only used for subscriptions filtering them.
+	pub fn new_pending_transactions<F>(&self, hashes: &[H256], transaction: F) where
+		F: Fn(&H256) -> Option<SignedTransaction>,
+	{
+		self.subscribers.lock().notify(&self.remote, |target| match *target {
+			Target::PendingTransactions(None) => hashes.iter()
+				.map(|hash| PubSubResult::TransactionHash(hash.clone().into()))
+				.collect(),
+			Target::PendingTransactions(Some(ref filter)) => hashes.iter()
+				.filter_map(|hash| transaction(hash))
+				.filter(|transaction| filter.matches(transaction))
+				.map(|transaction| PubSubResult::TransactionHash(transaction.hash().into()))
+				.collect(),
+			_ => Vec::new(),
+		});
+	}
+
+	/// Notify the subscribers of a new sealing work package.
+	pub fn new_work(&self, work: &WorkPackage) {
+		let work = Work {
+			pow_hash: work.pow_hash.into(),
+			seed_hash: self.seed_compute.lock().get_seedhash(work.number).into(),
+			target: Ethash::difficulty_to_boundary(&work.difficulty).into(),
+			number: Some(work.number),
+		};
+
+		self.subscribers.lock().notify(&self.remote, |target| match *target {
+			Target::Work => vec![PubSubResult::Work(work.clone())],
+			_ => Vec::new(),
+		});
+	}
+}
+
+impl<C: BlockChainClient, S: ?Sized> ChainNotificationHandler<C, S> {
+	fn logs(client: &C, filter: &EthFilter, blocks: &[H256], log_type: &str) -> Vec<PubSubResult> {
+		blocks.iter()
+			.flat_map(|hash| Self::block_logs(client, hash))
+			.filter(|entry| filter.matches(&entry.entry))
+			.map(|entry| {
+				let mut log = Log::from(entry);
+				log.log_type = log_type.into();
+				PubSubResult::Log(log)
+			})
+			.collect()
+	}
+
+	// all logs of the block with the given hash. Read from the block's own receipts and body,
+	// since filtering logs by block resolves to the canonical block at its number.
+	fn block_logs(client: &C, hash: &H256) -> Vec<LocalizedLogEntry> {
+		let header = client.block_header(BlockId::Hash(hash.clone()));
+		let body = client.block_body(BlockId::Hash(hash.clone()));
+		let receipts = client.block_receipts(hash)
+			.and_then(|receipts| UntrustedRlp::new(&receipts).as_list::<Receipt>().ok());
+		let (number, transaction_hashes, receipts) = match (header, body, receipts) {
+			(Some(header), Some(body), Some(receipts)) => (header.number(), body.transaction_hashes(), receipts),
+			_ => return Vec::new(),
+		};
+
+		let mut logs = Vec::new();
+		for (transaction_index, (receipt, transaction_hash)) in receipts.into_iter().zip(transaction_hashes).enumerate() {
+			for (transaction_log_index, entry) in receipt.logs.into_iter().enumerate() {
+				let log_index = logs.len();
+				logs.push(LocalizedLogEntry {
+					entry: entry,
+					block_hash: hash.clone(),
+					block_number: number,
+					transaction_hash: transaction_hash,
+					transaction_index: transaction_index,
+					transaction_log_index: transaction_log_index,
+					log_index: log_index,
+				});
+			}
+		}
+		logs
+	}
+
+	fn sync_status(client: &C, status: &EthSyncStatus) -> SyncStatus {
+		if is_major_importing(Some(status.state), client.queue_info()) {
+			SyncStatus::Info(SyncInfo {
+				starting_block: status.start_block_number.into(),
+				current_block: client.chain_info().best_block_number.into(),
+				highest_block: status.highest_block_number.unwrap_or(status.start_block_number).into(),
+				warp_chunks_amount: None,
+				warp_chunks_processed: None,
+			})
+		} else {
+			SyncStatus::None
+		}
+	}
+
+	fn chain_reorg(client: &C, enacted: &[H256], retracted: &[H256]) -> Option<ChainReorg> {
+		// the parent of the lowest retracted block is where both branches meet.
+		retracted.iter()
+			.filter_map(|hash| client.block_header(BlockId::Hash(hash.clone())))
+			.min_by_key(|header| header.number())
+			.map(|header| ChainReorg {
+				old_branch: retracted.iter().cloned().map(Into::into).collect(),
+				new_branch: enacted.iter().cloned().map(Into::into).collect(),
+				common_ancestor: header.parent_hash().into(),
+			})
+	}
+}
+
+impl<C: BlockChainClient, S: ?Sized + SyncProvider> ChainNotify for ChainNotificationHandler<C, S> {
+	fn new_blocks(
+		&self,
+		_imported: Vec<H256>,
+		_invalid: Vec<H256>,
+		enacted: Vec<H256>,
+		retracted: Vec<H256>,
+		_sealed: Vec<H256>,
+		_proposed: Vec<Bytes>,
+		_duration: u64,
+	) {
+		let client = match self.client.upgrade() {
+			Some(client) => client,
+			None => return,
+		};
+
+		let headers: Vec<_> = enacted.iter()
+			.filter_map(|hash| client.block_header(BlockId::Hash(hash.clone())))
+			.map(|header| PubSubResult::Header(Header::from(&header.decode())))
+			.collect();
+
+		// sync snapshots are sent while syncing and once more when it stops.
+		let sync_status = self.sync.upgrade()
+			.map(|sync| Self::sync_status(&*client, &sync.status()))
+			.and_then(|status| {
+				let is_syncing = status != SyncStatus::None;
+				let was_syncing = self.syncing.swap(is_syncing, Ordering::SeqCst);
+				match is_syncing || was_syncing {
+					true => Some(PubSubResult::Syncing(status)),
+					false => None,
+				}
+			});
+
+		let reorg = Self::chain_reorg(&*client, &enacted, &retracted).map(PubSubResult::ChainReorg);
+
+		self.subscribers.lock().notify(&self.remote, |target| match *target {
+			Target::Heads => headers.clone(),
+			Target::Logs(ref filter) => {
+				let mut logs = Self::logs(&*client, filter, &retracted, "removed");
+				logs.extend(Self::logs(&*client, filter, &enacted, "mined"));
+				logs
+			},
+			Target::Syncing => sync_status.iter().cloned().collect(),
+			Target::ChainReorg => reorg.iter().cloned().collect(),
+			Target::PendingTransactions(_) | Target::Work => Vec::new(),
+		});
+	}
+}
+
+impl<C: Send + Sync + 'static, S: ?Sized + Send + Sync + 'static> EthPubSub for EthPubSubClient<C, S> {
+	type Metadata = Metadata;
+
+	fn subscribe(
+		&self,
+		meta: Metadata,
+		subscriber: Subscriber<PubSubResult>,
+		kind: PubSubKind,
+		params: Trailing<PubSubParams>,
+	) {
+		let session = match meta.session {
+			Some(session) => session,
+			None => {
+				let _ = subscriber.reject(errors::unimplemented(Some("Subscriptions are only supported over WebSockets.".into())));
+				return;
+			},
+		};
+
+		let target = match (kind, params.0) {
+			(PubSubKind::NewHeads, PubSubParams::None) => Target::Heads,
+			(PubSubKind::Logs, PubSubParams::None) => Target::Logs(EthFilter {
+				from_block: BlockId::Latest,
+				to_block: BlockId::Latest,
+				address: None,
+				topics: vec![None, None, None, None],
+				limit: None,
+			}),
+			(PubSubKind::Logs, PubSubParams::Logs(filter)) => Target::Logs(filter.into()),
+			(PubSubKind::NewPendingTransactions, PubSubParams::None) => Target::PendingTransactions(None),
+			(PubSubKind::NewPendingTransactions, PubSubParams::Transactions(filter)) => Target::PendingTransactions(Some(filter)),
+			(PubSubKind::Syncing, PubSubParams::None) => Target::Syncing,
+			(PubSubKind::ChainReorg, PubSubParams::None) => Target::ChainReorg,
+			(PubSubKind::NewWork, PubSubParams::None) => Target::Work,
+			(kind, _) => {
+				let _ = subscriber.reject(errors::invalid_params("params", format!("{:?} subscriptions take no parameters", kind)));
+				return;
+			},
+		};
+
+		self.subscribers.lock().push(&session, subscriber, target);
+	}
+
+	fn unsubscribe(&self, id: SubscriptionId) -> BoxFuture<bool, Error> {
+		futures::future::ok(self.subscribers.lock().remove(&id)).boxed()
+	}
+}
@@ -43,12 +43,12 @@ use util::{RwLock, Mutex, Uint, U256};
 use futures::{future, Future, BoxFuture, IntoFuture};
 use futures::sync::oneshot;
 
-use v1::helpers::{CallRequest as CRequest, errors, limit_logs, dispatch};
+use v1::helpers::{CallRequest as CRequest, EstimateGasConfig, GasSearch, errors, limit_logs, dispatch};
 use v1::helpers::block_import::is_major_importing;
 use v1::traits::Eth;
 use v1::types::{
 	RichBlock, Block, BlockTransactions, BlockNumber, Bytes, SyncStatus, SyncInfo,
-	Transaction, CallRequest, Index, Filter, Log, Receipt, Work,
+	Transaction, CallRequest, Index, FeeHistory, Filter, Log, Receipt, Work, StateOverride,
 	H64 as RpcH64, H256 as RpcH256, H160 as RpcH160, U256 as RpcU256,
 };
 use v1::metadata::Metadata;
@@ -56,6 +56,7 @@ use v1::metadata::Metadata;
 use util::Address;
 
 /// Light client `ETH` RPC.
+#[derive(Clone)]
 pub struct EthClient {
 	sync: Arc<LightSync>,
 	client: Arc<LightClient>,
@@ -63,6 +64,7 @@ pub struct EthClient {
 	transaction_queue: Arc<RwLock<TransactionQueue>>,
 	accounts: Arc<AccountProvider>,
 	cache: Arc<Mutex<LightDataCache>>,
+	estimate_gas: EstimateGasConfig,
 }
 
 // helper for internal error: on demand sender cancelled.
@@ -82,6 +84,7 @@ impl EthClient {
 		transaction_queue: Arc<RwLock<TransactionQueue>>,
 		accounts: Arc<AccountProvider>,
 		cache: Arc<Mutex<LightDataCache>>,
+		estimate_gas: EstimateGasConfig,
 	) -> Self {
 		EthClient {
 			sync: sync,
@@ -90,6 +93,7 @@ impl EthClient {
 			transaction_queue: transaction_queue,
 			accounts: accounts,
 			cache: cache,
+			estimate_gas: estimate_gas,
 		}
 	}
 
@@ -163,13 +167,11 @@ impl EthClient {
 	}
 
 	// helper for getting proved execution.
-	fn proved_execution(&self, req: CallRequest, num: Trailing<BlockNumber>) -> BoxFuture<ExecutionResult, Error> {
+	fn proved_execution(&self, req: CRequest, id: BlockId) -> BoxFuture<ExecutionResult, Error> {
 		const DEFAULT_GAS_PRICE: U256 = U256([0, 0, 0, 21_000_000]);
 
 
 		let (sync, on_demand, client) = (self.sync.clone(), self.on_demand.clone(), self.client.clone());
-		let req: CRequest = req.into();
-		let id = num.0.into();
 
 		let from = req.from.unwrap_or(Address::zero());
 		let nonce_fut = match req.nonce {
@@ -237,6 +239,21 @@ impl EthClient {
 	}
 }
 
+// re-execute the call until the search settles on the least gas it succeeds with.
+fn estimate_gas_search(eth: EthClient, req: CRequest, id: BlockId, mut search: GasSearch) -> BoxFuture<U256, Error> {
+	let gas = match search.next_gas() {
+		Some(gas) => gas,
+		None => return future::ok(search.estimate()).boxed(),
+	};
+
+	let mut attempt = req.clone();
+	attempt.gas = Some(gas);
+	eth.proved_execution(attempt, id).and_then(move |res| {
+		search.record(gas, res.map(|exec| exec.exception.is_none()).unwrap_or(false));
+		estimate_gas_search(eth, req, id, search)
+	}).boxed()
+}
+
 impl Eth for EthClient {
 	type Metadata = Metadata;
 
@@ -264,6 +281,10 @@ impl Eth for EthClient {
 		Ok(Default::default())
 	}
 
+	fn fee_history(&self, _block_count: RpcU256, _newest_block: BlockNumber, _percentiles: Trailing<Vec<f64>>) -> Result<FeeHistory, Error> {
+		Err(errors::unimplemented(None))
+	}
+
 	fn accounts(&self, meta: Metadata) -> BoxFuture<Vec<RpcH160>, Error> {
 		let dapp: DappId = meta.dapp_id().into();
 
@@ -411,22 +432,47 @@ impl Eth for EthClient {
 	}
 
 	fn call(&self, req: CallRequest, num: Trailing<BlockNumber>) -> BoxFuture<Bytes, Error> {
-		self.proved_execution(req, num).and_then(|res| {
+		self.proved_execution(req.into(), num.0.into()).and_then(|res| {
 			match res {
+				Ok(Executed { exception: Some(e), output, .. }) => Err(errors::vm(&e, &output)),
 				Ok(exec) => Ok(exec.output.into()),
 				Err(e) => Err(errors::execution(e)),
 			}
 		}).boxed()
 	}
 
+	fn call_with_overrides(&self, _req: CallRequest, _num: BlockNumber, _overrides: StateOverride) -> BoxFuture<Bytes, Error> {
+		future::err(errors::light_unimplemented(None)).boxed()
+	}
+
+	fn call_many(&self, _reqs: Vec<CallRequest>, _num: Trailing<BlockNumber>) -> BoxFuture<Vec<Bytes>, Error> {
+		future::err(errors::light_unimplemented(None)).boxed()
+	}
+
 	fn estimate_gas(&self, req: CallRequest, num: Trailing<BlockNumber>) -> BoxFuture<RpcU256, Error> {
-		// TODO: binary chop for more accurate estimates.
-		self.proved_execution(req, num).and_then(|res| {
+		let mut req: CRequest = req.into();
+		let id: BlockId = num.0.into();
+		let upper = match req.gas {
+			Some(gas) => ::std::cmp::min(gas, self.estimate_gas.gas_cap),
+			None => self.estimate_gas.gas_cap,
+		};
+		let max_iterations = self.estimate_gas.max_iterations;
+
+		// the sender's balance isn't topped up, so don't charge for the gas unless asked to.
+		req.gas_price = Some(req.gas_price.unwrap_or_else(U256::zero));
+		req.gas = Some(upper);
+
+		let eth = self.clone();
+		self.proved_execution(req.clone(), id).and_then(move |res| {
 			match res {
-				Ok(exec) => Ok((exec.refunded + exec.gas_used).into()),
-				Err(e) => Err(errors::execution(e)),
+				Ok(Executed { exception: Some(e), output, .. }) => future::err(errors::vm(&e, &output)).boxed(),
+				Ok(exec) => {
+					let search = GasSearch::new(exec.gas_used + exec.refunded, upper, max_iterations);
+					estimate_gas_search(eth, req, id, search)
+				},
+				Err(e) => future::err(errors::execution(e)).boxed(),
 			}
-		}).boxed()
+		}).map(Into::into).boxed()
 	}
 
 	fn transaction_by_hash(&self, hash: RpcH256) -> Result<Option<Transaction>, Error> {
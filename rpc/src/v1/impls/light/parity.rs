@@ -40,7 +40,8 @@ use v1::types::{
 	TransactionStats, LocalTransactionStatus,
 	BlockNumber, ConsensusCapability, VersionInfo,
 	OperationsInfo, DappId, ChainStatus,
-	AccountInfo, HwAccountInfo
+	AccountInfo, HwAccountInfo, CallRequest, ExecutionProof, AccountProof, DbStats, DatabaseStatistics,
+	EvmStats, Receipt, Health, TxPoolStatus, TxPoolSender, StratumWorker, MineStats, ReorgStats,
 };
 
 /// Parity implementation for light client.
@@ -222,6 +223,10 @@ impl Parity for ParityClient {
 		Err(errors::light_unimplemented(None))
 	}
 
+	fn key_preimage(&self, _: H256) -> Result<Option<Bytes>, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn encrypt_message(&self, key: H512, phrase: Bytes) -> Result<Bytes, Error> {
 		ecies::encrypt(&key.into(), &DEFAULT_MAC, &phrase.0)
 			.map_err(errors::encryption_error)
@@ -250,6 +255,20 @@ impl Parity for ParityClient {
 		)
 	}
 
+	fn txpool_status(&self) -> Result<TxPoolStatus, Error> {
+		let txq = self.light_dispatch.transaction_queue.read();
+		let chain_info = self.light_dispatch.client.chain_info();
+		let (best_block, best_timestamp) = (chain_info.best_block_number, chain_info.best_block_timestamp);
+		Ok(TxPoolStatus {
+			pending: U256::from(txq.ready_transactions(best_block, best_timestamp).len() as u64),
+			queued: U256::from(txq.future_transactions(best_block, best_timestamp).len() as u64),
+		})
+	}
+
+	fn txpool_content(&self) -> Result<BTreeMap<H160, TxPoolSender>, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn pending_transactions_stats(&self) -> Result<BTreeMap<H256, TransactionStats>, Error> {
 		let stats = self.light_dispatch.sync.transactions_stats();
 		Ok(stats.into_iter()
@@ -277,6 +296,22 @@ impl Parity for ParityClient {
 		Ok(map)
 	}
 
+	fn banned_transaction_senders(&self) -> Result<Vec<H160>, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn mine_stats(&self) -> Result<MineStats, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn reorg_stats(&self) -> Result<ReorgStats, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn stratum_workers(&self) -> Result<Vec<StratumWorker>, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn signer_port(&self) -> Result<u16, Error> {
 		self.signer
 			.clone()
@@ -329,8 +364,12 @@ impl Parity for ParityClient {
 		let gap = chain_info.ancient_block_number.map(|x| U256::from(x + 1))
 			.and_then(|first| chain_info.first_block_number.map(|last| (first, U256::from(last))));
 
+		// state, bodies and receipts are fetched from the network on demand.
 		Ok(ChainStatus {
 			block_gap: gap.map(|(x, y)| (x.into(), y.into())),
+			earliest_state: None,
+			earliest_block: None,
+			warp_restoration: None,
 		})
 	}
 
@@ -342,4 +381,32 @@ impl Parity for ParityClient {
 			capability: Capability::Light,
 		})
 	}
+
+	fn node_health(&self) -> Result<Health, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn execute_with_proof(&self, _: CallRequest, _: Trailing<BlockNumber>) -> Result<ExecutionProof, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn get_proof(&self, _: H160, _: Vec<H256>, _: Trailing<BlockNumber>) -> Result<AccountProof, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn db_stats(&self) -> Result<DbStats, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn db_statistics(&self) -> Result<DatabaseStatistics, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn evm_stats(&self) -> Result<EvmStats, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn block_receipts(&self, _num: BlockNumber) -> Result<Vec<Receipt>, Error> {
+		Err(errors::light_unimplemented(None))
+	}
 }
@@ -59,6 +59,10 @@ impl<F: Fetch> ParitySet for ParitySetClient<F> {
 		Err(errors::light_unimplemented(None))
 	}
 
+	fn set_gas_limit_target(&self, _target: U256) -> Result<bool, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn set_extra_data(&self, _extra_data: Bytes) -> Result<bool, Error> {
 		Err(errors::light_unimplemented(None))
 	}
@@ -79,6 +83,10 @@ impl<F: Fetch> ParitySet for ParitySetClient<F> {
 		Err(errors::light_unimplemented(None))
 	}
 
+	fn set_transaction_ordering_hint(&self, _hashes: Vec<H256>) -> Result<bool, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn add_reserved_peer(&self, peer: String) -> Result<bool, Error> {
 		match self.net.add_reserved_peer(peer) {
 			Ok(()) => Ok(true),
@@ -143,4 +151,8 @@ impl<F: Fetch> ParitySet for ParitySetClient<F> {
 	fn remove_transaction(&self, _hash: H256) -> Result<Option<Transaction>, Error> {
 		Err(errors::light_unimplemented(None))
 	}
+
+	fn backup_database(&self, _path: String) -> Result<bool, Error> {
+		Err(errors::light_unimplemented(None))
+	}
 }
@@ -20,7 +20,7 @@ use jsonrpc_core::Error;
 use jsonrpc_macros::Trailing;
 use v1::traits::Traces;
 use v1::helpers::errors;
-use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, Index, CallRequest, Bytes, TraceResults, H256};
+use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, Index, CallRequest, Bytes, TraceResults, TracePage, GasProfileResults, StructLogConfig, StructLogResults, StateOverride, H256, U256};
 
 /// Traces api implementation.
 // TODO: all calling APIs should be possible w. proved remote TX execution.
@@ -47,6 +47,18 @@ impl Traces for TracesClient {
 		Err(errors::light_unimplemented(None))
 	}
 
+	fn call_with_overrides(&self, _request: CallRequest, _flags: Vec<String>, _block: BlockNumber, _overrides: StateOverride) -> Result<TraceResults, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn call_many(&self, _requests: Vec<(CallRequest, Vec<String>)>, _block: Trailing<BlockNumber>) -> Result<Vec<TraceResults>, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn call_with_gas_profile(&self, _request: CallRequest, _block: Trailing<BlockNumber>) -> Result<GasProfileResults, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn raw_transaction(&self, _raw_transaction: Bytes, _flags: Vec<String>, _block: Trailing<BlockNumber>) -> Result<TraceResults, Error> {
 		Err(errors::light_unimplemented(None))
 	}
@@ -54,4 +66,12 @@ impl Traces for TracesClient {
 	fn replay_transaction(&self, _transaction_hash: H256, _flags: Vec<String>) -> Result<TraceResults, Error> {
 		Err(errors::light_unimplemented(None))
 	}
+
+	fn continue_trace(&self, _token: U256) -> Result<TracePage, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn debug_trace_transaction(&self, _transaction_hash: H256, _config: Trailing<StructLogConfig>) -> Result<StructLogResults, Error> {
+		Err(errors::light_unimplemented(None))
+	}
 }
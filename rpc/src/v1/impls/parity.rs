@@ -21,7 +21,7 @@ use std::collections::{BTreeMap, HashSet};
 use futures::{future, Future, BoxFuture};
 
 use ethcore_logger::RotatingLogger;
-use util::Address;
+use util::{self, Address, Hashable};
 use util::misc::version_data;
 
 use crypto::ecies;
@@ -29,14 +29,15 @@ use ethkey::{Brain, Generator};
 use ethstore::random_phrase;
 use ethsync::{SyncProvider, ManageNetwork};
 use ethcore::miner::MinerService;
-use ethcore::client::{MiningBlockChainClient};
+use ethcore::client::{MiningBlockChainClient, ProvingBlockChainClient, BlockId};
 use ethcore::mode::Mode;
 use ethcore::account_provider::AccountProvider;
 use updater::{Service as UpdateService};
 
 use jsonrpc_core::Error;
 use jsonrpc_macros::Trailing;
-use v1::helpers::{errors, SigningQueue, SignerService, NetworkSettings};
+use v1::helpers::{errors, fake_sign, SigningQueue, SignerService, NetworkSettings};
+use v1::helpers::node_health::NodeHealth;
 use v1::helpers::dispatch::DEFAULT_MAC;
 use v1::metadata::Metadata;
 use v1::traits::Parity;
@@ -45,8 +46,9 @@ use v1::types::{
 	Peers, Transaction, RpcSettings, Histogram,
 	TransactionStats, LocalTransactionStatus,
 	BlockNumber, ConsensusCapability, VersionInfo,
-	OperationsInfo, DappId, ChainStatus,
-	AccountInfo, HwAccountInfo
+	OperationsInfo, DappId, ChainStatus, WarpRestoration,
+	AccountInfo, HwAccountInfo, CallRequest, ExecutionProof, AccountProof, StorageProof, DbStats, DatabaseStatistics,
+	EvmStats, Receipt, Health, TxPoolStatus, TxPoolSender, StratumWorker, MineStats, ReorgStats,
 };
 
 /// Parity implementation.
@@ -67,6 +69,7 @@ pub struct ParityClient<C, M, S: ?Sized, U> where
 	signer: Option<Arc<SignerService>>,
 	dapps_interface: Option<String>,
 	dapps_port: Option<u16>,
+	health: Arc<NodeHealth>,
 }
 
 impl<C, M, S: ?Sized, U> ParityClient<C, M, S, U> where
@@ -88,6 +91,7 @@ impl<C, M, S: ?Sized, U> ParityClient<C, M, S, U> where
 		signer: Option<Arc<SignerService>>,
 		dapps_interface: Option<String>,
 		dapps_port: Option<u16>,
+		health: Arc<NodeHealth>,
 	) -> Self {
 		ParityClient {
 			client: Arc::downgrade(client),
@@ -101,13 +105,14 @@ impl<C, M, S: ?Sized, U> ParityClient<C, M, S, U> where
 			signer: signer,
 			dapps_interface: dapps_interface,
 			dapps_port: dapps_port,
+			health: health,
 		}
 	}
 }
 
 impl<C, M, S: ?Sized, U> Parity for ParityClient<C, M, S, U> where
 	M: MinerService + 'static,
-	C: MiningBlockChainClient + 'static,
+	C: MiningBlockChainClient + ProvingBlockChainClient + 'static,
 	S: SyncProvider + 'static,
 	U: UpdateService + 'static,
 {
@@ -273,6 +278,10 @@ impl<C, M, S: ?Sized, U> Parity for ParityClient<C, M, S, U> where
 			.map(|a| a.into_iter().map(Into::into).collect()))
 	}
 
+	fn key_preimage(&self, hash: H256) -> Result<Option<Bytes>, Error> {
+		Ok(take_weak!(self.client).key_preimage(&hash.into()).map(Into::into))
+	}
+
 	fn encrypt_message(&self, key: H512, phrase: Bytes) -> Result<Bytes, Error> {
 		ecies::encrypt(&key.into(), &DEFAULT_MAC, &phrase.0)
 			.map_err(errors::encryption_error)
@@ -287,6 +296,58 @@ impl<C, M, S: ?Sized, U> Parity for ParityClient<C, M, S, U> where
 		Ok(take_weak!(self.miner).future_transactions().into_iter().map(Into::into).collect::<Vec<_>>())
 	}
 
+	fn txpool_status(&self) -> Result<TxPoolStatus, Error> {
+		let status = take_weak!(self.miner).status();
+		Ok(TxPoolStatus {
+			pending: U256::from(status.transactions_in_pending_queue as u64),
+			queued: U256::from(status.transactions_in_future_queue as u64),
+		})
+	}
+
+	fn txpool_content(&self) -> Result<BTreeMap<H160, TxPoolSender>, Error> {
+		let client = take_weak!(self.client);
+		let miner = take_weak!(self.miner);
+		let suggested_gas_price = miner.sensible_gas_price();
+
+		let mut senders = BTreeMap::new();
+		for tx in miner.pending_transactions() {
+			senders.entry(tx.sender()).or_insert_with(|| (Vec::new(), Vec::new())).0.push(tx);
+		}
+		for tx in miner.future_transactions() {
+			senders.entry(tx.sender()).or_insert_with(|| (Vec::new(), Vec::new())).1.push(tx);
+		}
+
+		Ok(senders.into_iter().map(|(sender, (mut pending, mut queued))| {
+			pending.sort_by_key(|tx| tx.nonce);
+			queued.sort_by_key(|tx| tx.nonce);
+
+			let nonce = client.latest_nonce(&sender);
+			let mut next_nonce = nonce;
+			let mut nonce_gaps = Vec::new();
+			for tx in pending.iter().chain(queued.iter()) {
+				if tx.nonce > next_nonce {
+					nonce_gaps.push((next_nonce.into(), (tx.nonce - 1.into()).into()));
+				}
+				if tx.nonce >= next_nonce {
+					next_nonce = tx.nonce + 1.into();
+				}
+			}
+
+			let replacement_candidates = pending.iter()
+				.filter(|tx| tx.gas_price < suggested_gas_price)
+				.map(|tx| tx.hash().into())
+				.collect();
+
+			(sender.into(), TxPoolSender {
+				nonce: nonce.into(),
+				pending: pending.into_iter().map(Into::into).collect(),
+				queued: queued.into_iter().map(Into::into).collect(),
+				nonce_gaps: nonce_gaps,
+				replacement_candidates: replacement_candidates,
+			})
+		}).collect())
+	}
+
 	fn pending_transactions_stats(&self) -> Result<BTreeMap<H256, TransactionStats>, Error> {
 		let stats = take_weak!(self.sync).transactions_stats();
 		Ok(stats.into_iter()
@@ -304,6 +365,22 @@ impl<C, M, S: ?Sized, U> Parity for ParityClient<C, M, S, U> where
 		)
 	}
 
+	fn banned_transaction_senders(&self) -> Result<Vec<H160>, Error> {
+		Ok(take_weak!(self.miner).banned_transaction_senders().into_iter().map(Into::into).collect())
+	}
+
+	fn mine_stats(&self) -> Result<MineStats, Error> {
+		Ok(take_weak!(self.miner).mine_stats().into())
+	}
+
+	fn reorg_stats(&self) -> Result<ReorgStats, Error> {
+		Ok(take_weak!(self.miner).reorg_stats().into())
+	}
+
+	fn stratum_workers(&self) -> Result<Vec<StratumWorker>, Error> {
+		Ok(take_weak!(self.miner).stratum_workers().into_iter().map(Into::into).collect())
+	}
+
 	fn signer_port(&self) -> Result<u16, Error> {
 		self.signer
 			.clone()
@@ -363,13 +440,26 @@ impl<C, M, S: ?Sized, U> Parity for ParityClient<C, M, S, U> where
 	}
 
 	fn chain_status(&self) -> Result<ChainStatus, Error> {
-		let chain_info = take_weak!(self.client).chain_info();
+		let client = take_weak!(self.client);
+		let chain_info = client.chain_info();
+		let sync_status = take_weak!(self.sync).status();
 
 		let gap = chain_info.ancient_block_number.map(|x| U256::from(x + 1))
 			.and_then(|first| chain_info.first_block_number.map(|last| (first, U256::from(last))));
 
+		let warp_restoration = match sync_status.is_snapshot_syncing() {
+			true => Some(WarpRestoration {
+				chunks_total: U256::from(sync_status.num_snapshot_chunks as u64),
+				chunks_done: U256::from(sync_status.snapshot_chunks_done as u64),
+			}),
+			false => None,
+		};
+
 		Ok(ChainStatus {
 			block_gap: gap.map(|(x, y)| (x.into(), y.into())),
+			earliest_state: Some(client.pruning_info().earliest_state.into()),
+			earliest_block: Some(chain_info.first_block_number.unwrap_or(0).into()),
+			warp_restoration: warp_restoration,
 		})
 	}
 
@@ -382,4 +472,81 @@ impl<C, M, S: ?Sized, U> Parity for ParityClient<C, M, S, U> where
 			capability: Capability::Full,
 		})
 	}
+
+	fn node_health(&self) -> Result<Health, Error> {
+		Ok(self.health.health())
+	}
+
+	fn execute_with_proof(&self, request: CallRequest, num: Trailing<BlockNumber>) -> Result<ExecutionProof, Error> {
+		let id = match num.0 {
+			BlockNumber::Pending => return Err(errors::invalid_params("block", "Execution proofs are not available for the pending block.")),
+			num => num.into(),
+		};
+		let signed = fake_sign::sign_call(&self.client, &self.miner, request.into())?;
+
+		let (executed, proof) = take_weak!(self.client).execute_with_proof(signed, id)
+			.map_err(errors::from_call_error)?;
+
+		Ok(ExecutionProof {
+			output: executed.output.into(),
+			gas_used: executed.gas_used.into(),
+			proof: proof.into_iter().map(|node| node.to_vec().into()).collect(),
+		})
+	}
+
+	fn get_proof(&self, address: H160, keys: Vec<H256>, num: Trailing<BlockNumber>) -> Result<AccountProof, Error> {
+		let client = take_weak!(self.client);
+		// all proofs are taken against the same block, even if a new one is imported meanwhile.
+		let id = match num.0 {
+			BlockNumber::Pending => return Err(errors::invalid_params("block", "Proofs are not available for the pending block.")),
+			num => BlockId::Hash(client.block_hash(num.into()).ok_or_else(errors::unknown_block)?),
+		};
+
+		let address: Address = address.into();
+		let address_hash = address.sha3();
+		let (account_proof, account) = client.prove_account(address_hash, id).ok_or_else(errors::state_pruned)?;
+
+		let storage_proof = keys.into_iter().map(|key| {
+			let key: util::H256 = key.into();
+			let (proof, value) = client.prove_storage(address_hash, key.sha3(), id).ok_or_else(errors::state_pruned)?;
+			Ok(StorageProof {
+				key: key.into(),
+				value: util::U256::from(&*value).into(),
+				proof: proof.into_iter().map(Into::into).collect(),
+			})
+		}).collect::<Result<Vec<_>, Error>>()?;
+
+		Ok(AccountProof {
+			address: address.into(),
+			account_proof: account_proof.into_iter().map(Into::into).collect(),
+			balance: account.balance.into(),
+			code_hash: account.code_hash.into(),
+			nonce: account.nonce.into(),
+			storage_hash: account.storage_root.into(),
+			storage_proof: storage_proof,
+		})
+	}
+
+	fn db_stats(&self) -> Result<DbStats, Error> {
+		Ok(take_weak!(self.client).db_stats().into())
+	}
+
+	fn db_statistics(&self) -> Result<DatabaseStatistics, Error> {
+		Ok(take_weak!(self.client).database_stats().into())
+	}
+
+	fn evm_stats(&self) -> Result<EvmStats, Error> {
+		Ok(take_weak!(self.client).evm_stats().into())
+	}
+
+	fn block_receipts(&self, num: BlockNumber) -> Result<Vec<Receipt>, Error> {
+		let id = match num {
+			BlockNumber::Pending => return Err(errors::invalid_params("block", "Receipts are not available for the pending block.")),
+			num => num.into(),
+		};
+
+		take_weak!(self.client).localized_block_receipts(id)
+			.map(|receipts| receipts.into_iter().map(Into::into).collect())
+			.ok_or_else(errors::unknown_block)
+	}
 }
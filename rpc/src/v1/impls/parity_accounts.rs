@@ -26,7 +26,7 @@ use ethcore::account_provider::AccountProvider;
 use jsonrpc_core::Error;
 use v1::helpers::errors;
 use v1::traits::ParityAccounts;
-use v1::types::{H160 as RpcH160, H256 as RpcH256, DappId, Derive, DeriveHierarchical, DeriveHash};
+use v1::types::{H160 as RpcH160, H256 as RpcH256, DappId, Derive, DeriveHierarchical, DeriveHash, VaultPolicy, SigningLogEntry};
 
 /// Account management (personal) rpc implementation.
 pub struct ParityAccountsClient {
@@ -291,6 +291,21 @@ impl ParityAccounts for ParityAccountsClient {
 			.map(|_| true)
 	}
 
+	fn get_vault_policy(&self, name: String) -> Result<Option<VaultPolicy>, Error> {
+		Ok(take_weak!(self.accounts).vault_policy(&name).map(Into::into))
+	}
+
+	fn set_vault_policy(&self, name: String, password: String, policy: Option<VaultPolicy>) -> Result<bool, Error> {
+		take_weak!(self.accounts)
+			.set_vault_policy(&name, &password, policy.map(Into::into))
+			.map_err(|e| errors::account("Could not update vault policy.", e))
+			.map(|_| true)
+	}
+
+	fn signing_history(&self, count: usize) -> Result<Vec<SigningLogEntry>, Error> {
+		Ok(take_weak!(self.accounts).signing_history(count).into_iter().map(Into::into).collect())
+	}
+
 	fn derive_key_index(&self, addr: RpcH160, password: String, derivation: DeriveHierarchical, save_as_account: bool) -> Result<RpcH160, Error> {
 		let addr: Address = addr.into();
 		take_weak!(self.accounts)
@@ -76,6 +76,12 @@ impl<C, M, U, F> ParitySet for ParitySetClient<C, M, U, F> where
 		Ok(true)
 	}
 
+	fn set_gas_limit_target(&self, target: U256) -> Result<bool, Error> {
+		let target: ::util::U256 = target.into();
+		take_weak!(self.miner).set_gas_limit_target(if target.is_zero() { None } else { Some(target) });
+		Ok(true)
+	}
+
 	fn set_extra_data(&self, extra_data: Bytes) -> Result<bool, Error> {
 		take_weak!(self.miner).set_extra_data(extra_data.into_vec());
 		Ok(true)
@@ -101,6 +107,11 @@ impl<C, M, U, F> ParitySet for ParitySetClient<C, M, U, F> where
 		Ok(true)
 	}
 
+	fn set_transaction_ordering_hint(&self, hashes: Vec<H256>) -> Result<bool, Error> {
+		take_weak!(self.miner).set_transaction_ordering_hint(hashes.into_iter().map(Into::into).collect());
+		Ok(true)
+	}
+
 	fn add_reserved_peer(&self, peer: String) -> Result<bool, Error> {
 		match take_weak!(self.net).add_reserved_peer(peer) {
 			Ok(()) => Ok(true),
@@ -179,4 +190,9 @@ impl<C, M, U, F> ParitySet for ParitySetClient<C, M, U, F> where
 
 		Ok(miner.remove_pending_transaction(&*client, &hash).map(Into::into))
 	}
+
+	fn backup_database(&self, path: String) -> Result<bool, Error> {
+		take_weak!(self.client).backup_database(path).map_err(errors::database_error)?;
+		Ok(true)
+	}
 }
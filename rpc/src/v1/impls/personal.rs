@@ -16,18 +16,19 @@
 
 //! Account management (personal) rpc implementation
 use std::sync::{Arc, Weak};
+use std::time::Duration;
 
-use ethcore::account_provider::AccountProvider;
+use ethcore::account_provider::{AccountProvider, SigningDecision};
 use ethcore::transaction::PendingTransaction;
 
 use util::{Address, U128, Uint, ToPretty};
 
 use futures::{future, Future, BoxFuture};
 use jsonrpc_core::Error;
-use v1::helpers::errors;
-use v1::helpers::dispatch::{Dispatcher, SignWith};
+use v1::helpers::{errors, ConfirmationPayload, FilledTransactionRequest};
+use v1::helpers::dispatch::{self, Dispatcher, SignWith};
 use v1::traits::Personal;
-use v1::types::{H160 as RpcH160, H256 as RpcH256, U128 as RpcU128, TransactionRequest};
+use v1::types::{H160 as RpcH160, H256 as RpcH256, U128 as RpcU128, TransactionRequest, RichRawTransaction as RpcRichRawTransaction};
 use v1::metadata::Metadata;
 
 /// Account management (personal) rpc implementation.
@@ -48,6 +49,61 @@ impl<D: Dispatcher> PersonalClient<D> {
 	}
 }
 
+impl<D: Dispatcher + 'static> PersonalClient<D> {
+	fn do_sign_transaction(
+		&self,
+		meta: Metadata,
+		request: TransactionRequest,
+		password: String,
+		into_payload: fn(FilledTransactionRequest) -> ConfirmationPayload,
+		method: &str,
+	) -> BoxFuture<(PendingTransaction, D), Error> {
+		let dispatcher = self.dispatcher.clone();
+		let accounts = take_weakf!(self.accounts);
+		let source = meta.request_source(method);
+
+		let default = match request.from.as_ref() {
+			Some(account) => Ok(account.clone().into()),
+			None => accounts
+				.dapp_default_address(meta.dapp_id().into())
+				.map_err(|e| errors::account("Cannot find default account.", e)),
+		};
+
+		let default = match default {
+			Ok(default) => default,
+			Err(e) => return future::err(e).boxed(),
+		};
+
+		dispatcher.fill_optional_fields(request.into(), default)
+			.and_then(move |filled| {
+				// session token can be used instead of the password
+				let session_password = accounts.session_password(&filled.from, &password);
+				let password = session_password.unwrap_or(password);
+				let condition = filled.condition.clone().map(Into::into);
+				let from = filled.from;
+				if let Err(e) = dispatch::check_vault_policy(&accounts, &into_payload(filled.clone())) {
+					dispatch::record_signing(&accounts, &source, from, None, SigningDecision::Rejected);
+					return future::err(e).boxed();
+				}
+
+				let log_accounts = accounts.clone();
+				dispatcher.sign(accounts, filled, SignWith::Password(password))
+					.then(move |result| {
+						match result {
+							Ok(ref tx) => dispatch::record_signing(&log_accounts, &source, from, Some(tx.hash()), SigningDecision::Signed),
+							Err(_) => dispatch::record_signing(&log_accounts, &source, from, None, SigningDecision::Failed),
+						}
+						result
+					})
+					.map(|tx| tx.into_value())
+					.map(move |tx| PendingTransaction::new(tx, condition))
+					.map(move |tx| (tx, dispatcher))
+					.boxed()
+			})
+			.boxed()
+	}
+}
+
 impl<D: Dispatcher + 'static> Personal for PersonalClient<D> {
 	type Metadata = Metadata;
 
@@ -94,30 +150,36 @@ impl<D: Dispatcher + 'static> Personal for PersonalClient<D> {
 		}
 	}
 
-	fn send_transaction(&self, meta: Metadata, request: TransactionRequest, password: String) -> BoxFuture<RpcH256, Error> {
-		let dispatcher = self.dispatcher.clone();
-		let accounts = take_weakf!(self.accounts);
-
-		let default = match request.from.as_ref() {
-			Some(account) => Ok(account.clone().into()),
-			None => accounts
-				.dapp_default_address(meta.dapp_id().into())
-				.map_err(|e| errors::account("Cannot find default account.", e)),
+	fn unlock_account_with_token(&self, account: RpcH160, account_pass: String, ttl: RpcU128, max_uses: Option<RpcU128>) -> Result<String, Error> {
+		let max_value = U128::from(u32::max_value());
+		let ttl: U128 = ttl.into();
+		if ttl.is_zero() || ttl > max_value {
+			return Err(errors::invalid_params("TTL", "Expected a positive number of seconds"));
+		}
+		let max_uses = match max_uses {
+			None => None,
+			Some(uses) => {
+				let uses: U128 = uses.into();
+				if uses.is_zero() || uses > max_value {
+					return Err(errors::invalid_params("Uses", "Expected a positive number"));
+				}
+				Some(uses.low_u64() as u32)
+			},
 		};
 
-		let default = match default {
-			Ok(default) => default,
-			Err(e) => return future::err(e).boxed(),
-		};
+		take_weak!(self.accounts)
+			.unlock_account_with_token(account.into(), account_pass, Duration::from_secs(ttl.low_u64()), max_uses)
+			.map_err(|e| errors::account("Unable to unlock the account.", e))
+	}
 
-		dispatcher.fill_optional_fields(request.into(), default)
-			.and_then(move |filled| {
-				let condition = filled.condition.clone().map(Into::into);
-				dispatcher.sign(accounts, filled, SignWith::Password(password))
-					.map(|tx| tx.into_value())
-					.map(move |tx| PendingTransaction::new(tx, condition))
-					.map(move |tx| (tx, dispatcher))
-			})
+	fn sign_transaction(&self, meta: Metadata, request: TransactionRequest, password: String) -> BoxFuture<RpcRichRawTransaction, Error> {
+		self.do_sign_transaction(meta, request, password, ConfirmationPayload::SignTransaction, "personal_signTransaction")
+			.map(|(pending_tx, _)| pending_tx.transaction.into())
+			.boxed()
+	}
+
+	fn send_transaction(&self, meta: Metadata, request: TransactionRequest, password: String) -> BoxFuture<RpcH256, Error> {
+		self.do_sign_transaction(meta, request, password, ConfirmationPayload::SendTransaction, "personal_sendTransaction")
 			.and_then(|(pending_tx, dispatcher)| {
 				let network_id = pending_tx.network_id();
 				trace!(target: "miner", "send_transaction: dispatching tx: {} for network ID {:?}",
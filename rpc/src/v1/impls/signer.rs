@@ -19,12 +19,12 @@
 use std::sync::{Arc, Weak};
 
 use rlp::UntrustedRlp;
-use ethcore::account_provider::AccountProvider;
+use ethcore::account_provider::{AccountProvider, SigningDecision};
 use ethcore::transaction::{SignedTransaction, PendingTransaction};
 use futures::{future, BoxFuture, Future, IntoFuture};
 
 use jsonrpc_core::Error;
-use v1::helpers::{errors, SignerService, SigningQueue, ConfirmationPayload};
+use v1::helpers::{errors, SignerService, SigningQueue, ConfirmationPayload, RequestSource};
 use v1::helpers::dispatch::{self, Dispatcher, WithToken};
 use v1::traits::Signer;
 use v1::types::{TransactionModification, ConfirmationRequest, ConfirmationResponse, ConfirmationResponseWithToken, U256, Bytes};
@@ -52,7 +52,7 @@ impl<D: Dispatcher + 'static> SignerClient<D> {
 	}
 
 	fn confirm_internal<F, T>(&self, id: U256, modification: TransactionModification, f: F) -> BoxFuture<WithToken<ConfirmationResponse>, Error> where
-		F: FnOnce(D, Arc<AccountProvider>, ConfirmationPayload) -> T,
+		F: FnOnce(D, Arc<AccountProvider>, ConfirmationPayload, RequestSource) -> T,
 		T: IntoFuture<Item=WithToken<ConfirmationResponse>, Error=Error>,
 		T::Future: Send + 'static
 	{
@@ -87,7 +87,7 @@ impl<D: Dispatcher + 'static> SignerClient<D> {
 					request.condition = condition.clone().map(Into::into);
 				}
 			}
-			let fut = f(dispatcher, accounts, payload);
+			let fut = f(dispatcher, accounts, payload, confirmation.source());
 			fut.into_future().then(move |result| {
 				// Execute
 				if let Ok(ref response) = result {
@@ -118,16 +118,16 @@ impl<D: Dispatcher + 'static> Signer for SignerClient<D> {
 	fn confirm_request(&self, id: U256, modification: TransactionModification, pass: String)
 		-> BoxFuture<ConfirmationResponse, Error>
 	{
-		self.confirm_internal(id, modification, move |dis, accounts, payload| {
-			dispatch::execute(dis, accounts, payload, dispatch::SignWith::Password(pass))
+		self.confirm_internal(id, modification, move |dis, accounts, payload, source| {
+			dispatch::execute(dis, accounts, payload, dispatch::SignWith::Password(pass), source)
 		}).map(|v| v.into_value()).boxed()
 	}
 
 	fn confirm_request_with_token(&self, id: U256, modification: TransactionModification, token: String)
 		-> BoxFuture<ConfirmationResponseWithToken, Error>
 	{
-		self.confirm_internal(id, modification, move |dis, accounts, payload| {
-			dispatch::execute(dis, accounts, payload, dispatch::SignWith::Token(token))
+		self.confirm_internal(id, modification, move |dis, accounts, payload, source| {
+			dispatch::execute(dis, accounts, payload, dispatch::SignWith::Token(token), source)
 		}).and_then(|v| match v {
 			WithToken::No(_) => Err(errors::internal("Unexpected response without token.", "")),
 			WithToken::Yes(response, token) => Ok(ConfirmationResponseWithToken {
@@ -142,6 +142,8 @@ impl<D: Dispatcher + 'static> Signer for SignerClient<D> {
 		let signer = take_weak!(self.signer);
 
 		signer.peek(&id).map(|confirmation| {
+			let source = confirmation.source();
+			let (account, _) = dispatch::signing_subject(&confirmation.payload);
 			let result = match confirmation.payload {
 				ConfirmationPayload::SendTransaction(request) => {
 					let signed_transaction = UntrustedRlp::new(&bytes.0).as_val().map_err(errors::from_rlp_error)?;
@@ -179,6 +181,9 @@ impl<D: Dispatcher + 'static> Signer for SignerClient<D> {
 				_ => Err(errors::unimplemented(Some("Non-transaction requests does not support RAW signing yet.".into()))),
 			};
 			if let Ok(ref response) = result {
+				if let Some(accounts) = self.accounts.upgrade() {
+					dispatch::record_signing(&accounts, &source, account, dispatch::response_hash(response), SigningDecision::Signed);
+				}
 				signer.request_confirmed(id, Ok(response.clone()));
 			}
 			result
@@ -189,6 +194,10 @@ impl<D: Dispatcher + 'static> Signer for SignerClient<D> {
 		let signer = take_weak!(self.signer);
 
 		let res = signer.request_rejected(id.into());
+		if let (Some(request), Some(accounts)) = (res.as_ref(), self.accounts.upgrade()) {
+			let (account, hash) = dispatch::signing_subject(&request.payload);
+			dispatch::record_signing(&accounts, &request.source(), account, hash, SigningDecision::Rejected);
+		}
 		Ok(res.is_some())
 	}
 
@@ -25,8 +25,8 @@ use ethcore::account_provider::AccountProvider;
 use futures::{future, BoxFuture, Future};
 use jsonrpc_core::Error;
 use v1::helpers::{
-	errors, oneshot,
-	DefaultAccount,
+	errors, oneshot, hash_typed_data,
+	DefaultAccount, RequestSource,
 	SIGNING_QUEUE_LIMIT, SigningQueue, ConfirmationPromise, ConfirmationResult, SignerService
 };
 use v1::helpers::dispatch::{self, Dispatcher};
@@ -37,9 +37,10 @@ use v1::types::{
 	Either as RpcEither,
 	RichRawTransaction as RpcRichRawTransaction,
 	TransactionRequest as RpcTransactionRequest,
+	TypedData as RpcTypedData,
+	SignTypedDataRequest as RpcSignTypedDataRequest,
 	ConfirmationPayload as RpcConfirmationPayload,
 	ConfirmationResponse as RpcConfirmationResponse,
-	Origin,
 };
 
 /// After 60s entries that are not queried with `check_request` will get garbage collected.
@@ -100,7 +101,7 @@ impl<D: Dispatcher + 'static> SigningQueueClient<D> {
 		}
 	}
 
-	fn dispatch(&self, payload: RpcConfirmationPayload, default_account: DefaultAccount, origin: Origin) -> BoxFuture<DispatchResult, Error> {
+	fn dispatch(&self, payload: RpcConfirmationPayload, default_account: DefaultAccount, source: RequestSource) -> BoxFuture<DispatchResult, Error> {
 		let accounts = take_weakf!(self.accounts);
 		let default_account = match default_account {
 			DefaultAccount::Provided(acc) => acc,
@@ -113,13 +114,13 @@ impl<D: Dispatcher + 'static> SigningQueueClient<D> {
 			.and_then(move |payload| {
 				let sender = payload.sender();
 				if accounts.is_unlocked(sender) {
-					dispatch::execute(dispatcher, accounts, payload, dispatch::SignWith::Nothing)
+					dispatch::execute(dispatcher, accounts, payload, dispatch::SignWith::Nothing, source)
 						.map(|v| v.into_value())
 						.map(DispatchResult::Value)
 						.boxed()
 				} else {
 					future::done(
-						signer.add_request(payload, origin)
+						signer.add_request(payload, source)
 							.map(DispatchResult::Promise)
 							.map_err(|_| errors::request_rejected_limit())
 					).boxed()
@@ -137,7 +138,7 @@ impl<D: Dispatcher + 'static> ParitySigning for SigningQueueClient<D> {
 		self.dispatch(
 			RpcConfirmationPayload::Signature((address.clone(), data).into()),
 			DefaultAccount::Provided(address.into()),
-			meta.origin
+			meta.request_source("parity_postSign")
 		).map(move |result| match result {
 			DispatchResult::Value(v) => RpcEither::Or(v),
 			DispatchResult::Promise(promise) => {
@@ -154,7 +155,7 @@ impl<D: Dispatcher + 'static> ParitySigning for SigningQueueClient<D> {
 
 	fn post_transaction(&self, meta: Metadata, request: RpcTransactionRequest) -> BoxFuture<RpcEither<RpcU256, RpcConfirmationResponse>, Error> {
 		let pending = self.pending.clone();
-		self.dispatch(RpcConfirmationPayload::SendTransaction(request), meta.dapp_id().into(), meta.origin)
+		self.dispatch(RpcConfirmationPayload::SendTransaction(request), meta.dapp_id().into(), meta.request_source("parity_postTransaction"))
 			.map(move |result| match result {
 				DispatchResult::Value(v) => RpcEither::Or(v),
 				DispatchResult::Promise(promise) => {
@@ -185,7 +186,7 @@ impl<D: Dispatcher + 'static> ParitySigning for SigningQueueClient<D> {
 		let res = self.dispatch(
 			RpcConfirmationPayload::Decrypt((address.clone(), data).into()),
 			address.into(),
-			meta.origin,
+			meta.request_source("parity_decryptMessage"),
 		);
 
 		let (ready, p) = oneshot::oneshot();
@@ -213,7 +214,38 @@ impl<D: Dispatcher + 'static> EthSigning for SigningQueueClient<D> {
 		let res = self.dispatch(
 			RpcConfirmationPayload::Signature((address.clone(), data).into()),
 			address.into(),
-			meta.origin,
+			meta.request_source("eth_sign"),
+		);
+
+		let (ready, p) = oneshot::oneshot();
+
+		res.then(move |res| {
+			handle_dispatch(res, move |response| {
+				match response {
+					Ok(RpcConfirmationResponse::Signature(sig)) => ready.send(Ok(sig)),
+					Err(e) => ready.send(Err(e)),
+					e => ready.send(Err(errors::internal("Unexpected result.", e))),
+				}
+			});
+
+			p
+		}).boxed()
+	}
+
+	fn sign_typed_data(&self, meta: Metadata, address: RpcH160, data: RpcTypedData) -> BoxFuture<RpcH520, Error> {
+		let (hash, fields) = match hash_typed_data(&data) {
+			Ok(hashed) => hashed,
+			Err(e) => return future::err(e).boxed(),
+		};
+		let res = self.dispatch(
+			RpcConfirmationPayload::SignTypedData(RpcSignTypedDataRequest {
+				address: address.clone(),
+				hash: hash.into(),
+				primary_type: data.primary_type,
+				fields: fields,
+			}),
+			address.into(),
+			meta.request_source("personal_signTypedData"),
 		);
 
 		let (ready, p) = oneshot::oneshot();
@@ -235,7 +267,7 @@ impl<D: Dispatcher + 'static> EthSigning for SigningQueueClient<D> {
 		let res = self.dispatch(
 			RpcConfirmationPayload::SendTransaction(request),
 			meta.dapp_id().into(),
-			meta.origin,
+			meta.request_source("eth_sendTransaction"),
 		);
 
 		let (ready, p) = oneshot::oneshot();
@@ -257,7 +289,7 @@ impl<D: Dispatcher + 'static> EthSigning for SigningQueueClient<D> {
 		let res = self.dispatch(
 			RpcConfirmationPayload::SignTransaction(request),
 			meta.dapp_id().into(),
-			meta.origin,
+			meta.request_source("eth_signTransaction"),
 		);
 
 		let (ready, p) = oneshot::oneshot();
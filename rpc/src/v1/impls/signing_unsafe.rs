@@ -22,7 +22,7 @@ use ethcore::account_provider::AccountProvider;
 
 use futures::{future, BoxFuture, Future};
 use jsonrpc_core::Error;
-use v1::helpers::{errors, DefaultAccount};
+use v1::helpers::{errors, hash_typed_data, DefaultAccount, RequestSource};
 use v1::helpers::dispatch::{self, Dispatcher};
 use v1::metadata::Metadata;
 use v1::traits::{EthSigning, ParitySigning};
@@ -32,6 +32,8 @@ use v1::types::{
 	Either as RpcEither,
 	RichRawTransaction as RpcRichRawTransaction,
 	TransactionRequest as RpcTransactionRequest,
+	TypedData as RpcTypedData,
+	SignTypedDataRequest as RpcSignTypedDataRequest,
 	ConfirmationPayload as RpcConfirmationPayload,
 	ConfirmationResponse as RpcConfirmationResponse,
 };
@@ -51,7 +53,7 @@ impl<D: Dispatcher + 'static> SigningUnsafeClient<D> {
 		}
 	}
 
-	fn handle(&self, payload: RpcConfirmationPayload, account: DefaultAccount) -> BoxFuture<RpcConfirmationResponse, Error> {
+	fn handle(&self, payload: RpcConfirmationPayload, account: DefaultAccount, source: RequestSource) -> BoxFuture<RpcConfirmationResponse, Error> {
 		let accounts = take_weakf!(self.accounts);
 		let default = match account {
 			DefaultAccount::Provided(acc) => acc,
@@ -61,7 +63,7 @@ impl<D: Dispatcher + 'static> SigningUnsafeClient<D> {
 		let dis = self.dispatcher.clone();
 		dispatch::from_rpc(payload, default, &dis)
 			.and_then(move |payload| {
-				dispatch::execute(dis, accounts, payload, dispatch::SignWith::Nothing)
+				dispatch::execute(dis, accounts, payload, dispatch::SignWith::Nothing, source)
 			})
 			.map(|v| v.into_value())
 			.boxed()
@@ -72,8 +74,28 @@ impl<D: Dispatcher + 'static> EthSigning for SigningUnsafeClient<D>
 {
 	type Metadata = Metadata;
 
-	fn sign(&self, _: Metadata, address: RpcH160, data: RpcBytes) -> BoxFuture<RpcH520, Error> {
-		self.handle(RpcConfirmationPayload::Signature((address.clone(), data).into()), address.into())
+	fn sign(&self, meta: Metadata, address: RpcH160, data: RpcBytes) -> BoxFuture<RpcH520, Error> {
+		self.handle(RpcConfirmationPayload::Signature((address.clone(), data).into()), address.into(), meta.request_source("eth_sign"))
+			.then(|res| match res {
+				Ok(RpcConfirmationResponse::Signature(signature)) => Ok(signature),
+				Err(e) => Err(e),
+				e => Err(errors::internal("Unexpected result", e)),
+			})
+			.boxed()
+	}
+
+	fn sign_typed_data(&self, meta: Metadata, address: RpcH160, data: RpcTypedData) -> BoxFuture<RpcH520, Error> {
+		let (hash, fields) = match hash_typed_data(&data) {
+			Ok(hashed) => hashed,
+			Err(e) => return future::err(e).boxed(),
+		};
+		let request = RpcSignTypedDataRequest {
+			address: address.clone(),
+			hash: hash.into(),
+			primary_type: data.primary_type,
+			fields: fields,
+		};
+		self.handle(RpcConfirmationPayload::SignTypedData(request), address.into(), meta.request_source("personal_signTypedData"))
 			.then(|res| match res {
 				Ok(RpcConfirmationResponse::Signature(signature)) => Ok(signature),
 				Err(e) => Err(e),
@@ -83,7 +105,7 @@ impl<D: Dispatcher + 'static> EthSigning for SigningUnsafeClient<D>
 	}
 
 	fn send_transaction(&self, meta: Metadata, request: RpcTransactionRequest) -> BoxFuture<RpcH256, Error> {
-		self.handle(RpcConfirmationPayload::SendTransaction(request), meta.dapp_id().into())
+		self.handle(RpcConfirmationPayload::SendTransaction(request), meta.dapp_id().into(), meta.request_source("eth_sendTransaction"))
 			.then(|res| match res {
 				Ok(RpcConfirmationResponse::SendTransaction(hash)) => Ok(hash),
 				Err(e) => Err(e),
@@ -93,7 +115,7 @@ impl<D: Dispatcher + 'static> EthSigning for SigningUnsafeClient<D>
 	}
 
 	fn sign_transaction(&self, meta: Metadata, request: RpcTransactionRequest) -> BoxFuture<RpcRichRawTransaction, Error> {
-		self.handle(RpcConfirmationPayload::SignTransaction(request), meta.dapp_id().into())
+		self.handle(RpcConfirmationPayload::SignTransaction(request), meta.dapp_id().into(), meta.request_source("eth_signTransaction"))
 			.then(|res| match res {
 				Ok(RpcConfirmationResponse::SignTransaction(tx)) => Ok(tx),
 				Err(e) => Err(e),
@@ -106,8 +128,8 @@ impl<D: Dispatcher + 'static> EthSigning for SigningUnsafeClient<D>
 impl<D: Dispatcher + 'static> ParitySigning for SigningUnsafeClient<D> {
 	type Metadata = Metadata;
 
-	fn decrypt_message(&self, _: Metadata, address: RpcH160, data: RpcBytes) -> BoxFuture<RpcBytes, Error> {
-		self.handle(RpcConfirmationPayload::Decrypt((address.clone(), data).into()), address.into())
+	fn decrypt_message(&self, meta: Metadata, address: RpcH160, data: RpcBytes) -> BoxFuture<RpcBytes, Error> {
+		self.handle(RpcConfirmationPayload::Decrypt((address.clone(), data).into()), address.into(), meta.request_source("parity_decryptMessage"))
 			.then(|res| match res {
 				Ok(RpcConfirmationResponse::Decrypt(data)) => Ok(data),
 				Err(e) => Err(e),
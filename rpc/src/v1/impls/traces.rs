@@ -16,24 +16,30 @@
 
 //! Traces api implementation.
 
+use std::mem;
 use std::sync::{Weak, Arc};
 
 use rlp::UntrustedRlp;
 use ethcore::client::{MiningBlockChainClient, CallAnalytics, TransactionId, TraceId};
 use ethcore::miner::MinerService;
+use ethcore::trace::StepFilter;
 use ethcore::transaction::SignedTransaction;
+use util::Mutex;
 
 use jsonrpc_core::Error;
 use jsonrpc_macros::Trailing;
 use v1::traits::Traces;
-use v1::helpers::{errors, fake_sign};
-use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, Index, CallRequest, Bytes, TraceResults, H256};
+use v1::helpers::{errors, fake_sign, TracePages};
+use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, Index, CallRequest, Bytes, TraceResults, TracePage, GasProfileResults, StructLogConfig, StructLogResults, StateOverride, H256, U256};
 
 fn to_call_analytics(flags: Vec<String>) -> CallAnalytics {
 	CallAnalytics {
 		transaction_tracing: flags.contains(&("trace".to_owned())),
 		vm_tracing: flags.contains(&("vmTrace".to_owned())),
 		state_diffing: flags.contains(&("stateDiff".to_owned())),
+		gas_profiling: false,
+		struct_logging: None,
+		state_override: Default::default(),
 	}
 }
 
@@ -41,14 +47,16 @@ fn to_call_analytics(flags: Vec<String>) -> CallAnalytics {
 pub struct TracesClient<C, M> {
 	client: Weak<C>,
 	miner: Weak<M>,
+	pages: Mutex<TracePages>,
 }
 
 impl<C, M> TracesClient<C, M> {
-	/// Creates new Traces client.
-	pub fn new(client: &Arc<C>, miner: &Arc<M>) -> Self {
+	/// Creates new Traces client, returning replayed traces in pages of at most `page_size`.
+	pub fn new(client: &Arc<C>, miner: &Arc<M>, page_size: usize) -> Self {
 		TracesClient {
 			client: Arc::downgrade(client),
 			miner: Arc::downgrade(miner),
+			pages: Mutex::new(TracePages::new(page_size)),
 		}
 	}
 }
@@ -90,6 +98,42 @@ impl<C, M> Traces for TracesClient<C, M> where C: MiningBlockChainClient + 'stat
 			.map_err(errors::from_call_error)
 	}
 
+	fn call_with_overrides(&self, request: CallRequest, flags: Vec<String>, block: BlockNumber, overrides: StateOverride) -> Result<TraceResults, Error> {
+		let request = CallRequest::into(request);
+		let signed = fake_sign::sign_call(&self.client, &self.miner, request)?;
+		let analytics = CallAnalytics { state_override: overrides.into(), ..to_call_analytics(flags) };
+
+		take_weak!(self.client).call(&signed, block.into(), analytics)
+			.map(TraceResults::from)
+			.map_err(errors::from_call_error)
+	}
+
+	fn call_many(&self, requests: Vec<(CallRequest, Vec<String>)>, block: Trailing<BlockNumber>) -> Result<Vec<TraceResults>, Error> {
+		let block = block.0;
+
+		let mut transactions = Vec::with_capacity(requests.len());
+		for (request, flags) in requests {
+			let signed = fake_sign::sign_call(&self.client, &self.miner, request.into())?;
+			transactions.push((signed, to_call_analytics(flags)));
+		}
+
+		take_weak!(self.client).call_many(transactions, block.into())
+			.map(|results| results.into_iter().map(TraceResults::from).collect())
+			.map_err(errors::from_call_error)
+	}
+
+	fn call_with_gas_profile(&self, request: CallRequest, block: Trailing<BlockNumber>) -> Result<GasProfileResults, Error> {
+		let block = block.0;
+
+		let request = CallRequest::into(request);
+		let signed = fake_sign::sign_call(&self.client, &self.miner, request)?;
+		let analytics = CallAnalytics { gas_profiling: true, ..Default::default() };
+
+		take_weak!(self.client).call(&signed, block.into(), analytics)
+			.map(GasProfileResults::from)
+			.map_err(errors::from_call_error)
+	}
+
 	fn raw_transaction(&self, raw_transaction: Bytes, flags: Vec<String>, block: Trailing<BlockNumber>) -> Result<TraceResults, Error> {
 		let block = block.0;
 
@@ -102,8 +146,41 @@ impl<C, M> Traces for TracesClient<C, M> where C: MiningBlockChainClient + 'stat
 	}
 
 	fn replay_transaction(&self, transaction_hash: H256, flags: Vec<String>) -> Result<TraceResults, Error> {
-		take_weak!(self.client).replay(TransactionId::Hash(transaction_hash.into()), to_call_analytics(flags))
-			.map(TraceResults::from)
+		let mut executed = take_weak!(self.client).replay(TransactionId::Hash(transaction_hash.into()), to_call_analytics(flags))
+			.map_err(errors::from_call_error)?;
+
+		let (page, continuation) = self.pages.lock().paginate(mem::replace(&mut executed.trace, Vec::new()));
+		executed.trace = page;
+		Ok(TraceResults {
+			continuation: continuation.map(Into::into),
+			..executed.into()
+		})
+	}
+
+	fn continue_trace(&self, token: U256) -> Result<TracePage, Error> {
+		let token: ::util::U256 = token.into();
+		if token > ::util::U256::from(u64::max_value()) {
+			return Err(errors::invalid_params("token", "Unknown continuation token"));
+		}
+
+		match self.pages.lock().next(token.low_u64()) {
+			Some((page, continuation)) => Ok(TracePage {
+				trace: page.into_iter().map(Into::into).collect(),
+				continuation: continuation.map(Into::into),
+			}),
+			None => Err(errors::invalid_params("token", "Unknown or expired continuation token")),
+		}
+	}
+
+	fn debug_trace_transaction(&self, transaction_hash: H256, config: Trailing<StructLogConfig>) -> Result<StructLogResults, Error> {
+		let config = config.0;
+		if let Some(ref tracer) = config.tracer {
+			tracer.parse::<StepFilter>().map_err(|e| errors::invalid_params("tracer", e))?;
+		}
+		let analytics = CallAnalytics { struct_logging: Some(config.into()), ..Default::default() };
+
+		take_weak!(self.client).replay(TransactionId::Hash(transaction_hash.into()), analytics)
+			.map(StructLogResults::from)
 			.map_err(errors::from_call_error)
 	}
 }
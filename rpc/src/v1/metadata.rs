@@ -14,15 +14,26 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::net::IpAddr;
+use std::sync::Arc;
+
 use jsonrpc_core;
+use jsonrpc_pubsub::{Session, PubSubMetadata};
 
+use v1::helpers::RequestSource;
 use v1::types::{DappId, Origin};
 
 /// RPC methods metadata.
-#[derive(Clone, Default, Debug, PartialEq)]
+#[derive(Clone, Default, Debug)]
 pub struct Metadata {
 	/// Request origin
 	pub origin: Origin,
+	/// Request PubSub Session
+	pub session: Option<Arc<Session>>,
+	/// API key presented by the client
+	pub api_key: Option<String>,
+	/// IP address of the client, if known
+	pub ip: Option<IpAddr>,
 }
 
 impl Metadata {
@@ -33,7 +44,22 @@ impl Metadata {
 			_ => DappId::default(),
 		}
 	}
+
+	/// Details of this call to the given RPC method.
+	pub fn request_source(&self, method: &str) -> RequestSource {
+		RequestSource {
+			origin: self.origin.clone(),
+			method: method.into(),
+			ip: self.ip,
+		}
+	}
 }
 
 impl jsonrpc_core::Metadata for Metadata {}
 
+impl PubSubMetadata for Metadata {
+	fn session(&self) -> Option<Arc<Session>> {
+		self.session.clone()
+	}
+}
+
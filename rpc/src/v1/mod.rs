@@ -58,8 +58,8 @@ pub mod traits;
 pub mod tests;
 pub mod types;
 
-pub use self::traits::{Web3, Eth, EthFilter, EthSigning, Net, Parity, ParityAccounts, ParitySet, ParitySigning, Signer, Personal, Traces, Rpc};
+pub use self::traits::{Web3, Eth, EthFilter, EthPubSub, EthSigning, Net, Parity, ParityAccounts, ParitySet, ParitySigning, Signer, Personal, Traces, Rpc};
 pub use self::impls::*;
-pub use self::helpers::{SigningQueue, SignerService, ConfirmationsQueue, NetworkSettings, block_import, informant, dispatch};
+pub use self::helpers::{SigningQueue, SignerService, ConfirmationsQueue, NetworkSettings, DEFAULT_TRACE_PAGE_SIZE, DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION, acl, block_import, informant, limits, dispatch, node_health};
 pub use self::metadata::Metadata;
 pub use self::types::Origin;
@@ -17,6 +17,7 @@
 //! rpc integration tests.
 use std::sync::Arc;
 use std::time::Duration;
+use std::collections::HashSet;
 
 use ethcore::client::{BlockChainClient, Client, ClientConfig};
 use ethcore::ids::BlockId;
@@ -24,7 +25,7 @@ use ethcore::spec::{Genesis, Spec};
 use ethcore::block::Block;
 use ethcore::views::BlockView;
 use ethcore::ethereum;
-use ethcore::miner::{MinerOptions, Banning, GasPricer, MinerService, ExternalMiner, Miner, PendingSet, PrioritizationStrategy, GasLimit};
+use ethcore::miner::{MinerOptions, Banning, GasPricer, MinerService, ExternalMiner, Miner, PendingSet, PrioritizationStrategy, GasLimit, BlockOrdering, UncleStrategy};
 use ethcore::account_provider::AccountProvider;
 use ethjson::blockchain::BlockChain;
 use io::IoChannel;
@@ -60,14 +61,20 @@ fn miner_service(spec: &Spec, accounts: Arc<AccountProvider>) -> Arc<Miner> {
 			tx_queue_size: 1024,
 			tx_gas_limit: !U256::zero(),
 			tx_queue_strategy: PrioritizationStrategy::GasPriceOnly,
+			tx_queue_price_bump: 0,
+			tx_queue_dynamic_gas_price: false,
 			tx_queue_gas_limit: GasLimit::None,
 			tx_queue_banning: Banning::Disabled,
+			tx_queue_ban_invalid: 0,
 			pending_set: PendingSet::SealingOrElseQueue,
 			reseal_min_period: Duration::from_secs(0),
 			reseal_max_period: Duration::from_secs(120),
 			work_queue_size: 50,
 			enable_resubmission: true,
 			refuse_service_transactions: false,
+			block_ordering: BlockOrdering::GasPrice,
+			uncle_strategy: UncleStrategy::All,
+			priority_senders: HashSet::new(),
 		},
 		GasPricer::new_fixed(20_000_000_000u64.into()),
 		&spec,
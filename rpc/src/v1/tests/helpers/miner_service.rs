@@ -25,7 +25,7 @@ use ethcore::block::{ClosedBlock, IsBlock};
 use ethcore::header::BlockNumber;
 use ethcore::transaction::{UnverifiedTransaction, SignedTransaction, PendingTransaction};
 use ethcore::receipt::{Receipt, RichReceipt};
-use ethcore::miner::{MinerService, MinerStatus, TransactionImportResult, LocalTransactionStatus};
+use ethcore::miner::{MinerService, MinerStatus, TransactionImportResult, LocalTransactionStatus, StratumWorkerInfo, MineStats, ReorgStats};
 use ethcore::account_provider::SignError as AccountError;
 
 /// Test miner service.
@@ -36,8 +36,20 @@ pub struct TestMinerService {
 	pub latest_closed_block: Mutex<Option<ClosedBlock>>,
 	/// Pre-existed pending transactions
 	pub pending_transactions: Mutex<HashMap<H256, SignedTransaction>>,
+	/// Pre-existed future transactions
+	pub future_transactions: Mutex<HashMap<H256, SignedTransaction>>,
 	/// Pre-existed local transactions
 	pub local_transactions: Mutex<BTreeMap<H256, LocalTransactionStatus>>,
+	/// Banned transaction senders
+	pub banned_senders: Mutex<Vec<Address>>,
+	/// Statistics of mined blocks
+	pub mine_stats: Mutex<MineStats>,
+	/// Statistics of transactions re-imported after reorgs
+	pub reorg_stats: Mutex<ReorgStats>,
+	/// Workers connected to stratum
+	pub stratum_workers: Mutex<Vec<StratumWorkerInfo>>,
+	/// Block ordering hint
+	pub ordering_hint: Mutex<Vec<H256>>,
 	/// Pre-existed pending receipts
 	pub pending_receipts: Mutex<BTreeMap<H256, Receipt>>,
 	/// Last nonces.
@@ -47,6 +59,7 @@ pub struct TestMinerService {
 
 	min_gas_price: RwLock<U256>,
 	gas_range_target: RwLock<(U256, U256)>,
+	gas_limit_target: RwLock<Option<U256>>,
 	author: RwLock<Address>,
 	extra_data: RwLock<Bytes>,
 	limit: RwLock<usize>,
@@ -59,11 +72,18 @@ impl Default for TestMinerService {
 			imported_transactions: Mutex::new(Vec::new()),
 			latest_closed_block: Mutex::new(None),
 			pending_transactions: Mutex::new(HashMap::new()),
+			future_transactions: Mutex::new(HashMap::new()),
 			local_transactions: Mutex::new(BTreeMap::new()),
+			banned_senders: Mutex::new(Vec::new()),
+			mine_stats: Mutex::new(MineStats::default()),
+			reorg_stats: Mutex::new(ReorgStats::default()),
+			stratum_workers: Mutex::new(Vec::new()),
+			ordering_hint: Mutex::new(Vec::new()),
 			pending_receipts: Mutex::new(BTreeMap::new()),
 			last_nonces: RwLock::new(HashMap::new()),
 			min_gas_price: RwLock::new(U256::from(20_000_000)),
 			gas_range_target: RwLock::new((U256::from(12345), U256::from(54321))),
+			gas_limit_target: RwLock::new(None),
 			author: RwLock::new(Address::zero()),
 			password: RwLock::new(String::new()),
 			extra_data: RwLock::new(vec![1, 2, 3, 4]),
@@ -124,6 +144,14 @@ impl MinerService for TestMinerService {
 		self.gas_range_target.write().1 = target;
 	}
 
+	fn gas_limit_target(&self) -> Option<U256> {
+		*self.gas_limit_target.read()
+	}
+
+	fn set_gas_limit_target(&self, target: Option<U256>) {
+		*self.gas_limit_target.write() = target;
+	}
+
 	fn set_minimal_gas_price(&self, min_gas_price: U256) {
 		*self.min_gas_price.write() = min_gas_price;
 	}
@@ -136,6 +164,10 @@ impl MinerService for TestMinerService {
 		*self.tx_gas_limit.write() = limit;
 	}
 
+	fn set_transaction_ordering_hint(&self, hashes: Vec<H256>) {
+		*self.ordering_hint.lock() = hashes;
+	}
+
 	fn transactions_limit(&self) -> usize {
 		*self.limit.read()
 	}
@@ -194,7 +226,7 @@ impl MinerService for TestMinerService {
 
 	/// Returns hashes of transactions currently in pending
 	fn pending_transactions_hashes(&self, _best_block: BlockNumber) -> Vec<H256> {
-		vec![]
+		self.pending_transactions.lock().keys().cloned().collect()
 	}
 
 	/// Removes all transactions from the queue and restart mining operation.
@@ -233,12 +265,28 @@ impl MinerService for TestMinerService {
 		self.local_transactions.lock().iter().map(|(hash, stats)| (*hash, stats.clone())).collect()
 	}
 
+	fn banned_transaction_senders(&self) -> Vec<Address> {
+		self.banned_senders.lock().clone()
+	}
+
+	fn mine_stats(&self) -> MineStats {
+		self.mine_stats.lock().clone()
+	}
+
+	fn reorg_stats(&self) -> ReorgStats {
+		self.reorg_stats.lock().clone()
+	}
+
+	fn stratum_workers(&self) -> Vec<StratumWorkerInfo> {
+		self.stratum_workers.lock().clone()
+	}
+
 	fn ready_transactions(&self, _best_block: BlockNumber, _best_timestamp: u64) -> Vec<PendingTransaction> {
 		self.pending_transactions.lock().values().cloned().map(Into::into).collect()
 	}
 
 	fn future_transactions(&self) -> Vec<PendingTransaction> {
-		vec![]
+		self.future_transactions.lock().values().cloned().map(Into::into).collect()
 	}
 
 	fn pending_receipt(&self, _best_block: BlockNumber, hash: &H256) -> Option<RichReceipt> {
@@ -26,6 +26,7 @@ use util::{Uint, U256, Address, H256, Mutex};
 use ethkey::Secret;
 use ethcore::account_provider::AccountProvider;
 use ethcore::client::{TestBlockChainClient, EachBlockWith, Executed, TransactionId};
+use ethcore::evm::Error as EvmError;
 use ethcore::log_entry::{LocalizedLogEntry, LogEntry};
 use ethcore::receipt::LocalizedReceipt;
 use ethcore::transaction::{Transaction, Action};
@@ -230,6 +231,45 @@ fn rpc_eth_logs() {
 	assert_eq!(tester.io.handle_request_sync(request3), Some(response3.to_owned()));
 }
 
+#[test]
+fn rpc_eth_logs_over_limit() {
+	let tester = EthTester::new_with_options(EthClientOptions::with(|mut options| {
+		options.max_logs = Some(1);
+	}));
+	let log = |block_number| LocalizedLogEntry {
+		block_number: block_number,
+		block_hash: H256::default(),
+		entry: LogEntry {
+			address: Address::default(),
+			topics: vec![],
+			data: vec![1,2,3],
+		},
+		transaction_index: 0,
+		transaction_log_index: 0,
+		transaction_hash: H256::default(),
+		log_index: 0,
+	};
+	tester.client.set_logs(vec![log(1), log(2)]);
+
+	let request1 = r#"{"jsonrpc": "2.0", "method": "eth_getLogs", "params": [{}], "id": 1}"#;
+	let request2 = r#"{"jsonrpc": "2.0", "method": "eth_getLogs", "params": [{"limit":2}], "id": 1}"#;
+
+	let response1 = r#"{"jsonrpc":"2.0","error":{"code":-32083,"message":"Query returned more than 1 logs. Query up to the block before nextBlock and continue from nextBlock.","data":{"nextBlock":"0x2"}},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request1), Some(response1.to_owned()));
+	assert_eq!(tester.io.handle_request_sync(request2), Some(response1.to_owned()));
+
+	let tester = EthTester::new_with_options(EthClientOptions::with(|mut options| {
+		options.max_logs = Some(10);
+	}));
+	tester.client.set_logs(vec![log(1), log(2)]);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getLogs", "params": [{"limit":1}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32083,"message":"Query returned more than 1 logs. Query up to the block before nextBlock and continue from nextBlock.","data":{"nextBlock":"0x2"}},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_logs_filter() {
 	let tester = EthTester::default();
@@ -364,6 +404,28 @@ fn rpc_eth_gas_price() {
 	assert_eq!(EthTester::default().io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_fee_history() {
+	use serde_json;
+
+	let tester = EthTester::default();
+	tester.add_blocks(2, EachBlockWith::Nothing);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_feeHistory", "params": ["0x2", "latest", [10, 50]], "id": 1}"#;
+	let response: serde_json::Value = serde_json::from_str(&tester.io.handle_request_sync(request).unwrap()).unwrap();
+	assert_eq!(response["result"]["oldestBlock"], serde_json::Value::String("0x1".into()));
+	assert_eq!(response["result"]["gasUsedRatio"].as_array().unwrap().len(), 2);
+	assert_eq!(response["result"]["reward"], serde_json::from_str::<serde_json::Value>(r#"[["0x0","0x0"],["0x0","0x0"]]"#).unwrap());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_feeHistory", "params": ["0x2", "latest", [50, 10]], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: rewardPercentiles","data":"\"must be increasing values between 0 and 100\""},"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_feeHistory", "params": ["0x0", "latest"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: blockCount","data":"\"must be between 1 and 1024\""},"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_eth_accounts() {
 	let tester = EthTester::default();
@@ -556,6 +618,34 @@ fn rpc_eth_pending_transaction_by_hash() {
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_pending_transactions_filter() {
+	use util::FromHex;
+	use rlp;
+	use ethcore::transaction::SignedTransaction;
+
+	let tester = EthTester::default();
+	let request = |params: &str| r#"{"jsonrpc": "2.0", "method": "parity_pendingTransactionsFilter", "params": ["#.to_owned() + params + r#"], "id": 1}"#;
+	let matching = request(r#"{"to": ["0x095e7baea6a6c7c4c2dfeb977efac326af552d87"], "value": {"min": "0xa"}}"#);
+	let response = r#"{"jsonrpc":"2.0","result":"0x0","id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&matching), Some(response.to_owned()));
+	let other = request(r#"{"gasPrice": {"min": "0x2"}}"#);
+	let response = r#"{"jsonrpc":"2.0","result":"0x1","id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&other), Some(response.to_owned()));
+
+	{
+		let tx = rlp::decode(&FromHex::from_hex("f85f800182520894095e7baea6a6c7c4c2dfeb977efac326af552d870a801ba048b55bfa915ac795c431978d8a6a992b628d557da5ff759b307d495a36649353a0efffd310ac743f371de3b9f7f9cb56c0b28ad43601b4ab949f53faa07bd2c804").unwrap());
+		let tx = SignedTransaction::new(tx).unwrap();
+		tester.miner.pending_transactions.lock().insert(tx.hash(), tx);
+	}
+
+	let changes = |id: &str| r#"{"jsonrpc": "2.0", "method": "eth_getFilterChanges", "params": [""#.to_owned() + id + r#""], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":["0x41df922fd0d4766fcc02e161f8295ec28522f329ae487f14d811e4b64c8d6e31"],"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&changes("0x0")), Some(response.to_owned()));
+	let response = r#"{"jsonrpc":"2.0","result":[],"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(&changes("0x0")), Some(response.to_owned()));
+	assert_eq!(tester.io.handle_request_sync(&changes("0x1")), Some(response.to_owned()));
+}
 
 #[test]
 fn rpc_eth_uncle_count_by_block_hash() {
@@ -614,6 +704,8 @@ fn rpc_eth_call_latest() {
 		trace: vec![],
 		vm_trace: None,
 		state_diff: None,
+		gas_profile: None,
+		struct_logs: None,
 	}));
 
 	let request = r#"{
@@ -635,6 +727,123 @@ fn rpc_eth_call_latest() {
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_call_reverted() {
+	let tester = EthTester::default();
+	// `Error("insufficient balance")`
+	let output = "08c379a0\
+		0000000000000000000000000000000000000000000000000000000000000020\
+		0000000000000000000000000000000000000000000000000000000000000014\
+		696e73756666696369656e742062616c616e6365000000000000000000000000".from_hex().unwrap();
+	tester.client.set_execution_result(Ok(Executed {
+		exception: Some(EvmError::Reverted { gas_left: U256::from(0x1234) }),
+		gas: U256::zero(),
+		gas_used: U256::from(0xff30),
+		refunded: U256::zero(),
+		cumulative_gas_used: U256::zero(),
+		logs: vec![],
+		contracts_created: vec![],
+		output: output.clone(),
+		trace: vec![],
+		vm_trace: None,
+		state_diff: None,
+		gas_profile: None,
+		struct_logs: None,
+	}));
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_call",
+		"params": [{
+			"from": "0xb60e8dd61c5d32be8058bb8eb970870f07233155",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567"
+		},
+		"latest"],
+		"id": 1
+	}"#;
+	let response = format!(r#"{{"jsonrpc":"2.0","error":{{"code":-32015,"message":"revert: insufficient balance","data":"0x{}"}},"id":1}}"#, output.to_hex());
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response));
+}
+
+#[test]
+fn rpc_eth_call_out_of_gas() {
+	let tester = EthTester::default();
+	tester.client.set_execution_result(Ok(Executed {
+		exception: Some(EvmError::OutOfGas),
+		gas: U256::zero(),
+		gas_used: U256::from(0x76c0),
+		refunded: U256::zero(),
+		cumulative_gas_used: U256::zero(),
+		logs: vec![],
+		contracts_created: vec![],
+		output: vec![],
+		trace: vec![],
+		vm_trace: None,
+		state_diff: None,
+		gas_profile: None,
+		struct_logs: None,
+	}));
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_call",
+		"params": [{
+			"from": "0xb60e8dd61c5d32be8058bb8eb970870f07233155",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567"
+		},
+		"latest"],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32015,"message":"VM execution error: Out of gas"},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_eth_call_cached_until_new_best_block() {
+	let tester = EthTester::default();
+	let executed = |output: Vec<u8>| Ok(Executed {
+		exception: None,
+		gas: U256::zero(),
+		gas_used: U256::from(0xff30),
+		refunded: U256::zero(),
+		cumulative_gas_used: U256::zero(),
+		logs: vec![],
+		contracts_created: vec![],
+		output: output,
+		trace: vec![],
+		vm_trace: None,
+		state_diff: None,
+		gas_profile: None,
+		struct_logs: None,
+	});
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_call",
+		"params": [{
+			"from": "0xb60e8dd61c5d32be8058bb8eb970870f07233155",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567",
+			"data": "0x70a08231"
+		},
+		"latest"],
+		"id": 1
+	}"#;
+	let first = r#"{"jsonrpc":"2.0","result":"0x1234ff","id":1}"#;
+	let second = r#"{"jsonrpc":"2.0","result":"0x5678","id":1}"#;
+
+	tester.client.set_execution_result(executed(vec![0x12, 0x34, 0xff]));
+	assert_eq!(tester.io.handle_request_sync(request), Some(first.to_owned()));
+
+	// the same call against the same best block isn't executed again.
+	tester.client.set_execution_result(executed(vec![0x56, 0x78]));
+	assert_eq!(tester.io.handle_request_sync(request), Some(first.to_owned()));
+
+	tester.add_blocks(1, EachBlockWith::Nothing);
+	assert_eq!(tester.io.handle_request_sync(request), Some(second.to_owned()));
+}
+
 #[test]
 fn rpc_eth_call() {
 	let tester = EthTester::default();
@@ -650,6 +859,8 @@ fn rpc_eth_call() {
 		trace: vec![],
 		vm_trace: None,
 		state_diff: None,
+		gas_profile: None,
+		struct_logs: None,
 	}));
 
 	let request = r#"{
@@ -686,6 +897,8 @@ fn rpc_eth_call_default_block() {
 		trace: vec![],
 		vm_trace: None,
 		state_diff: None,
+		gas_profile: None,
+		struct_logs: None,
 	}));
 
 	let request = r#"{
@@ -706,6 +919,92 @@ fn rpc_eth_call_default_block() {
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_call_with_overrides() {
+	let tester = EthTester::default();
+	tester.client.set_execution_result(Ok(Executed {
+		exception: None,
+		gas: U256::zero(),
+		gas_used: U256::from(0xff30),
+		refunded: U256::from(0x5),
+		cumulative_gas_used: U256::zero(),
+		logs: vec![],
+		contracts_created: vec![],
+		output: vec![0x12, 0x34, 0xff],
+		trace: vec![],
+		vm_trace: None,
+		state_diff: None,
+		gas_profile: None,
+		struct_logs: None,
+	}));
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_callWithOverrides",
+		"params": [{
+			"from": "0xb60e8dd61c5d32be8058bb8eb970870f07233155",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567",
+			"data": "0xd46e8dd6"
+		},
+		"latest",
+		{
+			"0xd46e8dd67c5d32be8058bb8eb970870f07244567": {
+				"code": "0x60006000f3",
+				"stateDiff": {
+					"0x0000000000000000000000000000000000000000000000000000000000000000": "0x0000000000000000000000000000000000000000000000000000000000000001"
+				}
+			},
+			"0xb60e8dd61c5d32be8058bb8eb970870f07233155": {
+				"balance": "0x9184e72a000",
+				"nonce": "0x1"
+			}
+		}],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x1234ff","id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_eth_call_many() {
+	let tester = EthTester::default();
+	tester.client.set_execution_result(Ok(Executed {
+		exception: None,
+		gas: U256::zero(),
+		gas_used: U256::from(0xff30),
+		refunded: U256::from(0x5),
+		cumulative_gas_used: U256::zero(),
+		logs: vec![],
+		contracts_created: vec![],
+		output: vec![0x12, 0x34, 0xff],
+		trace: vec![],
+		vm_trace: None,
+		state_diff: None,
+		gas_profile: None,
+		struct_logs: None,
+	}));
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_callMany",
+		"params": [[{
+			"from": "0xb60e8dd61c5d32be8058bb8eb970870f07233155",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567",
+			"data": "0xd46e8dd6"
+		}, {
+			"from": "0xb60e8dd61c5d32be8058bb8eb970870f07233155",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567",
+			"data": "0x70a08231"
+		}],
+		"latest"],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","result":["0x1234ff","0x1234ff"],"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_eth_estimate_gas() {
 	let tester = EthTester::default();
@@ -721,6 +1020,8 @@ fn rpc_eth_estimate_gas() {
 		trace: vec![],
 		vm_trace: None,
 		state_diff: None,
+		gas_profile: None,
+		struct_logs: None,
 	}));
 
 	let request = r#"{
@@ -757,6 +1058,8 @@ fn rpc_eth_estimate_gas_default_block() {
 		trace: vec![],
 		vm_trace: None,
 		state_diff: None,
+		gas_profile: None,
+		struct_logs: None,
 	}));
 
 	let request = r#"{
@@ -924,7 +1227,7 @@ fn rpc_eth_send_transaction_error() {
 		"id": 1
 	}"#;
 
-	let response = r#"{"jsonrpc":"2.0","error":{"code":-32020,"message":"Your account is locked. Unlock the account via CLI, personal_unlockAccount or use Trusted Signer.","data":"NotUnlocked"},"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32020,"message":"Your account is locked. Unlock the account via CLI, personal_unlockAccount or use Trusted Signer.","data":{"details":"NotUnlocked","kind":"AccountLocked"}},"id":1}"#;
 	assert_eq!(tester.io.handle_request_sync(&request), Some(response.into()));
 }
 
@@ -0,0 +1,213 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use futures::{Future, Stream};
+use futures::sync::mpsc;
+use jsonrpc_core::MetaIoHandler;
+use jsonrpc_pubsub::Session;
+use serde_json;
+
+use ethcore::client::{BlockChainClient, TestBlockChainClient, EachBlockWith, BlockId, ChainNotify};
+use ethsync::SyncState;
+use parity_reactor::Remote;
+use v1::{EthPubSub, EthPubSubClient, Metadata};
+use v1::tests::helpers::{TestSyncProvider, Config};
+
+fn sync_provider() -> Arc<TestSyncProvider> {
+	Arc::new(TestSyncProvider::new(Config {
+		network_id: 3,
+		num_peers: 120,
+	}))
+}
+
+fn subscription_id(response: &str) -> String {
+	let response: serde_json::Value = serde_json::from_str(response).unwrap();
+	response["result"].as_str().expect("subscription succeeded").to_owned()
+}
+
+#[test]
+fn should_subscribe_to_new_heads() {
+	// given
+	let mut client = TestBlockChainClient::new();
+	client.add_blocks(1, EachBlockWith::Nothing);
+	let h1 = client.block_hash_delta_minus(1);
+	let client = Arc::new(client);
+
+	let pubsub = EthPubSubClient::new(&client, &sync_provider(), Remote::new_sync(), 10);
+	let handler = pubsub.handler();
+	let mut io = MetaIoHandler::default();
+	io.extend_with(pubsub.to_delegate());
+
+	let mut metadata = Metadata::default();
+	let (sender, receiver) = mpsc::channel(8);
+	metadata.session = Some(Arc::new(Session::new(sender)));
+
+	// when
+	let request = r#"{"jsonrpc":"2.0","method":"eth_subscribe","params":["newHeads"],"id":1}"#;
+	let id = subscription_id(&io.handle_request_sync(request, metadata.clone()).unwrap());
+	handler.new_blocks(vec![], vec![], vec![h1], vec![], vec![], vec![], 0);
+
+	// then
+	let (notification, _receiver) = receiver.into_future().wait().unwrap();
+	let notification = notification.unwrap();
+	assert!(notification.contains(r#""method":"eth_subscription""#));
+	assert!(notification.contains(&format!(r#""subscription":"{}""#, id)));
+	assert!(notification.contains(&format!(r#""hash":"0x{:?}""#, h1)));
+
+	// and when
+	let request = format!(r#"{{"jsonrpc":"2.0","method":"eth_unsubscribe","params":["{}"],"id":1}}"#, id);
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(&request, metadata.clone()), Some(response.to_owned()));
+	assert_eq!(io.handle_request_sync(&request, metadata), Some(r#"{"jsonrpc":"2.0","result":false,"id":1}"#.to_owned()));
+}
+
+#[test]
+fn should_reject_subscriptions_over_limit() {
+	let client = Arc::new(TestBlockChainClient::new());
+	let pubsub = EthPubSubClient::new(&client, &sync_provider(), Remote::new_sync(), 1);
+	let mut io = MetaIoHandler::default();
+	io.extend_with(pubsub.to_delegate());
+
+	let mut metadata = Metadata::default();
+	let (sender, _receiver) = mpsc::channel(8);
+	metadata.session = Some(Arc::new(Session::new(sender)));
+
+	let request = r#"{"jsonrpc":"2.0","method":"eth_subscribe","params":["newPendingTransactions"],"id":1}"#;
+	subscription_id(&io.handle_request_sync(request, metadata.clone()).unwrap());
+
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32075,"message":"Too many subscriptions on this connection. At most 1 are allowed."},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata), Some(response.to_owned()));
+}
+
+#[test]
+fn should_reject_parameters_for_new_heads() {
+	let client = Arc::new(TestBlockChainClient::new());
+	let pubsub = EthPubSubClient::new(&client, &sync_provider(), Remote::new_sync(), 10);
+	let mut io = MetaIoHandler::default();
+	io.extend_with(pubsub.to_delegate());
+
+	let mut metadata = Metadata::default();
+	let (sender, _receiver) = mpsc::channel(8);
+	metadata.session = Some(Arc::new(Session::new(sender)));
+
+	let request = r#"{"jsonrpc":"2.0","method":"eth_subscribe","params":["newHeads",{}],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: params","data":"\"NewHeads subscriptions take no parameters\""},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata), Some(response.to_owned()));
+}
+
+#[test]
+fn should_notify_about_chain_reorgs() {
+	// given
+	let client = TestBlockChainClient::new();
+	client.add_blocks(3, EachBlockWith::Nothing);
+	let (h1, h2, h3) = {
+		let hash = |number| client.block_hash(BlockId::Number(number)).unwrap();
+		(hash(1), hash(2), hash(3))
+	};
+	let client = Arc::new(client);
+
+	let pubsub = EthPubSubClient::new(&client, &sync_provider(), Remote::new_sync(), 10);
+	let handler = pubsub.handler();
+	let mut io = MetaIoHandler::default();
+	io.extend_with(pubsub.to_delegate());
+
+	let mut metadata = Metadata::default();
+	let (sender, receiver) = mpsc::channel(8);
+	metadata.session = Some(Arc::new(Session::new(sender)));
+
+	let request = r#"{"jsonrpc":"2.0","method":"eth_subscribe","params":["chainReorg"],"id":1}"#;
+	subscription_id(&io.handle_request_sync(request, metadata).unwrap());
+
+	// when
+	handler.new_blocks(vec![], vec![], vec![h1.clone()], vec![], vec![], vec![], 0);
+	handler.new_blocks(vec![], vec![], vec![5.into()], vec![h3.clone(), h2.clone()], vec![], vec![], 0);
+
+	// then
+	let (notification, _receiver) = receiver.into_future().wait().unwrap();
+	let notification = notification.unwrap();
+	assert!(notification.contains(&format!(r#""oldBranch":["0x{:?}","0x{:?}"]"#, h3, h2)));
+	assert!(notification.contains(&format!(r#""commonAncestor":"0x{:?}""#, h1)));
+}
+
+#[test]
+fn should_notify_about_sync_progress() {
+	// given
+	let client = TestBlockChainClient::new();
+	client.add_blocks(1, EachBlockWith::Nothing);
+	let client = Arc::new(client);
+	let sync = sync_provider();
+
+	let pubsub = EthPubSubClient::new(&client, &sync, Remote::new_sync(), 10);
+	let handler = pubsub.handler();
+	let mut io = MetaIoHandler::default();
+	io.extend_with(pubsub.to_delegate());
+
+	let mut metadata = Metadata::default();
+	let (sender, receiver) = mpsc::channel(8);
+	metadata.session = Some(Arc::new(Session::new(sender)));
+
+	let request = r#"{"jsonrpc":"2.0","method":"eth_subscribe","params":["syncing"],"id":1}"#;
+	subscription_id(&io.handle_request_sync(request, metadata).unwrap());
+
+	// when
+	{
+		let mut status = sync.status.write();
+		status.state = SyncState::Blocks;
+		status.highest_block_number = Some(2500);
+	}
+	handler.new_blocks(vec![], vec![], vec![], vec![], vec![], vec![], 0);
+	sync.status.write().state = SyncState::Idle;
+	handler.new_blocks(vec![], vec![], vec![], vec![], vec![], vec![], 0);
+
+	// then
+	let (notification, receiver) = receiver.into_future().wait().unwrap();
+	assert!(notification.unwrap().contains(r#""result":{"currentBlock":"0x1","highestBlock":"0x9c4""#));
+	let (notification, _receiver) = receiver.into_future().wait().unwrap();
+	assert!(notification.unwrap().contains(r#""result":false"#));
+}
+
+#[test]
+fn should_notify_about_new_work() {
+	use ethcore::miner::WorkPackage;
+
+	// given
+	let client = Arc::new(TestBlockChainClient::new());
+	let pubsub = EthPubSubClient::new(&client, &sync_provider(), Remote::new_sync(), 10);
+	let handler = pubsub.handler();
+	let mut io = MetaIoHandler::default();
+	io.extend_with(pubsub.to_delegate());
+
+	let mut metadata = Metadata::default();
+	let (sender, receiver) = mpsc::channel(8);
+	metadata.session = Some(Arc::new(Session::new(sender)));
+
+	let request = r#"{"jsonrpc":"2.0","method":"eth_subscribe","params":["newWork"],"id":1}"#;
+	subscription_id(&io.handle_request_sync(request, metadata).unwrap());
+
+	// when
+	handler.new_work(&WorkPackage {
+		pow_hash: 5.into(),
+		difficulty: 0.into(),
+		number: 1,
+		timestamp: 0,
+	});
+
+	// then
+	let (notification, _receiver) = receiver.into_future().wait().unwrap();
+	assert!(notification.unwrap().contains(r#""result":["0x0000000000000000000000000000000000000000000000000000000000000005","0x0000000000000000000000000000000000000000000000000000000000000000","0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff","0x1"]"#));
+}
@@ -27,6 +27,7 @@ use jsonrpc_core::IoHandler;
 use v1::{Parity, ParityClient};
 use v1::metadata::Metadata;
 use v1::helpers::{SignerService, NetworkSettings};
+use v1::helpers::node_health::NodeHealth;
 use v1::tests::helpers::{TestSyncProvider, Config, TestMinerService, TestUpdater};
 use super::manage_network::TestManageNetwork;
 
@@ -84,6 +85,13 @@ impl Dependencies {
 			signer,
 			self.dapps_interface.clone(),
 			self.dapps_port,
+			Arc::new(NodeHealth::new(
+				self.client.clone(),
+				self.sync.clone(),
+				self.network.clone(),
+				".".into(),
+				vec![],
+			)),
 		)
 	}
 
@@ -373,6 +381,56 @@ fn rpc_parity_pending_transactions() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_txpool_status() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_txpoolStatus", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"pending":"0x0","queued":"0x0"},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_txpool_content() {
+	use serde_json;
+	use ethcore::transaction::{Transaction, Action};
+
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let keypair = Random.generate().unwrap();
+	let transaction = |nonce: u64, gas_price: u64| Transaction {
+		nonce: nonce.into(),
+		gas_price: gas_price.into(),
+		gas: 21_000.into(),
+		action: Action::Call(Address::default()),
+		value: 0.into(),
+		data: vec![],
+	}.sign(keypair.secret(), None);
+
+	let underpriced = transaction(0, 1);
+	let underpriced_hash = underpriced.hash();
+	deps.miner.pending_transactions.lock().insert(underpriced_hash, underpriced);
+	for nonce in &[3, 5] {
+		let tx = transaction(*nonce, 20_000_000_000);
+		deps.miner.future_transactions.lock().insert(tx.hash(), tx);
+	}
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_txpoolContent", "params":[], "id": 1}"#;
+	let response: serde_json::Value = serde_json::from_str(&io.handle_request_sync(request).unwrap()).unwrap();
+	let sender = &response["result"][format!("0x{:?}", keypair.address()).as_str()];
+
+	assert_eq!(sender["nonce"], serde_json::Value::String("0x0".into()));
+	assert_eq!(sender["pending"].as_array().unwrap().len(), 1);
+	assert_eq!(sender["queued"].as_array().unwrap().len(), 2);
+	assert_eq!(sender["nonceGaps"], serde_json::from_str::<serde_json::Value>(r#"[["0x1","0x2"],["0x4","0x4"]]"#).unwrap());
+	assert_eq!(sender["replacementCandidates"], serde_json::Value::Array(vec![
+		serde_json::Value::String(format!("0x{:?}", underpriced_hash)),
+	]));
+}
+
 #[test]
 fn rpc_parity_encrypt() {
 	let deps = Dependencies::new();
@@ -482,6 +540,77 @@ fn rpc_parity_local_transactions() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_banned_transaction_senders() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+	deps.miner.banned_senders.lock().push(10.into());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_bannedTransactionSenders", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":["0x000000000000000000000000000000000000000a"],"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_mine_stats() {
+	use ethcore::miner::MineStats;
+
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+	*deps.miner.mine_stats.lock() = MineStats {
+		blocks: 10,
+		uncles_included: 3,
+		uncles_missed: 1,
+	};
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_mineStats", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"blocks":"0xa","includedUncles":"0x3","missedUncles":"0x1"},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_reorg_stats() {
+	use ethcore::miner::ReorgStats;
+
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+	*deps.miner.reorg_stats.lock() = ReorgStats {
+		reorgs: 2,
+		abandoned_transactions: 5,
+		reimported_transactions: 4,
+	};
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_reorgStats", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"abandonedTransactions":"0x5","reimportedTransactions":"0x4","reorgs":"0x2"},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_stratum_workers() {
+	use ethcore::miner::StratumWorkerInfo;
+
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+	deps.miner.stratum_workers.lock().push(StratumWorkerInfo {
+		id: "miner1".into(),
+		address: "127.0.0.1:8008".parse().unwrap(),
+		difficulty: Some(1000.into()),
+		hashrate: 200.into(),
+		accepted: 3,
+		stale: 1,
+		rejected: 0,
+		blocks: 1,
+	});
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_stratumWorkers", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"acceptedShares":"0x3","address":"127.0.0.1:8008","blocks":"0x1","difficulty":"0x3e8","hashrate":"0xc8","rejectedShares":"0x0","staleRatio":0.25,"staleShares":"0x1","worker":"miner1"}],"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_chain_status() {
 	use util::{H256, U256};
@@ -493,11 +622,61 @@ fn rpc_parity_chain_status() {
 	*deps.client.first_block.write() = Some((H256::from(U256::from(1234)), 3333));
 
 	let request = r#"{"jsonrpc": "2.0", "method": "parity_chainStatus", "params":[], "id": 1}"#;
-	let response = r#"{"jsonrpc":"2.0","result":{"blockGap":["0x6","0xd05"]},"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"blockGap":["0x6","0xd05"],"earliestBlock":"0xd05","earliestState":"0x0","warpRestoration":null},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_execute_with_proof() {
+	use ethcore::client::Executed;
+	use util::U256;
+
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	deps.client.set_execution_result(Ok(Executed {
+		exception: None,
+		gas: U256::zero(),
+		gas_used: U256::from(0xff30),
+		refunded: U256::from(0x5),
+		cumulative_gas_used: U256::zero(),
+		logs: vec![],
+		contracts_created: vec![],
+		output: vec![0x12, 0x34, 0xff],
+		trace: vec![],
+		vm_trace: None,
+		state_diff: None,
+		gas_profile: None,
+		struct_logs: None,
+	}));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_executeWithProof", "params":[{"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567"}, "latest"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"output":"0x1234ff","gasUsed":"0xff30","proof":[]},"id":1}"#;
 
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_get_proof_state_pruned() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_getProof", "params":["0xd46e8dd67c5d32be8058bb8eb970870f07244567", [], "latest"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32004,"message":"This request is not supported because your node is running with state pruning. Run with --pruning=archive.","data":{"kind":"StatePruned"}},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_get_proof_pending() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_getProof", "params":["0xd46e8dd67c5d32be8058bb8eb970870f07244567", [], "pending"], "id": 1}"#;
+	assert!(io.handle_request_sync(request).unwrap().contains("error"));
+}
+
 #[test]
 fn rpc_parity_node_kind() {
 	let deps = Dependencies::new();
@@ -508,3 +687,99 @@ fn rpc_parity_node_kind() {
 
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
+
+#[test]
+fn rpc_parity_db_stats() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_dbStats", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"pruning":"archive","compaction":null},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_db_statistics() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_dbStatistics", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"columns":[],"sizeOnDisk":"0x0"},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_evm_stats() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_evmStats", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"interpreterRuns":"0x0","jitRuns":"0x0","hotContracts":"0x0"},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_key_preimage() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_keyPreimage", "params":["0x0000000000000000000000000000000000000000000000000000000000000001"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_block_receipts() {
+	use ethcore::client::{BlockId, TransactionId};
+	use ethcore::receipt::LocalizedReceipt;
+	use util::{H256, U256};
+
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	deps.client.set_transaction_receipt(TransactionId::Location(BlockId::Number(1), 0), LocalizedReceipt {
+		transaction_hash: H256::from(1),
+		transaction_index: 0,
+		block_hash: H256::from(2),
+		block_number: 1,
+		cumulative_gas_used: U256::from(0x5208),
+		gas_used: U256::from(0x5208),
+		contract_address: None,
+		logs: vec![],
+		log_bloom: 0.into(),
+		state_root: None,
+	});
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_getBlockReceipts", "params":["0x1"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"blockHash":"0x0000000000000000000000000000000000000000000000000000000000000002","blockNumber":"0x1","contractAddress":null,"cumulativeGasUsed":"0x5208","gasUsed":"0x5208","logs":[],"logsBloom":"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000","root":null,"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000001","transactionIndex":"0x0"}],"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_getBlockReceipts", "params":["0x2"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[],"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_node_health() {
+	use serde_json;
+
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_nodeHealth", "params":[], "id": 1}"#;
+	let response: serde_json::Value = serde_json::from_str(&io.handle_request_sync(request).unwrap()).unwrap();
+	let health = &response["result"];
+
+	assert_eq!(health["peers"]["status"], serde_json::Value::String("ok".into()));
+	assert_eq!(health["peers"]["details"]["connected"].as_u64(), Some(120));
+	assert_eq!(health["sync"]["status"], serde_json::Value::String("ok".into()));
+	assert_eq!(health["sync"]["details"].as_u64(), Some(0));
+	assert_eq!(health["time"]["status"], serde_json::Value::String("ok".into()));
+	assert!(health["disk"]["status"].is_string());
+}
@@ -16,7 +16,7 @@
 
 use std::sync::Arc;
 
-use ethcore::account_provider::{AccountProvider, AccountProviderSettings};
+use ethcore::account_provider::{AccountProvider, AccountProviderSettings, SigningLog, SigningDecision};
 use ethstore::EthStore;
 use ethstore::dir::RootDiskDirectory;
 use devtools::RandomTempPath;
@@ -339,6 +339,23 @@ fn rpc_parity_change_vault() {
 	assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_change_vault_out_of_vault_with_policy() {
+	let temp_path = RandomTempPath::new();
+	let tester = setup_with_vaults_support(temp_path.as_str());
+
+	let (address, _) = tester.accounts.new_account_and_public("root_password").unwrap();
+	assert!(tester.accounts.create_vault("vault1", "password1").is_ok());
+	assert!(tester.accounts.change_vault(address, "vault1").is_ok());
+	assert!(tester.accounts.set_vault_policy("vault1", "password1", Some(Default::default())).is_ok());
+
+	let request = format!(r#"{{"jsonrpc": "2.0", "method": "parity_changeVault", "params":["0x{}", ""], "id": 1}}"#, address.hex());
+	let response = tester.io.handle_request_sync(&request).unwrap();
+
+	assert!(response.contains("Could not change vault."));
+	assert!(tester.accounts.account_policy(&address).is_some());
+}
+
 #[test]
 fn rpc_parity_vault_adds_vault_field_to_acount_meta() {
 	let temp_path = RandomTempPath::new();
@@ -433,6 +450,59 @@ fn rpc_parity_get_set_vault_meta() {
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_get_set_vault_policy() {
+	let temp_path = RandomTempPath::new();
+	let tester = setup_with_vaults_support(temp_path.as_str());
+
+	assert!(tester.accounts.create_vault("vault1", "password1").is_ok());
+
+	// when no policy set
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_getVaultPolicy", "params":["vault1"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+
+	// set policy with a wrong password
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setVaultPolicy", "params":["vault1", "password2", null], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32023,"message":"Could not update vault policy.","data":"InvalidPassword"},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+
+	// set policy
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setVaultPolicy", "params":["vault1", "password1", {"maxValue":"0x3e8","allowedRecipients":["0x0000000000000000000000000000000000000001"],"allowedMethods":["sendTransaction"]}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+	assert_eq!(tester.accounts.vault_policy("vault1").unwrap().max_value, Some(1000.into()));
+
+	// query policy
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_getVaultPolicy", "params":["vault1"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"allowedMethods":["sendTransaction"],"allowedRecipients":["0x0000000000000000000000000000000000000001"],"maxValue":"0x3e8"},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+
+	// unknown vault
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setVaultPolicy", "params":["vault2", "password1", null], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32023,"message":"Could not update vault policy.","data":"VaultNotFound"},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_signing_history() {
+	let tester = setup();
+	tester.accounts.set_signing_log(SigningLog::new(Arc::new(::util::kvdb::in_memory(1)), Some(0)));
+	tester.accounts.record_signing(1.into(), "unknown origin".into(), "eth_sign".into(), Some(2.into()), SigningDecision::Signed);
+	tester.accounts.record_signing(1.into(), "unknown origin".into(), "eth_sendTransaction".into(), None, SigningDecision::Rejected);
+	let timestamp = tester.accounts.signing_history(1)[0].timestamp;
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_signingHistory", "params":[1], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"account":"0x0000000000000000000000000000000000000001","decision":"rejected","hash":null,"method":"eth_sendTransaction","origin":"unknown origin","timestamp":""#.to_owned() + &format!("{:#x}", timestamp) + r#""}],"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response));
+}
+
 // name: parity_deriveAddressHash
 // example: {"jsonrpc": "2.0", "method": "parity_deriveAddressHash", "params": ["0xc171033d5cbff7175f29dfd3a63dda3d6f8f385e", "password1", { "type": "soft", "hash": "0x0c0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0c0c" }, true ], "id": 3}
 #[test]
@@ -17,7 +17,7 @@
 use std::sync::Arc;
 use std::str::FromStr;
 use rustc_serialize::hex::FromHex;
-use util::{U256, Address};
+use util::{U256, H256, Address};
 
 use ethcore::miner::MinerService;
 use ethcore::client::TestBlockChainClient;
@@ -122,6 +122,28 @@ fn rpc_parity_set_gas_floor_target() {
 	assert_eq!(miner.gas_floor_target(), U256::from_str("cd1722f3947def4cf144679da39c4c32bdc35681").unwrap());
 }
 
+#[test]
+fn rpc_parity_set_gas_limit_target() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setGasLimitTarget", "params":["0x7a1200"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert_eq!(miner.gas_limit_target(), Some(U256::from(8_000_000)));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setGasLimitTarget", "params":["0x0"], "id": 1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert_eq!(miner.gas_limit_target(), None);
+}
+
 #[test]
 fn rpc_parity_set_extra_data() {
 	let miner = miner_service();
@@ -189,6 +211,22 @@ fn rpc_parity_set_transactions_limit() {
 	assert_eq!(miner.transactions_limit(), 10_240_240);
 }
 
+#[test]
+fn rpc_parity_set_transaction_ordering_hint() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setTransactionOrderingHint", "params":[["0x000000000000000000000000000000000000000000000000000000000000000a"]], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert_eq!(*miner.ordering_hint.lock(), vec![H256::from(10)]);
+}
+
 #[test]
 fn rpc_parity_set_hash_content() {
 	let miner = miner_service();
@@ -232,3 +270,18 @@ fn rpc_parity_remove_transaction() {
 	miner.pending_transactions.lock().insert(hash, signed);
 	assert_eq!(io.handle_request_sync(&request), Some(response.to_owned()));
 }
+
+#[test]
+fn rpc_parity_backup_database() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_backupDatabase", "params":["/tmp/backup"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32017,"message":"Database error.","data":"\"Test client has no database\""},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
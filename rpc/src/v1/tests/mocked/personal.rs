@@ -17,11 +17,16 @@
 use std::sync::Arc;
 use std::str::FromStr;
 
-use ethcore::account_provider::AccountProvider;
+use ethcore::account_provider::{AccountProvider, AccountProviderSettings, VaultPolicy, SigningLog, SigningDecision};
 use ethcore::client::TestBlockChainClient;
-use ethcore::transaction::{Action, Transaction};
+use ethstore::EthStore;
+use ethstore::dir::RootDiskDirectory;
+use devtools::RandomTempPath;
+use ethcore::transaction::{Action, Transaction, SignedTransaction};
 use jsonrpc_core::IoHandler;
-use util::{U256, Uint, Address};
+use util::{U256, Uint, Address, ToPretty};
+use rlp;
+use serde_json;
 
 use v1::{PersonalClient, Personal, Metadata};
 use v1::helpers::dispatch::FullDispatcher;
@@ -50,7 +55,10 @@ fn miner_service() -> Arc<TestMinerService> {
 }
 
 fn setup() -> PersonalTester {
-	let accounts = accounts_provider();
+	setup_with_accounts_provider(accounts_provider())
+}
+
+fn setup_with_accounts_provider(accounts: Arc<AccountProvider>) -> PersonalTester {
 	let client = blockchain_client();
 	let miner = miner_service();
 
@@ -112,7 +120,7 @@ fn sign_and_send_transaction_with_invalid_password() {
 		"id": 1
 	}"#;
 
-	let response = r#"{"jsonrpc":"2.0","error":{"code":-32021,"message":"Account password is invalid or account does not exist.","data":"SStore(InvalidPassword)"},"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32021,"message":"Account password is invalid or account does not exist.","data":{"details":"SStore(InvalidPassword)","kind":"InvalidPassword"}},"id":1}"#;
 
 	assert_eq!(tester.io.handle_request_sync(request.as_ref()), Some(response.into()));
 }
@@ -122,6 +130,52 @@ fn send_transaction() {
 	sign_and_send_test("personal_sendTransaction");
 }
 
+#[test]
+fn should_enforce_vault_policy() {
+	let temp_path = RandomTempPath::new();
+	let keys_dir = RootDiskDirectory::create(temp_path.as_str()).unwrap();
+	let secret_store = EthStore::open(Box::new(keys_dir)).unwrap();
+	let tester = setup_with_accounts_provider(Arc::new(AccountProvider::new(Box::new(secret_store), AccountProviderSettings::default())));
+	let address = tester.accounts.new_account("password123").unwrap();
+	tester.accounts.create_vault("hot", "vault_password").unwrap();
+	tester.accounts.change_vault(address, "hot").unwrap();
+	tester.accounts.set_vault_policy("hot", "vault_password", Some(VaultPolicy {
+		max_value: Some(U256::from(0x9184e72au64)),
+		allowed_recipients: None,
+		allowed_methods: Some(vec!["sendTransaction".into()]),
+	})).unwrap();
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "personal_sendTransaction",
+		"params": [{
+			"from": ""#.to_owned() + format!("0x{:?}", address).as_ref() + r#"",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567",
+			"gas": "0x76c0",
+			"gasPrice": "0x9184e72a000",
+			"value": "0x9184e72b"
+		}, "password123"],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32024,"message":"Request violates vault policy: recipient or value is not allowed"},"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request.as_ref()), Some(response.into()));
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "personal_signTransaction",
+		"params": [{
+			"from": ""#.to_owned() + format!("0x{:?}", address).as_ref() + r#"",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567",
+			"gas": "0x76c0",
+			"gasPrice": "0x9184e72a000",
+			"value": "0x9184e72a"
+		}, "password123"],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32024,"message":"Request violates vault policy: signTransaction is not allowed"},"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request.as_ref()), Some(response.into()));
+}
+
 #[test]
 fn sign_and_send_transaction() {
 	sign_and_send_test("personal_signAndSendTransaction");
@@ -179,6 +233,121 @@ fn sign_and_send_test(method: &str) {
 	assert_eq!(tester.io.handle_request_sync(request.as_ref()), Some(response));
 }
 
+#[test]
+fn should_record_signing_history() {
+	let tester = setup();
+	tester.accounts.set_signing_log(SigningLog::new(Arc::new(::util::kvdb::in_memory(1)), Some(0)));
+	let address = tester.accounts.new_account("password123").unwrap();
+	let request = |password: &str| r#"{
+		"jsonrpc": "2.0",
+		"method": "personal_sendTransaction",
+		"params": [{
+			"from": ""#.to_owned() + format!("0x{:?}", address).as_ref() + r#"",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567",
+			"gas": "0x76c0",
+			"gasPrice": "0x9184e72a000",
+			"value": "0x9184e72a"
+		}, ""# + password + r#""],
+		"id": 1
+	}"#;
+
+	let response = tester.io.handle_request_sync(&request("password123")).unwrap();
+	let response: serde_json::Value = serde_json::from_str(&response).unwrap();
+	tester.io.handle_request_sync(&request("password321")).unwrap();
+
+	let history = tester.accounts.signing_history(10);
+	assert_eq!(history.len(), 2);
+	assert_eq!(history[0].decision, SigningDecision::Failed);
+	assert_eq!(history[0].hash, None);
+	assert_eq!(history[1].account, address);
+	assert_eq!(history[1].method, "personal_sendTransaction");
+	assert_eq!(history[1].decision, SigningDecision::Signed);
+	assert_eq!(format!("0x{:?}", history[1].hash.unwrap()), response["result"].as_str().unwrap());
+}
+
+#[test]
+fn send_transaction_with_session_token() {
+	let tester = setup();
+	let address = tester.accounts.new_account("password123").unwrap();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "personal_unlockAccountWithToken", "params": [""#.to_owned() + format!("0x{:?}", address).as_ref() + r#"", "password123", "0x3c", "0x1"], "id": 1}"#;
+	let response: serde_json::Value = serde_json::from_str(&tester.io.handle_request_sync(request.as_ref()).unwrap()).unwrap();
+	let token = response["result"].as_str().unwrap().to_owned();
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "personal_sendTransaction",
+		"params": [{
+			"from": ""#.to_owned() + format!("0x{:?}", address).as_ref() + r#"",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567",
+			"gas": "0x76c0",
+			"gasPrice": "0x9184e72a000",
+			"value": "0x9184e72a"
+		}, ""# + &token + r#""],
+		"id": 1
+	}"#;
+
+	let t = Transaction {
+		nonce: U256::zero(),
+		gas_price: U256::from(0x9184e72a000u64),
+		gas: U256::from(0x76c0),
+		action: Action::Call(Address::from_str("d46e8dd67c5d32be8058bb8eb970870f07244567").unwrap()),
+		value: U256::from(0x9184e72au64),
+		data: vec![]
+	};
+	let signature = tester.accounts.sign(address, Some("password123".into()), t.hash(None)).unwrap();
+	let t = t.with_signature(signature, None);
+	let response = r#"{"jsonrpc":"2.0","result":""#.to_owned() + format!("0x{:?}", t.hash()).as_ref() + r#"","id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request.as_ref()), Some(response));
+
+	// the token allowed only a single use
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32021,"message":"Account password is invalid or account does not exist.","data":{"details":"SStore(InvalidPassword)","kind":"InvalidPassword"}},"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request.as_ref()), Some(response.into()));
+}
+
+#[test]
+fn sign_transaction() {
+	let tester = setup();
+	let address = tester.accounts.new_account("password123").unwrap();
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "personal_signTransaction",
+		"params": [{
+			"from": ""#.to_owned() + format!("0x{:?}", address).as_ref() + r#"",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567",
+			"gas": "0x76c0",
+			"gasPrice": "0x9184e72a000",
+			"value": "0x9184e72a"
+		}, "password123"],
+		"id": 1
+	}"#;
+
+	let t = Transaction {
+		nonce: U256::zero(),
+		gas_price: U256::from(0x9184e72a000u64),
+		gas: U256::from(0x76c0),
+		action: Action::Call(Address::from_str("d46e8dd67c5d32be8058bb8eb970870f07244567").unwrap()),
+		value: U256::from(0x9184e72au64),
+		data: vec![]
+	};
+	let signature = tester.accounts.sign(address, Some("password123".into()), t.hash(None)).unwrap();
+	let t = SignedTransaction::new(t.with_signature(signature, None)).unwrap();
+	let raw = format!("0x{}", rlp::encode(&t).to_hex());
+
+	let response: serde_json::Value = serde_json::from_str(&tester.io.handle_request_sync(request.as_ref()).unwrap()).unwrap();
+	let result = &response["result"];
+
+	assert_eq!(result["raw"], serde_json::Value::String(raw.clone()));
+	assert_eq!(result["tx"]["raw"], serde_json::Value::String(raw));
+	assert_eq!(result["tx"]["hash"], serde_json::Value::String(format!("0x{:?}", t.hash())));
+	assert_eq!(result["tx"]["from"], serde_json::Value::String(format!("0x{:?}", address)));
+	// signing alone must not submit the transaction
+	assert!(tester.miner.imported_transactions.lock().is_empty());
+	assert!(tester.accounts.sign(address, None, Default::default()).is_err(), "Should not unlock account.");
+}
+
 #[test]
 fn should_unlock_account_temporarily() {
 	let tester = setup();
@@ -28,7 +28,7 @@ use jsonrpc_core::IoHandler;
 use v1::{SignerClient, Signer, Origin};
 use v1::metadata::Metadata;
 use v1::tests::helpers::TestMinerService;
-use v1::helpers::{SigningQueue, SignerService, FilledTransactionRequest, ConfirmationPayload};
+use v1::helpers::{SigningQueue, SignerService, FilledTransactionRequest, ConfirmationPayload, RequestSource};
 use v1::helpers::dispatch::FullDispatcher;
 
 struct SignerTester {
@@ -88,17 +88,22 @@ fn should_return_list_of_items_to_confirm() {
 		data: vec![],
 		nonce: None,
 		condition: None,
-	}), Origin::Dapps("http://parity.io".into())).unwrap();
-	tester.signer.add_request(ConfirmationPayload::Signature(1.into(), vec![5].into()), Origin::Unknown).unwrap();
+	}), RequestSource {
+		origin: Origin::Dapps("http://parity.io".into()),
+		method: "eth_sendTransaction".into(),
+		ip: Some("127.0.0.1".parse().unwrap()),
+	}).unwrap();
+	tester.signer.add_request(ConfirmationPayload::Signature(1.into(), vec![5].into()), Origin::Unknown.into()).unwrap();
+	let created = tester.signer.requests().iter().map(|request| format!("{:#x}", request.created)).collect::<Vec<_>>();
 
 	// when
 	let request = r#"{"jsonrpc":"2.0","method":"signer_requestsToConfirm","params":[],"id":1}"#;
-	let response = concat!(
-		r#"{"jsonrpc":"2.0","result":["#,
-		r#"{"id":"0x1","origin":{"dapp":"http://parity.io"},"payload":{"sendTransaction":{"condition":null,"data":"0x","from":"0x0000000000000000000000000000000000000001","gas":"0x989680","gasPrice":"0x2710","nonce":null,"to":"0xd46e8dd67c5d32be8058bb8eb970870f07244567","value":"0x1"}}},"#,
-		r#"{"id":"0x2","origin":"unknown","payload":{"sign":{"address":"0x0000000000000000000000000000000000000001","data":"0x05"}}}"#,
-		r#"],"id":1}"#
-	);
+	let response = r#"{"jsonrpc":"2.0","result":["#.to_owned() +
+		&format!(r#"{{"created":"{}","expires":null,"#, created[0]) +
+		r#""id":"0x1","ip":"127.0.0.1","method":"eth_sendTransaction","origin":{"dapp":"http://parity.io"},"payload":{"sendTransaction":{"condition":null,"data":"0x","from":"0x0000000000000000000000000000000000000001","gas":"0x989680","gasPrice":"0x2710","nonce":null,"to":"0xd46e8dd67c5d32be8058bb8eb970870f07244567","value":"0x1"}}},"# +
+		&format!(r#"{{"created":"{}","expires":null,"#, created[1]) +
+		r#""id":"0x2","ip":null,"method":"","origin":"unknown","payload":{"sign":{"address":"0x0000000000000000000000000000000000000001","data":"0x05"}}}"# +
+		r#"],"id":1}"#;
 
 	// then
 	assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
@@ -119,7 +124,7 @@ fn should_reject_transaction_from_queue_without_dispatching() {
 		data: vec![],
 		nonce: None,
 		condition: None,
-	}), Origin::Unknown).unwrap();
+	}), Origin::Unknown.into()).unwrap();
 	assert_eq!(tester.signer.requests().len(), 1);
 
 	// when
@@ -146,12 +151,12 @@ fn should_not_remove_transaction_if_password_is_invalid() {
 		data: vec![],
 		nonce: None,
 		condition: None,
-	}), Origin::Unknown).unwrap();
+	}), Origin::Unknown.into()).unwrap();
 	assert_eq!(tester.signer.requests().len(), 1);
 
 	// when
 	let request = r#"{"jsonrpc":"2.0","method":"signer_confirmRequest","params":["0x1",{},"xxx"],"id":1}"#;
-	let response = r#"{"jsonrpc":"2.0","error":{"code":-32021,"message":"Account password is invalid or account does not exist.","data":"SStore(InvalidAccount)"},"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32021,"message":"Account password is invalid or account does not exist.","data":{"details":"SStore(InvalidAccount)","kind":"AccountNotFound"}},"id":1}"#;
 
 	// then
 	assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
@@ -162,12 +167,12 @@ fn should_not_remove_transaction_if_password_is_invalid() {
 fn should_not_remove_sign_if_password_is_invalid() {
 	// given
 	let tester = signer_tester();
-	tester.signer.add_request(ConfirmationPayload::Signature(0.into(), vec![5].into()), Origin::Unknown).unwrap();
+	tester.signer.add_request(ConfirmationPayload::Signature(0.into(), vec![5].into()), Origin::Unknown.into()).unwrap();
 	assert_eq!(tester.signer.requests().len(), 1);
 
 	// when
 	let request = r#"{"jsonrpc":"2.0","method":"signer_confirmRequest","params":["0x1",{},"xxx"],"id":1}"#;
-	let response = r#"{"jsonrpc":"2.0","error":{"code":-32021,"message":"Account password is invalid or account does not exist.","data":"SStore(InvalidAccount)"},"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32021,"message":"Account password is invalid or account does not exist.","data":{"details":"SStore(InvalidAccount)","kind":"AccountNotFound"}},"id":1}"#;
 
 	// then
 	assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
@@ -190,7 +195,7 @@ fn should_confirm_transaction_and_dispatch() {
 		data: vec![],
 		nonce: None,
 		condition: None,
-	}), Origin::Unknown).unwrap();
+	}), Origin::Unknown.into()).unwrap();
 
 	let t = Transaction {
 		nonce: U256::zero(),
@@ -236,7 +241,7 @@ fn should_alter_the_sender_and_nonce() {
 		data: vec![],
 		nonce: Some(10.into()),
 		condition: None,
-	}), Origin::Unknown).unwrap();
+	}), Origin::Unknown.into()).unwrap();
 
 	let t = Transaction {
 		nonce: U256::zero(),
@@ -286,7 +291,7 @@ fn should_confirm_transaction_with_token() {
 		data: vec![],
 		nonce: None,
 		condition: None,
-	}), Origin::Unknown).unwrap();
+	}), Origin::Unknown.into()).unwrap();
 
 	let t = Transaction {
 		nonce: U256::zero(),
@@ -335,7 +340,7 @@ fn should_confirm_transaction_with_rlp() {
 		data: vec![],
 		nonce: None,
 		condition: None,
-	}), Origin::Unknown).unwrap();
+	}), Origin::Unknown.into()).unwrap();
 
 	let t = Transaction {
 		nonce: U256::zero(),
@@ -383,7 +388,7 @@ fn should_return_error_when_sender_does_not_match() {
 		data: vec![],
 		nonce: None,
 		condition: None,
-	}), Origin::Unknown).unwrap();
+	}), Origin::Unknown.into()).unwrap();
 
 	let t = Transaction {
 		nonce: U256::zero(),
@@ -217,6 +217,81 @@ fn should_sign_if_account_is_unlocked() {
 	assert_eq!(tester.signer.requests().len(), 0);
 }
 
+fn typed_data_request(address: &Address, message: &str) -> String {
+	r#"{
+		"jsonrpc": "2.0",
+		"method": "personal_signTypedData",
+		"params": [
+			""#.to_owned() + format!("0x{:?}", address).as_ref() + r#"",
+			{
+				"types": {
+					"EIP712Domain": [{"name": "name", "type": "string"}],
+					"Mail": [{"name": "contents", "type": "string"}]
+				},
+				"primaryType": "Mail",
+				"domain": {"name": "Ether Mail"},
+				"message": "# + message + r#"
+			}
+		],
+		"id": 1
+	}"#
+}
+
+#[test]
+fn should_add_typed_data_sign_to_queue() {
+	use v1::helpers::ConfirmationPayload;
+
+	// given
+	let tester = eth_signing();
+	let address = Address::random();
+	let request = typed_data_request(&address, r#"{"contents": "Hello, Bob!"}"#);
+	let response = r#"{"jsonrpc":"2.0","result":"0x0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000","id":1}"#;
+
+	// when
+	let promise = tester.io.handle_request(&request);
+
+	// the future must be polled at least once before request is queued.
+	let signer = tester.signer.clone();
+	let (queued, payload) = ::std::sync::mpsc::channel();
+	::std::thread::spawn(move || loop {
+		if signer.requests().len() == 1 {
+			queued.send(signer.requests()[0].payload.clone()).unwrap();
+			signer.request_confirmed(1.into(), Ok(ConfirmationResponse::Signature(0.into())));
+			break
+		}
+		::std::thread::sleep(Duration::from_millis(100))
+	});
+
+	// then
+	let res = promise.wait().unwrap();
+	assert_eq!(res, Some(response.to_owned()));
+	match payload.recv().unwrap() {
+		ConfirmationPayload::SignTypedData(request) => {
+			assert_eq!(request.address, address);
+			assert_eq!(request.primary_type, "Mail");
+			assert_eq!(request.fields.len(), 2);
+			assert_eq!(request.fields[1].name, "message.contents");
+			assert_eq!(request.fields[1].value, "Hello, Bob!");
+		},
+		payload => panic!("Unexpected payload: {:?}", payload),
+	}
+}
+
+#[test]
+fn should_reject_invalid_typed_data() {
+	// given
+	let tester = eth_signing();
+	let address = Address::random();
+	let request = typed_data_request(&address, r#"{"contents": "Hello, Bob!", "hidden": "0x01"}"#);
+
+	// when
+	let response: serde_json::Value = serde_json::from_str(&tester.io.handle_request_sync(&request).unwrap()).unwrap();
+
+	// then
+	assert_eq!(response["error"]["code"].as_i64(), Some(-32602));
+	assert_eq!(tester.signer.requests().len(), 0);
+}
+
 #[test]
 fn should_add_transaction_to_queue() {
 	// given
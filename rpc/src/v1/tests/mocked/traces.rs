@@ -14,16 +14,17 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 use ethcore::executed::{CallType, Executed, CallError};
-use ethcore::trace::trace::{Action, Res, Call};
-use ethcore::trace::LocalizedTrace;
+use ethcore::trace::trace::{Action, Res, Call, Suicide};
+use ethcore::trace::{LocalizedTrace, FlatTrace, GasProfile, OpcodeGas, StructLog};
 use ethcore::client::TestBlockChainClient;
 
 use jsonrpc_core::IoHandler;
 use v1::tests::helpers::{TestMinerService};
-use v1::{Traces, TracesClient};
+use v1::{Traces, TracesClient, DEFAULT_TRACE_PAGE_SIZE};
 
 struct Tester {
 	client: Arc<TestBlockChainClient>,
@@ -32,6 +33,10 @@ struct Tester {
 }
 
 fn io() -> Tester {
+	io_with_page_size(DEFAULT_TRACE_PAGE_SIZE)
+}
+
+fn io_with_page_size(page_size: usize) -> Tester {
 	let client = Arc::new(TestBlockChainClient::new());
 	*client.traces.write() = Some(vec![LocalizedTrace {
 		action: Action::Call(Call {
@@ -62,9 +67,11 @@ fn io() -> Tester {
 		trace: vec![],
 		vm_trace: None,
 		state_diff: None,
+		gas_profile: None,
+		struct_logs: None,
 	}));
 	let miner = Arc::new(TestMinerService::default());
-	let traces = TracesClient::new(&client, &miner);
+	let traces = TracesClient::new(&client, &miner, page_size);
 	let mut io = IoHandler::new();
 	io.extend_with(traces.to_delegate());
 
@@ -168,13 +175,95 @@ fn rpc_trace_call() {
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_trace_call_with_overrides() {
+	let tester = io();
+
+	let request = r#"{"jsonrpc":"2.0","method":"trace_callWithOverrides","params":[{}, ["stateDiff", "vmTrace", "trace"], "latest", {"0x0000000000000000000000000000000000000010":{"balance":"0x1","code":"0x6000"}}],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"output":"0x010203","stateDiff":null,"trace":[],"vmTrace":null},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_trace_call_many() {
+	let tester = io();
+
+	let request = r#"{"jsonrpc":"2.0","method":"trace_callMany","params":[[[{}, ["stateDiff"]], [{}, ["trace"]]], "latest"],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"output":"0x010203","stateDiff":null,"trace":[],"vmTrace":null},{"output":"0x010203","stateDiff":null,"trace":[],"vmTrace":null}],"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_trace_call_with_gas_profile() {
+	let tester = io();
+	if let Some(Ok(ref mut executed)) = *tester.client.execution_result.write() {
+		executed.gas_profile = Some(GasProfile {
+			instructions: vec![
+				OpcodeGas { instruction: 0x55, count: 2, gas: 40_000.into() },
+				OpcodeGas { instruction: 0x60, count: 3, gas: 9.into() },
+			],
+		});
+	}
+
+	let request = r#"{"jsonrpc":"2.0","method":"trace_callWithGasProfile","params":[{}],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"gasUsed":"0x2710","output":"0x010203","profile":[{"count":"0x2","gas":"0x9c40","op":"SSTORE"},{"count":"0x3","gas":"0x9","op":"PUSH1"}]},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_debug_trace_transaction() {
+	let tester = io();
+	if let Some(Ok(ref mut executed)) = *tester.client.execution_result.write() {
+		executed.struct_logs = Some(vec![
+			StructLog {
+				pc: 0,
+				instruction: 0x60,
+				gas: 100.into(),
+				gas_cost: 3.into(),
+				depth: 1,
+				stack: Some(vec![]),
+				memory: Some(vec![]),
+				storage: Some(BTreeMap::new()),
+			},
+			StructLog {
+				pc: 2,
+				instruction: 0x54,
+				gas: 97.into(),
+				gas_cost: 200.into(),
+				depth: 1,
+				stack: Some(vec![1.into()]),
+				memory: None,
+				storage: None,
+			},
+		]);
+	}
+
+	let request = r#"{"jsonrpc":"2.0","method":"debug_traceTransaction","params":["0x0000000000000000000000000000000000000000000000000000000000000005", {"disableMemory":true,"disableStorage":true}],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"failed":false,"gas":10000,"returnValue":"010203","structLogs":[{"depth":1,"gas":100,"gasCost":3,"memory":[],"op":"PUSH1","pc":0,"stack":[],"storage":{}},{"depth":1,"gas":97,"gasCost":200,"op":"SLOAD","pc":2,"stack":["0000000000000000000000000000000000000000000000000000000000000001"]}]},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_debug_trace_transaction_invalid_tracer() {
+	let tester = io();
+
+	let request = r#"{"jsonrpc":"2.0","method":"debug_traceTransaction","params":["0x0000000000000000000000000000000000000000000000000000000000000005", {"tracer":"gas > 10"}],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: tracer","data":"\"Unknown field 'gas'\""},"id":1}"#;
+
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_trace_call_state_pruned() {
 	let tester = io();
 	*tester.client.execution_result.write() = Some(Err(CallError::StatePruned));
 
 	let request = r#"{"jsonrpc":"2.0","method":"trace_call","params":[{}, ["stateDiff", "vmTrace", "trace"]],"id":1}"#;
-	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"This request is not supported because your node is running with state pruning. Run with --pruning=archive."},"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32004,"message":"This request is not supported because your node is running with state pruning. Run with --pruning=archive.","data":{"kind":"StatePruned"}},"id":1}"#;
 
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
@@ -195,7 +284,7 @@ fn rpc_trace_raw_transaction_state_pruned() {
 	*tester.client.execution_result.write() = Some(Err(CallError::StatePruned));
 
 	let request = r#"{"jsonrpc":"2.0","method":"trace_rawTransaction","params":["0xf869018609184e72a0008276c094d46e8dd67c5d32be8058bb8eb970870f07244567849184e72a801ba0617f39c1a107b63302449c476d96a6cb17a5842fc98ff0c5bcf4d5c4d8166b95a009fdb6097c6196b9bbafc3a59f02f38d91baeef23d0c60a8e4f23c7714cea3a9", ["stateDiff", "vmTrace", "trace"]],"id":1}"#;
-	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"This request is not supported because your node is running with state pruning. Run with --pruning=archive."},"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32004,"message":"This request is not supported because your node is running with state pruning. Run with --pruning=archive.","data":{"kind":"StatePruned"}},"id":1}"#;
 
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
@@ -210,13 +299,42 @@ fn rpc_trace_replay_transaction() {
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_trace_replay_transaction_paginated() {
+	let tester = io_with_page_size(1);
+	let suicide = |address: Vec<usize>| FlatTrace {
+		action: Action::Suicide(Suicide {
+			address: 1.into(),
+			refund_address: 2.into(),
+			balance: 0.into(),
+		}),
+		result: Res::None,
+		subtraces: 0,
+		trace_address: address.into_iter().collect(),
+	};
+	if let Some(Ok(ref mut executed)) = *tester.client.execution_result.write() {
+		executed.trace = vec![suicide(vec![0]), suicide(vec![1])];
+	}
+
+	let request = r#"{"jsonrpc":"2.0","method":"trace_replayTransaction","params":["0x0000000000000000000000000000000000000000000000000000000000000005", ["trace"]],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"continuation":"0x0","output":"0x010203","stateDiff":null,"trace":[{"action":{"address":"0x0000000000000000000000000000000000000001","balance":"0x0","refundAddress":"0x0000000000000000000000000000000000000002"},"result":null,"subtraces":0,"traceAddress":[0],"type":"suicide"}],"vmTrace":null},"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc":"2.0","method":"trace_continue","params":["0x0"],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"continuation":null,"trace":[{"action":{"address":"0x0000000000000000000000000000000000000001","balance":"0x0","refundAddress":"0x0000000000000000000000000000000000000002"},"result":null,"subtraces":0,"traceAddress":[1],"type":"suicide"}]},"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: token","data":"\"Unknown or expired continuation token\""},"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_trace_replay_transaction_state_pruned() {
 	let tester = io();
 	*tester.client.execution_result.write() = Some(Err(CallError::StatePruned));
 
 	let request = r#"{"jsonrpc":"2.0","method":"trace_replayTransaction","params":["0x0000000000000000000000000000000000000000000000000000000000000005", ["trace", "stateDiff", "vmTrace"]],"id":1}"#;
-	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"This request is not supported because your node is running with state pruning. Run with --pruning=archive."},"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32004,"message":"This request is not supported because your node is running with state pruning. Run with --pruning=archive.","data":{"kind":"StatePruned"}},"id":1}"#;
 
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
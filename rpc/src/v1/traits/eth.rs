@@ -20,8 +20,8 @@ use jsonrpc_macros::Trailing;
 
 use futures::BoxFuture;
 
-use v1::types::{RichBlock, BlockNumber, Bytes, CallRequest, Filter, FilterChanges, Index};
-use v1::types::{Log, Receipt, SyncStatus, Transaction, Work};
+use v1::types::{RichBlock, BlockNumber, Bytes, CallRequest, FeeHistory, Filter, FilterChanges, Index, StateOverride};
+use v1::types::{Log, Receipt, SyncStatus, Transaction, TransactionFilter, Work};
 use v1::types::{H64, H160, H256, U256};
 
 build_rpc_trait! {
@@ -53,6 +53,11 @@ build_rpc_trait! {
 		#[rpc(name = "eth_gasPrice")]
 		fn gas_price(&self) -> Result<U256, Error>;
 
+		/// Returns gas usage ratios and transaction gas price percentiles of the given number of
+		/// blocks ending with the given one.
+		#[rpc(name = "eth_feeHistory")]
+		fn fee_history(&self, U256, BlockNumber, Trailing<Vec<f64>>) -> Result<FeeHistory, Error>;
+
 		/// Returns accounts list.
 		#[rpc(meta, name = "eth_accounts")]
 		fn accounts(&self, Self::Metadata) -> BoxFuture<Vec<H160>, Error>;
@@ -113,6 +118,14 @@ build_rpc_trait! {
 		#[rpc(async, name = "eth_call")]
 		fn call(&self, CallRequest, Trailing<BlockNumber>) -> BoxFuture<Bytes, Error>;
 
+		/// Call contract against a state with the given accounts modified, returning the output data.
+		#[rpc(async, name = "eth_callWithOverrides")]
+		fn call_with_overrides(&self, CallRequest, BlockNumber, StateOverride) -> BoxFuture<Bytes, Error>;
+
+		/// Call contracts in order, each seeing the changes made by the ones before, returning their output data.
+		#[rpc(async, name = "eth_callMany")]
+		fn call_many(&self, Vec<CallRequest>, Trailing<BlockNumber>) -> BoxFuture<Vec<Bytes>, Error>;
+
 		/// Estimate gas needed for execution of given contract.
 		#[rpc(async, name = "eth_estimateGas")]
 		fn estimate_gas(&self, CallRequest, Trailing<BlockNumber>) -> BoxFuture<U256, Error>;
@@ -195,6 +208,10 @@ build_rpc_trait! {
 		#[rpc(name = "eth_newPendingTransactionFilter")]
 		fn new_pending_transaction_filter(&self) -> Result<U256, Error>;
 
+		/// Returns id of new filter of the pending transactions matching the given predicates.
+		#[rpc(name = "parity_pendingTransactionsFilter")]
+		fn new_matching_pending_transaction_filter(&self, TransactionFilter) -> Result<U256, Error>;
+
 		/// Returns filter changes since last poll.
 		#[rpc(name = "eth_getFilterChanges")]
 		fn filter_changes(&self, Index) -> Result<FilterChanges, Error>;
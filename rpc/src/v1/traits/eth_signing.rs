@@ -19,7 +19,7 @@
 use jsonrpc_core::Error;
 use futures::BoxFuture;
 
-use v1::types::{Bytes, H160, H256, H520, TransactionRequest, RichRawTransaction};
+use v1::types::{Bytes, H160, H256, H520, TransactionRequest, RichRawTransaction, TypedData};
 
 build_rpc_trait! {
 	/// Signing methods implementation relying on unlocked accounts.
@@ -30,6 +30,11 @@ build_rpc_trait! {
 		#[rpc(meta, name = "eth_sign")]
 		fn sign(&self, Self::Metadata, H160, Bytes) -> BoxFuture<H520, Error>;
 
+		/// Signs the hash of typed structured data (EIP-712) with given address signature.
+		/// The request goes through the same confirmation path as `eth_sign`.
+		#[rpc(meta, name = "personal_signTypedData")]
+		fn sign_typed_data(&self, Self::Metadata, H160, TypedData) -> BoxFuture<H520, Error>;
+
 		/// Sends transaction; will block waiting for signer to return the
 		/// transaction hash.
 		/// If Signer is disable it will require the account to be unlocked.
@@ -18,6 +18,7 @@
 
 pub mod web3;
 pub mod eth;
+pub mod eth_pubsub;
 pub mod eth_signing;
 pub mod net;
 pub mod parity;
@@ -31,6 +32,7 @@ pub mod rpc;
 
 pub use self::web3::Web3;
 pub use self::eth::{Eth, EthFilter};
+pub use self::eth_pubsub::EthPubSub;
 pub use self::eth_signing::EthSigning;
 pub use self::net::Net;
 pub use self::parity::Parity;
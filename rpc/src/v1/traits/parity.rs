@@ -28,7 +28,8 @@ use v1::types::{
 	TransactionStats, LocalTransactionStatus,
 	BlockNumber, ConsensusCapability, VersionInfo,
 	OperationsInfo, DappId, ChainStatus,
-	AccountInfo, HwAccountInfo,
+	AccountInfo, HwAccountInfo, CallRequest, ExecutionProof, AccountProof, DbStats, DatabaseStatistics,
+	EvmStats, Receipt, Health, TxPoolStatus, TxPoolSender, StratumWorker, MineStats, ReorgStats,
 };
 
 build_rpc_trait! {
@@ -130,6 +131,11 @@ build_rpc_trait! {
 		#[rpc(name = "parity_listStorageKeys")]
 		fn list_storage_keys(&self, H160, u64, Option<H256>, Trailing<BlockNumber>) -> Result<Option<Vec<H256>>, Error>;
 
+		/// Returns the address or storage key hashing to the given secure trie key if preimages
+		/// are recorded (`--key-preimages`), or null if not known.
+		#[rpc(name = "parity_keyPreimage")]
+		fn key_preimage(&self, H256) -> Result<Option<Bytes>, Error>;
+
 		/// Encrypt some data with a public key under ECIES.
 		/// First parameter is the 512-byte destination public key, second is the message.
 		#[rpc(name = "parity_encryptMessage")]
@@ -139,6 +145,15 @@ build_rpc_trait! {
 		#[rpc(name = "parity_pendingTransactions")]
 		fn pending_transactions(&self) -> Result<Vec<Transaction>, Error>;
 
+		/// Returns the number of ready and queued (future-nonce) transactions in the queue.
+		#[rpc(name = "parity_txpoolStatus")]
+		fn txpool_status(&self) -> Result<TxPoolStatus, Error>;
+
+		/// Returns ready and queued transactions grouped by sender, with the nonce gaps
+		/// holding queued transactions back and transactions worth replacing.
+		#[rpc(name = "parity_txpoolContent")]
+		fn txpool_content(&self) -> Result<BTreeMap<H160, TxPoolSender>, Error>;
+
 		/// Returns all future transactions from transaction queue.
 		#[rpc(name = "parity_futureTransactions")]
 		fn future_transactions(&self) -> Result<Vec<Transaction>, Error>;
@@ -151,6 +166,22 @@ build_rpc_trait! {
 		#[rpc(name = "parity_localTransactions")]
 		fn local_transactions(&self) -> Result<BTreeMap<H256, LocalTransactionStatus>, Error>;
 
+		/// Returns senders currently banned from the transaction queue.
+		#[rpc(name = "parity_bannedTransactionSenders")]
+		fn banned_transaction_senders(&self) -> Result<Vec<H160>, Error>;
+
+		/// Returns statistics of blocks mined by the node, including included and missed uncles.
+		#[rpc(name = "parity_mineStats")]
+		fn mine_stats(&self) -> Result<MineStats, Error>;
+
+		/// Returns statistics of transactions re-imported to the queue after chain reorganisations.
+		#[rpc(name = "parity_reorgStats")]
+		fn reorg_stats(&self) -> Result<ReorgStats, Error>;
+
+		/// Returns workers connected to the stratum server with their share statistics.
+		#[rpc(name = "parity_stratumWorkers")]
+		fn stratum_workers(&self) -> Result<Vec<StratumWorker>, Error>;
+
 		/// Returns current Trusted Signer port or an error if signer is disabled.
 		#[rpc(name = "parity_signerPort")]
 		fn signer_port(&self) -> Result<u16, Error>;
@@ -191,12 +222,47 @@ build_rpc_trait! {
 		#[rpc(name = "parity_releasesInfo")]
 		fn releases_info(&self) -> Result<Option<OperationsInfo>, Error>;
 
-		/// Get the current chain status.
+		/// Get the current chain status: the gap left by warp sync, the earliest blocks
+		/// with state and with bodies and receipts, and warp restoration progress.
 		#[rpc(name = "parity_chainStatus")]
 		fn chain_status(&self) -> Result<ChainStatus, Error>;
 
 		/// Get node kind info.
 		#[rpc(name = "parity_nodeKind")]
 		fn node_kind(&self) -> Result<::v1::types::NodeKind, Error>;
+
+		/// Returns node health: sync lag, peer count, clock drift and free disk space,
+		/// each with a status level.
+		#[rpc(name = "parity_nodeHealth")]
+		fn node_health(&self) -> Result<Health, Error>;
+
+		/// Executes a call at the given block, returning its output along with every
+		/// state trie node read during execution.
+		#[rpc(name = "parity_executeWithProof")]
+		fn execute_with_proof(&self, CallRequest, Trailing<BlockNumber>) -> Result<ExecutionProof, Error>;
+
+		/// Returns an account and the given storage keys of it at a block, with the trie nodes
+		/// proving them against the block's state root. Works for any block whose state is
+		/// still in the database.
+		#[rpc(name = "parity_getProof")]
+		fn get_proof(&self, H160, Vec<H256>, Trailing<BlockNumber>) -> Result<AccountProof, Error>;
+
+		/// Returns statistics about the state database, including the progress of
+		/// online journal compaction.
+		#[rpc(name = "parity_dbStats")]
+		fn db_stats(&self) -> Result<DbStats, Error>;
+
+		/// Returns per-column access statistics for the backing database.
+		#[rpc(name = "parity_dbStatistics")]
+		fn db_statistics(&self) -> Result<DatabaseStatistics, Error>;
+
+		/// Returns how many times the interpreter and the JIT were used to run code,
+		/// and how many contracts are run often enough to be picked for the JIT.
+		#[rpc(name = "parity_evmStats")]
+		fn evm_stats(&self) -> Result<EvmStats, Error>;
+
+		/// Returns the receipts of all transactions in the given block, in transaction order.
+		#[rpc(name = "parity_getBlockReceipts")]
+		fn block_receipts(&self, BlockNumber) -> Result<Vec<Receipt>, Error>;
 	}
 }
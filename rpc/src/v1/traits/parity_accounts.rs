@@ -19,7 +19,7 @@ use std::collections::BTreeMap;
 
 use jsonrpc_core::Error;
 use ethstore::KeyFile;
-use v1::types::{H160, H256, DappId, DeriveHash, DeriveHierarchical};
+use v1::types::{H160, H256, DappId, DeriveHash, DeriveHierarchical, VaultPolicy, SigningLogEntry};
 
 build_rpc_trait! {
 	/// Personal Parity rpc interface.
@@ -166,6 +166,18 @@ build_rpc_trait! {
 		#[rpc(name = "parity_setVaultMeta")]
 		fn set_vault_meta(&self, String, String) -> Result<bool, Error>;
 
+		/// Get vault signing policy.
+		#[rpc(name = "parity_getVaultPolicy")]
+		fn get_vault_policy(&self, String) -> Result<Option<VaultPolicy>, Error>;
+
+		/// Set vault signing policy, given the vault password. Passing `null` removes the policy.
+		#[rpc(name = "parity_setVaultPolicy")]
+		fn set_vault_policy(&self, String, String, Option<VaultPolicy>) -> Result<bool, Error>;
+
+		/// Returns given number of most recent signing operations, newest first.
+		#[rpc(name = "parity_signingHistory")]
+		fn signing_history(&self, usize) -> Result<Vec<SigningLogEntry>, Error>;
+
 		/// Derive new address from given account address using specific hash.
 		/// Resulting address can be either saved as a new account (with the same password).
 		#[rpc(name = "parity_deriveAddressHash")]
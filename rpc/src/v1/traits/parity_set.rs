@@ -36,6 +36,11 @@ build_rpc_trait! {
 		#[rpc(name = "parity_setGasCeilTarget")]
 		fn set_gas_ceil_target(&self, U256) -> Result<bool, Error>;
 
+		/// Sets the gas limit mined blocks are gradually moved towards.
+		/// `0` disables it in favour of the floor and ceiling targets.
+		#[rpc(name = "parity_setGasLimitTarget")]
+		fn set_gas_limit_target(&self, U256) -> Result<bool, Error>;
+
 		/// Sets new extra data for mined blocks.
 		#[rpc(name = "parity_setExtraData")]
 		fn set_extra_data(&self, Bytes) -> Result<bool, Error>;
@@ -56,6 +61,11 @@ build_rpc_trait! {
 		#[rpc(name = "parity_setMaxTransactionGas")]
 		fn set_tx_gas_limit(&self, U256) -> Result<bool, Error>;
 
+		/// Sets transactions to be included first in the block prepared for sealing.
+		/// Only used when the node is configured with `hint` block ordering.
+		#[rpc(name = "parity_setTransactionOrderingHint")]
+		fn set_transaction_ordering_hint(&self, Vec<H256>) -> Result<bool, Error>;
+
 		/// Add a reserved peer.
 		#[rpc(name = "parity_addReservedPeer")]
 		fn add_reserved_peer(&self, String) -> Result<bool, Error>;
@@ -112,5 +122,10 @@ build_rpc_trait! {
 		/// Returns `true` when transaction was removed, `false` if it was not found.
 		#[rpc(name = "parity_removeTransaction")]
 		fn remove_transaction(&self, H256) -> Result<Option<Transaction>, Error>;
+
+		/// Copies a consistent image of the node's database to the given directory on the
+		/// node's host, which must not hold a database already. The node keeps running.
+		#[rpc(name = "parity_backupDatabase")]
+		fn backup_database(&self, String) -> Result<bool, Error>;
 	}
 }
@@ -19,7 +19,7 @@ use jsonrpc_core::Error;
 
 use futures::BoxFuture;
 
-use v1::types::{U128, H160, H256, TransactionRequest};
+use v1::types::{U128, H160, H256, TransactionRequest, RichRawTransaction};
 
 build_rpc_trait! {
 	/// Personal rpc interface. Safe (read-only) functions.
@@ -39,6 +39,17 @@ build_rpc_trait! {
 		#[rpc(name = "personal_unlockAccount")]
 		fn unlock_account(&self, H160, String, Option<U128>) -> Result<bool, Error>;
 
+		/// Unlocks specified account for a session limited by a TTL (in seconds) and optionally by a number of uses.
+		/// Returns a token which is accepted instead of the password by `personal_signTransaction`
+		/// and `personal_sendTransaction`. The account itself stays locked.
+		#[rpc(name = "personal_unlockAccountWithToken")]
+		fn unlock_account_with_token(&self, H160, String, U128, Option<U128>) -> Result<String, Error>;
+
+		/// Signs transaction without broadcasting it. The account is not unlocked in such case.
+		/// Returns the signed RLP together with the decoded transaction.
+		#[rpc(meta, name = "personal_signTransaction")]
+		fn sign_transaction(&self, Self::Metadata, TransactionRequest, String) -> BoxFuture<RichRawTransaction, Error>;
+
 		/// Sends transaction and signs it in single call. The account is not unlocked in such case.
 		#[rpc(meta, name = "personal_sendTransaction")]
 		fn send_transaction(&self, Self::Metadata, TransactionRequest, String) -> BoxFuture<H256, Error>;
@@ -18,7 +18,7 @@
 
 use jsonrpc_core::Error;
 use jsonrpc_macros::Trailing;
-use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, Index, CallRequest, Bytes, TraceResults, H256};
+use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, Index, CallRequest, Bytes, TraceResults, TracePage, GasProfileResults, StructLogConfig, StructLogResults, StateOverride, H256, U256};
 
 build_rpc_trait! {
 	/// Traces specific rpc interface.
@@ -43,6 +43,18 @@ build_rpc_trait! {
 		#[rpc(name = "trace_call")]
 		fn call(&self, CallRequest, Vec<String>, Trailing<BlockNumber>) -> Result<TraceResults, Error>;
 
+		/// Executes the given call against a state with the given accounts modified and returns a number of possible traces for it.
+		#[rpc(name = "trace_callWithOverrides")]
+		fn call_with_overrides(&self, CallRequest, Vec<String>, BlockNumber, StateOverride) -> Result<TraceResults, Error>;
+
+		/// Executes the given calls in order, each seeing the changes made by the ones before, and returns a number of possible traces for each.
+		#[rpc(name = "trace_callMany")]
+		fn call_many(&self, Vec<(CallRequest, Vec<String>)>, Trailing<BlockNumber>) -> Result<Vec<TraceResults>, Error>;
+
+		/// Executes the given call and returns the gas used by each instruction executed.
+		#[rpc(name = "trace_callWithGasProfile")]
+		fn call_with_gas_profile(&self, CallRequest, Trailing<BlockNumber>) -> Result<GasProfileResults, Error>;
+
 		/// Executes the given raw transaction and returns a number of possible traces for it.
 		#[rpc(name = "trace_rawTransaction")]
 		fn raw_transaction(&self, Bytes, Vec<String>, Trailing<BlockNumber>) -> Result<TraceResults, Error>;
@@ -50,5 +62,13 @@ build_rpc_trait! {
 		/// Executes the transaction with the given hash and returns a number of possible traces for it.
 		#[rpc(name = "trace_replayTransaction")]
 		fn replay_transaction(&self, H256, Vec<String>) -> Result<TraceResults, Error>;
+
+		/// Returns the next page of a trace too large to be returned at once.
+		#[rpc(name = "trace_continue")]
+		fn continue_trace(&self, U256) -> Result<TracePage, Error>;
+
+		/// Executes the transaction with the given hash and returns every step executed.
+		#[rpc(name = "debug_traceTransaction")]
+		fn debug_trace_transaction(&self, H256, Trailing<StructLogConfig>) -> Result<StructLogResults, Error>;
 	}
 }
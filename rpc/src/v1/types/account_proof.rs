@@ -0,0 +1,79 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Merkle proofs of an account and its storage.
+
+use v1::types::{Bytes, H160, H256, U256};
+
+/// An account at some block together with the state trie nodes proving it.
+#[derive(Debug, Serialize)]
+pub struct AccountProof {
+	/// The account address.
+	pub address: H160,
+	/// State trie nodes on the path to the account, from the root.
+	#[serde(rename="accountProof")]
+	pub account_proof: Vec<Bytes>,
+	/// Account balance.
+	pub balance: U256,
+	/// Hash of the account code.
+	#[serde(rename="codeHash")]
+	pub code_hash: H256,
+	/// Account nonce.
+	pub nonce: U256,
+	/// Root of the account storage trie.
+	#[serde(rename="storageHash")]
+	pub storage_hash: H256,
+	/// Proofs of the requested storage keys.
+	#[serde(rename="storageProof")]
+	pub storage_proof: Vec<StorageProof>,
+}
+
+/// A storage value together with the storage trie nodes proving it.
+#[derive(Debug, Serialize)]
+pub struct StorageProof {
+	/// The storage key.
+	pub key: H256,
+	/// Value stored under the key.
+	pub value: U256,
+	/// Storage trie nodes on the path to the key, from the root.
+	pub proof: Vec<Bytes>,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::{AccountProof, StorageProof};
+
+	#[test]
+	fn account_proof_serialization() {
+		let proof = AccountProof {
+			address: 1u64.into(),
+			account_proof: vec![vec![0xc0].into()],
+			balance: 2.into(),
+			code_hash: 3u64.into(),
+			nonce: 4.into(),
+			storage_hash: 5u64.into(),
+			storage_proof: vec![StorageProof {
+				key: 6u64.into(),
+				value: 7.into(),
+				proof: vec![],
+			}],
+		};
+
+		let serialized = serde_json::to_string(&proof).unwrap();
+		assert_eq!(serialized, r#"{"address":"0x0000000000000000000000000000000000000001","accountProof":["0xc0"],"balance":"0x2","codeHash":"0x0000000000000000000000000000000000000000000000000000000000000003","nonce":"0x4","storageHash":"0x0000000000000000000000000000000000000000000000000000000000000005","storageProof":[{"key":"0x0000000000000000000000000000000000000000000000000000000000000006","value":"0x7","proof":[]}]}"#);
+	}
+}
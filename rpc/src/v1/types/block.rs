@@ -18,6 +18,7 @@ use std::ops::Deref;
 use std::collections::BTreeMap;
 use serde::{Serialize, Serializer};
 use serde::ser::Error;
+use ethcore::header::Header as EthHeader;
 use v1::types::{Bytes, Transaction, H160, H256, H2048, U256};
 
 /// Block Transactions
@@ -96,6 +97,80 @@ pub struct Block {
 	pub size: Option<U256>,
 }
 
+/// Block header representation.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Header {
+	/// Hash of the block
+	pub hash: Option<H256>,
+	/// Hash of the parent
+	#[serde(rename="parentHash")]
+	pub parent_hash: H256,
+	/// Hash of the uncles
+	#[serde(rename="sha3Uncles")]
+	pub uncles_hash: H256,
+	/// Authors address
+	pub author: H160,
+	// TODO: get rid of this one
+	/// ?
+	pub miner: H160,
+	/// State root hash
+	#[serde(rename="stateRoot")]
+	pub state_root: H256,
+	/// Transactions root hash
+	#[serde(rename="transactionsRoot")]
+	pub transactions_root: H256,
+	/// Transactions receipts root hash
+	#[serde(rename="receiptsRoot")]
+	pub receipts_root: H256,
+	/// Block number
+	pub number: Option<U256>,
+	/// Gas Used
+	#[serde(rename="gasUsed")]
+	pub gas_used: U256,
+	/// Gas Limit
+	#[serde(rename="gasLimit")]
+	pub gas_limit: U256,
+	/// Extra data
+	#[serde(rename="extraData")]
+	pub extra_data: Bytes,
+	/// Logs bloom
+	#[serde(rename="logsBloom")]
+	pub logs_bloom: H2048,
+	/// Timestamp
+	pub timestamp: U256,
+	/// Difficulty
+	pub difficulty: U256,
+	/// Seal fields
+	#[serde(rename="sealFields")]
+	pub seal_fields: Vec<Bytes>,
+	/// Size in bytes
+	pub size: Option<U256>,
+}
+
+impl<'a> From<&'a EthHeader> for Header {
+	fn from(h: &'a EthHeader) -> Self {
+		Header {
+			hash: Some(h.hash().into()),
+			size: Some(::rlp::encode(h).len().into()),
+			parent_hash: h.parent_hash().clone().into(),
+			uncles_hash: h.uncles_hash().clone().into(),
+			author: h.author().clone().into(),
+			miner: h.author().clone().into(),
+			state_root: h.state_root().clone().into(),
+			transactions_root: h.transactions_root().clone().into(),
+			receipts_root: h.receipts_root().clone().into(),
+			number: Some(h.number().into()),
+			gas_used: h.gas_used().clone().into(),
+			gas_limit: h.gas_limit().clone().into(),
+			logs_bloom: h.log_bloom().clone().into(),
+			timestamp: h.timestamp().into(),
+			difficulty: h.difficulty().clone().into(),
+			seal_fields: h.seal().into_iter().cloned().map(Into::into).collect(),
+			extra_data: h.extra_data().clone().into(),
+		}
+	}
+}
+
 /// Block representation with additional info
 #[derive(Debug)]
 pub struct RichBlock {
@@ -21,7 +21,7 @@ use serde::{Serialize, Serializer};
 use util::Colour;
 use util::bytes::ToPretty;
 
-use v1::types::{U256, TransactionRequest, RichRawTransaction, H160, H256, H520, Bytes, TransactionCondition, Origin};
+use v1::types::{U256, TransactionRequest, RichRawTransaction, H160, H256, H520, Bytes, TransactionCondition, Origin, TypedDataField};
 use v1::helpers;
 
 /// Confirmation waiting in a queue
@@ -34,6 +34,14 @@ pub struct ConfirmationRequest {
 	pub payload: ConfirmationPayload,
 	/// Request origin
 	pub origin: Origin,
+	/// RPC method that created the request
+	pub method: String,
+	/// IP address of the requesting client, if known
+	pub ip: Option<String>,
+	/// UNIX timestamp of the moment the request was queued
+	pub created: U256,
+	/// UNIX timestamp after which the request is rejected automatically
+	pub expires: Option<U256>,
 }
 
 impl From<helpers::ConfirmationRequest> for ConfirmationRequest {
@@ -42,6 +50,10 @@ impl From<helpers::ConfirmationRequest> for ConfirmationRequest {
 			id: c.id.into(),
 			payload: c.payload.into(),
 			origin: c.origin,
+			method: c.method,
+			ip: c.ip.map(|ip| ip.to_string()),
+			created: c.created.into(),
+			expires: c.expires.map(Into::into),
 		}
 	}
 }
@@ -58,6 +70,7 @@ impl fmt::Display for ConfirmationPayload {
 			ConfirmationPayload::SendTransaction(ref transaction) => write!(f, "{}", transaction),
 			ConfirmationPayload::SignTransaction(ref transaction) => write!(f, "(Sign only) {}", transaction),
 			ConfirmationPayload::Signature(ref sign) => write!(f, "{}", sign),
+			ConfirmationPayload::SignTypedData(ref sign) => write!(f, "{}", sign),
 			ConfirmationPayload::Decrypt(ref decrypt) => write!(f, "{}", decrypt),
 		}
 	}
@@ -93,6 +106,33 @@ impl fmt::Display for SignRequest {
 	}
 }
 
+/// Typed data sign request
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SignTypedDataRequest {
+	/// Address
+	pub address: H160,
+	/// Hash of the typed data to sign
+	pub hash: H256,
+	/// Struct type of the signed message
+	#[serde(rename="primaryType")]
+	pub primary_type: String,
+	/// Signed fields in a human-readable form
+	pub fields: Vec<TypedDataField>,
+}
+
+impl fmt::Display for SignTypedDataRequest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(
+			f,
+			"sign typed data {} (0x{:?}) with {}",
+			self.primary_type,
+			self.hash,
+			Colour::White.bold().paint(format!("0x{:?}", self.address)),
+		)
+	}
+}
+
 /// Decrypt request
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -170,6 +210,9 @@ pub enum ConfirmationPayload {
 	/// Signature
 	#[serde(rename="sign")]
 	Signature(SignRequest),
+	/// Typed data signature
+	#[serde(rename="signTypedData")]
+	SignTypedData(SignTypedDataRequest),
 	/// Decryption
 	#[serde(rename="decrypt")]
 	Decrypt(DecryptRequest),
@@ -184,6 +227,12 @@ impl From<helpers::ConfirmationPayload> for ConfirmationPayload {
 				address: address.into(),
 				data: data.into(),
 			}),
+			helpers::ConfirmationPayload::SignTypedData(request) => ConfirmationPayload::SignTypedData(SignTypedDataRequest {
+				address: request.address.into(),
+				hash: request.hash.into(),
+				primary_type: request.primary_type,
+				fields: request.fields,
+			}),
 			helpers::ConfirmationPayload::Decrypt(address, msg) => ConfirmationPayload::Decrypt(DecryptRequest {
 				address: address.into(),
 				msg: msg.into(),
@@ -246,7 +295,7 @@ impl<A, B> Serialize for Either<A, B>  where
 mod tests {
 	use std::str::FromStr;
 	use serde_json;
-	use v1::types::{U256, H256, TransactionCondition};
+	use v1::types::{U256, H256, TransactionCondition, TypedDataField};
 	use v1::helpers;
 	use super::*;
 
@@ -257,11 +306,15 @@ mod tests {
 			id: 15.into(),
 			payload: helpers::ConfirmationPayload::Signature(1.into(), vec![5].into()),
 			origin: Origin::Rpc("test service".into()),
+			method: "eth_sign".into(),
+			ip: Some("127.0.0.1".parse().unwrap()),
+			created: 1_500_000_000,
+			expires: Some(1_500_000_060),
 		};
 
 		// when
 		let res = serde_json::to_string(&ConfirmationRequest::from(request));
-		let expected = r#"{"id":"0xf","payload":{"sign":{"address":"0x0000000000000000000000000000000000000001","data":"0x05"}},"origin":{"rpc":"test service"}}"#;
+		let expected = r#"{"id":"0xf","payload":{"sign":{"address":"0x0000000000000000000000000000000000000001","data":"0x05"}},"origin":{"rpc":"test service"},"method":"eth_sign","ip":"127.0.0.1","created":"0x59682f00","expires":"0x59682f3c"}"#;
 
 		// then
 		assert_eq!(res.unwrap(), expected.to_owned());
@@ -284,11 +337,15 @@ mod tests {
 				condition: None,
 			}),
 			origin: Origin::Signer(5.into()),
+			method: "eth_sendTransaction".into(),
+			ip: None,
+			created: 0,
+			expires: None,
 		};
 
 		// when
 		let res = serde_json::to_string(&ConfirmationRequest::from(request));
-		let expected = r#"{"id":"0xf","payload":{"sendTransaction":{"from":"0x0000000000000000000000000000000000000000","to":null,"gasPrice":"0x2710","gas":"0x3a98","value":"0x186a0","data":"0x010203","nonce":"0x1","condition":null}},"origin":{"signer":"0x0000000000000000000000000000000000000000000000000000000000000005"}}"#;
+		let expected = r#"{"id":"0xf","payload":{"sendTransaction":{"from":"0x0000000000000000000000000000000000000000","to":null,"gasPrice":"0x2710","gas":"0x3a98","value":"0x186a0","data":"0x010203","nonce":"0x1","condition":null}},"origin":{"signer":"0x0000000000000000000000000000000000000000000000000000000000000005"},"method":"eth_sendTransaction","ip":null,"created":"0x0","expires":null}"#;
 
 		// then
 		assert_eq!(res.unwrap(), expected.to_owned());
@@ -311,11 +368,45 @@ mod tests {
 				condition: None,
 			}),
 			origin: Origin::Dapps("http://parity.io".into()),
+			method: "eth_signTransaction".into(),
+			ip: None,
+			created: 0,
+			expires: None,
+		};
+
+		// when
+		let res = serde_json::to_string(&ConfirmationRequest::from(request));
+		let expected = r#"{"id":"0xf","payload":{"signTransaction":{"from":"0x0000000000000000000000000000000000000000","to":null,"gasPrice":"0x2710","gas":"0x3a98","value":"0x186a0","data":"0x010203","nonce":"0x1","condition":null}},"origin":{"dapp":"http://parity.io"},"method":"eth_signTransaction","ip":null,"created":"0x0","expires":null}"#;
+
+		// then
+		assert_eq!(res.unwrap(), expected.to_owned());
+	}
+
+	#[test]
+	fn should_serialize_sign_typed_data_confirmation() {
+		// given
+		let request = helpers::ConfirmationRequest {
+			id: 15.into(),
+			payload: helpers::ConfirmationPayload::SignTypedData(helpers::TypedDataRequest {
+				address: 1.into(),
+				hash: 5.into(),
+				primary_type: "Mail".into(),
+				fields: vec![TypedDataField {
+					name: "message.contents".into(),
+					kind: "string".into(),
+					value: "Hello, Bob!".into(),
+				}],
+			}),
+			origin: Origin::Rpc("test service".into()),
+			method: "personal_signTypedData".into(),
+			ip: None,
+			created: 0,
+			expires: None,
 		};
 
 		// when
 		let res = serde_json::to_string(&ConfirmationRequest::from(request));
-		let expected = r#"{"id":"0xf","payload":{"signTransaction":{"from":"0x0000000000000000000000000000000000000000","to":null,"gasPrice":"0x2710","gas":"0x3a98","value":"0x186a0","data":"0x010203","nonce":"0x1","condition":null}},"origin":{"dapp":"http://parity.io"}}"#;
+		let expected = r#"{"id":"0xf","payload":{"signTypedData":{"address":"0x0000000000000000000000000000000000000001","hash":"0x0000000000000000000000000000000000000000000000000000000000000005","primaryType":"Mail","fields":[{"name":"message.contents","type":"string","value":"Hello, Bob!"}]}},"origin":{"rpc":"test service"},"method":"personal_signTypedData","ip":null,"created":"0x0","expires":null}"#;
 
 		// then
 		assert_eq!(res.unwrap(), expected.to_owned());
@@ -330,11 +421,15 @@ mod tests {
 				10.into(), vec![1, 2, 3].into(),
 			),
 			origin: Default::default(),
+			method: "parity_decryptMessage".into(),
+			ip: None,
+			created: 0,
+			expires: None,
 		};
 
 		// when
 		let res = serde_json::to_string(&ConfirmationRequest::from(request));
-		let expected = r#"{"id":"0xf","payload":{"decrypt":{"address":"0x000000000000000000000000000000000000000a","msg":"0x010203"}},"origin":"unknown"}"#;
+		let expected = r#"{"id":"0xf","payload":{"decrypt":{"address":"0x000000000000000000000000000000000000000a","msg":"0x010203"}},"origin":"unknown","method":"parity_decryptMessage","ip":null,"created":"0x0","expires":null}"#;
 
 		// then
 		assert_eq!(res.unwrap(), expected.to_owned());
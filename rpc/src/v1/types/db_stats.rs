@@ -0,0 +1,150 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! State database statistics.
+
+use ethcore::client::{DbStats as EthDbStats, JournalCompaction as EthJournalCompaction};
+use util::kvdb::{ColumnStats, DatabaseStats};
+use v1::types::U256;
+
+/// Progress of the online compaction of the on-disk state journal.
+#[derive(Debug, Serialize)]
+pub struct JournalCompaction {
+	/// The next era to be examined.
+	#[serde(rename="nextEra")]
+	pub next_era: U256,
+	/// The latest era which may currently be compacted.
+	#[serde(rename="targetEra")]
+	pub target_era: U256,
+	/// Number of eras compacted since startup.
+	#[serde(rename="compactedEras")]
+	pub compacted_eras: U256,
+	/// Journal bytes reclaimed since startup.
+	#[serde(rename="reclaimedBytes")]
+	pub reclaimed_bytes: U256,
+}
+
+impl From<EthJournalCompaction> for JournalCompaction {
+	fn from(c: EthJournalCompaction) -> Self {
+		JournalCompaction {
+			next_era: c.next_era.into(),
+			target_era: c.target_era.into(),
+			compacted_eras: c.compacted_eras.into(),
+			reclaimed_bytes: c.reclaimed_bytes.into(),
+		}
+	}
+}
+
+/// State database statistics.
+#[derive(Debug, Serialize)]
+pub struct DbStats {
+	/// Pruning algorithm in use.
+	pub pruning: String,
+	/// Journal compaction progress, if the pruning algorithm keeps its journal on disk.
+	pub compaction: Option<JournalCompaction>,
+}
+
+impl From<EthDbStats> for DbStats {
+	fn from(s: EthDbStats) -> Self {
+		DbStats {
+			pruning: s.pruning,
+			compaction: s.compaction.map(Into::into),
+		}
+	}
+}
+
+/// Access statistics for a single column of the backing database.
+#[derive(Debug, Serialize)]
+pub struct ColumnStatistics {
+	/// Column index, `null` for the default column.
+	pub column: Option<u32>,
+	/// Number of keys stored, if known.
+	pub keys: Option<U256>,
+	/// Size of stored keys and values in bytes, if known.
+	pub size: Option<U256>,
+	/// Writes buffered in memory and not yet flushed to disk.
+	#[serde(rename="pendingWrites")]
+	pub pending_writes: U256,
+	/// Reads since startup.
+	pub reads: U256,
+	/// Reads answered from the in-memory write buffer.
+	#[serde(rename="bufferHits")]
+	pub buffer_hits: U256,
+	/// Keys written since startup.
+	pub writes: U256,
+	/// Keys deleted since startup.
+	pub deletes: U256,
+	/// Bytes written since startup.
+	#[serde(rename="bytesWritten")]
+	pub bytes_written: U256,
+}
+
+impl From<ColumnStats> for ColumnStatistics {
+	fn from(c: ColumnStats) -> Self {
+		ColumnStatistics {
+			column: c.column,
+			keys: c.keys.map(Into::into),
+			size: c.size.map(Into::into),
+			pending_writes: c.pending_writes.into(),
+			reads: c.reads.into(),
+			buffer_hits: c.buffer_hits.into(),
+			writes: c.writes.into(),
+			deletes: c.deletes.into(),
+			bytes_written: c.bytes_written.into(),
+		}
+	}
+}
+
+/// Statistics for the backing database.
+#[derive(Debug, Serialize)]
+pub struct DatabaseStatistics {
+	/// Per-column statistics, the default column first.
+	pub columns: Vec<ColumnStatistics>,
+	/// Size of the database files on disk, in bytes.
+	#[serde(rename="sizeOnDisk")]
+	pub size_on_disk: U256,
+}
+
+impl From<DatabaseStats> for DatabaseStatistics {
+	fn from(s: DatabaseStats) -> Self {
+		DatabaseStatistics {
+			columns: s.columns.into_iter().map(Into::into).collect(),
+			size_on_disk: s.size_on_disk.into(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::{DbStats, JournalCompaction};
+
+	#[test]
+	fn db_stats_serialization() {
+		let stats = DbStats {
+			pruning: "basic".into(),
+			compaction: Some(JournalCompaction {
+				next_era: 16.into(),
+				target_era: 100.into(),
+				compacted_eras: 3.into(),
+				reclaimed_bytes: 1024.into(),
+			}),
+		};
+
+		let serialized = serde_json::to_string(&stats).unwrap();
+		assert_eq!(serialized, r#"{"pruning":"basic","compaction":{"nextEra":"0x10","targetEra":"0x64","compactedEras":"0x3","reclaimedBytes":"0x400"}}"#);
+	}
+}
@@ -0,0 +1,44 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Virtual machine statistics.
+
+use ethcore::client::EvmStats as EthEvmStats;
+use v1::types::U256;
+
+/// Numbers of virtual machines created to run code since startup.
+#[derive(Debug, Serialize)]
+pub struct EvmStats {
+	/// Runs of the interpreter.
+	#[serde(rename="interpreterRuns")]
+	pub interpreter_runs: U256,
+	/// Runs of the JIT.
+	#[serde(rename="jitRuns")]
+	pub jit_runs: U256,
+	/// Number of contracts run often enough to be picked for the JIT.
+	#[serde(rename="hotContracts")]
+	pub hot_contracts: U256,
+}
+
+impl From<EthEvmStats> for EvmStats {
+	fn from(s: EthEvmStats) -> Self {
+		EvmStats {
+			interpreter_runs: s.interpreter_runs.into(),
+			jit_runs: s.jit_runs.into(),
+			hot_contracts: s.hot_contracts.into(),
+		}
+	}
+}
@@ -0,0 +1,49 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Execution result with state witness.
+
+use v1::types::{Bytes, U256};
+
+/// Result of executing a call together with the state trie nodes it read.
+#[derive(Debug, Serialize)]
+pub struct ExecutionProof {
+	/// Output of the call.
+	pub output: Bytes,
+	/// Gas used by the call.
+	#[serde(rename="gasUsed")]
+	pub gas_used: U256,
+	/// Trie nodes needed to re-execute the call without the state database.
+	pub proof: Vec<Bytes>,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::ExecutionProof;
+
+	#[test]
+	fn execution_proof_serialization() {
+		let proof = ExecutionProof {
+			output: vec![0x12].into(),
+			gas_used: 21000.into(),
+			proof: vec![vec![0xc0].into()],
+		};
+
+		let serialized = serde_json::to_string(&proof).unwrap();
+		assert_eq!(serialized, r#"{"output":"0x12","gasUsed":"0x5208","proof":["0xc0"]}"#);
+	}
+}
@@ -0,0 +1,43 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Gas usage and gas price history.
+
+use ethcore::client::FeeHistory as EthFeeHistory;
+use v1::types::U256;
+
+/// Gas usage and gas price percentiles of consecutive blocks, oldest first.
+#[derive(Debug, Serialize)]
+pub struct FeeHistory {
+	/// Number of the oldest block of the range.
+	#[serde(rename="oldestBlock")]
+	pub oldest_block: U256,
+	/// Ratio of the gas used to the gas limit of each block.
+	#[serde(rename="gasUsedRatio")]
+	pub gas_used_ratio: Vec<f64>,
+	/// Transaction gas prices of each block at the requested percentiles.
+	pub reward: Vec<Vec<U256>>,
+}
+
+impl From<EthFeeHistory> for FeeHistory {
+	fn from(h: EthFeeHistory) -> Self {
+		FeeHistory {
+			oldest_block: h.oldest_block.into(),
+			gas_used_ratio: h.gas_used_ratio,
+			reward: h.gas_prices.into_iter().map(|prices| prices.into_iter().map(Into::into).collect()).collect(),
+		}
+	}
+}
@@ -0,0 +1,43 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Statistics of mined blocks.
+
+use ethcore::miner::MineStats as EthMineStats;
+use v1::types::U256;
+
+/// Statistics of blocks mined by the node.
+#[derive(Debug, Serialize)]
+pub struct MineStats {
+	/// Number of mined blocks.
+	pub blocks: U256,
+	/// Number of uncles included in mined blocks.
+	#[serde(rename="includedUncles")]
+	pub included_uncles: U256,
+	/// Number of uncles which could have been included in mined blocks but weren't.
+	#[serde(rename="missedUncles")]
+	pub missed_uncles: U256,
+}
+
+impl From<EthMineStats> for MineStats {
+	fn from(s: EthMineStats) -> Self {
+		MineStats {
+			blocks: s.blocks.into(),
+			included_uncles: s.uncles_included.into(),
+			missed_uncles: s.uncles_missed.into(),
+		}
+	}
+}
@@ -18,59 +18,93 @@
 //! RPC types
 
 mod account_info;
+mod account_proof;
 mod block;
 mod block_number;
 mod bytes;
 mod call_request;
 mod confirmations;
 mod consensus_status;
+mod db_stats;
 mod derivation;
+mod evm_stats;
+mod execution_proof;
+mod fee_history;
 mod filter;
 mod hash;
 mod histogram;
 mod index;
 mod log;
+mod mine_stats;
+mod node_health;
 mod node_kind;
 mod provenance;
+mod pubsub;
 mod receipt;
+mod reorg_stats;
 mod rpc_settings;
+mod signing_log;
+mod state_override;
+mod stratum;
+mod struct_log;
 mod sync;
 mod trace;
 mod trace_filter;
 mod transaction;
+mod transaction_filter;
 mod transaction_request;
 mod transaction_condition;
+mod txpool;
+mod typed_data;
 mod uint;
+mod vault_policy;
 mod work;
 
 pub use self::account_info::{AccountInfo, HwAccountInfo};
+pub use self::account_proof::{AccountProof, StorageProof};
 pub use self::bytes::Bytes;
-pub use self::block::{RichBlock, Block, BlockTransactions};
+pub use self::block::{RichBlock, Block, BlockTransactions, Header};
 pub use self::block_number::BlockNumber;
 pub use self::call_request::CallRequest;
 pub use self::confirmations::{
 	ConfirmationPayload, ConfirmationRequest, ConfirmationResponse, ConfirmationResponseWithToken,
-	TransactionModification, SignRequest, DecryptRequest, Either
+	TransactionModification, SignRequest, SignTypedDataRequest, DecryptRequest, Either
 };
 pub use self::consensus_status::*;
+pub use self::db_stats::{DbStats, JournalCompaction, DatabaseStatistics, ColumnStatistics};
 pub use self::derivation::{DeriveHash, DeriveHierarchical, Derive};
+pub use self::evm_stats::EvmStats;
+pub use self::execution_proof::ExecutionProof;
+pub use self::fee_history::FeeHistory;
 pub use self::filter::{Filter, FilterChanges};
 pub use self::hash::{H64, H160, H256, H512, H520, H2048};
 pub use self::histogram::Histogram;
 pub use self::index::Index;
 pub use self::log::Log;
+pub use self::mine_stats::MineStats;
+pub use self::node_health::{Health, HealthInfo, HealthStatus, PeersHealth};
 pub use self::node_kind::{NodeKind, Availability, Capability};
 pub use self::provenance::{Origin, DappId};
+pub use self::pubsub::{ChainReorg, Kind as PubSubKind, Params as PubSubParams, Result as PubSubResult};
 pub use self::receipt::Receipt;
+pub use self::reorg_stats::ReorgStats;
 pub use self::rpc_settings::RpcSettings;
+pub use self::signing_log::{SigningLogEntry, SigningDecision};
+pub use self::state_override::{AccountOverride, StateOverride};
+pub use self::stratum::StratumWorker;
+pub use self::struct_log::{StructLogConfig, StructLogResults};
 pub use self::sync::{
 	SyncStatus, SyncInfo, Peers, PeerInfo, PeerNetworkInfo, PeerProtocolsInfo,
-	TransactionStats, ChainStatus, EthProtocolInfo, LesProtocolInfo,
+	TransactionStats, ChainStatus, WarpRestoration, EthProtocolInfo, LesProtocolInfo,
 };
-pub use self::trace::{LocalizedTrace, TraceResults};
+pub use self::trace::{LocalizedTrace, TraceResults, TracePage, GasProfileResults, OpcodeGas};
 pub use self::trace_filter::TraceFilter;
 pub use self::transaction::{Transaction, RichRawTransaction, LocalTransactionStatus};
+pub use self::transaction_filter::{TransactionFilter, ValueRange};
 pub use self::transaction_request::TransactionRequest;
 pub use self::transaction_condition::TransactionCondition;
+pub use self::txpool::{TxPoolStatus, TxPoolSender};
+pub use self::typed_data::{TypedData, TypedDataMember, TypedDataField};
 pub use self::uint::{U128, U256};
+pub use self::vault_policy::VaultPolicy;
 pub use self::work::Work;
@@ -0,0 +1,91 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Node health report.
+
+/// Health level of a single check or of the whole node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum HealthStatus {
+	/// Everything's fine.
+	#[serde(rename="ok")]
+	Ok,
+	/// The node still serves requests but something should be looked at.
+	#[serde(rename="needsAttention")]
+	NeedsAttention,
+	/// The node should not be used to serve requests.
+	#[serde(rename="bad")]
+	Bad,
+}
+
+/// Result of a single health check.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HealthInfo<T> {
+	/// Status of the check.
+	pub status: HealthStatus,
+	/// Human-readable explanation of the status.
+	pub message: String,
+	/// Raw value the status was derived from.
+	pub details: T,
+}
+
+/// Connected peers compared to the configured minimum.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PeersHealth {
+	/// Number of connected peers.
+	pub connected: usize,
+	/// Configured minimum number of peers.
+	pub minimum: usize,
+}
+
+/// Node health report.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Health {
+	/// Worst status of all the checks.
+	pub status: HealthStatus,
+	/// Peer count check.
+	pub peers: HealthInfo<PeersHealth>,
+	/// Sync check; details are the number of blocks the node is behind.
+	pub sync: HealthInfo<u64>,
+	/// Clock drift check; details are the drift against NTP in milliseconds.
+	pub time: HealthInfo<i64>,
+	/// Disk space check; details are the bytes available in the data directory.
+	pub disk: HealthInfo<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{HealthStatus, HealthInfo};
+	use serde_json;
+
+	#[test]
+	fn health_status_serialization() {
+		assert_eq!(serde_json::to_string(&HealthStatus::Ok).unwrap(), r#""ok""#);
+		assert_eq!(serde_json::to_string(&HealthStatus::NeedsAttention).unwrap(), r#""needsAttention""#);
+		assert_eq!(serde_json::to_string(&HealthStatus::Bad).unwrap(), r#""bad""#);
+		assert!(HealthStatus::Ok < HealthStatus::NeedsAttention);
+		assert!(HealthStatus::NeedsAttention < HealthStatus::Bad);
+	}
+
+	#[test]
+	fn health_info_serialization() {
+		let info = HealthInfo {
+			status: HealthStatus::Bad,
+			message: "Behind".into(),
+			details: 10u64,
+		};
+		assert_eq!(serde_json::to_string(&info).unwrap(), r#"{"status":"bad","message":"Behind","details":10}"#);
+	}
+}
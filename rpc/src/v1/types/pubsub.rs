@@ -0,0 +1,189 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Pub-Sub types.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::Error;
+use serde_json::{Value, from_value};
+use v1::types::{Filter, Header, Log, SyncStatus, TransactionFilter, Work, H256};
+
+/// Subscription result.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Result {
+	/// New block header.
+	Header(Header),
+	/// Log
+	Log(Log),
+	/// Transaction hash
+	TransactionHash(H256),
+	/// Sync progress snapshot
+	Syncing(SyncStatus),
+	/// Chain reorganisation
+	ChainReorg(ChainReorg),
+	/// New work package
+	Work(Work),
+}
+
+/// Blocks retracted and enacted by a chain reorganisation.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChainReorg {
+	/// Hashes of the blocks removed from the canonical chain.
+	#[serde(rename="oldBranch")]
+	pub old_branch: Vec<H256>,
+	/// Hashes of the blocks that replaced them.
+	#[serde(rename="newBranch")]
+	pub new_branch: Vec<H256>,
+	/// Last block shared by both branches.
+	#[serde(rename="commonAncestor")]
+	pub common_ancestor: H256,
+}
+
+impl Serialize for Result {
+	fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+		where S: Serializer
+	{
+		match *self {
+			Result::Header(ref header) => header.serialize(serializer),
+			Result::Log(ref log) => log.serialize(serializer),
+			Result::TransactionHash(ref hash) => hash.serialize(serializer),
+			Result::Syncing(ref status) => status.serialize(serializer),
+			Result::ChainReorg(ref reorg) => reorg.serialize(serializer),
+			Result::Work(ref work) => work.serialize(serializer),
+		}
+	}
+}
+
+/// Subscription kind.
+#[derive(Debug, Deserialize, PartialEq, Eq, Hash, Clone)]
+pub enum Kind {
+	/// New block headers subscription.
+	#[serde(rename="newHeads")]
+	NewHeads,
+	/// Logs subscription.
+	#[serde(rename="logs")]
+	Logs,
+	/// New Pending Transactions subscription.
+	#[serde(rename="newPendingTransactions")]
+	NewPendingTransactions,
+	/// Sync progress subscription.
+	#[serde(rename="syncing")]
+	Syncing,
+	/// Chain reorganisations subscription.
+	#[serde(rename="chainReorg")]
+	ChainReorg,
+	/// New work packages subscription.
+	#[serde(rename="newWork")]
+	NewWork,
+}
+
+/// Subscription parameters.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Params {
+	/// No parameters passed.
+	None,
+	/// Log parameters.
+	Logs(Filter),
+	/// Pending transaction parameters.
+	Transactions(TransactionFilter),
+}
+
+impl Default for Params {
+	fn default() -> Self {
+		Params::None
+	}
+}
+
+impl Deserialize for Params {
+	fn deserialize<D>(deserializer: D) -> ::std::result::Result<Params, D::Error>
+		where D: Deserializer
+	{
+		let v: Value = Deserialize::deserialize(deserializer)?;
+
+		if v.is_null() {
+			return Ok(Params::None);
+		}
+
+		from_value(v.clone()).map(Params::Logs)
+			.or_else(|_| from_value(v).map(Params::Transactions))
+			.map_err(|_| D::Error::custom("Invalid Pub-Sub parameters"))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::{Result, Kind, Params, ChainReorg};
+	use v1::types::{Filter, SyncStatus, TransactionFilter, H256};
+
+	#[test]
+	fn should_deserialize_kind() {
+		assert_eq!(serde_json::from_str::<Kind>(r#""newHeads""#).unwrap(), Kind::NewHeads);
+		assert_eq!(serde_json::from_str::<Kind>(r#""logs""#).unwrap(), Kind::Logs);
+		assert_eq!(serde_json::from_str::<Kind>(r#""newPendingTransactions""#).unwrap(), Kind::NewPendingTransactions);
+		assert_eq!(serde_json::from_str::<Kind>(r#""syncing""#).unwrap(), Kind::Syncing);
+		assert_eq!(serde_json::from_str::<Kind>(r#""chainReorg""#).unwrap(), Kind::ChainReorg);
+		assert_eq!(serde_json::from_str::<Kind>(r#""newWork""#).unwrap(), Kind::NewWork);
+		assert!(serde_json::from_str::<Kind>(r#""newBlocks""#).is_err());
+	}
+
+	#[test]
+	fn should_deserialize_logs() {
+		let none = serde_json::from_str::<Params>(r#"null"#).unwrap();
+		assert_eq!(none, Params::None);
+
+		let logs = serde_json::from_str::<Params>(r#"{"address":"0x0000000000000000000000000000000000000001"}"#).unwrap();
+		assert_eq!(logs, Params::Logs(Filter {
+			from_block: None,
+			to_block: None,
+			address: Some(serde_json::from_str(r#""0x0000000000000000000000000000000000000001""#).unwrap()),
+			topics: None,
+			limit: None,
+		}));
+	}
+
+	#[test]
+	fn should_deserialize_transactions() {
+		let transactions = serde_json::from_str::<Params>(r#"{"to":["0x0000000000000000000000000000000000000001"]}"#).unwrap();
+		assert_eq!(transactions, Params::Transactions(TransactionFilter {
+			from: None,
+			to: Some(vec![serde_json::from_str(r#""0x0000000000000000000000000000000000000001""#).unwrap()]),
+			method_selector: None,
+			gas_price: None,
+			value: None,
+		}));
+	}
+
+	#[test]
+	fn should_serialize_transaction_hash() {
+		let result = Result::TransactionHash(H256::from(5));
+		assert_eq!(serde_json::to_string(&result).unwrap(), r#""0x0000000000000000000000000000000000000000000000000000000000000005""#);
+	}
+
+	#[test]
+	fn should_serialize_chain_reorg() {
+		let result = Result::ChainReorg(ChainReorg {
+			old_branch: vec![H256::from(2)],
+			new_branch: vec![H256::from(3), H256::from(4)],
+			common_ancestor: H256::from(1),
+		});
+		let serialized = serde_json::to_string(&result).unwrap();
+		assert_eq!(serialized, r#"{"oldBranch":["0x0000000000000000000000000000000000000000000000000000000000000002"],"newBranch":["0x0000000000000000000000000000000000000000000000000000000000000003","0x0000000000000000000000000000000000000000000000000000000000000004"],"commonAncestor":"0x0000000000000000000000000000000000000000000000000000000000000001"}"#);
+
+		let result = Result::Syncing(SyncStatus::None);
+		assert_eq!(serde_json::to_string(&result).unwrap(), "false");
+	}
+}
@@ -0,0 +1,43 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Statistics of transactions re-imported after chain reorganisations.
+
+use ethcore::miner::ReorgStats as EthReorgStats;
+use v1::types::U256;
+
+/// Statistics of transactions re-imported after chain reorganisations.
+#[derive(Debug, Serialize)]
+pub struct ReorgStats {
+	/// Number of transactions in retracted blocks which are not part of the new canonical chain.
+	#[serde(rename="abandonedTransactions")]
+	pub abandoned_transactions: U256,
+	/// Number of abandoned transactions imported back to the queue.
+	#[serde(rename="reimportedTransactions")]
+	pub reimported_transactions: U256,
+	/// Number of reorganisations which retracted at least one block.
+	pub reorgs: U256,
+}
+
+impl From<EthReorgStats> for ReorgStats {
+	fn from(s: EthReorgStats) -> Self {
+		ReorgStats {
+			reorgs: s.reorgs.into(),
+			abandoned_transactions: s.abandoned_transactions.into(),
+			reimported_transactions: s.reimported_transactions.into(),
+		}
+	}
+}
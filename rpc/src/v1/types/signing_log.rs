@@ -0,0 +1,74 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Signing log entries.
+
+use ethcore::account_provider::{SigningLogEntry as EthSigningLogEntry, SigningDecision as EthSigningDecision};
+use v1::types::{H160, H256, U256};
+
+/// Outcome of a signing request.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum SigningDecision {
+	/// Request was signed.
+	#[serde(rename="signed")]
+	Signed,
+	/// Request was rejected by the user or by a signing policy.
+	#[serde(rename="rejected")]
+	Rejected,
+	/// Request was confirmed, but signing failed.
+	#[serde(rename="failed")]
+	Failed,
+}
+
+impl From<EthSigningDecision> for SigningDecision {
+	fn from(d: EthSigningDecision) -> Self {
+		match d {
+			EthSigningDecision::Signed => SigningDecision::Signed,
+			EthSigningDecision::Rejected => SigningDecision::Rejected,
+			EthSigningDecision::Failed => SigningDecision::Failed,
+		}
+	}
+}
+
+/// Recorded signing operation.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SigningLogEntry {
+	/// Account requested to sign
+	pub account: H160,
+	/// Origin of the request
+	pub origin: String,
+	/// RPC method of the request
+	pub method: String,
+	/// Hash of the signed transaction or message, if known
+	pub hash: Option<H256>,
+	/// Outcome of the request
+	pub decision: SigningDecision,
+	/// UNIX timestamp (in seconds) of the decision
+	pub timestamp: U256,
+}
+
+impl From<EthSigningLogEntry> for SigningLogEntry {
+	fn from(e: EthSigningLogEntry) -> Self {
+		SigningLogEntry {
+			account: e.account.into(),
+			origin: e.origin,
+			method: e.method,
+			hash: e.hash.map(Into::into),
+			decision: e.decision.into(),
+			timestamp: e.timestamp.into(),
+		}
+	}
+}
@@ -0,0 +1,83 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! State override set of simulated calls.
+
+use std::collections::BTreeMap;
+use ethcore::client::AccountOverride as EthAccountOverride;
+use util::Address;
+use v1::types::{Bytes, H160, H256, U256};
+
+/// Changes made to an account before executing a call.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AccountOverride {
+	/// Balance
+	pub balance: Option<U256>,
+	/// Nonce
+	pub nonce: Option<U256>,
+	/// Code
+	pub code: Option<Bytes>,
+	/// Storage slots to set
+	#[serde(rename="stateDiff")]
+	pub state_diff: Option<BTreeMap<H256, H256>>,
+}
+
+impl Into<EthAccountOverride> for AccountOverride {
+	fn into(self) -> EthAccountOverride {
+		EthAccountOverride {
+			balance: self.balance.map(Into::into),
+			nonce: self.nonce.map(Into::into),
+			code: self.code.map(Into::into),
+			storage: self.state_diff.map_or_else(BTreeMap::new, |s| s.into_iter().map(|(k, v)| (k.into(), v.into())).collect()),
+		}
+	}
+}
+
+/// Accounts to modify before executing a call, by address.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+pub struct StateOverride(pub BTreeMap<H160, AccountOverride>);
+
+impl Into<BTreeMap<Address, EthAccountOverride>> for StateOverride {
+	fn into(self) -> BTreeMap<Address, EthAccountOverride> {
+		self.0.into_iter().map(|(address, account)| (address.into(), account.into())).collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use std::collections::BTreeMap;
+	use v1::types::{H160, H256, U256};
+	use super::{AccountOverride, StateOverride};
+
+	#[test]
+	fn state_override_deserialization() {
+		let s = r#"{"0x0000000000000000000000000000000000000042":{"balance":"0x10","code":"0x6000","stateDiff":{"0x0000000000000000000000000000000000000000000000000000000000000001":"0x0000000000000000000000000000000000000000000000000000000000000002"}}}"#;
+		let deserialized: StateOverride = serde_json::from_str(s).unwrap();
+
+		let mut state_diff = BTreeMap::new();
+		state_diff.insert(H256::from(1u64), H256::from(2u64));
+		let mut expected = BTreeMap::new();
+		expected.insert(H160::from(0x42u64), AccountOverride {
+			balance: Some(U256::from(0x10u64)),
+			nonce: None,
+			code: Some(vec![0x60, 0x00].into()),
+			state_diff: Some(state_diff),
+		});
+		assert_eq!(deserialized, StateOverride(expected));
+	}
+}
@@ -0,0 +1,63 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Stratum server statistics.
+
+use ethcore::miner::StratumWorkerInfo;
+use v1::types::U256;
+
+/// Worker connected to the stratum server.
+#[derive(Debug, Serialize)]
+pub struct StratumWorker {
+	/// Worker id given during authorization.
+	pub worker: String,
+	/// Address of the worker's connection.
+	pub address: String,
+	/// Share difficulty assigned to the worker, `null` if block difficulty is used.
+	pub difficulty: Option<U256>,
+	/// Estimated hash rate (hashes per second).
+	pub hashrate: U256,
+	/// Accepted shares.
+	#[serde(rename="acceptedShares")]
+	pub accepted_shares: U256,
+	/// Shares submitted for out of date work.
+	#[serde(rename="staleShares")]
+	pub stale_shares: U256,
+	/// Invalid shares.
+	#[serde(rename="rejectedShares")]
+	pub rejected_shares: U256,
+	/// Ratio of stale shares to all submitted shares.
+	#[serde(rename="staleRatio")]
+	pub stale_ratio: f64,
+	/// Blocks found by the worker.
+	pub blocks: U256,
+}
+
+impl From<StratumWorkerInfo> for StratumWorker {
+	fn from(w: StratumWorkerInfo) -> Self {
+		StratumWorker {
+			stale_ratio: w.stale_ratio(),
+			worker: w.id,
+			address: format!("{}", w.address),
+			difficulty: w.difficulty.map(Into::into),
+			hashrate: w.hashrate.into(),
+			accepted_shares: w.accepted.into(),
+			stale_shares: w.stale.into(),
+			rejected_shares: w.rejected.into(),
+			blocks: w.blocks.into(),
+		}
+	}
+}
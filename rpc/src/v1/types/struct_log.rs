@@ -0,0 +1,132 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Struct logs, in the format of `debug_traceTransaction`.
+
+use std::collections::BTreeMap;
+use rustc_serialize::hex::ToHex;
+use ethcore::client::Executed;
+use ethcore::trace::{StructLog as EthStructLog, StructLogOptions};
+use util::{H256, U256, Uint};
+
+/// Options of `debug_traceTransaction`.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+pub struct StructLogConfig {
+	/// Don't record the stack.
+	#[serde(rename="disableStack")]
+	pub disable_stack: Option<bool>,
+	/// Don't record the memory.
+	#[serde(rename="disableMemory")]
+	pub disable_memory: Option<bool>,
+	/// Don't record the storage.
+	#[serde(rename="disableStorage")]
+	pub disable_storage: Option<bool>,
+	/// Maximum number of steps to record.
+	pub limit: Option<usize>,
+	/// Maximum call depth to record steps at.
+	#[serde(rename="maxDepth")]
+	pub max_depth: Option<usize>,
+	/// Filter expression selecting the steps to record, e.g. `op == SSTORE`.
+	pub tracer: Option<String>,
+}
+
+impl Into<StructLogOptions> for StructLogConfig {
+	fn into(self) -> StructLogOptions {
+		StructLogOptions {
+			stack: !self.disable_stack.unwrap_or(false),
+			memory: !self.disable_memory.unwrap_or(false),
+			storage: !self.disable_storage.unwrap_or(false),
+			limit: self.limit.unwrap_or(0),
+			max_depth: self.max_depth.unwrap_or(0),
+			filter: self.tracer,
+		}
+	}
+}
+
+fn word_hex(value: &U256) -> String {
+	H256::from(value).hex()
+}
+
+/// A single step of execution.
+#[derive(Debug, Serialize)]
+pub struct StructLog {
+	/// Program counter.
+	pub pc: usize,
+	/// The instruction's mnemonic.
+	pub op: String,
+	/// Gas left before executing the instruction.
+	pub gas: u64,
+	/// Gas cost of the instruction.
+	#[serde(rename="gasCost")]
+	pub gas_cost: u64,
+	/// Call depth, starting with 1.
+	pub depth: usize,
+	/// The stack, bottom first, as 32-byte words.
+	#[serde(skip_serializing_if="Option::is_none")]
+	pub stack: Option<Vec<String>>,
+	/// The memory, as 32-byte words.
+	#[serde(skip_serializing_if="Option::is_none")]
+	pub memory: Option<Vec<String>>,
+	/// The storage touched so far.
+	#[serde(skip_serializing_if="Option::is_none")]
+	pub storage: Option<BTreeMap<String, String>>,
+}
+
+impl From<EthStructLog> for StructLog {
+	fn from(l: EthStructLog) -> Self {
+		let op = match ::ethcore::evm::instruction_name(l.instruction) {
+			"" => format!("opcode 0x{:x} not defined", l.instruction),
+			name => name.to_owned(),
+		};
+
+		StructLog {
+			pc: l.pc,
+			op: op,
+			gas: l.gas.low_u64(),
+			gas_cost: l.gas_cost.low_u64(),
+			depth: l.depth,
+			stack: l.stack.map(|s| s.iter().map(word_hex).collect()),
+			memory: l.memory.map(|m| m.chunks(32).map(|w| w.to_hex()).collect()),
+			storage: l.storage.map(|s| s.iter().map(|(k, v)| (word_hex(k), word_hex(v))).collect()),
+		}
+	}
+}
+
+/// Results of `debug_traceTransaction`.
+#[derive(Debug, Serialize)]
+pub struct StructLogResults {
+	/// Gas used by the transaction.
+	pub gas: u64,
+	/// Whether the transaction failed.
+	pub failed: bool,
+	/// The output of the transaction.
+	#[serde(rename="returnValue")]
+	pub return_value: String,
+	/// The steps executed.
+	#[serde(rename="structLogs")]
+	pub struct_logs: Vec<StructLog>,
+}
+
+impl From<Executed> for StructLogResults {
+	fn from(t: Executed) -> Self {
+		StructLogResults {
+			gas: t.gas_used.low_u64(),
+			failed: t.exception.is_some(),
+			return_value: t.output.to_hex(),
+			struct_logs: t.struct_logs.map_or_else(Vec::new, |logs| logs.into_iter().map(Into::into).collect()),
+		}
+	}
+}
@@ -20,7 +20,7 @@ use serde::{Serialize, Serializer};
 use v1::types::{U256, H512};
 
 /// Sync info
-#[derive(Default, Debug, Serialize, PartialEq)]
+#[derive(Default, Debug, Clone, Serialize, PartialEq)]
 pub struct SyncInfo {
 	/// Starting block
 	#[serde(rename="startingBlock")]
@@ -130,7 +130,7 @@ impl From<ethsync::LesProtocolInfo> for LesProtocolInfo {
 }
 
 /// Sync status
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum SyncStatus {
 	/// Info when syncing
 	Info(SyncInfo),
@@ -195,13 +195,34 @@ pub struct ChainStatus {
 	/// Describes the gap in the blockchain, if there is one: (first, last)
 	#[serde(rename="blockGap")]
 	pub block_gap: Option<(U256, U256)>,
+	/// Earliest block whose full state is available, if state is stored locally.
+	#[serde(rename="earliestState")]
+	pub earliest_state: Option<U256>,
+	/// First block from which all bodies and receipts are available up to the best block,
+	/// if they are stored locally.
+	#[serde(rename="earliestBlock")]
+	pub earliest_block: Option<U256>,
+	/// Progress of an ongoing warp restoration, if there is one.
+	#[serde(rename="warpRestoration")]
+	pub warp_restoration: Option<WarpRestoration>,
+}
+
+/// Progress of a warp (snapshot) restoration.
+#[derive(Default, Debug, Serialize)]
+pub struct WarpRestoration {
+	/// Number of snapshot chunks to restore.
+	#[serde(rename="chunksTotal")]
+	pub chunks_total: U256,
+	/// Number of snapshot chunks restored so far.
+	#[serde(rename="chunksDone")]
+	pub chunks_done: U256,
 }
 
 #[cfg(test)]
 mod tests {
 	use serde_json;
 	use std::collections::BTreeMap;
-	use super::{SyncInfo, SyncStatus, Peers, TransactionStats, ChainStatus};
+	use super::{SyncInfo, SyncStatus, Peers, TransactionStats, ChainStatus, WarpRestoration};
 
 	#[test]
 	fn test_serialize_sync_info() {
@@ -232,12 +253,15 @@ mod tests {
 	fn test_serialize_block_gap() {
 		let mut t = ChainStatus::default();
 		let serialized = serde_json::to_string(&t).unwrap();
-		assert_eq!(serialized, r#"{"blockGap":null}"#);
+		assert_eq!(serialized, r#"{"blockGap":null,"earliestState":null,"earliestBlock":null,"warpRestoration":null}"#);
 
 		t.block_gap = Some((1.into(), 5.into()));
+		t.earliest_state = Some(100.into());
+		t.earliest_block = Some(6.into());
+		t.warp_restoration = Some(WarpRestoration { chunks_total: 10.into(), chunks_done: 4.into() });
 
 		let serialized = serde_json::to_string(&t).unwrap();
-		assert_eq!(serialized, r#"{"blockGap":["0x1","0x5"]}"#);
+		assert_eq!(serialized, r#"{"blockGap":["0x1","0x5"],"earliestState":"0x64","earliestBlock":"0x6","warpRestoration":{"chunksTotal":"0xa","chunksDone":"0x4"}}"#);
 	}
 
 	#[test]
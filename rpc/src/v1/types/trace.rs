@@ -554,6 +554,9 @@ pub struct TraceResults {
 	/// The transaction trace.
 	#[serde(rename="stateDiff")]
 	pub state_diff: Option<StateDiff>,
+	/// Token for the remainder of the trace, if it didn't fit in a single page.
+	#[serde(skip_serializing_if="Option::is_none")]
+	pub continuation: Option<U256>,
 }
 
 impl From<Executed> for TraceResults {
@@ -563,6 +566,64 @@ impl From<Executed> for TraceResults {
 			trace: t.trace.into_iter().map(Into::into).collect(),
 			vm_trace: t.vm_trace.map(Into::into),
 			state_diff: t.state_diff.map(Into::into),
+			continuation: None,
+		}
+	}
+}
+
+#[derive(Debug, Serialize)]
+/// A further page of a paginated trace.
+pub struct TracePage {
+	/// The traces in this page.
+	pub trace: Vec<Trace>,
+	/// Token for the next page, if this isn't the last one.
+	pub continuation: Option<U256>,
+}
+
+#[derive(Debug, Serialize)]
+/// Gas used by the executions of a single instruction.
+pub struct OpcodeGas {
+	/// The instruction's mnemonic.
+	pub op: String,
+	/// Number of times it was executed.
+	pub count: U256,
+	/// Total gas used by its executions.
+	pub gas: U256,
+}
+
+impl From<et::OpcodeGas> for OpcodeGas {
+	fn from(g: et::OpcodeGas) -> Self {
+		let op = match ::ethcore::evm::instruction_name(g.instruction) {
+			"" => format!("0x{:02x}", g.instruction),
+			name => name.to_owned(),
+		};
+
+		OpcodeGas {
+			op: op,
+			count: g.count.into(),
+			gas: g.gas.into(),
+		}
+	}
+}
+
+#[derive(Debug, Serialize)]
+/// Results of a call executed with gas profiling.
+pub struct GasProfileResults {
+	/// The output of the call/create
+	pub output: Bytes,
+	/// Gas used by the call/create.
+	#[serde(rename="gasUsed")]
+	pub gas_used: U256,
+	/// The gas used by each instruction executed, including in nested calls.
+	pub profile: Vec<OpcodeGas>,
+}
+
+impl From<Executed> for GasProfileResults {
+	fn from(t: Executed) -> Self {
+		GasProfileResults {
+			output: t.output.into(),
+			gas_used: t.gas_used.into(),
+			profile: t.gas_profile.map_or_else(Vec::new, |p| p.instructions.into_iter().map(Into::into).collect()),
 		}
 	}
 }
@@ -582,11 +643,25 @@ mod tests {
 			trace: vec![],
 			vm_trace: None,
 			state_diff: None,
+			continuation: None,
 		};
 		let serialized = serde_json::to_string(&r).unwrap();
 		assert_eq!(serialized, r#"{"output":"0x60","trace":[],"vmTrace":null,"stateDiff":null}"#);
 	}
 
+	#[test]
+	fn should_serialize_trace_results_continuation() {
+		let r = TraceResults {
+			output: vec![0x60].into(),
+			trace: vec![],
+			vm_trace: None,
+			state_diff: None,
+			continuation: Some(3.into()),
+		};
+		let serialized = serde_json::to_string(&r).unwrap();
+		assert_eq!(serialized, r#"{"output":"0x60","trace":[],"vmTrace":null,"stateDiff":null,"continuation":"0x3"}"#);
+	}
+
 	#[test]
 	fn test_trace_call_serialize() {
 		let t = LocalizedTrace {
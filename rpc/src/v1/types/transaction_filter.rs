@@ -0,0 +1,125 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Pending transaction filter deserialization.
+
+use ethcore::transaction::{Action, SignedTransaction};
+use util::U256 as EthU256;
+use v1::types::{Bytes, H160, U256};
+
+/// Inclusive range of values; a missing bound is unrestricted.
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ValueRange {
+	/// Lowest value
+	pub min: Option<U256>,
+	/// Highest value
+	pub max: Option<U256>,
+}
+
+impl ValueRange {
+	fn contains(&self, value: &EthU256) -> bool {
+		let min: Option<EthU256> = self.min.map(Into::into);
+		let max: Option<EthU256> = self.max.map(Into::into);
+		min.map_or(true, |min| *value >= min) && max.map_or(true, |max| *value <= max)
+	}
+}
+
+/// Pending transaction filter
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TransactionFilter {
+	/// Senders
+	pub from: Option<Vec<H160>>,
+	/// Recipients; contract creations never match
+	pub to: Option<Vec<H160>>,
+	/// Leading bytes of the call data
+	#[serde(rename="methodSelector")]
+	pub method_selector: Option<Vec<Bytes>>,
+	/// Gas price range
+	#[serde(rename="gasPrice")]
+	pub gas_price: Option<ValueRange>,
+	/// Value range
+	pub value: Option<ValueRange>,
+}
+
+impl TransactionFilter {
+	/// Returns true if the transaction matches all the restrictions of the filter.
+	pub fn matches(&self, transaction: &SignedTransaction) -> bool {
+		let from = self.from.as_ref().map_or(true, |from| {
+			let sender: H160 = transaction.sender().into();
+			from.contains(&sender)
+		});
+		let to = self.to.as_ref().map_or(true, |to| match transaction.action {
+			Action::Call(ref address) => to.contains(&address.clone().into()),
+			Action::Create => false,
+		});
+		let method_selector = self.method_selector.as_ref().map_or(true, |selectors| {
+			selectors.iter().any(|selector| transaction.data.starts_with(&selector.0))
+		});
+		let gas_price = self.gas_price.as_ref().map_or(true, |range| range.contains(&transaction.gas_price));
+		let value = self.value.as_ref().map_or(true, |range| range.contains(&transaction.value));
+
+		from && to && method_selector && gas_price && value
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use ethcore::transaction::{Action, Transaction};
+	use ethkey::{Generator, Random};
+	use util::Address;
+	use v1::types::TransactionFilter;
+
+	#[test]
+	fn should_deserialize_transaction_filter() {
+		let s = r#"{
+			"from": ["0x0000000000000000000000000000000000000001"],
+			"methodSelector": ["0xa9059cbb"],
+			"gasPrice": {"min": "0x4a817c800"}
+		}"#;
+		let filter: TransactionFilter = serde_json::from_str(s).unwrap();
+		assert_eq!(filter.from, Some(vec![Address::from(1).into()]));
+		assert_eq!(filter.to, None);
+		assert_eq!(filter.method_selector, Some(vec![vec![0xa9, 0x05, 0x9c, 0xbb].into()]));
+		assert_eq!(filter.gas_price.unwrap().min, Some(20_000_000_000u64.into()));
+		assert_eq!(filter.value, None);
+		assert!(serde_json::from_str::<TransactionFilter>(r#"{"fromBlock": "0x1"}"#).is_err());
+	}
+
+	#[test]
+	fn should_match_transactions() {
+		let keypair = Random.generate().unwrap();
+		let transaction = Transaction {
+			nonce: 0.into(),
+			gas_price: 10.into(),
+			gas: 100_000.into(),
+			action: Action::Call(Address::from(5)),
+			value: 1_000.into(),
+			data: vec![0xa9, 0x05, 0x9c, 0xbb, 0x01],
+		}.sign(keypair.secret(), None);
+
+		let filter = |s: &str| serde_json::from_str::<TransactionFilter>(s).unwrap();
+		assert!(filter(r#"{}"#).matches(&transaction));
+		assert!(filter(&format!(r#"{{"from": ["0x{:?}"]}}"#, keypair.address())).matches(&transaction));
+		assert!(filter(r#"{"to": ["0x0000000000000000000000000000000000000005"], "methodSelector": ["0xa9059cbb"]}"#).matches(&transaction));
+		assert!(filter(r#"{"gasPrice": {"min": "0xa", "max": "0xa"}, "value": {"max": "0x3e8"}}"#).matches(&transaction));
+		assert!(!filter(r#"{"from": ["0x0000000000000000000000000000000000000005"]}"#).matches(&transaction));
+		assert!(!filter(r#"{"methodSelector": ["0x095ea7b3"]}"#).matches(&transaction));
+		assert!(!filter(r#"{"value": {"min": "0x3e9"}}"#).matches(&transaction));
+	}
+}
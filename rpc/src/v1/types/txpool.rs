@@ -0,0 +1,73 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transaction pool inspection.
+
+use v1::types::{H256, U256, Transaction};
+
+/// Number of transactions in the queue.
+#[derive(Debug, Default, Serialize)]
+pub struct TxPoolStatus {
+	/// Transactions ready to be included in a block.
+	pub pending: U256,
+	/// Transactions waiting for transactions with lower nonces.
+	pub queued: U256,
+}
+
+/// Queued transactions of a single sender.
+#[derive(Debug, Serialize)]
+pub struct TxPoolSender {
+	/// Nonce of the sender in the latest state.
+	pub nonce: U256,
+	/// Transactions ready to be included in a block, ordered by nonce.
+	pub pending: Vec<Transaction>,
+	/// Transactions waiting for transactions with lower nonces, ordered by nonce.
+	pub queued: Vec<Transaction>,
+	/// Ranges (inclusive) of nonces which are missing before the queued transactions.
+	#[serde(rename="nonceGaps")]
+	pub nonce_gaps: Vec<(U256, U256)>,
+	/// Ready transactions priced below the node's suggested gas price. Re-sending them
+	/// with a higher gas price is likely to get the sender's transactions moving.
+	#[serde(rename="replacementCandidates")]
+	pub replacement_candidates: Vec<H256>,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::{TxPoolStatus, TxPoolSender};
+
+	#[test]
+	fn status_serialization() {
+		let status = TxPoolStatus { pending: 3.into(), queued: 1.into() };
+		assert_eq!(serde_json::to_string(&status).unwrap(), r#"{"pending":"0x3","queued":"0x1"}"#);
+	}
+
+	#[test]
+	fn sender_serialization() {
+		let sender = TxPoolSender {
+			nonce: 2.into(),
+			pending: vec![],
+			queued: vec![],
+			nonce_gaps: vec![(2.into(), 4.into())],
+			replacement_candidates: vec![],
+		};
+		assert_eq!(
+			serde_json::to_string(&sender).unwrap(),
+			r#"{"nonce":"0x2","pending":[],"queued":[],"nonceGaps":[["0x2","0x4"]],"replacementCandidates":[]}"#
+		);
+	}
+}
@@ -0,0 +1,90 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Structured data accepted by `personal_signTypedData`.
+
+use std::collections::BTreeMap;
+use serde_json::Value;
+
+/// Single member of a struct type definition.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TypedDataMember {
+	/// Member name
+	pub name: String,
+	/// Member type, e.g. `address`, `uint256[]` or the name of another struct.
+	#[serde(rename="type")]
+	pub kind: String,
+}
+
+/// Typed structured data to be hashed and signed.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TypedData {
+	/// Struct type definitions, including `EIP712Domain`.
+	pub types: BTreeMap<String, Vec<TypedDataMember>>,
+	/// Struct type of the `message`.
+	#[serde(rename="primaryType")]
+	pub primary_type: String,
+	/// Signing domain, an instance of `EIP712Domain`.
+	pub domain: Value,
+	/// Message, an instance of `primaryType`.
+	pub message: Value,
+}
+
+/// Signed field rendered for display in the Trusted Signer.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TypedDataField {
+	/// Path of the field, e.g. `message.from.wallet`.
+	pub name: String,
+	/// Type of the field.
+	#[serde(rename="type")]
+	pub kind: String,
+	/// Normalized value of the field.
+	pub value: String,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::TypedData;
+
+	#[test]
+	fn should_deserialize_typed_data() {
+		let s = r#"{
+			"types": {
+				"EIP712Domain": [{"name": "name", "type": "string"}],
+				"Mail": [{"name": "contents", "type": "string"}]
+			},
+			"primaryType": "Mail",
+			"domain": {"name": "Ether Mail"},
+			"message": {"contents": "Hello, Bob!"}
+		}"#;
+		let data: TypedData = serde_json::from_str(s).unwrap();
+
+		assert_eq!(data.primary_type, "Mail");
+		assert_eq!(data.types["Mail"][0].kind, "string");
+		assert_eq!(data.message["contents"], serde_json::Value::String("Hello, Bob!".into()));
+	}
+
+	#[test]
+	fn should_reject_unknown_fields() {
+		let s = r#"{"types": {}, "primaryType": "Mail", "domain": {}, "message": {}, "version": 1}"#;
+
+		assert!(serde_json::from_str::<TypedData>(s).is_err());
+	}
+}
@@ -0,0 +1,73 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Vault signing policy.
+
+use ethcore::account_provider::VaultPolicy as EthVaultPolicy;
+use v1::types::{H160, U256};
+
+/// Restrictions on signing with accounts stored in a vault.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VaultPolicy {
+	/// Maximal value of a single transaction.
+	#[serde(rename="maxValue")]
+	pub max_value: Option<U256>,
+	/// If set, transactions may only be sent to these addresses.
+	#[serde(rename="allowedRecipients")]
+	pub allowed_recipients: Option<Vec<H160>>,
+	/// If set, only these signing operations are allowed
+	/// (`sendTransaction`, `signTransaction`, `sign`, `signTypedData`, `decrypt`).
+	#[serde(rename="allowedMethods")]
+	pub allowed_methods: Option<Vec<String>>,
+}
+
+impl From<EthVaultPolicy> for VaultPolicy {
+	fn from(p: EthVaultPolicy) -> Self {
+		VaultPolicy {
+			max_value: p.max_value.map(Into::into),
+			allowed_recipients: p.allowed_recipients.map(|recipients| recipients.into_iter().map(Into::into).collect()),
+			allowed_methods: p.allowed_methods,
+		}
+	}
+}
+
+impl Into<EthVaultPolicy> for VaultPolicy {
+	fn into(self) -> EthVaultPolicy {
+		EthVaultPolicy {
+			max_value: self.max_value.map(Into::into),
+			allowed_recipients: self.allowed_recipients.map(|recipients| recipients.into_iter().map(Into::into).collect()),
+			allowed_methods: self.allowed_methods,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::VaultPolicy;
+
+	#[test]
+	fn should_deserialize_partial_policy() {
+		let s = r#"{"maxValue":"0x3e8","allowedRecipients":["0x0000000000000000000000000000000000000001"]}"#;
+		let policy: VaultPolicy = serde_json::from_str(s).unwrap();
+
+		assert_eq!(policy, VaultPolicy {
+			max_value: Some(1000.into()),
+			allowed_recipients: Some(vec![1.into()]),
+			allowed_methods: None,
+		});
+	}
+}
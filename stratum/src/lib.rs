@@ -39,10 +39,13 @@ mod traits {
 	include!(concat!(env!("OUT_DIR"), "/traits.rs"));
 }
 
+mod workers;
+
 pub use traits::{
 	JobDispatcher, PushWorkHandler, Error, ServiceConfiguration,
 	RemoteWorkHandler, RemoteJobDispatcher,
 };
+pub use workers::{VarDiff, WorkerInfo};
 
 use jsonrpc_tcp_server::{
 	Server as JsonRpcServer, ServerBuilder as JsonRpcServerBuilder,
@@ -54,7 +57,9 @@ use std::sync::Arc;
 
 use std::net::SocketAddr;
 use std::collections::{HashSet, HashMap};
-use util::{H256, Hashable, RwLock, RwLockReadGuard};
+use std::time::Instant;
+use util::{H256, U256, Hashable, RwLock, RwLockReadGuard};
+use workers::{Worker, Share};
 
 type RpcResult = BoxFuture<jsonrpc_core::Value, jsonrpc_core::Error>;
 
@@ -124,6 +129,10 @@ pub struct Stratum {
 	dispatcher: Arc<JobDispatcher>,
 	/// Authorized workers (socket - worker_id)
 	workers: Arc<RwLock<HashMap<SocketAddr, String>>>,
+	/// Share accounting of authorized workers
+	stats: RwLock<HashMap<SocketAddr, Worker>>,
+	/// Variable difficulty settings, if enabled
+	vardiff: Option<VarDiff>,
 	/// Secret if any
 	secret: Option<H256>,
 	/// Dispatch notify couinter
@@ -143,6 +152,7 @@ impl Stratum {
 		addr: &SocketAddr,
 		dispatcher: Arc<JobDispatcher>,
 		secret: Option<H256>,
+		vardiff: Option<VarDiff>,
 	) -> Result<Arc<Stratum>, Error> {
 
 		let rpc = Arc::new(StratumRpc {
@@ -167,6 +177,8 @@ impl Stratum {
 			job_que: RwLock::new(HashSet::new()),
 			dispatcher: dispatcher,
 			workers: Arc::new(RwLock::new(HashMap::new())),
+			stats: RwLock::new(HashMap::new()),
+			vardiff: vardiff,
 			secret: secret,
 			notify_counter: RwLock::new(NOTIFY_COUNTER_INITIAL),
 		});
@@ -182,22 +194,95 @@ impl Stratum {
 		}
 	}
 
-	fn submit(&self, params: Params, _meta: SocketMetadata) -> RpcResult {
+	fn next_request_id(&self) -> u32 {
+		let mut counter = self.notify_counter.write();
+		if *counter == ::std::u32::MAX { *counter = NOTIFY_COUNTER_INITIAL; }
+		else { *counter = *counter + 1 }
+		*counter
+	}
+
+	fn notify_message(request_id: u32, payload: &str) -> String {
+		format!("{{ \"id\": {}, \"method\": \"mining.notify\", \"params\": {} }}", request_id, payload)
+	}
+
+	/// Submits share to the dispatcher and accounts it for the worker.
+	fn submit_share(&self, addr: &SocketAddr, payload: Vec<String>) -> Share {
+		let worker_difficulty = self.stats.read().get(addr).and_then(|worker| worker.difficulty());
+		let share_difficulty = self.dispatcher.share_difficulty(payload.clone());
+
+		let share = match self.dispatcher.submit(payload) {
+			Ok(()) => Share::Accepted(true),
+			Err(Error::StaleShare) => Share::Stale,
+			Err(submit_err) => match (worker_difficulty, share_difficulty) {
+				(Some(target), Some(achieved)) if achieved >= target => Share::Accepted(false),
+				_ => {
+					warn!("Error while submitting share: {:?}", submit_err);
+					Share::Rejected
+				},
+			},
+		};
+		trace!(target: "stratum", "Share from {}: {:?}", addr, share);
+
+		let now = Instant::now();
+		let retarget = {
+			let mut stats = self.stats.write();
+			let retarget = match stats.get_mut(addr) {
+				Some(worker) => {
+					// credit the assigned difficulty, or the achieved one if there is none
+					let credit = worker_difficulty.or(share_difficulty).unwrap_or_else(U256::zero);
+					worker.note_share(share, credit, now);
+					match self.vardiff {
+						Some(ref vardiff) => worker.retarget(vardiff, now),
+						None => None,
+					}
+				},
+				None => None,
+			};
+			retarget
+		};
+
+		if let Some(difficulty) = retarget {
+			if let Some(job) = self.dispatcher.job_for_difficulty(difficulty) {
+				let message = Self::notify_message(self.next_request_id(), &job);
+				if let Err(e) = self.tcp_dispatcher.push_message(addr, message) {
+					trace!(target: "stratum", "Failed to push retargeted work to {}: {:?}", addr, e);
+				}
+			}
+		}
+
+		share
+	}
+
+	/// Retargets idle variable difficulty workers and returns current difficulties of all of them.
+	fn worker_difficulties(&self) -> HashMap<SocketAddr, U256> {
+		let now = Instant::now();
+		let mut stats = self.stats.write();
+		let mut difficulties = HashMap::new();
+		for (addr, worker) in stats.iter_mut() {
+			if let Some(ref vardiff) = self.vardiff {
+				worker.retarget(vardiff, now);
+			}
+			if let Some(difficulty) = worker.difficulty() {
+				difficulties.insert(*addr, difficulty);
+			}
+		}
+		difficulties
+	}
+
+	fn submit(&self, params: Params, meta: SocketMetadata) -> RpcResult {
 		future::ok(match params {
 			Params::Array(vals) => {
 				// first two elements are service messages (worker_id & job_id)
-				match self.dispatcher.submit(vals.iter().skip(2)
+				let payload = vals.iter().skip(2)
 					.filter_map(|val| match val { &Value::String(ref str) => Some(str.to_owned()), _ => None })
-					.collect::<Vec<String>>()) {
-						Ok(()) => {
-							self.update_peers();
-							to_value(true)
-						},
-						Err(submit_err) => {
-							warn!("Error while submitting share: {:?}", submit_err);
-							to_value(false)
-						}
-					}
+					.collect::<Vec<String>>();
+				match self.submit_share(meta.addr(), payload) {
+					Share::Accepted(block) => {
+						if block { self.update_peers(); }
+						to_value(true)
+					},
+					Share::Stale | Share::Rejected => to_value(false),
+				}
 			},
 			_ => {
 				trace!(target: "stratum", "Invalid submit work format {:?}", params);
@@ -234,6 +319,7 @@ impl Stratum {
 				}
 			}
 			trace!(target: "stratum", "New worker #{} registered", worker_id);
+			self.stats.write().insert(meta.addr().clone(), Worker::new(worker_id.clone(), self.vardiff.as_ref(), Instant::now()));
 			self.workers.write().insert(meta.addr().clone(), worker_id);
 			to_value(true)
 		}).map(|v| v.expect("Only true/false is returned and it's always serializable; qed"))).boxed()
@@ -243,6 +329,12 @@ impl Stratum {
 		self.subscribers.read()
 	}
 
+	/// Statistics of authorized workers.
+	pub fn workers(&self) -> Vec<WorkerInfo> {
+		let now = Instant::now();
+		self.stats.read().iter().map(|(addr, worker)| worker.info(*addr, now)).collect()
+	}
+
 	pub fn maintain(&self) {
 		let mut job_que = self.job_que.write();
 		let job_payload = self.dispatcher.job();
@@ -258,19 +350,26 @@ impl PushWorkHandler for Stratum {
 	fn push_work_all(&self, payload: String) -> Result<(), Error> {
 		let hup_peers = {
 			let workers = self.workers.read();
-			let next_request_id = {
-				let mut counter = self.notify_counter.write();
-				if *counter == ::std::u32::MAX { *counter = NOTIFY_COUNTER_INITIAL; }
-				else { *counter = *counter + 1 }
-				*counter
-			};
+			let difficulties = self.worker_difficulties();
+			let next_request_id = self.next_request_id();
 
 			let mut hup_peers = HashSet::with_capacity(0); // most of the cases won't be needed, hence avoid allocation
-			let workers_msg = format!("{{ \"id\": {}, \"method\": \"mining.notify\", \"params\": {} }}", next_request_id, payload);
+			let workers_msg = Self::notify_message(next_request_id, &payload);
+			// variable difficulty workers get the same work with their own share target
+			let mut difficulty_msgs = HashMap::new();
 			trace!(target: "stratum", "pushing work for {} workers (payload: '{}')", workers.len(), &workers_msg);
 			for (ref addr, _) in workers.iter() {
 				trace!(target: "stratum", "pusing work to {}", addr);
-				match self.tcp_dispatcher.push_message(addr, workers_msg.clone()) {
+				let msg = match difficulties.get(addr) {
+					Some(difficulty) => difficulty_msgs.entry(*difficulty).or_insert_with(||
+						match self.dispatcher.job_for_difficulty(*difficulty) {
+							Some(job) => Self::notify_message(next_request_id, &job),
+							None => workers_msg.clone(),
+						}
+					).clone(),
+					None => workers_msg.clone(),
+				};
+				match self.tcp_dispatcher.push_message(addr, msg) {
 					Err(PushMessageError::NoSuchPeer) => {
 						trace!(target: "stratum", "Worker no longer connected: {}", &addr);
 						hup_peers.insert(*addr.clone());
@@ -286,7 +385,11 @@ impl PushWorkHandler for Stratum {
 
 		if !hup_peers.is_empty() {
 			let mut workers = self.workers.write();
-			for hup_peer in hup_peers { workers.remove(&hup_peer); }
+			let mut stats = self.stats.write();
+			for hup_peer in hup_peers {
+				workers.remove(&hup_peer);
+				stats.remove(&hup_peer);
+			}
 		}
 
 		Ok(())
@@ -362,7 +465,7 @@ mod tests {
 
 	#[test]
 	fn can_be_started() {
-		let stratum = Stratum::start(&SocketAddr::from_str("127.0.0.1:19980").unwrap(), Arc::new(VoidManager), None);
+		let stratum = Stratum::start(&SocketAddr::from_str("127.0.0.1:19980").unwrap(), Arc::new(VoidManager), None, None);
 		assert!(stratum.is_ok());
 	}
 
@@ -371,7 +474,7 @@ mod tests {
 		init_log();
 
 		let addr = SocketAddr::from_str("127.0.0.1:19985").unwrap();
-		let stratum = Stratum::start(&addr, Arc::new(VoidManager), None).unwrap();
+		let stratum = Stratum::start(&addr, Arc::new(VoidManager), None, None).unwrap();
 		let request = r#"{"jsonrpc": "2.0", "method": "mining.subscribe", "params": [], "id": 1}"#;
 		dummy_request(&addr, request);
 		assert_eq!(1, stratum.subscribers.read().len());
@@ -416,7 +519,7 @@ mod tests {
 	#[test]
 	fn receives_initial_paylaod() {
 		let addr = SocketAddr::from_str("127.0.0.1:19975").unwrap();
-		Stratum::start(&addr, DummyManager::new(), None).expect("There should be no error starting stratum");
+		Stratum::start(&addr, DummyManager::new(), None, None).expect("There should be no error starting stratum");
 		let request = r#"{"jsonrpc": "2.0", "method": "mining.subscribe", "params": [], "id": 2}"#;
 
 		let response = String::from_utf8(dummy_request(&addr, request)).unwrap();
@@ -430,6 +533,7 @@ mod tests {
 		let stratum = Stratum::start(
 			&addr,
 			Arc::new(DummyManager::build().of_initial(r#"["dummy autorize payload"]"#)),
+			None,
 			None
 		).expect("There should be no error starting stratum");
 
@@ -438,6 +542,7 @@ mod tests {
 
 		assert_eq!(terminated_str(r#"{"jsonrpc":"2.0","result":true,"id":1}"#), response);
 		assert_eq!(1, stratum.workers.read().len());
+		assert_eq!("miner1", stratum.workers()[0].id);
 	}
 
 	#[test]
@@ -448,6 +553,7 @@ mod tests {
 		let stratum = Stratum::start(
 			&addr,
 			Arc::new(DummyManager::build().of_initial(r#"["dummy autorize payload"]"#)),
+			None,
 			None
 		).expect("There should be no error starting stratum");
 
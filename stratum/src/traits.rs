@@ -16,7 +16,7 @@
 
 use std;
 use std::error::Error as StdError;
-use util::H256;
+use util::{H256, U256};
 use ipc::IpcConfig;
 use jsonrpc_tcp_server::PushMessageError;
 
@@ -25,6 +25,7 @@ use jsonrpc_tcp_server::PushMessageError;
 pub enum Error {
 	NoWork,
 	NoWorkers,
+	StaleShare,
 	Io(String),
 	Tcp(String),
 	Dispatch(String),
@@ -51,6 +52,10 @@ pub trait JobDispatcher: Send + Sync {
 	fn difficulty(&self) -> Option<String> { None }
 	// json for job update given worker_id (payload manager should split job!)
 	fn job(&self) -> Option<String> { None }
+	// json for job update with share target of given difficulty (for variable difficulty workers)
+	fn job_for_difficulty(&self, difficulty: U256) -> Option<String> { None }
+	// difficulty actually achieved by the miner job result, none if the result does not verify
+	fn share_difficulty(&self, payload: Vec<String>) -> Option<U256> { None }
 	// miner job result
 	fn submit(&self, payload: Vec<String>) -> Result<(), Error>;
 }
@@ -0,0 +1,274 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Per-connection share accounting and variable difficulty
+
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use util::U256;
+
+/// Window over which worker hash rate is estimated.
+const HASHRATE_WINDOW_SECS: u64 = 600;
+/// Number of accepted shares between difficulty retargets.
+const RETARGET_SHARES: u64 = 4;
+/// Maximal factor difficulty is allowed to change by in one retarget.
+const RETARGET_MAX_FACTOR: u64 = 4;
+
+/// Variable difficulty configuration.
+#[derive(Debug, PartialEq, Clone)]
+pub struct VarDiff {
+	/// Difficulty assigned to new workers and the lowest difficulty ever assigned.
+	pub min_difficulty: U256,
+	/// Desired average interval between shares of a single worker.
+	pub share_interval: Duration,
+}
+
+/// Outcome of a share submitted by a worker.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Share {
+	/// Share accepted; `true` if it also sealed a block.
+	Accepted(bool),
+	/// Share was computed for work which is no longer current.
+	Stale,
+	/// Share is invalid or doesn't meet the worker's difficulty.
+	Rejected,
+}
+
+/// Publicly visible statistics of a connected worker.
+#[derive(Debug, PartialEq, Clone)]
+pub struct WorkerInfo {
+	/// Worker id given during authorization.
+	pub id: String,
+	/// Address of the worker's connection.
+	pub address: SocketAddr,
+	/// Share difficulty assigned to the worker (`None` if block difficulty is used).
+	pub difficulty: Option<U256>,
+	/// Estimated hash rate (hashes per second).
+	pub hashrate: U256,
+	/// Number of accepted shares.
+	pub accepted: u64,
+	/// Number of stale shares.
+	pub stale: u64,
+	/// Number of rejected shares.
+	pub rejected: u64,
+	/// Number of blocks found.
+	pub blocks: u64,
+}
+
+impl WorkerInfo {
+	/// Ratio of stale shares to all submitted shares.
+	pub fn stale_ratio(&self) -> f64 {
+		let total = self.accepted + self.stale + self.rejected;
+		if total == 0 {
+			0.0
+		} else {
+			self.stale as f64 / total as f64
+		}
+	}
+}
+
+/// Share accounting for a single connection.
+pub struct Worker {
+	id: String,
+	difficulty: Option<U256>,
+	accepted: u64,
+	stale: u64,
+	rejected: u64,
+	blocks: u64,
+	/// Credited difficulty of recently accepted shares.
+	shares: VecDeque<(Instant, U256)>,
+	/// Last retarget and number of shares accepted since.
+	retarget: (Instant, u64),
+}
+
+impl Worker {
+	/// New worker, with difficulty taken from variable difficulty settings (if any).
+	pub fn new(id: String, vardiff: Option<&VarDiff>, now: Instant) -> Self {
+		Worker {
+			id: id,
+			difficulty: vardiff.map(|v| v.min_difficulty),
+			accepted: 0,
+			stale: 0,
+			rejected: 0,
+			blocks: 0,
+			shares: VecDeque::new(),
+			retarget: (now, 0),
+		}
+	}
+
+	/// Difficulty worker shares are checked against (`None` if block difficulty is used).
+	pub fn difficulty(&self) -> Option<U256> {
+		self.difficulty
+	}
+
+	/// Account a share, crediting `difficulty` for accepted ones.
+	pub fn note_share(&mut self, share: Share, difficulty: U256, now: Instant) {
+		match share {
+			Share::Accepted(block) => {
+				self.accepted += 1;
+				if block { self.blocks += 1; }
+				self.shares.push_back((now, difficulty));
+				self.retarget.1 += 1;
+			},
+			Share::Stale => self.stale += 1,
+			Share::Rejected => self.rejected += 1,
+		}
+		self.prune(now);
+	}
+
+	/// Adjust worker difficulty so that it submits a share every `share_interval` on average.
+	/// Returns new difficulty if it has changed.
+	pub fn retarget(&mut self, vardiff: &VarDiff, now: Instant) -> Option<U256> {
+		let current = match self.difficulty {
+			Some(difficulty) => difficulty,
+			None => return None,
+		};
+		let (since, shares) = self.retarget;
+		let elapsed = now.duration_since(since);
+		let target = vardiff.share_interval;
+		let idle = shares == 0 && elapsed >= target * RETARGET_MAX_FACTOR as u32;
+		if shares < RETARGET_SHARES && !idle {
+			return None;
+		}
+
+		let elapsed_ms = U256::from(duration_millis(elapsed)) + U256::one();
+		let target_ms = U256::from(duration_millis(target)) * U256::from(::std::cmp::max(shares, 1));
+		let lowest = ::std::cmp::max(current / U256::from(RETARGET_MAX_FACTOR), vardiff.min_difficulty);
+		let highest = match current.overflowing_mul(U256::from(RETARGET_MAX_FACTOR)) {
+			(_, true) => U256::max_value(),
+			(highest, false) => highest,
+		};
+		let proposed = match current.overflowing_mul(target_ms) {
+			(_, true) => highest,
+			(value, false) => value / elapsed_ms,
+		};
+		let difficulty = ::std::cmp::min(::std::cmp::max(proposed, lowest), highest);
+
+		self.retarget = (now, 0);
+		if difficulty == current {
+			None
+		} else {
+			trace!(target: "stratum", "Worker {} difficulty retargeted {} -> {}", self.id, current, difficulty);
+			self.difficulty = Some(difficulty);
+			Some(difficulty)
+		}
+	}
+
+	/// Public statistics of this worker.
+	pub fn info(&self, address: SocketAddr, now: Instant) -> WorkerInfo {
+		let window = Duration::from_secs(HASHRATE_WINDOW_SECS);
+		let work = self.shares.iter()
+			.filter(|&&(at, _)| now.duration_since(at) < window)
+			.fold(U256::zero(), |acc, &(_, difficulty)| acc.overflowing_add(difficulty).0);
+
+		WorkerInfo {
+			id: self.id.clone(),
+			address: address,
+			difficulty: self.difficulty,
+			hashrate: work / U256::from(HASHRATE_WINDOW_SECS),
+			accepted: self.accepted,
+			stale: self.stale,
+			rejected: self.rejected,
+			blocks: self.blocks,
+		}
+	}
+
+	fn prune(&mut self, now: Instant) {
+		let window = Duration::from_secs(HASHRATE_WINDOW_SECS);
+		while self.shares.front().map_or(false, |&(at, _)| now.duration_since(at) >= window) {
+			self.shares.pop_front();
+		}
+	}
+}
+
+fn duration_millis(duration: Duration) -> u64 {
+	duration.as_secs() * 1000 + duration.subsec_nanos() as u64 / 1_000_000
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::{Duration, Instant};
+	use util::U256;
+	use super::*;
+
+	fn vardiff() -> VarDiff {
+		VarDiff {
+			min_difficulty: U256::from(1000),
+			share_interval: Duration::from_secs(10),
+		}
+	}
+
+	#[test]
+	fn should_account_shares_and_estimate_hashrate() {
+		// given
+		let now = Instant::now();
+		let vardiff = vardiff();
+		let mut worker = Worker::new("miner1".into(), Some(&vardiff), now);
+
+		// when
+		worker.note_share(Share::Accepted(false), U256::from(60_000), now);
+		worker.note_share(Share::Accepted(true), U256::from(60_000), now);
+		worker.note_share(Share::Stale, U256::from(60_000), now);
+		worker.note_share(Share::Rejected, U256::from(60_000), now);
+
+		// then
+		let info = worker.info("127.0.0.1:8008".parse().unwrap(), now);
+		assert_eq!(info.difficulty, Some(U256::from(1000)));
+		assert_eq!(info.accepted, 2);
+		assert_eq!(info.blocks, 1);
+		assert_eq!(info.stale, 1);
+		assert_eq!(info.rejected, 1);
+		assert_eq!(info.hashrate, U256::from(200));
+		assert_eq!(info.stale_ratio(), 0.25);
+	}
+
+	#[test]
+	fn should_retarget_difficulty_to_share_interval() {
+		// given
+		let start = Instant::now();
+		let vardiff = vardiff();
+		let mut worker = Worker::new("miner1".into(), Some(&vardiff), start);
+
+		// when
+		for _ in 0..4 {
+			worker.note_share(Share::Accepted(false), U256::from(1000), start);
+		}
+		let raised = worker.retarget(&vardiff, start + Duration::from_secs(20));
+
+		// then
+		assert_eq!(raised, Some(U256::from(1999)));
+
+		// when
+		let idle = worker.retarget(&vardiff, start + Duration::from_secs(20 + 40));
+
+		// then
+		assert_eq!(idle, Some(U256::from(1000)));
+		assert_eq!(worker.retarget(&vardiff, start + Duration::from_secs(200)), None);
+	}
+
+	#[test]
+	fn should_not_retarget_without_vardiff() {
+		let now = Instant::now();
+		let mut worker = Worker::new("miner1".into(), None, now);
+		for _ in 0..10 {
+			worker.note_share(Share::Accepted(false), U256::from(1000), now);
+		}
+
+		assert_eq!(worker.retarget(&vardiff(), now + Duration::from_secs(1)), None);
+		assert_eq!(worker.difficulty(), None);
+	}
+}
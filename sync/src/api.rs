@@ -64,6 +64,8 @@ pub struct SyncConfig {
 	pub warp_sync: bool,
 	/// Enable light client server.
 	pub serve_light: bool,
+	/// Number of peers which must agree on a snapshot manifest before it's restored.
+	pub snapshot_manifest_votes: usize,
 }
 
 impl Default for SyncConfig {
@@ -77,6 +79,7 @@ impl Default for SyncConfig {
 			fork_block: None,
 			warp_sync: false,
 			serve_light: false,
+			snapshot_manifest_votes: 3,
 		}
 	}
 }
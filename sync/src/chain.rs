@@ -128,6 +128,8 @@ const MAX_NEW_BLOCK_AGE: BlockNumber = 20;
 const MAX_TRANSACTION_SIZE: usize = 300*1024;
 // Maximal number of transactions in sent in single packet.
 const MAX_TRANSACTIONS_TO_PROPAGATE: usize = 64;
+// Number of blocks after which unmined local transactions are broadcast again.
+const LOCAL_TRANSACTIONS_REBROADCAST_BLOCKS: BlockNumber = 10;
 // Min number of blocks to be behind for a snapshot sync
 const SNAPSHOT_RESTORE_THRESHOLD: BlockNumber = 100000;
 const SNAPSHOT_MIN_PEERS: usize = 3;
@@ -158,8 +160,6 @@ pub const SNAPSHOT_SYNC_PACKET_COUNT: u8 = 0x16;
 
 const MAX_SNAPSHOT_CHUNKS_DOWNLOAD_AHEAD: usize = 3;
 
-const MIN_SUPPORTED_SNAPSHOT_MANIFEST_VERSION: u64 = 1;
-
 const WAIT_PEERS_TIMEOUT_SEC: u64 = 5;
 const STATUS_TIMEOUT_SEC: u64 = 5;
 const HEADERS_TIMEOUT_SEC: u64 = 15;
@@ -364,6 +364,8 @@ pub struct ChainSync {
 	old_blocks: Option<BlockDownloader>,
 	/// Last propagated block number
 	last_sent_block_number: BlockNumber,
+	/// Best block number at the time local transactions were last re-broadcast
+	last_local_rebroadcast: BlockNumber,
 	/// Network ID
 	network_id: u64,
 	/// Optional fork block to check
@@ -379,6 +381,8 @@ pub struct ChainSync {
 	transactions_stats: TransactionsStats,
 	/// Enable ancient block downloading
 	download_old_blocks: bool,
+	/// Number of peers which must agree on a snapshot manifest before it's restored.
+	snapshot_manifest_votes: usize,
 }
 
 type RlpResponseResult = Result<Option<(PacketId, RlpStream)>, PacketDecodeError>;
@@ -397,9 +401,11 @@ impl ChainSync {
 			new_blocks: BlockDownloader::new(false, &chain_info.best_block_hash, chain_info.best_block_number),
 			old_blocks: None,
 			last_sent_block_number: 0,
+			last_local_rebroadcast: 0,
 			network_id: config.network_id,
 			fork_block: config.fork_block,
 			download_old_blocks: config.download_old_blocks,
+			snapshot_manifest_votes: config.snapshot_manifest_votes,
 			snapshot: Snapshot::new(),
 			sync_start_time: None,
 			transactions_stats: TransactionsStats::default(),
@@ -1036,29 +1042,67 @@ impl ChainSync {
 			}
 			Ok(manifest) => manifest,
 		};
-		if manifest.version < MIN_SUPPORTED_SNAPSHOT_MANIFEST_VERSION {
-			trace!(target: "sync", "{}: Snapshot manifest version too low: {}", peer_id, manifest.version);
-			io.disable_peer(peer_id);
+		if let Err(e) = ::ethcore::snapshot::chunk_decoders(manifest.version) {
+			// not the peer's fault; its snapshot just can't be restored here, so it isn't asked again.
+			debug!(target: "sync", "{}: Ignored snapshot manifest: {}", peer_id, e);
+			if let Some(peer) = self.peers.get_mut(&peer_id) {
+				peer.snapshot_number = None;
+				peer.snapshot_hash = None;
+			}
+			self.maybe_select_snapshot_manifest(io);
 			self.continue_sync(io);
 			return Ok(());
 		}
-		self.snapshot.reset_to(&manifest, &manifest_rlp.as_raw().sha3());
-		io.snapshot_service().begin_restore(manifest);
-		self.state = SyncState::SnapshotData;
-
-		// give a task to the same peer first.
-		self.sync_peer(io, peer_id, false);
+		self.snapshot.note_manifest(peer_id, manifest, manifest_rlp.as_raw().sha3());
+		if self.maybe_select_snapshot_manifest(io) {
+			// give a task to the same peer first.
+			self.sync_peer(io, peer_id, false);
+		}
 		// give tasks to other peers
 		self.continue_sync(io);
 		Ok(())
 	}
 
+	/// Begin restoring the manifest most peers agree on, once enough of them have answered.
+	/// Returns true if restoration began.
+	fn maybe_select_snapshot_manifest(&mut self, io: &mut SyncIo) -> bool {
+		if self.state != SyncState::SnapshotManifest {
+			return false;
+		}
+
+		// fewer votes are needed when fewer peers were asked, as for an unconfirmed snapshot sync.
+		let outstanding = self.peers.values().filter(|p| p.asking == PeerAsking::SnapshotManifest).count();
+		let threshold = min(self.snapshot_manifest_votes, self.snapshot.manifest_votes() + outstanding);
+
+		let (manifest, hash) = match self.snapshot.best_manifest(threshold) {
+			Some(best) => best,
+			None => {
+				if outstanding == 0 {
+					trace!(target: "sync", "Peers disagree on snapshot manifest, waiting for more peers");
+					self.snapshot.clear();
+					self.state = SyncState::WaitingPeers;
+				}
+				return false;
+			}
+		};
+
+		trace!(target: "sync", "Selected snapshot manifest {:?} for block #{}", hash, manifest.block_number);
+		self.snapshot.reset_to(&manifest, &hash);
+		// chunks restored before an interrupted restoration of the same snapshot aren't downloaded again.
+		let completed = io.snapshot_service().completed_chunks(&manifest);
+		self.snapshot.note_completed(&completed);
+		io.snapshot_service().begin_restore(manifest);
+		self.state = SyncState::SnapshotData;
+		true
+	}
+
 	/// Called when snapshot data is downloaded from a peer.
 	fn on_snapshot_data(&mut self, io: &mut SyncIo, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
 		if !self.peers.get(&peer_id).map_or(false, |p| p.can_sync()) {
 			trace!(target: "sync", "Ignoring snapshot data from unconfirmed peer {}", peer_id);
 			return Ok(());
 		}
+		let requested = self.peers.get(&peer_id).and_then(|p| p.asking_snapshot_data);
 		self.clear_peer_download(peer_id);
 		if !self.reset_peer_asking(peer_id, PeerAsking::SnapshotData) || (self.state != SyncState::SnapshotData && self.state != SyncState::SnapshotWaiting) {
 			trace!(target: "sync", "{}: Ignored unexpected snapshot data", peer_id);
@@ -1091,8 +1135,15 @@ impl ChainSync {
 				io.snapshot_service().restore_state_chunk(hash, snapshot_data);
 			}
 			Err(()) => {
-				trace!(target: "sync", "{}: Got bad snapshot chunk", peer_id);
-				io.disconnect_peer(peer_id);
+				if requested.map_or(false, |hash| hash != snapshot_data.sha3()) {
+					// the peer served something other than the chunk asked for.
+					debug!(target: "sync", "{}: Got invalid snapshot chunk, disabling peer", peer_id);
+					self.snapshot.remove_manifest_vote(peer_id);
+					io.disable_peer(peer_id);
+				} else {
+					trace!(target: "sync", "{}: Got bad snapshot chunk", peer_id);
+					io.disconnect_peer(peer_id);
+				}
 				self.continue_sync(io);
 				return Ok(());
 			}
@@ -1118,6 +1169,9 @@ impl ChainSync {
 			self.clear_peer_download(peer);
 			self.peers.remove(&peer);
 			self.active_peers.remove(&peer);
+			self.snapshot.remove_manifest_vote(peer);
+			// the manifest vote may have been waiting on this peer.
+			self.maybe_select_snapshot_manifest(io);
 			self.continue_sync(io);
 		}
 	}
@@ -1229,7 +1283,7 @@ impl ChainSync {
 				},
 				SyncState::SnapshotData => {
 					if let RestorationStatus::Ongoing { state_chunks_done, block_chunks_done, .. } = io.snapshot_service().status() {
-						if self.snapshot.done_chunks() - (state_chunks_done + block_chunks_done) as usize > MAX_SNAPSHOT_CHUNKS_DOWNLOAD_AHEAD {
+						if self.snapshot.done_chunks().saturating_sub((state_chunks_done + block_chunks_done) as usize) > MAX_SNAPSHOT_CHUNKS_DOWNLOAD_AHEAD {
 							trace!(target: "sync", "Snapshot queue full, pausing sync");
 							self.state = SyncState::SnapshotWaiting;
 							return;
@@ -1807,7 +1861,7 @@ impl ChainSync {
 					self.continue_sync(io);
 				},
 				RestorationStatus::Ongoing { state_chunks_done, block_chunks_done, .. } => {
-					if !self.snapshot.is_complete() && self.snapshot.done_chunks() - (state_chunks_done + block_chunks_done) as usize <= MAX_SNAPSHOT_CHUNKS_DOWNLOAD_AHEAD {
+					if !self.snapshot.is_complete() && self.snapshot.done_chunks().saturating_sub((state_chunks_done + block_chunks_done) as usize) <= MAX_SNAPSHOT_CHUNKS_DOWNLOAD_AHEAD {
 						trace!(target:"sync", "Resuming snapshot sync");
 						self.state = SyncState::SnapshotData;
 						self.continue_sync(io);
@@ -2143,6 +2197,29 @@ impl ChainSync {
 			self.peers.values_mut().nth(peer).map(|mut peer_info|
 				peer_info.last_sent_transactions.clear()
 			);
+			self.rebroadcast_local_transactions(io);
+		}
+	}
+
+	/// Makes local transactions that are still not mined eligible for propagation again.
+	/// They are sent on the next propagation round to a fresh random selection of peers.
+	fn rebroadcast_local_transactions(&mut self, io: &mut SyncIo) {
+		let best_block = io.chain().chain_info().best_block_number;
+		if best_block.saturating_sub(self.last_local_rebroadcast) < LOCAL_TRANSACTIONS_REBROADCAST_BLOCKS {
+			return;
+		}
+		self.last_local_rebroadcast = best_block;
+
+		let local = io.chain().local_pending_transactions();
+		if local.is_empty() {
+			return;
+		}
+
+		trace!(target: "sync", "Re-broadcasting {} local transactions.", local.len());
+		for peer_info in self.peers.values_mut() {
+			for hash in &local {
+				peer_info.last_sent_transactions.remove(hash);
+			}
 		}
 	}
 
@@ -2684,6 +2761,33 @@ mod tests {
 		assert_eq!(0x02, queue.read()[1].packet_id);
 	}
 
+	#[test]
+	fn rebroadcasts_unmined_local_transactions() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::Uncle);
+		client.insert_local_transaction_to_queue();
+		client.insert_transaction_to_queue();
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(1), &client);
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+		let peer_count = sync.propagate_new_transactions(&mut io);
+		// Local transactions become eligible for propagation again
+		sync.rebroadcast_local_transactions(&mut io);
+		let peer_count2 = sync.propagate_new_transactions(&mut io);
+		// But not before enough blocks have been imported
+		sync.rebroadcast_local_transactions(&mut io);
+		let peer_count3 = sync.propagate_new_transactions(&mut io);
+
+		assert_eq!(1, peer_count);
+		assert_eq!(1, peer_count2);
+		assert_eq!(0, peer_count3);
+		assert_eq!(2, io.packets.len());
+		// Only the local transaction is sent again
+		assert_eq!(2, Rlp::new(&io.packets[0].data).item_count());
+		assert_eq!(1, Rlp::new(&io.packets[1].data).item_count());
+	}
+
 	#[test]
 	fn should_maintain_transations_propagation_stats() {
 		let mut client = TestBlockChainClient::new();
@@ -16,8 +16,9 @@
 
 
 use util::{H256, Hashable};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use ethcore::snapshot::ManifestData;
+use network::PeerId;
 
 #[derive(PartialEq, Eq, Debug)]
 pub enum ChunkType {
@@ -31,6 +32,7 @@ pub struct Snapshot {
 	downloading_chunks: HashSet<H256>,
 	completed_chunks: HashSet<H256>,
 	snapshot_hash: Option<H256>,
+	manifest_votes: HashMap<PeerId, (ManifestData, H256)>,
 }
 
 impl Snapshot {
@@ -42,6 +44,7 @@ impl Snapshot {
 			downloading_chunks: HashSet::new(),
 			completed_chunks: HashSet::new(),
 			snapshot_hash: None,
+			manifest_votes: HashMap::new(),
 		}
 	}
 
@@ -52,6 +55,7 @@ impl Snapshot {
 		self.downloading_chunks.clear();
 		self.completed_chunks.clear();
 		self.snapshot_hash = None;
+		self.manifest_votes.clear();
 	}
 
 	/// Check if currently downloading a snapshot.
@@ -59,14 +63,66 @@ impl Snapshot {
 		self.snapshot_hash.is_some()
 	}
 
+	/// Note the manifest served by a peer. Each peer has a single vote.
+	pub fn note_manifest(&mut self, peer: PeerId, manifest: ManifestData, hash: H256) {
+		self.manifest_votes.insert(peer, (manifest, hash));
+	}
+
+	/// Forget the manifest served by a peer.
+	pub fn remove_manifest_vote(&mut self, peer: PeerId) {
+		self.manifest_votes.remove(&peer);
+	}
+
+	/// Number of peers which have served a manifest.
+	pub fn manifest_votes(&self) -> usize {
+		self.manifest_votes.len()
+	}
+
+	/// Pick a manifest out of those served. Manifests are grouped by snapshot block and
+	/// state root, and the group attested by the most peers wins if it has at least
+	/// `threshold` of them. Within it, the manifest served by the most peers is chosen.
+	pub fn best_manifest(&self, threshold: usize) -> Option<(ManifestData, H256)> {
+		let mut groups: HashMap<(u64, H256), HashMap<H256, usize>> = HashMap::new();
+		for &(ref manifest, ref hash) in self.manifest_votes.values() {
+			*groups.entry((manifest.block_number, manifest.state_root))
+				.or_insert_with(HashMap::new)
+				.entry(*hash)
+				.or_insert(0) += 1;
+		}
+
+		// ties are broken in favour of the later snapshot, then by hash.
+		let best = groups.into_iter()
+			.map(|(key, hashes)| (hashes.values().sum::<usize>(), key, hashes))
+			.max_by_key(|&(votes, key, _)| (votes, key.0, key.1));
+
+		let hashes = match best {
+			Some((votes, _, hashes)) if votes >= threshold => hashes,
+			_ => return None,
+		};
+
+		hashes.into_iter()
+			.max_by_key(|&(hash, votes)| (votes, hash))
+			.and_then(|(hash, _)| self.manifest_votes.values().find(|&&(_, ref h)| h == &hash).cloned())
+	}
+
 	/// Reset collection for a manifest RLP
 	pub fn reset_to(&mut self, manifest: &ManifestData, hash: &H256) {
 		self.clear();
 		self.pending_state_chunks = manifest.state_hashes.clone();
-		self.pending_block_chunks = manifest.block_hashes.clone();
+		// trace chunks are restored along with the block chunks.
+		self.pending_block_chunks = manifest.block_hashes.iter().chain(manifest.trace_hashes.iter()).cloned().collect();
 		self.snapshot_hash = Some(hash.clone());
 	}
 
+	/// Mark chunks restored earlier as completed, so they aren't downloaded again.
+	pub fn note_completed(&mut self, hashes: &[H256]) {
+		for hash in hashes {
+			if self.pending_state_chunks.contains(hash) || self.pending_block_chunks.contains(hash) {
+				self.completed_chunks.insert(hash.clone());
+			}
+		}
+	}
+
 	/// Validate chunk and mark it as downloaded
 	pub fn validate_chunk(&mut self, chunk: &[u8]) -> Result<ChunkType, ()> {
 		let hash = chunk.sha3();
@@ -137,7 +193,8 @@ mod test {
 		snapshot.pending_state_chunks.is_empty() &&
 		snapshot.completed_chunks.is_empty() &&
 		snapshot.downloading_chunks.is_empty() &&
-		snapshot.snapshot_hash.is_none()
+		snapshot.snapshot_hash.is_none() &&
+		snapshot.manifest_votes.is_empty()
 	}
 
 	fn test_manifest() -> (ManifestData, H256, Vec<Bytes>, Vec<Bytes>) {
@@ -150,6 +207,7 @@ mod test {
 			state_root: H256::new(),
 			block_number: 42,
 			block_hash: H256::new(),
+			trace_hashes: Vec::new(),
 		};
 		let mhash = manifest.clone().into_rlp().sha3();
 		(manifest, mhash, state_chunks, block_chunks)
@@ -205,5 +263,47 @@ mod test {
 		assert_eq!(snapshot.done_chunks(), snapshot.total_chunks());
 		assert_eq!(snapshot.snapshot_hash(), Some(manifest.into_rlp().sha3()));
 	}
-}
 
+	#[test]
+	fn skips_completed_chunks() {
+		let mut snapshot = Snapshot::new();
+		let (manifest, mhash, state_chunks, _) = test_manifest();
+		snapshot.reset_to(&manifest, &mhash);
+
+		snapshot.note_completed(&[manifest.state_hashes[0], manifest.block_hashes[0], H256::random()]);
+		assert_eq!(snapshot.done_chunks(), 2);
+
+		let requested: Vec<H256> = (0..38).map(|_| snapshot.needed_chunk().unwrap()).collect();
+		assert!(snapshot.needed_chunk().is_none());
+		assert!(!requested.contains(&manifest.state_hashes[0]));
+		assert!(!requested.contains(&manifest.block_hashes[0]));
+
+		// the chunk isn't processed again.
+		assert!(snapshot.validate_chunk(&state_chunks[0]).is_err());
+	}
+
+	#[test]
+	fn selects_manifest_by_votes() {
+		let mut snapshot = Snapshot::new();
+		let (manifest, mhash, _, _) = test_manifest();
+		let (mut other, _, _, _) = test_manifest();
+		other.state_root = H256::random();
+		let ohash = other.clone().into_rlp().sha3();
+
+		snapshot.note_manifest(0, manifest.clone(), mhash);
+		snapshot.note_manifest(1, other.clone(), ohash);
+		assert_eq!(snapshot.best_manifest(2), None);
+
+		snapshot.note_manifest(2, manifest.clone(), mhash);
+		assert_eq!(snapshot.best_manifest(2), Some((manifest.clone(), mhash)));
+		assert_eq!(snapshot.best_manifest(3), None);
+
+		// a peer only has a single vote.
+		snapshot.note_manifest(0, other.clone(), ohash);
+		assert_eq!(snapshot.best_manifest(2), Some((other, ohash)));
+		assert_eq!(snapshot.manifest_votes(), 3);
+
+		snapshot.remove_manifest_vote(0);
+		assert_eq!(snapshot.best_manifest(2), None);
+	}
+}
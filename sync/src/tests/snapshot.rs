@@ -55,6 +55,7 @@ impl TestSnapshotService {
 			state_root: H256::new(),
 			block_number: block_number,
 			block_hash: block_hash,
+			trace_hashes: Vec::new(),
 		};
 		let mut chunks: HashMap<H256, Bytes> = state_chunks.into_iter().map(|data| (data.sha3(), data)).collect();
 		chunks.extend(block_chunks.into_iter().map(|data| (data.sha3(), data)));
@@ -93,7 +94,10 @@ impl SnapshotService for TestSnapshotService {
 	}
 
 	fn begin_restore(&self, manifest: ManifestData) {
-		*self.restoration_manifest.lock() = Some(manifest);
+		let mut restoration_manifest = self.restoration_manifest.lock();
+		if restoration_manifest.as_ref() == Some(&manifest) { return }
+
+		*restoration_manifest = Some(manifest);
 		self.state_restoration_chunks.lock().clear();
 		self.block_restoration_chunks.lock().clear();
 	}
@@ -119,6 +123,16 @@ impl SnapshotService for TestSnapshotService {
 	fn provide_canon_hashes(&self, hashes: &[(u64, H256)]) {
 		self.canon_hashes.lock().extend(hashes.iter().cloned());
 	}
+
+	fn completed_chunks(&self, manifest: &ManifestData) -> Vec<H256> {
+		match *self.restoration_manifest.lock() {
+			Some(ref m) if m == manifest => self.state_restoration_chunks.lock().keys()
+				.chain(self.block_restoration_chunks.lock().keys())
+				.cloned()
+				.collect(),
+			_ => Vec::new(),
+		}
+	}
 }
 
 #[test]
@@ -52,6 +52,8 @@ impl<K: Eq + Hash, V: HeapSizeOf> MemoryLruCache<K, V> {
 			self.inner.set_capacity(cap * 2);
 		}
 
+		self.cur_size += val.heap_size_of_children();
+
 		// account for any element displaced from the cache.
 		if let Some(lru) = self.inner.insert(key, val) {
 			self.cur_size -= lru.heap_size_of_children();
@@ -82,6 +82,25 @@ pub trait HashDB: AsHashDB + Send + Sync {
 	/// ```
 	fn insert(&mut self, value: &[u8]) -> H256;
 
+	/// Insert several data items at once, returning their hashes in the same order. Equivalent
+	/// to calling `insert()` for each of them, but implementations may do it more cheaply.
+	///
+	/// # Examples
+	/// ```rust
+	/// extern crate ethcore_util;
+	/// use ethcore_util::hashdb::*;
+	/// use ethcore_util::memorydb::*;
+	/// fn main() {
+	///   let mut m = MemoryDB::new();
+	///   let keys = m.insert_batch(&["Hello".as_bytes(), "world!".as_bytes()]);
+	///   assert!(m.contains(&keys[0]));
+	///   assert!(m.contains(&keys[1]));
+	/// }
+	/// ```
+	fn insert_batch(&mut self, values: &[&[u8]]) -> Vec<H256> {
+		values.iter().map(|value| self.insert(value)).collect()
+	}
+
 	/// Like `insert()` , except you provide the key and the data is all moved.
 	fn emplace(&mut self, key: H256, value: DBValue);
 
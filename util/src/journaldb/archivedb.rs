@@ -93,6 +93,10 @@ impl HashDB for ArchiveDB {
 		self.overlay.insert(value)
 	}
 
+	fn insert_batch(&mut self, values: &[&[u8]]) -> Vec<H256> {
+		self.overlay.insert_batch(values)
+	}
+
 	fn emplace(&mut self, key: H256, value: DBValue) {
 		self.overlay.emplace(key, value);
 	}
@@ -20,7 +20,7 @@ use common::*;
 use rlp::*;
 use hashdb::*;
 use memorydb::*;
-use super::{DB_PREFIX_LEN, LATEST_ERA_KEY};
+use super::{DB_PREFIX_LEN, LATEST_ERA_KEY, read_journal};
 use super::traits::JournalDB;
 use kvdb::{KeyValueDB, DBTransaction};
 
@@ -276,28 +276,10 @@ impl EarlyMergeDB {
 
 	fn read_refs(db: &KeyValueDB, col: Option<u32>) -> (Option<u64>, HashMap<H256, RefInfo>) {
 		let mut refs = HashMap::new();
-		let mut latest_era = None;
-		if let Some(val) = db.get(col, &LATEST_ERA_KEY).expect("Low-level database error.") {
-			let mut era = decode::<u64>(&val);
-			latest_era = Some(era);
-			loop {
-				let mut index = 0usize;
-				while let Some(rlp_data) = db.get(col, {
-					let mut r = RlpStream::new_list(3);
-					r.append(&era);
-					r.append(&index);
-					r.append(&&PADDING[..]);
-					&r.drain()
-				}).expect("Low-level database error.") {
-					let rlp = Rlp::new(&rlp_data);
-					let inserts: Vec<H256> = rlp.list_at(1);
-					Self::replay_keys(&inserts, db, col, &mut refs);
-					index += 1;
-				};
-				if index == 0 || era == 0 {
-					break;
-				}
-				era -= 1;
+		let (latest_era, eras) = read_journal(db, col, |rlp| rlp.list_at::<H256>(1));
+		for (_, records) in eras {
+			for inserts in records {
+				Self::replay_keys(&inserts, db, col, &mut refs);
 			}
 		}
 		(latest_era, refs)
@@ -334,6 +316,9 @@ impl HashDB for EarlyMergeDB {
 	fn insert(&mut self, value: &[u8]) -> H256 {
 		self.overlay.insert(value)
 	}
+	fn insert_batch(&mut self, values: &[&[u8]]) -> Vec<H256> {
+		self.overlay.insert_batch(values)
+	}
 	fn emplace(&mut self, key: H256, value: DBValue) {
 		self.overlay.emplace(key, value);
 	}
@@ -17,6 +17,8 @@
 //! `JournalDB` interface and implementation.
 
 use common::*;
+use rlp::{RlpStream, UntrustedRlp, DecoderError, encode};
+use kvdb::{KeyValueDB, DBTransaction};
 
 /// Export the journaldb module.
 pub mod traits;
@@ -28,6 +30,34 @@ mod refcounteddb;
 /// Export the `JournalDB` trait.
 pub use self::traits::JournalDB;
 
+/// Outcome of a single `JournalDB::compact` step.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CompactionReport {
+	/// Number of eras whose journal records were applied and removed.
+	pub eras: u64,
+	/// Total size of the removed journal records, in bytes.
+	pub reclaimed_bytes: u64,
+	/// The era the next step will start from.
+	pub next_era: u64,
+}
+
+/// Size of the in-memory overlay of recent history kept by a `JournalDB`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct OverlayStats {
+	/// Number of nodes whose values are held in memory.
+	pub resident_nodes: usize,
+	/// Total size of the values held in memory, in bytes.
+	pub resident_bytes: usize,
+	/// Number of nodes whose values were dropped from memory and live only on disk.
+	pub spilled_nodes: usize,
+	/// Total size of the spilled values, in bytes.
+	pub spilled_bytes: usize,
+	/// How many times a node was spilled so far.
+	pub spills: usize,
+	/// How many times a spilled value was read back from disk so far.
+	pub disk_reads: usize,
+}
+
 /// A journal database algorithm.
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Algorithm {
@@ -116,10 +146,19 @@ impl fmt::Display for Algorithm {
 
 /// Create a new `JournalDB` trait object over a generic key-value database.
 pub fn new(backing: Arc<::kvdb::KeyValueDB>, algorithm: Algorithm, col: Option<u32>) -> Box<JournalDB> {
+	new_with_overlay_budget(backing, algorithm, col, None)
+}
+
+/// Create a new `JournalDB` trait object over a generic key-value database, keeping the values
+/// of its in-memory overlay within `overlay_budget` bytes where the algorithm has one.
+pub fn new_with_overlay_budget(backing: Arc<::kvdb::KeyValueDB>, algorithm: Algorithm, col: Option<u32>, overlay_budget: Option<usize>) -> Box<JournalDB> {
 	match algorithm {
 		Algorithm::Archive => Box::new(archivedb::ArchiveDB::new(backing, col)),
 		Algorithm::EarlyMerge => Box::new(earlymergedb::EarlyMergeDB::new(backing, col)),
-		Algorithm::OverlayRecent => Box::new(overlayrecentdb::OverlayRecentDB::new(backing, col)),
+		Algorithm::OverlayRecent => match overlay_budget {
+			Some(budget) => Box::new(overlayrecentdb::OverlayRecentDB::with_memory_budget(backing, col, budget)),
+			None => Box::new(overlayrecentdb::OverlayRecentDB::new(backing, col)),
+		},
 		Algorithm::RefCounted => Box::new(refcounteddb::RefCountedDB::new(backing, col)),
 	}
 }
@@ -128,6 +167,112 @@ pub fn new(backing: Arc<::kvdb::KeyValueDB>, algorithm: Algorithm, col: Option<u
 const DB_PREFIX_LEN : usize = 12;
 const LATEST_ERA_KEY : [u8; DB_PREFIX_LEN] = [ b'l', b'a', b's', b't', 0, 0, 0, 0, 0, 0, 0, 0 ];
 
+// how far below a latest era without journal records to look for records.
+const RECOVERY_SCAN_ERAS: u64 = 1024;
+
+// key of the journal record with the given index within an era.
+fn journal_record_key(era: u64, index: usize) -> Bytes {
+	let mut r = RlpStream::new_list(3);
+	r.append(&era);
+	r.append(&index);
+	r.append(&&[0u8; 10][..]);
+	r.drain().to_vec()
+}
+
+/// Read the records of every era in the journal, newest era first, decoding each with `decode`.
+/// Returns the latest era along with the eras' records.
+///
+/// A damaged journal doesn't prevent startup. If the records of the latest era are missing, the
+/// journal is truncated to the newest era below it which has some. If any record fails to decode,
+/// the journal is truncated to the era below the newest damaged one. Discarded records are logged
+/// and removed from the database.
+fn read_journal<T, F>(db: &KeyValueDB, col: Option<u32>, decode: F) -> (Option<u64>, Vec<(u64, Vec<T>)>)
+	where F: Fn(&UntrustedRlp) -> Result<T, DecoderError>
+{
+	let latest_era = match db.get(col, &LATEST_ERA_KEY).expect("Low-level database error.") {
+		None => return (None, Vec::new()),
+		Some(val) => match UntrustedRlp::new(&val).as_val::<u64>() {
+			Ok(era) => era,
+			Err(e) => {
+				warn!(target: "journaldb", "Latest journal era is unreadable ({:?}); ignoring the journal.", e);
+				return (None, Vec::new());
+			}
+		},
+	};
+
+	let read_era = |era: u64| {
+		let mut records = Vec::new();
+		while let Some(data) = db.get(col, &journal_record_key(era, records.len())).expect("Low-level database error.") {
+			records.push(decode(&UntrustedRlp::new(&data)));
+		}
+		records
+	};
+
+	let mut batch = DBTransaction::new();
+	let mut truncated = false;
+	let mut era = latest_era;
+	let mut records = read_era(era);
+
+	if records.is_empty() {
+		let floor = era.saturating_sub(RECOVERY_SCAN_ERAS);
+		while records.is_empty() && era > floor {
+			era -= 1;
+			records = read_era(era);
+		}
+
+		// nothing left to journal; this is how a journal without history looks too.
+		if records.is_empty() {
+			return (Some(latest_era), Vec::new());
+		}
+
+		warn!(target: "journaldb", "Journal records of eras {}..{} are missing. Truncating the journal to era {}.", era + 1, latest_era, era);
+		batch.put(col, &LATEST_ERA_KEY, &encode(&era));
+		truncated = true;
+	}
+
+	let mut eras = Vec::new();
+	loop {
+		eras.push((era, records));
+		if era == 0 { break }
+		era -= 1;
+		records = read_era(era);
+		if records.is_empty() { break }
+	}
+
+	// eras are contiguous and newest first: drop everything down to the newest damaged one.
+	let mut latest = eras[0].0;
+	if let Some(damaged) = eras.iter().position(|&(_, ref records)| records.iter().any(Result::is_err)) {
+		let mut discarded = 0;
+		for &(era, ref records) in &eras[..damaged + 1] {
+			for index in 0..records.len() {
+				batch.delete(col, &journal_record_key(era, index));
+			}
+			discarded += records.len();
+		}
+
+		let damaged_era = eras[damaged].0;
+		latest = damaged_era.saturating_sub(1);
+		warn!(target: "journaldb", "Journal record of era {} is damaged. Discarded {} journal records of eras {}..{}, truncating the journal to era {}.",
+			damaged_era, discarded, damaged_era, eras[0].0, latest);
+
+		batch.put(col, &LATEST_ERA_KEY, &encode(&latest));
+		eras.drain(..damaged + 1);
+		truncated = true;
+	}
+
+	if truncated {
+		if let Err(e) = db.write(batch) {
+			warn!(target: "journaldb", "Failed to truncate the journal: {}", e);
+		}
+	}
+
+	let eras = eras.into_iter()
+		.map(|(era, records)| (era, records.into_iter().filter_map(Result::ok).collect()))
+		.collect();
+
+	(Some(latest), eras)
+}
+
 #[cfg(test)]
 mod tests {
 	use super::Algorithm;
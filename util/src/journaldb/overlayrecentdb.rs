@@ -16,13 +16,15 @@
 
 //! `JournalDB` over in-memory overlay
 
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
 use common::*;
 use rlp::*;
 use hashdb::*;
 use memorydb::*;
-use super::{DB_PREFIX_LEN, LATEST_ERA_KEY};
+use lru_cache::LruCache;
+use super::{DB_PREFIX_LEN, LATEST_ERA_KEY, read_journal, journal_record_key};
 use kvdb::{KeyValueDB, DBTransaction};
-use super::JournalDB;
+use super::{JournalDB, OverlayStats};
 
 /// Implementation of the `JournalDB` trait for a disk-backed database with a memory overlay
 /// and, possibly, latent-removal semantics.
@@ -54,6 +56,10 @@ use super::JournalDB;
 /// 6. For a canonical journal record that becomes ancient delete its removals from the disk only if
 /// the removed key is not present in the history overlay.
 /// 7. Delete ancient record from memory and disk.
+///
+/// With a memory budget, values of the least recently used nodes in the history overlay are
+/// dropped from memory whenever the resident ones exceed it. Every such node is also stored in
+/// the journal record which inserted it, so its value is read back from disk when needed.
 
 pub struct OverlayRecentDB {
 	transaction_overlay: MemoryDB,
@@ -62,7 +68,6 @@ pub struct OverlayRecentDB {
 	column: Option<u32>,
 }
 
-#[derive(PartialEq)]
 struct JournalOverlay {
 	backing_overlay: MemoryDB, // Nodes added in the history period
 	pending_overlay: H256FastMap<DBValue>, // Nodes being transfered from backing_overlay to backing db
@@ -70,6 +75,178 @@ struct JournalOverlay {
 	latest_era: Option<u64>,
 	earliest_era: Option<u64>,
 	cumulative_size: usize, // cumulative size of all entries.
+	spill: Option<Spill>, // Nodes spilled to disk, if the overlay has a memory budget
+}
+
+// Era and index of a journal record.
+type RecordPosition = (u64, usize);
+
+struct Spill {
+	budget: usize,
+	// resident nodes with the newest journal record inserting them, least recently used first.
+	resident: Mutex<LruCache<H256, RecordPosition>>,
+	// nodes whose values were dropped from memory.
+	nodes: H256FastMap<SpilledNode>,
+	size: usize,
+	spills: usize,
+	disk_reads: AtomicUsize,
+}
+
+struct SpilledNode {
+	record: RecordPosition,
+	size: usize,
+	rc: i32,
+}
+
+impl HeapSizeOf for SpilledNode {
+	fn heap_size_of_children(&self) -> usize { 0 }
+}
+
+impl Spill {
+	fn new(budget: usize) -> Spill {
+		Spill {
+			budget: budget,
+			resident: Mutex::new(LruCache::new(usize::max_value())),
+			nodes: H256FastMap::default(),
+			size: 0,
+			spills: 0,
+			disk_reads: AtomicUsize::new(0),
+		}
+	}
+}
+
+impl JournalOverlay {
+	fn get(&self, key: &H256, db: &KeyValueDB, col: Option<u32>) -> Option<DBValue> {
+		if let Some(value) = self.backing_overlay.get(key) {
+			if let Some(ref spill) = self.spill {
+				spill.resident.lock().get_mut(key);
+			}
+			return Some(value);
+		}
+
+		if let Some(value) = self.pending_overlay.get(key) {
+			return Some(value.clone());
+		}
+
+		let spill = match self.spill {
+			Some(ref spill) => spill,
+			None => return None,
+		};
+
+		spill.nodes.get(key).and_then(|node| {
+			spill.disk_reads.fetch_add(1, AtomicOrdering::Relaxed);
+			let keys: HashSet<H256> = ::std::iter::once(key.clone()).collect();
+			read_spilled(db, col, node.record, &keys).pop().map(|(_, value)| value)
+		})
+	}
+
+	// note that `key` was inserted into the history overlay by the journal record at `record`.
+	fn note_insertion(&mut self, key: H256, record: RecordPosition) {
+		if let Some(ref spill) = self.spill {
+			let mut resident = spill.resident.lock();
+			let newest = resident.remove(&key).map_or(record, |r| cmp::max(r, record));
+			resident.insert(key, newest);
+		}
+	}
+
+	// bring a spilled node back into memory with its value.
+	fn restore(&mut self, key: &H256, value: &DBValue) {
+		if let Some(ref mut spill) = self.spill {
+			if let Some(node) = spill.nodes.remove(key) {
+				spill.size -= node.size;
+				spill.resident.lock().insert(key.clone(), node.record);
+				for _ in 0..node.rc {
+					self.backing_overlay.emplace(key.clone(), value.clone());
+				}
+			}
+		}
+	}
+
+	// bring the spilled nodes among `keys` back into memory, reading every journal record once.
+	fn reload(&mut self, db: &KeyValueDB, col: Option<u32>, keys: &HashSet<H256>) {
+		let mut records: HashMap<RecordPosition, HashSet<H256>> = HashMap::new();
+		if let Some(ref spill) = self.spill {
+			for key in keys {
+				if let Some(node) = spill.nodes.get(key) {
+					records.entry(node.record).or_insert_with(HashSet::new).insert(key.clone());
+				}
+			}
+		}
+
+		for (record, keys) in records {
+			for (key, value) in read_spilled(db, col, record, &keys) {
+				self.restore(&key, &value);
+			}
+		}
+	}
+
+	// drop the values of the least recently used nodes until the resident ones fit the budget.
+	// nodes inserted by the `unwritten` journal record are kept, as it can't be read back yet.
+	fn enforce_budget(&mut self, unwritten: Option<RecordPosition>) {
+		let spill = match self.spill {
+			Some(ref mut spill) => spill,
+			None => return,
+		};
+
+		let mut spilled = 0;
+		while self.cumulative_size.saturating_sub(spill.size) > spill.budget {
+			let mut resident = spill.resident.lock();
+			let (key, record) = match resident.remove_lru() {
+				Some(lru) => lru,
+				None => break,
+			};
+
+			if Some(record) == unwritten {
+				resident.insert(key, record);
+				break;
+			}
+
+			if let Some((value, rc)) = self.backing_overlay.remove_entry(&key) {
+				spill.size += value.len();
+				spill.nodes.insert(key, SpilledNode { record: record, size: value.len(), rc: rc });
+				spilled += 1;
+			}
+		}
+
+		if spilled > 0 {
+			spill.spills += spilled;
+			debug!(target: "journaldb", "Spilled {} overlay nodes to disk, {} nodes ({} bytes) spilled in total",
+				spilled, spill.nodes.len(), spill.size);
+		}
+	}
+
+	fn stats(&self) -> OverlayStats {
+		let mut stats = OverlayStats::default();
+		stats.resident_bytes = self.cumulative_size;
+		if let Some(ref spill) = self.spill {
+			stats.resident_bytes -= spill.size;
+			stats.spilled_nodes = spill.nodes.len();
+			stats.spilled_bytes = spill.size;
+			stats.spills = spill.spills;
+			stats.disk_reads = spill.disk_reads.load(AtomicOrdering::Relaxed);
+		}
+		stats
+	}
+}
+
+// read the values of the history overlay nodes `keys` from the journal record at `record`.
+fn read_spilled(db: &KeyValueDB, col: Option<u32>, record: RecordPosition, keys: &HashSet<H256>) -> Vec<(H256, DBValue)> {
+	let data = db.get(col, &journal_record_key(record.0, record.1))
+		.expect("Low-level database error. Some issue with your hard disk?")
+		.expect("spilled nodes refer to the newest record inserting them, which lives as long as they do; qed");
+
+	let decode = || -> Result<Vec<(H256, DBValue)>, DecoderError> {
+		let mut values = Vec::new();
+		for r in UntrustedRlp::new(&data).at(1)?.iter() {
+			let key = to_short_key(&r.val_at::<H256>(0)?);
+			if keys.contains(&key) {
+				values.push((key, DBValue::from_slice(r.at(1)?.data()?)));
+			}
+		}
+		Ok(values)
+	};
+
+	decode().expect("journal records are only written by this database; qed")
 }
 
 #[derive(PartialEq)]
@@ -101,7 +278,18 @@ const PADDING : [u8; 10] = [ 0u8; 10 ];
 impl OverlayRecentDB {
 	/// Create a new instance.
 	pub fn new(backing: Arc<KeyValueDB>, col: Option<u32>) -> OverlayRecentDB {
-		let journal_overlay = Arc::new(RwLock::new(OverlayRecentDB::read_overlay(&*backing, col)));
+		Self::open(backing, col, None)
+	}
+
+	/// Create a new instance keeping the values of the history overlay within `budget` bytes.
+	pub fn with_memory_budget(backing: Arc<KeyValueDB>, col: Option<u32>, budget: usize) -> OverlayRecentDB {
+		Self::open(backing, col, Some(budget))
+	}
+
+	fn open(backing: Arc<KeyValueDB>, col: Option<u32>, budget: Option<usize>) -> OverlayRecentDB {
+		let mut overlay = OverlayRecentDB::read_overlay(&*backing, col, budget);
+		overlay.enforce_budget(None);
+		let journal_overlay = Arc::new(RwLock::new(overlay));
 		OverlayRecentDB {
 			transaction_overlay: MemoryDB::new(),
 			backing: backing,
@@ -119,7 +307,7 @@ impl OverlayRecentDB {
 
 	#[cfg(test)]
 	fn can_reconstruct_refs(&self) -> bool {
-		let reconstructed = Self::read_overlay(&*self.backing, self.column);
+		let reconstructed = Self::read_overlay(&*self.backing, self.column, None);
 		let journal_overlay = self.journal_overlay.read();
 		journal_overlay.backing_overlay == reconstructed.backing_overlay &&
 		journal_overlay.pending_overlay == reconstructed.pending_overlay &&
@@ -132,60 +320,64 @@ impl OverlayRecentDB {
 		self.backing.get(self.column, key).expect("Low-level database error. Some issue with your hard disk?")
 	}
 
-	fn read_overlay(db: &KeyValueDB, col: Option<u32>) -> JournalOverlay {
+	fn read_overlay(db: &KeyValueDB, col: Option<u32>, budget: Option<usize>) -> JournalOverlay {
 		let mut journal = HashMap::new();
 		let mut overlay = MemoryDB::new();
+		let mut positions: H256FastMap<RecordPosition> = H256FastMap::default();
 		let mut count = 0;
-		let mut latest_era = None;
 		let mut earliest_era = None;
 		let mut cumulative_size = 0;
-		if let Some(val) = db.get(col, &LATEST_ERA_KEY).expect("Low-level database error.") {
-			let mut era = decode::<u64>(&val);
-			latest_era = Some(era);
-			loop {
-				let mut index = 0usize;
-				while let Some(rlp_data) = db.get(col, {
-					let mut r = RlpStream::new_list(3);
-					r.append(&era);
-					r.append(&index);
-					r.append(&&PADDING[..]);
-					&r.drain()
-				}).expect("Low-level database error.") {
-					trace!("read_overlay: era={}, index={}", era, index);
-					let rlp = Rlp::new(&rlp_data);
-					let id: H256 = rlp.val_at(0);
-					let insertions = rlp.at(1);
-					let deletions: Vec<H256> = rlp.list_at(2);
-					let mut inserted_keys = Vec::new();
-					for r in insertions.iter() {
-						let k: H256 = r.val_at(0);
-						let v = r.at(1).data();
-
-						let short_key = to_short_key(&k);
-
-						if !overlay.contains(&short_key) {
-							cumulative_size += v.len();
-						}
-
-						overlay.emplace(short_key, DBValue::from_slice(v));
-						inserted_keys.push(k);
-						count += 1;
+		let (latest_era, eras) = read_journal(db, col, |rlp| {
+			let id: H256 = rlp.val_at(0)?;
+			let insertions = rlp.at(1)?.iter()
+				.map(|r| Ok((r.val_at::<H256>(0)?, DBValue::from_slice(r.at(1)?.data()?))))
+				.collect::<Result<Vec<_>, DecoderError>>()?;
+			let deletions: Vec<H256> = rlp.list_at(2)?;
+			Ok((id, insertions, deletions))
+		});
+
+		for (era, records) in eras {
+			for (index, (id, insertions, deletions)) in records.into_iter().enumerate() {
+				trace!("read_overlay: era={}, index={}", era, index);
+				let mut inserted_keys = Vec::new();
+				for (k, v) in insertions {
+					let short_key = to_short_key(&k);
+
+					if !overlay.contains(&short_key) {
+						cumulative_size += v.len();
 					}
-					journal.entry(era).or_insert_with(Vec::new).push(JournalEntry {
-						id: id,
-						insertions: inserted_keys,
-						deletions: deletions,
-					});
-					index += 1;
-					earliest_era = Some(era);
-				};
-				if index == 0 || era == 0 {
-					break;
+
+					// eras are read newest first.
+					positions.entry(short_key).or_insert((era, index));
+
+					overlay.emplace(short_key, v);
+					inserted_keys.push(k);
+					count += 1;
 				}
-				era -= 1;
+				journal.entry(era).or_insert_with(Vec::new).push(JournalEntry {
+					id: id,
+					insertions: inserted_keys,
+					deletions: deletions,
+				});
 			}
+			earliest_era = Some(era);
 		}
 		trace!("Recovered {} overlay entries, {} journal entries", count, journal.len());
+
+		let spill = budget.map(|budget| {
+			let spill = Spill::new(budget);
+			{
+				// oldest insertions are the least recently used.
+				let mut positions: Vec<_> = positions.into_iter().collect();
+				positions.sort_by_key(|&(_, record)| record);
+				let mut resident = spill.resident.lock();
+				for (key, record) in positions {
+					resident.insert(key, record);
+				}
+			}
+			spill
+		});
+
 		JournalOverlay {
 			backing_overlay: overlay,
 			pending_overlay: HashMap::default(),
@@ -193,10 +385,9 @@ impl OverlayRecentDB {
 			latest_era: latest_era,
 			earliest_era: earliest_era,
 			cumulative_size: cumulative_size,
+			spill: spill,
 		}
 	}
-
-
 }
 
 #[inline]
@@ -218,13 +409,19 @@ impl JournalDB for OverlayRecentDB {
 		mem += overlay.backing_overlay.mem_used();
 		mem += overlay.pending_overlay.heap_size_of_children();
 		mem += overlay.journal.heap_size_of_children();
+		if let Some(ref spill) = overlay.spill {
+			mem += spill.nodes.heap_size_of_children();
+		}
 
 		mem
 	}
 
 	fn journal_size(&self) -> usize {
 		self.journal_overlay.read().cumulative_size
+	}
 
+	fn overlay_stats(&self) -> OverlayStats {
+		self.journal_overlay.read().stats()
 	}
 
 	fn is_empty(&self) -> bool {
@@ -242,8 +439,7 @@ impl JournalDB for OverlayRecentDB {
 	fn state(&self, key: &H256) -> Option<Bytes> {
 		let journal_overlay = self.journal_overlay.read();
 		let key = to_short_key(key);
-		journal_overlay.backing_overlay.get(&key).map(|v| v.to_vec())
-		.or_else(|| journal_overlay.pending_overlay.get(&key).map(|d| d.clone().to_vec()))
+		journal_overlay.get(&key, &*self.backing, self.column).map(|v| v.to_vec())
 		.or_else(|| self.backing.get_by_prefix(self.column, &key[0..DB_PREFIX_LEN]).map(|b| b.to_vec()))
 	}
 
@@ -263,6 +459,7 @@ impl JournalDB for OverlayRecentDB {
 
 		// Increase counter for each inserted key no matter if the block is canonical or not.
 		let insertions = tx.drain().filter_map(|(k, (v, c))| if c > 0 { Some((k, v)) } else { None });
+		let index = journal_overlay.journal.get(&now).map_or(0, |j| j.len());
 
 		r.append(id);
 		r.begin_list(inserted_keys.len());
@@ -272,16 +469,17 @@ impl JournalDB for OverlayRecentDB {
 			r.append(&&*v);
 
 			let short_key = to_short_key(&k);
+			journal_overlay.restore(&short_key, &v);
 			if !journal_overlay.backing_overlay.contains(&short_key) {
 				journal_overlay.cumulative_size += v.len();
 			}
 
 			journal_overlay.backing_overlay.emplace(short_key, v);
+			journal_overlay.note_insertion(short_key, (now, index));
 		}
 		r.append_list(&removed_keys);
 
 		let mut k = RlpStream::new_list(3);
-		let index = journal_overlay.journal.get(&now).map_or(0, |j| j.len());
 		k.append(&now);
 		k.append(&index);
 		k.append(&&PADDING[..]);
@@ -298,6 +496,7 @@ impl JournalDB for OverlayRecentDB {
 		}
 
 		journal_overlay.journal.entry(now).or_insert_with(Vec::new).push(JournalEntry { id: id.clone(), insertions: inserted_keys, deletions: removed_keys });
+		journal_overlay.enforce_budget(Some((now, index)));
 		Ok(ops as u32)
 	}
 
@@ -307,6 +506,14 @@ impl JournalDB for OverlayRecentDB {
 		let mut journal_overlay = self.journal_overlay.write();
 		let journal_overlay = &mut *journal_overlay;
 
+		// nodes leaving the overlay and canon insertions need their values in memory.
+		if journal_overlay.spill.as_ref().map_or(false, |spill| !spill.nodes.is_empty()) {
+			let keys: HashSet<H256> = journal_overlay.journal.get(&end_era).map_or_else(HashSet::new, |records| records.iter()
+				.flat_map(|record| record.insertions.iter().map(to_short_key))
+				.collect());
+			journal_overlay.reload(&*self.backing, self.column, &keys);
+		}
+
 		let mut ops = 0;
 		// apply old commits' details
 		if let Some(ref mut records) = journal_overlay.journal.get_mut(&end_era) {
@@ -348,13 +555,19 @@ impl JournalDB for OverlayRecentDB {
 			}
 			// update the overlay
 			for k in overlay_deletions {
-				if let Some(val) = journal_overlay.backing_overlay.remove_and_purge(&to_short_key(&k)) {
+				let short_key = to_short_key(&k);
+				if let Some(val) = journal_overlay.backing_overlay.remove_and_purge(&short_key) {
 					journal_overlay.cumulative_size -= val.len();
+					if let Some(ref spill) = journal_overlay.spill {
+						spill.resident.lock().remove(&short_key);
+					}
 				}
 			}
 			// apply canon deletions
 			for k in canon_deletions {
-				if !journal_overlay.backing_overlay.contains(&to_short_key(&k)) {
+				let short_key = to_short_key(&k);
+				let spilled = journal_overlay.spill.as_ref().map_or(false, |spill| spill.nodes.contains_key(&short_key));
+				if !journal_overlay.backing_overlay.contains(&short_key) && !spilled {
 					batch.delete(self.column, &k);
 				}
 			}
@@ -421,12 +634,7 @@ impl HashDB for OverlayRecentDB {
 		if let Some((d, rc)) = k {
 			if rc > 0 { return Some(d) }
 		}
-		let v = {
-			let journal_overlay = self.journal_overlay.read();
-			let key = to_short_key(key);
-			journal_overlay.backing_overlay.get(&key)
-				.or_else(|| journal_overlay.pending_overlay.get(&key).cloned())
-		};
+		let v = self.journal_overlay.read().get(&to_short_key(key), &*self.backing, self.column);
 		v.or_else(|| self.payload(key))
 	}
 
@@ -437,6 +645,9 @@ impl HashDB for OverlayRecentDB {
 	fn insert(&mut self, value: &[u8]) -> H256 {
 		self.transaction_overlay.insert(value)
 	}
+	fn insert_batch(&mut self, values: &[&[u8]]) -> Vec<H256> {
+		self.transaction_overlay.insert_batch(values)
+	}
 	fn emplace(&mut self, key: H256, value: DBValue) {
 		self.transaction_overlay.emplace(key, value);
 	}
@@ -1030,4 +1241,125 @@ mod tests {
 		let jdb = new_db(temp.as_path().as_path());
 		assert_eq!(jdb.earliest_era(), None);
 	}
+
+	#[test]
+	fn recovers_from_damaged_journal() {
+		use journaldb::journal_record_key;
+
+		let backing = Arc::new(::kvdb::in_memory(0));
+		let (foo, bar) = {
+			let mut jdb = OverlayRecentDB::new(backing.clone(), None);
+			let foo = jdb.insert(b"foo");
+			jdb.commit_batch(0, &b"0".sha3(), None).unwrap();
+			jdb.insert(b"baz");
+			jdb.commit_batch(1, &b"1".sha3(), None).unwrap();
+			jdb.commit_batch(2, &b"2".sha3(), None).unwrap();
+			let bar = jdb.insert(b"bar");
+			jdb.commit_batch(3, &b"3".sha3(), None).unwrap();
+			(foo, bar)
+		};
+
+		// the records of the latest era went missing.
+		let mut batch = backing.transaction();
+		batch.delete(None, &journal_record_key(3, 0));
+		backing.write(batch).unwrap();
+
+		let jdb = OverlayRecentDB::new(backing.clone(), None);
+		assert_eq!(jdb.latest_era(), Some(2));
+		assert!(jdb.contains(&foo));
+		assert!(!jdb.contains(&bar));
+		assert!(jdb.can_reconstruct_refs());
+
+		// a record in the middle of the journal is garbage.
+		let mut batch = backing.transaction();
+		batch.put(None, &journal_record_key(1, 0), b"garbage");
+		backing.write(batch).unwrap();
+
+		let mut jdb = OverlayRecentDB::new(backing.clone(), None);
+		assert_eq!(jdb.latest_era(), Some(0));
+		assert_eq!(jdb.earliest_era(), Some(0));
+		assert!(jdb.contains(&foo));
+		assert!(backing.get(None, &journal_record_key(1, 0)).unwrap().is_none());
+		assert!(backing.get(None, &journal_record_key(2, 0)).unwrap().is_none());
+		assert!(jdb.can_reconstruct_refs());
+
+		// and it carries on from there.
+		jdb.commit_batch(1, &b"1".sha3(), Some((0, b"0".sha3()))).unwrap();
+		assert!(jdb.can_reconstruct_refs());
+		assert!(jdb.contains(&foo));
+	}
+
+	#[test]
+	fn spills_cold_nodes_over_budget() {
+		use journaldb::OverlayStats;
+
+		let mut jdb = OverlayRecentDB::with_memory_budget(Arc::new(::kvdb::in_memory(0)), None, 0);
+		let foo = jdb.insert(b"foo");
+		jdb.commit_batch(0, &b"0".sha3(), None).unwrap();
+		// nodes of the newest record stay until it's written.
+		assert_eq!(jdb.overlay_stats().spilled_nodes, 0);
+
+		let bar = jdb.insert(b"bar");
+		jdb.commit_batch(1, &b"1".sha3(), None).unwrap();
+		assert_eq!(jdb.overlay_stats(), OverlayStats {
+			resident_bytes: 3,
+			spilled_nodes: 1,
+			spilled_bytes: 3,
+			spills: 1,
+			disk_reads: 0,
+		});
+		assert!(jdb.contains(&foo));
+		assert_eq!(jdb.overlay_stats().disk_reads, 1);
+
+		jdb.remove(&foo);
+		jdb.commit_batch(2, &b"2".sha3(), Some((0, b"0".sha3()))).unwrap();
+		assert!(jdb.contains(&foo));
+		assert!(jdb.contains(&bar));
+		jdb.commit_batch(3, &b"3".sha3(), Some((1, b"1".sha3()))).unwrap();
+		jdb.commit_batch(4, &b"4".sha3(), Some((2, b"2".sha3()))).unwrap();
+		assert!(!jdb.contains(&foo));
+		assert!(jdb.contains(&bar));
+		assert_eq!(jdb.overlay_stats().spilled_nodes, 0);
+	}
+
+	#[test]
+	fn restores_spilled_node_inserted_again() {
+		let mut jdb = OverlayRecentDB::with_memory_budget(Arc::new(::kvdb::in_memory(0)), None, 0);
+		let foo = jdb.insert(b"foo");
+		jdb.commit_batch(1, &b"1a".sha3(), None).unwrap();
+		jdb.commit_batch(2, &b"2".sha3(), None).unwrap();
+		assert_eq!(jdb.overlay_stats().spilled_nodes, 1);
+
+		jdb.insert(b"foo");
+		jdb.commit_batch(1, &b"1b".sha3(), None).unwrap();
+		assert_eq!(jdb.overlay_stats().spilled_nodes, 0);
+
+		// spilled again with both references, then applied from the newest record.
+		jdb.commit_batch(3, &b"3".sha3(), Some((1, b"1b".sha3()))).unwrap();
+		assert_eq!(jdb.overlay_stats().spills, 2);
+		assert!(jdb.contains(&foo));
+		assert_eq!(jdb.overlay_stats().resident_bytes, 0);
+		assert_eq!(jdb.overlay_stats().spilled_nodes, 0);
+	}
+
+	#[test]
+	fn spills_on_reopen() {
+		let temp = ::devtools::RandomTempPath::new();
+		let backing = Arc::new(Database::open_default(temp.as_path().to_str().unwrap()).unwrap());
+		let keys: Vec<_> = {
+			let mut jdb = OverlayRecentDB::new(backing.clone(), None);
+			(0..4u64).map(|era| {
+				let key = jdb.insert(era.to_string().as_bytes());
+				jdb.commit_batch(era, &H256::from(era), None).unwrap();
+				key
+			}).collect()
+		};
+
+		let jdb = OverlayRecentDB::with_memory_budget(backing, None, 1);
+		let stats = jdb.overlay_stats();
+		assert_eq!(stats.spilled_nodes, 3);
+		assert_eq!(stats.resident_bytes, 1);
+		assert!(keys.iter().all(|key| jdb.contains(key)));
+		assert_eq!(jdb.overlay_stats().disk_reads, 3);
+	}
 }
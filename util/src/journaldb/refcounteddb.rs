@@ -21,7 +21,7 @@ use rlp::*;
 use hashdb::*;
 use overlaydb::OverlayDB;
 use memorydb::MemoryDB;
-use super::{DB_PREFIX_LEN, LATEST_ERA_KEY};
+use super::{DB_PREFIX_LEN, LATEST_ERA_KEY, CompactionReport};
 use super::traits::JournalDB;
 use kvdb::{KeyValueDB, DBTransaction};
 
@@ -52,14 +52,25 @@ pub struct RefCountedDB {
 	inserts: Vec<H256>,
 	removes: Vec<H256>,
 	column: Option<u32>,
+	compacted_era: Option<u64>,
 }
 
 const PADDING : [u8; 10] = [ 0u8; 10 ];
+const COMPACTED_ERA_KEY : [u8; DB_PREFIX_LEN] = [ b'c', b'o', b'm', b'p', 0, 0, 0, 0, 0, 0, 0, 0 ];
+
+fn journal_key(era: u64, index: usize) -> Bytes {
+	let mut r = RlpStream::new_list(3);
+	r.append(&era);
+	r.append(&index);
+	r.append(&&PADDING[..]);
+	r.drain().to_vec()
+}
 
 impl RefCountedDB {
 	/// Create a new instance given a `backing` database.
 	pub fn new(backing: Arc<KeyValueDB>, col: Option<u32>) -> RefCountedDB {
 		let latest_era = backing.get(col, &LATEST_ERA_KEY).expect("Low-level database error.").map(|val| decode::<u64>(&val));
+		let compacted_era = backing.get(col, &COMPACTED_ERA_KEY).expect("Low-level database error.").map(|val| decode::<u64>(&val));
 
 		RefCountedDB {
 			forward: OverlayDB::new(backing.clone(), col),
@@ -68,9 +79,38 @@ impl RefCountedDB {
 			removes: vec![],
 			latest_era: latest_era,
 			column: col,
+			compacted_era: compacted_era,
 		}
 	}
 
+	// Apply the journal records of an era, treating the one with `canon_id` as canonical,
+	// and remove them. Returns the number of operations and the size of the removed records.
+	fn apply_era(&mut self, batch: &mut DBTransaction, end_era: u64, canon_id: &H256) -> Result<(u32, usize), UtilError> {
+		let mut index = 0usize;
+		let mut removed_bytes = 0usize;
+		loop {
+			let key = journal_key(end_era, index);
+			let rlp_data = match self.backing.get(self.column, &key)? {
+				Some(data) => data,
+				None => break,
+			};
+
+			let rlp = Rlp::new(&rlp_data);
+			let our_id: H256 = rlp.val_at(0);
+			let to_remove: Vec<H256> = rlp.list_at(if *canon_id == our_id {2} else {1});
+			trace!(target: "rcdb", "delete journal for time #{}.{}=>{}, (canon was {}): deleting {:?}", end_era, index, our_id, canon_id, to_remove);
+			for i in &to_remove {
+				self.forward.remove(i);
+			}
+			batch.delete(self.column, &key);
+			removed_bytes += key.len() + rlp_data.len();
+			index += 1;
+		}
+
+		let r = self.forward.commit_to_batch(batch)?;
+		Ok((r, removed_bytes))
+	}
+
 	/// Create a new instance with an anonymous temporary database.
 	#[cfg(test)]
 	fn new_temp() -> RefCountedDB {
@@ -84,6 +124,7 @@ impl HashDB for RefCountedDB {
 	fn get(&self, key: &H256) -> Option<DBValue> { self.forward.get(key) }
 	fn contains(&self, key: &H256) -> bool { self.forward.contains(key) }
 	fn insert(&mut self, value: &[u8]) -> H256 { let r = self.forward.insert(value); self.inserts.push(r.clone()); r }
+	fn insert_batch(&mut self, values: &[&[u8]]) -> Vec<H256> { let r = self.forward.insert_batch(values); self.inserts.extend_from_slice(&r); r }
 	fn emplace(&mut self, key: H256, value: DBValue) { self.inserts.push(key.clone()); self.forward.emplace(key, value); }
 	fn remove(&mut self, key: &H256) { self.removes.push(key.clone()); }
 }
@@ -97,6 +138,7 @@ impl JournalDB for RefCountedDB {
 			inserts: self.inserts.clone(),
 			removes: self.removes.clone(),
 			column: self.column.clone(),
+			compacted_era: self.compacted_era,
 		})
 	}
 
@@ -156,32 +198,38 @@ impl JournalDB for RefCountedDB {
 	}
 
 	fn mark_canonical(&mut self, batch: &mut DBTransaction, end_era: u64, canon_id: &H256) -> Result<u32, UtilError> {
-		// apply old commits' details
-		let mut index = 0usize;
-		let mut last;
-		while let Some(rlp_data) = {
-			self.backing.get(self.column, {
-				let mut r = RlpStream::new_list(3);
-				r.append(&end_era);
-				r.append(&index);
-				r.append(&&PADDING[..]);
-				last = r.drain();
-				&last
-			})?
-		} {
-			let rlp = Rlp::new(&rlp_data);
-			let our_id: H256 = rlp.val_at(0);
-			let to_remove: Vec<H256> = rlp.list_at(if *canon_id == our_id {2} else {1});
-			trace!(target: "rcdb", "delete journal for time #{}.{}=>{}, (canon was {}): deleting {:?}", end_era, index, our_id, canon_id, to_remove);
-			for i in &to_remove {
-				self.forward.remove(i);
+		self.apply_era(batch, end_era, canon_id).map(|(ops, _)| ops)
+	}
+
+	fn compact(&mut self, batch: &mut DBTransaction, up_to: u64, limit: usize, canonical: &Fn(u64) -> Option<H256>) -> Result<CompactionReport, UtilError> {
+		let mut report = CompactionReport::default();
+		let mut era = self.compacted_era.map_or(0, |e| e + 1);
+		let mut examined = 0;
+
+		while era <= up_to && examined < limit {
+			if self.backing.get(self.column, &journal_key(era, 0))?.is_some() {
+				let canon_id = match canonical(era) {
+					Some(id) => id,
+					None => break,
+				};
+
+				let (_, removed_bytes) = self.apply_era(batch, era, &canon_id)?;
+				report.eras += 1;
+				report.reclaimed_bytes += removed_bytes as u64;
 			}
-			batch.delete(self.column, &last);
-			index += 1;
+
+			self.compacted_era = Some(era);
+			era += 1;
+			examined += 1;
 		}
 
-		let r = self.forward.commit_to_batch(batch)?;
-		Ok(r)
+		if let Some(compacted) = self.compacted_era {
+			batch.put(self.column, &COMPACTED_ERA_KEY, &encode(&compacted));
+		}
+
+		trace!(target: "rcdb", "compacted {} eras, reclaiming {} bytes; next era is {}", report.eras, report.reclaimed_bytes, era);
+		report.next_era = era;
+		Ok(report)
 	}
 
 	fn inject(&mut self, batch: &mut DBTransaction) -> Result<u32, UtilError> {
@@ -330,4 +378,41 @@ mod tests {
 
 		assert!(jdb.get(&key).is_none());
 	}
+
+	#[test]
+	fn compact_unpruned_eras() {
+		let mut jdb = RefCountedDB::new_temp();
+		let canonical = |era: u64| Some(era.to_string().sha3());
+
+		let foo = jdb.insert(b"foo");
+		jdb.commit_batch(0, &b"0".sha3(), None).unwrap();
+		jdb.remove(&foo);
+		let bar = jdb.insert(b"bar");
+		jdb.commit_batch(1, &b"1".sha3(), None).unwrap();
+		jdb.remove(&bar);
+		jdb.commit_batch(2, &b"2".sha3(), None).unwrap();
+
+		let mut batch = jdb.backing().transaction();
+		let report = jdb.compact(&mut batch, 1, 1, &canonical).unwrap();
+		jdb.backing().write(batch).unwrap();
+		assert_eq!(report.eras, 1);
+		assert_eq!(report.next_era, 1);
+		assert!(jdb.contains(&foo));
+
+		let mut batch = jdb.backing().transaction();
+		let report = jdb.compact(&mut batch, 1, 16, &canonical).unwrap();
+		jdb.backing().write(batch).unwrap();
+		assert_eq!(report.eras, 1);
+		assert_eq!(report.next_era, 2);
+		assert!(report.reclaimed_bytes > 0);
+		assert!(!jdb.contains(&foo));
+		assert!(jdb.contains(&bar));
+
+		// progress survives reopening.
+		let mut jdb = RefCountedDB::new(jdb.backing().clone(), None);
+		let mut batch = jdb.backing().transaction();
+		let report = jdb.compact(&mut batch, 1, 16, &canonical).unwrap();
+		assert_eq!(report.eras, 0);
+		assert_eq!(report.next_era, 2);
+	}
 }
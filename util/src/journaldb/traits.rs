@@ -19,6 +19,7 @@
 use common::*;
 use hashdb::*;
 use kvdb::{self, DBTransaction};
+use super::{CompactionReport, OverlayStats};
 
 /// A `HashDB` which can manage a short-term journal potentially containing many forks of mutually
 /// exclusive actions.
@@ -34,6 +35,9 @@ pub trait JournalDB: HashDB {
 	/// it must be fast enough to call several times per block imported.
 	fn journal_size(&self) -> usize { 0 }
 
+	/// Returns the size of the in-memory overlay, for databases which keep one.
+	fn overlay_stats(&self) -> OverlayStats { OverlayStats::default() }
+
 	/// Check if this database has any commits
 	fn is_empty(&self) -> bool;
 
@@ -59,6 +63,16 @@ pub trait JournalDB: HashDB {
 	/// from this point onwards.
 	fn inject(&mut self, batch: &mut DBTransaction) -> Result<u32, UtilError>;
 
+	/// Apply and remove journal records of eras up to and including `up_to` which were
+	/// never marked canonical, examining at most `limit` eras. `canonical` gives the hash
+	/// of the canonical block of an era.
+	///
+	/// Only databases keeping their whole journal on disk have anything to compact;
+	/// progress is persisted in `batch` so that compaction resumes across restarts.
+	fn compact(&mut self, _batch: &mut DBTransaction, up_to: u64, _limit: usize, _canonical: &Fn(u64) -> Option<H256>) -> Result<CompactionReport, UtilError> {
+		Ok(CompactionReport { next_era: up_to + 1, ..Default::default() })
+	}
+
 	/// State data query
 	fn state(&self, _id: &H256) -> Option<Bytes>;
 
@@ -19,6 +19,7 @@
 use std::io::ErrorKind;
 use std::marker::PhantomData;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
 
 use common::*;
 use elastic_array::*;
@@ -35,6 +36,7 @@ use std::fs::File;
 
 const DB_BACKGROUND_FLUSHES: i32 = 2;
 const DB_BACKGROUND_COMPACTIONS: i32 = 2;
+const BACKUP_BATCH_SIZE: usize = 4096;
 
 /// Write transaction. Batches a sequence of put/delete operations for efficiency.
 #[derive(Default, Clone, PartialEq)]
@@ -167,8 +169,77 @@ pub trait KeyValueDB: Sync + Send {
 	/// Iterate over flushed data for a given column.
 	fn iter<'a>(&'a self, col: Option<u32>) -> Box<Iterator<Item=(Box<[u8]>, Box<[u8]>)> + 'a>;
 
+	/// Iterate over flushed data for the keys of a column within `[start, end)`, in ascending order.
+	fn iter_range<'a>(&'a self, col: Option<u32>, start: &[u8], end: &[u8]) -> Box<Iterator<Item=(Box<[u8]>, Box<[u8]>)> + 'a>;
+
+	/// Iterate over flushed data for the keys of a column within `[start, end)`, in descending order.
+	fn iter_range_rev<'a>(&'a self, col: Option<u32>, start: &[u8], end: &[u8]) -> Box<Iterator<Item=(Box<[u8]>, Box<[u8]>)> + 'a>;
+
+	/// Iterate over flushed data for the keys of a column starting with the given prefix.
+	fn iter_from_prefix<'a>(&'a self, col: Option<u32>, prefix: &[u8]) -> Box<Iterator<Item=(Box<[u8]>, Box<[u8]>)> + 'a> {
+		match prefix_end(prefix) {
+			Some(end) => self.iter_range(col, prefix, &end),
+			// only keys with the prefix can sort after it.
+			None => {
+				let prefix = prefix.to_vec();
+				Box::new(self.iter(col).skip_while(move |&(ref k, _)| k[..] < prefix[..]))
+			}
+		}
+	}
+
+	/// Get statistics for every column, the default column first.
+	fn stats(&self) -> DatabaseStats;
+
 	/// Attempt to replace this database with a new one located at the given path.
 	fn restore(&self, new_db: &str) -> Result<(), UtilError>;
+
+	/// Copy a consistent image of the database into a new database at the given path,
+	/// without interrupting reads or writes.
+	fn backup(&self, path: &str) -> Result<(), String>;
+}
+
+// the smallest key greater than every key starting with `prefix`, if any.
+fn prefix_end(prefix: &[u8]) -> Option<Vec<u8>> {
+	let mut end = prefix.to_vec();
+	while let Some(last) = end.pop() {
+		if last < 0xff {
+			end.push(last + 1);
+			return Some(end);
+		}
+	}
+	None
+}
+
+/// Statistics for a single column of a key-value database.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ColumnStats {
+	/// The column these statistics are for.
+	pub column: Option<u32>,
+	/// Number of keys stored, if cheaply known.
+	pub keys: Option<u64>,
+	/// Total size of stored keys and values in bytes, if cheaply known.
+	pub size: Option<u64>,
+	/// Number of writes buffered in memory and not yet flushed to disk.
+	pub pending_writes: u64,
+	/// Number of reads since the database was opened.
+	pub reads: u64,
+	/// Number of reads answered from the in-memory write buffer.
+	pub buffer_hits: u64,
+	/// Number of keys written since the database was opened.
+	pub writes: u64,
+	/// Number of keys deleted since the database was opened.
+	pub deletes: u64,
+	/// Number of bytes written since the database was opened.
+	pub bytes_written: u64,
+}
+
+/// Statistics for a key-value database.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DatabaseStats {
+	/// Per-column statistics, the default column first.
+	pub columns: Vec<ColumnStats>,
+	/// Total size of the database files on disk, in bytes.
+	pub size_on_disk: u64,
 }
 
 /// A key-value database fulfilling the `KeyValueDB` trait, living in memory.
@@ -192,6 +263,18 @@ pub fn in_memory(num_cols: u32) -> InMemory {
 	}
 }
 
+impl InMemory {
+	fn collect_range(&self, col: Option<u32>, start: &[u8], end: &[u8]) -> Vec<(Box<[u8]>, Box<[u8]>)> {
+		match self.columns.read().get(&col) {
+			Some(map) => map.iter()
+				.filter(|&(k, _)| &k[..] >= start && &k[..] < end)
+				.map(|(k, v)| (k.clone().into_boxed_slice(), v.to_vec().into_boxed_slice()))
+				.collect(),
+			None => Vec::new(),
+		}
+	}
+}
+
 impl KeyValueDB for InMemory {
 	fn get(&self, col: Option<u32>, key: &[u8]) -> Result<Option<DBValue>, String> {
 		let columns = self.columns.read();
@@ -251,9 +334,53 @@ impl KeyValueDB for InMemory {
 		}
 	}
 
+	fn iter_range<'a>(&'a self, col: Option<u32>, start: &[u8], end: &[u8]) -> Box<Iterator<Item=(Box<[u8]>, Box<[u8]>)> + 'a> {
+		Box::new(self.collect_range(col, start, end).into_iter())
+	}
+
+	fn iter_range_rev<'a>(&'a self, col: Option<u32>, start: &[u8], end: &[u8]) -> Box<Iterator<Item=(Box<[u8]>, Box<[u8]>)> + 'a> {
+		Box::new(self.collect_range(col, start, end).into_iter().rev())
+	}
+
+	fn stats(&self) -> DatabaseStats {
+		let columns = self.columns.read();
+		let mut cols: Vec<_> = columns.keys().cloned().collect();
+		cols.sort();
+
+		DatabaseStats {
+			columns: cols.into_iter().map(|col| {
+				let map = &columns[&col];
+				ColumnStats {
+					column: col,
+					keys: Some(map.len() as u64),
+					size: Some(map.iter().map(|(k, v)| (k.len() + v.len()) as u64).sum()),
+					..Default::default()
+				}
+			}).collect(),
+			size_on_disk: 0,
+		}
+	}
+
 	fn restore(&self, _new_db: &str) -> Result<(), UtilError> {
 		Err(UtilError::SimpleString("Attempted to restore in-memory database".into()))
 	}
+
+	fn backup(&self, path: &str) -> Result<(), String> {
+		let columns = self.columns.read();
+		let config = DatabaseConfig::with_columns(match columns.len() as u32 - 1 {
+			0 => None,
+			n => Some(n),
+		});
+		let db = Database::open_new(&config, path)?;
+
+		let mut transaction = DBTransaction::new();
+		for (col, map) in columns.iter() {
+			for (key, value) in map {
+				transaction.put(*col, key, value);
+			}
+		}
+		db.write(transaction)
+	}
 }
 
 /// Compaction profile for the database settings
@@ -346,6 +473,42 @@ impl CompactionProfile {
 	}
 }
 
+/// Compression applied to the values of a database column.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CompressionType {
+	/// No compression. Best for high-entropy data such as trie nodes.
+	None,
+	/// Snappy compression: cheap, with modest savings.
+	Snappy,
+	/// LZ4 compression.
+	Lz4,
+	/// Zstandard compression: slower, with better ratio.
+	Zstd,
+	/// Zstandard compression with a dictionary of at most the given number of bytes,
+	/// trained per file. Pays off for many small, similar values like receipts.
+	ZstdDictionary(u32),
+}
+
+impl Default for CompressionType {
+	fn default() -> Self {
+		CompressionType::Snappy
+	}
+}
+
+impl CompressionType {
+	// rocksdb option string selecting this compression.
+	fn to_options(&self) -> String {
+		match *self {
+			CompressionType::None => "compression=kNoCompression".into(),
+			CompressionType::Snappy => "compression=kSnappyCompression".into(),
+			CompressionType::Lz4 => "compression=kLZ4Compression".into(),
+			CompressionType::Zstd => "compression=kZSTD".into(),
+			CompressionType::ZstdDictionary(max_bytes) =>
+				format!("compression=kZSTD;compression_opts=-14:32767:0:{}", max_bytes),
+		}
+	}
+}
+
 /// Database configuration
 #[derive(Clone)]
 pub struct DatabaseConfig {
@@ -359,6 +522,8 @@ pub struct DatabaseConfig {
 	pub columns: Option<u32>,
 	/// Should we keep WAL enabled?
 	pub wal: bool,
+	/// Compression for specific columns. Columns not given use the default.
+	pub compression: HashMap<Option<u32>, CompressionType>,
 }
 
 impl DatabaseConfig {
@@ -374,6 +539,11 @@ impl DatabaseConfig {
 	pub fn set_cache(&mut self, col: Option<u32>, size: usize) {
 		self.cache_sizes.insert(col, size);
 	}
+
+	/// Set the column compression.
+	pub fn set_compression(&mut self, col: Option<u32>, compression: CompressionType) {
+		self.compression.insert(col, compression);
+	}
 }
 
 impl Default for DatabaseConfig {
@@ -384,6 +554,7 @@ impl Default for DatabaseConfig {
 			compaction: CompactionProfile::default(),
 			columns: None,
 			wal: true,
+			compression: HashMap::new(),
 		}
 	}
 }
@@ -405,13 +576,58 @@ impl<'a> Iterator for DatabaseIterator<'a> {
 	}
 }
 
+/// Database iterator over a bounded key range (for flushed data only)
+pub struct DatabaseRangeIterator<'a> {
+	iter: DBIterator,
+	start: Vec<u8>,
+	end: Vec<u8>,
+	backward: bool,
+	done: bool,
+	_marker: PhantomData<&'a Database>,
+}
+
+impl<'a> Iterator for DatabaseRangeIterator<'a> {
+	type Item = (Box<[u8]>, Box<[u8]>);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		while !self.done {
+			let (key, value) = match self.iter.next() {
+				Some(item) => item,
+				None => break,
+			};
+
+			// a backward iterator starts at the first key not less than `end`.
+			if self.backward && key[..] >= self.end[..] {
+				continue;
+			}
+
+			if (self.backward && key[..] < self.start[..]) || (!self.backward && key[..] >= self.end[..]) {
+				break;
+			}
+
+			return Some((key, value));
+		}
+
+		self.done = true;
+		None
+	}
+}
+
+// open a raw iterator over a column.
+fn column_iterator(db: &DB, cfs: &[Column], col: Option<u32>, mode: IteratorMode, read_opts: &ReadOptions) -> DBIterator {
+	match col {
+		None => db.iterator_opt(mode, read_opts),
+		Some(c) => db.iterator_cf_opt(cfs[c as usize], mode, read_opts).expect("iterator params are valid; qed"),
+	}
+}
+
 struct DBAndColumns {
 	db: DB,
 	cfs: Vec<Column>,
 }
 
 // get column family configuration from database config.
-fn col_config(col: u32, config: &DatabaseConfig) -> Options {
+fn col_config(col: u32, config: &DatabaseConfig) -> Result<Options, String> {
 	// default cache size for columns not specified.
 	const DEFAULT_CACHE: usize = 2;
 
@@ -421,6 +637,8 @@ fn col_config(col: u32, config: &DatabaseConfig) -> Options {
 	opts.set_target_file_size_multiplier(config.compaction.file_size_multiplier);
 
 	let col_opt = config.columns.map(|_| col);
+	let compression = config.compression.get(&col_opt).cloned().unwrap_or_default();
+	opts.set_parsed_options(&compression.to_options())?;
 
 	{
 		let cache_size = config.cache_sizes.get(&col_opt).cloned().unwrap_or(DEFAULT_CACHE);
@@ -430,7 +648,7 @@ fn col_config(col: u32, config: &DatabaseConfig) -> Options {
 		opts.set_block_based_table_factory(&block_opts);
 	}
 
-	opts
+	Ok(opts)
 }
 
 /// Key-Value database.
@@ -447,6 +665,75 @@ pub struct Database {
 	// Prevents concurrent flushes.
 	// Value indicates if a flush is in progress.
 	flushing_lock: Mutex<bool>,
+	// Transactions waiting to be written by `write`, and the outcomes of written ones.
+	group_commit: Mutex<GroupCommit>,
+	// Signalled whenever a group of transactions has been written.
+	group_committed: Condvar,
+	// Access counters for each column, indexed like the overlay.
+	counters: Vec<ColumnCounters>,
+}
+
+#[derive(Default)]
+struct GroupCommit {
+	// transactions for the next group, with the tickets of their writers.
+	pending: Vec<(u64, DBTransaction)>,
+	// outcomes of written transactions not yet collected by their writers.
+	done: HashMap<u64, Result<(), String>>,
+	next_ticket: u64,
+	// whether a group is being written.
+	writing: bool,
+}
+
+// Reports the outcome of a group to its writers and lets the next group start,
+// also when writing the group panicked.
+struct GroupLeader<'a> {
+	db: &'a Database,
+	tickets: Vec<u64>,
+	result: Option<Result<(), String>>,
+}
+
+impl<'a> Drop for GroupLeader<'a> {
+	fn drop(&mut self) {
+		let result = self.result.take().unwrap_or_else(|| Err("Group commit panicked.".into()));
+		let mut group = self.db.group_commit.lock();
+		for ticket in self.tickets.drain(..) {
+			group.done.insert(ticket, result.clone());
+		}
+		group.writing = false;
+		self.db.group_committed.notify_all();
+	}
+}
+
+#[derive(Default)]
+struct ColumnCounters {
+	reads: AtomicUsize,
+	buffer_hits: AtomicUsize,
+	writes: AtomicUsize,
+	deletes: AtomicUsize,
+	bytes_written: AtomicUsize,
+}
+
+impl ColumnCounters {
+	fn note_write(&self, value: &[u8]) {
+		self.writes.fetch_add(1, AtomicOrdering::Relaxed);
+		self.bytes_written.fetch_add(value.len(), AtomicOrdering::Relaxed);
+	}
+
+	fn note_delete(&self) {
+		self.deletes.fetch_add(1, AtomicOrdering::Relaxed);
+	}
+}
+
+// Total size of the files in the given directory.
+fn dir_size(path: &Path) -> u64 {
+	fs::read_dir(path)
+		.map(|entries| entries
+			.filter_map(|entry| entry.ok())
+			.filter_map(|entry| entry.metadata().ok())
+			.filter(|metadata| metadata.is_file())
+			.map(|metadata| metadata.len())
+			.sum())
+		.unwrap_or(0)
 }
 
 impl Database {
@@ -475,12 +762,17 @@ impl Database {
 		opts.set_target_file_size_base(config.compaction.initial_file_size);
 		opts.set_target_file_size_multiplier(config.compaction.file_size_multiplier);
 
+		if config.columns.is_none() {
+			let compression = config.compression.get(&None).cloned().unwrap_or_default();
+			opts.set_parsed_options(&compression.to_options())?;
+		}
+
 		let mut cf_options = Vec::with_capacity(config.columns.unwrap_or(0) as usize);
 		let cfnames: Vec<_> = (0..config.columns.unwrap_or(0)).map(|c| format!("col{}", c)).collect();
 		let cfnames: Vec<&str> = cfnames.iter().map(|n| n as &str).collect();
 
 		for col in 0 .. config.columns.unwrap_or(0) {
-			cf_options.push(col_config(col, &config));
+			cf_options.push(col_config(col, &config)?);
 		}
 
 		let mut write_opts = WriteOptions::new();
@@ -537,6 +829,9 @@ impl Database {
 			overlay: RwLock::new((0..(num_cols + 1)).map(|_| HashMap::new()).collect()),
 			flushing: RwLock::new((0..(num_cols + 1)).map(|_| HashMap::new()).collect()),
 			flushing_lock: Mutex::new((false)),
+			group_commit: Mutex::new(GroupCommit::default()),
+			group_committed: Condvar::new(),
+			counters: (0..(num_cols + 1)).map(|_| ColumnCounters::default()).collect(),
 			path: path.to_owned(),
 			read_opts: read_opts,
 		})
@@ -560,14 +855,17 @@ impl Database {
 			match op {
 				DBOp::Insert { col, key, value } => {
 					let c = Self::to_overlay_column(col);
+					self.counters[c].note_write(&value);
 					overlay[c].insert(key, KeyState::Insert(value));
 				},
 				DBOp::InsertCompressed { col, key, value } => {
 					let c = Self::to_overlay_column(col);
+					self.counters[c].note_write(&value);
 					overlay[c].insert(key, KeyState::InsertCompressed(value));
 				},
 				DBOp::Delete { col, key } => {
 					let c = Self::to_overlay_column(col);
+					self.counters[c].note_delete();
 					overlay[c].insert(key, KeyState::Delete);
 				},
 			}
@@ -637,22 +935,63 @@ impl Database {
 	}
 
 	/// Commit transaction to database.
+	///
+	/// Concurrent writers are grouped: whichever finds no write in progress writes the
+	/// transactions of every waiting writer in a single batch, and the others wait for
+	/// its outcome.
 	pub fn write(&self, tr: DBTransaction) -> Result<(), String> {
+		let mut group = self.group_commit.lock();
+		let ticket = group.next_ticket;
+		group.next_ticket += 1;
+		group.pending.push((ticket, tr));
+
+		loop {
+			if let Some(result) = group.done.remove(&ticket) {
+				return result;
+			}
+
+			if group.writing {
+				self.group_committed.wait(&mut group);
+				continue;
+			}
+
+			group.writing = true;
+			let pending = mem::replace(&mut group.pending, Vec::new());
+			drop(group);
+
+			{
+				let mut leader = GroupLeader {
+					db: self,
+					tickets: pending.iter().map(|&(t, _)| t).collect(),
+					result: None,
+				};
+				trace!(target: "kvdb", "group commit of {} transactions", pending.len());
+				leader.result = Some(self.write_group(pending.iter().map(|&(_, ref tr)| tr)));
+			}
+
+			group = self.group_commit.lock();
+		}
+	}
+
+	// Write the given transactions to the database in a single batch.
+	fn write_group<'a, I>(&self, transactions: I) -> Result<(), String> where I: Iterator<Item=&'a DBTransaction> {
 		match *self.db.read() {
 			Some(DBAndColumns { ref db, ref cfs }) => {
 				let batch = WriteBatch::new();
-				let ops = tr.ops;
-				for op in ops {
-					match op {
-						DBOp::Insert { col, key, value } => {
-							col.map_or_else(|| batch.put(&key, &value), |c| batch.put_cf(cfs[c as usize], &key, &value))?
+				for op in transactions.flat_map(|tr| tr.ops.iter()) {
+					match *op {
+						DBOp::Insert { col, ref key, ref value } => {
+							self.counters[Self::to_overlay_column(col)].note_write(value);
+							col.map_or_else(|| batch.put(key, value), |c| batch.put_cf(cfs[c as usize], key, value))?
 						},
-						DBOp::InsertCompressed { col, key, value } => {
-							let compressed = UntrustedRlp::new(&value).compress(RlpType::Blocks);
-							col.map_or_else(|| batch.put(&key, &compressed), |c| batch.put_cf(cfs[c as usize], &key, &compressed))?
+						DBOp::InsertCompressed { col, ref key, ref value } => {
+							let compressed = UntrustedRlp::new(value).compress(RlpType::Blocks);
+							self.counters[Self::to_overlay_column(col)].note_write(&compressed);
+							col.map_or_else(|| batch.put(key, &compressed), |c| batch.put_cf(cfs[c as usize], key, &compressed))?
 						},
-						DBOp::Delete { col, key } => {
-							col.map_or_else(|| batch.delete(&key), |c| batch.delete_cf(cfs[c as usize], &key))?
+						DBOp::Delete { col, ref key } => {
+							self.counters[Self::to_overlay_column(col)].note_delete();
+							col.map_or_else(|| batch.delete(key), |c| batch.delete_cf(cfs[c as usize], key))?
 						},
 					}
 				}
@@ -666,14 +1005,23 @@ impl Database {
 	pub fn get(&self, col: Option<u32>, key: &[u8]) -> Result<Option<DBValue>, String> {
 		match *self.db.read() {
 			Some(DBAndColumns { ref db, ref cfs }) => {
+				let counters = &self.counters[Self::to_overlay_column(col)];
+				counters.reads.fetch_add(1, AtomicOrdering::Relaxed);
+
 				let overlay = &self.overlay.read()[Self::to_overlay_column(col)];
 				match overlay.get(key) {
-					Some(&KeyState::Insert(ref value)) | Some(&KeyState::InsertCompressed(ref value)) => Ok(Some(value.clone())),
+					Some(&KeyState::Insert(ref value)) | Some(&KeyState::InsertCompressed(ref value)) => {
+						counters.buffer_hits.fetch_add(1, AtomicOrdering::Relaxed);
+						Ok(Some(value.clone()))
+					},
 					Some(&KeyState::Delete) => Ok(None),
 					None => {
 						let flushing = &self.flushing.read()[Self::to_overlay_column(col)];
 						match flushing.get(key) {
-							Some(&KeyState::Insert(ref value)) | Some(&KeyState::InsertCompressed(ref value)) => Ok(Some(value.clone())),
+							Some(&KeyState::Insert(ref value)) | Some(&KeyState::InsertCompressed(ref value)) => {
+								counters.buffer_hits.fetch_add(1, AtomicOrdering::Relaxed);
+								Ok(Some(value.clone()))
+							},
 							Some(&KeyState::Delete) => Ok(None),
 							None => {
 								col.map_or_else(
@@ -726,6 +1074,117 @@ impl Database {
 		}
 	}
 
+	/// Get database iterator for flushed data, starting at the first key not less than `from`.
+	pub fn iter_from(&self, col: Option<u32>, from: &[u8]) -> DatabaseIterator {
+		match *self.db.read() {
+			Some(DBAndColumns { ref db, ref cfs }) => {
+				let iter = col.map_or_else(
+					|| db.iterator_opt(IteratorMode::From(from, Direction::Forward), &self.read_opts),
+					|c| db.iterator_cf_opt(cfs[c as usize], IteratorMode::From(from, Direction::Forward), &self.read_opts)
+						.expect("iterator params are valid; qed")
+				);
+
+				DatabaseIterator {
+					iter: iter,
+					_marker: PhantomData,
+				}
+			},
+			None => panic!("Not supported yet") //TODO: return an empty iterator or change return type
+		}
+	}
+
+	/// Get database iterator for flushed data with keys within `[start, end)`, in ascending order.
+	pub fn iter_range(&self, col: Option<u32>, start: &[u8], end: &[u8]) -> DatabaseRangeIterator {
+		self.range_iterator(col, start, end, false)
+	}
+
+	/// Get database iterator for flushed data with keys within `[start, end)`, in descending order.
+	pub fn iter_range_rev(&self, col: Option<u32>, start: &[u8], end: &[u8]) -> DatabaseRangeIterator {
+		self.range_iterator(col, start, end, true)
+	}
+
+	fn range_iterator(&self, col: Option<u32>, start: &[u8], end: &[u8], backward: bool) -> DatabaseRangeIterator {
+		match *self.db.read() {
+			Some(DBAndColumns { ref db, ref cfs }) => {
+				let iter = match backward {
+					false => column_iterator(db, cfs, col, IteratorMode::From(start, Direction::Forward), &self.read_opts),
+					true => {
+						// seeking past the last key leaves nothing to step back from.
+						let past_end = column_iterator(db, cfs, col, IteratorMode::From(end, Direction::Forward), &self.read_opts).next().is_none();
+						match past_end {
+							true => column_iterator(db, cfs, col, IteratorMode::End, &self.read_opts),
+							false => column_iterator(db, cfs, col, IteratorMode::From(end, Direction::Reverse), &self.read_opts),
+						}
+					}
+				};
+
+				DatabaseRangeIterator {
+					iter: iter,
+					start: start.to_vec(),
+					end: end.to_vec(),
+					backward: backward,
+					done: false,
+					_marker: PhantomData,
+				}
+			},
+			None => panic!("Not supported yet") //TODO: return an empty iterator or change return type
+		}
+	}
+
+	// Open a database at a path which must not hold one already.
+	fn open_new(config: &DatabaseConfig, path: &str) -> Result<Database, String> {
+		let exists = fs::read_dir(path).map(|mut entries| entries.next().is_some()).unwrap_or(false);
+		if exists {
+			return Err(format!("Cannot create database at {}: directory is not empty", path));
+		}
+		Database::open(config, path)
+	}
+
+	/// Copy a consistent image of the database into a new database at the given path,
+	/// which must not exist or be empty. Buffered changes are flushed first, and the
+	/// database stays available for reads and writes while the copy is made.
+	pub fn backup(&self, path: &str) -> Result<(), String> {
+		self.flush()?;
+
+		// every iterator reads from the state of the database at the time it was created,
+		// so no write may land between creating the first and the last one.
+		let iters = match *self.db.write() {
+			Some(DBAndColumns { ref db, ref cfs }) => {
+				let mut iters = vec![db.iterator_opt(IteratorMode::Start, &self.read_opts)];
+				for cf in cfs {
+					iters.push(db.iterator_cf_opt(*cf, IteratorMode::Start, &self.read_opts)
+						.expect("iterator params are valid; qed"));
+				}
+				iters
+			},
+			None => return Err("Database is closed".to_owned()),
+		};
+
+		let backup = Database::open_new(&self.config, path)?;
+		for (c, iter) in iters.into_iter().enumerate() {
+			let col = match c {
+				0 => None,
+				c => Some(c as u32 - 1),
+			};
+
+			let mut transaction = DBTransaction::new();
+			let mut pending = 0;
+			for (key, value) in iter {
+				// values are copied as stored, compressed ones included.
+				transaction.put(col, &key, &value);
+				pending += 1;
+				if pending == BACKUP_BATCH_SIZE {
+					backup.write(mem::replace(&mut transaction, DBTransaction::new()))?;
+					pending = 0;
+				}
+			}
+			backup.write(transaction)?;
+		}
+
+		trace!(target: "kvdb", "Backed up database {} to {}", self.path, path);
+		Ok(())
+	}
+
 	/// Close the database
 	fn close(&self) {
 		*self.db.write() = None;
@@ -774,6 +1233,30 @@ impl Database {
 		Ok(())
 	}
 
+	/// Get access statistics for every column, the default column first, along with
+	/// the size of the database on disk. Key counts and sizes aren't tracked per column.
+	pub fn stats(&self) -> DatabaseStats {
+		let overlay = self.overlay.read();
+		let flushing = self.flushing.read();
+
+		let columns = self.counters.iter().enumerate().map(|(c, counters)| ColumnStats {
+			column: if c == 0 { None } else { Some(c as u32 - 1) },
+			keys: None,
+			size: None,
+			pending_writes: (overlay[c].len() + flushing[c].len()) as u64,
+			reads: counters.reads.load(AtomicOrdering::Relaxed) as u64,
+			buffer_hits: counters.buffer_hits.load(AtomicOrdering::Relaxed) as u64,
+			writes: counters.writes.load(AtomicOrdering::Relaxed) as u64,
+			deletes: counters.deletes.load(AtomicOrdering::Relaxed) as u64,
+			bytes_written: counters.bytes_written.load(AtomicOrdering::Relaxed) as u64,
+		}).collect();
+
+		DatabaseStats {
+			columns: columns,
+			size_on_disk: dir_size(Path::new(&self.path)),
+		}
+	}
+
 	/// The number of non-default column families.
 	pub fn num_columns(&self) -> u32 {
 		self.db.read().as_ref()
@@ -803,7 +1286,7 @@ impl Database {
 			Some(DBAndColumns { ref mut db, ref mut cfs }) => {
 				let col = cfs.len() as u32;
 				let name = format!("col{}", col);
-				cfs.push(db.create_cf(&name, &col_config(col, &self.config))?);
+				cfs.push(db.create_cf(&name, &col_config(col, &self.config)?)?);
 				Ok(())
 			},
 			None => Ok(()),
@@ -839,9 +1322,25 @@ impl KeyValueDB for Database {
 		Box::new(unboxed)
 	}
 
+	fn iter_range<'a>(&'a self, col: Option<u32>, start: &[u8], end: &[u8]) -> Box<Iterator<Item=(Box<[u8]>, Box<[u8]>)> + 'a> {
+		Box::new(Database::iter_range(self, col, start, end))
+	}
+
+	fn iter_range_rev<'a>(&'a self, col: Option<u32>, start: &[u8], end: &[u8]) -> Box<Iterator<Item=(Box<[u8]>, Box<[u8]>)> + 'a> {
+		Box::new(Database::iter_range_rev(self, col, start, end))
+	}
+
+	fn stats(&self) -> DatabaseStats {
+		Database::stats(self)
+	}
+
 	fn restore(&self, new_db: &str) -> Result<(), UtilError> {
 		Database::restore(self, new_db)
 	}
+
+	fn backup(&self, path: &str) -> Result<(), String> {
+		Database::backup(self, path)
+	}
 }
 
 impl Drop for Database {
@@ -861,6 +1360,10 @@ mod tests {
 	fn test_db(config: &DatabaseConfig) {
 		let path = RandomTempPath::create_dir();
 		let db = Database::open(config, path.as_path().to_str().unwrap()).unwrap();
+		test_kvdb(&db);
+	}
+
+	fn test_kvdb(db: &KeyValueDB) {
 		let key1 = H256::from_str("02c69be41d0b7e40352fc85be1cd65eb03d40ef8427a0ca4596b1ead9a00e9fc").unwrap();
 		let key2 = H256::from_str("03c69be41d0b7e40352fc85be1cd65eb03d40ef8427a0ca4596b1ead9a00e9fc").unwrap();
 		let key3 = H256::from_str("01c69be41d0b7e40352fc85be1cd65eb03d40ef8427a0ca4596b1ead9a00e9fc").unwrap();
@@ -918,6 +1421,57 @@ mod tests {
 		test_db(&DatabaseConfig::default());
 	}
 
+	#[test]
+	fn per_column_compression() {
+		let path = RandomTempPath::create_dir();
+		let mut config = DatabaseConfig::with_columns(Some(2));
+		config.set_compression(Some(0), CompressionType::None);
+		config.set_compression(Some(1), CompressionType::Snappy);
+		{
+			let db = Database::open(&config, path.as_path().to_str().unwrap()).unwrap();
+			let mut batch = db.transaction();
+			batch.put(Some(0), b"node", b"high entropy");
+			batch.put(Some(1), b"receipt", &[0u8; 1024]);
+			db.write(batch).unwrap();
+		}
+
+		let db = Database::open(&config, path.as_path().to_str().unwrap()).unwrap();
+		assert_eq!(&*db.get(Some(0), b"node").unwrap().unwrap(), b"high entropy");
+		assert_eq!(&*db.get(Some(1), b"receipt").unwrap().unwrap(), &[0u8; 1024][..]);
+	}
+
+	#[test]
+	fn in_memory_kvdb() {
+		test_kvdb(&in_memory(0));
+	}
+
+	fn test_ranges(db: &KeyValueDB) {
+		let mut batch = db.transaction();
+		for key in &[&b"a"[..], b"b1", b"b2", b"b\xff", b"c", b"\xff\xff"] {
+			batch.put(None, key, key);
+		}
+		db.write(batch).unwrap();
+
+		fn keys<'a>(iter: Box<Iterator<Item=(Box<[u8]>, Box<[u8]>)> + 'a>) -> Vec<Vec<u8>> {
+			iter.map(|(k, _)| k.into_vec()).collect()
+		}
+
+		assert_eq!(keys(db.iter_range(None, b"b", b"c")), vec![b"b1".to_vec(), b"b2".to_vec(), b"b\xff".to_vec()]);
+		assert_eq!(keys(db.iter_range_rev(None, b"a", b"b2")), vec![b"b1".to_vec(), b"a".to_vec()]);
+		assert_eq!(keys(db.iter_range_rev(None, b"c", b"\xff\xff\xff")), vec![b"\xff\xff".to_vec(), b"c".to_vec()]);
+		assert_eq!(keys(db.iter_range(None, b"d", b"e")), Vec::<Vec<u8>>::new());
+		assert_eq!(keys(db.iter_from_prefix(None, b"b")), vec![b"b1".to_vec(), b"b2".to_vec(), b"b\xff".to_vec()]);
+		assert_eq!(keys(db.iter_from_prefix(None, b"\xff")), vec![b"\xff\xff".to_vec()]);
+	}
+
+	#[test]
+	fn range_iteration() {
+		let path = RandomTempPath::create_dir();
+		let db = Database::open_default(path.as_path().to_str().unwrap()).unwrap();
+		test_ranges(&db);
+		test_ranges(&in_memory(0));
+	}
+
 	#[test]
 	#[cfg(target_os = "linux")]
 	fn df_to_rotational() {
@@ -928,6 +1482,98 @@ mod tests {
 		assert_eq!(rotational_from_df_output(example_df), expected_output);
 	}
 
+	#[test]
+	fn concurrent_writes_are_grouped() {
+		use std::thread;
+
+		let path = RandomTempPath::create_dir();
+		let db = Arc::new(Database::open_default(path.as_path().to_str().unwrap()).unwrap());
+
+		let writers: Vec<_> = (0..8u8).map(|i| {
+			let db = db.clone();
+			thread::spawn(move || {
+				for j in 0..32u8 {
+					let mut batch = db.transaction();
+					batch.put(None, &[i, j], &[j, i]);
+					db.write(batch).unwrap();
+				}
+			})
+		}).collect();
+
+		for writer in writers {
+			writer.join().unwrap();
+		}
+
+		for i in 0..8u8 {
+			for j in 0..32u8 {
+				assert_eq!(&*db.get(None, &[i, j]).unwrap().unwrap(), &[j, i]);
+			}
+		}
+		assert!(db.group_commit.lock().done.is_empty());
+	}
+
+	#[test]
+	fn stats() {
+		let path = RandomTempPath::create_dir();
+		let config = DatabaseConfig::with_columns(Some(1));
+		let db = Database::open(&config, path.as_path().to_str().unwrap()).unwrap();
+
+		let mut batch = db.transaction();
+		batch.put(Some(0), b"key", b"value");
+		batch.delete(Some(0), b"other");
+		db.write_buffered(batch);
+		assert!(db.get(Some(0), b"key").unwrap().is_some());
+
+		let stats = db.stats();
+		assert_eq!(stats.columns.len(), 2);
+		assert_eq!(stats.columns[1].column, Some(0));
+		assert_eq!(stats.columns[1].writes, 1);
+		assert_eq!(stats.columns[1].deletes, 1);
+		assert_eq!(stats.columns[1].bytes_written, 5);
+		assert_eq!(stats.columns[1].pending_writes, 2);
+		assert_eq!(stats.columns[1].reads, 1);
+		assert_eq!(stats.columns[1].buffer_hits, 1);
+
+		db.flush().unwrap();
+		assert_eq!(db.stats().columns[1].pending_writes, 0);
+		assert!(db.stats().size_on_disk > 0);
+
+		let memory = in_memory(1);
+		let mut batch = memory.transaction();
+		batch.put(Some(0), b"key", b"value");
+		memory.write(batch).unwrap();
+		assert_eq!(memory.stats().columns[1].keys, Some(1));
+		assert_eq!(memory.stats().columns[1].size, Some(8));
+	}
+
+	#[test]
+	fn backup() {
+		let path = RandomTempPath::create_dir();
+		let backup_path = RandomTempPath::create_dir();
+		let config = DatabaseConfig::with_columns(Some(1));
+		let db = Database::open(&config, path.as_path().to_str().unwrap()).unwrap();
+
+		let mut batch = db.transaction();
+		batch.put(None, b"flushed", b"1");
+		batch.put_compressed(Some(0), b"compressed", vec![0xc2, 0x80, 0x80]);
+		db.write(batch).unwrap();
+		let mut batch = db.transaction();
+		batch.put(Some(0), b"buffered", b"2");
+		db.write_buffered(batch);
+
+		db.backup(backup_path.as_path().to_str().unwrap()).unwrap();
+		// the source database stays usable.
+		assert_eq!(&*db.get(Some(0), b"buffered").unwrap().unwrap(), b"2");
+
+		let copy = Database::open(&config, backup_path.as_path().to_str().unwrap()).unwrap();
+		assert_eq!(&*copy.get(None, b"flushed").unwrap().unwrap(), b"1");
+		assert_eq!(&*copy.get(Some(0), b"buffered").unwrap().unwrap(), b"2");
+		assert_eq!(copy.get(Some(0), b"compressed").unwrap(), db.get(Some(0), b"compressed").unwrap());
+
+		// refuses to overwrite an existing database.
+		assert!(db.backup(backup_path.as_path().to_str().unwrap()).is_err());
+	}
+
 	#[test]
 	fn add_columns() {
 		let config = DatabaseConfig::default();
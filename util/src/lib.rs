@@ -105,6 +105,7 @@ extern crate tiny_keccak;
 extern crate rlp;
 extern crate regex;
 extern crate lru_cache;
+extern crate crossbeam;
 extern crate heapsize;
 extern crate itertools;
 extern crate ethcore_logger;
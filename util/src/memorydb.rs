@@ -153,6 +153,12 @@ impl MemoryDB {
 		}
 	}
 
+	/// Remove an element regardless of its reference count.
+	/// Returns the old value and reference count.
+	pub fn remove_entry(&mut self, key: &H256) -> Option<(DBValue, i32)> {
+		self.data.remove(key)
+	}
+
 	/// Consolidate all the entries of `other` into `self`.
 	pub fn consolidate(&mut self, mut other: Self) {
 		for (key, (value, rc)) in other.drain() {
@@ -220,6 +226,11 @@ impl HashDB for MemoryDB {
 		key
 	}
 
+	fn insert_batch(&mut self, values: &[&[u8]]) -> Vec<H256> {
+		self.data.reserve(values.len());
+		values.iter().map(|value| self.insert(value)).collect()
+	}
+
 	fn emplace(&mut self, key: H256, value: DBValue) {
 		if &*value == &NULL_RLP {
 			return;
@@ -295,3 +306,16 @@ fn consolidate() {
 	assert_eq!(overlay.get(&remove_key).unwrap(), &(DBValue::from_slice(b"doggo"), 0));
 	assert_eq!(overlay.get(&insert_key).unwrap(), &(DBValue::from_slice(b"arf"), 2));
 }
+
+#[test]
+fn insert_batch() {
+	let mut batched = MemoryDB::new();
+	let mut single = MemoryDB::new();
+	let values: [&[u8]; 4] = [b"doggo", &NULL_RLP, b"arf", b"doggo"];
+
+	let keys = batched.insert_batch(&values);
+	assert_eq!(keys, values.iter().map(|value| single.insert(value)).collect::<Vec<_>>());
+	assert_eq!(keys[1], SHA3_NULL_RLP);
+	assert!(batched == single);
+	assert_eq!(batched.raw(&keys[0]).unwrap().1, 2);
+}
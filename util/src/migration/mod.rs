@@ -23,7 +23,9 @@ use std::fs;
 use std::fmt;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+use rlp::{RlpStream, UntrustedRlp};
 use ::kvdb::{CompactionProfile, Database, DatabaseConfig, DBTransaction};
 
 /// Migration config.
@@ -70,6 +72,11 @@ impl Batch {
 		Ok(())
 	}
 
+	/// Whether the batch has no pending items.
+	pub fn is_empty(&self) -> bool {
+		self.inner.is_empty()
+	}
+
 	/// Commit all the items in the batch to the given database.
 	pub fn commit(&mut self, dest: &mut Database) -> Result<(), Error> {
 		if self.inner.is_empty() { return Ok(()) }
@@ -134,6 +141,15 @@ pub trait Migration: 'static {
 	fn version(&self) -> u32;
 	/// Migrate a source to a destination.
 	fn migrate(&mut self, source: Arc<Database>, config: &Config, destination: &mut Database, col: Option<u32>) -> Result<(), Error>;
+	/// Whether `migrate_from` honours the cursor, so that the migration of a column
+	/// can be resumed part-way through.
+	fn resumable(&self) -> bool { false }
+	/// Migrate a source to a destination, starting after the position of the cursor and
+	/// advancing it as batches are committed. By default the cursor is ignored and the
+	/// whole column is migrated.
+	fn migrate_from(&mut self, source: Arc<Database>, config: &Config, destination: &mut Database, col: Option<u32>, _cursor: &mut Cursor) -> Result<(), Error> {
+		self.migrate(source, config, destination, col)
+	}
 }
 
 /// A simple migration over key-value pairs.
@@ -165,6 +181,40 @@ impl<T: SimpleMigration> Migration for T {
 
 		batch.commit(dest)
 	}
+
+	fn resumable(&self) -> bool { true }
+
+	fn migrate_from(&mut self, source: Arc<Database>, config: &Config, dest: &mut Database, col: Option<u32>, cursor: &mut Cursor) -> Result<(), Error> {
+		let mut batch = Batch::new(config, col);
+		let start = cursor.position().map(|key| key.to_vec());
+		let iter = match start {
+			Some(ref key) => source.iter_from(col, key),
+			None => source.iter(col),
+		};
+
+		let mut last = None;
+		for (key, value) in iter {
+			// the key under the cursor has already been migrated.
+			if start.as_ref().map_or(false, |start| &key[..] == &start[..]) { continue }
+			if cursor.is_exhausted() { break }
+
+			cursor.tick();
+			if let Some((new_key, new_value)) = self.simple_migrate(key.to_vec(), value.to_vec()) {
+				batch.insert(new_key, new_value, dest)?;
+				// an insert leaves the batch empty only when it was committed.
+				if batch.is_empty() {
+					cursor.commit(&key)?;
+				}
+			}
+			last = Some(key);
+		}
+
+		batch.commit(dest)?;
+		match last {
+			Some(key) => cursor.commit(&key),
+			None => Ok(()),
+		}
+	}
 }
 
 /// An even simpler migration which just changes the number of columns.
@@ -220,6 +270,208 @@ impl TempIndex {
 	}
 }
 
+// given the path to the old database, get the path of the migration cursor.
+fn cursor_path(db_root: &Path) -> PathBuf {
+	let mut buf = db_root.to_owned();
+	buf.push("migration_cursor");
+	buf
+}
+
+// all the columns of a database with the given number of columns.
+fn column_list(columns: Option<u32>) -> Vec<Option<u32>> {
+	match columns {
+		None => vec![None],
+		Some(v) => (0..v).map(Some).collect(),
+	}
+}
+
+fn open_database(config: &DatabaseConfig, path: &Path) -> Result<Database, Error> {
+	let path_str = path.to_str().ok_or(Error::MigrationImpossible)?;
+	Database::open(config, path_str).map_err(Error::Custom)
+}
+
+/// Position of an in-progress migration.
+///
+/// The cursor is persisted next to the databases whenever a batch is committed, so that a
+/// migration interrupted by a crash or shutdown carries on from the last committed key
+/// rather than starting over. It also reports the progress of the current column.
+pub struct Cursor {
+	// where the cursor is persisted. `None` for dry runs.
+	path: Option<PathBuf>,
+	version: u32,
+	source: PathBuf,
+	destination: PathBuf,
+	// number of columns which have been completely migrated.
+	completed: u32,
+	position: Option<Vec<u8>>,
+	processed: u64,
+	total: u64,
+	percent: u64,
+	limit: Option<u64>,
+}
+
+impl Cursor {
+	fn new(path: Option<PathBuf>, version: u32, source: PathBuf, destination: PathBuf) -> Self {
+		Cursor {
+			path: path,
+			version: version,
+			source: source,
+			destination: destination,
+			completed: 0,
+			position: None,
+			processed: 0,
+			total: 0,
+			percent: 0,
+			limit: None,
+		}
+	}
+
+	// load a persisted cursor, if any.
+	fn load(path: &Path) -> Result<Option<Self>, Error> {
+		let bytes = match fs::File::open(path) {
+			Ok(mut file) => {
+				let mut bytes = Vec::new();
+				::std::io::Read::read_to_end(&mut file, &mut bytes)?;
+				bytes
+			}
+			Err(ref e) if e.kind() == ::std::io::ErrorKind::NotFound => return Ok(None),
+			Err(e) => return Err(e.into()),
+		};
+
+		let decode = |bytes: &[u8]| -> Result<Cursor, ::rlp::DecoderError> {
+			let rlp = UntrustedRlp::new(bytes);
+			let source: String = rlp.val_at(1)?;
+			let destination: String = rlp.val_at(2)?;
+			let total: u64 = rlp.val_at(6)?;
+			let processed: u64 = rlp.val_at(5)?;
+
+			Ok(Cursor {
+				path: Some(path.to_owned()),
+				version: rlp.val_at(0)?,
+				source: source.into(),
+				destination: destination.into(),
+				completed: rlp.val_at(3)?,
+				position: rlp.val_at(4)?,
+				processed: processed,
+				total: total,
+				percent: processed.saturating_mul(100) / ::std::cmp::max(total, 1),
+				limit: None,
+			})
+		};
+
+		decode(&bytes).map(Some).map_err(|e| Error::Custom(format!("Corrupted migration cursor: {:?}", e)))
+	}
+
+	// persist the cursor, atomically replacing the previous one.
+	fn save(&self) -> Result<(), Error> {
+		let path = match self.path {
+			Some(ref path) => path,
+			None => return Ok(()),
+		};
+
+		let mut stream = RlpStream::new_list(7);
+		stream.append(&self.version);
+		stream.append(&self.source.to_string_lossy().into_owned());
+		stream.append(&self.destination.to_string_lossy().into_owned());
+		stream.append(&self.completed);
+		stream.append(&self.position);
+		stream.append(&self.processed);
+		stream.append(&self.total);
+
+		let temp = path.with_extension("tmp");
+		{
+			let mut file = fs::File::create(&temp)?;
+			::std::io::Write::write_all(&mut file, &stream.out())?;
+			file.sync_all()?;
+		}
+		fs::rename(&temp, path).map_err(Into::into)
+	}
+
+	// remove the persisted cursor.
+	fn clear(&self) {
+		if let Some(ref path) = self.path {
+			let _ = fs::remove_file(path);
+		}
+	}
+
+	// begin migrating the next column, which holds `total` keys.
+	fn start_column(&mut self, total: u64) -> Result<(), Error> {
+		self.position = None;
+		self.processed = 0;
+		self.total = total;
+		self.percent = 0;
+		self.save()
+	}
+
+	// note that the current column has been completely migrated.
+	fn complete_column(&mut self) -> Result<(), Error> {
+		self.completed += 1;
+		self.position = None;
+		self.processed = 0;
+		self.total = 0;
+		self.save()
+	}
+
+	/// The last source key of the current column which has been migrated and committed.
+	pub fn position(&self) -> Option<&[u8]> {
+		self.position.as_ref().map(|key| &key[..])
+	}
+
+	/// Note that one more source key has been processed, reporting the progress of the column
+	/// each time another percent has been migrated.
+	pub fn tick(&mut self) {
+		self.processed += 1;
+		let percent = ::std::cmp::min(self.processed.saturating_mul(100) / ::std::cmp::max(self.total, 1), 100);
+		if percent != self.percent && self.path.is_some() {
+			self.percent = percent;
+			info!(target: "migration", "Migrating to version {}: column {} {}%", self.version, self.completed, percent);
+		}
+	}
+
+	/// Note that everything up to and including `key` has been committed to the destination.
+	pub fn commit(&mut self, key: &[u8]) -> Result<(), Error> {
+		self.position = Some(key.to_vec());
+		self.save()
+	}
+
+	/// Whether the migration should stop for now. Only dry runs limit the amount of keys migrated.
+	pub fn is_exhausted(&self) -> bool {
+		self.limit.map_or(false, |limit| self.processed >= limit)
+	}
+}
+
+/// Amount of keys migrated by a dry run to measure the throughput of a migration.
+const DRY_RUN_SAMPLE: u64 = 100_000;
+
+/// Estimated cost of migrating a database, as produced by a dry run.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Estimate {
+	/// Number of migrations which would be executed.
+	pub migrations: usize,
+	/// Number of migrations which rewrite the whole database.
+	pub rewrites: usize,
+	/// Number of key-value pairs which would be rewritten.
+	pub keys: u64,
+	/// Size of the rewritten key-value pairs, in bytes.
+	pub bytes: u64,
+	/// Additional disk space needed while migrating, in bytes.
+	pub space: u64,
+	/// Estimated duration of the migration.
+	pub duration: Duration,
+}
+
+impl Estimate {
+	/// Add the cost of another migration to this one.
+	pub fn accrue(&mut self, other: Estimate) {
+		self.migrations += other.migrations;
+		self.rewrites += other.rewrites;
+		self.keys += other.keys;
+		self.bytes += other.bytes;
+		self.space = ::std::cmp::max(self.space, other.space);
+		self.duration = self.duration + other.duration;
+	}
+}
+
 /// Manages database migration.
 pub struct Manager {
 	config: Config,
@@ -250,6 +502,9 @@ impl Manager {
 
 	/// Performs migration in order, starting with a source path, migrating between two temporary databases,
 	/// and producing a path where the final migration lives.
+	///
+	/// If a previous execution was interrupted, the migration it was performing is resumed
+	/// from its persisted cursor.
 	pub fn execute(&mut self, old_path: &Path, version: u32) -> Result<PathBuf, Error> {
 		let config = self.config.clone();
 		let migrations = self.migrations_from(version);
@@ -265,42 +520,83 @@ impl Manager {
 			compaction: config.compaction_profile,
 			columns: columns,
 			wal: true,
+			compression: Default::default(),
 		};
 
 		let db_root = database_path(old_path);
+		let cursor_path = cursor_path(&db_root);
 		let mut temp_idx = TempIndex::One;
 		let mut temp_path = old_path.to_path_buf();
 
+		// pick up an interrupted migration, provided it is still part of the plan.
+		let mut resume = match Cursor::load(&cursor_path)? {
+			Some(ref cursor) if !migrations.iter().any(|m| m.alters_existing() && m.version() == cursor.version) => {
+				warn!(target: "migration", "Discarding stale migration cursor for version {}", cursor.version);
+				cursor.clear();
+				None
+			}
+			cursor => cursor,
+		};
+
 		// start with the old db.
-		let old_path_str = old_path.to_str().ok_or(Error::MigrationImpossible)?;
-		let mut cur_db = Arc::new(Database::open(&db_config, old_path_str).map_err(Error::Custom)?);
+		let mut cur_path = old_path.to_path_buf();
+		let mut cur_db = Arc::new(open_database(&db_config, &cur_path)?);
 
-		for migration in migrations {
-			trace!(target: "migration", "starting migration to version {}", migration.version());
+		let total = migrations.len();
+		for (index, migration) in migrations.into_iter().enumerate() {
 			// Change number of columns in new db
 			let current_columns = db_config.columns;
 			db_config.columns = migration.columns();
 
+			// skip the migrations which completed before the interruption.
+			if resume.as_ref().map_or(false, |cursor| migration.version() < cursor.version) {
+				trace!(target: "migration", "skipping completed migration to version {}", migration.version());
+				continue;
+			}
+
+			info!(target: "migration", "Migration {}/{}: to version {}", index + 1, total, migration.version());
+
 			// slow migrations: alter existing data.
 			if migration.alters_existing() {
-				temp_path = temp_idx.path(&db_root);
+				let mut cursor = match resume.take() {
+					Some(cursor) => {
+						info!(target: "migration", "Resuming interrupted migration to version {}", cursor.version);
+						if cursor.source != cur_path {
+							let mut source_config = db_config.clone();
+							source_config.columns = current_columns;
+							cur_db = Arc::new(open_database(&source_config, &cursor.source)?);
+							cur_path = cursor.source.clone();
+						}
+						if cursor.destination != temp_idx.path(&db_root) {
+							temp_idx.swap();
+						}
+						cursor
+					}
+					None => {
+						// whatever is left over from an interrupted run can't be trusted.
+						let destination = temp_idx.path(&db_root);
+						let _ = fs::remove_dir_all(&destination);
+						Cursor::new(Some(cursor_path.clone()), migration.version(), cur_path.clone(), destination)
+					}
+				};
 
 				// open the target temporary database.
-				let temp_path_str = temp_path.to_str().ok_or(Error::MigrationImpossible)?;
-				let mut new_db = Database::open(&db_config, temp_path_str).map_err(Error::Custom)?;
-
-				match current_columns {
-					// migrate only default column
-					None => migration.migrate(cur_db.clone(), &config, &mut new_db, None)?,
-					Some(v) => {
-						// Migrate all columns in previous DB
-						for col in 0..v {
-							migration.migrate(cur_db.clone(), &config, &mut new_db, Some(col))?
-						}
+				temp_path = cursor.destination.clone();
+				let mut new_db = open_database(&db_config, &temp_path)?;
+
+				for col in column_list(current_columns).into_iter().skip(cursor.completed as usize) {
+					if cursor.position.is_none() && cursor.processed == 0 {
+						let keys = cur_db.iter(col).count() as u64;
+						cursor.start_column(keys)?;
 					}
+					info!(target: "migration", "Migrating to version {}: column {} ({} keys)", cursor.version, cursor.completed, cursor.total);
+					migration.migrate_from(cur_db.clone(), &config, &mut new_db, col, &mut cursor)?;
+					cursor.complete_column()?;
 				}
+
 				// next iteration, we will migrate from this db into the other temp.
 				cur_db = Arc::new(new_db);
+				cur_path = temp_path.clone();
 				temp_idx.swap();
 
 				// remove the other temporary migration database.
@@ -318,9 +614,91 @@ impl Manager {
 				}
 			}
 		}
+
+		let _ = fs::remove_file(&cursor_path);
 		Ok(temp_path)
 	}
 
+	/// Estimates the cost of migrating the database at `old_path` without altering it.
+	///
+	/// The first migration is run over a sample of each column into a scratch database
+	/// to measure its throughput, provided it can be stopped part-way through. Otherwise
+	/// the duration is extrapolated from the time it takes to read the database.
+	pub fn estimate(&mut self, old_path: &Path, version: u32) -> Result<Estimate, Error> {
+		let config = self.config.clone();
+		let migrations = self.migrations_from(version);
+		if migrations.is_empty() { return Err(Error::MigrationImpossible) };
+
+		let columns = migrations.get(0).and_then(|m| m.pre_columns());
+		let mut db_config = DatabaseConfig {
+			max_open_files: 64,
+			cache_sizes: Default::default(),
+			compaction: config.compaction_profile,
+			columns: columns,
+			wal: true,
+			compression: Default::default(),
+		};
+
+		let db = Arc::new(open_database(&db_config, old_path)?);
+		let mut estimate = Estimate {
+			migrations: migrations.len(),
+			rewrites: migrations.iter().filter(|m| m.alters_existing()).count(),
+			..Default::default()
+		};
+
+		let started = Instant::now();
+		let (mut keys, mut bytes) = (0u64, 0u64);
+		for col in column_list(columns) {
+			for (key, value) in db.iter(col) {
+				keys += 1;
+				bytes += (key.len() + value.len()) as u64;
+			}
+		}
+		let read_time = started.elapsed();
+
+		estimate.keys = keys * estimate.rewrites as u64;
+		estimate.bytes = bytes * estimate.rewrites as u64;
+		// the source and at most two temporary databases exist at once.
+		estimate.space = bytes * ::std::cmp::min(estimate.rewrites, 2) as u64;
+
+		let first = migrations.into_iter().next().expect("checked non-empty above; qed");
+		let sampled = match first.alters_existing() && first.resumable() {
+			true => {
+				let scratch = database_path(old_path).join("temp_migration_estimate");
+				let _ = fs::remove_dir_all(&scratch);
+				db_config.columns = first.columns();
+
+				let started = Instant::now();
+				let mut processed = 0;
+				{
+					let mut scratch_db = open_database(&db_config, &scratch)?;
+					for col in column_list(columns) {
+						let mut cursor = Cursor::new(None, first.version(), old_path.to_owned(), scratch.clone());
+						cursor.total = DRY_RUN_SAMPLE;
+						cursor.limit = Some(DRY_RUN_SAMPLE);
+						first.migrate_from(db.clone(), &config, &mut scratch_db, col, &mut cursor)?;
+						processed += cursor.processed;
+					}
+				}
+				let _ = fs::remove_dir_all(&scratch);
+				Some((started.elapsed(), processed))
+			}
+			false => None,
+		};
+
+		let nanos = |d: Duration| d.as_secs() as f64 * 1e9 + d.subsec_nanos() as f64;
+		let per_key = match sampled {
+			Some((elapsed, processed)) if processed > 0 => nanos(elapsed) / processed as f64,
+			// rewriting a key is assumed to cost about as much as reading it again.
+			_ if keys > 0 => 2.0 * nanos(read_time) / keys as f64,
+			_ => 0.0,
+		};
+
+		let total = per_key * estimate.keys as f64;
+		estimate.duration = Duration::new((total / 1e9) as u64, (total % 1e9) as u32);
+		Ok(estimate)
+	}
+
 	/// Returns true if migration is needed.
 	pub fn is_needed(&self, version: u32) -> bool {
 		match self.migrations.last() {
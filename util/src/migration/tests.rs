@@ -19,7 +19,7 @@
 //! are performed in temp sub-directories.
 
 use common::*;
-use migration::{Batch, Config, Error, SimpleMigration, Migration, Manager};
+use migration::{Batch, Config, Error, SimpleMigration, Migration, Manager, Cursor, TempIndex, cursor_path};
 use kvdb::Database;
 
 use devtools::RandomTempPath;
@@ -249,3 +249,62 @@ fn change_columns() {
 	let db = Database::open(&config, new_path.to_str().unwrap()).unwrap();
 	assert_eq!(db.num_columns(), 4);
 }
+
+// records the keys it is asked to migrate.
+struct Recording(Arc<Mutex<Vec<Vec<u8>>>>);
+
+impl SimpleMigration for Recording {
+	fn columns(&self) -> Option<u32> { None }
+
+	fn version(&self) -> u32 { 1 }
+
+	fn simple_migrate(&mut self, key: Vec<u8>, value: Vec<u8>) -> Option<(Vec<u8>, Vec<u8>)> {
+		self.0.lock().push(key.clone());
+		Some((key, value))
+	}
+}
+
+#[test]
+fn resumes_interrupted_migration() {
+	let dir = RandomTempPath::create_dir();
+	let db_path = db_path(dir.as_path());
+	make_db(&db_path, map![vec![1] => vec![1], vec![2] => vec![2], vec![3] => vec![3], vec![4] => vec![4]]);
+
+	// pretend a previous run got as far as the second key before being interrupted.
+	let destination = TempIndex::One.path(dir.as_path());
+	make_db(&destination, map![vec![1] => vec![1], vec![2] => vec![2]]);
+	let mut cursor = Cursor::new(Some(cursor_path(dir.as_path())), 1, db_path.clone(), destination.clone());
+	cursor.start_column(4).unwrap();
+	cursor.processed = 2;
+	cursor.commit(&[2]).unwrap();
+
+	let seen = Arc::new(Mutex::new(Vec::new()));
+	let mut manager = Manager::new(Config::default());
+	manager.add_migration(Recording(seen.clone())).unwrap();
+	let end_path = manager.execute(&db_path, 0).unwrap();
+
+	assert_eq!(end_path, destination);
+	assert_eq!(&*seen.lock(), &[vec![3], vec![4]]);
+	assert!(!cursor_path(dir.as_path()).exists());
+	verify_migration(&end_path, map![vec![1] => vec![1], vec![2] => vec![2], vec![3] => vec![3], vec![4] => vec![4]]);
+}
+
+#[test]
+fn estimate_leaves_database_untouched() {
+	let dir = RandomTempPath::create_dir();
+	let db_path = db_path(dir.as_path());
+	make_db(&db_path, map![vec![] => vec![], vec![1] => vec![1]]);
+
+	let mut manager = Manager::new(Config::default());
+	manager.add_migration(Migration0).unwrap();
+	manager.add_migration(Migration1).unwrap();
+	let estimate = manager.estimate(&db_path, 0).unwrap();
+
+	assert_eq!(estimate.migrations, 2);
+	assert_eq!(estimate.rewrites, 2);
+	assert_eq!(estimate.keys, 4);
+	assert_eq!(estimate.bytes, 4);
+	assert_eq!(estimate.space, 4);
+	assert!(!dir.as_path().join("temp_migration_estimate").exists());
+	verify_migration(&db_path, map![vec![] => vec![], vec![1] => vec![1]]);
+}
@@ -74,3 +74,24 @@ pub fn version_data() -> Bytes {
 pub fn raw_package_info() -> (&'static str, &'static str, &'static str) {
 	(THIS_TRACK, env!["CARGO_PKG_VERSION"], sha())
 }
+
+/// Get the number of bytes available to unprivileged users on the filesystem holding `path`.
+#[cfg(unix)]
+pub fn available_disk_space(path: &::std::path::Path) -> ::std::io::Result<u64> {
+	use std::ffi::CString;
+	use std::io;
+	use std::os::unix::ffi::OsStrExt;
+
+	let path = CString::new(path.as_os_str().as_bytes()).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+	let mut stat: ::libc::statvfs = unsafe { ::std::mem::zeroed() };
+	match unsafe { ::libc::statvfs(path.as_ptr(), &mut stat) } {
+		0 => Ok(stat.f_bavail as u64 * stat.f_frsize as u64),
+		_ => Err(io::Error::last_os_error()),
+	}
+}
+
+/// Get the number of bytes available to unprivileged users on the filesystem holding `path`.
+#[cfg(not(unix))]
+pub fn available_disk_space(_path: &::std::path::Path) -> ::std::io::Result<u64> {
+	Err(::std::io::Error::new(::std::io::ErrorKind::Other, "Disk space reporting is not supported on this platform."))
+}
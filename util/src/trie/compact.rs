@@ -0,0 +1,234 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Compact storage encoding of trie nodes.
+//!
+//! Nodes are always hashed over their canonical encoding, in which a child of 32 bytes or
+//! more is referenced by its hash. The compact encoding stores small children of that kind
+//! inside their parent's record instead, saving a database entry and a lookup for each.
+//! Readers take any embedded list for an inline node, so records of both encodings can be
+//! mixed in one database. Proofs are made of canonical nodes only: see `canonical`.
+
+use std::collections::HashMap;
+use std::ops::Range;
+use hash::H256;
+use hashdb::DBValue;
+use rlp::{RlpStream, UntrustedRlp};
+use sha3::Hashable;
+use Bytes;
+
+/// Largest stored record of a node which is embedded into its parent in the compact encoding.
+pub const MAX_EMBEDDED_SIZE: usize = 64;
+
+/// How trie nodes are written to the database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeEncoding {
+	/// Every node of 32 bytes or more is stored under its hash.
+	Canonical,
+	/// Nodes whose records are at most `MAX_EMBEDDED_SIZE` bytes are embedded in their parent.
+	Compact,
+}
+
+impl Default for NodeEncoding {
+	fn default() -> Self {
+		NodeEncoding::Canonical
+	}
+}
+
+// indices of the items of a node which reference children.
+fn child_items(node: &UntrustedRlp) -> Option<Range<usize>> {
+	match node.item_count() {
+		Ok(17) => Some(0..16),
+		// a leaf or an extension, told apart by the flag of the encoded partial key.
+		Ok(2) => match node.at(0).and_then(|key| key.data()) {
+			Ok(key) if key.first().map_or(false, |flags| flags & 0x20 == 0) => Some(1..2),
+			_ => None,
+		},
+		_ => None,
+	}
+}
+
+/// Encode a node for storage, embedding the children referenced by hash in its `canonical`
+/// encoding whose records are found in `embedded`. Returns `None` if no child was embedded.
+pub fn compact(canonical: &[u8], embedded: &HashMap<H256, DBValue>) -> Option<Bytes> {
+	let node = UntrustedRlp::new(canonical);
+	let children = match child_items(&node) {
+		Some(children) if !embedded.is_empty() => children,
+		_ => return None,
+	};
+
+	let count = node.item_count().unwrap_or(0);
+	let mut stream = RlpStream::new_list(count);
+	let mut changed = false;
+	for (i, item) in node.iter().enumerate() {
+		let record = match item.as_val::<H256>() {
+			Ok(ref hash) if children.start <= i && i < children.end && item.size() == 32 => embedded.get(hash),
+			_ => None,
+		};
+		match record {
+			Some(record) => {
+				stream.append_raw(record, 1);
+				changed = true;
+			}
+			None => { stream.append_raw(item.as_raw(), 1); },
+		}
+	}
+
+	match changed {
+		true => Some(stream.out()),
+		false => None,
+	}
+}
+
+/// Convert a stored node into its canonical encoding. The canonical encodings of the children
+/// embedded in it which are referenced by hash are pushed to `embedded`, with their depth
+/// below the node. Returns `None` if the record is already canonical or is not a node.
+pub fn canonical(stored: &[u8], embedded: &mut Vec<(Bytes, u32)>) -> Option<Bytes> {
+	canonical_at(UntrustedRlp::new(stored), 1, embedded)
+}
+
+fn canonical_at(node: UntrustedRlp, depth: u32, embedded: &mut Vec<(Bytes, u32)>) -> Option<Bytes> {
+	let children = match child_items(&node) {
+		Some(children) => children,
+		None => return None,
+	};
+
+	let count = node.item_count().unwrap_or(0);
+	let mut stream = RlpStream::new_list(count);
+	let mut changed = false;
+	for (i, item) in node.iter().enumerate() {
+		// canonical inline children are shorter than 32 bytes.
+		if children.start <= i && i < children.end && item.is_list() && item.as_raw().len() >= 32 {
+			let child = canonical_at(item.clone(), depth + 1, embedded).unwrap_or_else(|| item.as_raw().to_vec());
+			stream.append(&child.sha3());
+			embedded.push((child, depth));
+			changed = true;
+		} else {
+			stream.append_raw(item.as_raw(), 1);
+		}
+	}
+
+	match changed {
+		true => Some(stream.out()),
+		false => None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use hash::H256;
+	use hashdb::{HashDB, DBValue};
+	use memorydb::MemoryDB;
+	use sha3::Hashable;
+	use super::super::{TrieDB, TrieDBMut, Trie, TrieMut, Recorder};
+	use super::{NodeEncoding, canonical};
+
+	fn build(db: &mut MemoryDB, encoding: NodeEncoding, pairs: &[(Vec<u8>, Vec<u8>)]) -> H256 {
+		let mut root = H256::new();
+		{
+			let mut t = TrieDBMut::new(db, &mut root);
+			t.set_node_encoding(encoding);
+			for &(ref key, ref value) in pairs {
+				t.insert(key, value).unwrap();
+			}
+		}
+		root
+	}
+
+	fn pairs() -> Vec<(Vec<u8>, Vec<u8>)> {
+		(0u32..512).map(|i| {
+			let key = H256::from(i as u64).sha3();
+			(key[..].to_vec(), vec![i as u8; 1 + (i % 40) as usize])
+		}).collect()
+	}
+
+	fn proof(db: &HashDB, root: &H256, key: &[u8]) -> Vec<(H256, Vec<u8>)> {
+		let mut recorder = Recorder::new();
+		TrieDB::new(db, root).unwrap().get_with(key, &mut recorder).unwrap();
+		recorder.drain().into_iter().map(|r| (r.hash, r.data)).collect()
+	}
+
+	#[test]
+	fn compact_trie_has_canonical_root_and_fewer_records() {
+		let pairs = pairs();
+		let mut canonical_db = MemoryDB::new();
+		let canonical_root = build(&mut canonical_db, NodeEncoding::Canonical, &pairs);
+		let mut compact_db = MemoryDB::new();
+		let compact_root = build(&mut compact_db, NodeEncoding::Compact, &pairs);
+
+		assert_eq!(compact_root, canonical_root);
+		assert!(compact_db.keys().len() < canonical_db.keys().len());
+
+		let t = TrieDB::new(&compact_db, &compact_root).unwrap();
+		for &(ref key, ref value) in &pairs {
+			assert_eq!(t.get(key).unwrap(), Some(DBValue::from_slice(value)));
+		}
+		assert_eq!(t.iter().unwrap().count(), pairs.len());
+	}
+
+	#[test]
+	fn proofs_from_compact_trie_are_canonical() {
+		let pairs = pairs();
+		let mut canonical_db = MemoryDB::new();
+		let root = build(&mut canonical_db, NodeEncoding::Canonical, &pairs);
+		let mut compact_db = MemoryDB::new();
+		build(&mut compact_db, NodeEncoding::Compact, &pairs);
+
+		for (_, &(ref key, ref value)) in pairs.iter().enumerate().filter(|&(i, _)| i % 37 == 0) {
+			let expected = proof(&canonical_db, &root, key);
+			let actual = proof(&compact_db, &root, key);
+			// embedded siblings of the nodes on the path are proven along with them.
+			assert!(expected.iter().all(|node| actual.contains(node)));
+			assert!(actual.iter().all(|&(ref hash, ref data)| &data.sha3() == hash));
+
+			let mut proof_db = MemoryDB::new();
+			for &(_, ref data) in &actual {
+				proof_db.insert(data);
+			}
+			let t = TrieDB::new(&proof_db, &root).unwrap();
+			assert_eq!(t.get(key).unwrap(), Some(DBValue::from_slice(value)));
+		}
+	}
+
+	#[test]
+	fn compact_trie_can_be_changed_canonically() {
+		let pairs = pairs();
+		let (first, rest) = pairs.split_at(256);
+		let mut canonical_db = MemoryDB::new();
+		let expected = build(&mut canonical_db, NodeEncoding::Canonical, &pairs);
+
+		let mut db = MemoryDB::new();
+		let mut root = build(&mut db, NodeEncoding::Compact, first);
+		{
+			let mut t = TrieDBMut::from_existing(&mut db, &mut root).unwrap();
+			for &(ref key, ref value) in rest {
+				t.insert(key, value).unwrap();
+			}
+		}
+		assert_eq!(root, expected);
+
+		let t = TrieDB::new(&db, &root).unwrap();
+		assert_eq!(t.iter().unwrap().count(), pairs.len());
+	}
+
+	#[test]
+	fn leaves_other_records_alone() {
+		let mut embedded = Vec::new();
+		assert_eq!(canonical(b"not a node", &mut embedded), None);
+		assert_eq!(canonical(&[0x80], &mut embedded), None);
+		assert!(embedded.is_empty());
+	}
+}
@@ -0,0 +1,171 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Trie traversal which can be stopped and resumed later, possibly in another process.
+
+use hash::H256;
+use rlp::{Encodable, Decodable, RlpStream, UntrustedRlp, DecoderError};
+use Bytes;
+use super::{Trie, TrieDB, TrieDBIterator, TrieItem, TrieIterator, TrieError, Result};
+
+/// Where a `TrieCursor` stands: the trie it walks and the last key it yielded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CursorPosition {
+	/// Root of the trie being walked.
+	pub root: H256,
+	/// Key of the last item yielded, or `None` if the walk hasn't yielded anything yet.
+	pub key: Option<Bytes>,
+}
+
+impl CursorPosition {
+	/// Position before the first item of the trie with `root`.
+	pub fn start(root: H256) -> Self {
+		CursorPosition {
+			root: root,
+			key: None,
+		}
+	}
+}
+
+impl Encodable for CursorPosition {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		match self.key {
+			Some(ref key) => s.begin_list(2).append(&self.root).append(key),
+			None => s.begin_list(1).append(&self.root),
+		};
+	}
+}
+
+impl Decodable for CursorPosition {
+	fn decode(rlp: &UntrustedRlp) -> ::std::result::Result<Self, DecoderError> {
+		Ok(CursorPosition {
+			root: rlp.val_at(0)?,
+			key: match rlp.item_count()? {
+				1 => None,
+				_ => Some(rlp.val_at(1)?),
+			},
+		})
+	}
+}
+
+/// Iterator over the items of a trie which keeps track of its position.
+///
+/// The position can be stored at any point, and `resume` continues with the item
+/// after the last one yielded, as long as the trie is still in the database.
+pub struct TrieCursor<'a> {
+	iter: TrieDBIterator<'a>,
+	position: CursorPosition,
+}
+
+impl<'a> TrieCursor<'a> {
+	/// Start walking `trie` from its first item.
+	pub fn new(trie: &'a TrieDB) -> Result<Self> {
+		Ok(TrieCursor {
+			iter: TrieDBIterator::new(trie)?,
+			position: CursorPosition::start(*trie.root()),
+		})
+	}
+
+	/// Continue walking `trie` from `position`. Fails if the position is in another trie.
+	pub fn resume(trie: &'a TrieDB, position: CursorPosition) -> Result<Self> {
+		if &position.root != trie.root() {
+			return Err(Box::new(TrieError::InvalidStateRoot(position.root)));
+		}
+
+		let mut iter = TrieDBIterator::new(trie)?;
+		if let Some(ref key) = position.key {
+			iter.seek(key)?;
+		}
+
+		Ok(TrieCursor {
+			iter: iter,
+			position: position,
+		})
+	}
+
+	/// The current position, from which `resume` continues with the next item.
+	pub fn position(&self) -> &CursorPosition {
+		&self.position
+	}
+}
+
+impl<'a> Iterator for TrieCursor<'a> {
+	type Item = TrieItem<'a>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let item = self.iter.next();
+		if let Some(Ok((ref key, _))) = item {
+			self.position.key = Some(key.clone());
+		}
+		item
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use hash::H256;
+	use memorydb::MemoryDB;
+	use rlp;
+	use super::super::{TrieDB, TrieDBMut, TrieMut, Trie};
+	use super::super::standardmap::{StandardMap, Alphabet, ValueMode};
+	use super::{TrieCursor, CursorPosition};
+
+	#[test]
+	fn resumes_from_stored_position() {
+		let mut seed = H256::new();
+		let x = StandardMap {
+			alphabet: Alphabet::All,
+			min_key: 32,
+			journal_key: 0,
+			value_mode: ValueMode::Random,
+			count: 200,
+		}.make_with(&mut seed);
+
+		let mut db = MemoryDB::new();
+		let mut root = H256::new();
+		{
+			let mut t = TrieDBMut::new(&mut db, &mut root);
+			for &(ref key, ref value) in &x {
+				t.insert(key, value).unwrap();
+			}
+		}
+
+		let t = TrieDB::new(&db, &root).unwrap();
+		let all: Vec<_> = t.iter().unwrap().map(Result::unwrap).collect();
+		assert_eq!(all.len(), 200);
+
+		for stop in (0..all.len() + 1).filter(|n| n % 7 == 0 || *n == all.len()) {
+			let mut cursor = TrieCursor::new(&t).unwrap();
+			let first: Vec<_> = cursor.by_ref().take(stop).map(Result::unwrap).collect();
+			let stored = rlp::encode(cursor.position()).to_vec();
+
+			let position: CursorPosition = rlp::decode(&stored);
+			let rest: Vec<_> = TrieCursor::resume(&t, position).unwrap().map(Result::unwrap).collect();
+			assert_eq!(&first[..], &all[..stop]);
+			assert_eq!(&rest[..], &all[stop..]);
+		}
+	}
+
+	#[test]
+	fn rejects_position_in_other_trie() {
+		let mut db = MemoryDB::new();
+		let mut root = H256::new();
+		TrieDBMut::new(&mut db, &mut root).insert(b"key", b"value").unwrap();
+
+		let t = TrieDB::new(&db, &root).unwrap();
+		assert!(TrieCursor::resume(&t, CursorPosition::start(H256::from(1))).is_err());
+	}
+}
@@ -39,9 +39,10 @@ impl<'a, Q: Query> Lookup<'a, Q> {
 	/// function to decode or copy.
 	pub fn look_up(mut self, mut key: NibbleSlice) -> super::Result<Option<Q::Item>> {
 		let mut hash = self.hash;
+		let mut depth = 0;
 
 		// this loop iterates through non-inline nodes.
-		for depth in 0.. {
+		loop {
 			let node_data = match self.db.get(&hash) {
 				Some(value) => value,
 				None => return Err(Box::new(match depth {
@@ -53,7 +54,7 @@ impl<'a, Q: Query> Lookup<'a, Q> {
 			self.query.record(&hash, &node_data, depth);
 
 			// this loop iterates through all inline children (usually max 1)
-			// without incrementing the depth.
+			// without incrementing the depth, unless they were embedded for storage.
 			let mut node_data = &node_data[..];
 			loop {
 				match Node::decoded(node_data) {
@@ -85,10 +86,15 @@ impl<'a, Q: Query> Lookup<'a, Q> {
 				let r = Rlp::new(node_data);
 				if r.is_data() && r.size() == 32 {
 					hash = r.as_val();
+					depth += 1;
 					break
 				}
+
+				// nodes embedded by the compact encoding are a level of their own in proofs.
+				if node_data.len() >= 32 {
+					depth += 1;
+				}
 			}
 		}
-		Ok(None)
 	}
 }
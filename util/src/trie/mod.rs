@@ -34,6 +34,14 @@ pub mod sectriedb;
 pub mod sectriedbmut;
 /// Trie query recording.
 pub mod recorder;
+/// Proofs of key ranges.
+pub mod range_proof;
+/// Node statistics.
+pub mod stats;
+/// Resumable traversal.
+pub mod cursor;
+/// Compact storage encoding of nodes.
+pub mod compact;
 
 
 mod fatdb;
@@ -48,6 +56,10 @@ pub use self::sectriedb::SecTrieDB;
 pub use self::fatdb::{FatDB, FatDBIterator};
 pub use self::fatdbmut::FatDBMut;
 pub use self::recorder::Recorder;
+pub use self::range_proof::verify_range_proof;
+pub use self::stats::TrieStats;
+pub use self::cursor::{TrieCursor, CursorPosition};
+pub use self::compact::NodeEncoding;
 
 /// Trie Errors.
 ///
@@ -193,9 +205,17 @@ impl Default for TrieSpec {
 }
 
 /// Trie factory.
-#[derive(Default, Clone)]
+#[derive(Clone)]
 pub struct TrieFactory {
 	spec: TrieSpec,
+	parallel_commit_threshold: usize,
+	encoding: NodeEncoding,
+}
+
+impl Default for TrieFactory {
+	fn default() -> Self {
+		TrieFactory::new(TrieSpec::default())
+	}
 }
 
 /// All different kinds of tries.
@@ -250,6 +270,27 @@ impl TrieFactory {
 	pub fn new(spec: TrieSpec) -> Self {
 		TrieFactory {
 			spec: spec,
+			parallel_commit_threshold: triedbmut::DEFAULT_PARALLEL_COMMIT_THRESHOLD,
+			encoding: NodeEncoding::default(),
+		}
+	}
+
+	/// Creates new factory whose mutable tries commit on several threads once at least
+	/// `threshold` nodes have changed.
+	pub fn with_parallel_commit_threshold(spec: TrieSpec, threshold: usize) -> Self {
+		TrieFactory {
+			spec: spec,
+			parallel_commit_threshold: threshold,
+			encoding: NodeEncoding::default(),
+		}
+	}
+
+	/// Creates new factory whose mutable tries write new nodes with the given encoding.
+	pub fn with_node_encoding(spec: TrieSpec, encoding: NodeEncoding) -> Self {
+		TrieFactory {
+			spec: spec,
+			parallel_commit_threshold: triedbmut::DEFAULT_PARALLEL_COMMIT_THRESHOLD,
+			encoding: encoding,
 		}
 	}
 
@@ -264,19 +305,53 @@ impl TrieFactory {
 
 	/// Create new mutable instance of Trie.
 	pub fn create<'db>(&self, db: &'db mut HashDB, root: &'db mut H256) -> Box<TrieMut + 'db> {
+		let threshold = self.parallel_commit_threshold;
+		let encoding = self.encoding;
 		match self.spec {
-			TrieSpec::Generic => Box::new(TrieDBMut::new(db, root)),
-			TrieSpec::Secure => Box::new(SecTrieDBMut::new(db, root)),
-			TrieSpec::Fat => Box::new(FatDBMut::new(db, root)),
+			TrieSpec::Generic => {
+				let mut t = TrieDBMut::new(db, root);
+				t.set_parallel_commit_threshold(threshold);
+				t.set_node_encoding(encoding);
+				Box::new(t)
+			},
+			TrieSpec::Secure => {
+				let mut t = SecTrieDBMut::new(db, root);
+				t.set_parallel_commit_threshold(threshold);
+				t.set_node_encoding(encoding);
+				Box::new(t)
+			},
+			TrieSpec::Fat => {
+				let mut t = FatDBMut::new(db, root);
+				t.set_parallel_commit_threshold(threshold);
+				t.set_node_encoding(encoding);
+				Box::new(t)
+			},
 		}
 	}
 
 	/// Create new mutable instance of trie and check for errors.
 	pub fn from_existing<'db>(&self, db: &'db mut HashDB, root: &'db mut H256) -> Result<Box<TrieMut + 'db>> {
+		let threshold = self.parallel_commit_threshold;
+		let encoding = self.encoding;
 		match self.spec {
-			TrieSpec::Generic => Ok(Box::new(TrieDBMut::from_existing(db, root)?)),
-			TrieSpec::Secure => Ok(Box::new(SecTrieDBMut::from_existing(db, root)?)),
-			TrieSpec::Fat => Ok(Box::new(FatDBMut::from_existing(db, root)?)),
+			TrieSpec::Generic => {
+				let mut t = TrieDBMut::from_existing(db, root)?;
+				t.set_parallel_commit_threshold(threshold);
+				t.set_node_encoding(encoding);
+				Ok(Box::new(t))
+			},
+			TrieSpec::Secure => {
+				let mut t = SecTrieDBMut::from_existing(db, root)?;
+				t.set_parallel_commit_threshold(threshold);
+				t.set_node_encoding(encoding);
+				Ok(Box::new(t))
+			},
+			TrieSpec::Fat => {
+				let mut t = FatDBMut::from_existing(db, root)?;
+				t.set_parallel_commit_threshold(threshold);
+				t.set_node_encoding(encoding);
+				Ok(Box::new(t))
+			},
 		}
 	}
 
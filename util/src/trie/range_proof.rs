@@ -0,0 +1,243 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Proofs of all the key/value pairs within a range of keys.
+//!
+//! A range proof holds every node whose keys may fall in the range: the nodes on the
+//! paths to both ends of the range and the whole subtries between them. Walking the
+//! proof from the root therefore finds every pair in the range, and a missing node
+//! means the proof can't show that nothing else lies there.
+
+use std::cmp;
+use hash::H256;
+use hashdb::{HashDB, DBValue};
+use memorydb::MemoryDB;
+use nibbleslice::NibbleSlice;
+use rlp::Rlp;
+use Bytes;
+use super::node::Node;
+use super::compact;
+use super::{Result, TrieError};
+
+// walks the nodes of a trie which may hold keys in `[start, end)`.
+struct RangeWalk<'a> {
+	db: &'a HashDB,
+	start: Bytes,
+	end: Option<Bytes>,
+	nodes: Vec<DBValue>,
+	items: Vec<(Bytes, DBValue)>,
+}
+
+impl<'a> RangeWalk<'a> {
+	fn new(db: &'a HashDB, start: &[u8], end: Option<&[u8]>) -> Self {
+		RangeWalk {
+			db: db,
+			start: to_nibbles(start),
+			end: end.map(to_nibbles),
+			nodes: Vec::new(),
+			items: Vec::new(),
+		}
+	}
+
+	fn walk_root(&mut self, root: &H256) -> Result<()> {
+		let data = self.db.get(root).ok_or_else(|| Box::new(TrieError::InvalidStateRoot(*root)))?;
+		self.note(&data);
+		self.walk(&data, &mut Vec::new())
+	}
+
+	// add a node read from the database to the proof, canonically encoded.
+	fn note(&mut self, data: &DBValue) {
+		let mut embedded = Vec::new();
+		match compact::canonical(data, &mut embedded) {
+			Some(canonical) => {
+				self.nodes.push(DBValue::from_vec(canonical));
+				self.nodes.extend(embedded.into_iter().map(|(child, _)| DBValue::from_vec(child)));
+			}
+			None => self.nodes.push(data.clone()),
+		}
+	}
+
+	// whether keys starting with `prefix` may fall in the range.
+	fn intersects(&self, prefix: &[u8]) -> bool {
+		prefix >= &self.start[..cmp::min(prefix.len(), self.start.len())] &&
+			self.end.as_ref().map_or(true, |end| prefix < &end[..])
+	}
+
+	fn contains(&self, key: &[u8]) -> bool {
+		key >= &self.start[..] && self.end.as_ref().map_or(true, |end| key < &end[..])
+	}
+
+	// the node referenced from a parent: either inline or by hash.
+	fn child(&mut self, reference: &[u8]) -> Result<DBValue> {
+		let r = Rlp::new(reference);
+		if r.is_data() && r.size() == 32 {
+			let hash = r.as_val::<H256>();
+			let data = self.db.get(&hash).ok_or_else(|| Box::new(TrieError::IncompleteDatabase(hash)))?;
+			self.note(&data);
+			Ok(data)
+		} else {
+			Ok(DBValue::from_slice(reference))
+		}
+	}
+
+	fn walk(&mut self, data: &[u8], prefix: &mut Bytes) -> Result<()> {
+		match Node::decoded(data) {
+			Node::Empty => {},
+			Node::Leaf(slice, value) => {
+				prefix.extend((0..slice.len()).map(|i| slice.at(i)));
+				if self.contains(prefix) {
+					self.items.push((from_nibbles(prefix), DBValue::from_slice(value)));
+				}
+				let len = prefix.len() - slice.len();
+				prefix.truncate(len);
+			},
+			Node::Extension(slice, reference) => {
+				prefix.extend((0..slice.len()).map(|i| slice.at(i)));
+				if self.intersects(prefix) {
+					let child = self.child(reference)?;
+					self.walk(&child, prefix)?;
+				}
+				let len = prefix.len() - slice.len();
+				prefix.truncate(len);
+			},
+			Node::Branch(children, value) => {
+				if let Some(value) = value {
+					if self.contains(prefix) {
+						self.items.push((from_nibbles(prefix), DBValue::from_slice(value)));
+					}
+				}
+
+				for (i, reference) in children.iter().enumerate() {
+					prefix.push(i as u8);
+					if self.intersects(prefix) {
+						let child = self.child(reference)?;
+						self.walk(&child, prefix)?;
+					}
+					prefix.pop();
+				}
+			},
+		}
+		Ok(())
+	}
+}
+
+fn to_nibbles(key: &[u8]) -> Bytes {
+	let slice = NibbleSlice::new(key);
+	(0..slice.len()).map(|i| slice.at(i)).collect()
+}
+
+fn from_nibbles(nibbles: &[u8]) -> Bytes {
+	nibbles.chunks(2).map(|pair| pair[0] * 16 + pair.get(1).cloned().unwrap_or(0)).collect()
+}
+
+/// Collect the nodes of the trie `root` in `db` proving every key/value pair with
+/// `start <= key < end`. A missing `end` leaves the range unbounded above.
+pub fn prove_range(db: &HashDB, root: &H256, start: &[u8], end: Option<&[u8]>) -> Result<Vec<DBValue>> {
+	let mut walk = RangeWalk::new(db, start, end);
+	walk.walk_root(root)?;
+
+	// a node may appear more than once in the trie.
+	let mut nodes = walk.nodes;
+	nodes.sort_by(|a, b| a[..].cmp(&b[..]));
+	nodes.dedup();
+	Ok(nodes)
+}
+
+/// Check a proof produced by `TrieDB::range_proof` against the trie `root`, returning the
+/// key/value pairs with `start <= key < end` in key order.
+///
+/// Fails if the proof lacks a node which might hold keys in the range.
+pub fn verify_range_proof(root: &H256, start: &[u8], end: Option<&[u8]>, proof: &[DBValue]) -> Result<Vec<(Bytes, DBValue)>> {
+	let mut db = MemoryDB::new();
+	db.insert_batch(&proof.iter().map(|node| &node[..]).collect::<Vec<_>>());
+
+	let mut walk = RangeWalk::new(&db, start, end);
+	walk.walk_root(root)?;
+	Ok(walk.items)
+}
+
+#[cfg(test)]
+mod tests {
+	use hash::H256;
+	use hashdb::DBValue;
+	use memorydb::MemoryDB;
+	use super::super::{TrieDB, TrieDBMut, TrieMut, TrieError};
+	use super::verify_range_proof;
+
+	#[test]
+	fn proves_range() {
+		let pairs = [
+			("do", "verb"), ("dog", "puppy"), ("doge", "coin"), ("horse", "stallion"),
+			("house", "building"), ("kitten", "cat"), ("kitty", "kitty cat"),
+		];
+		let mut db = MemoryDB::new();
+		let mut root = H256::new();
+		{
+			let mut t = TrieDBMut::new(&mut db, &mut root);
+			for &(key, value) in &pairs {
+				t.insert(key.as_bytes(), value.as_bytes()).unwrap();
+			}
+		}
+		let t = TrieDB::new(&db, &root).unwrap();
+
+		let check = |start: &str, end: Option<&str>| {
+			let expected: Vec<_> = pairs.iter()
+				.filter(|&&(key, _)| key >= start && end.map_or(true, |end| key < end))
+				.map(|&(key, value)| (key.as_bytes().to_vec(), DBValue::from_slice(value.as_bytes())))
+				.collect();
+
+			let (start, end) = (start.as_bytes(), end.map(str::as_bytes));
+			let proof = t.range_proof(start, end).unwrap();
+			assert_eq!(verify_range_proof(&root, start, end, &proof).unwrap(), expected);
+		};
+
+		check("dog", Some("hou"));
+		check("do", Some("dog"));
+		check("a", Some("b"));
+		check("house", None);
+		check("", None);
+	}
+
+	#[test]
+	fn rejects_incomplete_proof() {
+		let pairs: Vec<_> = (0..64u8).map(|i| ([i, i, i], [i; 40])).collect();
+		let mut db = MemoryDB::new();
+		let mut root = H256::new();
+		{
+			let mut t = TrieDBMut::new(&mut db, &mut root);
+			for &(ref key, ref value) in &pairs {
+				t.insert(key, value).unwrap();
+			}
+		}
+
+		let t = TrieDB::new(&db, &root).unwrap();
+		let (start, end) = (&[10u8][..], Some(&[20u8][..]));
+		let proof = t.range_proof(start, end).unwrap();
+		assert_eq!(verify_range_proof(&root, start, end, &proof).unwrap().len(), 10);
+
+		// dropping any node loses either the root or pairs in the range.
+		for i in 0..proof.len() {
+			let mut partial = proof.clone();
+			partial.remove(i);
+			match *verify_range_proof(&root, start, end, &partial).unwrap_err() {
+				TrieError::IncompleteDatabase(_) | TrieError::InvalidStateRoot(_) => {},
+			}
+		}
+
+		// a proof of one range doesn't prove another.
+		assert!(verify_range_proof(&root, &[30], Some(&[40u8][..]), &proof).is_err());
+	}
+}
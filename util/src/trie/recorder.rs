@@ -18,6 +18,7 @@
 
 use sha3::Hashable;
 use {Bytes, H256};
+use super::compact;
 
 /// A record of a visited node.
 #[derive(PartialEq, Eq, Debug, Clone)]
@@ -61,14 +62,29 @@ impl Recorder {
 	}
 
 	/// Record a visited node, given its hash, data, and depth.
+	/// A node stored in the compact encoding is recorded canonically, along with the
+	/// children embedded in it.
 	pub fn record(&mut self, hash: &H256, data: &[u8], depth: u32) {
-		debug_assert_eq!(data.sha3(), *hash);
+		let mut embedded = Vec::new();
+		match compact::canonical(data, &mut embedded) {
+			Some(canonical) => {
+				self.push(*hash, canonical, depth);
+				for (child, offset) in embedded {
+					self.push(child.sha3(), child, depth + offset);
+				}
+			}
+			None => self.push(*hash, data.into(), depth),
+		}
+	}
+
+	fn push(&mut self, hash: H256, data: Bytes, depth: u32) {
+		debug_assert_eq!(data.sha3(), hash);
 
 		if depth >= self.min_depth {
 			self.nodes.push(Record {
 				depth: depth,
-				data: data.into(),
-				hash: *hash,
+				data: data,
+				hash: hash,
 			})
 		}
 	}
@@ -18,7 +18,7 @@ use hash::H256;
 use sha3::Hashable;
 use hashdb::{HashDB, DBValue};
 use super::triedbmut::TrieDBMut;
-use super::TrieMut;
+use super::{TrieMut, NodeEncoding};
 
 /// A mutable `Trie` implementation which hashes keys and uses a generic `HashDB` backing database.
 ///
@@ -42,6 +42,18 @@ impl<'db> SecTrieDBMut<'db> {
 		Ok(SecTrieDBMut { raw: TrieDBMut::from_existing(db, root)? })
 	}
 
+	/// Commit on several threads once at least `threshold` nodes have changed.
+	/// See `TrieDBMut::set_parallel_commit_threshold`.
+	pub fn set_parallel_commit_threshold(&mut self, threshold: usize) {
+		self.raw.set_parallel_commit_threshold(threshold)
+	}
+
+	/// Set how new nodes are written to the database.
+	/// See `TrieDBMut::set_node_encoding`.
+	pub fn set_node_encoding(&mut self, encoding: NodeEncoding) {
+		self.raw.set_node_encoding(encoding)
+	}
+
 	/// Get the backing database.
 	pub fn db(&self) -> &HashDB { self.raw.db() }
 
@@ -0,0 +1,181 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Node type and size distribution of tries.
+//!
+//! Nodes shorter than 32 bytes are embedded in their parent, all others are
+//! stored under their hash. The sizes of stored nodes show how many database
+//! entries a different embedding threshold would save.
+
+use std::cmp;
+use std::collections::BTreeMap;
+use std::fmt;
+use hash::H256;
+use hashdb::HashDB;
+use rlp::Rlp;
+use super::node::Node;
+use super::{Result, TrieError};
+
+/// Node statistics accumulated over any number of tries.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TrieStats {
+	/// Number of tries walked.
+	pub tries: usize,
+	/// Number of leaf nodes.
+	pub leaves: usize,
+	/// Number of extension nodes.
+	pub extensions: usize,
+	/// Number of branch nodes.
+	pub branches: usize,
+	/// Number of branch nodes holding a value.
+	pub branch_values: usize,
+	/// Number of branch nodes by number of children.
+	pub branch_children: [usize; 17],
+	/// Number of nodes embedded in their parent.
+	pub embedded_nodes: usize,
+	/// Number of nodes stored under their hash, including the roots.
+	pub stored_nodes: usize,
+	/// Total size of the stored nodes.
+	pub stored_bytes: usize,
+	/// Number of stored nodes by size, rounded up to a power of two.
+	pub stored_sizes: BTreeMap<usize, usize>,
+	/// Length of the longest path from a root to a leaf, in nodes.
+	pub max_depth: usize,
+}
+
+impl TrieStats {
+	/// Walk the whole trie with `root` in `db`, adding its nodes to the statistics.
+	pub fn add_trie(&mut self, db: &HashDB, root: &H256) -> Result<()> {
+		let data = db.get(root).ok_or_else(|| Box::new(TrieError::InvalidStateRoot(*root)))?;
+		self.tries += 1;
+		self.stored(data.len());
+		self.walk(db, &data, 1)
+	}
+
+	/// Total number of nodes.
+	pub fn nodes(&self) -> usize {
+		self.leaves + self.extensions + self.branches
+	}
+
+	fn stored(&mut self, size: usize) {
+		self.stored_nodes += 1;
+		self.stored_bytes += size;
+		*self.stored_sizes.entry(size.next_power_of_two()).or_insert(0) += 1;
+	}
+
+	fn walk(&mut self, db: &HashDB, data: &[u8], depth: usize) -> Result<()> {
+		self.max_depth = cmp::max(self.max_depth, depth);
+		match Node::decoded(data) {
+			Node::Empty => {},
+			Node::Leaf(..) => self.leaves += 1,
+			Node::Extension(_, child) => {
+				self.extensions += 1;
+				self.child(db, child, depth + 1)?;
+			},
+			Node::Branch(children, value) => {
+				self.branches += 1;
+				if value.is_some() {
+					self.branch_values += 1;
+				}
+
+				let children: Vec<_> = children.iter().filter(|child| !Rlp::new(child).is_empty()).collect();
+				self.branch_children[children.len()] += 1;
+				for child in children {
+					self.child(db, child, depth + 1)?;
+				}
+			},
+		}
+		Ok(())
+	}
+
+	// a child referenced from its parent: either embedded or by hash.
+	fn child(&mut self, db: &HashDB, reference: &[u8], depth: usize) -> Result<()> {
+		let r = Rlp::new(reference);
+		if r.is_data() && r.size() == 32 {
+			let hash = r.as_val::<H256>();
+			let data = db.get(&hash).ok_or_else(|| Box::new(TrieError::IncompleteDatabase(hash)))?;
+			self.stored(data.len());
+			self.walk(db, &data, depth)
+		} else {
+			self.embedded_nodes += 1;
+			self.walk(db, reference, depth)
+		}
+	}
+}
+
+impl fmt::Display for TrieStats {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let percent = |n: usize| match self.nodes() {
+			0 => 0.0,
+			total => n as f64 * 100.0 / total as f64,
+		};
+
+		writeln!(f, "Tries: {}, nodes: {}, max depth: {}", self.tries, self.nodes(), self.max_depth)?;
+		writeln!(f, "  leaves:     {:>12} ({:.1}%)", self.leaves, percent(self.leaves))?;
+		writeln!(f, "  extensions: {:>12} ({:.1}%)", self.extensions, percent(self.extensions))?;
+		writeln!(f, "  branches:   {:>12} ({:.1}%), {} with a value", self.branches, percent(self.branches), self.branch_values)?;
+		writeln!(f, "Branches by number of children:")?;
+		for (children, &count) in self.branch_children.iter().enumerate().filter(|&(_, &count)| count > 0) {
+			writeln!(f, "  {:>2}: {:>12}", children, count)?;
+		}
+		writeln!(f, "Embedded nodes: {} ({:.1}%)", self.embedded_nodes, percent(self.embedded_nodes))?;
+		writeln!(f, "Stored nodes: {} ({:.1}%), {} bytes", self.stored_nodes, percent(self.stored_nodes), self.stored_bytes)?;
+		writeln!(f, "Stored nodes by size:")?;
+		for (size, count) in &self.stored_sizes {
+			writeln!(f, "  <= {:>5} bytes: {:>12}", size, count)?;
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use hash::H256;
+	use memorydb::MemoryDB;
+	use super::super::{TrieDBMut, TrieMut};
+	use super::TrieStats;
+
+	#[test]
+	fn counts_nodes() {
+		let mut db = MemoryDB::new();
+		let mut root = H256::new();
+		{
+			let mut t = TrieDBMut::new(&mut db, &mut root);
+			t.insert(b"do", b"verb").unwrap();
+			t.insert(b"dog", b"puppy").unwrap();
+			t.insert(b"doge", b"coin").unwrap();
+			t.insert(b"horse", &[0x55; 40]).unwrap();
+		}
+
+		let mut stats = TrieStats::default();
+		stats.add_trie(&db, &root).unwrap();
+		stats.add_trie(&db, &root).unwrap();
+
+		// per trie: an extension on the shared first nibble to a branch between "do..."
+		// and "horse", then extensions to the branches holding "do" and "dog" as values,
+		// and leaves for "doge" and "horse".
+		assert_eq!(stats.tries, 2);
+		assert_eq!(stats.leaves, 4);
+		assert_eq!(stats.extensions, 6);
+		assert_eq!(stats.branches, 6);
+		assert_eq!(stats.branch_values, 4);
+		assert_eq!(stats.branch_children[1], 4);
+		assert_eq!(stats.branch_children[2], 2);
+		assert_eq!(stats.embedded_nodes + stats.stored_nodes, stats.nodes());
+		assert_eq!(stats.stored_sizes.values().sum::<usize>(), stats.stored_nodes);
+		assert!(stats.stored_bytes >= stats.stored_nodes * 32);
+	}
+}
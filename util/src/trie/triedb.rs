@@ -73,6 +73,14 @@ impl<'db> TrieDB<'db> {
 		self.db
 	}
 
+	/// Get the nodes proving every key/value pair with `start <= key < end`, and that the
+	/// trie holds no others in that range. A missing `end` leaves the range unbounded above.
+	///
+	/// The pairs are recovered from the proof with `verify_range_proof`.
+	pub fn range_proof(&self, start: &[u8], end: Option<&[u8]>) -> super::Result<Vec<DBValue>> {
+		super::range_proof::prove_range(self.db, self.root, start, end)
+	}
+
 	/// Get the data of the root node.
 	fn root_data(&self) -> super::Result<DBValue> {
 		self.db.get(self.root).ok_or_else(|| Box::new(TrieError::InvalidStateRoot(*self.root)))
@@ -220,17 +228,15 @@ impl<'a> TrieDBIterator<'a> {
 		let node = Node::decoded(&node_data);
 		match node {
 			Node::Leaf(ref slice, _) => {
-				if slice == key {
-					self.trail.push(Crumb {
-						status: Status::At,
-						node: node.clone().into(),
-					});
-				} else {
-					self.trail.push(Crumb {
-						status: Status::Exiting,
-						node: node.clone().into(),
-					});
-				}
+				// the leaf is yielded next only if its key comes after the sought one.
+				let status = match slice > key {
+					true => Status::Entering,
+					false => Status::Exiting,
+				};
+				self.trail.push(Crumb {
+					status: status,
+					node: node.clone().into(),
+				});
 
 				self.key_nibbles.extend(slice.iter());
 				Ok(())
@@ -244,9 +250,17 @@ impl<'a> TrieDBIterator<'a> {
 					self.key_nibbles.extend(slice.iter());
 					let data = self.db.get_raw_or_lookup(&*item)?;
 					self.seek_descend(data, &key.mid(slice.len()))
-				} else {
+				} else if slice > key {
 					self.descend(&node_data)?;
 					Ok(())
+				} else {
+					// every key under the extension comes before the sought one.
+					self.trail.push(Crumb {
+						status: Status::Exiting,
+						node: node.clone().into(),
+					});
+					self.key_nibbles.extend(slice.iter());
+					Ok(())
 				}
 			},
 			Node::Branch(ref nodes, _) => match key.is_empty() {
@@ -296,7 +310,7 @@ impl<'a> TrieDBIterator<'a> {
 }
 
 impl<'a> TrieIterator for TrieDBIterator<'a> {
-	/// Position the iterator on the first element with key >= `key`
+	/// Position the iterator on the first element with key > `key`
 	fn seek(&mut self, key: &[u8]) -> super::Result<()> {
 		self.trail.clear();
 		self.key_nibbles.clear();
@@ -427,6 +441,55 @@ fn iterator_seek() {
 	assert_eq!(&d[4..], &iter.map(|x| x.unwrap().1).collect::<Vec<_>>()[..]);
 }
 
+#[test]
+fn iterator_seek_between_keys() {
+	use memorydb::*;
+	use super::TrieMut;
+	use super::triedbmut::*;
+	use super::standardmap::*;
+
+	let mut seed = H256::new();
+	let x = StandardMap {
+		alphabet: Alphabet::Low,
+		min_key: 4,
+		journal_key: 2,
+		value_mode: ValueMode::Index,
+		count: 100,
+	}.make_with(&mut seed);
+
+	let mut memdb = MemoryDB::new();
+	let mut root = H256::new();
+	{
+		let mut t = TrieDBMut::new(&mut memdb, &mut root);
+		for &(ref key, ref value) in &x {
+			t.insert(key, value).unwrap();
+		}
+	}
+
+	let t = TrieDB::new(&memdb, &root).unwrap();
+	let all: Vec<_> = t.iter().unwrap().map(|x| x.unwrap().0).collect();
+
+	// keys in the trie, their prefixes and keys diverging from them within a node.
+	let mut targets = vec![vec![], vec![0xff]];
+	for key in &all {
+		targets.push(key.clone());
+		targets.push(key[..key.len() / 2].to_vec());
+		let mut lower = key.clone();
+		*lower.last_mut().unwrap() -= 1;
+		targets.push(lower);
+		let mut higher = key.clone();
+		*higher.last_mut().unwrap() += 1;
+		targets.push(higher);
+	}
+
+	for target in targets {
+		let mut iter = t.iter().unwrap();
+		iter.seek(&target).unwrap();
+		let expected: Vec<_> = all.iter().filter(|key| &key[..] > &target[..]).cloned().collect();
+		assert_eq!(iter.map(|x| x.unwrap().0).collect::<Vec<_>>(), expected);
+	}
+}
+
 #[test]
 fn get_len() {
 	use memorydb::*;
@@ -17,6 +17,7 @@
 //! In-memory trie representation.
 
 use super::{TrieError, TrieMut};
+use super::compact::{self, NodeEncoding};
 use super::lookup::Lookup;
 use super::node::Node as RlpNode;
 use super::node::NodeKey;
@@ -25,15 +26,19 @@ use ::{HashDB, H256};
 use ::bytes::ToPretty;
 use ::nibbleslice::NibbleSlice;
 use ::rlp::{Rlp, RlpStream};
-use ::sha3::SHA3_NULL_RLP;
+use ::sha3::{Hashable, SHA3_NULL_RLP};
 use hashdb::DBValue;
 
+use crossbeam;
 use elastic_array::ElasticArray1024;
 
-use std::collections::{HashSet, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::mem;
 use std::ops::Index;
 
+/// Default number of changed nodes from which `TrieDBMut::commit` uses several threads.
+pub const DEFAULT_PARALLEL_COMMIT_THRESHOLD: usize = 1024;
+
 // For lookups into the Node storage buffer.
 // This is deliberately non-copyable.
 #[derive(Debug)]
@@ -122,39 +127,38 @@ impl Node {
 	}
 
 	// encode a node to RLP
-	// TODO: parallelize
-	fn into_rlp<F>(self, mut child_cb: F) -> ElasticArray1024<u8>
-		where F: FnMut(NodeHandle, &mut RlpStream)
+	fn to_rlp<F>(&self, mut child_cb: F) -> ElasticArray1024<u8>
+		where F: FnMut(&NodeHandle, &mut RlpStream)
 	{
-		match self {
+		match *self {
 			Node::Empty => {
 				let mut stream = RlpStream::new();
 				stream.append_empty_data();
 				stream.drain()
 			}
-			Node::Leaf(partial, value) => {
+			Node::Leaf(ref partial, ref value) => {
 				let mut stream = RlpStream::new_list(2);
-				stream.append(&&*partial);
-				stream.append(&&*value);
+				stream.append(&&**partial);
+				stream.append(&&**value);
 				stream.drain()
 			}
-			Node::Extension(partial, child) => {
+			Node::Extension(ref partial, ref child) => {
 				let mut stream = RlpStream::new_list(2);
-				stream.append(&&*partial);
+				stream.append(&&**partial);
 				child_cb(child, &mut stream);
 				stream.drain()
 			}
-			Node::Branch(mut children, value) => {
+			Node::Branch(ref children, ref value) => {
 				let mut stream = RlpStream::new_list(17);
-				for child in children.iter_mut().map(Option::take) {
-					if let Some(handle) = child {
+				for child in children.iter() {
+					if let Some(ref handle) = *child {
 						child_cb(handle, &mut stream);
 					} else {
 						stream.append_empty_data();
 					}
 				}
-				if let Some(value) = value {
-					stream.append(&&*value);
+				if let Some(ref value) = *value {
+					stream.append(&&**value);
 				} else {
 					stream.append_empty_data();
 				}
@@ -165,6 +169,107 @@ impl Node {
 	}
 }
 
+// New nodes hashed while committing, and the storage to free afterwards.
+// Only reads the storage, so independent subtries can be committed on separate threads.
+#[derive(Default)]
+struct Committed {
+	nodes: Vec<(H256, DBValue)>,
+	freed: Vec<StorageHandle>,
+	// whether small new nodes are embedded in their parents' records.
+	compact: bool,
+	// records of the new nodes to embed, by hash.
+	embedded: HashMap<H256, DBValue>,
+}
+
+impl Committed {
+	fn new(encoding: NodeEncoding) -> Self {
+		Committed {
+			compact: encoding == NodeEncoding::Compact,
+			..Default::default()
+		}
+	}
+
+	// the record to store for a new node, given its canonical encoding.
+	fn record(&self, node_rlp: &[u8]) -> DBValue {
+		match self.compact {
+			true => compact::compact(node_rlp, &self.embedded).map_or_else(|| DBValue::from_slice(node_rlp), DBValue::from_vec),
+			false => DBValue::from_slice(node_rlp),
+		}
+	}
+
+	// keep a new node to be stored under its hash, or embedded into its parent's record.
+	fn store(&mut self, hash: H256, node_rlp: &[u8]) {
+		let record = self.record(node_rlp);
+		if self.compact && record.len() <= compact::MAX_EMBEDDED_SIZE {
+			self.embedded.insert(hash, record);
+		} else {
+			self.nodes.push((hash, record));
+		}
+	}
+
+	// append a reference to the child `handle` to its parent's `stream`, hashing
+	// the child and its new descendants as necessary.
+	fn encode_child(&mut self, storage: &NodeStorage, handle: &NodeHandle, stream: &mut RlpStream) {
+		match *handle {
+			NodeHandle::Hash(ref hash) => { stream.append(hash); },
+			NodeHandle::InMemory(ref h) => {
+				self.freed.push(StorageHandle(h.0));
+				match storage.nodes[h.0] {
+					Stored::Cached(_, ref hash) => { stream.append(hash); },
+					Stored::New(ref node) => {
+						let node_rlp = node.to_rlp(|child, stream| self.encode_child(storage, child, stream));
+						if node_rlp.len() >= 32 {
+							let hash = (&node_rlp[..]).sha3();
+							stream.append(&hash);
+							self.store(hash, &node_rlp);
+						} else {
+							stream.append_raw(&node_rlp, 1);
+						}
+					}
+				}
+			}
+		}
+	}
+
+	// encode a new root node. Children of a root branch are committed on separate
+	// threads if at least `threshold` nodes are new.
+	fn encode_root(&mut self, storage: &NodeStorage, node: &Node, threshold: usize) -> ElasticArray1024<u8> {
+		let children = match *node {
+			Node::Branch(ref children, _) if storage.count_new(node, threshold) >= threshold => children,
+			_ => return node.to_rlp(|child, stream| self.encode_child(storage, child, stream)),
+		};
+
+		let encoded: Vec<(Vec<u8>, Committed)> = crossbeam::scope(|scope| {
+			let compact = self.compact;
+			let workers: Vec<_> = children.iter().filter_map(Option::as_ref).map(|handle| scope.spawn(move || {
+				let mut committed = Committed {
+					compact: compact,
+					..Default::default()
+				};
+				let mut stream = RlpStream::new();
+				committed.encode_child(storage, handle, &mut stream);
+				(stream.out(), committed)
+			})).collect();
+
+			workers.into_iter().map(|worker| worker.join()).collect()
+		});
+
+		let mut references = Vec::with_capacity(encoded.len());
+		for (reference, mut committed) in encoded {
+			self.nodes.append(&mut committed.nodes);
+			self.freed.append(&mut committed.freed);
+			self.embedded.extend(committed.embedded);
+			references.push(reference);
+		}
+
+		// children are visited in the same order.
+		let mut references = references.into_iter();
+		node.to_rlp(|_, stream| {
+			stream.append_raw(&references.next().expect("one reference per child; qed"), 1);
+		})
+	}
+}
+
 // post-inspect action.
 enum Action {
 	// Replace a node with a new one.
@@ -233,6 +338,31 @@ impl NodeStorage {
 		}
 	}
 
+	/// Count the new nodes in the subtrie under `node`, stopping at `limit`.
+	fn count_new<'a>(&'a self, node: &'a Node, limit: usize) -> usize {
+		let mut stack = vec![node];
+		let mut count = 0;
+		while let Some(node) = stack.pop() {
+			count += 1;
+			if count >= limit { break }
+
+			let mut push = |handle: &NodeHandle| if let NodeHandle::InMemory(ref h) = *handle {
+				if let Stored::New(ref node) = self.nodes[h.0] {
+					stack.push(node);
+				}
+			};
+
+			match *node {
+				Node::Extension(_, ref child) => push(child),
+				Node::Branch(ref children, _) => for child in children.iter().filter_map(Option::as_ref) {
+					push(child);
+				},
+				_ => {},
+			}
+		}
+		count
+	}
+
 	/// Remove a node from the storage, consuming the handle and returning the node.
 	fn destroy(&mut self, handle: StorageHandle) -> Stored {
 		let idx = handle.0;
@@ -287,6 +417,8 @@ pub struct TrieDBMut<'a> {
 	root: &'a mut H256,
 	root_handle: NodeHandle,
 	death_row: HashSet<H256>,
+	parallel_commit_threshold: usize,
+	encoding: NodeEncoding,
 	/// The number of hash operations this trie has performed.
 	/// Note that none are performed until changes are committed.
 	pub hash_count: usize,
@@ -304,6 +436,8 @@ impl<'a> TrieDBMut<'a> {
 			root: root,
 			root_handle: root_handle,
 			death_row: HashSet::new(),
+			parallel_commit_threshold: DEFAULT_PARALLEL_COMMIT_THRESHOLD,
+			encoding: NodeEncoding::default(),
 			hash_count: 0,
 		}
 	}
@@ -322,9 +456,24 @@ impl<'a> TrieDBMut<'a> {
 			root: root,
 			root_handle: root_handle,
 			death_row: HashSet::new(),
+			parallel_commit_threshold: DEFAULT_PARALLEL_COMMIT_THRESHOLD,
+			encoding: NodeEncoding::default(),
 			hash_count: 0,
 		})
 	}
+
+	/// Commit the subtries under the root on separate threads once at least `threshold` nodes
+	/// have changed. `usize::max_value()` keeps commits on the calling thread.
+	pub fn set_parallel_commit_threshold(&mut self, threshold: usize) {
+		self.parallel_commit_threshold = threshold;
+	}
+
+	/// Set how new nodes are written to the database. Nodes already stored are read
+	/// in either encoding.
+	pub fn set_node_encoding(&mut self, encoding: NodeEncoding) {
+		self.encoding = encoding;
+	}
+
 	/// Get the backing database.
 	pub fn db(&self) -> &HashDB {
 		self.db
@@ -812,8 +961,19 @@ impl<'a> TrieDBMut<'a> {
 
 		match self.storage.destroy(handle) {
 			Stored::New(node) => {
-				let root_rlp = node.into_rlp(|child, stream| self.commit_node(child, stream));
-				*self.root = self.db.insert(&root_rlp[..]);
+				let mut committed = Committed::new(self.encoding);
+				let root_rlp = committed.encode_root(&self.storage, &node, self.parallel_commit_threshold);
+				let root_record = committed.record(&root_rlp);
+				for handle in committed.freed {
+					self.storage.destroy(handle);
+				}
+				self.hash_count += committed.nodes.len() + committed.embedded.len();
+				for (hash, value) in committed.nodes {
+					self.db.emplace(hash, value);
+				}
+
+				*self.root = (&root_rlp[..]).sha3();
+				self.db.emplace(*self.root, root_record);
 				self.hash_count += 1;
 
 				trace!(target: "trie", "root node rlp: {:?}", (&root_rlp[..]).pretty());
@@ -827,27 +987,6 @@ impl<'a> TrieDBMut<'a> {
 		}
 	}
 
-	/// commit a node, hashing it, committing it to the db,
-	/// and writing it to the rlp stream as necessary.
-	fn commit_node(&mut self, handle: NodeHandle, stream: &mut RlpStream) {
-		match handle {
-			NodeHandle::Hash(h) => stream.append(&h),
-			NodeHandle::InMemory(h) => match self.storage.destroy(h) {
-				Stored::Cached(_, h) => stream.append(&h),
-				Stored::New(node) => {
-					let node_rlp = node.into_rlp(|child, stream| self.commit_node(child, stream));
-					if node_rlp.len() >= 32 {
-						let hash = self.db.insert(&node_rlp[..]);
-						self.hash_count += 1;
-						stream.append(&hash)
-					} else {
-						stream.append_raw(&node_rlp, 1)
-					}
-				}
-			}
-		};
-	}
-
 	// a hack to get the root node's handle
 	fn root_handle(&self) -> NodeHandle {
 		match self.root_handle {
@@ -1289,4 +1428,41 @@ mod tests {
 			assert!(t.remove(&key).unwrap().is_none());
 		}
 	}
+
+	#[test]
+	fn parallel_commit_matches_serial() {
+		let mut seed = H256::new();
+		let x = StandardMap {
+			alphabet: Alphabet::Custom(b"@QWERTYUIOPASDFGHJKLZXCVBNM[/]^_".to_vec()),
+			min_key: 5,
+			journal_key: 0,
+			value_mode: ValueMode::Index,
+			count: 500,
+		}.make_with(&mut seed);
+		let real = trie_root(x.clone());
+
+		let mut serial_db = MemoryDB::new();
+		let mut serial_root = H256::new();
+		{
+			let mut t = TrieDBMut::new(&mut serial_db, &mut serial_root);
+			t.set_parallel_commit_threshold(usize::max_value());
+			for &(ref key, ref value) in &x {
+				t.insert(key, value).unwrap();
+			}
+		}
+
+		let mut parallel_db = MemoryDB::new();
+		let mut parallel_root = H256::new();
+		{
+			let mut t = TrieDBMut::new(&mut parallel_db, &mut parallel_root);
+			t.set_parallel_commit_threshold(0);
+			for &(ref key, ref value) in &x {
+				t.insert(key, value).unwrap();
+			}
+		}
+
+		assert_eq!(serial_root, real);
+		assert_eq!(parallel_root, real);
+		assert_eq!(serial_db.keys(), parallel_db.keys());
+	}
 }
@@ -51,6 +51,15 @@ impl<T: Ord> Corpus<T> {
 		self.0.get(self.0.len() / 2)
 	}
 
+	/// Get the element at the given percentile (0 to 100) using the nearest rank, if it exists.
+	pub fn percentile(&self, percentile: f64) -> Option<&T> {
+		if self.0.is_empty() {
+			return None;
+		}
+		let rank = (self.0.len() as f64 * percentile / 100.0) as usize;
+		self.0.get(::std::cmp::min(rank, self.0.len() - 1))
+	}
+
 	/// Whether the corpus is empty.
 	pub fn is_empty(&self) -> bool {
 		self.0.is_empty()
@@ -121,7 +130,17 @@ impl<T: Ord + Copy + ::std::fmt::Display> Histogram<T>
 
 #[cfg(test)]
 mod tests {
-	use super::Histogram;
+	use super::{Corpus, Histogram};
+
+	#[test]
+	fn check_percentile() {
+		let corpus: Corpus<usize> = vec![5, 1, 4, 2, 3].into();
+		assert_eq!(corpus.percentile(0.0), Some(&1));
+		assert_eq!(corpus.percentile(50.0), corpus.median());
+		assert_eq!(corpus.percentile(90.0), Some(&5));
+		assert_eq!(corpus.percentile(100.0), Some(&5));
+		assert_eq!(Corpus::<usize>::from(vec![]).percentile(50.0), None);
+	}
 
 	#[test]
 	fn check_histogram() {